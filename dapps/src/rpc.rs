@@ -92,6 +92,7 @@ impl HttpMetaExtractor<Metadata> for MetadataExtractor {
 			});
 		Metadata {
 			origin: Origin::Dapps(dapp_id.map(Into::into).unwrap_or_default()),
+			..Default::default()
 		}
 	}
 }
@@ -19,6 +19,13 @@
 //! Stores ancient block headers, bodies, receipts, and total difficulties.
 //! Furthermore, stores a "gas price corpus" of relative recency, which is a sorted
 //! vector of all gas prices from a recent range of blocks.
+//!
+//! All caches share one memory budget, the sum of their quotas in bytes. A cache may
+//! use more than its quota while others leave theirs unused; once the budget is
+//! exceeded, least recently used items are evicted from the caches furthest above
+//! their quota.
+
+use std::mem;
 
 use ethcore::encoded;
 use ethcore::header::BlockNumber;
@@ -42,6 +49,8 @@ pub struct CacheSizes {
 	pub receipts: usize,
 	/// Maximum size, in bytes, of cached chain score for the block.
 	pub chain_score: usize,
+	/// Maximum size, in bytes, of the cached gas price corpus.
+	pub gas_price_corpus: usize,
 }
 
 impl Default for CacheSizes {
@@ -53,10 +62,55 @@ impl Default for CacheSizes {
 			bodies: 20 * MB,
 			receipts: 10 * MB,
 			chain_score: 7 * MB,
+			gas_price_corpus: MB,
 		}
 	}
 }
 
+impl CacheSizes {
+	/// Sizes using at most `total` bytes altogether, split in the default proportions.
+	pub fn with_total(total: usize) -> Self {
+		let default = CacheSizes::default();
+		let scale = |size: usize| (size as u64 * total as u64 / default.total() as u64) as usize;
+		CacheSizes {
+			headers: scale(default.headers),
+			canon_hashes: scale(default.canon_hashes),
+			bodies: scale(default.bodies),
+			receipts: scale(default.receipts),
+			chain_score: scale(default.chain_score),
+			gas_price_corpus: scale(default.gas_price_corpus),
+		}
+	}
+
+	/// Sum of all sizes, in bytes.
+	pub fn total(&self) -> usize {
+		self.headers + self.canon_hashes + self.bodies + self.receipts + self.chain_score + self.gas_price_corpus
+	}
+}
+
+// Index of each cache in `Cache::usage`.
+const HEADERS: usize = 0;
+const CANON_HASHES: usize = 1;
+const BODIES: usize = 2;
+const RECEIPTS: usize = 3;
+const CHAIN_SCORE: usize = 4;
+const GAS_PRICE_CORPUS: usize = 5;
+
+/// Memory usage of one of the caches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheUsage {
+	/// Name of the cache.
+	pub name: &'static str,
+	/// Bytes used by cached items.
+	pub used: usize,
+	/// Number of bytes the cache may use while the shared budget is exceeded.
+	pub quota: usize,
+	/// Number of cached items.
+	pub items: usize,
+	/// Number of items evicted or refused to stay within the budget.
+	pub evictions: usize,
+}
+
 /// The light client data cache.
 ///
 /// Note that almost all getter methods take `&mut self` due to the necessity to update
@@ -69,19 +123,25 @@ pub struct Cache {
 	chain_score: MemoryLruCache<H256, U256>,
 	corpus: Option<(Corpus<U256>, SteadyTime)>,
 	corpus_expiration: Duration,
+	corpus_evictions: usize,
+	sizes: CacheSizes,
 }
 
 impl Cache {
 	/// Create a new data cache with the given sizes and gas price corpus expiration time.
 	pub fn new(sizes: CacheSizes, corpus_expiration: Duration) -> Self {
+		// each cache may grow up to the shared budget, which is enforced by `collect_garbage`.
+		let total = sizes.total();
 		Cache {
-			headers: MemoryLruCache::new(sizes.headers),
-			canon_hashes: MemoryLruCache::new(sizes.canon_hashes),
-			bodies: MemoryLruCache::new(sizes.bodies),
-			receipts: MemoryLruCache::new(sizes.receipts),
-			chain_score: MemoryLruCache::new(sizes.chain_score),
+			headers: MemoryLruCache::new(total),
+			canon_hashes: MemoryLruCache::new(total),
+			bodies: MemoryLruCache::new(total),
+			receipts: MemoryLruCache::new(total),
+			chain_score: MemoryLruCache::new(total),
 			corpus: None,
 			corpus_expiration: corpus_expiration,
+			corpus_evictions: 0,
+			sizes: sizes,
 		}
 	}
 
@@ -113,26 +173,31 @@ impl Cache {
 	/// Cache the given header.
 	pub fn insert_block_header(&mut self, hash: H256, hdr: encoded::Header) {
 		self.headers.insert(hash, hdr);
+		self.collect_garbage();
 	}
 
 	/// Cache the given canonical block hash.
 	pub fn insert_block_hash(&mut self, num: BlockNumber, hash: H256) {
 		self.canon_hashes.insert(num, hash);
+		self.collect_garbage();
 	}
 
 	/// Cache the given block body.
 	pub fn insert_block_body(&mut self, hash: H256, body: encoded::Body) {
 		self.bodies.insert(hash, body);
+		self.collect_garbage();
 	}
 
 	/// Cache the given block receipts.
 	pub fn insert_block_receipts(&mut self, hash: H256, receipts: Vec<Receipt>) {
 		self.receipts.insert(hash, receipts);
+		self.collect_garbage();
 	}
 
 	/// Cache the given chain scoring.
 	pub fn insert_chain_score(&mut self, hash: H256, score: U256) {
 		self.chain_score.insert(hash, score);
+		self.collect_garbage();
 	}
 
 	/// Get gas price corpus, if recent enough.
@@ -148,16 +213,91 @@ impl Cache {
 		})
 	}
 
-	/// Set the cached gas price corpus.
+	/// Set the cached gas price corpus. A corpus exceeding its quota isn't cached.
 	pub fn set_gas_price_corpus(&mut self, corpus: Corpus<U256>) {
-		self.corpus = Some((corpus, SteadyTime::now()))
+		if corpus_size(&corpus) > self.sizes.gas_price_corpus {
+			self.corpus_evictions += 1;
+			self.corpus = None;
+			return;
+		}
+		self.corpus = Some((corpus, SteadyTime::now()));
+		self.collect_garbage();
+	}
+
+	// evict items from the caches furthest above their quota until all fit in the budget.
+	fn collect_garbage(&mut self) {
+		let total = self.sizes.total();
+		loop {
+			let usage = self.usage();
+			if usage.iter().map(|cache| cache.used).sum::<usize>() <= total {
+				return;
+			}
+
+			// the budget is the sum of quotas, so some cache is above its quota.
+			let (index, _) = usage.iter()
+				.map(|cache| cache.used.saturating_sub(cache.quota))
+				.enumerate()
+				.max_by_key(|&(_, excess)| excess)
+				.expect("there is a usage entry for every cache; qed");
+
+			let evicted = match index {
+				HEADERS => self.headers.remove_lru(),
+				CANON_HASHES => self.canon_hashes.remove_lru(),
+				BODIES => self.bodies.remove_lru(),
+				RECEIPTS => self.receipts.remove_lru(),
+				CHAIN_SCORE => self.chain_score.remove_lru(),
+				_ => {
+					debug_assert_eq!(index, GAS_PRICE_CORPUS);
+					self.corpus_evictions += 1;
+					self.corpus.take().is_some()
+				},
+			};
+			if !evicted {
+				return;
+			}
+		}
+	}
+
+	/// Memory usage of each of the caches.
+	pub fn usage(&self) -> Vec<CacheUsage> {
+		macro_rules! lru_usage {
+			($name: expr, $cache: expr, $quota: expr) => {
+				CacheUsage {
+					name: $name,
+					used: $cache.current_size(),
+					quota: $quota,
+					items: $cache.len(),
+					evictions: $cache.evictions(),
+				}
+			}
+		}
+
+		vec![
+			lru_usage!("headers", self.headers, self.sizes.headers),
+			lru_usage!("canonHashes", self.canon_hashes, self.sizes.canon_hashes),
+			lru_usage!("bodies", self.bodies, self.sizes.bodies),
+			lru_usage!("receipts", self.receipts, self.sizes.receipts),
+			lru_usage!("chainScore", self.chain_score, self.sizes.chain_score),
+			CacheUsage {
+				name: "gasPriceCorpus",
+				used: self.corpus.as_ref().map_or(0, |&(ref corpus, _)| corpus_size(corpus)),
+				quota: self.sizes.gas_price_corpus,
+				items: self.corpus.as_ref().map_or(0, |&(ref corpus, _)| corpus.len()),
+				evictions: self.corpus_evictions,
+			},
+		]
 	}
 }
 
+fn corpus_size(corpus: &Corpus<U256>) -> usize {
+	corpus.len() * mem::size_of::<U256>()
+}
+
 #[cfg(test)]
 mod tests {
-	use super::Cache;
+	use super::{Cache, CacheSizes};
 	use time::Duration;
+	use util::{H256, U256};
 
 	#[test]
 	fn corpus_inaccessible() {
@@ -172,4 +312,56 @@ mod tests {
 		}
 		assert!(cache.gas_price_corpus().is_none());
 	}
+
+	#[test]
+	fn corpus_over_quota_not_cached() {
+		let sizes = CacheSizes { gas_price_corpus: 64, ..Default::default() };
+		let mut cache = Cache::new(sizes, Duration::hours(5));
+
+		cache.set_gas_price_corpus(vec![U256::from(1), U256::from(2)].into());
+		assert!(cache.gas_price_corpus().is_some());
+		cache.set_gas_price_corpus(vec![U256::from(1), U256::from(2), U256::from(3)].into());
+		assert!(cache.gas_price_corpus().is_none());
+
+		let corpus = cache.usage().into_iter().find(|usage| usage.name == "gasPriceCorpus").unwrap();
+		assert_eq!((corpus.used, corpus.quota, corpus.evictions), (0, 64, 1));
+	}
+
+	#[test]
+	fn caches_share_the_budget() {
+		let sizes = CacheSizes {
+			headers: 0,
+			canon_hashes: 1024,
+			bodies: 0,
+			receipts: 0,
+			chain_score: 1024,
+			gas_price_corpus: 0,
+		};
+		let mut cache = Cache::new(sizes, Duration::hours(5));
+
+		// fixed-size values are accounted, and a cache may use the quota others leave unused.
+		for i in 0..32 {
+			cache.insert_block_hash(i, H256::from(i));
+		}
+		let usage = cache.usage();
+		assert_eq!(usage[1].items, 32);
+		assert!(usage[1].used > 1024);
+
+		// once the budget is exceeded, the cache above its quota is evicted from.
+		for i in 0..64 {
+			cache.insert_chain_score(H256::from(i), U256::from(i));
+		}
+		let usage = cache.usage();
+		assert!(usage.iter().map(|cache| cache.used).sum::<usize>() <= 2048);
+		assert!(usage[1].evictions > 0);
+		assert!(usage[1].used <= 1024);
+		assert!(cache.chain_score(&H256::from(63)).is_some());
+	}
+
+	#[test]
+	fn sizes_split_total() {
+		let sizes = CacheSizes::with_total(8 * 1024 * 1024);
+		assert!(sizes.total() <= 8 * 1024 * 1024);
+		assert!(sizes.bodies > sizes.headers);
+	}
 }
@@ -28,13 +28,14 @@
 use std::collections::{BTreeMap, HashMap};
 
 use cht;
+use super::Checkpoint;
 
 use ethcore::block_status::BlockStatus;
 use ethcore::error::BlockError;
 use ethcore::encoded;
 use ethcore::header::Header;
 use ethcore::ids::BlockId;
-use util::{H256, U256, HeapSizeOf, Mutex, RwLock};
+use util::{H256, U256, HeapSizeOf, Mutex, RwLock, Mismatch};
 
 use smallvec::SmallVec;
 
@@ -82,11 +83,18 @@ pub struct HeaderChain {
 	headers: RwLock<HashMap<H256, encoded::Header>>,
 	best_block: RwLock<BlockDescriptor>,
 	cht_roots: Mutex<Vec<H256>>,
+	checkpoint: Option<Checkpoint>,
 }
 
 impl HeaderChain {
 	/// Create a new header chain given this genesis block.
 	pub fn new(genesis: &[u8]) -> Self {
+		HeaderChain::with_checkpoint(genesis, None)
+	}
+
+	/// Create a new header chain given this genesis block, which only accepts
+	/// chains containing the checkpoint header.
+	pub fn with_checkpoint(genesis: &[u8], checkpoint: Option<Checkpoint>) -> Self {
 		use ethcore::views::HeaderView;
 
 		let g_view = HeaderView::new(genesis);
@@ -101,6 +109,7 @@ impl HeaderChain {
 			candidates: RwLock::new(BTreeMap::new()),
 			headers: RwLock::new(HashMap::new()),
 			cht_roots: Mutex::new(Vec::new()),
+			checkpoint: checkpoint,
 		}
 	}
 
@@ -112,6 +121,12 @@ impl HeaderChain {
 		let number = header.number();
 		let parent_hash = *header.parent_hash();
 
+		if let Some(ref checkpoint) = self.checkpoint {
+			if number == checkpoint.number && hash != checkpoint.hash {
+				return Err(BlockError::CheckpointMismatch(Mismatch { expected: checkpoint.hash, found: hash }));
+			}
+		}
+
 		// hold candidates the whole time to guard import order.
 		let mut candidates = self.candidates.write();
 
@@ -169,9 +184,10 @@ impl HeaderChain {
 				total_difficulty: total_difficulty,
 			};
 
-			// produce next CHT root if it's time.
+			// produce next CHT root if it's time. only chains containing the checkpoint are finalized.
 			let earliest_era = *candidates.keys().next().expect("at least one era just created; qed");
-			if earliest_era + HISTORY + cht::SIZE <= number {
+			let past_checkpoint = self.checkpoint.as_ref().map_or(true, |checkpoint| number >= checkpoint.number);
+			if past_checkpoint && earliest_era + HISTORY + cht::SIZE <= number {
 				let cht_num = cht::block_to_cht_number(earliest_era)
 					.expect("fails only for number == 0; genesis never imported; qed");
 				debug_assert_eq!(cht_num as usize, self.cht_roots.lock().len());
@@ -323,7 +339,7 @@ impl<'a> Iterator for AncestryIter<'a> {
 
 #[cfg(test)]
 mod tests {
-	use super::HeaderChain;
+	use super::{HeaderChain, Checkpoint};
 	use ethcore::ids::BlockId;
 	use ethcore::header::Header;
 	use ethcore::spec::Spec;
@@ -439,4 +455,65 @@ mod tests {
 		assert!(chain.block_header(BlockId::Latest).is_some());
 		assert!(chain.block_header(BlockId::Pending).is_some());
 	}
+
+	#[test]
+	fn rejects_chain_without_checkpoint() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+
+		let header = |parent_hash, number: u64, timestamp| {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(number);
+			header.set_timestamp(timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * number.into());
+			header
+		};
+
+		let mut headers = Vec::new();
+		let mut parent_hash = genesis_header.hash();
+		for i in 1..6 {
+			let h = header(parent_hash, i, genesis_header.timestamp() + i * 10);
+			parent_hash = h.hash();
+			headers.push(h);
+		}
+
+		let checkpoint = Checkpoint { number: 5, hash: headers[4].hash() };
+		let chain = HeaderChain::with_checkpoint(&::rlp::encode(&genesis_header), Some(checkpoint));
+		for h in headers.iter().take(4) {
+			chain.insert(h.clone()).unwrap();
+		}
+
+		let fork = header(headers[3].hash(), 5, genesis_header.timestamp() + 1);
+		assert!(chain.insert(fork).is_err());
+		chain.insert(headers[4].clone()).unwrap();
+		assert_eq!(chain.best_block().hash, headers[4].hash());
+	}
+
+	#[test]
+	fn no_cht_before_checkpoint() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+
+		let checkpoint = Checkpoint { number: 20000, hash: Default::default() };
+		let chain = HeaderChain::with_checkpoint(&::rlp::encode(&genesis_header), Some(checkpoint));
+
+		let mut parent_hash = genesis_header.hash();
+		let mut rolling_timestamp = genesis_header.timestamp();
+		for i in 1..10000 {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(i);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * i.into());
+			parent_hash = header.hash();
+
+			chain.insert(header).unwrap();
+
+			rolling_timestamp += 10;
+		}
+
+		assert!(chain.block_header(BlockId::Number(10)).is_some());
+		assert!(chain.cht_root(0).is_none());
+	}
 }
@@ -16,6 +16,7 @@
 
 //! Light client implementation. Stores data from light sync
 
+use std::str::FromStr;
 use std::sync::Arc;
 
 use ethcore::block_import_error::BlockImportError;
@@ -24,6 +25,7 @@ use ethcore::client::{ClientReport, EnvInfo};
 use ethcore::engines::Engine;
 use ethcore::ids::BlockId;
 use ethcore::header::Header;
+use ethcore::verification::SealPolicy;
 use ethcore::verification::queue::{self, HeaderQueue};
 use ethcore::blockchain_info::BlockChainInfo;
 use ethcore::spec::Spec;
@@ -45,6 +47,36 @@ mod service;
 pub struct Config {
 	/// Verification queue config.
 	pub queue: queue::Config,
+	/// Header the chain must contain to be finalized.
+	pub checkpoint: Option<Checkpoint>,
+}
+
+/// A trusted header. Headers at its height with a different hash are rejected and
+/// no CHT is produced until the best chain reaches it, so servers can't feed a
+/// chain forked off below the checkpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+	/// Number of the trusted header.
+	pub number: u64,
+	/// Hash of the trusted header.
+	pub hash: H256,
+}
+
+impl FromStr for Checkpoint {
+	type Err = String;
+
+	/// Parses `NUMBER:HASH`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("Invalid checkpoint: {}", s);
+		let parts: Vec<_> = s.split(':').collect();
+		match parts.len() {
+			2 => Ok(Checkpoint {
+				number: parts[0].parse().map_err(|_| invalid())?,
+				hash: parts[1].trim_left_matches("0x").parse().map_err(|_| invalid())?,
+			}),
+			_ => Err(invalid()),
+		}
+	}
 }
 
 /// Trait for interacting with the header chain abstractly.
@@ -112,9 +144,9 @@ impl Client {
 	/// Create a new `Client`.
 	pub fn new(config: Config, spec: &Spec, io_channel: IoChannel<ClientIoMessage>) -> Self {
 		Client {
-			queue: HeaderQueue::new(config.queue, spec.engine.clone(), io_channel, true),
+			queue: HeaderQueue::new(config.queue, spec.engine.clone(), io_channel, SealPolicy::Full),
 			engine: spec.engine.clone(),
-			chain: HeaderChain::new(&::rlp::encode(&spec.genesis_header())),
+			chain: HeaderChain::with_checkpoint(&::rlp::encode(&spec.genesis_header()), config.checkpoint),
 			report: RwLock::new(ClientReport::default()),
 			import_lock: Mutex::new(()),
 		}
@@ -33,7 +33,7 @@
 #![deny(missing_docs)]
 
 pub mod client;
-pub mod cht;
+pub use ethcore::cht;
 pub mod net;
 pub mod on_demand;
 pub mod transaction_queue;
@@ -40,11 +40,13 @@ use self::context::{Ctx, TickCtx};
 use self::error::Punishment;
 use self::request_set::RequestSet;
 use self::id_guard::IdGuard;
+use self::serve_queue::{ServeQueue, Priority};
 
 mod context;
 mod error;
 mod status;
 mod request_set;
+mod serve_queue;
 
 #[cfg(test)]
 mod tests;
@@ -248,6 +250,7 @@ pub struct LightProtocol {
 	flow_params: FlowParams, // assumed static and same for every peer.
 	handlers: Vec<Arc<Handler>>,
 	req_id: AtomicUsize,
+	serve_queue: ServeQueue<Requests>,
 }
 
 impl LightProtocol {
@@ -266,6 +269,7 @@ impl LightProtocol {
 			flow_params: params.flow_params,
 			handlers: Vec::new(),
 			req_id: AtomicUsize::new(0),
+			serve_queue: ServeQueue::default(),
 		}
 	}
 
@@ -275,6 +279,21 @@ impl LightProtocol {
 			.map(|peer| peer.lock().status.clone())
 	}
 
+	/// Get the estimated current and maximum request credits we have with a peer.
+	/// `None` if the peer is unknown or doesn't serve requests.
+	pub fn remote_credits(&self, peer: &PeerId) -> Option<(U256, U256)> {
+		self.peers.read().get(peer).and_then(|peer| {
+			let mut peer = peer.lock();
+			match peer.remote_flow {
+				Some((ref mut creds, ref params)) => {
+					params.recharge(creds);
+					Some((creds.current(), params.limit().clone()))
+				}
+				None => None,
+			}
+		})
+	}
+
 	/// Get number of (connected, active) peers.
 	pub fn peer_count(&self) -> (usize, usize) {
 		let num_pending = self.pending_peers.read().len();
@@ -528,6 +547,7 @@ impl LightProtocol {
 		trace!(target: "pip", "Peer {} disconnecting", peer);
 
 		self.pending_peers.write().remove(&peer);
+		self.serve_queue.remove_peer(peer);
 		let unfulfilled = match self.peers.write().remove(&peer) {
 			None => return,
 			Some(peer_info) => {
@@ -665,60 +685,98 @@ impl LightProtocol {
 		const MAX_REQUESTS: usize = 256;
 
 		use ::request::RequestBuilder;
-		use ::request::CompleteRequest;
-
-		let peers = self.peers.read();
-		let peer = match peers.get(peer_id) {
-			Some(peer) => peer,
-			None => {
-				debug!(target: "pip", "Ignoring request from unknown peer");
-				return Ok(())
-			}
-		};
-		let mut peer = peer.lock();
 
 		let req_id: u64 = raw.val_at(0)?;
-		let mut request_builder = RequestBuilder::default();
+		let requests = {
+			let peers = self.peers.read();
+			let peer = match peers.get(peer_id) {
+				Some(peer) => peer,
+				None => {
+					debug!(target: "pip", "Ignoring request from unknown peer");
+					return Ok(())
+				}
+			};
+			let mut peer = peer.lock();
 
-		trace!(target: "pip", "Received requests (id: {}) from peer {}", req_id, peer_id);
+			let mut request_builder = RequestBuilder::default();
 
-		// deserialize requests, check costs and request validity.
-		peer.local_credits.deduct_cost(self.flow_params.base_cost())?;
-		for request_rlp in raw.at(1)?.iter().take(MAX_REQUESTS) {
-			let request: Request = request_rlp.as_val()?;
-			peer.local_credits.deduct_cost(self.flow_params.compute_cost(&request))?;
-			request_builder.push(request).map_err(|_| Error::BadBackReference)?;
-		}
+			trace!(target: "pip", "Received requests (id: {}) from peer {}", req_id, peer_id);
 
-		let requests = request_builder.build();
-		let num_requests = requests.requests().len();
-		trace!(target: "pip", "Beginning to respond to requests (id: {}) from peer {}", req_id, peer_id);
-
-		// respond to all requests until one fails.
-		let responses = requests.respond_to_all(|complete_req| {
-			match complete_req {
-				CompleteRequest::Headers(req) => self.provider.block_headers(req).map(Response::Headers),
-				CompleteRequest::HeaderProof(req) => self.provider.header_proof(req).map(Response::HeaderProof),
-				CompleteRequest::Body(req) => self.provider.block_body(req).map(Response::Body),
-				CompleteRequest::Receipts(req) => self.provider.block_receipts(req).map(Response::Receipts),
-				CompleteRequest::Account(req) => self.provider.account_proof(req).map(Response::Account),
-				CompleteRequest::Storage(req) => self.provider.storage_proof(req).map(Response::Storage),
-				CompleteRequest::Code(req) => self.provider.contract_code(req).map(Response::Code),
-				CompleteRequest::Execution(req) => self.provider.transaction_proof(req).map(Response::Execution),
+			// refuse to queue more packets before deducting anything. The empty response
+			// tells the peer right away that none of the requests will be served.
+			if !self.serve_queue.can_queue(*peer_id) {
+				debug!(target: "pip", "Too many packets queued for peer {}, refusing requests (id: {})", peer_id, req_id);
+				let mut stream = RlpStream::new_list(3);
+				stream.append(&req_id).append(&peer.local_credits.current()).begin_list(0);
+				io.respond(packet::RESPONSE, stream.out());
+				return Ok(());
 			}
-		});
 
-		trace!(target: "pip", "Responded to {}/{} requests in packet {}", responses.len(), num_requests, req_id);
+			// deserialize requests, check costs and request validity.
+			peer.local_credits.deduct_cost(self.flow_params.base_cost())?;
+			for request_rlp in raw.at(1)?.iter().take(MAX_REQUESTS) {
+				let request: Request = request_rlp.as_val()?;
+				peer.local_credits.deduct_cost(self.flow_params.compute_cost(&request))?;
+				request_builder.push(request).map_err(|_| Error::BadBackReference)?;
+			}
+
+			request_builder.build()
+		};
+
+		let priority = Priority::of(requests.requests());
+		trace!(target: "pip", "Queueing requests (id: {}, priority: {:?}) from peer {}", req_id, priority, peer_id);
+		self.serve_queue.push(*peer_id, req_id, priority, requests, SteadyTime::now());
+		self.serve_requests(io, (*peer_id, req_id));
 
-		io.respond(packet::RESPONSE, {
-			let mut stream = RlpStream::new_list(3);
-			let cur_credits = peer.local_credits.current();
-			stream.append(&req_id).append(&cur_credits).append_list(&responses);
-			stream.out()
-		});
 		Ok(())
 	}
 
+	// serve queued request packets, most urgent first, unless enough threads already are.
+	// `current` is the packet being handled, which can be answered directly.
+	fn serve_requests(&self, io: &IoContext, current: (PeerId, u64)) {
+		use ::request::CompleteRequest;
+
+		if !self.serve_queue.try_begin() { return }
+
+		while let Some(queued) = self.serve_queue.next(SteadyTime::now()) {
+			let (peer_id, req_id) = (queued.peer, queued.req_id);
+			let num_requests = queued.requests.requests().len();
+			trace!(target: "pip", "Beginning to respond to requests (id: {}) from peer {}", req_id, peer_id);
+
+			// respond to all requests until one fails.
+			let responses = queued.requests.respond_to_all(|complete_req| {
+				match complete_req {
+					CompleteRequest::Headers(req) => self.provider.block_headers(req).map(Response::Headers),
+					CompleteRequest::HeaderProof(req) => self.provider.header_proof(req).map(Response::HeaderProof),
+					CompleteRequest::Body(req) => self.provider.block_body(req).map(Response::Body),
+					CompleteRequest::Receipts(req) => self.provider.block_receipts(req).map(Response::Receipts),
+					CompleteRequest::Account(req) => self.provider.account_proof(req).map(Response::Account),
+					CompleteRequest::Storage(req) => self.provider.storage_proof(req).map(Response::Storage),
+					CompleteRequest::Code(req) => self.provider.contract_code(req).map(Response::Code),
+					CompleteRequest::Execution(req) => self.provider.transaction_proof(req).map(Response::Execution),
+				}
+			});
+
+			trace!(target: "pip", "Responded to {}/{} requests in packet {}", responses.len(), num_requests, req_id);
+
+			let cur_credits = match self.peers.read().get(&peer_id) {
+				Some(peer) => peer.lock().local_credits.current(),
+				None => continue, // peer disconnected meanwhile.
+			};
+
+			let body = {
+				let mut stream = RlpStream::new_list(3);
+				stream.append(&req_id).append(&cur_credits).append_list(&responses);
+				stream.out()
+			};
+
+			match (peer_id, req_id) == current {
+				true => io.respond(packet::RESPONSE, body),
+				false => io.send(peer_id, packet::RESPONSE, body),
+			}
+		}
+	}
+
 	// handle a packet with responses.
 	fn response(&self, peer: &PeerId, io: &IoContext, raw: UntrustedRlp) -> Result<(), Error> {
 		let (req_id, responses) = {
@@ -0,0 +1,207 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scheduling of the request packets we serve.
+//!
+//! At most `MAX_SERVERS` threads serve packets at a time; packets arriving meanwhile wait
+//! and are served cheapest kind first: headers and header proofs, then other chain data,
+//! then execution proofs. A packet waiting for longer than `STARVATION_MS` is served next
+//! regardless of its kind, and each peer may only have `MAX_QUEUED_PER_PEER` packets waiting.
+
+use std::collections::VecDeque;
+
+use network::PeerId;
+use request::Request;
+use util::Mutex;
+
+use time::{Duration, SteadyTime};
+
+/// Maximum number of threads serving requests at once.
+pub const MAX_SERVERS: usize = 2;
+/// Maximum number of request packets of a single peer waiting to be served.
+pub const MAX_QUEUED_PER_PEER: usize = 8;
+/// Waiting time after which a packet is served before those of more urgent kinds.
+pub const STARVATION_MS: i64 = 2000;
+
+/// Urgency of a request packet, given by its most expensive request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+	/// Headers and header proofs only.
+	Headers = 0,
+	/// Bodies, receipts, state proofs or code.
+	Data = 1,
+	/// Execution proofs.
+	Execution = 2,
+}
+
+impl Priority {
+	/// Priority of a packet with given requests.
+	pub fn of(requests: &[Request]) -> Self {
+		requests.iter()
+			.map(|request| match *request {
+				Request::Headers(_) | Request::HeaderProof(_) => Priority::Headers,
+				Request::Execution(_) => Priority::Execution,
+				_ => Priority::Data,
+			})
+			.max()
+			.unwrap_or(Priority::Headers)
+	}
+}
+
+/// A request packet waiting to be served.
+pub struct Queued<T> {
+	/// Peer which sent the packet.
+	pub peer: PeerId,
+	/// Request ID of the packet.
+	pub req_id: u64,
+	/// The requests.
+	pub requests: T,
+	queued_at: SteadyTime,
+}
+
+struct State<T> {
+	queues: [VecDeque<Queued<T>>; 3],
+	servers: usize,
+}
+
+/// Queue of request packets waiting to be served.
+pub struct ServeQueue<T> {
+	state: Mutex<State<T>>,
+}
+
+impl<T> Default for ServeQueue<T> {
+	fn default() -> Self {
+		ServeQueue {
+			state: Mutex::new(State {
+				queues: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+				servers: 0,
+			}),
+		}
+	}
+}
+
+impl<T> ServeQueue<T> {
+	/// Whether given peer may queue another packet.
+	pub fn can_queue(&self, peer: PeerId) -> bool {
+		let state = self.state.lock();
+		state.queues.iter().flat_map(|queue| queue.iter()).filter(|queued| queued.peer == peer).count() < MAX_QUEUED_PER_PEER
+	}
+
+	/// Queue a packet.
+	pub fn push(&self, peer: PeerId, req_id: u64, priority: Priority, requests: T, now: SteadyTime) {
+		self.state.lock().queues[priority as usize].push_back(Queued {
+			peer: peer,
+			req_id: req_id,
+			requests: requests,
+			queued_at: now,
+		});
+	}
+
+	/// Claim a serving slot. Returns `false` if all are taken; the packets will be served
+	/// by the threads holding them.
+	pub fn try_begin(&self) -> bool {
+		let mut state = self.state.lock();
+		match state.servers < MAX_SERVERS {
+			true => {
+				state.servers += 1;
+				true
+			},
+			false => false,
+		}
+	}
+
+	/// Take the next packet to serve with a claimed slot. Releases the slot if there is none.
+	pub fn next(&self, now: SteadyTime) -> Option<Queued<T>> {
+		let mut state = self.state.lock();
+		let starved = state.queues.iter()
+			.enumerate()
+			.filter_map(|(i, queue)| queue.front().map(|queued| (i, queued.queued_at)))
+			.filter(|&(_, queued_at)| queued_at + Duration::milliseconds(STARVATION_MS) <= now)
+			.min_by_key(|&(_, queued_at)| queued_at)
+			.map(|(i, _)| i);
+		let index = starved.or_else(|| state.queues.iter().position(|queue| !queue.is_empty()));
+
+		match index {
+			Some(i) => state.queues[i].pop_front(),
+			None => {
+				state.servers -= 1;
+				None
+			}
+		}
+	}
+
+	/// Drop the packets of a disconnected peer.
+	pub fn remove_peer(&self, peer: PeerId) {
+		for queue in self.state.lock().queues.iter_mut() {
+			queue.retain(|queued| queued.peer != peer);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use time::{Duration, SteadyTime};
+	use super::{ServeQueue, Priority, MAX_SERVERS, MAX_QUEUED_PER_PEER, STARVATION_MS};
+
+	#[test]
+	fn serves_cheap_requests_first() {
+		let queue = ServeQueue::default();
+		let now = SteadyTime::now();
+		queue.push(1, 0, Priority::Execution, "execution", now);
+		queue.push(2, 0, Priority::Data, "body", now);
+		queue.push(3, 0, Priority::Headers, "headers", now);
+
+		assert!(queue.try_begin());
+		let served: Vec<_> = (0..3).map(|_| queue.next(now).unwrap().requests).collect();
+		assert_eq!(served, vec!["headers", "body", "execution"]);
+		assert!(queue.next(now).is_none());
+	}
+
+	#[test]
+	fn serves_starved_requests() {
+		let queue = ServeQueue::default();
+		let start = SteadyTime::now();
+		queue.push(1, 0, Priority::Execution, "execution", start);
+		let later = start + Duration::milliseconds(STARVATION_MS);
+		queue.push(2, 0, Priority::Headers, "headers", later);
+
+		assert!(queue.try_begin());
+		assert_eq!(queue.next(later).unwrap().requests, "execution");
+		assert_eq!(queue.next(later).unwrap().requests, "headers");
+	}
+
+	#[test]
+	fn limits_servers_and_queued_packets() {
+		let queue = ServeQueue::default();
+		let now = SteadyTime::now();
+		for _ in 0..MAX_SERVERS {
+			assert!(queue.try_begin());
+		}
+		assert!(!queue.try_begin());
+		assert!(queue.next(now).is_none());
+		assert!(queue.try_begin());
+
+		for i in 0..MAX_QUEUED_PER_PEER {
+			assert!(queue.can_queue(1));
+			queue.push(1, i as u64, Priority::Headers, (), now);
+		}
+		assert!(!queue.can_queue(1));
+		assert!(queue.can_queue(2));
+
+		queue.remove_peer(1);
+		assert!(queue.can_queue(1));
+	}
+}
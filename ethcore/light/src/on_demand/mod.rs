@@ -21,7 +21,7 @@
 // TODO [ToDr] Suppressing deprecation warnings. Rob will fix the API anyway.
 #![allow(deprecated)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use ethcore::basic_account::BasicAccount;
@@ -34,6 +34,7 @@ use futures::{Async, Poll, Future};
 use futures::sync::oneshot::{self, Sender, Receiver};
 use network::PeerId;
 use rlp::RlpStream;
+use time::{Duration, SteadyTime};
 use util::{Bytes, RwLock, Mutex, U256, H256};
 use util::sha3::{SHA3_NULL_RLP, SHA3_EMPTY_LIST_RLP};
 
@@ -47,9 +48,18 @@ pub mod request;
 struct Peer {
 	status: Status,
 	capabilities: Capabilities,
+	latency: Option<Duration>,
 }
 
 impl Peer {
+	// Fold a measured response time into the average latency.
+	fn note_latency(&mut self, sample: Duration) {
+		self.latency = Some(match self.latency {
+			Some(latency) => (latency * 3 + sample) / 4,
+			None => sample,
+		});
+	}
+
 	// Whether a given peer can handle a specific request.
 	fn can_handle(&self, pending: &Pending) -> bool {
 		match *pending {
@@ -128,12 +138,28 @@ impl Pending {
 	}
 }
 
+/// Information about a connected server as seen by the on-demand service.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerInfo {
+	/// Peer id.
+	pub id: PeerId,
+	/// Last known status, updated with announcements.
+	pub status: Status,
+	/// Capabilities the peer serves.
+	pub capabilities: Capabilities,
+	/// Average time it takes the peer to respond, if measured.
+	pub latency: Option<Duration>,
+	/// Whether requests are preferably dispatched to this peer.
+	pub pinned: bool,
+}
+
 /// On demand request service. See module docs for more details.
 /// Accumulates info about all peers' capabilities and dispatches
 /// requests to them accordingly.
 pub struct OnDemand {
 	peers: RwLock<HashMap<PeerId, Peer>>,
-	pending_requests: RwLock<HashMap<ReqId, Pending>>,
+	pinned: RwLock<HashSet<PeerId>>,
+	pending_requests: RwLock<HashMap<ReqId, (Pending, SteadyTime)>>,
 	cache: Arc<Mutex<Cache>>,
 	orphaned_requests: RwLock<Vec<Pending>>,
 }
@@ -145,6 +171,7 @@ impl OnDemand {
 	pub fn new(cache: Arc<Mutex<Cache>>) -> Self {
 		OnDemand {
 			peers: RwLock::new(HashMap::new()),
+			pinned: RwLock::new(HashSet::new()),
 			pending_requests: RwLock::new(HashMap::new()),
 			cache: cache,
 			orphaned_requests: RwLock::new(Vec::new()),
@@ -295,7 +322,33 @@ impl OnDemand {
 		receiver
 	}
 
+	/// Get info about all connected servers.
+	pub fn peers(&self) -> Vec<PeerInfo> {
+		let pinned = self.pinned.read();
+		self.peers.read().iter().map(|(id, peer)| PeerInfo {
+			id: *id,
+			status: peer.status.clone(),
+			capabilities: peer.capabilities.clone(),
+			latency: peer.latency,
+			pinned: pinned.contains(id),
+		}).collect()
+	}
+
+	/// Prefer the given peer when dispatching requests, as long as it stays connected.
+	/// Returns `false` if there is no such peer.
+	pub fn pin(&self, peer: PeerId) -> bool {
+		if !self.peers.read().contains_key(&peer) { return false }
+		self.pinned.write().insert(peer);
+		true
+	}
+
+	/// Stop preferring the given peer. Returns `false` if it wasn't pinned.
+	pub fn unpin(&self, peer: PeerId) -> bool {
+		self.pinned.write().remove(&peer)
+	}
+
 	// dispatch the request, with a "suitability" function to filter acceptable peers.
+	// pinned peers are tried first.
 	fn dispatch(&self, ctx: &BasicContext, pending: Pending) {
 		let mut builder = basic_request::RequestBuilder::default();
 		builder.push(pending.make_request())
@@ -303,14 +356,19 @@ impl OnDemand {
 
 		let complete = builder.build();
 
-		for (id, peer) in self.peers.read().iter() {
+		let peers = self.peers.read();
+		let pinned = self.pinned.read();
+		let candidates = peers.iter().filter(|&(id, _)| pinned.contains(id))
+			.chain(peers.iter().filter(|&(id, _)| !pinned.contains(id)));
+
+		for (id, peer) in candidates {
 			if !peer.can_handle(&pending) { continue }
 			match ctx.request_from(*id, complete.clone()) {
 				Ok(req_id) => {
 					trace!(target: "on_demand", "Assigning request to peer {}", id);
 					self.pending_requests.write().insert(
 						req_id,
-						pending,
+						(pending, SteadyTime::now()),
 					);
 					return
 				}
@@ -375,18 +433,19 @@ impl OnDemand {
 
 impl Handler for OnDemand {
 	fn on_connect(&self, ctx: &EventContext, status: &Status, capabilities: &Capabilities) {
-		self.peers.write().insert(ctx.peer(), Peer { status: status.clone(), capabilities: capabilities.clone() });
+		self.peers.write().insert(ctx.peer(), Peer { status: status.clone(), capabilities: capabilities.clone(), latency: None });
 		self.dispatch_orphaned(ctx.as_basic());
 	}
 
 	fn on_disconnect(&self, ctx: &EventContext, unfulfilled: &[ReqId]) {
 		self.peers.write().remove(&ctx.peer());
+		self.pinned.write().remove(&ctx.peer());
 		let ctx = ctx.as_basic();
 
 		{
 			let mut orphaned = self.orphaned_requests.write();
 			for unfulfilled in unfulfilled {
-				if let Some(pending) = self.pending_requests.write().remove(unfulfilled) {
+				if let Some((pending, _)) = self.pending_requests.write().remove(unfulfilled) {
 					trace!(target: "on_demand", "Attempting to reassign dropped request");
 					orphaned.push(pending);
 				}
@@ -409,7 +468,12 @@ impl Handler for OnDemand {
 	fn on_responses(&self, ctx: &EventContext, req_id: ReqId, responses: &[basic_request::Response]) {
 		let peer = ctx.peer();
 		let req = match self.pending_requests.write().remove(&req_id) {
-			Some(req) => req,
+			Some((req, sent_at)) => {
+				if let Some(peer) = self.peers.write().get_mut(&peer) {
+					peer.note_latency(SteadyTime::now() - sent_at);
+				}
+				req
+			}
 			None => return,
 		};
 
@@ -571,4 +635,14 @@ mod tests {
 		on_demand.dispatch_orphaned(&FakeContext);
 		assert!(on_demand.orphaned_requests.read().is_empty());
 	}
+
+	#[test]
+	fn pins_only_connected_peers() {
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::hours(6))));
+		let on_demand = OnDemand::new(cache);
+
+		assert!(!on_demand.pin(1));
+		assert!(!on_demand.unpin(1));
+		assert!(on_demand.peers().is_empty());
+	}
 }
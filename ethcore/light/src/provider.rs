@@ -191,6 +191,16 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 			}
 		};
 
+		// only CHTs whose roots the client generated are served: their blocks are known
+		// to be available and final.
+		let cht_root = match self.cht_root(cht_number) {
+			Some(root) => root,
+			None => {
+				trace!(target: "pip_provider", "Requested proof from unavailable CHT {}", cht_number);
+				return None;
+			}
+		};
+
 		let mut needed = None;
 
 		// build the CHT, caching the requested header as we pass through it.
@@ -223,6 +233,11 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 			}
 		};
 
+		if cht.root() != cht_root {
+			debug!(target: "pip_provider", "Built CHT {} doesn't match the generated root", cht_number);
+			return None;
+		}
+
 		let (needed_hdr, needed_td) = needed.expect("`needed` always set in loop, number checked before; qed");
 
 		// prove our result.
@@ -258,7 +273,7 @@ impl<T: ProvingBlockChainClient + ?Sized> Provider for T {
 		}.fake_sign(req.from);
 
 		self.prove_transaction(transaction, id)
-			.map(|proof| ::request::ExecutionResponse { items: proof })
+			.map(|(_, proof)| ::request::ExecutionResponse { items: proof })
 	}
 
 	fn ready_transactions(&self) -> Vec<PendingTransaction> {
@@ -345,7 +360,9 @@ impl<L: AsLightClient> AsLightClient for LightProvider<L> {
 
 #[cfg(test)]
 mod tests {
-	use ethcore::client::{EachBlockWith, TestBlockChainClient};
+	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
+	use ethcore::ids::BlockId;
+	use util::H256;
 	use super::Provider;
 
 	#[test]
@@ -361,6 +378,17 @@ mod tests {
 
 		client.add_blocks(48, EachBlockWith::Nothing);
 
+		// the client hasn't generated the root yet.
+		assert!(client.header_proof(req.clone()).is_none());
+
+		client.cht_roots.write().insert(0, H256::default());
+		assert!(client.header_proof(req.clone()).is_none());
+
+		let root = ::cht::compute_root(0, (1..2049).map(|n| {
+			let id = BlockId::Number(n);
+			(client.block_hash(id).unwrap(), client.block_total_difficulty(id).unwrap())
+		})).unwrap();
+		client.cht_roots.write().insert(0, root);
 		assert!(client.header_proof(req.clone()).is_some());
 	}
 }
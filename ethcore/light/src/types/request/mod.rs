@@ -613,14 +613,11 @@ pub mod header {
 
 	impl Decodable for Response {
 		fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
-			use ethcore::header::Header as FullHeader;
-
 			let mut headers = Vec::new();
 
 			for item in rlp.iter() {
 				// check that it's a valid encoding.
-				// TODO: just return full headers here?
-				let _: FullHeader = item.as_val()?;
+				encoded::Header::check_rlp(&item)?;
 				headers.push(encoded::Header::new(item.as_raw().to_owned()));
 			}
 
@@ -576,6 +576,18 @@ impl AccountProvider {
 		Ok(self.sstore.sign(&account, &password, &message)?)
 	}
 
+	/// Signs a batch of messages with a single password check, e.g. for services signing many payouts at once.
+	/// If password is not provided the account must be unlocked; a temporary unlock covers the whole batch.
+	/// Fails if the account can't be used, otherwise returns a result per message.
+	pub fn sign_batch(&self, address: Address, password: Option<String>, messages: &[Message])
+		-> Result<Vec<Result<Signature, SignError>>, SignError>
+	{
+		let account = self.sstore.account_ref(&address)?;
+		let password = password.map(Ok).unwrap_or_else(|| self.password(&account))?;
+		let signatures = self.sstore.sign_many(&account, &password, messages)?;
+		Ok(signatures.into_iter().map(|signature| signature.map_err(SignError::SStore)).collect())
+	}
+
 	/// Signs message using the derived secret. If password is not provided the account must be unlocked.
 	pub fn sign_derived(&self, address: &Address, password: Option<String>, derivation: Derivation, message: Message)
 		-> Result<Signature, SignError>
@@ -734,6 +746,24 @@ mod tests {
 		assert!(ap.sign(kp.address(), None, Default::default()).is_err());
 	}
 
+	#[test]
+	fn sign_batch_with_single_unlock() {
+		let kp = Random.generate().unwrap();
+		let ap = AccountProvider::transient_provider();
+		assert!(ap.insert_account(kp.secret().clone(), "test").is_ok());
+		let messages = vec![H256::from(1), H256::from(2), H256::from(3)];
+
+		assert!(ap.sign_batch(kp.address(), None, &messages).is_err());
+		assert!(ap.sign_batch(kp.address(), Some("test1".into()), &messages).is_err());
+
+		assert!(ap.unlock_account_temporarily(kp.address(), "test".into()).is_ok());
+		let signatures = ap.sign_batch(kp.address(), None, &messages).unwrap();
+		assert!(ap.sign_batch(kp.address(), None, &messages).is_err());
+
+		let expected: Vec<_> = messages.iter().map(|m| ap.sign(kp.address(), Some("test".into()), *m).unwrap()).collect();
+		assert_eq!(signatures.into_iter().map(Result::unwrap).collect::<Vec<_>>(), expected);
+	}
+
 	#[test]
 	fn derived_account_nosave() {
 		let kp = Random.generate().unwrap();
@@ -26,7 +26,7 @@ use util::error::{Mismatch, OutOfBounds};
 
 use basic_types::{LogBloom, Seal};
 use env_info::{EnvInfo, LastHashes};
-use engines::Engine;
+use engines::{Engine, SystemCall, SystemCallOutcome, execute_system_call};
 use error::{Error, BlockError, TransactionError};
 use factory::Factories;
 use header::Header;
@@ -92,6 +92,8 @@ pub struct ExecutedBlock {
 	transactions_set: HashSet<H256>,
 	state: State<StateDB>,
 	traces: Option<Vec<Vec<FlatTrace>>>,
+	last_hashes: Arc<LastHashes>,
+	system_calls: Vec<SystemCallOutcome>,
 }
 
 /// A set of references to `ExecutedBlock` fields that are publicly accessible.
@@ -128,7 +130,7 @@ pub struct BlockRef<'a> {
 
 impl ExecutedBlock {
 	/// Create a new block from the given `state`.
-	fn new(state: State<StateDB>, tracing: bool) -> ExecutedBlock {
+	fn new(state: State<StateDB>, last_hashes: Arc<LastHashes>, tracing: bool) -> ExecutedBlock {
 		ExecutedBlock {
 			header: Default::default(),
 			transactions: Default::default(),
@@ -137,9 +139,34 @@ impl ExecutedBlock {
 			transactions_set: Default::default(),
 			state: state,
 			traces: if tracing {Some(Vec::new())} else {None},
+			last_hashes: last_hashes,
+			system_calls: Vec::new(),
+		}
+	}
+
+	/// The environment transactions of this block are executed in.
+	fn env_info(&self) -> EnvInfo {
+		EnvInfo {
+			number: self.header.number(),
+			author: self.header.author().clone(),
+			timestamp: self.header.timestamp(),
+			difficulty: self.header.difficulty().clone(),
+			last_hashes: self.last_hashes.clone(),
+			gas_used: self.receipts.last().map_or(U256::zero(), |r| r.gas_used),
+			gas_limit: self.header.gas_limit().clone(),
 		}
 	}
 
+	/// Call a contract on behalf of the engine on top of the current state.
+	/// The outcome is recorded along with the block; the call's output is returned.
+	pub fn system_call(&mut self, engine: &Engine, call: SystemCall) -> Result<Bytes, Error> {
+		let env_info = self.env_info();
+		let outcome = execute_system_call(&mut self.state, &env_info, engine, call, self.traces.is_some())?;
+		let output = outcome.output.clone();
+		self.system_calls.push(outcome);
+		Ok(output)
+	}
+
 	/// Get a structure containing individual references to all public fields.
 	pub fn fields_mut(&mut self) -> BlockRefMut {
 		BlockRefMut {
@@ -196,6 +223,9 @@ pub trait IsBlock {
 
 	/// Get all uncles in this block.
 	fn uncles(&self) -> &[Header] { &self.block().uncles }
+
+	/// Get outcomes of system calls made by the engine in this block.
+	fn system_calls(&self) -> &[SystemCallOutcome] { &self.block().system_calls }
 }
 
 /// Trait for a object that has a state database.
@@ -263,7 +293,7 @@ impl<'x> OpenBlock<'x> {
 	) -> Result<Self, Error> {
 		let state = State::from_existing(db, parent.state_root().clone(), engine.account_start_nonce(), factories)?;
 		let mut r = OpenBlock {
-			block: ExecutedBlock::new(state, tracing),
+			block: ExecutedBlock::new(state, last_hashes.clone(), tracing),
 			engine: engine,
 			last_hashes: last_hashes,
 		};
@@ -333,15 +363,7 @@ impl<'x> OpenBlock<'x> {
 	/// Get the environment info concerning this block.
 	pub fn env_info(&self) -> EnvInfo {
 		// TODO: memoise.
-		EnvInfo {
-			number: self.block.header.number(),
-			author: self.block.header.author().clone(),
-			timestamp: self.block.header.timestamp(),
-			difficulty: self.block.header.difficulty().clone(),
-			last_hashes: self.last_hashes.clone(),
-			gas_used: self.block.receipts.last().map_or(U256::zero(), |r| r.gas_used),
-			gas_limit: self.block.header.gas_limit().clone(),
-		}
+		self.block.env_info()
 	}
 
 	/// Push a transaction into the block.
@@ -474,10 +496,10 @@ impl LockedBlock {
 	/// NOTE: This does not check the validity of `seal` with the engine.
 	pub fn seal(self, engine: &Engine, seal: Vec<Bytes>) -> Result<SealedBlock, BlockError> {
 		let mut s = self;
-		if seal.len() != engine.seal_fields() {
-			return Err(BlockError::InvalidSealArity(Mismatch{expected: engine.seal_fields(), found: seal.len()}));
+		engine.apply_seal(&mut s.block.header, seal);
+		if s.block.header.seal().len() != engine.seal_fields() {
+			return Err(BlockError::InvalidSealArity(Mismatch{expected: engine.seal_fields(), found: s.block.header.seal().len()}));
 		}
-		s.block.header.set_seal(seal);
 		Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes })
 	}
 
@@ -486,7 +508,7 @@ impl LockedBlock {
 	/// Returns the `ClosedBlock` back again if the seal is no good.
 	pub fn try_seal(self, engine: &Engine, seal: Vec<Bytes>) -> Result<SealedBlock, (Error, LockedBlock)> {
 		let mut s = self;
-		s.block.header.set_seal(seal);
+		engine.apply_seal(&mut s.block.header, seal);
 		match engine.verify_block_seal(&s.block.header) {
 			Err(e) => Err((e, s)),
 			_ => Ok(SealedBlock { block: s.block, uncle_bytes: s.uncle_bytes }),
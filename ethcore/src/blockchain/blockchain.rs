@@ -35,10 +35,27 @@ use blockchain::{CacheSize, ImportRoute, Config};
 use db::{self, Writable, Readable, CacheUpdatePolicy};
 use cache_manager::CacheManager;
 use encoded;
+use ethkey::public_to_address;
 
 const LOG_BLOOMS_LEVELS: usize = 3;
 const LOG_BLOOMS_ELEMENTS_PER_INDEX: usize = 16;
 
+/// Key prefixes of content-addressed transaction lists in the bodies column.
+const TRANSACTION_LIST_DATA: u8 = 0;
+const TRANSACTION_LIST_REFS: u8 = 1;
+
+/// Key of the first block whose transaction index entries haven't been pruned yet.
+const TX_INDEX_PRUNED_KEY: &'static [u8] = b"tx_index_pruned";
+/// Maximal number of blocks pruned from the transaction index at once.
+const TX_INDEX_PRUNE_BATCH: u64 = 100;
+
+fn transaction_list_key(list_hash: &H256, prefix: u8) -> H264 {
+	let mut result = H264::default();
+	result[0] = prefix;
+	(*result)[1..].clone_from_slice(list_hash);
+	result
+}
+
 /// Interface for querying blocks by hash and by number.
 pub trait BlockProvider {
 	/// Returns true if the given block is known
@@ -199,6 +216,12 @@ pub struct BlockChain {
 	pending_block_hashes: RwLock<HashMap<BlockNumber, H256>>,
 	pending_block_details: RwLock<HashMap<H256, BlockDetails>>,
 	pending_transaction_addresses: RwLock<HashMap<H256, Option<TransactionAddress>>>,
+	pending_transaction_list_refs: RwLock<HashMap<H256, u32>>,
+
+	dedup_transactions: bool,
+	tx_index_history: Option<u64>,
+	tx_index_watch: HashSet<Address>,
+	tx_index_pruned: Mutex<BlockNumber>,
 }
 
 impl BlockProvider for BlockChain {
@@ -241,6 +264,7 @@ impl BlockProvider for BlockChain {
 		{
 			let read = self.block_headers.read();
 			if let Some(v) = read.get(hash) {
+				db::note_cache_lookup(db::COL_HEADERS, true);
 				return Some(encoded::Header::new(v.clone()));
 			}
 		}
@@ -256,6 +280,7 @@ impl BlockProvider for BlockChain {
 		}
 
 		// Read from DB and populate cache
+		db::note_cache_lookup(db::COL_HEADERS, false);
 		let opt = self.db.get(db::COL_HEADERS, hash)
 			.expect("Low level database error. Some issue with disk?");
 
@@ -279,6 +304,7 @@ impl BlockProvider for BlockChain {
 		{
 			let read = self.block_bodies.read();
 			if let Some(v) = read.get(hash) {
+				db::note_cache_lookup(db::COL_BODIES, true);
 				return Some(encoded::Body::new(v.clone()));
 			}
 		}
@@ -292,12 +318,13 @@ impl BlockProvider for BlockChain {
 		}
 
 		// Read from DB and populate cache
+		db::note_cache_lookup(db::COL_BODIES, false);
 		let opt = self.db.get(db::COL_BODIES, hash)
 			.expect("Low level database error. Some issue with disk?");
 
 		let result = match opt {
 			Some(b) => {
-				let bytes: Bytes = UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec();
+				let bytes = self.expand_body(UntrustedRlp::new(&b).decompress(RlpType::Blocks).to_vec());
 				let mut write = self.block_bodies.write();
 				write.insert(hash.clone(), bytes.clone());
 				Some(encoded::Body::new(bytes))
@@ -446,8 +473,17 @@ impl BlockChain {
 			pending_block_hashes: RwLock::new(HashMap::new()),
 			pending_block_details: RwLock::new(HashMap::new()),
 			pending_transaction_addresses: RwLock::new(HashMap::new()),
+			pending_transaction_list_refs: RwLock::new(HashMap::new()),
+			dedup_transactions: config.dedup_transactions,
+			tx_index_history: config.tx_index_history,
+			tx_index_watch: config.tx_index_watch.clone(),
+			tx_index_pruned: Mutex::new(0),
 		};
 
+		if let Some(pruned) = bc.db.get(db::COL_EXTRA, TX_INDEX_PRUNED_KEY).unwrap() {
+			*bc.tx_index_pruned.lock() = ::rlp::decode(&pruned);
+		}
+
 		// load best block
 		let best_block_hash = match bc.db.get(db::COL_EXTRA, b"best").unwrap() {
 			Some(best) => {
@@ -608,6 +644,73 @@ impl BlockChain {
 		None
 	}
 
+	/// Walk back from the best block, at most `max_depth` blocks, to the most recent canonical block
+	/// whose header, body and details are all present and whose header satisfies `is_usable`
+	/// (e.g. its state is available). The genesis block is always consistent.
+	pub fn last_consistent_block<F>(&self, max_depth: BlockNumber, is_usable: F) -> Option<(BlockNumber, H256)>
+		where F: Fn(&Header) -> bool
+	{
+		let best = self.best_block_number();
+		let lowest = best.saturating_sub(max_depth);
+		for number in (lowest..best + 1).rev() {
+			if number == 0 {
+				return Some((0, self.genesis_hash()));
+			}
+
+			let hash = match self.block_hash(number) {
+				Some(hash) => hash,
+				None => continue,
+			};
+			let consistent = self.block_details(&hash).is_some()
+				&& self.block_body(&hash).is_some()
+				&& self.block_header(&hash).map_or(false, |header| is_usable(&header));
+			if consistent {
+				return Some((number, hash));
+			}
+		}
+		None
+	}
+
+	/// Make the canonical block with given number the best block, dropping the extras of the
+	/// canonical blocks above it so they are imported again. Returns the hashes of the dropped blocks,
+	/// lowest first. The changes are put into `batch`, the chain has to be reopened once it's written.
+	pub fn rollback_to(&self, batch: &mut DBTransaction, number: BlockNumber) -> Vec<H256> {
+		let best = self.best_block_number();
+		let hash = match self.block_hash(number) {
+			Some(hash) => hash,
+			None => return Vec::new(),
+		};
+
+		let mut dropped = Vec::new();
+		for n in (number + 1)..(best + 1) {
+			if let Some(dropped_hash) = self.block_hash(n) {
+				if let Some(body) = self.block_body(&dropped_hash) {
+					for tx in body.transaction_hashes() {
+						batch.delete(db::COL_EXTRA, &db::Key::<TransactionAddress>::key(&tx));
+					}
+				}
+				batch.delete(db::COL_EXTRA, &db::Key::<BlockDetails>::key(&dropped_hash));
+				dropped.push(dropped_hash);
+			}
+			batch.delete(db::COL_EXTRA, &db::Key::<H256>::key(&n));
+		}
+
+		if best > number {
+			let range = (number + 1) as bc::Number .. best as bc::Number;
+			let chain = bc::group::BloomGroupChain::new(self.blooms_config, self);
+			for (k, v) in chain.replace(&range, vec![]) {
+				batch.write(db::COL_EXTRA, &LogGroupPosition::from(k), &BloomGroup::from(v));
+			}
+		}
+
+		if let Some(mut details) = self.block_details(&hash) {
+			details.children.clear();
+			batch.write(db::COL_EXTRA, &hash, &details);
+		}
+		batch.put(db::COL_EXTRA, b"best", &hash);
+		dropped
+	}
+
 	/// Returns a tree route between `from` and `to`, which is a tuple of:
 	///
 	/// - a vector of hashes of all blocks, ordered from `from` to `to`.
@@ -717,7 +820,7 @@ impl BlockChain {
 
 		let block_rlp = UntrustedRlp::new(bytes);
 		let compressed_header = block_rlp.at(0).unwrap().compress(RlpType::Blocks);
-		let compressed_body = UntrustedRlp::new(&Self::block_to_body(bytes)).compress(RlpType::Blocks);
+		let compressed_body = UntrustedRlp::new(&self.prepare_body_update(batch, bytes)).compress(RlpType::Blocks);
 
 		// store block in db
 		batch.put(db::COL_HEADERS, &hash, &compressed_header);
@@ -817,6 +920,15 @@ impl BlockChain {
 		self.cache_man.lock().note_used(CacheId::BlockDetails(block_hash));
 	}
 
+	/// Writes receipts of a block already in the chain, e.g. ones missing after a snapshot restoration.
+	/// Expects the receipts to be verified against the block's receipts root.
+	pub fn insert_block_receipts(&self, batch: &mut DBTransaction, hash: &H256, receipts: Vec<Receipt>) {
+		let mut update = HashMap::new();
+		update.insert(hash.clone(), BlockReceipts::new(receipts));
+		let mut write_receipts = self.block_receipts.write();
+		batch.extend_with_cache(db::COL_EXTRA, &mut *write_receipts, update, CacheUpdatePolicy::Remove);
+	}
+
 	#[cfg_attr(feature="dev", allow(similar_names))]
 	/// Inserts the block into backing cache database.
 	/// Expects the block to be valid and already verified.
@@ -835,7 +947,8 @@ impl BlockChain {
 
 		// store block in db
 		batch.put_compressed(db::COL_HEADERS, &hash, block.header_rlp().as_raw().to_vec());
-		batch.put_compressed(db::COL_BODIES, &hash, Self::block_to_body(bytes));
+		let body = self.prepare_body_update(batch, bytes);
+		batch.put_compressed(db::COL_BODIES, &hash, body);
 
 		let info = self.block_info(&header);
 
@@ -948,6 +1061,7 @@ impl BlockChain {
 		let mut pending_write_hashes = self.pending_block_hashes.write();
 		let mut pending_block_details = self.pending_block_details.write();
 		let mut pending_write_txs = self.pending_transaction_addresses.write();
+		let mut pending_transaction_list_refs = self.pending_transaction_list_refs.write();
 
 		let mut best_block = self.best_block.write();
 		let mut write_block_details = self.block_details.write();
@@ -968,6 +1082,8 @@ impl BlockChain {
 		write_hashes.extend(mem::replace(&mut *pending_write_hashes, HashMap::new()));
 		write_txs.extend(enacted_txs.into_iter().map(|(k, v)| (k, v.expect("Transactions were partitioned; qed"))));
 		write_block_details.extend(mem::replace(&mut *pending_block_details, HashMap::new()));
+		// reference counts are readable from the database once the batch is written
+		pending_transaction_list_refs.clear();
 
 		for hash in retracted_txs.keys() {
 			write_txs.remove(hash);
@@ -1004,17 +1120,16 @@ impl BlockChain {
 		self.find_uncle_hashes(parent, uncle_generations).map(|v| v.into_iter().filter_map(|h| self.block_header(&h)).collect())
 	}
 
-	/// Given a block's `parent`, find every block hash which represents a valid possible uncle.
-	pub fn find_uncle_hashes(&self, parent: &H256, uncle_generations: usize) -> Option<Vec<H256>> {
-		if !self.is_known(parent) { return None; }
-
-		let mut excluded = HashSet::new();
+	/// Given a block's `parent`, find the hashes of blocks which can't be its uncles:
+	/// its recent ancestors and the uncles they already include.
+	pub fn excluded_uncle_hashes(&self, parent: &H256, uncle_generations: usize) -> Option<HashSet<H256>> {
 		let ancestry = match self.ancestry_iter(parent.clone()) {
 			Some(iter) => iter,
 			None => return None,
 		};
 
-		for a in ancestry.clone().take(uncle_generations) {
+		let mut excluded = HashSet::new();
+		for a in ancestry.take(uncle_generations) {
 			if let Some(uncles) = self.uncle_hashes(&a) {
 				excluded.extend(uncles);
 				excluded.insert(a);
@@ -1022,6 +1137,19 @@ impl BlockChain {
 				break
 			}
 		}
+		Some(excluded)
+	}
+
+	/// Given a block's `parent`, find every block hash which represents a valid possible uncle.
+	pub fn find_uncle_hashes(&self, parent: &H256, uncle_generations: usize) -> Option<Vec<H256>> {
+		let excluded = match self.excluded_uncle_hashes(parent, uncle_generations) {
+			Some(excluded) => excluded,
+			None => return None,
+		};
+		let ancestry = match self.ancestry_iter(parent.clone()) {
+			Some(iter) => iter,
+			None => return None,
+		};
 
 		let mut ret = Vec::new();
 		for a in ancestry.skip(1).take(uncle_generations) {
@@ -1294,6 +1422,236 @@ impl BlockChain {
 		body.out()
 	}
 
+	/// Prepare block body for storage. With transaction deduplication enabled a non-empty
+	/// transaction list is stored once under its hash and the body only references it.
+	fn prepare_body_update(&self, batch: &mut DBTransaction, block: &[u8]) -> Bytes {
+		let block_rlp = Rlp::new(block);
+		let transactions = block_rlp.at(1);
+		if !self.dedup_transactions || transactions.item_count() == 0 {
+			return Self::block_to_body(block);
+		}
+
+		let list_hash = transactions.as_raw().sha3();
+		let refs = self.transaction_list_refs(&list_hash);
+		if refs == 0 {
+			batch.put_compressed(db::COL_BODIES, &transaction_list_key(&list_hash, TRANSACTION_LIST_DATA), transactions.as_raw().to_vec());
+		}
+		self.set_transaction_list_refs(batch, &list_hash, refs + 1);
+
+		let mut body = RlpStream::new_list(2);
+		body.append(&list_hash);
+		body.append_raw(block_rlp.at(2).as_raw(), 1);
+		body.out()
+	}
+
+	/// Replace the transaction list reference of a stored body with the list itself.
+	fn expand_body(&self, stored: Bytes) -> Bytes {
+		let list_hash: H256 = {
+			let rlp = Rlp::new(&stored);
+			if rlp.at(0).is_list() {
+				return stored;
+			}
+			rlp.val_at(0)
+		};
+
+		let transactions = self.db.get(db::COL_BODIES, &transaction_list_key(&list_hash, TRANSACTION_LIST_DATA))
+			.expect("Low level database error. Some issue with disk?")
+			.expect("Referenced transaction list is removed only with its last body; qed");
+		let transactions = UntrustedRlp::new(&transactions).decompress(RlpType::Blocks);
+
+		let mut body = RlpStream::new_list(2);
+		body.append_raw(&transactions, 1);
+		body.append_raw(Rlp::new(&stored).at(1).as_raw(), 1);
+		body.out()
+	}
+
+	fn transaction_list_refs(&self, list_hash: &H256) -> u32 {
+		if let Some(refs) = self.pending_transaction_list_refs.read().get(list_hash) {
+			return *refs;
+		}
+
+		self.db.get(db::COL_BODIES, &transaction_list_key(list_hash, TRANSACTION_LIST_REFS))
+			.expect("Low level database error. Some issue with disk?")
+			.map_or(0, |refs| ::rlp::decode(&refs))
+	}
+
+	fn set_transaction_list_refs(&self, batch: &mut DBTransaction, list_hash: &H256, refs: u32) {
+		let key = transaction_list_key(list_hash, TRANSACTION_LIST_REFS);
+		match refs {
+			0 => {
+				batch.delete(db::COL_BODIES, &key);
+				batch.delete(db::COL_BODIES, &transaction_list_key(list_hash, TRANSACTION_LIST_DATA));
+			},
+			refs => batch.put(db::COL_BODIES, &key, &::rlp::encode(&refs)),
+		}
+		self.pending_transaction_list_refs.write().insert(list_hash.clone(), refs);
+	}
+
+	/// Remove the body of a pruned block. A deduplicated transaction list is removed
+	/// together with the last body referencing it.
+	pub fn remove_block_body(&self, batch: &mut DBTransaction, hash: &H256) {
+		let stored = self.db.get(db::COL_BODIES, hash)
+			.expect("Low level database error. Some issue with disk?");
+
+		if let Some(stored) = stored {
+			let body = UntrustedRlp::new(&stored).decompress(RlpType::Blocks);
+			let body = Rlp::new(&body);
+			if !body.at(0).is_list() {
+				let list_hash: H256 = body.val_at(0);
+				let refs = self.transaction_list_refs(&list_hash);
+				self.set_transaction_list_refs(batch, &list_hash, refs.saturating_sub(1));
+			}
+		}
+
+		batch.delete(db::COL_BODIES, hash);
+		self.block_bodies.write().remove(hash);
+	}
+
+	/// Remove the bodies of side-chain blocks at height `number` and of all their descendants,
+	/// once blocks at that height are final. Headers and details are kept.
+	/// Does nothing unless transaction lists are deduplicated.
+	/// Returns the number of removed bodies.
+	pub fn remove_stale_bodies(&self, batch: &mut DBTransaction, number: BlockNumber) -> usize {
+		if !self.dedup_transactions {
+			return 0;
+		}
+
+		let canonical = match self.block_hash(number) {
+			Some(hash) => hash,
+			None => return 0,
+		};
+		let parent = match self.block_details(&canonical) {
+			Some(details) => details.parent,
+			None => return 0,
+		};
+
+		let mut stale: Vec<H256> = self.block_details(&parent)
+			.map_or_else(Vec::new, |details| details.children.into_iter().filter(|hash| *hash != canonical).collect());
+		let mut removed = 0;
+		while let Some(hash) = stale.pop() {
+			if let Some(details) = self.block_details(&hash) {
+				stale.extend(details.children);
+			}
+			self.remove_block_body(batch, &hash);
+			removed += 1;
+		}
+		removed
+	}
+
+	/// Rebuild the transaction address index for canonical blocks in `[from, to]` by scanning
+	/// their bodies. Blocks are processed in batches, each split across `workers` threads;
+	/// `progress` is called with the last block number of every written batch.
+	/// Returns the number of indexed transactions.
+	pub fn reindex_transaction_addresses<F>(&self, from: BlockNumber, to: BlockNumber, workers: usize, mut progress: F) -> usize
+		where F: FnMut(BlockNumber)
+	{
+		const BATCH_SIZE: u64 = 1000;
+
+		let workers = cmp::max(workers, 1);
+		let mut indexed = 0;
+		let mut start = from;
+
+		while start <= to {
+			let end = cmp::min(to, start.saturating_add(BATCH_SIZE - 1));
+			let bodies: Vec<_> = (start..end + 1)
+				.filter_map(|number| self.block_hash(number))
+				.filter_map(|hash| self.block_body(&hash).map(|body| (hash, body)))
+				.collect();
+
+			let chunk_size = cmp::max(1, (bodies.len() + workers - 1) / workers);
+			let addresses: HashMap<H256, TransactionAddress> = ::crossbeam::scope(|scope| {
+				let handles: Vec<_> = bodies.chunks(chunk_size).map(|chunk| scope.spawn(move || {
+					chunk.iter().flat_map(|&(ref hash, ref body)| {
+						body.transaction_hashes().into_iter()
+							.enumerate()
+							.map(move |(i, tx_hash)| (tx_hash, TransactionAddress {
+								block_hash: hash.clone(),
+								index: i,
+							}))
+					}).collect::<Vec<_>>()
+				})).collect();
+
+				handles.into_iter().flat_map(|handle| handle.join()).collect()
+			});
+
+			indexed += addresses.len();
+
+			let mut batch = DBTransaction::new();
+			batch.extend_with_cache(db::COL_EXTRA, &mut *self.transaction_addresses.write(), addresses, CacheUpdatePolicy::Overwrite);
+			self.db.write(batch).expect("Low level database error. Some issue with disk?");
+			progress(end);
+
+			if end == to { break; }
+			start = end + 1;
+		}
+
+		indexed
+	}
+
+	/// Drop transaction index entries of canonical blocks which are older than the configured
+	/// history, keeping the ones of transactions sent from or to a watched address.
+	/// Works through at most `TX_INDEX_PRUNE_BATCH` blocks per call, and stops after the block
+	/// which takes the number of checked transactions to `max_transactions`, since checking
+	/// the sender needs a signature recovery.
+	/// Returns the number of dropped entries.
+	pub fn prune_transaction_addresses(&self, max_transactions: usize) -> usize {
+		let history = match self.tx_index_history {
+			Some(history) => history,
+			None => return 0,
+		};
+
+		let mut pruned_until = self.tx_index_pruned.lock();
+		let from = *pruned_until;
+		let to = cmp::min(self.best_block_number().saturating_sub(history), from + TX_INDEX_PRUNE_BATCH);
+		if from >= to {
+			return 0;
+		}
+
+		let mut dropped: Vec<H256> = Vec::new();
+		let mut checked = 0;
+		let mut end = from;
+		while end < to && checked < max_transactions {
+			if let Some(body) = self.block_hash(end).and_then(|hash| self.block_body(&hash)) {
+				let transactions = body.transactions();
+				checked += transactions.len();
+				dropped.extend(transactions.into_iter().filter(|tx| !self.is_watched(tx)).map(|tx| tx.hash()));
+			}
+			end += 1;
+		}
+		let to = end;
+
+		let mut batch = DBTransaction::new();
+		{
+			let mut cache = self.transaction_addresses.write();
+			for hash in &dropped {
+				batch.delete(db::COL_EXTRA, &db::Key::<TransactionAddress>::key(hash));
+				cache.remove(hash);
+			}
+		}
+		batch.put(db::COL_EXTRA, TX_INDEX_PRUNED_KEY, &::rlp::encode(&to));
+		self.db.write(batch).expect("Low level database error. Some issue with disk?");
+
+		trace!(target: "blockchain", "Pruned {} transaction index entries of blocks #{}..#{}", dropped.len(), from, to);
+		*pruned_until = to;
+		dropped.len()
+	}
+
+	fn is_watched(&self, tx: &UnverifiedTransaction) -> bool {
+		if self.tx_index_watch.is_empty() {
+			return false;
+		}
+
+		if let Action::Call(ref to) = tx.action {
+			if self.tx_index_watch.contains(to) {
+				return true;
+			}
+		}
+
+		tx.recover_public()
+			.map(|public| self.tx_index_watch.contains(&public_to_address(&public)))
+			.unwrap_or(false)
+	}
+
 	/// Returns general blockchain information
 	pub fn chain_info(&self) -> BlockChainInfo {
 		// ensure data consistencly by locking everything first
@@ -1327,8 +1685,10 @@ mod tests {
 	use util::kvdb::KeyValueDB;
 	use util::hash::*;
 	use util::sha3::Hashable;
+	use rlp::Rlp;
 	use receipt::Receipt;
 	use blockchain::{BlockProvider, BlockChain, Config, ImportRoute};
+	use super::{transaction_list_key, TRANSACTION_LIST_DATA};
 	use tests::helpers::*;
 	use blockchain::generator::{ChainGenerator, ChainIterator, BlockFinalizer};
 	use blockchain::extras::TransactionAddress;
@@ -1536,7 +1896,248 @@ mod tests {
 		assert_eq!(bc.transaction_address(&t1_hash), None);
 	}
 
+	#[test]
+	fn test_reindex_transaction_addresses() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap(),
+		}.sign(&secret(), None);
+
+		let b1 = canon_chain.generate(&mut finalizer).unwrap();
+		let b2 = canon_chain
+			.with_transaction(t1.clone())
+			.generate(&mut finalizer).unwrap();
+
+		let b2_hash = BlockView::new(&b2).header_view().sha3();
+		let t1_hash = t1.hash();
+
+		let db = new_db();
+		{
+			let bc = new_chain(&genesis, db.clone());
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, &b1, vec![]);
+			bc.commit();
+			bc.insert_block(&mut batch, &b2, vec![]);
+			bc.commit();
+			db.write(batch).unwrap();
+		}
+
+		// drop the index entry
+		let mut batch = db.transaction();
+		batch.delete(::db::COL_EXTRA, &::db::Key::<TransactionAddress>::key(&t1_hash));
+		db.write(batch).unwrap();
 
+		let bc = new_chain(&genesis, db.clone());
+		assert_eq!(bc.transaction_address(&t1_hash), None);
+
+		let mut reported = vec![];
+		assert_eq!(bc.reindex_transaction_addresses(0, 2, 4, |n| reported.push(n)), 1);
+		assert_eq!(reported, vec![2]);
+		assert_eq!(bc.transaction_address(&t1_hash), Some(TransactionAddress {
+			block_hash: b2_hash,
+			index: 0,
+		}));
+	}
+
+	#[test]
+	fn test_prune_transaction_addresses() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+
+		let watched = Address::from(0x1234);
+		let transaction = |nonce: u64, action: Action| Transaction {
+			nonce: nonce.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: action,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+
+		let t1 = transaction(0, Action::Create);
+		let t2 = transaction(1, Action::Call(watched));
+		let t3 = transaction(2, Action::Create);
+
+		let blocks = vec![
+			canon_chain.with_transaction(t1.clone()).generate(&mut finalizer).unwrap(),
+			canon_chain.with_transaction(t2.clone()).generate(&mut finalizer).unwrap(),
+			canon_chain.with_transaction(t3.clone()).generate(&mut finalizer).unwrap(),
+			canon_chain.generate(&mut finalizer).unwrap(),
+		];
+
+		let mut config = Config::default();
+		config.tx_index_history = Some(1);
+		config.tx_index_watch.insert(watched);
+
+		let db = new_db();
+		{
+			let bc = BlockChain::new(config.clone(), &genesis, db.clone());
+			let mut batch = db.transaction();
+			for block in &blocks {
+				bc.insert_block(&mut batch, block, vec![]);
+				bc.commit();
+			}
+			db.write(batch).unwrap();
+
+			// blocks 0 to 2 fall out of history, but only one transaction is checked per call.
+			assert_eq!(bc.prune_transaction_addresses(1), 1);
+			assert_eq!(*bc.tx_index_pruned.lock(), 2);
+			assert_eq!(bc.prune_transaction_addresses(1), 0);
+			assert_eq!(*bc.tx_index_pruned.lock(), 3);
+			assert_eq!(bc.transaction_address(&t1.hash()), None);
+			assert!(bc.transaction_address(&t2.hash()).is_some());
+			assert!(bc.transaction_address(&t3.hash()).is_some());
+		}
+
+		// progress is persisted.
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		assert_eq!(bc.prune_transaction_addresses(10), 0);
+		assert!(bc.transaction_address(&t3.hash()).is_some());
+	}
+
+	#[test]
+	fn test_dedup_transaction_lists() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let mut fork_chain = canon_chain.fork(1);
+		let mut fork_finalizer = finalizer.fork();
+
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: "601080600c6000396000f3006000355415600957005b60203560003555".from_hex().unwrap(),
+		}.sign(&secret(), None);
+
+		// the same transaction re-included by a competing block
+		let b1a = canon_chain
+			.with_transaction(t1.clone())
+			.generate(&mut finalizer).unwrap();
+		let b1b = fork_chain
+			.with_transaction(t1.clone())
+			.generate(&mut fork_finalizer).unwrap();
+
+		let b1a_hash = BlockView::new(&b1a).header_view().sha3();
+		let b1b_hash = BlockView::new(&b1b).header_view().sha3();
+		let list_hash = Rlp::new(&b1a).at(1).as_raw().sha3();
+		let data_key = transaction_list_key(&list_hash, TRANSACTION_LIST_DATA);
+
+		let db = new_db();
+		let config = Config { dedup_transactions: true, ..Config::default() };
+		let bc = BlockChain::new(config.clone(), &genesis, db.clone());
+
+		for block in &[&b1a, &b1b] {
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, block, vec![]);
+			db.write(batch).unwrap();
+			bc.commit();
+		}
+
+		assert_eq!(bc.transaction_list_refs(&list_hash), 2);
+
+		// read bodies from the database rather than from the cache
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		assert_eq!(bc.block_body(&b1a_hash).unwrap().into_inner(), BlockChain::block_to_body(&b1a));
+		assert_eq!(bc.block_body(&b1b_hash).unwrap().into_inner(), BlockChain::block_to_body(&b1b));
+
+		let mut batch = db.transaction();
+		bc.remove_block_body(&mut batch, &b1b_hash);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert_eq!(bc.transaction_list_refs(&list_hash), 1);
+		assert!(db.get(::db::COL_BODIES, &data_key).unwrap().is_some());
+
+		let mut batch = db.transaction();
+		bc.remove_block_body(&mut batch, &b1a_hash);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert_eq!(bc.transaction_list_refs(&list_hash), 0);
+		assert!(db.get(::db::COL_BODIES, &data_key).unwrap().is_none());
+	}
+
+
+
+	#[test]
+	fn test_remove_stale_bodies() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let mut fork_chain = canon_chain.fork(1);
+		let mut fork_finalizer = finalizer.fork();
+
+		let b1a = canon_chain.generate(&mut finalizer).unwrap();
+		let b2a = canon_chain.generate(&mut finalizer).unwrap();
+		let b3a = canon_chain.generate(&mut finalizer).unwrap();
+		let b1b = fork_chain.generate(&mut fork_finalizer).unwrap();
+		let b2b = fork_chain.generate(&mut fork_finalizer).unwrap();
+
+		let hash = |block: &[u8]| BlockView::new(block).header_view().sha3();
+		let db = new_db();
+		let config = Config { dedup_transactions: true, ..Config::default() };
+		let bc = BlockChain::new(config, &genesis, db.clone());
+		for block in &[&b1a, &b2a, &b3a, &b1b, &b2b] {
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, block, vec![]);
+			db.write(batch).unwrap();
+			bc.commit();
+		}
+		assert_eq!(bc.best_block_hash(), hash(&b3a));
+
+		let mut batch = db.transaction();
+		assert_eq!(bc.remove_stale_bodies(&mut batch, 1), 2);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert!(bc.block_body(&hash(&b1a)).is_some());
+		assert!(bc.block_body(&hash(&b2a)).is_some());
+		assert!(bc.block_body(&hash(&b1b)).is_none());
+		assert!(bc.block_body(&hash(&b2b)).is_none());
+		assert!(bc.block_header(&hash(&b2b)).is_some());
+	}
+
+	#[test]
+	fn test_keeps_stale_bodies_without_dedup() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let mut fork_chain = canon_chain.fork(1);
+		let mut fork_finalizer = finalizer.fork();
+
+		let b1a = canon_chain.generate(&mut finalizer).unwrap();
+		let b2a = canon_chain.generate(&mut finalizer).unwrap();
+		let b1b = fork_chain.generate(&mut fork_finalizer).unwrap();
+
+		let hash = |block: &[u8]| BlockView::new(block).header_view().sha3();
+		let db = new_db();
+		let bc = BlockChain::new(Config::default(), &genesis, db.clone());
+		for block in &[&b1a, &b2a, &b1b] {
+			let mut batch = db.transaction();
+			bc.insert_block(&mut batch, block, vec![]);
+			db.write(batch).unwrap();
+			bc.commit();
+		}
+
+		let mut batch = db.transaction();
+		assert_eq!(bc.remove_stale_bodies(&mut batch, 1), 0);
+		db.write(batch).unwrap();
+		bc.commit();
+
+		assert!(bc.block_body(&hash(&b1b)).is_some());
+	}
 
 	#[test]
 	fn test_overwriting_transaction_addresses() {
@@ -2110,4 +2711,62 @@ mod tests {
 		assert_eq!(bc.rewind(), Some(genesis_hash.clone()));
 		assert_eq!(bc.rewind(), None);
 	}
+
+	#[test]
+	fn rolls_back_to_last_consistent_block() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let first = canon_chain.generate(&mut finalizer).unwrap();
+		let second = canon_chain.generate(&mut finalizer).unwrap();
+		let third = canon_chain.generate(&mut finalizer).unwrap();
+		let first_hash = BlockView::new(&first).header_view().sha3();
+		let second_hash = BlockView::new(&second).header_view().sha3();
+		let third_hash = BlockView::new(&third).header_view().sha3();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		let mut batch = db.transaction();
+		for block in &[&first, &second, &third] {
+			bc.insert_block(&mut batch, block, vec![]);
+			bc.commit();
+		}
+		db.write(batch).unwrap();
+
+		assert_eq!(bc.last_consistent_block(1, |header| header.number() < 2), None);
+		assert_eq!(bc.last_consistent_block(10, |header| header.number() < 2), Some((1, first_hash.clone())));
+		let mut batch = db.transaction();
+		assert_eq!(bc.rollback_to(&mut batch, 1), vec![second_hash.clone(), third_hash.clone()]);
+		db.write(batch).unwrap();
+
+		let bc = new_chain(&genesis, db.clone());
+		assert_eq!(bc.best_block_number(), 1);
+		assert_eq!(bc.best_block_hash(), first_hash);
+		assert!(!bc.is_known(&second_hash));
+		assert_eq!(bc.block_hash(2), None);
+		assert!(bc.block_details(&first_hash).unwrap().children.is_empty());
+	}
+
+	#[test]
+	fn inserts_receipts_of_known_block() {
+		let mut canon_chain = ChainGenerator::default();
+		let mut finalizer = BlockFinalizer::default();
+		let genesis = canon_chain.generate(&mut finalizer).unwrap();
+		let first = canon_chain.generate(&mut finalizer).unwrap();
+		let first_hash = BlockView::new(&first).header_view().sha3();
+
+		let db = new_db();
+		let bc = new_chain(&genesis, db.clone());
+		let mut batch = db.transaction();
+		bc.insert_block(&mut batch, &first, vec![]);
+		bc.commit();
+		db.write(batch).unwrap();
+
+		let receipts = vec![Receipt::new(Some(H256::from(1)), 21_000.into(), vec![])];
+		let mut batch = db.transaction();
+		bc.insert_block_receipts(&mut batch, &first_hash, receipts.clone());
+		db.write(batch).unwrap();
+
+		assert_eq!(bc.block_receipts(&first_hash).unwrap().receipts, receipts);
+	}
 }
@@ -16,6 +16,9 @@
 
 //! Blockchain configuration.
 
+use std::collections::HashSet;
+use util::Address;
+
 /// Blockchain configuration.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Config {
@@ -25,6 +28,14 @@ pub struct Config {
 	pub max_cache_size: usize,
 	/// Backing db cache_size
 	pub db_cache_size: Option<usize>,
+	/// Store identical transaction lists of block bodies only once.
+	pub dedup_transactions: bool,
+	/// Number of recent blocks whose transactions are all kept in the transaction index.
+	/// Older transactions stay indexed only if sent from or to a watched address.
+	/// `None` keeps the whole index.
+	pub tx_index_history: Option<u64>,
+	/// Addresses whose transactions are never pruned from the transaction index.
+	pub tx_index_watch: HashSet<Address>,
 }
 
 impl Default for Config {
@@ -33,6 +44,9 @@ impl Default for Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
 			db_cache_size: None,
+			dedup_transactions: false,
+			tx_index_history: None,
+			tx_index_watch: HashSet::new(),
 		}
 	}
 }
@@ -106,6 +106,9 @@ impl Builtin {
 
 	/// Whether the builtin is activated at the given block number.
 	pub fn is_active(&self, at: u64) -> bool { at >= self.activate_at }
+
+	/// First block at which the builtin is active.
+	pub fn activate_at(&self) -> u64 { self.activate_at }
 }
 
 impl From<ethjson::spec::Builtin> for Builtin {
@@ -20,7 +20,7 @@
 //! root has. A correct proof implies that the claimed block is identical to the one
 //! we discarded.
 
-use ethcore::ids::BlockId;
+use ids::BlockId;
 use util::{Bytes, H256, U256, HashDB, MemoryDB};
 use util::trie::{self, TrieMut, TrieDBMut, Trie, TrieDB, Recorder};
 use rlp::{RlpStream, UntrustedRlp};
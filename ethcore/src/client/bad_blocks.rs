@@ -0,0 +1,191 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hashes of blocks which failed verification, persisted so that a known-bad fork
+//! isn't downloaded and verified again after a restart.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use linked_hash_map::LinkedHashMap;
+use rlp::{RlpStream, UntrustedRlp, DecoderError};
+use util::H256;
+use util::kvdb::KeyValueDB;
+
+const BAD_BLOCKS_KEY: &'static [u8] = b"BAD_BLOCKS";
+
+fn unix_now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Bounded set of bad block hashes. The oldest entries are forgotten first, and entries
+/// expire after a while, so that a wrongly blacklisted fork is eventually retried.
+pub struct BadBlocks {
+	limit: usize,
+	ttl: u64,
+	// hash -> unix time it was found bad at.
+	hashes: LinkedHashMap<H256, u64>,
+}
+
+impl BadBlocks {
+	/// Create an empty set remembering up to `limit` hashes for `ttl` each.
+	pub fn new(limit: usize, ttl: Duration) -> Self {
+		BadBlocks {
+			limit: limit,
+			ttl: ttl.as_secs(),
+			hashes: LinkedHashMap::new(),
+		}
+	}
+
+	/// Read the set from given column of the database.
+	pub fn load(db: &KeyValueDB, col: Option<u32>, limit: usize, ttl: Duration) -> Self {
+		let mut bad_blocks = BadBlocks::new(limit, ttl);
+		let raw = match db.get(col, BAD_BLOCKS_KEY) {
+			Ok(Some(raw)) => raw,
+			Ok(None) => return bad_blocks,
+			Err(e) => {
+				warn!(target: "client", "Unable to read bad blocks: {}", e);
+				return bad_blocks;
+			}
+		};
+
+		let entries = UntrustedRlp::new(&raw).iter()
+			.map(|entry| -> Result<(H256, u64), DecoderError> { Ok((entry.val_at(0)?, entry.val_at(1)?)) })
+			.collect::<Result<Vec<_>, _>>();
+		match entries {
+			Ok(entries) => for (hash, time) in entries {
+				bad_blocks.insert_at(&[hash], time);
+			},
+			Err(e) => warn!(target: "client", "Ignoring corrupt bad blocks list: {}", e),
+		}
+		bad_blocks
+	}
+
+	/// Write the set to given column of the database.
+	pub fn save(&self, db: &KeyValueDB, col: Option<u32>) {
+		let mut stream = RlpStream::new_list(self.hashes.len());
+		for (hash, time) in self.hashes.iter() {
+			stream.begin_list(2).append(hash).append(time);
+		}
+
+		let mut batch = db.transaction();
+		batch.put_vec(col, BAD_BLOCKS_KEY, stream.out());
+		if let Err(e) = db.write(batch) {
+			warn!(target: "client", "Unable to write bad blocks: {}", e);
+		}
+	}
+
+	/// Whether the block is known to be bad.
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.contains_at(hash, unix_now())
+	}
+
+	/// Add hashes of bad blocks. Returns `true` if any of them wasn't known before.
+	pub fn insert(&mut self, hashes: &[H256]) -> bool {
+		self.insert_at(hashes, unix_now())
+	}
+
+	/// Forget all hashes. Returns `true` if there were any.
+	pub fn clear(&mut self) -> bool {
+		let changed = !self.hashes.is_empty();
+		self.hashes.clear();
+		changed
+	}
+
+	fn contains_at(&self, hash: &H256, now: u64) -> bool {
+		self.hashes.get(hash).map_or(false, |time| now < time.saturating_add(self.ttl))
+	}
+
+	fn insert_at(&mut self, hashes: &[H256], now: u64) -> bool {
+		let mut changed = false;
+		for hash in hashes {
+			if !self.contains_at(hash, now) {
+				changed = true;
+			}
+			// re-inserting refreshes both the time and the position.
+			self.hashes.remove(hash);
+			self.hashes.insert(*hash, now);
+		}
+		// expired entries are the oldest ones.
+		while self.hashes.front().map_or(false, |(_, time)| now >= time.saturating_add(self.ttl)) {
+			self.hashes.pop_front();
+			changed = true;
+		}
+		while self.hashes.len() > self.limit {
+			self.hashes.pop_front();
+		}
+		changed
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::time::Duration;
+	use util::H256;
+	use util::kvdb::in_memory;
+	use super::BadBlocks;
+
+	const TTL: u64 = 3600;
+
+	#[test]
+	fn forgets_oldest_hashes() {
+		let mut bad = BadBlocks::new(2, Duration::from_secs(TTL));
+		assert!(bad.insert(&[H256::from(1), H256::from(2)]));
+		assert!(!bad.insert(&[H256::from(2)]));
+		assert!(bad.insert(&[H256::from(3)]));
+
+		assert!(!bad.contains(&H256::from(1)));
+		assert!(bad.contains(&H256::from(2)));
+		assert!(bad.contains(&H256::from(3)));
+	}
+
+	#[test]
+	fn expires_hashes() {
+		let mut bad = BadBlocks::new(10, Duration::from_secs(TTL));
+		assert!(bad.insert_at(&[H256::from(1)], 1000));
+		assert!(bad.insert_at(&[H256::from(2)], 2000));
+		assert!(bad.contains_at(&H256::from(1), 1000 + TTL - 1));
+		assert!(!bad.contains_at(&H256::from(1), 1000 + TTL));
+
+		// expired entries are dropped on the next insertion.
+		assert!(bad.insert_at(&[H256::from(3)], 1000 + TTL));
+		assert_eq!(bad.hashes.len(), 2);
+		assert!(bad.contains_at(&H256::from(2), 1000 + TTL));
+	}
+
+	#[test]
+	fn clears_hashes() {
+		let mut bad = BadBlocks::new(10, Duration::from_secs(TTL));
+		assert!(!bad.clear());
+		bad.insert(&[H256::from(1)]);
+		assert!(bad.clear());
+		assert!(!bad.contains(&H256::from(1)));
+	}
+
+	#[test]
+	fn persists_hashes() {
+		let db = Arc::new(in_memory(1));
+		let ttl = Duration::from_secs(TTL);
+		assert!(!BadBlocks::load(&*db, Some(0), 10, ttl).contains(&H256::from(1)));
+
+		let mut bad = BadBlocks::new(10, ttl);
+		bad.insert(&[H256::from(1)]);
+		bad.save(&*db, Some(0));
+
+		let loaded = BadBlocks::load(&*db, Some(0), 10, ttl);
+		assert!(loaded.contains(&H256::from(1)));
+		assert!(!loaded.contains(&H256::from(2)));
+	}
+}
@@ -0,0 +1,141 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction calls and gas estimation against a given state.
+
+use util::U256;
+use engines::Engine;
+use env_info::{EnvInfo, EnvOverrides};
+use error::{CallError, ExecutionError};
+use evm::Factory as EvmFactory;
+use executive::{Executive, Executed, TransactOptions};
+use state::{State, CleanupMode};
+use state_db::StateDB;
+use transaction::SignedTransaction;
+use types::call_analytics::CallAnalytics;
+
+/// Execute a transaction against a copy of `state`, without checking its nonce or gas limit.
+/// The sender is given sufficient balance if needed. The block gas limit is maximal
+/// unless overridden by `env`.
+pub fn call(
+	state: &State<StateDB>,
+	mut env_info: EnvInfo,
+	env: &EnvOverrides,
+	engine: &Engine,
+	vm_factory: &EvmFactory,
+	t: &SignedTransaction,
+	analytics: CallAnalytics,
+) -> Result<Executed, CallError> {
+	env_info.gas_limit = U256::max_value();
+	env.apply(&mut env_info);
+
+	// that's just a copy of the state.
+	let mut state = state.clone();
+	let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
+
+	let sender = t.sender();
+	let balance = state.balance(&sender).map_err(|_| CallError::StateCorrupt)?;
+	let needed_balance = t.value + t.gas * t.gas_price;
+	if balance < needed_balance {
+		// give the sender a sufficient balance
+		state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
+			.map_err(|_| CallError::StateCorrupt)?;
+	}
+	let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
+	let mut ret = Executive::new(&mut state, &env_info, engine, vm_factory).transact(t, options)?;
+
+	// TODO gav move this into Executive.
+	if let Some(original) = original_state {
+		ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
+	}
+
+	Ok(ret)
+}
+
+/// Find the lowest gas limit the transaction executes without exception with against `state`.
+pub fn estimate_gas(
+	state: &State<StateDB>,
+	mut env_info: EnvInfo,
+	engine: &Engine,
+	vm_factory: &EvmFactory,
+	t: &SignedTransaction,
+) -> Result<U256, CallError> {
+	const UPPER_CEILING: u64 = 1_000_000_000_000u64;
+	let mut upper = env_info.gas_limit;
+	env_info.gas_limit = UPPER_CEILING.into();
+
+	let sender = t.sender();
+	let balance = state.balance(&sender).map_err(ExecutionError::from)?;
+	let options = TransactOptions { tracing: true, vm_tracing: false, check_nonce: false };
+
+	let cond = |gas| {
+		let mut tx = t.as_unsigned().clone();
+		tx.gas = gas;
+		let tx = tx.fake_sign(sender);
+
+		let mut state = state.clone();
+		let needed_balance = tx.value + tx.gas * tx.gas_price;
+		if balance < needed_balance {
+			// give the sender a sufficient balance
+			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
+				.map_err(ExecutionError::from)?;
+		}
+
+		Ok(Executive::new(&mut state, &env_info, engine, vm_factory)
+			.transact(&tx, options.clone())
+			.map(|r| r.exception.is_none())
+			.unwrap_or(false))
+	};
+
+	if !cond(upper)? {
+		// impossible at block gas limit - try `UPPER_CEILING` instead.
+		// TODO: consider raising limit by powers of two.
+		upper = UPPER_CEILING.into();
+		if !cond(upper)? {
+			trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
+			let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
+			return Err(err.into())
+		}
+	}
+	let lower = t.gas_required(&engine.schedule(&env_info)).into();
+	if cond(lower)? {
+		trace!(target: "estimate_gas", "estimate_gas succeeded with {}", lower);
+		return Ok(lower)
+	}
+
+	/// Find transition point between `lower` and `upper` where `cond` changes from `false` to `true`.
+	/// Returns the lowest value between `lower` and `upper` for which `cond` returns true.
+	/// We assert: `cond(lower) = false`, `cond(upper) = true`
+	fn binary_chop<F, E>(mut lower: U256, mut upper: U256, mut cond: F) -> Result<U256, E>
+		where F: FnMut(U256) -> Result<bool, E>
+	{
+		while upper - lower > 1.into() {
+			let mid = (lower + upper) / 2.into();
+			trace!(target: "estimate_gas", "{} .. {} .. {}", lower, mid, upper);
+			let c = cond(mid)?;
+			match c {
+				true => upper = mid,
+				false => lower = mid,
+			};
+			trace!(target: "estimate_gas", "{} => {} .. {}", c, lower, upper);
+		}
+		Ok(upper)
+	}
+
+	// binary chop to non-excepting call with gas somewhere between 21000 and block gas limit
+	trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
+	binary_chop(lower, upper, cond)
+}
@@ -0,0 +1,118 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Roots of the chain's canonical hash tries, generated as blocks become final and
+//! persisted so they don't have to be rebuilt after a restart.
+//!
+//! After a warp sync the blocks of early CHTs may be missing, so the known roots
+//! don't have to be consecutive.
+
+use std::collections::BTreeMap;
+use rlp::{RlpStream, UntrustedRlp, DecoderError};
+use util::H256;
+use util::kvdb::KeyValueDB;
+
+const CHT_ROOTS_KEY: &'static [u8] = b"CHT_ROOTS";
+
+/// Roots of CHTs, by CHT number.
+#[derive(Default)]
+pub struct ChtRoots {
+	roots: BTreeMap<u64, H256>,
+}
+
+impl ChtRoots {
+	/// Read the roots from given column of the database.
+	pub fn load(db: &KeyValueDB, col: Option<u32>) -> Self {
+		let raw = match db.get(col, CHT_ROOTS_KEY) {
+			Ok(Some(raw)) => raw,
+			Ok(None) => return ChtRoots::default(),
+			Err(e) => {
+				warn!(target: "client", "Unable to read CHT roots: {}", e);
+				return ChtRoots::default();
+			}
+		};
+
+		let decoded: Result<BTreeMap<u64, H256>, DecoderError> = UntrustedRlp::new(&raw).iter()
+			.map(|entry| Ok((entry.val_at(0)?, entry.val_at(1)?)))
+			.collect();
+		match decoded {
+			Ok(roots) => ChtRoots { roots: roots },
+			Err(e) => {
+				warn!(target: "client", "Ignoring corrupt CHT roots: {}", e);
+				ChtRoots::default()
+			}
+		}
+	}
+
+	/// Write the roots to given column of the database.
+	pub fn save(&self, db: &KeyValueDB, col: Option<u32>) {
+		let mut stream = RlpStream::new_list(self.roots.len());
+		for (number, root) in &self.roots {
+			stream.begin_list(2).append(number).append(root);
+		}
+
+		let mut batch = db.transaction();
+		batch.put_vec(col, CHT_ROOTS_KEY, stream.out());
+		if let Err(e) = db.write(batch) {
+			warn!(target: "client", "Unable to write CHT roots: {}", e);
+		}
+	}
+
+	/// All known roots, by CHT number.
+	pub fn roots(&self) -> &BTreeMap<u64, H256> {
+		&self.roots
+	}
+
+	/// Root of the given CHT, if known.
+	pub fn root(&self, number: u64) -> Option<H256> {
+		self.roots.get(&number).cloned()
+	}
+
+	/// Add the root of a CHT.
+	pub fn insert(&mut self, number: u64, root: H256) {
+		self.roots.insert(number, root);
+	}
+
+	/// Forget all roots.
+	pub fn clear(&mut self) {
+		self.roots.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use util::H256;
+	use util::kvdb::in_memory;
+	use super::ChtRoots;
+
+	#[test]
+	fn persists_roots() {
+		let db = Arc::new(in_memory(1));
+		assert!(ChtRoots::load(&*db, Some(0)).roots().is_empty());
+
+		let mut roots = ChtRoots::default();
+		roots.insert(3, H256::from(1));
+		roots.insert(5, H256::from(2));
+		roots.save(&*db, Some(0));
+
+		let loaded = ChtRoots::load(&*db, Some(0));
+		assert_eq!(loaded.roots().len(), 2);
+		assert_eq!(loaded.root(3), Some(H256::from(1)));
+		assert_eq!(loaded.root(4), None);
+		assert_eq!(loaded.root(5), Some(H256::from(2)));
+	}
+}
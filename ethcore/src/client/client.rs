@@ -18,14 +18,15 @@ use std::collections::{HashSet, HashMap, BTreeMap, VecDeque};
 use std::str::FromStr;
 use std::sync::{Arc, Weak};
 use std::fmt;
+use std::thread;
 use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering as AtomicOrdering};
-use std::time::{Instant};
-use time::precise_time_ns;
+use std::time::{Instant, Duration};
+use time::{precise_time_ns, get_time};
 
 // util
 use util::{Bytes, PerfTimer, Itertools, Mutex, RwLock, MutexGuard, Hashable};
 use util::{journaldb, DBValue, TrieFactory, Trie};
-use util::{U256, H256, Address, H2048, Uint};
+use util::{U256, H256, Address, H2048, Uint, OutOfBounds, Mismatch, ordered_trie_root};
 use util::trie::TrieSpec;
 use util::kvdb::*;
 
@@ -33,15 +34,15 @@ use util::kvdb::*;
 use io::*;
 use views::BlockView;
 use error::{ImportError, ExecutionError, CallError, BlockError, ImportResult, Error as EthcoreError};
-use header::BlockNumber;
-use state::{self, State, CleanupMode};
+use header::{Header, BlockNumber};
+use state::{self, State};
 use spec::Spec;
 use basic_types::Seal;
 use engines::Engine;
 use service::ClientIoMessage;
 use env_info::LastHashes;
 use verification;
-use verification::{PreverifiedBlock, Verifier};
+use verification::{PreverifiedBlock, Verifier, SealPolicy, MAX_TIMESTAMP_DRIFT};
 use block::*;
 use transaction::{LocalizedTransaction, UnverifiedTransaction, SignedTransaction, Transaction, PendingTransaction, Action};
 use blockchain::extras::TransactionAddress;
@@ -49,25 +50,37 @@ use types::filter::Filter;
 use types::mode::Mode as IpcMode;
 use log_entry::LocalizedLogEntry;
 use verification::queue::BlockQueue;
+use verification::queue::kind::BlockLike;
+use verification::queue::kind::blocks::Unverified;
 use blockchain::{BlockChain, BlockProvider, TreeRoute, ImportRoute};
 use client::{
 	BlockId, TransactionId, UncleId, TraceId, ClientConfig, BlockChainClient,
 	MiningBlockChainClient, EngineClient, TraceFilter, CallAnalytics, BlockImportError, Mode,
-	ChainNotify, PruningInfo,
+	ChainNotify, PruningInfo, ChainSpecInfo, DbFlushMarker, CompactionStatus, DbColumnStats,
 };
 use client::Error as ClientError;
-use env_info::EnvInfo;
+use client::call;
+use client::uncle_pool::UnclePool;
+use client::bad_blocks::BadBlocks;
+use client::cht_roots::ChtRoots;
+use client::future_blocks::FutureBlocks;
+use client::replay::{ReplayBundle, ReplayReport};
+use cht;
+use env_info::{EnvInfo, EnvOverrides};
 use executive::{Executive, Executed, TransactOptions, contract_address};
 use receipt::{Receipt, LocalizedReceipt};
 use trace::{TraceDB, ImportRequest as TraceImportRequest, LocalizedTrace, Database as TraceDatabase};
 use trace;
 use trace::FlatTransactionTraces;
 use evm::{Factory as EvmFactory, Schedule};
-use miner::{Miner, MinerService, TransactionImportResult};
+use miner::{Miner, MinerService, TransactionImportResult, LocalTransactionStatus};
 use snapshot::{self, io as snapshot_io};
 use factory::Factories;
 use rlp::UntrustedRlp;
 use state_db::StateDB;
+use pod_account::PodAccount;
+use pod_state::{self, PodState};
+use types::state_diff::StateDiff;
 use rand::OsRng;
 use client::registry::Registry;
 use encoded;
@@ -81,6 +94,20 @@ pub use verification::queue::QueueInfo as BlockQueueInfo;
 const MAX_TX_QUEUE_SIZE: usize = 4096;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// number of storage keys listed at a time when building state diffs.
+const STATE_DIFF_STORAGE_PAGE: u64 = 1024;
+// number of known bad block hashes remembered across restarts.
+const MAX_BAD_BLOCKS: usize = 1024;
+// time a bad block is remembered for, after which its fork may be retried.
+const BAD_BLOCKS_TTL_SECS: u64 = 24 * 60 * 60;
+// number of blocks with future timestamps kept for later import.
+const MAX_FUTURE_BLOCKS: usize = 64;
+/// Number of blocks the last block of a CHT must be buried under before its root is generated.
+const CHT_FINALITY: u64 = 2048;
+/// Maximal number of CHT roots generated per tick, so catching up doesn't stall the client.
+const MAX_CHT_ROOTS_PER_TICK: usize = 16;
+/// Maximal number of transactions checked for watched senders per tick when pruning the transaction index.
+const MAX_TX_INDEX_PRUNE_PER_TICK: usize = 1000;
 
 impl fmt::Display for BlockChainInfo {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -128,10 +155,12 @@ impl SleepState {
 /// Call `import_block()` to import a block asynchronously; `flush_queue()` flushes the queue.
 pub struct Client {
 	enabled: AtomicBool,
+	shutting_down: AtomicBool,
 	mode: Mutex<Mode>,
 	chain: RwLock<Arc<BlockChain>>,
 	tracedb: RwLock<TraceDB<BlockChain>>,
 	engine: Arc<Engine>,
+	spec_hash: H256,
 	config: ClientConfig,
 	pruning: journaldb::Algorithm,
 	db: RwLock<Arc<KeyValueDB>>,
@@ -154,6 +183,13 @@ pub struct Client {
 	on_user_defaults_change: Mutex<Option<Box<FnMut(Option<Mode>) + 'static + Send>>>,
 	registrar: Mutex<Option<Registry>>,
 	exit_handler: Mutex<Option<Box<Fn(bool, Option<String>) + 'static + Send>>>,
+	flush_marker: RwLock<DbFlushMarker>,
+	compaction: RwLock<CompactionStatus>,
+	last_compaction: Mutex<Instant>,
+	uncle_pool: Mutex<UnclePool>,
+	bad_blocks: RwLock<BadBlocks>,
+	future_blocks: Mutex<FutureBlocks<Unverified>>,
+	cht_roots: RwLock<ChtRoots>,
 }
 
 impl Client {
@@ -182,6 +218,9 @@ impl Client {
 		let journal_db = journaldb::new(db.clone(), config.pruning, ::db::COL_STATE);
 		let mut state_db = StateDB::new(journal_db, config.state_cache_size);
 		if state_db.journal_db().is_empty() {
+			if config.read_only {
+				return Err(ClientError::Database("Database is empty and can't be initialized in read-only mode.".into()));
+			}
 			// Sets the correct state root.
 			state_db = spec.ensure_db_good(state_db, &factories)?;
 			let mut batch = DBTransaction::new();
@@ -190,8 +229,7 @@ impl Client {
 		}
 
 		let gb = spec.genesis_block();
-		let chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
-		let tracedb = RwLock::new(TraceDB::new(config.tracing.clone(), db.clone(), chain.clone()));
+		let mut chain = Arc::new(BlockChain::new(config.blockchain.clone(), &gb, db.clone()));
 
 		trace!("Cleanup journal: DB Earliest = {:?}, Latest = {:?}", state_db.journal_db().earliest_era(), state_db.journal_db().latest_era());
 
@@ -204,26 +242,50 @@ impl Client {
 			config.history
 		};
 
-		if !chain.block_header(&chain.best_block_hash()).map_or(true, |h| state_db.journal_db().contains(h.state_root())) {
+		if !chain.block_header(&chain.best_block_hash()).map_or(false, |h| state_db.journal_db().contains(h.state_root())) {
 			warn!("State root not found for block #{} ({})", chain.best_block_number(), chain.best_block_hash().hex());
+			if !config.read_only {
+				chain = Client::recover_chain(&config, &gb, &chain, &state_db, &db, history)?;
+			}
 		}
 
+		let tracedb = RwLock::new(TraceDB::new(config.tracing.clone(), db.clone(), chain.clone()));
+
 		let engine = spec.engine.clone();
 
-		let block_queue = BlockQueue::new(config.queue.clone(), engine.clone(), message_channel.clone(), config.verifier_type.verifying_seal());
+		let seal_policy = match config.verifier_type.verifying_seal() {
+			true => config.seal_policy.clone(),
+			false => SealPolicy::None,
+		};
+		let block_queue = BlockQueue::new(config.queue.clone(), engine.clone(), message_channel.clone(), seal_policy);
 		let panic_handler = PanicHandler::new_in_arc();
 		panic_handler.forward_from(&block_queue);
 
 		let awake = match config.mode { Mode::Dark(..) | Mode::Off => false, _ => true };
+		let flush_marker = DbFlushMarker {
+			sequence: 0,
+			best_block_number: chain.best_block_number(),
+			best_block_hash: chain.best_block_hash(),
+		};
+
+		let bad_blocks_ttl = Duration::from_secs(BAD_BLOCKS_TTL_SECS);
+		let mut bad_blocks = BadBlocks::load(&*db, ::db::COL_NODE_INFO, MAX_BAD_BLOCKS, bad_blocks_ttl);
+		if config.clear_bad_blocks && bad_blocks.clear() && !config.read_only {
+			bad_blocks.save(&*db, ::db::COL_NODE_INFO);
+		}
+		let cht_roots = ChtRoots::load(&*db, ::db::COL_NODE_INFO);
+		let future_block_gap = config.future_block_gap;
 
 		let client = Arc::new(Client {
 			enabled: AtomicBool::new(true),
+			shutting_down: AtomicBool::new(false),
 			sleep_state: Mutex::new(SleepState::new(awake)),
 			liveness: AtomicBool::new(awake),
 			mode: Mutex::new(config.mode.clone()),
 			chain: RwLock::new(chain),
 			tracedb: tracedb,
 			engine: engine,
+			spec_hash: spec.hash,
 			pruning: config.pruning.clone(),
 			verifier: verification::new(config.verifier_type.clone()),
 			config: config,
@@ -244,9 +306,16 @@ impl Client {
 			on_user_defaults_change: Mutex::new(None),
 			registrar: Mutex::new(None),
 			exit_handler: Mutex::new(None),
+			flush_marker: RwLock::new(flush_marker),
+			compaction: RwLock::new(CompactionStatus::default()),
+			last_compaction: Mutex::new(Instant::now()),
+			uncle_pool: Mutex::new(UnclePool::new(spec.engine.maximum_uncle_age())),
+			bad_blocks: RwLock::new(bad_blocks),
+			future_blocks: Mutex::new(FutureBlocks::new(future_block_gap, MAX_FUTURE_BLOCKS)),
+			cht_roots: RwLock::new(cht_roots),
 		});
 
-		{
+		if !client.config.read_only {
 			let state_db = client.state_db.lock().boxed_clone();
 			let chain = client.chain.read();
 			client.prune_ancient(state_db, &chain)?;
@@ -261,6 +330,46 @@ impl Client {
 		Ok(client)
 	}
 
+	/// Roll the chain head back to the most recent block with its data and state intact, at most
+	/// `max_depth` blocks back, after the database was found inconsistent (e.g. following a repair).
+	/// Returns the reopened chain.
+	fn recover_chain(config: &ClientConfig, genesis: &[u8], chain: &Arc<BlockChain>, state_db: &StateDB, db: &Arc<KeyValueDB>, max_depth: u64) -> Result<Arc<BlockChain>, ClientError> {
+		let best = chain.best_block_number();
+		let (number, hash) = chain.last_consistent_block(max_depth, |header| state_db.journal_db().contains(header.state_root()))
+			.ok_or_else(|| ClientError::Database(format!("No block with intact data and state found within {} blocks of #{}. \
+				Remove the database with `parity db kill` and sync again.", max_depth, best)))?;
+
+		// traces of the dropped blocks go in the same batch, so they can't outlive the blocks.
+		let tracedb = TraceDB::new(config.tracing.clone(), db.clone(), chain.clone());
+		let mut batch = DBTransaction::new();
+		let dropped = chain.rollback_to(&mut batch, number);
+		tracedb.remove_traces(&mut batch, number + 1, best, &dropped);
+		db.write(batch).map_err(ClientError::Database)?;
+
+		warn!("Rolled the chain head back from #{} to #{} ({}), the last block with intact data and state.", best, number, hash.hex());
+		warn!("Blocks #{}..#{} ({} imported) have to be synced again.", number + 1, best, dropped.len());
+		Ok(Arc::new(BlockChain::new(config.blockchain.clone(), genesis, db.clone())))
+	}
+
+	/// Reopen the database to see the changes flushed by another process since it was opened.
+	/// Used by read-only clients sharing the database with a running writer.
+	pub fn reopen_db(&self) -> Result<(), EthcoreError> {
+		trace!(target: "client", "Reopening client database");
+
+		let _import_lock = self.import_lock.lock();
+		let mut state_db = self.state_db.lock();
+		let mut chain = self.chain.write();
+		let mut tracedb = self.tracedb.write();
+		let db = self.db.write();
+		db.reopen()?;
+
+		let cache_size = state_db.cache_size();
+		*state_db = StateDB::new(journaldb::new(db.clone(), self.pruning, ::db::COL_STATE), cache_size);
+		*chain = Arc::new(BlockChain::new(self.config.blockchain.clone(), &[], db.clone()));
+		*tracedb = TraceDB::new(self.config.tracing.clone(), db.clone(), chain.clone());
+		Ok(())
+	}
+
 	/// Wakes up client if it's a sleep.
 	pub fn keep_alive(&self) {
 		let should_wake = match *self.mode.lock() {
@@ -306,6 +415,12 @@ impl Client {
 		*self.on_user_defaults_change.lock() = Some(Box::new(f));
 	}
 
+	/// Stop accepting blocks and transactions ahead of shutdown, as if the client was read-only.
+	/// Blocks already in the import queue are still imported by `flush_queue`.
+	pub fn begin_shutdown(&self) {
+		self.shutting_down.store(true, AtomicOrdering::SeqCst);
+	}
+
 	/// Flush the block import queue.
 	pub fn flush_queue(&self) {
 		self.block_queue.flush();
@@ -314,6 +429,22 @@ impl Client {
 		}
 	}
 
+	/// Import the queued blocks until the queue is empty or `deadline` passes, dropping the
+	/// blocks left in the queue then. Returns `false` if any blocks were dropped.
+	pub fn flush_queue_until(&self, deadline: Instant) -> bool {
+		while !self.block_queue.queue_info().is_empty() {
+			if Instant::now() >= deadline {
+				self.block_queue.clear();
+				return false;
+			}
+			if self.import_verified_blocks() == 0 {
+				// wait for the verifiers.
+				thread::sleep(Duration::from_millis(10));
+			}
+		}
+		true
+	}
+
 	/// The env info as of the best block.
 	pub fn latest_env_info(&self) -> EnvInfo {
 		self.env_info(BlockId::Latest).expect("Best block header always stored; qed")
@@ -361,7 +492,9 @@ impl Client {
 		Arc::new(last_hashes)
 	}
 
-	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, ()> {
+	/// Fails with the error proving the block invalid, if any; ancient blocks and blocks
+	/// whose parent is missing fail without one.
+	fn check_and_close_block(&self, block: &PreverifiedBlock) -> Result<LockedBlock, Option<EthcoreError>> {
 		let engine = &*self.engine;
 		let header = &block.header;
 
@@ -370,14 +503,14 @@ impl Client {
 		let best_block_number = chain.best_block_number();
 		if best_block_number >= self.history && header.number() <= best_block_number - self.history {
 			warn!(target: "client", "Block import failed for #{} ({})\nBlock is ancient (current best block: #{}).", header.number(), header.hash(), best_block_number);
-			return Err(());
+			return Err(None);
 		}
 
 		// Verify Block Family
 		let verify_family_result = self.verifier.verify_block_family(header, &block.bytes, engine, &**chain);
 		if let Err(e) = verify_family_result {
 			warn!(target: "client", "Stage 3 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-			return Err(());
+			return Err(Some(e));
 		};
 
 		// Check if Parent is in chain
@@ -390,18 +523,43 @@ impl Client {
 			let enact_result = enact_verified(block, engine, self.tracedb.read().tracing_enabled(), db, &parent, last_hashes, self.factories.clone());
 			let locked_block = enact_result.map_err(|e| {
 				warn!(target: "client", "Block import failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
+				self.write_replay_bundle(block, &parent, format!("{:?}", e));
+				Some(e)
 			})?;
 
 			// Final Verification
 			if let Err(e) = self.verifier.verify_block_final(header, locked_block.block().header(), self.engine().params().validate_receipts) {
 				warn!(target: "client", "Stage 4 block verification failed for #{} ({})\nError: {:?}", header.number(), header.hash(), e);
-				return Err(());
+				self.write_replay_bundle(block, &parent, format!("{:?}", e));
+				return Err(Some(e));
 			}
 
 			Ok(locked_block)
 		} else {
 			warn!(target: "client", "Block import failed for #{} ({}): Parent not found ({}) ", header.number(), header.hash(), header.parent_hash());
-			Err(())
+			Err(None)
+		}
+	}
+
+	// Keep the inputs of a block failing execution, if configured, so the failure can be replayed.
+	fn write_replay_bundle(&self, block: &PreverifiedBlock, parent: &Header, error: String) {
+		let dir = match self.config.replay_bundle_dir {
+			Some(ref dir) => dir,
+			None => return,
+		};
+
+		let bundle = ReplayBundle {
+			version: ::util::misc::version(),
+			genesis_hash: self.chain.read().genesis_hash(),
+			spec_hash: self.spec_hash,
+			parent_hash: parent.hash(),
+			parent_state_root: parent.state_root().clone(),
+			block: block.bytes.clone(),
+			error: error,
+		};
+		match bundle.write(dir) {
+			Ok(path) => warn!(target: "client", "Replay bundle of block #{} written to {}", block.header.number(), path.display()),
+			Err(e) => warn!(target: "client", "Unable to write replay bundle of block #{}: {}", block.header.number(), e),
 		}
 	}
 
@@ -443,6 +601,8 @@ impl Client {
 		let (imported_blocks, import_results, invalid_blocks, imported, proposed_blocks, duration, is_empty) = {
 			let mut imported_blocks = Vec::with_capacity(max_blocks_to_import);
 			let mut invalid_blocks = HashSet::new();
+			// blocks which failed verification themselves, rather than because of their parent.
+			let mut failed_blocks = Vec::new();
 			let mut proposed_blocks = Vec::with_capacity(max_blocks_to_import);
 			let mut import_results = Vec::with_capacity(max_blocks_to_import);
 
@@ -461,8 +621,8 @@ impl Client {
 					invalid_blocks.insert(header.hash());
 					continue;
 				}
-				if let Ok(closed_block) = self.check_and_close_block(&block) {
-					if self.engine.is_proposal(&block.header) {
+				match self.check_and_close_block(&block) {
+					Ok(closed_block) => if self.engine.is_proposal(&block.header) {
 						self.block_queue.mark_as_good(&[header.hash()]);
 						proposed_blocks.push(block.bytes);
 					} else {
@@ -472,9 +632,13 @@ impl Client {
 						import_results.push(route);
 
 						self.report.write().accrue_block(&block);
-					}
-				} else {
-					invalid_blocks.insert(header.hash());
+					},
+					Err(e) => {
+						if e.map_or(false, |e| e.is_invalid_block()) {
+							failed_blocks.push(header.hash());
+						}
+						invalid_blocks.insert(header.hash());
+					},
 				}
 			}
 
@@ -483,6 +647,7 @@ impl Client {
 
 			if !invalid_blocks.is_empty() {
 				self.block_queue.mark_as_bad(&invalid_blocks);
+				self.note_bad_blocks(&failed_blocks);
 			}
 			let is_empty = self.block_queue.mark_as_good(&imported_blocks);
 			let duration_ns = precise_time_ns() - start;
@@ -511,7 +676,7 @@ impl Client {
 			}
 		}
 
-		self.db.read().flush().expect("DB flush failed.");
+		self.flush_db();
 		imported
 	}
 
@@ -546,10 +711,28 @@ impl Client {
 			self.db.read().write_buffered(batch);
 			chain.commit();
 		}
-		self.db.read().flush().expect("DB flush failed.");
+		self.flush_db();
 		Ok(hash)
 	}
 
+	// Remember blocks which failed verification, so they're refused even after a restart.
+	fn note_bad_blocks(&self, hashes: &[H256]) {
+		let mut bad_blocks = self.bad_blocks.write();
+		if bad_blocks.insert(hashes) && !self.config.read_only {
+			bad_blocks.save(&**self.db.read(), ::db::COL_NODE_INFO);
+		}
+	}
+
+	/// Write buffered changes, e.g. journaled state and block extras, to disk and update the flush marker.
+	pub fn flush_db(&self) {
+		self.db.read().flush().expect("DB flush failed.");
+		let chain = self.chain.read();
+		let mut marker = self.flush_marker.write();
+		marker.sequence += 1;
+		marker.best_block_number = chain.best_block_number();
+		marker.best_block_hash = chain.best_block_hash();
+	}
+
 	fn commit_block<B>(&self, block: B, hash: &H256, block_data: &[u8]) -> ImportRoute where B: IsBlock + Drain {
 		let number = block.header().number();
 		let parent = block.header().parent_hash().clone();
@@ -586,6 +769,7 @@ impl Client {
 		self.db.read().write_buffered(batch);
 		chain.commit();
 		self.update_last_hashes(&parent, hash);
+		self.update_uncle_pool(&chain, &route);
 
 		if let Err(e) = self.prune_ancient(state, &chain) {
 			warn!("Failed to prune ancient state data: {}", e);
@@ -594,6 +778,62 @@ impl Client {
 		route
 	}
 
+	// keep side-chain blocks around as uncle candidates and drop the ones which became canonical.
+	fn update_uncle_pool(&self, chain: &BlockChain, route: &ImportRoute) {
+		let mut pool = self.uncle_pool.lock();
+		for hash in route.retracted.iter().chain(route.omitted.iter()) {
+			if let Some(header) = chain.block_header(hash) {
+				pool.insert(header);
+			}
+		}
+		for hash in &route.enacted {
+			if let Some(number) = chain.block_number(hash) {
+				pool.remove(number, hash);
+			}
+		}
+		pool.prune(chain.best_block_number());
+	}
+
+	/// Uncles for a child of the given block: pooled side-chain headers passing the checks done on
+	/// import, followed by any other candidates found in the database.
+	fn uncle_candidates(&self, chain: &BlockChain, parent: &H256) -> Vec<Header> {
+		let max_age = self.engine.maximum_uncle_age();
+		if self.engine.maximum_uncle_count() == 0 {
+			return Vec::new();
+		}
+		let (number, excluded) = match (chain.block_number(parent), chain.excluded_uncle_hashes(parent, max_age)) {
+			(Some(number), Some(excluded)) => (number + 1, excluded),
+			_ => return Vec::new(),
+		};
+
+		let mut uncles: Vec<Header> = self.uncle_pool.lock().candidates(number).into_iter()
+			.filter(|uncle| !excluded.contains(&uncle.hash()) && self.is_valid_uncle(chain, uncle))
+			.collect();
+		let pooled: HashSet<H256> = uncles.iter().map(Header::hash).collect();
+		uncles.extend(chain.find_uncle_headers(parent, max_age)
+			.unwrap_or_else(Vec::new)
+			.into_iter()
+			.filter(|uncle| !pooled.contains(&uncle.hash())));
+		uncles
+	}
+
+	// an uncle has to branch off the canonical chain and pass the engine's family checks.
+	fn is_valid_uncle(&self, chain: &BlockChain, uncle: &Header) -> bool {
+		if uncle.number() == 0 || chain.block_hash(uncle.number() - 1).as_ref() != Some(uncle.parent_hash()) {
+			return false;
+		}
+		match chain.block_header(uncle.parent_hash()) {
+			Some(parent) => match self.engine.verify_block_family(uncle, &parent, None) {
+				Ok(()) => true,
+				Err(e) => {
+					debug!(target: "client", "Skipping invalid uncle candidate {}: {}", uncle.hash(), e);
+					false
+				},
+			},
+			None => false,
+		}
+	}
+
 	// prune ancient states until below the memory limit or only the minimum amount remain.
 	fn prune_ancient(&self, mut state_db: StateDB, chain: &BlockChain) -> Result<(), ClientError> {
 		let number = match state_db.journal_db().latest_era() {
@@ -615,8 +855,17 @@ impl Client {
 						Some(ancient_hash) => {
 							let mut batch = DBTransaction::new();
 							state_db.mark_canonical(&mut batch, era, &ancient_hash)?;
+							// side-chain bodies are only dropped with deduplicated transaction lists.
+							let stale = match self.config.blockchain.dedup_transactions {
+								true => chain.remove_stale_bodies(&mut batch, era),
+								false => 0,
+							};
 							self.db.read().write_buffered(batch);
+							chain.commit();
 							state_db.journal_db().flush();
+							if stale > 0 {
+								trace!(target: "client", "Removed {} side-chain block bodies at ancient era {}", stale, era);
+							}
 						}
 						None =>
 							debug!(target: "client", "Missing expected hash for block {}", era),
@@ -694,6 +943,53 @@ impl Client {
 		})
 	}
 
+	/// Collect the given accounts at block `id` into a pod state. Storage is
+	/// only included if fat DB is in operation.
+	fn pod_state_at(&self, accounts: &[Address], id: BlockId) -> Option<PodState> {
+		let state = match self.state_at(id.clone()) {
+			Some(state) => state,
+			None => return None,
+		};
+
+		let mut pod = BTreeMap::new();
+		for address in accounts {
+			match state.exists(address) {
+				Ok(true) => {},
+				Ok(false) => continue,
+				Err(_) => return None,
+			}
+
+			let mut storage = BTreeMap::new();
+			let mut after = None;
+			while let Some(keys) = self.list_storage(id.clone(), address, after.as_ref(), STATE_DIFF_STORAGE_PAGE) {
+				for key in &keys {
+					match state.storage_at(address, key) {
+						Ok(value) => { storage.insert(key.clone(), value); },
+						Err(_) => return None,
+					}
+				}
+
+				if (keys.len() as u64) < STATE_DIFF_STORAGE_PAGE {
+					break;
+				}
+				after = keys.last().cloned();
+			}
+
+			let account = match (state.balance(address), state.nonce(address), state.code(address)) {
+				(Ok(balance), Ok(nonce), Ok(code)) => PodAccount {
+					balance: balance,
+					nonce: nonce,
+					code: Some(code.map_or_else(Vec::new, |code| (*code).clone())),
+					storage: storage,
+				},
+				_ => return None,
+			};
+			pod.insert(address.clone(), account);
+		}
+
+		Some(PodState::from(pod))
+	}
+
 	/// Attempt to get a copy of a specific block's beginning state.
 	///
 	/// This will not fail if given BlockId::Latest.
@@ -735,8 +1031,133 @@ impl Client {
 	/// Tick the client.
 	// TODO: manage by real events.
 	pub fn tick(&self) {
+		self.note_bad_blocks(&self.block_queue.take_failed());
+		self.import_future_blocks();
 		self.check_garbage();
 		self.check_snooze();
+		self.check_compaction();
+		self.prune_transaction_index();
+		self.advance_cht_roots();
+	}
+
+	// Generate roots of CHTs whose blocks have become final, a few at a time.
+	// CHTs whose blocks are not available yet are skipped and generated once they are.
+	fn advance_cht_roots(&self) {
+		if self.config.read_only {
+			return;
+		}
+
+		let final_chts = self.final_cht_count();
+		let mut roots = self.cht_roots.write();
+		let mut generated = 0;
+		for number in 0..final_chts {
+			if generated == MAX_CHT_ROOTS_PER_TICK {
+				break;
+			}
+			if roots.root(number).is_some() {
+				continue;
+			}
+			if let Some(root) = self.compute_cht_root(number) {
+				roots.insert(number, root);
+				generated += 1;
+			}
+		}
+
+		if generated > 0 {
+			debug!(target: "client", "Generated {} CHT roots, {} in total", generated, roots.roots().len());
+			roots.save(&**self.db.read(), ::db::COL_NODE_INFO);
+		}
+	}
+
+	// Number of CHTs whose blocks are all final.
+	fn final_cht_count(&self) -> u64 {
+		let best = self.chain.read().best_block_number();
+		match best.checked_sub(CHT_FINALITY) {
+			Some(last_final) => last_final / cht::SIZE,
+			None => 0,
+		}
+	}
+
+	// Root of the given CHT if all its blocks are known.
+	fn compute_cht_root(&self, number: u64) -> Option<H256> {
+		let chain = self.chain.read();
+		let last = cht::start_number(number + 1) - 1;
+
+		// ancient blocks may still be missing after a warp sync.
+		let entries: Option<Vec<_>> = (cht::start_number(number)..last + 1)
+			.map(|n| chain.block_hash(n).and_then(|hash| chain.block_details(&hash).map(|details| (hash, details.total_difficulty))))
+			.collect();
+		entries.and_then(|entries| cht::compute_root(number, entries))
+	}
+
+	/// Discard the generated CHT roots and generate them again for all final blocks,
+	/// reporting the number of each generated CHT. Returns the number of roots.
+	pub fn rebuild_cht_roots<F: FnMut(u64)>(&self, mut progress: F) -> usize {
+		let final_chts = self.final_cht_count();
+		let mut roots = self.cht_roots.write();
+		roots.clear();
+		for number in 0..final_chts {
+			if let Some(root) = self.compute_cht_root(number) {
+				progress(number);
+				roots.insert(number, root);
+			}
+		}
+		roots.save(&**self.db.read(), ::db::COL_NODE_INFO);
+		roots.roots().len()
+	}
+
+	fn prune_transaction_index(&self) {
+		let pruned = self.chain.read().prune_transaction_addresses(MAX_TX_INDEX_PRUNE_PER_TICK);
+		if pruned > 0 {
+			debug!(target: "client", "Pruned {} old transaction index entries", pruned);
+		}
+	}
+
+	/// Import blocks kept back because of their timestamps once their time has come.
+	fn import_future_blocks(&self) {
+		let due = self.future_blocks.lock().take_due(get_time().sec as u64 + MAX_TIMESTAMP_DRIFT);
+		for block in due {
+			let hash = block.hash();
+			if let Err(e) = self.import_unverified(block) {
+				debug!(target: "client", "Failed to import future block {}: {:?}", hash, e);
+			}
+		}
+	}
+
+	fn import_unverified(&self, unverified: Unverified) -> Result<H256, BlockImportError> {
+		{
+			if self.chain.read().is_known(&unverified.hash()) {
+				return Err(BlockImportError::Import(ImportError::AlreadyInChain));
+			}
+			if self.bad_blocks.read().contains(&unverified.hash()) {
+				return Err(BlockImportError::Import(ImportError::KnownBad));
+			}
+			if self.future_blocks.lock().contains(&unverified.hash()) {
+				return Err(BlockImportError::Import(ImportError::AlreadyQueued));
+			}
+			match self.block_status(BlockId::Hash(unverified.parent_hash())) {
+				BlockStatus::Unknown => return Err(BlockImportError::Block(BlockError::UnknownParent(unverified.parent_hash()))),
+				BlockStatus::Bad => return Err(BlockImportError::Import(ImportError::KnownBad)),
+				_ => {},
+			}
+		}
+
+		let max_timestamp = get_time().sec as u64 + MAX_TIMESTAMP_DRIFT;
+		let timestamp = unverified.header().timestamp();
+		if timestamp > max_timestamp {
+			let hash = unverified.hash();
+			if self.future_blocks.lock().insert(hash, timestamp, max_timestamp, unverified) {
+				debug!(target: "client", "Block {} is {}s ahead of time, importing later", hash, timestamp - max_timestamp);
+				return Ok(hash);
+			}
+			return Err(BlockImportError::Block(BlockError::InvalidTimestamp(OutOfBounds { max: Some(max_timestamp), min: None, found: timestamp })));
+		}
+
+		let result = self.block_queue.import(unverified);
+		if result.is_err() {
+			self.note_bad_blocks(&self.block_queue.take_failed());
+		}
+		Ok(result?)
 	}
 
 	fn check_garbage(&self) {
@@ -779,6 +1200,80 @@ impl Client {
 		}
 	}
 
+	fn check_compaction(&self) {
+		let interval = match self.config.db_compaction_interval {
+			Some(interval) => interval,
+			None => return,
+		};
+		if Instant::now() < *self.last_compaction.lock() + interval {
+			return;
+		}
+		// compaction competes with block import for disk I/O; wait for the queue to drain.
+		if !self.block_queue.queue_info().is_empty() {
+			return;
+		}
+		if let Err(e) = self.start_compaction() {
+			debug!(target: "client", "Scheduled database compaction not started: {}", e);
+		}
+		*self.last_compaction.lock() = Instant::now();
+	}
+
+	fn start_compaction(&self) -> Result<(), String> {
+		if self.config.read_only {
+			return Err("Database is opened read-only".into());
+		}
+		{
+			let mut status = self.compaction.write();
+			if status.in_progress {
+				return Err("Database compaction is already in progress".into());
+			}
+			status.in_progress = true;
+			status.columns_compacted = 0;
+			status.columns_total = ::db::NUM_COLUMNS.unwrap_or(0);
+		}
+		self.io_channel.lock().send(ClientIoMessage::CompactDatabase).map_err(|e| {
+			self.compaction_aborted();
+			format!("{:?}", e)
+		})
+	}
+
+	/// Mark a requested compaction as not running, e.g. when its thread couldn't be spawned.
+	pub fn compaction_aborted(&self) {
+		self.compaction.write().in_progress = false;
+	}
+
+	/// Compact every column of the database, blocking until done.
+	/// Use `BlockChainClient::compact_database` to run it in the background.
+	pub fn compact_db(&self) {
+		let db = self.db.read().clone();
+		let total = ::db::NUM_COLUMNS.unwrap_or(0);
+		{
+			let mut status = self.compaction.write();
+			status.in_progress = true;
+			status.columns_compacted = 0;
+			status.columns_total = total;
+		}
+
+		info!("Compacting database ({} columns)", total);
+		let started = Instant::now();
+		for col in 0..total {
+			if let Err(e) = db.compact(Some(col)) {
+				warn!("Database compaction failed at column {}: {}", col, e);
+				break;
+			}
+			self.compaction.write().columns_compacted = col + 1;
+			info!("Database compaction: {}/{} columns done", col + 1, total);
+		}
+
+		let mut status = self.compaction.write();
+		status.in_progress = false;
+		if status.columns_compacted == total {
+			status.last_finished = Some(get_time().sec as u64);
+			info!("Database compaction finished in {}s", started.elapsed().as_secs());
+		}
+		*self.last_compaction.lock() = Instant::now();
+	}
+
 	/// Take a snapshot at the given block.
 	/// If the ID given is "latest", this will default to 1000 blocks behind.
 	pub fn take_snapshot<W: snapshot_io::SnapshotWriter + Send>(&self, writer: W, at: BlockId, p: &snapshot::Progress) -> Result<(), EthcoreError> {
@@ -815,6 +1310,192 @@ impl Client {
 		Ok(())
 	}
 
+	/// Rebuild the transaction address index for canonical blocks in `[from, to]`.
+	/// See `BlockChain::reindex_transaction_addresses`.
+	pub fn reindex_transaction_addresses<F>(&self, from: BlockNumber, to: BlockNumber, workers: usize, progress: F) -> usize
+		where F: FnMut(BlockNumber)
+	{
+		self.chain.read().reindex_transaction_addresses(from, to, workers, progress)
+	}
+
+	/// Re-execute canonical blocks `from..=to` using up to `workers` threads and store their traces,
+	/// skipping blocks which already have them. Parent states are needed, so a pruned database can
+	/// only be backfilled within its history. Returns the number of traced blocks.
+	pub fn backfill_traces<F>(&self, from: BlockNumber, to: BlockNumber, workers: usize, mut progress: F) -> Result<usize, String>
+		where F: FnMut(BlockNumber)
+	{
+		const BATCH_SIZE: u64 = 100;
+
+		let tracedb = self.tracedb.read();
+		if !tracedb.tracing_enabled() {
+			return Err("Tracing is disabled".into());
+		}
+
+		let workers = ::std::cmp::max(workers, 1);
+		let mut traced = 0;
+		// genesis traces are written when the trace database is created.
+		let mut start = ::std::cmp::max(from, 1);
+
+		while start <= to {
+			let end = ::std::cmp::min(to, start.saturating_add(BATCH_SIZE - 1));
+			let hashes: Vec<_> = {
+				let chain = self.chain.read();
+				(start..end + 1)
+					.filter_map(|number| chain.block_hash(number).map(|hash| (number, hash)))
+					.filter(|&(_, ref hash)| !tracedb.has_block_traces(hash))
+					.collect()
+			};
+
+			let chunk_size = ::std::cmp::max(1, (hashes.len() + workers - 1) / workers);
+			let traces: Vec<Result<_, String>> = ::crossbeam::scope(|scope| {
+				let handles: Vec<_> = hashes.chunks(chunk_size).map(|chunk| scope.spawn(move || {
+					chunk.iter()
+						.map(|&(number, ref hash)| self.replay_block_traces(hash).map(|traces| (number, hash.clone(), traces)))
+						.collect::<Vec<_>>()
+				})).collect();
+
+				handles.into_iter().flat_map(|handle| handle.join()).collect()
+			});
+
+			// bloom groups are read from the database on import, so each block is committed
+			// before the next one is imported.
+			for result in traces {
+				let (number, hash, traces) = result?;
+				let mut batch = DBTransaction::new();
+				tracedb.import(&mut batch, TraceImportRequest {
+					traces: traces,
+					block_hash: hash,
+					block_number: number,
+					enacted: vec![hash],
+					retracted: 0,
+				});
+				self.db.read().write(batch).map_err(|e| format!("Database write failed: {}", e))?;
+				traced += 1;
+			}
+			progress(end);
+
+			if end == to { break; }
+			start = end + 1;
+		}
+
+		Ok(traced)
+	}
+
+	// re-execute a canonical block on top of its parent state, checking the resulting state root.
+	fn replay_block_traces(&self, hash: &H256) -> Result<trace::FlatBlockTraces, String> {
+		let (block, parent) = {
+			let chain = self.chain.read();
+			let block = chain.block(hash).ok_or_else(|| format!("Block {} not found", hash))?.decode();
+			let parent = chain.block_header(block.header.parent_hash())
+				.ok_or_else(|| format!("Parent of block {} not found", hash))?;
+			(block, parent)
+		};
+
+		let number = block.header.number();
+		let transactions = block.transactions.into_iter()
+			.map(SignedTransaction::new)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Invalid transaction in block #{}: {}", number, e))?;
+
+		let last_hashes = self.build_last_hashes(block.header.parent_hash().clone());
+		let db = self.state_db.lock().boxed_clone();
+		let locked = enact(&block.header, &transactions, &block.uncles, &*self.engine, true, db, &parent, last_hashes, self.factories.clone())
+			.map_err(|e| format!("Unable to re-execute block #{}: {}. Is the parent state pruned?", number, e))?;
+
+		if locked.block().header().state_root() != block.header.state_root() {
+			return Err(format!("Re-execution of block #{} resulted in a different state root", number));
+		}
+
+		let traces: Vec<FlatTransactionTraces> = locked.traces().clone().unwrap_or_else(Vec::new)
+			.into_iter()
+			.map(Into::into)
+			.collect();
+		Ok(traces.into())
+	}
+
+	/// Re-execute the block of a replay bundle on top of its parent state, tracing each
+	/// transaction down to the VM level. The parent state must be in the database.
+	pub fn replay_bundle(&self, bundle: &ReplayBundle) -> Result<ReplayReport, String> {
+		let (parent, last_hashes) = {
+			let chain = self.chain.read();
+			if chain.genesis_hash() != bundle.genesis_hash {
+				return Err(format!("Bundle is of another chain: genesis {:?}, expected {:?}", bundle.genesis_hash, chain.genesis_hash()));
+			}
+			if self.spec_hash != bundle.spec_hash {
+				return Err(format!("Bundle was captured with another chain spec: {:?}, expected {:?}", bundle.spec_hash, self.spec_hash));
+			}
+			let parent = chain.block_header(&bundle.parent_hash)
+				.ok_or_else(|| format!("Parent block {:?} not found", bundle.parent_hash))?;
+			(parent, self.build_last_hashes(bundle.parent_hash))
+		};
+		if parent.state_root() != &bundle.parent_state_root {
+			return Err(format!("Parent state root {:?} differs from the bundle's {:?}", parent.state_root(), bundle.parent_state_root));
+		}
+
+		let block = UntrustedRlp::new(&bundle.block).as_val::<Block>()
+			.map_err(|e| format!("Invalid block RLP: {:?}", e))?;
+		if block.header.parent_hash() != &bundle.parent_hash {
+			return Err("Block is not a child of the bundle's parent".into());
+		}
+
+		let db = self.state_db.lock().boxed_clone();
+		// engine changes at the start of the block (e.g. irregular state changes) precede the first transaction.
+		let mut state = OpenBlock::new(
+			&*self.engine,
+			self.factories.clone(),
+			false,
+			db.boxed_clone(),
+			&parent,
+			last_hashes.clone(),
+			block.header.author().clone(),
+			(block.header.gas_limit().clone(), block.header.gas_limit().clone()),
+			block.header.extra_data().clone(),
+		)
+			.map(|open| open.state().clone())
+			.map_err(|_| format!("Parent state {:?} is not in the database; is it pruned?", bundle.parent_state_root))?;
+
+		let mut env_info = EnvInfo {
+			number: block.header.number(),
+			author: block.header.author().clone(),
+			timestamp: block.header.timestamp(),
+			difficulty: block.header.difficulty().clone(),
+			last_hashes: last_hashes.clone(),
+			gas_used: U256::zero(),
+			gas_limit: block.header.gas_limit().clone(),
+		};
+
+		let options = TransactOptions { tracing: true, vm_tracing: true, check_nonce: true };
+		let mut transactions = Vec::with_capacity(block.transactions.len());
+		let mut signed = Vec::with_capacity(block.transactions.len());
+		for t in block.transactions {
+			let hash = t.hash();
+			let result = SignedTransaction::new(t)
+				.map_err(|e| format!("Invalid signature: {}", e))
+				.and_then(|t| {
+					let executed = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(&t, options);
+					signed.push(t);
+					executed.map_err(|e| format!("{}", e))
+				});
+			if let Ok(ref executed) = result {
+				env_info.gas_used = env_info.gas_used + executed.gas_used;
+			}
+			transactions.push((hash, result));
+		}
+
+		let produced = match signed.len() == transactions.len() {
+			true => enact(&block.header, &signed, &block.uncles, &*self.engine, true, db, &parent, last_hashes, self.factories.clone())
+				.map(|locked| locked.block().header().clone())
+				.map_err(|e| format!("{:?}", e)),
+			false => Err("Block contains transactions with invalid signatures".into()),
+		};
+
+		Ok(ReplayReport {
+			transactions: transactions,
+			expected: block.header,
+			produced: produced,
+		})
+	}
+
 	/// Ask the client what the history parameter is.
 	pub fn pruning_history(&self) -> u64 {
 		self.history
@@ -886,104 +1567,19 @@ impl snapshot::DatabaseRestore for Client {
 
 impl BlockChainClient for Client {
 	fn call(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
-		env_info.gas_limit = U256::max_value();
-
-		// that's just a copy of the state.
-		let mut state = self.state_at(block).ok_or(CallError::StatePruned)?;
-		let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
-
-		let sender = t.sender();
-		let balance = state.balance(&sender).map_err(|_| CallError::StateCorrupt)?;
-		let needed_balance = t.value + t.gas * t.gas_price;
-		if balance < needed_balance {
-			// give the sender a sufficient balance
-			state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
-				.map_err(|_| CallError::StateCorrupt)?;
-		}
-		let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-		let mut ret = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(t, options)?;
-
-		// TODO gav move this into Executive.
-		if let Some(original) = original_state {
-			ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
-		}
+		self.call_with_env(t, block, analytics, &EnvOverrides::default())
+	}
 
-		Ok(ret)
+	fn call_with_env(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics, env: &EnvOverrides) -> Result<Executed, CallError> {
+		let env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		call::call(&state, env_info, env, &*self.engine, &self.factories.vm, t, analytics)
 	}
 
 	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError> {
-		const UPPER_CEILING: u64 = 1_000_000_000_000u64;
-		let (mut upper, env_info)  = {
-			let mut env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
-			let initial_upper = env_info.gas_limit;
-			env_info.gas_limit = UPPER_CEILING.into();
-			(initial_upper, env_info)
-		};
-
-		// that's just a copy of the state.
-		let original_state = self.state_at(block).ok_or(CallError::StatePruned)?;
-		let sender = t.sender();
-		let balance = original_state.balance(&sender).map_err(ExecutionError::from)?;
-		let options = TransactOptions { tracing: true, vm_tracing: false, check_nonce: false };
-
-		let cond = |gas| {
-			let mut tx = t.as_unsigned().clone();
-			tx.gas = gas;
-			let tx = tx.fake_sign(sender);
-
-			let mut state = original_state.clone();
-			let needed_balance = tx.value + tx.gas * tx.gas_price;
-			if balance < needed_balance {
-				// give the sender a sufficient balance
-				state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
-					.map_err(ExecutionError::from)?;
-			}
-
-			Ok(Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm)
-				.transact(&tx, options.clone())
-				.map(|r| r.exception.is_none())
-				.unwrap_or(false))
-		};
-
-		if !cond(upper)? {
-			// impossible at block gas limit - try `UPPER_CEILING` instead.
-			// TODO: consider raising limit by powers of two.
-			upper = UPPER_CEILING.into();
-			if !cond(upper)? {
-				trace!(target: "estimate_gas", "estimate_gas failed with {}", upper);
-				let err = ExecutionError::Internal(format!("Requires higher than upper limit of {}", upper));
-				return Err(err.into())
-			}
-		}
-		let lower = t.gas_required(&self.engine.schedule(&env_info)).into();
-		if cond(lower)? {
-			trace!(target: "estimate_gas", "estimate_gas succeeded with {}", lower);
-			return Ok(lower)
-		}
-
-		/// Find transition point between `lower` and `upper` where `cond` changes from `false` to `true`.
-		/// Returns the lowest value between `lower` and `upper` for which `cond` returns true.
-		/// We assert: `cond(lower) = false`, `cond(upper) = true`
-		fn binary_chop<F, E>(mut lower: U256, mut upper: U256, mut cond: F) -> Result<U256, E>
-			where F: FnMut(U256) -> Result<bool, E>
-		{
-			while upper - lower > 1.into() {
-				let mid = (lower + upper) / 2.into();
-				trace!(target: "estimate_gas", "{} .. {} .. {}", lower, mid, upper);
-				let c = cond(mid)?;
-				match c {
-					true => upper = mid,
-					false => lower = mid,
-				};
-				trace!(target: "estimate_gas", "{} => {} .. {}", c, lower, upper);
-			}
-			Ok(upper)
-		}
-
-		// binary chop to non-excepting call with gas somewhere between 21000 and block gas limit
-		trace!(target: "estimate_gas", "estimate_gas chopping {} .. {}", lower, upper);
-		binary_chop(lower, upper, cond)
+		let env_info = self.env_info(block).ok_or(CallError::StatePruned)?;
+		let state = self.state_at(block).ok_or(CallError::StatePruned)?;
+		call::estimate_gas(&state, env_info, &*self.engine, &self.factories.vm, t)
 	}
 
 	fn replay(&self, id: TransactionId, analytics: CallAnalytics) -> Result<Executed, CallError> {
@@ -1021,6 +1617,18 @@ impl BlockChainClient for Client {
 		r
 	}
 
+	fn is_read_only(&self) -> bool {
+		self.config.read_only || self.shutting_down.load(AtomicOrdering::SeqCst)
+	}
+
+	fn cht_roots(&self) -> BTreeMap<u64, H256> {
+		self.cht_roots.read().roots().clone()
+	}
+
+	fn cht_root(&self, number: u64) -> Option<H256> {
+		self.cht_roots.read().root(number)
+	}
+
 	fn disable(&self) {
 		self.set_mode(IpcMode::Off);
 		self.enabled.store(false, AtomicOrdering::Relaxed);
@@ -1103,6 +1711,8 @@ impl BlockChainClient for Client {
 		let chain = self.chain.read();
 		match Self::block_hash(&chain, id) {
 			Some(ref hash) if chain.is_known(hash) => BlockStatus::InChain,
+			Some(ref hash) if self.bad_blocks.read().contains(hash) => BlockStatus::Bad,
+			Some(ref hash) if self.future_blocks.lock().contains(hash) => BlockStatus::Queued,
 			Some(hash) => self.block_queue.status(&hash).into(),
 			None => BlockStatus::Unknown
 		}
@@ -1225,6 +1835,14 @@ impl BlockChainClient for Client {
 		Some(keys)
 	}
 
+	fn state_diff(&self, accounts: &[Address], from: BlockId, to: BlockId) -> Option<StateDiff> {
+		let pre = match self.pod_state_at(accounts, from) {
+			Some(pre) => pre,
+			None => return None,
+		};
+		self.pod_state_at(accounts, to).map(|post| pod_state::diff_pod(&pre, &post))
+	}
+
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
@@ -1283,24 +1901,18 @@ impl BlockChainClient for Client {
 	}
 
 	fn import_block(&self, bytes: Bytes) -> Result<H256, BlockImportError> {
-		use verification::queue::kind::BlockLike;
-		use verification::queue::kind::blocks::Unverified;
+		if self.is_read_only() {
+			return Err(BlockImportError::Import(ImportError::ReadOnly));
+		}
 
 		// create unverified block here so the `sha3` calculation can be cached.
-		let unverified = Unverified::new(bytes);
-
-		{
-			if self.chain.read().is_known(&unverified.hash()) {
-				return Err(BlockImportError::Import(ImportError::AlreadyInChain));
-			}
-			if self.block_status(BlockId::Hash(unverified.parent_hash())) == BlockStatus::Unknown {
-				return Err(BlockImportError::Block(BlockError::UnknownParent(unverified.parent_hash())));
-			}
-		}
-		Ok(self.block_queue.import(unverified)?)
+		self.import_unverified(Unverified::new(bytes))
 	}
 
 	fn import_block_with_receipts(&self, block_bytes: Bytes, receipts_bytes: Bytes) -> Result<H256, BlockImportError> {
+		if self.is_read_only() {
+			return Err(BlockImportError::Import(ImportError::ReadOnly));
+		}
 		{
 			// check block order
 			let header = BlockView::new(&block_bytes).header_view();
@@ -1314,6 +1926,40 @@ impl BlockChainClient for Client {
 		self.import_old_block(block_bytes, receipts_bytes).map_err(Into::into)
 	}
 
+	fn import_block_receipts(&self, receipts: Vec<(H256, Bytes)>) -> Vec<Result<(), BlockImportError>> {
+		if self.is_read_only() {
+			return receipts.iter().map(|_| Err(BlockImportError::Import(ImportError::ReadOnly))).collect();
+		}
+		let results = {
+			let _import_lock = self.import_lock.lock();
+			let chain = self.chain.read();
+			let mut batch = DBTransaction::new();
+
+			let results = receipts.into_iter().map(|(hash, receipts_bytes)| -> Result<(), BlockImportError> {
+				let header = chain.block_header(&hash)
+					.ok_or_else(|| BlockImportError::Other(format!("Unknown block {}", hash)))?;
+
+				let rlp = UntrustedRlp::new(&receipts_bytes);
+				let receipts_root = ordered_trie_root(rlp.iter().map(|r| r.as_raw().to_vec()));
+				if receipts_root != *header.receipts_root() {
+					return Err(BlockImportError::Block(BlockError::InvalidReceiptsRoot(Mismatch {
+						expected: header.receipts_root().clone(),
+						found: receipts_root,
+					})));
+				}
+				let receipts = rlp.as_list().map_err(|e| BlockImportError::Other(format!("Invalid receipts: {}", e)))?;
+
+				chain.insert_block_receipts(&mut batch, &hash, receipts);
+				Ok(())
+			}).collect::<Vec<_>>();
+
+			self.db.read().write_buffered(batch);
+			results
+		};
+		self.flush_db();
+		results
+	}
+
 	fn queue_info(&self) -> BlockQueueInfo {
 		self.block_queue.queue_info()
 	}
@@ -1322,6 +1968,14 @@ impl BlockChainClient for Client {
 		self.block_queue.clear();
 	}
 
+	fn clear_bad_blocks(&self) {
+		let mut bad_blocks = self.bad_blocks.write();
+		if bad_blocks.clear() && !self.config.read_only {
+			bad_blocks.save(&**self.db.read(), ::db::COL_NODE_INFO);
+		}
+		self.block_queue.clear_bad();
+	}
+
 	fn chain_info(&self) -> BlockChainInfo {
 		let mut chain_info = self.chain.read().chain_info();
 		chain_info.pending_total_difficulty = chain_info.total_difficulty + self.block_queue.total_difficulty();
@@ -1397,6 +2051,10 @@ impl BlockChainClient for Client {
 	}
 
 	fn queue_transactions(&self, transactions: Vec<Bytes>, peer_id: usize) {
+		if self.is_read_only() {
+			debug!("Ignoring {} transactions: client is read-only", transactions.len());
+			return;
+		}
 		let queue_size = self.queue_transactions.load(AtomicOrdering::Relaxed);
 		trace!(target: "external_tx", "Queue size: {}", queue_size);
 		if queue_size > MAX_TX_QUEUE_SIZE {
@@ -1422,6 +2080,16 @@ impl BlockChainClient for Client {
 		self.miner.ready_transactions(number, timestamp)
 	}
 
+	fn local_transaction_hashes(&self) -> Vec<H256> {
+		self.miner.local_transactions().into_iter()
+			.filter(|&(_, ref status)| match *status {
+				LocalTransactionStatus::Pending | LocalTransactionStatus::Future => true,
+				_ => false,
+			})
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn queue_consensus_message(&self, message: Bytes) {
 		let channel = self.io_channel.lock().clone();
 		if let Err(e) = channel.send(ClientIoMessage::NewMessage(message)) {
@@ -1450,6 +2118,40 @@ impl BlockChainClient for Client {
 		}
 	}
 
+	fn chain_spec_info(&self) -> ChainSpecInfo {
+		ChainSpecInfo::new(self.config.spec_name.clone(), &*self.engine)
+	}
+
+	fn db_flush_marker(&self) -> DbFlushMarker {
+		self.flush_marker.read().clone()
+	}
+
+	fn compact_database(&self) -> Result<(), String> {
+		self.start_compaction()
+	}
+
+	fn compaction_status(&self) -> CompactionStatus {
+		self.compaction.read().clone()
+	}
+
+	fn db_stats(&self) -> Vec<DbColumnStats> {
+		let db = self.db.read();
+		::db::COLUMN_NAMES.iter().enumerate().map(|(col, name)| {
+			let stats = db.column_stats(Some(col as u32));
+			let (cache_hits, cache_misses) = ::db::cache_lookups(Some(col as u32));
+			DbColumnStats {
+				name: name.to_string(),
+				size_on_disk: stats.size_on_disk,
+				estimated_keys: stats.estimated_keys,
+				cache_usage: stats.cache_usage,
+				cache_capacity: stats.cache_capacity,
+				pending_compaction_bytes: stats.pending_compaction_bytes,
+				cache_hits: cache_hits,
+				cache_misses: cache_misses,
+			}
+		}).collect()
+	}
+
 	fn call_contract(&self, block_id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String> {
 		let from = Address::default();
 		let transaction = Transaction {
@@ -1517,9 +2219,7 @@ impl MiningBlockChainClient for Client {
 		).expect("OpenBlock::new only fails if parent state root invalid; state root of best block's header is never invalid; qed");
 
 		// Add uncles
-		chain
-			.find_uncle_headers(&h, engine.maximum_uncle_age())
-			.unwrap_or_else(Vec::new)
+		self.uncle_candidates(&chain, &h)
 			.into_iter()
 			.take(engine.maximum_uncle_count())
 			.foreach(|h| {
@@ -1552,6 +2252,9 @@ impl MiningBlockChainClient for Client {
 	}
 
 	fn import_sealed_block(&self, block: SealedBlock) -> ImportResult {
+		if self.is_read_only() {
+			return Err(ImportError::ReadOnly.into());
+		}
 		let h = block.header().hash();
 		let start = precise_time_ns();
 		let route = {
@@ -1579,7 +2282,7 @@ impl MiningBlockChainClient for Client {
 				precise_time_ns() - start,
 			);
 		});
-		self.db.read().flush().expect("DB flush failed.");
+		self.flush_db();
 		Ok(h)
 	}
 }
@@ -1617,7 +2320,7 @@ impl ::client::ProvingBlockChainClient for Client {
 			.and_then(move |state| state.prove_account(key1).ok())
 	}
 
-	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<Vec<DBValue>> {
+	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
 		let (state, env_info) = match (self.state_at(id), self.env_info(id)) {
 			(Some(s), Some(e)) => (s, e),
 			_ => return None,
@@ -1630,8 +2333,9 @@ impl ::client::ProvingBlockChainClient for Client {
 		let res = Executive::new(&mut state, &env_info, &*self.engine, &self.factories.vm).transact(&transaction, options);
 
 		match res {
-			Err(ExecutionError::Internal(_)) => return None,
-			_ => return Some(state.drop().1.extract_proof()),
+			Err(ExecutionError::Internal(_)) => None,
+			Err(_) => Some((Vec::new(), state.drop().1.extract_proof())),
+			Ok(executed) => Some((executed.output, state.drop().1.extract_proof())),
 		}
 	}
 }
@@ -15,18 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::FromStr;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::fmt::{Display, Formatter, Error as FmtError};
 pub use std::time::Duration;
 pub use blockchain::Config as BlockChainConfig;
 pub use trace::Config as TraceConfig;
 pub use evm::VMType;
 
-use verification::{VerifierType, QueueConfig};
+use verification::{VerifierType, SealPolicy, QueueConfig};
 use util::{journaldb, CompactionProfile};
 
 /// Client state db compaction profile
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum DatabaseCompactionProfile {
 	/// Try to determine compaction profile automatically
 	Auto,
@@ -121,12 +121,18 @@ pub struct ClientConfig {
 	pub db_compaction: DatabaseCompactionProfile,
 	/// Should db have WAL enabled?
 	pub db_wal: bool,
+	/// Time between scheduled full database compactions, if any.
+	pub db_compaction_interval: Option<Duration>,
+	/// Limit on the disk write rate of background flushes and compactions, bytes/sec, if any.
+	pub db_compaction_rate_limit: Option<u64>,
 	/// Operating mode
 	pub mode: Mode,
 	/// The chain spec name
 	pub spec_name: String,
 	/// Type of block verifier used by client.
 	pub verifier_type: VerifierType,
+	/// Which seals are verified, if the verifier checks seals at all.
+	pub seal_policy: SealPolicy,
 	/// State db cache-size.
 	pub state_cache_size: usize,
 	/// EVM jump-tables cache size.
@@ -137,6 +143,15 @@ pub struct ClientConfig {
 	pub history_mem: usize,
 	/// Check seal valididity on block import
 	pub check_seal: bool,
+	/// Open the database read-only. Blocks and transactions are not imported.
+	pub read_only: bool,
+	/// Seconds a block's timestamp may exceed the allowed drift for the block to be kept
+	/// and imported once its time comes, rather than rejected. Zero rejects such blocks.
+	pub future_block_gap: u64,
+	/// Directory to write replay bundles of blocks failing execution to, if any.
+	pub replay_bundle_dir: Option<PathBuf>,
+	/// Forget the blocks remembered as failing verification on start.
+	pub clear_bad_blocks: bool,
 }
 
 #[cfg(test)]
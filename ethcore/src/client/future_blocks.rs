@@ -0,0 +1,113 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Blocks with timestamps slightly too far in the future, kept until they
+//! can be imported instead of being rejected.
+
+use std::collections::BTreeMap;
+use util::H256;
+
+/// Bounded buffer of blocks waiting for their timestamp to come into the allowed range.
+pub struct FutureBlocks<T> {
+	gap: u64,
+	limit: usize,
+	blocks: BTreeMap<(u64, H256), T>,
+}
+
+impl<T> FutureBlocks<T> {
+	/// Create a buffer accepting blocks up to `gap` seconds past the latest allowed
+	/// timestamp, holding at most `limit` of them. A zero `gap` disables the buffer.
+	pub fn new(gap: u64, limit: usize) -> Self {
+		FutureBlocks {
+			gap: gap,
+			limit: limit,
+			blocks: BTreeMap::new(),
+		}
+	}
+
+	/// Keep a block whose timestamp is past `max_timestamp`. Returns `false` if the block is
+	/// too far in the future even with the gap or the buffer is full.
+	pub fn insert(&mut self, hash: H256, timestamp: u64, max_timestamp: u64, block: T) -> bool {
+		if timestamp > max_timestamp + self.gap {
+			return false;
+		}
+		let key = (timestamp, hash);
+		if !self.blocks.contains_key(&key) && self.blocks.len() >= self.limit {
+			return false;
+		}
+		self.blocks.insert(key, block);
+		true
+	}
+
+	/// Take all blocks with timestamps up to `max_timestamp`, oldest first.
+	pub fn take_due(&mut self, max_timestamp: u64) -> Vec<T> {
+		let due = match self.blocks.keys().position(|&(timestamp, _)| timestamp > max_timestamp) {
+			Some(pos) => pos,
+			None => self.blocks.len(),
+		};
+		let keys = self.blocks.keys().take(due).cloned().collect::<Vec<_>>();
+		keys.into_iter().filter_map(|key| self.blocks.remove(&key)).collect()
+	}
+
+	/// Whether the block is waiting.
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.blocks.keys().any(|&(_, ref h)| h == hash)
+	}
+
+	/// Number of blocks waiting.
+	pub fn len(&self) -> usize {
+		self.blocks.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::H256;
+	use super::FutureBlocks;
+
+	#[test]
+	fn keeps_blocks_within_gap() {
+		let mut blocks = FutureBlocks::new(10, 2);
+		assert!(blocks.insert(H256::from(1), 105, 100, 1));
+		assert!(!blocks.insert(H256::from(2), 111, 100, 2));
+		assert!(blocks.insert(H256::from(3), 110, 100, 3));
+		assert!(!blocks.insert(H256::from(4), 101, 100, 4));
+		assert!(blocks.insert(H256::from(3), 110, 100, 3));
+		assert_eq!(blocks.len(), 2);
+		assert!(blocks.contains(&H256::from(3)));
+		assert!(!blocks.contains(&H256::from(2)));
+	}
+
+	#[test]
+	fn zero_gap_keeps_nothing() {
+		let mut blocks = FutureBlocks::new(0, 2);
+		assert!(!blocks.insert(H256::from(1), 101, 100, 1));
+		assert_eq!(blocks.len(), 0);
+	}
+
+	#[test]
+	fn releases_due_blocks_in_order() {
+		let mut blocks = FutureBlocks::new(10, 10);
+		blocks.insert(H256::from(1), 108, 100, 1);
+		blocks.insert(H256::from(2), 103, 100, 2);
+		blocks.insert(H256::from(3), 105, 100, 3);
+
+		assert_eq!(blocks.take_due(102), Vec::<u32>::new());
+		assert_eq!(blocks.take_due(105), vec![2, 3]);
+		assert_eq!(blocks.len(), 1);
+		assert_eq!(blocks.take_due(110), vec![1]);
+	}
+}
@@ -22,6 +22,13 @@ mod error;
 mod test_client;
 mod trace;
 mod client;
+mod uncle_pool;
+mod bad_blocks;
+mod cht_roots;
+mod future_blocks;
+
+pub mod call;
+pub mod replay;
 
 pub use self::client::*;
 pub use self::config::{Mode, ClientConfig, DatabaseCompactionProfile, BlockChainConfig, VMType};
@@ -35,15 +42,20 @@ pub use self::traits::ProvingBlockChainClient;
 pub use types::ids::*;
 pub use types::trace_filter::Filter as TraceFilter;
 pub use types::pruning_info::PruningInfo;
+pub use types::chain_spec_info::ChainSpecInfo;
+pub use types::db_flush_marker::DbFlushMarker;
+pub use types::compaction_status::CompactionStatus;
+pub use types::db_stats::DbColumnStats;
+pub use types::fee_stats::BlockFeeStats;
 pub use types::call_analytics::CallAnalytics;
 
 pub use executive::{Executed, Executive, TransactOptions};
-pub use env_info::{LastHashes, EnvInfo};
+pub use env_info::{LastHashes, EnvInfo, EnvOverrides};
 
 pub use block_import_error::BlockImportError;
 pub use transaction_import::TransactionImportResult;
 pub use transaction_import::TransactionImportError;
-pub use verification::VerifierType;
+pub use verification::{VerifierType, SealPolicy};
 
 /// IPC interfaces
 #[cfg(feature="ipc")]
@@ -0,0 +1,189 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Replay bundles: the inputs of a failed block import, written so the failure
+//! can be reproduced against any database holding the parent state.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use executive::Executed;
+use header::Header;
+use rlp::{self, Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+use util::{Bytes, H256};
+use views::BlockView;
+
+/// Inputs of a block import which failed during execution or final verification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayBundle {
+	/// Version of the client which failed the import.
+	pub version: String,
+	/// Genesis hash of the chain the block belongs to.
+	pub genesis_hash: H256,
+	/// Hash of the chain spec the import failed with.
+	pub spec_hash: H256,
+	/// Hash of the parent block.
+	pub parent_hash: H256,
+	/// State root of the parent block, where execution starts from.
+	pub parent_state_root: H256,
+	/// RLP of the block.
+	pub block: Bytes,
+	/// Error the import failed with.
+	pub error: String,
+}
+
+impl ReplayBundle {
+	/// Name of the bundle file: block number and hash.
+	pub fn file_name(&self) -> String {
+		let header = BlockView::new(&self.block).header_view();
+		format!("{}-{}.rlp", header.number(), header.sha3().hex())
+	}
+
+	/// Write the bundle to given directory, creating it if needed. Returns the path of the file.
+	pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+		fs::create_dir_all(dir)?;
+		let path = dir.join(self.file_name());
+		File::create(&path)?.write_all(&rlp::encode(self))?;
+		Ok(path)
+	}
+
+	/// Read a bundle from given file.
+	pub fn read(path: &Path) -> Result<Self, String> {
+		let mut bytes = Vec::new();
+		File::open(path)
+			.and_then(|mut file| file.read_to_end(&mut bytes))
+			.map_err(|e| format!("Unable to read replay bundle {}: {}", path.display(), e))?;
+		UntrustedRlp::new(&bytes).as_val()
+			.map_err(|e| format!("Invalid replay bundle {}: {:?}", path.display(), e))
+	}
+}
+
+impl Encodable for ReplayBundle {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(7)
+			.append(&self.version)
+			.append(&self.genesis_hash)
+			.append(&self.spec_hash)
+			.append(&self.parent_hash)
+			.append(&self.parent_state_root)
+			.append(&self.block)
+			.append(&self.error);
+	}
+}
+
+impl Decodable for ReplayBundle {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		Ok(ReplayBundle {
+			version: rlp.val_at(0)?,
+			genesis_hash: rlp.val_at(1)?,
+			spec_hash: rlp.val_at(2)?,
+			parent_hash: rlp.val_at(3)?,
+			parent_state_root: rlp.val_at(4)?,
+			block: rlp.val_at(5)?,
+			error: rlp.val_at(6)?,
+		})
+	}
+}
+
+/// Result of replaying a bundle.
+#[derive(Debug)]
+pub struct ReplayReport {
+	/// Hash and execution of each transaction of the block, with call and VM traces.
+	pub transactions: Vec<(H256, Result<Executed, String>)>,
+	/// Header of the replayed block.
+	pub expected: Header,
+	/// Header resulting from enacting the block on the parent state.
+	pub produced: Result<Header, String>,
+}
+
+impl ReplayReport {
+	/// Descriptions of the header fields in which the produced header differs from the expected one.
+	pub fn mismatches(&self) -> Vec<String> {
+		let produced = match self.produced {
+			Ok(ref header) => header,
+			Err(_) => return Vec::new(),
+		};
+
+		let mut mismatches = Vec::new();
+		if produced.state_root() != self.expected.state_root() {
+			mismatches.push(format!("state root: expected {:?}, got {:?}", self.expected.state_root(), produced.state_root()));
+		}
+		if produced.receipts_root() != self.expected.receipts_root() {
+			mismatches.push(format!("receipts root: expected {:?}, got {:?}", self.expected.receipts_root(), produced.receipts_root()));
+		}
+		if produced.gas_used() != self.expected.gas_used() {
+			mismatches.push(format!("gas used: expected {}, got {}", self.expected.gas_used(), produced.gas_used()));
+		}
+		if produced.log_bloom() != self.expected.log_bloom() {
+			mismatches.push("log bloom differs".into());
+		}
+		mismatches
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use devtools::RandomTempPath;
+	use header::Header;
+	use rlp::RlpStream;
+	use super::{ReplayBundle, ReplayReport};
+
+	fn block(number: u64) -> Vec<u8> {
+		let mut header = Header::new();
+		header.set_number(number);
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&header);
+		stream.begin_list(0);
+		stream.begin_list(0);
+		stream.out()
+	}
+
+	#[test]
+	fn writes_and_reads_bundle() {
+		let dir = RandomTempPath::new();
+		let bundle = ReplayBundle {
+			version: "Parity/v1.7.0".into(),
+			genesis_hash: 1.into(),
+			spec_hash: 4.into(),
+			parent_hash: 2.into(),
+			parent_state_root: 3.into(),
+			block: block(42),
+			error: "Block(InvalidStateRoot)".into(),
+		};
+
+		let path = bundle.write(dir.as_path()).unwrap();
+		assert!(path.file_name().unwrap().to_str().unwrap().starts_with("42-"));
+		assert_eq!(ReplayBundle::read(&path).unwrap(), bundle);
+	}
+
+	#[test]
+	fn reports_mismatching_fields() {
+		let expected = Header::new();
+		let mut produced = Header::new();
+		produced.set_state_root(5.into());
+		produced.set_gas_used(21000.into());
+
+		let report = ReplayReport { transactions: Vec::new(), expected: expected.clone(), produced: Ok(produced) };
+		let mismatches = report.mismatches();
+		assert_eq!(mismatches.len(), 2);
+		assert!(mismatches[0].starts_with("state root"));
+		assert!(mismatches[1].starts_with("gas used"));
+
+		let report = ReplayReport { transactions: Vec::new(), expected: expected, produced: Ok(Header::new()) };
+		assert!(report.mismatches().is_empty());
+	}
+}
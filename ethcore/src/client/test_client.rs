@@ -17,6 +17,7 @@
 //! Test client.
 
 use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrder};
+use std::collections::BTreeMap;
 use util::*;
 use rlp::*;
 use ethkey::{Generator, Random};
@@ -26,21 +27,27 @@ use blockchain::TreeRoute;
 use client::{
 	BlockChainClient, MiningBlockChainClient, EngineClient, BlockChainInfo, BlockStatus, BlockId,
 	TransactionId, UncleId, TraceId, TraceFilter, LastHashes, CallAnalytics, BlockImportError,
-	ProvingBlockChainClient,
+	ProvingBlockChainClient, EnvOverrides,
 };
 use db::{NUM_COLUMNS, COL_STATE};
 use header::{Header as BlockHeader, BlockNumber};
 use filter::Filter;
+use views::BlockView;
 use log_entry::LocalizedLogEntry;
 use receipt::{Receipt, LocalizedReceipt};
 use blockchain::extras::BlockReceipts;
 use error::{ImportResult, Error as EthcoreError};
 use evm::{Factory as EvmFactory, VMType, Schedule};
-use miner::{Miner, MinerService, TransactionImportResult};
+use miner::{Miner, MinerService, TransactionImportResult, LocalTransactionStatus};
 use spec::Spec;
 use types::basic_account::BasicAccount;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::chain_spec_info::ChainSpecInfo;
+use types::db_flush_marker::DbFlushMarker;
+use types::compaction_status::CompactionStatus;
+use types::db_stats::DbColumnStats;
+use types::state_diff::StateDiff;
 
 use verification::queue::QueueInfo;
 use block::{OpenBlock, SealedBlock};
@@ -74,6 +81,8 @@ pub struct TestBlockChainClient {
 	pub code: RwLock<HashMap<Address, Bytes>>,
 	/// Execution result.
 	pub execution_result: RwLock<Option<Result<Executed, CallError>>>,
+	/// Environment overrides of the last call.
+	pub last_env_overrides: RwLock<Option<EnvOverrides>>,
 	/// Transaction receipts.
 	pub receipts: RwLock<HashMap<TransactionId, LocalizedReceipt>>,
 	/// Logs
@@ -96,6 +105,18 @@ pub struct TestBlockChainClient {
 	pub traces: RwLock<Option<Vec<LocalizedTrace>>>,
 	/// Pruning history size to report.
 	pub history: RwLock<Option<u64>>,
+	/// Database compaction status
+	pub compaction_status: RwLock<CompactionStatus>,
+	/// Database column statistics
+	pub db_stats: RwLock<Vec<DbColumnStats>>,
+	/// Hashes of blocks known to be bad.
+	pub bad_blocks: RwLock<HashSet<H256>>,
+	/// Generated CHT roots.
+	pub cht_roots: RwLock<BTreeMap<u64, H256>>,
+	/// Receipts imported for existing blocks.
+	pub receipts_imported: RwLock<HashMap<H256, Bytes>>,
+	/// Outputs of contract calls, by contract address and call data.
+	pub contract_calls: RwLock<HashMap<(Address, Bytes), Bytes>>,
 }
 
 /// Used for generating test client blocks.
@@ -151,6 +172,7 @@ impl TestBlockChainClient {
 			storage: RwLock::new(HashMap::new()),
 			code: RwLock::new(HashMap::new()),
 			execution_result: RwLock::new(None),
+			last_env_overrides: RwLock::new(None),
 			receipts: RwLock::new(HashMap::new()),
 			logs: RwLock::new(Vec::new()),
 			queue_size: AtomicUsize::new(0),
@@ -162,6 +184,12 @@ impl TestBlockChainClient {
 			first_block: RwLock::new(None),
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
+			compaction_status: RwLock::new(CompactionStatus::default()),
+			db_stats: RwLock::new(Vec::new()),
+			bad_blocks: RwLock::new(HashSet::new()),
+			cht_roots: RwLock::new(BTreeMap::new()),
+			receipts_imported: RwLock::new(HashMap::new()),
+			contract_calls: RwLock::new(HashMap::new()),
 		};
 
 		// insert genesis hash.
@@ -395,6 +423,11 @@ impl BlockChainClient for TestBlockChainClient {
 		self.execution_result.read().clone().unwrap()
 	}
 
+	fn call_with_env(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics, env: &EnvOverrides) -> Result<Executed, CallError> {
+		*self.last_env_overrides.write() = Some(env.clone());
+		self.call(t, block, analytics)
+	}
+
 	fn estimate_gas(&self, _t: &SignedTransaction, _block: BlockId) -> Result<U256, CallError> {
 		Ok(21000.into())
 	}
@@ -457,11 +490,36 @@ impl BlockChainClient for TestBlockChainClient {
 		None
 	}
 
-	fn list_storage(&self, _id: BlockId, _account: &Address, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
-		None
+	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>> {
+		if self.block_hash(id).is_none() {
+			return None;
+		}
+
+		let mut keys: Vec<H256> = self.storage.read().keys()
+			.filter(|&&(ref address, ref key)| address == account && after.map_or(true, |after| key > after))
+			.map(|&(_, ref key)| key.clone())
+			.collect();
+		keys.sort();
+		keys.truncate(count as usize);
+		Some(keys)
 	}
-	fn transaction(&self, _id: TransactionId) -> Option<LocalizedTransaction> {
-		None	// Simple default.
+
+	fn state_diff(&self, _accounts: &[Address], from: BlockId, to: BlockId) -> Option<StateDiff> {
+		// the test client keeps a single state, so nothing changes between blocks.
+		match (self.block_hash(from), self.block_hash(to)) {
+			(Some(_), Some(_)) => Some(StateDiff { raw: BTreeMap::new() }),
+			_ => None,
+		}
+	}
+
+	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction> {
+		let hash = match id {
+			TransactionId::Hash(hash) => hash,
+			_ => return None,
+		};
+		self.blocks.read().values()
+			.flat_map(|block| BlockView::new(block).localized_transactions())
+			.find(|tx| tx.hash() == hash)
 	}
 
 	fn transaction_block(&self, _id: TransactionId) -> Option<H256> {
@@ -508,8 +566,8 @@ impl BlockChainClient for TestBlockChainClient {
 			.map(encoded::Header::new)
 	}
 
-	fn block_number(&self, _id: BlockId) -> Option<BlockNumber> {
-		unimplemented!()
+	fn block_number(&self, id: BlockId) -> Option<BlockNumber> {
+		self.block_header(id).map(|header| header.number())
 	}
 
 	fn block_body(&self, id: BlockId) -> Option<encoded::Body> {
@@ -538,6 +596,7 @@ impl BlockChainClient for TestBlockChainClient {
 		match id {
 			BlockId::Number(number) if (number as usize) < self.blocks.read().len() => BlockStatus::InChain,
 			BlockId::Hash(ref hash) if self.blocks.read().get(hash).is_some() => BlockStatus::InChain,
+			BlockId::Hash(ref hash) if self.bad_blocks.read().contains(hash) => BlockStatus::Bad,
 			BlockId::Latest | BlockId::Pending | BlockId::Earliest => BlockStatus::InChain,
 			_ => BlockStatus::Unknown,
 		}
@@ -589,6 +648,9 @@ impl BlockChainClient for TestBlockChainClient {
 	}
 
 	fn block_receipts(&self, hash: &H256) -> Option<Bytes> {
+		if let Some(receipts) = self.receipts_imported.read().get(hash) {
+			return Some(receipts.clone());
+		}
 		// starts with 'f' ?
 		if *hash > H256::from("f000000000000000000000000000000000000000000000000000000000000000") {
 			let receipt = BlockReceipts::new(vec![Receipt::new(
@@ -651,6 +713,16 @@ impl BlockChainClient for TestBlockChainClient {
 		self.import_block(b)
 	}
 
+	fn import_block_receipts(&self, receipts: Vec<(H256, Bytes)>) -> Vec<Result<(), BlockImportError>> {
+		receipts.into_iter().map(|(hash, r)| match self.blocks.read().contains_key(&hash) {
+			true => {
+				self.receipts_imported.write().insert(hash, r);
+				Ok(())
+			},
+			false => Err(BlockImportError::Other(format!("Unknown block {}", hash))),
+		}).collect()
+	}
+
 	fn queue_info(&self) -> QueueInfo {
 		QueueInfo {
 			verified_queue_size: self.queue_size.load(AtomicOrder::Relaxed),
@@ -665,6 +737,10 @@ impl BlockChainClient for TestBlockChainClient {
 	fn clear_queue(&self) {
 	}
 
+	fn clear_bad_blocks(&self) {
+		self.bad_blocks.write().clear();
+	}
+
 	fn additional_params(&self) -> BTreeMap<String, String> {
 		Default::default()
 	}
@@ -716,6 +792,16 @@ impl BlockChainClient for TestBlockChainClient {
 		self.miner.ready_transactions(info.best_block_number, info.best_block_timestamp)
 	}
 
+	fn local_transaction_hashes(&self) -> Vec<H256> {
+		self.miner.local_transactions().into_iter()
+			.filter(|&(_, ref status)| match *status {
+				LocalTransactionStatus::Pending | LocalTransactionStatus::Future => true,
+				_ => false,
+			})
+			.map(|(hash, _)| hash)
+			.collect()
+	}
+
 	fn signing_network_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
@@ -724,6 +810,10 @@ impl BlockChainClient for TestBlockChainClient {
 
 	fn spec_name(&self) -> String { "foundation".into() }
 
+	fn cht_roots(&self) -> BTreeMap<u64, H256> { self.cht_roots.read().clone() }
+
+	fn cht_root(&self, number: u64) -> Option<H256> { self.cht_roots.read().get(&number).cloned() }
+
 	fn set_spec_name(&self, _: String) { unimplemented!(); }
 
 	fn disable(&self) { unimplemented!(); }
@@ -736,7 +826,39 @@ impl BlockChainClient for TestBlockChainClient {
 		}
 	}
 
-	fn call_contract(&self, _id: BlockId, _address: Address, _data: Bytes) -> Result<Bytes, String> { Ok(vec![]) }
+	fn chain_spec_info(&self) -> ChainSpecInfo {
+		ChainSpecInfo::new(self.spec_name(), &*self.spec.engine)
+	}
+
+	fn db_flush_marker(&self) -> DbFlushMarker {
+		let chain_info = self.chain_info();
+		DbFlushMarker {
+			sequence: 0,
+			best_block_number: chain_info.best_block_number,
+			best_block_hash: chain_info.best_block_hash,
+		}
+	}
+
+	fn compact_database(&self) -> Result<(), String> {
+		let mut status = self.compaction_status.write();
+		if status.in_progress {
+			return Err("Database compaction is already in progress".into());
+		}
+		status.in_progress = true;
+		Ok(())
+	}
+
+	fn compaction_status(&self) -> CompactionStatus {
+		self.compaction_status.read().clone()
+	}
+
+	fn db_stats(&self) -> Vec<DbColumnStats> {
+		self.db_stats.read().clone()
+	}
+
+	fn call_contract(&self, _id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String> {
+		Ok(self.contract_calls.read().get(&(address, data)).cloned().unwrap_or_else(Vec::new))
+	}
 
 	fn transact_contract(&self, address: Address, data: Bytes) -> Result<TransactionImportResult, EthcoreError> {
 		let transaction = Transaction {
@@ -767,7 +889,7 @@ impl ProvingBlockChainClient for TestBlockChainClient {
 		None
 	}
 
-	fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<Vec<DBValue>> {
+	fn prove_transaction(&self, _: SignedTransaction, _: BlockId) -> Option<(Bytes, Vec<DBValue>)> {
 		None
 	}
 }
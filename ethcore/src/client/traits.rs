@@ -30,7 +30,7 @@ use receipt::LocalizedReceipt;
 use trace::LocalizedTrace;
 use evm::{Factory as EvmFactory, Schedule};
 use executive::Executed;
-use env_info::LastHashes;
+use env_info::{LastHashes, EnvOverrides};
 use block_import_error::BlockImportError;
 use ipc::IpcConfig;
 use types::ids::*;
@@ -41,6 +41,12 @@ use types::blockchain_info::BlockChainInfo;
 use types::block_status::BlockStatus;
 use types::mode::Mode;
 use types::pruning_info::PruningInfo;
+use types::chain_spec_info::ChainSpecInfo;
+use types::db_flush_marker::DbFlushMarker;
+use types::compaction_status::CompactionStatus;
+use types::db_stats::DbColumnStats;
+use types::fee_stats::BlockFeeStats;
+use types::state_diff::StateDiff;
 use encoded;
 
 #[ipc(client_ident="RemoteClient")]
@@ -127,6 +133,10 @@ pub trait BlockChainClient : Sync + Send {
 	/// If `after` is set the list starts with the following item.
 	fn list_storage(&self, id: BlockId, account: &Address, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
 
+	/// Get the changes to the given accounts between the states of blocks `from` and `to`,
+	/// or `None` if either state is unavailable. Storage is only compared if fat DB is in operation.
+	fn state_diff(&self, accounts: &[Address], from: BlockId, to: BlockId) -> Option<StateDiff>;
+
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
@@ -158,12 +168,19 @@ pub trait BlockChainClient : Sync + Send {
 	/// Import a block with transaction receipts. Does no sealing and transaction validation.
 	fn import_block_with_receipts(&self, block_bytes: Bytes, receipts_bytes: Bytes) -> Result<H256, BlockImportError>;
 
+	/// Import missing receipts of blocks already in the chain, checking them against the receipts roots.
+	/// Valid receipts are written at once, the result for every block is returned in order.
+	fn import_block_receipts(&self, receipts: Vec<(H256, Bytes)>) -> Vec<Result<(), BlockImportError>>;
+
 	/// Get block queue information.
 	fn queue_info(&self) -> BlockQueueInfo;
 
 	/// Clear block queue and abort all import activity.
 	fn clear_queue(&self);
 
+	/// Forget the blocks remembered as failing verification, so they may be imported again.
+	fn clear_bad_blocks(&self);
+
 	/// Get blockchain information.
 	fn chain_info(&self) -> BlockChainInfo;
 
@@ -182,6 +199,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// Makes a non-persistent transaction call.
 	fn call(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Makes a non-persistent transaction call with parts of the block's environment replaced.
+	fn call_with_env(&self, t: &SignedTransaction, block: BlockId, analytics: CallAnalytics, env: &EnvOverrides) -> Result<Executed, CallError>;
+
 	/// Estimates how much gas will be necessary for a call.
 	fn estimate_gas(&self, t: &SignedTransaction, block: BlockId) -> Result<U256, CallError>;
 
@@ -212,6 +232,9 @@ pub trait BlockChainClient : Sync + Send {
 	/// List all transactions that are allowed into the next block.
 	fn ready_transactions(&self) -> Vec<PendingTransaction>;
 
+	/// Hashes of locally submitted transactions still in the queue.
+	fn local_transaction_hashes(&self) -> Vec<H256>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
@@ -233,6 +256,28 @@ pub trait BlockChainClient : Sync + Send {
 		corpus.into()
 	}
 
+	/// Gas usage and transaction gas prices of up to `count` blocks ending with the given one,
+	/// oldest first.
+	fn block_fee_stats(&self, newest: BlockId, count: u64) -> Vec<BlockFeeStats> {
+		let mut stats = Vec::new();
+		let mut id = newest;
+		for _ in 0..count {
+			let block = match self.block(id) {
+				Some(block) => block,
+				None => break,
+			};
+
+			let gas_prices = block.transaction_views().iter().map(|t| t.gas_price()).collect();
+			stats.push(BlockFeeStats::new(block.number(), block.gas_used(), block.gas_limit(), gas_prices));
+			if block.number() == 0 {
+				break;
+			}
+			id = BlockId::Hash(block.parent_hash());
+		}
+		stats.reverse();
+		stats
+	}
+
 	/// Get the preferred network ID to sign on
 	fn signing_network_id(&self) -> Option<u64>;
 
@@ -248,6 +293,15 @@ pub trait BlockChainClient : Sync + Send {
 	/// Set the chain via a spec name.
 	fn set_spec_name(&self, spec_name: String);
 
+	/// Whether the client is read-only and doesn't import blocks or transactions.
+	fn is_read_only(&self) -> bool { false }
+
+	/// Roots of the canonical hash tries generated so far, by CHT number.
+	fn cht_roots(&self) -> BTreeMap<u64, H256> { BTreeMap::new() }
+
+	/// Root of the canonical hash trie with given number, if generated.
+	fn cht_root(&self, _number: u64) -> Option<H256> { None }
+
 	/// Disable the client from importing blocks. This cannot be undone in this session and indicates
 	/// that a subsystem has reason to believe this executable incapable of syncing the chain.
 	fn disable(&self);
@@ -261,6 +315,22 @@ pub trait BlockChainClient : Sync + Send {
 	/// Returns information about pruning/data availability.
 	fn pruning_info(&self) -> PruningInfo;
 
+	/// Returns normalized parameters of the active chain spec.
+	fn chain_spec_info(&self) -> ChainSpecInfo;
+
+	/// Returns the marker of the latest database flush.
+	fn db_flush_marker(&self) -> DbFlushMarker;
+
+	/// Start compacting the database in the background.
+	/// Fails if a compaction is already running or the database is read-only.
+	fn compact_database(&self) -> Result<(), String>;
+
+	/// Returns the progress of the latest database compaction.
+	fn compaction_status(&self) -> CompactionStatus;
+
+	/// Returns the estimated statistics of every database column.
+	fn db_stats(&self) -> Vec<DbColumnStats>;
+
 	/// Like `call`, but with various defaults. Designed to be used for calling contracts.
 	fn call_contract(&self, id: BlockId, address: Address, data: Bytes) -> Result<Bytes, String>;
 
@@ -324,5 +394,6 @@ pub trait ProvingBlockChainClient: BlockChainClient {
 	fn prove_account(&self, key1: H256, id: BlockId) -> Option<(Vec<Bytes>, BasicAccount)>;
 
 	/// Prove execution of a transaction at the given block.
-	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<Vec<DBValue>>;
+	/// Returns the output of the execution along with the proof.
+	fn prove_transaction(&self, transaction: SignedTransaction, id: BlockId) -> Option<(Bytes, Vec<DBValue>)>;
 }
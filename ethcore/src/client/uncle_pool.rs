@@ -0,0 +1,117 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! In-memory pool of side-chain headers which could be included as uncles.
+
+use std::collections::BTreeMap;
+use util::H256;
+use header::{Header, BlockNumber};
+
+/// Headers of recently imported blocks which are not part of the canonical chain,
+/// kept around so they can be included as uncles as soon as the chain reorganises.
+pub struct UnclePool {
+	max_age: u64,
+	headers: BTreeMap<BlockNumber, Vec<Header>>,
+}
+
+impl UnclePool {
+	/// Create a pool keeping headers that could still be uncles of blocks up to `max_age` generations later.
+	pub fn new(max_age: usize) -> Self {
+		UnclePool {
+			max_age: max_age as u64,
+			headers: BTreeMap::new(),
+		}
+	}
+
+	/// Add a side-chain header.
+	pub fn insert(&mut self, header: Header) {
+		let hash = header.hash();
+		let headers = self.headers.entry(header.number()).or_insert_with(Vec::new);
+		if !headers.iter().any(|h| h.hash() == hash) {
+			headers.push(header);
+		}
+	}
+
+	/// Drop a header which became canonical.
+	pub fn remove(&mut self, number: BlockNumber, hash: &H256) {
+		let now_empty = match self.headers.get_mut(&number) {
+			Some(headers) => {
+				headers.retain(|h| h.hash() != *hash);
+				headers.is_empty()
+			},
+			None => false,
+		};
+		if now_empty {
+			self.headers.remove(&number);
+		}
+	}
+
+	/// Drop headers too old to be an uncle of a child of the best block.
+	pub fn prune(&mut self, best_number: BlockNumber) {
+		let oldest = (best_number + 1).saturating_sub(self.max_age);
+		self.headers = self.headers.split_off(&oldest);
+	}
+
+	/// Headers of the right age to be uncles of a block with the given number, closest generation first.
+	pub fn candidates(&self, number: BlockNumber) -> Vec<Header> {
+		let oldest = number.saturating_sub(self.max_age);
+		self.headers.range(oldest..number).rev()
+			.flat_map(|(_, headers)| headers.iter().cloned())
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use header::Header;
+	use super::UnclePool;
+
+	fn header(number: u64, extra: u8) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_extra_data(vec![extra]);
+		header
+	}
+
+	#[test]
+	fn yields_candidates_in_age_window() {
+		let mut pool = UnclePool::new(6);
+		for number in 1..12 {
+			pool.insert(header(number, 0));
+		}
+		pool.insert(header(10, 1));
+		pool.insert(header(10, 1));
+
+		let numbers: Vec<_> = pool.candidates(12).iter().map(Header::number).collect();
+		assert_eq!(numbers, vec![11, 10, 10, 9, 8, 7, 6]);
+	}
+
+	#[test]
+	fn prunes_old_and_canonical_headers() {
+		let mut pool = UnclePool::new(6);
+		for number in 1..12 {
+			pool.insert(header(number, 0));
+		}
+
+		pool.prune(11);
+		assert_eq!(pool.candidates(12).len(), 6);
+
+		let canon = header(11, 0);
+		pool.remove(11, &canon.hash());
+		let numbers: Vec<_> = pool.candidates(12).iter().map(Header::number).collect();
+		assert_eq!(numbers, vec![10, 9, 8, 7, 6]);
+	}
+}
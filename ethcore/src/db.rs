@@ -19,6 +19,7 @@
 use std::ops::Deref;
 use std::hash::Hash;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use util::{DBTransaction, KeyValueDB, RwLock};
 
 use rlp;
@@ -40,6 +41,30 @@ pub const COL_ACCOUNT_BLOOM: Option<u32> = Some(5);
 pub const COL_NODE_INFO: Option<u32> = Some(6);
 /// Number of columns in DB
 pub const NUM_COLUMNS: Option<u32> = Some(7);
+/// Names of the columns in DB, indexed by column number.
+pub const COLUMN_NAMES: [&'static str; 7] = ["state", "headers", "bodies", "extra", "trace", "account_bloom", "node_info"];
+
+lazy_static! {
+	// hits and misses of the in-memory caches in front of each column, indexed by column.
+	static ref CACHE_HITS: Vec<AtomicUsize> = COLUMN_NAMES.iter().map(|_| AtomicUsize::new(0)).collect();
+	static ref CACHE_MISSES: Vec<AtomicUsize> = COLUMN_NAMES.iter().map(|_| AtomicUsize::new(0)).collect();
+}
+
+/// Record a lookup in the in-memory cache in front of a column.
+pub fn note_cache_lookup(col: Option<u32>, hit: bool) {
+	let counters = if hit { &*CACHE_HITS } else { &*CACHE_MISSES };
+	if let Some(counter) = col.and_then(|c| counters.get(c as usize)) {
+		counter.fetch_add(1, Ordering::Relaxed);
+	}
+}
+
+/// Hits and misses of the in-memory caches in front of a column since the process started.
+pub fn cache_lookups(col: Option<u32>) -> (u64, u64) {
+	let load = |counters: &Vec<AtomicUsize>| col
+		.and_then(|c| counters.get(c as usize))
+		.map_or(0, |counter| counter.load(Ordering::Relaxed) as u64);
+	(load(&*CACHE_HITS), load(&*CACHE_MISSES))
+}
 
 /// Modes for updating caches.
 #[derive(Clone, Copy)]
@@ -174,10 +199,12 @@ pub trait Readable {
 		{
 			let read = cache.read();
 			if let Some(v) = read.get(key) {
+				note_cache_lookup(col, true);
 				return Some(v.clone());
 			}
 		}
 
+		note_cache_lookup(col, false);
 		self.read(col, key).map(|value: T|{
 			let mut write = cache.write();
 			write.insert(key.clone(), value.clone());
@@ -196,10 +223,12 @@ pub trait Readable {
 		{
 			let read = cache.read();
 			if read.get(key).is_some() {
+				note_cache_lookup(col, true);
 				return true;
 			}
 		}
 
+		note_cache_lookup(col, false);
 		self.exists::<T, R>(col, key)
 	}
 }
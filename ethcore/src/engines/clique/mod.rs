@@ -0,0 +1,594 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+/// Proof-of-authority engine compatible with Clique chains such as Rinkeby.
+/// Each block is signed by an authorized signer in the last 65 bytes of its extra data.
+/// Signers vote on adding or removing a signer with the block's author and nonce fields;
+/// a change passes as soon as a majority of signers agrees on it. Votes are reset every epoch,
+/// at which point the checkpoint block lists all signers in its extra data.
+
+mod params;
+mod snapshot;
+
+use std::sync::Weak;
+use util::*;
+use lru_cache::LruCache;
+use time::get_time;
+use rlp::{UntrustedRlp, encode};
+use ethkey::{recover, public_to_address, Signature};
+use account_provider::AccountProvider;
+use block::*;
+use spec::CommonParams;
+use engines::{Engine, Seal};
+use header::{Header, BlockNumber};
+use error::{Error, BlockError};
+use evm::Schedule;
+use env_info::EnvInfo;
+use builtin::Builtin;
+use client::{Client, BlockChainClient, BlockId};
+use super::signer::EngineSigner;
+use self::snapshot::Snapshot;
+
+pub use self::params::CliqueParams;
+
+/// Number of extra data bytes reserved for signer vanity.
+const EXTRA_VANITY: usize = 32;
+/// Number of extra data bytes reserved for the signer's signature.
+const EXTRA_SEAL: usize = 65;
+/// Nonce voting to authorize the account in the author field.
+const NONCE_AUTH: u64 = 0xffffffffffffffff;
+/// Nonce voting to deauthorize the account in the author field.
+const NONCE_DROP: u64 = 0;
+/// Difficulty of a block sealed by the signer whose turn it was.
+const DIFFICULTY_IN_TURN: u64 = 2;
+/// Difficulty of a block sealed by any other signer.
+const DIFFICULTY_NO_TURN: u64 = 1;
+/// Number of recent snapshots kept in memory.
+const SNAPSHOT_CACHE_SIZE: usize = 128;
+
+/// Hash of the header signed by the signer: everything but the signature itself.
+fn seal_hash(header: &Header) -> H256 {
+	let mut header = header.clone();
+	let unsigned_len = header.extra_data().len().saturating_sub(EXTRA_SEAL);
+	header.extra_data_mut().truncate(unsigned_len);
+	header.rlp_sha3(::basic_types::Seal::With)
+}
+
+fn check_extra_data(header: &Header) -> Result<(), Error> {
+	let len = header.extra_data().len();
+	if len < EXTRA_VANITY + EXTRA_SEAL {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: Some(EXTRA_VANITY + EXTRA_SEAL), max: None, found: len }).into());
+	}
+	Ok(())
+}
+
+fn header_signer(header: &Header) -> Result<Address, Error> {
+	check_extra_data(header)?;
+	let extra = header.extra_data();
+	let signature = Signature::from(H520::from_slice(&extra[extra.len() - EXTRA_SEAL..]));
+	Ok(public_to_address(&recover(&signature, &seal_hash(header))?))
+}
+
+fn checkpoint_signers(header: &Header) -> Result<Vec<Address>, Error> {
+	check_extra_data(header)?;
+	let extra = header.extra_data();
+	let signers = &extra[EXTRA_VANITY..extra.len() - EXTRA_SEAL];
+	if signers.len() % 20 != 0 {
+		trace!(target: "engine", "checkpoint_signers: signer list of {} bytes", signers.len());
+		return Err(BlockError::InvalidSeal.into());
+	}
+	Ok(signers.chunks(20).map(Address::from_slice).collect())
+}
+
+fn header_vote(header: &Header) -> Result<(Address, bool), Error> {
+	let nonce: H64 = UntrustedRlp::new(&header.seal().get(1).expect("was checked with verify_block_basic; has 2 fields; qed")).as_val()?;
+	match nonce.low_u64() {
+		NONCE_AUTH => Ok((header.author().clone(), true)),
+		NONCE_DROP => Ok((header.author().clone(), false)),
+		_ => {
+			trace!(target: "engine", "header_vote: invalid vote nonce {}", nonce);
+			Err(BlockError::InvalidSeal.into())
+		},
+	}
+}
+
+/// Engine using Clique proof-of-authority consensus with in-header signer voting.
+pub struct Clique {
+	params: CommonParams,
+	gas_limit_bound_divisor: U256,
+	period: u64,
+	epoch: u64,
+	builtins: BTreeMap<Address, Builtin>,
+	client: RwLock<Option<Weak<Client>>>,
+	signer: EngineSigner,
+	snapshots: Mutex<LruCache<H256, Snapshot>>,
+	proposals: RwLock<HashMap<Address, bool>>,
+}
+
+impl Clique {
+	/// Create a new instance of Clique engine.
+	pub fn new(params: CommonParams, our_params: CliqueParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		Clique {
+			params: params,
+			gas_limit_bound_divisor: our_params.gas_limit_bound_divisor,
+			period: our_params.period,
+			epoch: our_params.epoch,
+			builtins: builtins,
+			client: RwLock::new(None),
+			signer: Default::default(),
+			snapshots: Mutex::new(LruCache::new(SNAPSHOT_CACHE_SIZE)),
+			proposals: RwLock::new(HashMap::new()),
+		}
+	}
+
+	fn is_checkpoint(&self, number: BlockNumber) -> bool {
+		number % self.epoch == 0
+	}
+
+	fn parent_header(&self, header: &Header) -> Result<Header, Error> {
+		self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Hash(*header.parent_hash())))
+			.map(|h| h.decode())
+			.ok_or_else(|| BlockError::UnknownParent(*header.parent_hash()).into())
+	}
+
+	/// Signer set and votes as of the given block. Walks back to the last cached snapshot or
+	/// checkpoint and replays the signatures and votes of the blocks on top of it.
+	fn snapshot(&self, header: &Header) -> Result<Snapshot, Error> {
+		let mut pending = Vec::new();
+		let mut current = header.clone();
+		let mut snapshot = loop {
+			if let Some(snapshot) = self.snapshots.lock().get_mut(&current.hash()) {
+				break snapshot.clone();
+			}
+			if self.is_checkpoint(current.number()) {
+				let snapshot = Snapshot::checkpoint(current.number(), current.hash(), checkpoint_signers(&current)?);
+				self.snapshots.lock().insert(snapshot.hash, snapshot.clone());
+				break snapshot;
+			}
+			let parent = self.parent_header(&current)?;
+			pending.push(current);
+			current = parent;
+		};
+
+		for header in pending.into_iter().rev() {
+			snapshot.apply(header.number(), header.hash(), header_signer(&header)?, Some(header_vote(&header)?), self.epoch)?;
+			self.snapshots.lock().insert(snapshot.hash, snapshot.clone());
+		}
+		Ok(snapshot)
+	}
+
+	/// Snapshot as of the given block hash, if it is known.
+	fn snapshot_at(&self, hash: &H256) -> Result<Snapshot, Error> {
+		if let Some(snapshot) = self.snapshots.lock().get_mut(hash) {
+			return Ok(snapshot.clone());
+		}
+		let header = self.client.read().as_ref()
+			.and_then(Weak::upgrade)
+			.and_then(|c| c.block_header(BlockId::Hash(*hash)))
+			.map(|h| h.decode());
+		match header {
+			Some(header) => self.snapshot(&header),
+			None => Err(BlockError::UnknownParent(*hash).into()),
+		}
+	}
+}
+
+impl Engine for Clique {
+	fn name(&self) -> &str { "Clique" }
+
+	fn version(&self) -> SemanticVersion { SemanticVersion::new(1, 0, 0) }
+
+	/// Two fields - mix hash (always zero) and the vote nonce.
+	fn seal_fields(&self) -> usize { 2 }
+
+	fn params(&self) -> &CommonParams { &self.params }
+
+	fn additional_params(&self) -> HashMap<String, String> {
+		hash_map![
+			"period".to_owned() => self.period.to_string(),
+			"epoch".to_owned() => self.epoch.to_string()
+		]
+	}
+
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> { &self.builtins }
+
+	/// Checkpoints list all signers in the extra data; its layout is checked in `verify_block_basic`.
+	fn maximum_extra_data_size(&self) -> usize { usize::max_value() }
+
+	fn maximum_uncle_count(&self) -> usize { 0 }
+
+	/// Additional engine-specific information for the user/developer concerning `header`.
+	fn extra_info(&self, header: &Header) -> BTreeMap<String, String> {
+		map![
+			"signer".into() => header_signer(header).as_ref().map(ToString::to_string).unwrap_or("".into())
+		]
+	}
+
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params().update_schedule(env_info.number, &mut schedule);
+		schedule
+	}
+
+	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, _gas_ceil_target: U256) {
+		header.set_gas_limit({
+			let gas_limit = parent.gas_limit().clone();
+			let bound_divisor = self.gas_limit_bound_divisor;
+			if gas_limit < gas_floor_target {
+				min(gas_floor_target, gas_limit + gas_limit / bound_divisor - 1.into())
+			} else {
+				max(gas_floor_target, gas_limit - gas_limit / bound_divisor + 1.into())
+			}
+		});
+		let timestamp = max(header.timestamp(), parent.timestamp() + self.period);
+		header.set_timestamp(timestamp);
+
+		let signer = self.signer.address();
+		let snapshot = if signer == Address::default() {
+			None
+		} else {
+			self.snapshot(parent).map_err(|e| warn!(target: "engine", "Unable to determine signers at block {}: {}", parent.number(), e)).ok()
+		};
+
+		let mut extra = header.extra_data().clone();
+		extra.resize(EXTRA_VANITY, 0);
+		let mut author = Address::default();
+		let mut difficulty = DIFFICULTY_NO_TURN;
+		if let Some(snapshot) = snapshot {
+			if self.is_checkpoint(header.number()) {
+				for s in &snapshot.signers {
+					extra.extend_from_slice(s);
+				}
+			} else if let Some((address, _)) = self.proposals.read().iter().find(|&(a, auth)| snapshot.is_valid_vote(a, *auth)) {
+				author = address.clone();
+			}
+			if snapshot.is_in_turn(header.number(), &signer) {
+				difficulty = DIFFICULTY_IN_TURN;
+			}
+		}
+		extra.resize(extra.len() + EXTRA_SEAL, 0);
+
+		header.set_extra_data(extra);
+		header.set_author(author);
+		header.set_difficulty(difficulty.into());
+	}
+
+	fn seals_internally(&self) -> Option<bool> {
+		Some(self.signer.address() != Address::default())
+	}
+
+	/// Attempt to seal the block internally.
+	///
+	/// Signs the block if the node is an authorized signer that did not seal any of the most recent blocks.
+	fn generate_seal(&self, block: &ExecutedBlock) -> Seal {
+		let header = block.header();
+		// Wait for the block period to pass.
+		if self.period > 0 && header.timestamp() > get_time().sec as u64 { return Seal::None; }
+
+		let signer = self.signer.address();
+		let snapshot = match self.snapshot_at(header.parent_hash()) {
+			Ok(snapshot) => snapshot,
+			Err(e) => {
+				warn!(target: "engine", "generate_seal: Unable to determine signers: {}", e);
+				return Seal::None;
+			},
+		};
+		if !snapshot.signers.contains(&signer) {
+			trace!(target: "engine", "generate_seal: {} is not an authorized signer.", signer);
+			return Seal::None;
+		}
+		if snapshot.is_recent(header.number(), &signer) {
+			trace!(target: "engine", "generate_seal: {} signed one of the recent blocks.", signer);
+			return Seal::None;
+		}
+
+		let authorize = self.proposals.read().get(header.author()).cloned().unwrap_or(false);
+		let nonce = H64::from(if authorize { NONCE_AUTH } else { NONCE_DROP });
+		let seal = vec![encode(&H256::default()).to_vec(), encode(&nonce).to_vec()];
+
+		let mut unsigned = header.clone();
+		unsigned.set_seal(seal.clone());
+		match self.signer.sign(seal_hash(&unsigned)) {
+			Ok(signature) => {
+				trace!(target: "engine", "generate_seal: Signing block {}.", header.number());
+				let mut seal = seal;
+				seal.push(H520::from(signature).to_vec());
+				Seal::Regular(seal)
+			},
+			Err(e) => {
+				warn!(target: "engine", "generate_seal: FAIL: Accounts secret key unavailable: {}", e);
+				Seal::None
+			},
+		}
+	}
+
+	/// The signature produced by `generate_seal` goes into the end of the extra data.
+	fn apply_seal(&self, header: &mut Header, mut seal: Vec<Bytes>) {
+		let signed = seal.len() == self.seal_fields() + 1
+			&& seal[self.seal_fields()].len() == EXTRA_SEAL
+			&& header.extra_data().len() >= EXTRA_VANITY + EXTRA_SEAL;
+		if signed {
+			let signature = seal.pop().expect("seal has an extra field; qed");
+			let start = header.extra_data().len() - EXTRA_SEAL;
+			header.extra_data_mut()[start..].copy_from_slice(&signature);
+		}
+		header.set_seal(seal);
+	}
+
+	/// Check the seal fields, extra data layout and difficulty.
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		if header.seal().len() != self.seal_fields() {
+			trace!(target: "engine", "verify_block_basic: wrong number of seal fields");
+			return Err(BlockError::InvalidSealArity(
+				Mismatch { expected: self.seal_fields(), found: header.seal().len() }
+			).into());
+		}
+
+		let signers = checkpoint_signers(header)?;
+		let checkpoint = self.is_checkpoint(header.number());
+		if checkpoint != !signers.is_empty() {
+			trace!(target: "engine", "verify_block_basic: {} signers listed in block {}", signers.len(), header.number());
+			return Err(BlockError::InvalidSeal.into());
+		}
+
+		let (address, authorize) = header_vote(header)?;
+		if checkpoint && (address != Address::default() || authorize) {
+			trace!(target: "engine", "verify_block_basic: vote in checkpoint block {}", header.number());
+			return Err(BlockError::InvalidSeal.into());
+		}
+
+		let mix_hash: H256 = UntrustedRlp::new(&header.seal()[0]).as_val()?;
+		if mix_hash != H256::default() {
+			return Err(BlockError::MismatchedH256SealElement(Mismatch { expected: H256::default(), found: mix_hash }).into());
+		}
+
+		if *header.uncles_hash() != SHA3_EMPTY_LIST_RLP {
+			return Err(BlockError::InvalidUnclesHash(Mismatch { expected: SHA3_EMPTY_LIST_RLP, found: *header.uncles_hash() }).into());
+		}
+
+		let difficulty = *header.difficulty();
+		if difficulty != DIFFICULTY_IN_TURN.into() && difficulty != DIFFICULTY_NO_TURN.into() {
+			return Err(BlockError::DifficultyOutOfBounds(OutOfBounds { min: Some(DIFFICULTY_NO_TURN.into()), max: Some(DIFFICULTY_IN_TURN.into()), found: difficulty }).into());
+		}
+		Ok(())
+	}
+
+	/// Check that the signature recovers.
+	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		header_signer(header).map(|_| ())
+	}
+
+	/// Check the signer against the signers as of the parent and tally the block's vote.
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		if header.timestamp() < parent.timestamp() + self.period {
+			return Err(BlockError::InvalidTimestamp(OutOfBounds { min: Some(parent.timestamp() + self.period), max: None, found: header.timestamp() }).into());
+		}
+
+		let gas_limit_divisor = self.gas_limit_bound_divisor;
+		let min_gas = parent.gas_limit().clone() - parent.gas_limit().clone() / gas_limit_divisor;
+		let max_gas = parent.gas_limit().clone() + parent.gas_limit().clone() / gas_limit_divisor;
+		if header.gas_limit() <= &min_gas || header.gas_limit() >= &max_gas {
+			return Err(From::from(BlockError::InvalidGasLimit(OutOfBounds { min: Some(min_gas), max: Some(max_gas), found: header.gas_limit().clone() })));
+		}
+
+		let mut snapshot = self.snapshot(parent)?;
+		let signer = header_signer(header)?;
+		let difficulty = if snapshot.is_in_turn(header.number(), &signer) { DIFFICULTY_IN_TURN } else { DIFFICULTY_NO_TURN };
+		if *header.difficulty() != difficulty.into() {
+			return Err(BlockError::InvalidDifficulty(Mismatch { expected: difficulty.into(), found: *header.difficulty() }).into());
+		}
+
+		if self.is_checkpoint(header.number()) {
+			let listed = checkpoint_signers(header)?;
+			if !listed.iter().eq(snapshot.signers.iter()) {
+				trace!(target: "engine", "verify_block_family: checkpoint {} lists wrong signers", header.number());
+				return Err(BlockError::InvalidSeal.into());
+			}
+		}
+
+		snapshot.apply(header.number(), header.hash(), signer, Some(header_vote(header)?), self.epoch)?;
+		self.snapshots.lock().insert(snapshot.hash, snapshot);
+		Ok(())
+	}
+
+	fn register_client(&self, client: Weak<Client>) {
+		*self.client.write() = Some(client);
+	}
+
+	fn set_signer(&self, ap: Arc<AccountProvider>, address: Address, password: String) {
+		self.signer.set(ap, address, password);
+	}
+
+	fn propose_signer(&self, address: Address, authorize: bool) -> bool {
+		self.proposals.write().insert(address, authorize);
+		true
+	}
+
+	fn discard_signer_proposal(&self, address: &Address) -> bool {
+		self.proposals.write().remove(address);
+		true
+	}
+
+	fn sign(&self, hash: H256) -> Result<Signature, Error> {
+		self.signer.sign(hash).map_err(Into::into)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use header::Header;
+	use error::{Error, BlockError};
+	use ethkey::Secret;
+	use block::*;
+	use tests::helpers::*;
+	use account_provider::AccountProvider;
+	use spec::Spec;
+	use engines::{Seal, EngineError};
+	use super::{header_signer, EXTRA_VANITY, EXTRA_SEAL};
+
+	#[test]
+	fn has_valid_metadata() {
+		let engine = Spec::new_test_clique().engine;
+		assert!(!engine.name().is_empty());
+		assert!(engine.version().major >= 1);
+	}
+
+	#[test]
+	fn verification_fails_on_short_seal() {
+		let engine = Spec::new_test_clique().engine;
+		let header: Header = Header::default();
+
+		let verify_result = engine.verify_block_basic(&header, None);
+
+		match verify_result {
+			Err(Error::Block(BlockError::InvalidSealArity(_))) => {},
+			Err(_) => { panic!("should be block seal-arity mismatch error (got {:?})", verify_result); },
+			_ => { panic!("Should be error, got Ok"); },
+		}
+	}
+
+	#[test]
+	fn seals_blocks_in_extra_data() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr0 = tap.insert_account(Secret::from_slice(&"0".sha3()).unwrap(), "0").unwrap();
+		let addr1 = tap.insert_account(Secret::from_slice(&"1".sha3()).unwrap(), "1").unwrap();
+
+		let spec = Spec::new_test_clique();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		// Signers are sorted by address, "1".sha3() is in turn for block 1.
+		engine.set_signer(tap.clone(), addr1, "1".into());
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes.clone(), addr1, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(*b.header().difficulty(), 2.into());
+		assert_eq!(*b.header().author(), Address::default());
+		let seal = match engine.generate_seal(b.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in turn signer should seal"),
+		};
+		let b1 = b.seal(engine, seal).unwrap();
+		let header1 = b1.header().clone();
+		assert_eq!(header1.extra_data().len(), EXTRA_VANITY + EXTRA_SEAL);
+		assert_eq!(header_signer(&header1).unwrap(), addr1);
+		assert!(engine.verify_block_seal(&header1).is_ok());
+		assert!(engine.verify_block_family(&header1, &genesis_header, None).is_ok());
+
+		// The same signer has to wait for the other one.
+		// Blocks carry no rewards, so the state stays the genesis one.
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let b = OpenBlock::new(engine, Default::default(), false, db, &header1, last_hashes.clone(), addr1, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(engine.generate_seal(b.block()), Seal::None);
+
+		engine.set_signer(tap, addr0, "0".into());
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let b = OpenBlock::new(engine, Default::default(), false, db, &header1, last_hashes, addr0, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		let seal = match engine.generate_seal(b.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in turn signer should seal"),
+		};
+		let header2 = b.seal(engine, seal).unwrap().header().clone();
+		assert_eq!(header_signer(&header2).unwrap(), addr0);
+		assert!(engine.verify_block_family(&header2, &header1, None).is_ok());
+	}
+
+	#[test]
+	fn votes_on_proposed_signers() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr1 = tap.insert_account(Secret::from_slice(&"1".sha3()).unwrap(), "1").unwrap();
+		let candidate = Address::from(5);
+
+		let spec = Spec::new_test_clique();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		engine.set_signer(tap, addr1, "1".into());
+
+		assert!(engine.propose_signer(candidate, true));
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes.clone(), addr1, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(*b.header().author(), candidate);
+		let seal = match engine.generate_seal(b.block()) {
+			Seal::Regular(seal) => seal,
+			_ => panic!("in turn signer should seal"),
+		};
+		let header = b.seal(engine, seal).unwrap().header().clone();
+		assert_eq!(super::header_vote(&header).unwrap(), (candidate, true));
+
+		assert!(engine.discard_signer_proposal(&candidate));
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, addr1, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		assert_eq!(*b.close_and_lock().header().author(), Address::default());
+	}
+
+	#[test]
+	fn rejects_unauthorized_signer() {
+		let tap = Arc::new(AccountProvider::transient_provider());
+		let addr = tap.insert_account(Secret::from_slice(&"2".sha3()).unwrap(), "2").unwrap();
+
+		let spec = Spec::new_test_clique();
+		let engine = &*spec.engine;
+		let genesis_header = spec.genesis_header();
+		let db = spec.ensure_db_good(get_temp_state_db().take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+
+		engine.set_signer(tap.clone(), addr, "2".into());
+		let b = OpenBlock::new(engine, Default::default(), false, db, &genesis_header, last_hashes, addr, (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close_and_lock();
+		assert_eq!(engine.generate_seal(b.block()), Seal::None);
+
+		// Sign it anyway.
+		let mut header = b.header().clone();
+		header.set_seal(vec![::rlp::encode(&H256::default()).to_vec(), ::rlp::encode(&H64::default()).to_vec()]);
+		let signature = tap.sign(addr, Some("2".into()), super::seal_hash(&header)).unwrap();
+		let mut seal = header.seal().to_vec();
+		seal.push(H520::from(signature).to_vec());
+		engine.apply_seal(&mut header, seal);
+
+		assert!(engine.verify_block_seal(&header).is_ok());
+		match engine.verify_block_family(&header, &genesis_header, None) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, addr),
+			other => panic!("should be unauthorized signer error (got {:?})", other),
+		}
+	}
+
+	#[test]
+	fn signer_list_only_in_checkpoints() {
+		let engine = Spec::new_test_clique().engine;
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_difficulty(1.into());
+		header.set_seal(vec![::rlp::encode(&H256::default()).to_vec(), ::rlp::encode(&H64::default()).to_vec()]);
+		header.set_extra_data(vec![0; EXTRA_VANITY + EXTRA_SEAL]);
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+
+		header.set_extra_data(vec![0; EXTRA_VANITY + 20 + EXTRA_SEAL]);
+		assert!(engine.verify_block_basic(&header, None).is_err());
+
+		// Votes take the nonce of all ones or all zeros.
+		header.set_extra_data(vec![0; EXTRA_VANITY + EXTRA_SEAL]);
+		header.set_seal(vec![::rlp::encode(&H256::default()).to_vec(), ::rlp::encode(&H64::from(1)).to_vec()]);
+		assert!(engine.verify_block_basic(&header, None).is_err());
+	}
+}
@@ -0,0 +1,45 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Clique specific parameters.
+
+use ethjson;
+use util::U256;
+
+/// Number of blocks after which votes are reset if the spec leaves it out.
+const DEFAULT_EPOCH: u64 = 30000;
+
+/// `Clique` params.
+#[derive(Debug, PartialEq)]
+pub struct CliqueParams {
+	/// Gas limit divisor.
+	pub gas_limit_bound_divisor: U256,
+	/// Minimum number of seconds between consecutive blocks.
+	pub period: u64,
+	/// Number of blocks after which votes are reset and the signer list is checkpointed.
+	pub epoch: u64,
+}
+
+impl From<ethjson::spec::CliqueParams> for CliqueParams {
+	fn from(p: ethjson::spec::CliqueParams) -> Self {
+		let epoch = p.epoch.map_or(DEFAULT_EPOCH, Into::into);
+		CliqueParams {
+			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
+			period: p.period.map_or(0, Into::into),
+			epoch: if epoch == 0 { DEFAULT_EPOCH } else { epoch },
+		}
+	}
+}
@@ -0,0 +1,335 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signer set and vote tally of a Clique chain as of a given block.
+
+use std::collections::{BTreeMap, BTreeSet};
+use util::{Address, H256, Mismatch};
+use error::{Error, BlockError};
+use header::BlockNumber;
+use engines::EngineError;
+
+/// A vote of a signer on adding or removing a signer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vote {
+	/// Signer who cast the vote.
+	pub signer: Address,
+	/// Account voted on.
+	pub address: Address,
+	/// Whether the account should be authorized or deauthorized.
+	pub authorize: bool,
+}
+
+/// Signer set and votes of an epoch, as of a given block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+	/// Number of the block the snapshot was taken at.
+	pub number: BlockNumber,
+	/// Hash of the block the snapshot was taken at.
+	pub hash: H256,
+	/// Authorized signers.
+	pub signers: BTreeSet<Address>,
+	/// Signers of recent blocks by block number, to prevent spamming.
+	pub recents: BTreeMap<BlockNumber, Address>,
+	/// Votes cast in the current epoch, in chronological order.
+	pub votes: Vec<Vote>,
+}
+
+impl Snapshot {
+	/// Create a snapshot of a checkpoint block listing the given signers.
+	pub fn checkpoint(number: BlockNumber, hash: H256, signers: Vec<Address>) -> Self {
+		Snapshot {
+			number: number,
+			hash: hash,
+			signers: signers.into_iter().collect(),
+			recents: BTreeMap::new(),
+			votes: Vec::new(),
+		}
+	}
+
+	/// Whether the signer is the one expected to seal the block with the given number.
+	pub fn is_in_turn(&self, number: BlockNumber, signer: &Address) -> bool {
+		if self.signers.is_empty() { return false; }
+		let index = (number % self.signers.len() as u64) as usize;
+		self.signers.iter().nth(index) == Some(signer)
+	}
+
+	/// Whether the signer sealed one of the most recent blocks and has to leave the next one to others.
+	pub fn is_recent(&self, number: BlockNumber, signer: &Address) -> bool {
+		let limit = self.recent_limit();
+		self.recents.iter().any(|(n, s)| s == signer && (number < limit || *n > number - limit))
+	}
+
+	/// Number of votes an account has collected to be (de)authorized.
+	pub fn tally(&self, address: &Address) -> usize {
+		self.votes.iter().filter(|v| v.address == *address).count()
+	}
+
+	/// Direction of the votes cast on an account, if there are any. Votes against the direction
+	/// of the pending ones aren't cast, so all votes on an account agree.
+	pub fn tally_direction(&self, address: &Address) -> Option<bool> {
+		self.votes.iter().find(|v| v.address == *address).map(|v| v.authorize)
+	}
+
+	/// Whether a vote on the account would change anything.
+	pub fn is_valid_vote(&self, address: &Address, authorize: bool) -> bool {
+		self.signers.contains(address) != authorize
+	}
+
+	/// Apply a block sealed by `signer` on top of this snapshot. `vote` is the account voted on along
+	/// with the direction of the vote, if the block carries one.
+	pub fn apply(&mut self, number: BlockNumber, hash: H256, signer: Address, vote: Option<(Address, bool)>, epoch: u64) -> Result<(), Error> {
+		if number != self.number + 1 {
+			return Err(BlockError::InvalidNumber(Mismatch { expected: self.number + 1, found: number }).into());
+		}
+
+		// Votes are reset on every checkpoint.
+		if number % epoch == 0 {
+			self.votes.clear();
+		}
+
+		// Drop the oldest signer from the recent list to let them seal again.
+		let limit = self.recent_limit();
+		if number >= limit {
+			self.recents.remove(&(number - limit));
+		}
+
+		if !self.signers.contains(&signer) {
+			return Err(EngineError::NotAuthorized(signer).into());
+		}
+		if self.recents.values().any(|s| *s == signer) {
+			return Err(EngineError::DoubleVote(signer).into());
+		}
+		self.recents.insert(number, signer);
+
+		if let Some((address, authorize)) = vote {
+			// A new vote of the signer on the same account replaces the old one.
+			self.votes.retain(|v| v.signer != signer || v.address != address);
+			if self.is_valid_vote(&address, authorize) && self.tally_direction(&address).map_or(true, |d| d == authorize) {
+				self.votes.push(Vote { signer: signer, address: address, authorize: authorize });
+			}
+
+			// The tally decides the change, not the vote of this block: a tally which reached the
+			// majority after the signer set shrunk is executed by any vote touching the account.
+			match self.tally_direction(&address) {
+				Some(authorize) if self.tally(&address) > self.signers.len() / 2 => {
+					if authorize {
+						self.signers.insert(address);
+					} else {
+						self.signers.remove(&address);
+
+						// The signer set shrunk; free up a recent slot and discard the votes of the removed signer.
+						let limit = self.recent_limit();
+						if number >= limit {
+							self.recents.remove(&(number - limit));
+						}
+						self.votes.retain(|v| v.signer != address);
+					}
+					self.votes.retain(|v| v.address != address);
+				},
+				_ => {},
+			}
+		}
+
+		self.number = number;
+		self.hash = hash;
+		Ok(())
+	}
+
+	fn recent_limit(&self) -> u64 {
+		(self.signers.len() / 2 + 1) as u64
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, H256};
+	use super::Snapshot;
+
+	const EPOCH: u64 = 30000;
+
+	fn signers() -> Vec<Address> {
+		vec![Address::from(1), Address::from(2), Address::from(3)]
+	}
+
+	#[test]
+	fn signers_take_turns() {
+		let snapshot = Snapshot::checkpoint(0, H256::default(), signers());
+		assert!(snapshot.is_in_turn(1, &Address::from(2)));
+		assert!(snapshot.is_in_turn(3, &Address::from(1)));
+		assert!(!snapshot.is_in_turn(3, &Address::from(2)));
+	}
+
+	#[test]
+	fn rejects_unauthorized_and_recent_signers() {
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), signers());
+		assert!(snapshot.clone().apply(1, H256::from(1), Address::from(4), None, EPOCH).is_err());
+
+		snapshot.apply(1, H256::from(1), Address::from(1), None, EPOCH).unwrap();
+		assert!(snapshot.is_recent(2, &Address::from(1)));
+		assert!(snapshot.clone().apply(2, H256::from(2), Address::from(1), None, EPOCH).is_err());
+
+		snapshot.apply(2, H256::from(2), Address::from(2), None, EPOCH).unwrap();
+		// With three signers, a signer may seal again after one other block.
+		assert!(!snapshot.is_recent(3, &Address::from(1)));
+		snapshot.apply(3, H256::from(3), Address::from(1), None, EPOCH).unwrap();
+	}
+
+	#[test]
+	fn majority_authorizes_signer() {
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), signers());
+		let candidate = Address::from(4);
+
+		snapshot.apply(1, H256::from(1), Address::from(1), Some((candidate, true)), EPOCH).unwrap();
+		assert_eq!(snapshot.tally(&candidate), 1);
+		assert!(!snapshot.signers.contains(&candidate));
+
+		snapshot.apply(2, H256::from(2), Address::from(2), Some((candidate, true)), EPOCH).unwrap();
+		assert!(snapshot.signers.contains(&candidate));
+		assert!(snapshot.votes.is_empty());
+	}
+
+	#[test]
+	fn majority_deauthorizes_signer() {
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), vec![Address::from(1), Address::from(2), Address::from(3), Address::from(4)]);
+		let target = Address::from(4);
+
+		snapshot.apply(1, H256::from(1), target, Some((Address::from(1), false)), EPOCH).unwrap();
+		snapshot.apply(2, H256::from(2), Address::from(1), Some((target, false)), EPOCH).unwrap();
+		snapshot.apply(3, H256::from(3), Address::from(2), Some((target, false)), EPOCH).unwrap();
+		assert!(snapshot.signers.contains(&target));
+
+		snapshot.apply(4, H256::from(4), Address::from(3), Some((target, false)), EPOCH).unwrap();
+		assert!(!snapshot.signers.contains(&target));
+		assert_eq!(snapshot.tally(&target), 0);
+		// Votes cast by the removed signer are discarded as well.
+		assert_eq!(snapshot.tally(&Address::from(1)), 0);
+	}
+
+	#[test]
+	fn repeated_and_pointless_votes_do_not_count() {
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), vec![Address::from(1), Address::from(2), Address::from(3), Address::from(4)]);
+		let candidate = Address::from(5);
+
+		snapshot.apply(1, H256::from(1), Address::from(1), Some((candidate, true)), EPOCH).unwrap();
+		snapshot.apply(2, H256::from(2), Address::from(2), Some((Address::from(3), true)), EPOCH).unwrap();
+		snapshot.apply(3, H256::from(3), Address::from(3), None, EPOCH).unwrap();
+		snapshot.apply(4, H256::from(4), Address::from(1), Some((candidate, true)), EPOCH).unwrap();
+
+		assert_eq!(snapshot.tally(&candidate), 1);
+		assert_eq!(snapshot.tally(&Address::from(3)), 0);
+	}
+
+	#[test]
+	fn votes_reset_on_checkpoint() {
+		let epoch = 3;
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), signers());
+		let candidate = Address::from(4);
+
+		snapshot.apply(1, H256::from(1), Address::from(1), Some((candidate, true)), epoch).unwrap();
+		snapshot.apply(2, H256::from(2), Address::from(2), None, epoch).unwrap();
+		snapshot.apply(3, H256::from(3), Address::from(3), None, epoch).unwrap();
+		assert_eq!(snapshot.tally(&candidate), 0);
+
+		snapshot.apply(4, H256::from(4), Address::from(2), Some((candidate, true)), epoch).unwrap();
+		assert!(!snapshot.signers.contains(&candidate));
+	}
+
+	// Voting scenarios of the Clique specification (EIP-225). Signers are named by letters and
+	// seal consecutive blocks; `None` stands for a block without a vote.
+	fn run_scenario(signers: &str, blocks: &[(char, Option<(char, bool)>)], epoch: u64) -> Vec<Address> {
+		let address = |c: char| Address::from(c as u64);
+		let mut snapshot = Snapshot::checkpoint(0, H256::default(), signers.chars().map(&address).collect());
+		for (i, &(signer, vote)) in blocks.iter().enumerate() {
+			let number = i as u64 + 1;
+			snapshot.apply(number, H256::from(number), address(signer), vote.map(|(c, auth)| (address(c), auth)), epoch).unwrap();
+		}
+		snapshot.signers.into_iter().collect()
+	}
+
+	fn named(signers: &str) -> Vec<Address> {
+		signers.chars().map(|c| Address::from(c as u64)).collect()
+	}
+
+	#[test]
+	fn eip225_voting_scenarios() {
+		let (auth, drop) = (true, false);
+		let scenarios: Vec<(&str, Vec<(char, Option<(char, bool)>)>, &str)> = vec![
+			// Single signer, no votes cast.
+			("A", vec![('A', None)], "A"),
+			// Single signer, voting to add two others (only accept first, second needs 2 votes).
+			("A", vec![('A', Some(('B', auth))), ('B', None), ('A', Some(('C', auth)))], "AB"),
+			// Two signers, voting to add three others (only accept first two, third needs 3 votes already).
+			("AB", vec![
+				('A', Some(('C', auth))), ('B', Some(('C', auth))), ('A', Some(('D', auth))), ('B', Some(('D', auth))),
+				('C', None), ('A', Some(('E', auth))), ('B', Some(('E', auth))),
+			], "ABCD"),
+			// Single signer, dropping itself.
+			("A", vec![('A', Some(('A', drop)))], ""),
+			// Two signers, actually needing mutual consent to drop either of them (not fulfilled).
+			("AB", vec![('A', Some(('B', drop)))], "AB"),
+			// Two signers, actually needing mutual consent to drop either of them (fulfilled).
+			("AB", vec![('A', Some(('B', drop))), ('B', Some(('B', drop)))], "A"),
+			// Three signers, two of them deciding to drop the third.
+			("ABC", vec![('A', Some(('C', drop))), ('B', Some(('C', drop)))], "AB"),
+			// Four signers, consensus of two not being enough to drop anyone.
+			("ABCD", vec![('A', Some(('C', drop))), ('B', Some(('C', drop)))], "ABCD"),
+			// Four signers, consensus of three already being enough to drop someone.
+			("ABCD", vec![('A', Some(('D', drop))), ('B', Some(('D', drop))), ('C', Some(('D', drop)))], "ABC"),
+			// Authorizations are counted once per signer per target.
+			("AB", vec![('A', Some(('C', auth))), ('B', None), ('A', Some(('C', auth))), ('B', None), ('A', Some(('C', auth)))], "AB"),
+			// Authorizing multiple accounts concurrently is permitted.
+			("AB", vec![
+				('A', Some(('C', auth))), ('B', None), ('A', Some(('D', auth))), ('B', None),
+				('A', None), ('B', Some(('D', auth))), ('A', None), ('B', Some(('C', auth))),
+			], "ABCD"),
+			// Deauthorizations are counted once per signer per target.
+			("AB", vec![('A', Some(('B', drop))), ('B', None), ('A', Some(('B', drop))), ('B', None), ('A', Some(('B', drop)))], "AB"),
+			// Deauthorizing multiple accounts concurrently is permitted.
+			("ABCD", vec![
+				('A', Some(('C', drop))), ('B', None), ('C', None), ('A', Some(('D', drop))), ('B', None), ('C', None),
+				('A', None), ('B', Some(('D', drop))), ('C', Some(('D', drop))), ('A', None), ('B', Some(('C', drop))),
+			], "AB"),
+			// Votes from deauthorized signers are discarded immediately (deauth votes).
+			("ABC", vec![('C', Some(('B', drop))), ('A', Some(('C', drop))), ('B', Some(('C', drop))), ('A', Some(('B', drop)))], "AB"),
+			// Votes from deauthorized signers are discarded immediately (auth votes).
+			("ABC", vec![('C', Some(('D', auth))), ('A', Some(('C', drop))), ('B', Some(('C', drop))), ('A', Some(('D', auth)))], "AB"),
+			// Cascading changes are not allowed, only the account being voted on may change.
+			("ABCD", vec![
+				('A', Some(('C', drop))), ('B', None), ('C', None), ('A', Some(('D', drop))), ('B', Some(('C', drop))),
+				('C', None), ('A', None), ('B', Some(('D', drop))), ('C', Some(('D', drop))),
+			], "ABC"),
+			// Changes reaching consensus out of bounds (via a deauth) execute on touch.
+			("ABCD", vec![
+				('A', Some(('C', drop))), ('B', None), ('C', None), ('A', Some(('D', drop))), ('B', Some(('C', drop))),
+				('C', None), ('A', None), ('B', Some(('D', drop))), ('C', Some(('D', drop))), ('A', None), ('C', Some(('C', auth))),
+			], "AB"),
+			// Changes reaching consensus out of bounds (via a deauth) may go out of consensus on first touch.
+			("ABCD", vec![
+				('A', Some(('C', drop))), ('B', None), ('C', None), ('A', Some(('D', drop))), ('B', Some(('C', drop))),
+				('C', None), ('A', None), ('B', Some(('D', drop))), ('C', Some(('D', drop))), ('A', None), ('B', Some(('C', auth))),
+			], "ABC"),
+		];
+
+		for (i, (signers, blocks, result)) in scenarios.into_iter().enumerate() {
+			assert_eq!(run_scenario(signers, &blocks, EPOCH), named(result), "scenario {}", i);
+		}
+
+		// Epoch transitions reset all votes to allow chain checkpointing.
+		let blocks = vec![('A', Some(('C', true))), ('B', None), ('A', None), ('B', Some(('C', true)))];
+		assert_eq!(run_scenario("AB", &blocks, 3), named("AB"));
+	}
+}
@@ -0,0 +1,196 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashSet};
+use util::{Address, H520, Hashable, HashMap, Mismatch, OutOfBounds};
+use ethkey::{recover, public_to_address, Signature};
+use builtin::Builtin;
+use engines::{Engine, EngineError};
+use env_info::EnvInfo;
+use error::{Error, BlockError};
+use header::Header;
+use spec::CommonParams;
+use evm::Schedule;
+use basic_types::Seal as RlpSeal;
+use ethjson;
+
+/// Number of extra data bytes taken by the producer's signature.
+const SIGNATURE_LENGTH: usize = 65;
+
+/// `ExternalSeal` params.
+#[derive(Debug, PartialEq)]
+pub struct ExternalSealParams {
+	/// Accounts authorized to produce blocks.
+	pub producers: Vec<Address>,
+}
+
+impl From<ethjson::spec::ExternalSealParams> for ExternalSealParams {
+	fn from(p: ethjson::spec::ExternalSealParams) -> Self {
+		ExternalSealParams {
+			producers: p.producers.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// An engine for chains whose blocks are produced by a separate consensus layer.
+/// The seal is not checked; instead every block has to carry a signature of one of the
+/// authorized producers over the header without the seal in the last 65 bytes of its extra data.
+pub struct ExternalSeal {
+	params: CommonParams,
+	producers: HashSet<Address>,
+	builtins: BTreeMap<Address, Builtin>,
+}
+
+impl ExternalSeal {
+	/// Returns new instance of ExternalSeal with default VM Factory
+	pub fn new(params: CommonParams, our_params: ExternalSealParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		ExternalSeal {
+			params: params,
+			producers: our_params.producers.into_iter().collect(),
+			builtins: builtins,
+		}
+	}
+}
+
+/// Hash signed by the producer: the header without the seal and without the signature itself.
+fn producer_signing_hash(header: &Header) -> ::util::H256 {
+	let mut header = header.clone();
+	let unsigned_len = header.extra_data().len().saturating_sub(SIGNATURE_LENGTH);
+	header.extra_data_mut().truncate(unsigned_len);
+	header.rlp(RlpSeal::Without).sha3()
+}
+
+fn producer(header: &Header) -> Result<Address, Error> {
+	let extra = header.extra_data();
+	if extra.len() < SIGNATURE_LENGTH {
+		return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: Some(SIGNATURE_LENGTH), max: None, found: extra.len() }).into());
+	}
+	let signature = Signature::from(H520::from_slice(&extra[extra.len() - SIGNATURE_LENGTH..]));
+	Ok(public_to_address(&recover(&signature, &producer_signing_hash(header))?))
+}
+
+impl Engine for ExternalSeal {
+	fn name(&self) -> &str {
+		"ExternalSeal"
+	}
+
+	fn params(&self) -> &CommonParams {
+		&self.params
+	}
+
+	fn additional_params(&self) -> HashMap<String, String> {
+		let producers: Vec<_> = self.producers.iter().map(|a| a.hex()).collect();
+		hash_map!["producers".to_owned() => producers.join(",")]
+	}
+
+	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
+		&self.builtins
+	}
+
+	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
+		let mut schedule = Schedule::new_post_eip150(usize::max_value(), true, true, true);
+		self.params().update_schedule(env_info.number, &mut schedule);
+		schedule
+	}
+
+	/// The producer's signature comes on top of the regular extra data.
+	fn maximum_extra_data_size(&self) -> usize {
+		self.params().maximum_extra_data_size + SIGNATURE_LENGTH
+	}
+
+	fn verify_block_basic(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		let len = header.extra_data().len();
+		if len < SIGNATURE_LENGTH {
+			return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: Some(SIGNATURE_LENGTH), max: None, found: len }).into());
+		}
+		Ok(())
+	}
+
+	/// Check the block is signed by an authorized producer.
+	fn verify_block_unordered(&self, header: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		let producer = producer(header)?;
+		if !self.producers.contains(&producer) {
+			trace!(target: "engine", "verify_block_unordered: block {} signed by unauthorized {}", header.number(), producer);
+			return Err(EngineError::NotAuthorized(producer).into());
+		}
+		Ok(())
+	}
+
+	/// Blocks follow each other directly; the producer vouches for everything else.
+	fn verify_block_family(&self, header: &Header, parent: &Header, _block: Option<&[u8]>) -> Result<(), Error> {
+		if header.number() != parent.number() + 1 {
+			return Err(BlockError::InvalidNumber(Mismatch { expected: parent.number() + 1, found: header.number() }).into());
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::*;
+	use ethkey::Secret;
+	use account_provider::AccountProvider;
+	use error::{Error, BlockError};
+	use header::Header;
+	use spec::Spec;
+	use engines::EngineError;
+	use super::producer_signing_hash;
+
+	fn signed_header(tap: &AccountProvider, address: Address, password: &str) -> Header {
+		let mut header = Header::default();
+		header.set_number(1);
+		header.set_extra_data(vec![0; 65]);
+		header.set_seal(vec![::rlp::encode(&H256::random()).to_vec()]);
+		let signature = tap.sign(address, Some(password.into()), producer_signing_hash(&header)).unwrap();
+		header.extra_data_mut().copy_from_slice(&*H520::from(signature));
+		header
+	}
+
+	#[test]
+	fn accepts_authorized_producer() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(Secret::from_slice(&"0".sha3()).unwrap(), "0").unwrap();
+		let engine = Spec::new_test_external_seal().engine;
+
+		let header = signed_header(&tap, addr, "0");
+		assert!(engine.verify_block_basic(&header, None).is_ok());
+		assert!(engine.verify_block_unordered(&header, None).is_ok());
+	}
+
+	#[test]
+	fn rejects_unauthorized_producer() {
+		let tap = AccountProvider::transient_provider();
+		let addr = tap.insert_account(Secret::from_slice(&"1".sha3()).unwrap(), "1").unwrap();
+		let engine = Spec::new_test_external_seal().engine;
+
+		let header = signed_header(&tap, addr, "1");
+		match engine.verify_block_unordered(&header, None) {
+			Err(Error::Engine(EngineError::NotAuthorized(a))) => assert_eq!(a, addr),
+			other => panic!("should be unauthorized producer error (got {:?})", other),
+		}
+	}
+
+	#[test]
+	fn rejects_unsigned_block() {
+		let engine = Spec::new_test_external_seal().engine;
+		let header = Header::default();
+
+		match engine.verify_block_basic(&header, None) {
+			Err(Error::Block(BlockError::ExtraDataOutOfBounds(_))) => {},
+			other => panic!("should be extra data error (got {:?})", other),
+		}
+	}
+}
@@ -23,14 +23,22 @@ mod instant_seal;
 mod basic_authority;
 mod authority_round;
 mod tendermint;
+mod clique;
+mod external_seal;
 mod validator_set;
 mod signer;
+mod replay_protection;
+mod system_call;
 
 pub use self::null_engine::NullEngine;
 pub use self::instant_seal::InstantSeal;
 pub use self::basic_authority::BasicAuthority;
 pub use self::authority_round::AuthorityRound;
 pub use self::tendermint::Tendermint;
+pub use self::clique::Clique;
+pub use self::external_seal::ExternalSeal;
+pub use self::replay_protection::{ReplayProtection, ChainIdProtection};
+pub use self::system_call::{SystemCall, SystemCallOutcome, SYSTEM_ADDRESS, SYSTEM_CALL_GAS, execute_system_call, push_last_hash};
 
 use std::sync::Weak;
 use util::*;
@@ -42,7 +50,7 @@ use env_info::EnvInfo;
 use error::Error;
 use spec::CommonParams;
 use evm::Schedule;
-use header::Header;
+use header::{Header, BlockNumber};
 use transaction::{UnverifiedTransaction, SignedTransaction};
 use client::Client;
 
@@ -59,6 +67,8 @@ pub enum EngineError {
 	UnexpectedMessage,
 	/// Seal field has an unexpected size.
 	BadSealFieldSize(OutOfBounds<usize>),
+	/// A call made by the engine into a contract failed.
+	FailedSystemCall(String),
 }
 
 impl fmt::Display for EngineError {
@@ -70,6 +80,7 @@ impl fmt::Display for EngineError {
 			NotAuthorized(ref address) => format!("Signer {} is not authorized.", address),
 			UnexpectedMessage => "This Engine should not be fed messages.".into(),
 			BadSealFieldSize(ref oob) => format!("Seal field has an unexpected length: {}", oob),
+			FailedSystemCall(ref msg) => format!("System call failed: {}", msg),
 		};
 
 		f.write_fmt(format_args!("Engine error ({})", msg))
@@ -104,6 +115,9 @@ pub trait Engine : Sync + Send {
 	/// Additional information.
 	fn additional_params(&self) -> HashMap<String, String> { HashMap::new() }
 
+	/// Engine-specific rule transitions, mapped to the first block they are enforced at.
+	fn transitions(&self) -> BTreeMap<String, BlockNumber> { BTreeMap::new() }
+
 	/// Get the general parameters of the chain.
 	fn params(&self) -> &CommonParams;
 
@@ -140,6 +154,10 @@ pub trait Engine : Sync + Send {
 	/// be returned.
 	fn generate_seal(&self, _block: &ExecutedBlock) -> Seal { Seal::None }
 
+	/// Put a seal into the header of the block being sealed.
+	/// By default the seal makes up the header's seal fields.
+	fn apply_seal(&self, header: &mut Header, seal: Vec<Bytes>) { header.set_seal(seal); }
+
 	/// Phase 1 quick block verification. Only does checks that are cheap. `block` (the header's full block)
 	/// may be provided for additional checks. Returns either a null `Ok` or a general error detailing the problem with import.
 	fn verify_block_basic(&self, _header: &Header,  _block: Option<&[u8]>) -> Result<(), Error> { Ok(()) }
@@ -155,8 +173,9 @@ pub trait Engine : Sync + Send {
 	/// Additional verification for transactions in blocks.
 	// TODO: Add flags for which bits of the transaction to check.
 	// TODO: consider including State in the params.
-	fn verify_transaction_basic(&self, t: &UnverifiedTransaction, _header: &Header) -> Result<(), Error> {
+	fn verify_transaction_basic(&self, t: &UnverifiedTransaction, header: &Header) -> Result<(), Error> {
 		t.check_low_s()?;
+		self.replay_protection().verify(t, header.number())?;
 		Ok(())
 	}
 
@@ -165,9 +184,13 @@ pub trait Engine : Sync + Send {
 		SignedTransaction::new(t)
 	}
 
+	/// The scheme protecting transactions from being replayed on other chains.
+	/// By default chain IDs as configured in the spec params.
+	fn replay_protection(&self) -> &ReplayProtection { &self.params().replay_protection }
+
 	/// The network ID that transactions should be signed with.
-	fn signing_network_id(&self, _env_info: &EnvInfo) -> Option<u64> {
-		Some(self.params().chain_id)
+	fn signing_network_id(&self, env_info: &EnvInfo) -> Option<u64> {
+		self.replay_protection().signing_chain_id(env_info.number)
 	}
 
 	/// Verify the seal of a block. This is an auxilliary method that actually just calls other `verify_` methods
@@ -206,6 +229,13 @@ pub trait Engine : Sync + Send {
 	/// Register an account which signs consensus messages.
 	fn set_signer(&self, _account_provider: Arc<AccountProvider>, _address: Address, _password: String) {}
 
+	/// Vote on authorizing (`true`) or deauthorizing (`false`) a signer in the blocks sealed by this
+	/// node. Returns `false` if the engine doesn't vote on signers.
+	fn propose_signer(&self, _address: Address, _authorize: bool) -> bool { false }
+
+	/// Stop voting on a signer. Returns `false` if the engine doesn't vote on signers.
+	fn discard_signer_proposal(&self, _address: &Address) -> bool { false }
+
 	/// Sign using the EngineSigner, to be used for consensus tx signing.
 	fn sign(&self, _hash: H256) -> Result<Signature, Error> { unimplemented!() }
 
@@ -0,0 +1,114 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction replay protection schemes.
+
+use error::TransactionError;
+use header::BlockNumber;
+use transaction::UnverifiedTransaction;
+
+/// Protects transactions from being replayed on other chains.
+pub trait ReplayProtection: Send + Sync {
+	/// The chain ID new transactions for the given block should be signed with, if any.
+	fn signing_chain_id(&self, number: BlockNumber) -> Option<u64>;
+
+	/// Check that a transaction included in the given block can't have been meant for another chain.
+	fn verify(&self, t: &UnverifiedTransaction, number: BlockNumber) -> Result<(), TransactionError>;
+}
+
+/// Replay protection by committing to a chain ID in the transaction signature (EIP-155).
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ChainIdProtection {
+	/// Chain ID transactions commit to.
+	pub chain_id: u64,
+	/// First block transactions may commit to a chain ID in. From then on transactions
+	/// committing to a different chain ID are invalid; before, any commitment is invalid.
+	/// `None` leaves chain IDs unchecked.
+	pub transition: Option<BlockNumber>,
+	/// First block transactions must commit to the chain ID in, if any.
+	pub required_transition: Option<BlockNumber>,
+}
+
+impl ReplayProtection for ChainIdProtection {
+	fn signing_chain_id(&self, number: BlockNumber) -> Option<u64> {
+		match self.transition {
+			Some(transition) if number < transition => None,
+			_ => Some(self.chain_id),
+		}
+	}
+
+	fn verify(&self, t: &UnverifiedTransaction, number: BlockNumber) -> Result<(), TransactionError> {
+		match t.network_id() {
+			Some(n) => match self.transition {
+				Some(transition) if number < transition || n != self.chain_id => Err(TransactionError::InvalidNetworkId),
+				_ => Ok(()),
+			},
+			None => match self.required_transition {
+				Some(transition) if number >= transition => Err(TransactionError::MissingNetworkId),
+				_ => Ok(()),
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Generator, Random};
+	use error::TransactionError;
+	use transaction::{Transaction, Action, UnverifiedTransaction};
+	use super::{ReplayProtection, ChainIdProtection};
+
+	fn transaction(chain_id: Option<u64>) -> UnverifiedTransaction {
+		let keypair = Random.generate().unwrap();
+		Transaction {
+			action: Action::Create,
+			value: 0.into(),
+			data: Vec::new(),
+			gas: 100_000.into(),
+			gas_price: 0.into(),
+			nonce: 0.into(),
+		}.sign(keypair.secret(), chain_id).into()
+	}
+
+	#[test]
+	fn chain_id_from_transition() {
+		let protection = ChainIdProtection { chain_id: 42, transition: Some(10), required_transition: None };
+
+		assert_eq!(protection.signing_chain_id(9), None);
+		assert_eq!(protection.signing_chain_id(10), Some(42));
+
+		assert_eq!(protection.verify(&transaction(Some(42)), 9), Err(TransactionError::InvalidNetworkId));
+		assert_eq!(protection.verify(&transaction(Some(42)), 10), Ok(()));
+		assert_eq!(protection.verify(&transaction(Some(1)), 10), Err(TransactionError::InvalidNetworkId));
+		assert_eq!(protection.verify(&transaction(None), 10), Ok(()));
+	}
+
+	#[test]
+	fn unchecked_chain_id() {
+		let protection = ChainIdProtection { chain_id: 42, transition: None, required_transition: None };
+
+		assert_eq!(protection.signing_chain_id(0), Some(42));
+		assert_eq!(protection.verify(&transaction(Some(1)), 0), Ok(()));
+	}
+
+	#[test]
+	fn mandatory_chain_id() {
+		let protection = ChainIdProtection { chain_id: 42, transition: Some(0), required_transition: Some(0) };
+
+		assert_eq!(protection.verify(&transaction(None), 0), Err(TransactionError::MissingNetworkId));
+		assert_eq!(protection.verify(&transaction(Some(42)), 0), Ok(()));
+	}
+}
@@ -0,0 +1,224 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! System calls: calls made by the engine into contracts at block boundaries,
+//! outside of any transaction.
+
+use util::{Address, U256, H160, Bytes, BytesRef};
+use action_params::{ActionParams, ActionValue};
+use block::ExecutedBlock;
+use engines::{Engine, EngineError};
+use env_info::EnvInfo;
+use error::Error;
+use executive::Executive;
+use log_entry::LogEntry;
+use state::{Backend, State, Substate};
+use trace::{FlatTrace, Tracer, ExecutiveTracer, NoopTracer, NoopVMTracer};
+use types::executed::CallType;
+
+/// Sender of system calls. It isn't charged for gas and its nonce is never incremented.
+pub const SYSTEM_ADDRESS: Address = H160([0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe]);
+
+/// Gas available to a system call unless given otherwise.
+pub const SYSTEM_CALL_GAS: u64 = 50_000_000;
+
+/// A call into a contract made on behalf of the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemCall {
+	/// Contract to call.
+	pub contract: Address,
+	/// Call data.
+	pub data: Bytes,
+	/// Gas available to the call.
+	pub gas: U256,
+}
+
+impl SystemCall {
+	/// Call given contract with `SYSTEM_CALL_GAS`.
+	pub fn new(contract: Address, data: Bytes) -> Self {
+		SystemCall {
+			contract: contract,
+			data: data,
+			gas: SYSTEM_CALL_GAS.into(),
+		}
+	}
+}
+
+/// Result of a system call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemCallOutcome {
+	/// Called contract.
+	pub contract: Address,
+	/// Data returned by the contract.
+	pub output: Bytes,
+	/// Gas used by the call.
+	pub gas_used: U256,
+	/// Logs emitted by the call.
+	pub logs: Vec<LogEntry>,
+	/// Traces of the call, empty unless tracing is enabled.
+	pub trace: Vec<FlatTrace>,
+}
+
+/// Execute a system call on top of the given state. Changes are kept only if the call
+/// succeeds; the state is not committed.
+pub fn execute_system_call<B: Backend>(
+	state: &mut State<B>,
+	env_info: &EnvInfo,
+	engine: &Engine,
+	call: SystemCall,
+	tracing: bool,
+) -> Result<SystemCallOutcome, Error> {
+	match tracing {
+		true => execute_with_tracer(state, env_info, engine, call, ExecutiveTracer::default()),
+		false => execute_with_tracer(state, env_info, engine, call, NoopTracer),
+	}
+}
+
+fn execute_with_tracer<B: Backend, T: Tracer>(
+	state: &mut State<B>,
+	env_info: &EnvInfo,
+	engine: &Engine,
+	call: SystemCall,
+	mut tracer: T,
+) -> Result<SystemCallOutcome, Error> {
+	let params = ActionParams {
+		code_address: call.contract.clone(),
+		address: call.contract.clone(),
+		sender: SYSTEM_ADDRESS,
+		origin: SYSTEM_ADDRESS,
+		gas: call.gas,
+		gas_price: U256::zero(),
+		// no transfer, so that `SYSTEM_ADDRESS` is never touched
+		value: ActionValue::Apparent(U256::zero()),
+		code: state.code(&call.contract)?,
+		code_hash: state.code_hash(&call.contract)?,
+		data: Some(call.data),
+		call_type: CallType::Call,
+	};
+
+	let vm_factory = state.vm_factory();
+	let mut substate = Substate::new();
+	let mut output = Vec::new();
+	let result = Executive::new(state, env_info, engine, &vm_factory)
+		.call(params, &mut substate, BytesRef::Flexible(&mut output), &mut tracer, &mut NoopVMTracer);
+
+	match result {
+		Ok(ref res) if res.apply_state => Ok(SystemCallOutcome {
+			contract: call.contract,
+			output: output,
+			gas_used: call.gas - res.gas_left,
+			logs: substate.logs,
+			trace: tracer.traces(),
+		}),
+		Ok(_) => Err(EngineError::FailedSystemCall(format!("Call to {} reverted", call.contract)).into()),
+		Err(e) => Err(EngineError::FailedSystemCall(format!("Call to {} failed: {}", call.contract, e)).into()),
+	}
+}
+
+/// Record the parent hash in the EIP-210 contract, installing the contract at the transition block.
+/// Engines call it from `Engine::on_new_block`.
+pub fn push_last_hash(engine: &Engine, block: &mut ExecutedBlock) -> Result<(), Error> {
+	let params = engine.params();
+	let (number, parent_hash) = {
+		let header = &block.fields().header;
+		(header.number(), header.parent_hash().clone())
+	};
+
+	if number < params.eip210_transition {
+		return Ok(());
+	}
+	if number == params.eip210_transition {
+		block.fields_mut().state.init_code(&params.eip210_contract_address, params.eip210_contract_code.clone())?;
+	}
+
+	let call = SystemCall {
+		contract: params.eip210_contract_address.clone(),
+		data: parent_hash.to_vec(),
+		gas: params.eip210_contract_gas,
+	};
+	block.system_call(engine, call).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+	use std::str::FromStr;
+	use util::*;
+	use engines::Engine;
+	use error::Error;
+	use tests::helpers::*;
+	use super::{execute_system_call, SystemCall, SYSTEM_ADDRESS};
+
+	#[test]
+	fn system_address() {
+		assert_eq!(SYSTEM_ADDRESS, Address::from_str("fffffffffffffffffffffffffffffffffffffffe").unwrap());
+	}
+
+	#[test]
+	fn calls_contract_without_charging_sender() {
+		let spec = get_test_spec();
+		let engine = &*spec.engine;
+		let mut state_result = get_temp_state();
+		let state = state_result.reference_mut();
+		let contract = Address::from(0x10);
+		// returns the first word of call data
+		state.init_code(&contract, "60003560005260206000f3".from_hex().unwrap()).unwrap();
+
+		let mut data = vec![0u8; 32];
+		data[31] = 42;
+		let outcome = execute_system_call(state, &Default::default(), engine, SystemCall::new(contract, data.clone()), true).unwrap();
+
+		assert_eq!(outcome.output, data);
+		assert!(outcome.gas_used > U256::zero());
+		assert_eq!(outcome.trace.len(), 1);
+		assert_eq!(state.balance(&SYSTEM_ADDRESS).unwrap(), U256::zero());
+		assert_eq!(state.nonce(&SYSTEM_ADDRESS).unwrap(), U256::zero());
+	}
+
+	#[test]
+	fn leaves_state_root_of_no_op_call() {
+		let spec = get_test_spec();
+		let engine: &Engine = &*spec.engine;
+		let mut state_result = get_temp_state();
+		let state = state_result.reference_mut();
+		let contract = Address::from(0x10);
+		// stop
+		state.init_code(&contract, vec![0x00]).unwrap();
+		state.commit().unwrap();
+		let root = state.root().clone();
+
+		execute_system_call(state, &Default::default(), engine, SystemCall::new(contract, Vec::new()), false).unwrap();
+		state.commit().unwrap();
+
+		assert_eq!(state.root(), &root);
+		assert!(!state.exists(&SYSTEM_ADDRESS).unwrap());
+	}
+
+	#[test]
+	fn reports_failed_call() {
+		let spec = get_test_spec();
+		let engine: &Engine = &*spec.engine;
+		let mut state_result = get_temp_state();
+		let state = state_result.reference_mut();
+		let contract = Address::from(0x10);
+		// invalid instruction
+		state.init_code(&contract, vec![0xfe]).unwrap();
+
+		match execute_system_call(state, &Default::default(), engine, SystemCall::new(contract, Vec::new()), false) {
+			Err(Error::Engine(_)) => {},
+			other => panic!("Unexpected result: {:?}", other.map(|o| o.output)),
+		}
+	}
+}
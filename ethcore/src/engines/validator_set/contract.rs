@@ -52,6 +52,10 @@ impl ValidatorSet for ValidatorContract {
 		self.validators.count(bh)
 	}
 
+	fn definition(&self) -> String {
+		format!("{{\"contract\":\"0x{}\"}}", self.validators.address.hex())
+	}
+
 	fn report_malicious(&self, address: &Address) {
 		if let Some(ref provider) = *self.provider.read() {
 			match provider.report_malicious(address) {
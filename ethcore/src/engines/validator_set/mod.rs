@@ -49,6 +49,8 @@ pub trait ValidatorSet: Send + Sync {
 	fn get(&self, parent_block_hash: &H256, nonce: usize) -> Address;
 	/// Returns the current number of validators.
 	fn count(&self, parent_block_hash: &H256) -> usize;
+	/// Returns JSON definition of this set, in the same format as in the chain spec.
+	fn definition(&self) -> String;
 	/// Notifies about malicious behaviour.
 	fn report_malicious(&self, _validator: &Address) {}
 	/// Notifies about benign misbehaviour.
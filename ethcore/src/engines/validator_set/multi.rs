@@ -78,6 +78,11 @@ impl ValidatorSet for Multi {
 		self.correct_set(bh).map_or_else(usize::max_value, |set| set.count(bh))
 	}
 
+	fn definition(&self) -> String {
+		let sets: Vec<_> = self.sets.iter().map(|(block, set)| format!("\"{}\":{}", block, set.definition())).collect();
+		format!("{{\"multi\":{{{}}}}}", sets.join(","))
+	}
+
 	fn report_malicious(&self, validator: &Address) {
 		for set in self.sets.values() {
 			set.report_malicious(validator);
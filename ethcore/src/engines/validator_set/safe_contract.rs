@@ -112,6 +112,10 @@ impl ValidatorSet for ValidatorSafeContract {
 				 }))
 	}
 
+	fn definition(&self) -> String {
+		format!("{{\"safeContract\":\"0x{}\"}}", self.address.hex())
+	}
+
 	fn register_contract(&self, client: Weak<Client>) {
 		trace!(target: "engine", "Setting up contract caller.");
 		let contract = ethabi::Contract::new(ethabi::Interface::load(CONTRACT_INTERFACE).expect("JSON interface is valid; qed"));
@@ -52,6 +52,11 @@ impl ValidatorSet for SimpleList {
 	fn count(&self, _bh: &H256) -> usize {
 		self.validator_n
 	}
+
+	fn definition(&self) -> String {
+		let list: Vec<_> = self.validators.iter().map(|a| format!("\"0x{}\"", a.hex())).collect();
+		format!("{{\"list\":[{}]}}", list.join(","))
+	}
 }
 
 #[cfg(test)]
@@ -70,5 +75,6 @@ mod tests {
 		assert_eq!(list.get(&Default::default(), 0), a1);
 		assert_eq!(list.get(&Default::default(), 1), a2);
 		assert_eq!(list.get(&Default::default(), 2), a1);
+		assert_eq!(list.definition(), r#"{"list":["0xcd1722f3947def4cf144679da39c4c32bdc35681","0x0f572e5295c57f15886f9b263e2f6d2d6c7b5ec6"]}"#);
 	}
 }
@@ -59,6 +59,43 @@ impl Default for EnvInfo {
 	}
 }
 
+/// Replacements for parts of the execution environment, used to run calls
+/// under other block conditions than those of the block they're executed at.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnvOverrides {
+	/// The block author.
+	pub author: Option<Address>,
+	/// The block timestamp.
+	pub timestamp: Option<u64>,
+	/// The block difficulty.
+	pub difficulty: Option<U256>,
+	/// The block gas limit.
+	pub gas_limit: Option<U256>,
+}
+
+impl EnvOverrides {
+	/// Whether nothing is overridden.
+	pub fn is_empty(&self) -> bool {
+		*self == EnvOverrides::default()
+	}
+
+	/// Replace the overridden parts of given environment.
+	pub fn apply(&self, env_info: &mut EnvInfo) {
+		if let Some(ref author) = self.author {
+			env_info.author = author.clone();
+		}
+		if let Some(timestamp) = self.timestamp {
+			env_info.timestamp = timestamp;
+		}
+		if let Some(ref difficulty) = self.difficulty {
+			env_info.difficulty = difficulty.clone();
+		}
+		if let Some(ref gas_limit) = self.gas_limit {
+			env_info.gas_limit = gas_limit.clone();
+		}
+	}
+}
+
 impl From<ethjson::vm::Env> for EnvInfo {
 	fn from(e: ethjson::vm::Env) -> Self {
 		let number = e.number.into();
@@ -106,4 +143,15 @@ mod tests {
 
 		assert_eq!(default_env_info.difficulty, 0.into());
 	}
+
+	#[test]
+	fn it_applies_overrides() {
+		let mut env_info = EnvInfo { number: 10, gas_limit: 1_000.into(), ..Default::default() };
+		let overrides = EnvOverrides { timestamp: Some(5), difficulty: Some(7.into()), ..Default::default() };
+		assert!(!overrides.is_empty());
+
+		overrides.apply(&mut env_info);
+		assert_eq!((env_info.number, env_info.timestamp), (10, 5));
+		assert_eq!((env_info.difficulty, env_info.gas_limit), (7.into(), 1_000.into()));
+	}
 }
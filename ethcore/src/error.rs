@@ -42,6 +42,15 @@ pub enum TransactionError {
 	TooCheapToReplace,
 	/// Transaction was not imported to the queue because limit has been reached.
 	LimitReached,
+	/// Transaction was not imported to the queue because sender's memory limit has been reached.
+	SenderLimitReached,
+	/// Transaction's nonce is too far ahead of the sender's current nonce.
+	NonceGapTooLarge {
+		/// Maximal allowed gap
+		limit: U256,
+		/// Gap between transaction nonce and sender's nonce
+		got: U256,
+	},
 	/// Transaction's gas price is below threshold.
 	InsufficientGasPrice {
 		/// Minimal expected gas price
@@ -80,6 +89,17 @@ pub enum TransactionError {
 	CodeBanned,
 	/// Invalid network ID given.
 	InvalidNetworkId,
+	/// Transaction doesn't commit to a network ID, but the chain requires one.
+	MissingNetworkId,
+	/// Contract creation code is larger than allowed.
+	InitCodeTooLarge {
+		/// Maximal init code size
+		limit: usize,
+		/// Transaction init code size
+		got: usize,
+	},
+	/// Transaction queue is disabled because the client is read-only.
+	ReadOnly,
 }
 
 impl fmt::Display for TransactionError {
@@ -90,6 +110,9 @@ impl fmt::Display for TransactionError {
 			Old => "No longer valid".into(),
 			TooCheapToReplace => "Gas price too low to replace".into(),
 			LimitReached => "Transaction limit reached".into(),
+			SenderLimitReached => "Sender's transaction limit reached".into(),
+			NonceGapTooLarge { limit, got } =>
+				format!("Nonce too far in the future. Max gap={}, Given={}", limit, got),
 			InsufficientGasPrice { minimal, got } =>
 				format!("Insufficient gas price. Min={}, Given={}", minimal, got),
 			InsufficientGas { minimal, got } =>
@@ -104,6 +127,10 @@ impl fmt::Display for TransactionError {
 			RecipientBanned => "Recipient is temporarily banned.".into(),
 			CodeBanned => "Contract code is temporarily banned.".into(),
 			InvalidNetworkId => "Transaction of this network ID is not allowed on this chain.".into(),
+			MissingNetworkId => "Transaction must be signed with the network ID of this chain.".into(),
+			InitCodeTooLarge { limit, got } =>
+				format!("Contract creation code too large. Max={}, Given={}", limit, got),
+			ReadOnly => "Client is read-only.".into(),
 		};
 
 		f.write_fmt(format_args!("Transaction error ({})", msg))
@@ -117,6 +144,8 @@ pub enum BlockError {
 	TooManyUncles(OutOfBounds<usize>),
 	/// Extra data is of an invalid length.
 	ExtraDataOutOfBounds(OutOfBounds<usize>),
+	/// Extra data doesn't start with the prefix required by the chain.
+	ExtraDataMissingPrefix,
 	/// Seal is incorrect format.
 	InvalidSealArity(Mismatch<usize>),
 	/// Block has too much gas used.
@@ -169,6 +198,8 @@ pub enum BlockError {
 	UnknownParent(H256),
 	/// Uncle parent given is unknown.
 	UnknownUncleParent(H256),
+	/// Block at a trusted checkpoint has a different hash.
+	CheckpointMismatch(Mismatch<H256>),
 }
 
 impl fmt::Display for BlockError {
@@ -178,6 +209,7 @@ impl fmt::Display for BlockError {
 		let msg = match *self {
 			TooManyUncles(ref oob) => format!("Block has too many uncles. {}", oob),
 			ExtraDataOutOfBounds(ref oob) => format!("Extra block data too long. {}", oob),
+			ExtraDataMissingPrefix => "Extra block data doesn't start with the prefix required by the chain.".into(),
 			InvalidSealArity(ref mis) => format!("Block seal in incorrect format: {}", mis),
 			TooMuchGasUsed(ref oob) => format!("Block has too much gas used. {}", oob),
 			InvalidUnclesHash(ref mis) => format!("Block has invalid uncles hash: {}", mis),
@@ -202,6 +234,7 @@ impl fmt::Display for BlockError {
 			RidiculousNumber(ref oob) => format!("Implausible block number. {}", oob),
 			UnknownParent(ref hash) => format!("Unknown parent: {}", hash),
 			UnknownUncleParent(ref hash) => format!("Unknown uncle parent: {}", hash),
+			CheckpointMismatch(ref mis) => format!("Block doesn't match the trusted checkpoint: {}", mis),
 		};
 
 		f.write_fmt(format_args!("Block error ({})", msg))
@@ -217,6 +250,8 @@ pub enum ImportError {
 	AlreadyQueued,
 	/// Already marked as bad from a previous import (could mean parent is bad).
 	KnownBad,
+	/// Client is read-only and doesn't import blocks.
+	ReadOnly,
 }
 
 impl fmt::Display for ImportError {
@@ -225,6 +260,7 @@ impl fmt::Display for ImportError {
 			ImportError::AlreadyInChain => "block already in chain",
 			ImportError::AlreadyQueued => "block already in the block queue",
 			ImportError::KnownBad => "block known to be bad",
+			ImportError::ReadOnly => "client is read-only",
 		};
 
 		f.write_fmt(format_args!("Block import error ({})", msg))
@@ -319,6 +355,20 @@ impl From<ImportError> for Error {
 	}
 }
 
+impl Error {
+	/// Whether the error proves the block being imported invalid, rather than the node
+	/// being unable to process it right now (missing parent or state, I/O failures).
+	pub fn is_invalid_block(&self) -> bool {
+		match *self {
+			Error::Block(BlockError::UnknownParent(_)) => false,
+			Error::Block(BlockError::InvalidTimestamp(ref oob)) if oob.max.is_some() => false,
+			Error::Block(_) | Error::Execution(_) | Error::Transaction(_) | Error::Engine(_) => true,
+			Error::PowHashInvalid | Error::PowInvalid => true,
+			_ => false,
+		}
+	}
+}
+
 impl From<BlockError> for Error {
 	fn from(err: BlockError) -> Error {
 		Error::Block(err)
@@ -15,23 +15,30 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use ethash::{quick_get_difficulty, slow_get_seedhash, EthashManager};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use lru_cache::LruCache;
 use util::*;
 use block::*;
 use builtin::Builtin;
 use env_info::EnvInfo;
 use error::{BlockError, TransactionError, Error};
-use header::Header;
+use header::{Header, BlockNumber};
 use state::CleanupMode;
 use spec::CommonParams;
 use transaction::UnverifiedTransaction;
-use engines::Engine;
+use engines::{Engine, ReplayProtection, ChainIdProtection, push_last_hash};
 use evm::Schedule;
+use evm::schedule::{ScheduleBuilder, ScheduleOverride};
+use super::irregular::{IrregularStateChange, BalanceMove};
 use ethjson;
 use rlp::{self, UntrustedRlp};
 
 /// Parity tries to round block.gas_limit to multiple of this constant
 pub const PARITY_GAS_LIMIT_DETERMINANT: U256 = U256([37, 0, 0, 0]);
 
+/// Number of header hashes with an already verified seal to remember.
+const VERIFIED_SEALS_CACHE_SIZE: usize = 4096;
+
 /// Ethash params.
 #[derive(Debug, PartialEq)]
 pub struct EthashParams {
@@ -53,10 +60,8 @@ pub struct EthashParams {
 	pub homestead_transition: u64,
 	/// DAO hard-fork transition block (X).
 	pub dao_hardfork_transition: u64,
-	/// DAO hard-fork refund contract address (C).
-	pub dao_hardfork_beneficiary: Address,
-	/// DAO hard-fork DAO accounts list (L)
-	pub dao_hardfork_accounts: Vec<Address>,
+	/// One-off state changes by block number. Includes the DAO hard-fork refund.
+	pub irregular_state_changes: BTreeMap<BlockNumber, IrregularStateChange>,
 	/// Transition block for a change of difficulty params (currently just bound_divisor).
 	pub difficulty_hardfork_transition: u64,
 	/// Difficulty param after the difficulty transition.
@@ -91,6 +96,26 @@ pub struct EthashParams {
 
 impl From<ethjson::spec::EthashParams> for EthashParams {
 	fn from(p: ethjson::spec::EthashParams) -> Self {
+		let dao_hardfork_transition = p.dao_hardfork_transition.map_or(u64::max_value(), Into::into);
+		let mut irregular_state_changes: BTreeMap<BlockNumber, IrregularStateChange> = BTreeMap::new();
+		for change in p.irregular_state_changes.unwrap_or_else(Vec::new) {
+			let block: BlockNumber = change.block.clone().into();
+			let change = IrregularStateChange::from(change);
+			let entry = irregular_state_changes.entry(block).or_insert_with(Default::default);
+			entry.balance_moves.extend(change.balance_moves);
+			entry.storage_writes.extend(change.storage_writes);
+		}
+		// DAO hard-fork: move the whole balance of each account in L to C.
+		if let Some(accounts) = p.dao_hardfork_accounts {
+			let beneficiary: Address = p.dao_hardfork_beneficiary.map_or_else(Address::new, Into::into);
+			let moves = accounts.into_iter().map(|account| BalanceMove {
+				from: account.into(),
+				to: beneficiary.clone(),
+				value: None,
+			});
+			irregular_state_changes.entry(dao_hardfork_transition).or_insert_with(Default::default).balance_moves.extend(moves);
+		}
+
 		EthashParams {
 			gas_limit_bound_divisor: p.gas_limit_bound_divisor.into(),
 			minimum_difficulty: p.minimum_difficulty.into(),
@@ -100,9 +125,8 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 			block_reward: p.block_reward.into(),
 			registrar: p.registrar.map_or_else(Address::new, Into::into),
 			homestead_transition: p.homestead_transition.map_or(0, Into::into),
-			dao_hardfork_transition: p.dao_hardfork_transition.map_or(u64::max_value(), Into::into),
-			dao_hardfork_beneficiary: p.dao_hardfork_beneficiary.map_or_else(Address::new, Into::into),
-			dao_hardfork_accounts: p.dao_hardfork_accounts.unwrap_or_else(Vec::new).into_iter().map(Into::into).collect(),
+			dao_hardfork_transition: dao_hardfork_transition,
+			irregular_state_changes: irregular_state_changes,
 			difficulty_hardfork_transition: p.difficulty_hardfork_transition.map_or(u64::max_value(), Into::into),
 			difficulty_hardfork_bound_divisor: p.difficulty_hardfork_bound_divisor.map_or(p.difficulty_bound_divisor.into(), Into::into),
 			bomb_defuse_transition: p.bomb_defuse_transition.map_or(u64::max_value(), Into::into),
@@ -127,20 +151,50 @@ impl From<ethjson::spec::EthashParams> for EthashParams {
 pub struct Ethash {
 	params: CommonParams,
 	ethash_params: EthashParams,
+	replay_protection: ChainIdProtection,
+	schedule_builder: ScheduleBuilder,
 	builtins: BTreeMap<Address, Builtin>,
 	pow: EthashManager,
+	verified_seals: Mutex<LruCache<H256, ()>>,
+	verified_seals_hits: AtomicUsize,
 }
 
 impl Ethash {
 	/// Create a new instance of Ethash engine
 	pub fn new(params: CommonParams, ethash_params: EthashParams, builtins: BTreeMap<Address, Builtin>) -> Self {
+		let replay_protection = ChainIdProtection {
+			transition: Some(ethash_params.eip155_transition),
+			..params.replay_protection.clone()
+		};
+
+		let schedule_builder = {
+			let p = &ethash_params;
+			// EIP-160 and EIP-161 only take effect along with EIP-150.
+			let post_eip150 = |transition: u64| cmp::max(transition, p.eip150_transition);
+			ScheduleBuilder::new()
+				.transition(p.homestead_transition, ScheduleOverride::homestead())
+				.transition(p.eip150_transition, ScheduleOverride::eip150(p.max_code_size as usize))
+				.transition(post_eip150(p.eip160_transition), ScheduleOverride { exp_byte_gas: Some(50), ..Default::default() })
+				.transition(post_eip150(p.eip161abc_transition), ScheduleOverride { no_empty: Some(true), ..Default::default() })
+				.transition(post_eip150(p.eip161d_transition), ScheduleOverride { kill_empty: Some(true), ..Default::default() })
+		};
+
 		Ethash {
 			params: params,
 			ethash_params: ethash_params,
+			replay_protection: replay_protection,
+			schedule_builder: schedule_builder,
 			builtins: builtins,
 			pow: EthashManager::new(),
+			verified_seals: Mutex::new(LruCache::new(VERIFIED_SEALS_CACHE_SIZE)),
+			verified_seals_hits: AtomicUsize::new(0),
 		}
 	}
+
+	/// Number of seal verifications avoided because the header was already proven valid.
+	pub fn verified_seals_hits(&self) -> usize {
+		self.verified_seals_hits.load(AtomicOrdering::Relaxed)
+	}
 }
 
 impl Engine for Ethash {
@@ -150,7 +204,33 @@ impl Engine for Ethash {
 	fn seal_fields(&self) -> usize { 2 }
 
 	fn params(&self) -> &CommonParams { &self.params }
-	fn additional_params(&self) -> HashMap<String, String> { hash_map!["registrar".to_owned() => self.ethash_params.registrar.hex()] }
+	fn additional_params(&self) -> HashMap<String, String> {
+		let mut params = hash_map!["registrar".to_owned() => self.ethash_params.registrar.hex()];
+		if !self.ethash_params.irregular_state_changes.is_empty() {
+			let blocks = self.ethash_params.irregular_state_changes.keys().map(ToString::to_string).collect::<Vec<_>>();
+			params.insert("irregularStateChanges".to_owned(), blocks.join(","));
+		}
+		params
+	}
+
+	fn transitions(&self) -> BTreeMap<String, BlockNumber> {
+		let p = &self.ethash_params;
+		map![
+			"homestead".to_owned() => p.homestead_transition,
+			"daoHardfork".to_owned() => p.dao_hardfork_transition,
+			"difficultyHardfork".to_owned() => p.difficulty_hardfork_transition,
+			"bombDefuse".to_owned() => p.bomb_defuse_transition,
+			"eip150".to_owned() => p.eip150_transition,
+			"eip155".to_owned() => p.eip155_transition,
+			"eip160".to_owned() => p.eip160_transition,
+			"eip161abc".to_owned() => p.eip161abc_transition,
+			"eip161d".to_owned() => p.eip161d_transition,
+			"ecip1010Pause".to_owned() => p.ecip1010_pause_transition,
+			"ecip1010Continue".to_owned() => p.ecip1010_continue_transition,
+			"maxGasLimit".to_owned() => p.max_gas_limit_transition,
+			"minGasPrice".to_owned() => p.min_gas_price_transition
+		]
+	}
 
 	fn builtins(&self) -> &BTreeMap<Address, Builtin> {
 		&self.builtins
@@ -164,27 +244,12 @@ impl Engine for Ethash {
 	fn schedule(&self, env_info: &EnvInfo) -> Schedule {
 		trace!(target: "client", "Creating schedule. fCML={}, bGCML={}", self.ethash_params.homestead_transition, self.ethash_params.eip150_transition);
 
-		if env_info.number < self.ethash_params.homestead_transition {
-			Schedule::new_frontier()
-		} else if env_info.number < self.ethash_params.eip150_transition {
-			Schedule::new_homestead()
-		} else {
-			Schedule::new_post_eip150(
-				self.ethash_params.max_code_size as usize,
-				env_info.number >= self.ethash_params.eip160_transition,
-				env_info.number >= self.ethash_params.eip161abc_transition,
-				env_info.number >= self.ethash_params.eip161d_transition
-			)
-		}
+		let mut schedule = self.schedule_builder.build(env_info.number);
+		self.params().update_schedule(env_info.number, &mut schedule);
+		schedule
 	}
 
-	fn signing_network_id(&self, env_info: &EnvInfo) -> Option<u64> {
-		if env_info.number >= self.ethash_params.eip155_transition {
-			Some(self.params().chain_id)
-		} else {
-			None
-		}
-	}
+	fn replay_protection(&self) -> &ReplayProtection { &self.replay_protection }
 
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header, gas_floor_target: U256, mut gas_ceil_target: U256) {
 		let difficulty = self.calculate_difficulty(header, parent);
@@ -226,21 +291,16 @@ impl Engine for Ethash {
 	}
 
 	fn on_new_block(&self, block: &mut ExecutedBlock) {
-		if block.fields().header.number() == self.ethash_params.dao_hardfork_transition {
-			// TODO: enable trigger function maybe?
-//			if block.fields().header.gas_limit() <= 4_000_000.into() {
-				let state = block.fields_mut().state;
-				for child in &self.ethash_params.dao_hardfork_accounts {
-					let beneficiary = &self.ethash_params.dao_hardfork_beneficiary;
-					let res = state.balance(child)
-						.and_then(|b| state.transfer_balance(child, beneficiary, &b, CleanupMode::NoEmpty));
-
-					if let Err(_) = res {
-						warn!("Unable to apply DAO hardfork due to database corruption.");
-						warn!("Your node is now likely out of consensus.");
-					}
-				}
-//			}
+		let number = block.fields().header.number();
+		if let Some(change) = self.ethash_params.irregular_state_changes.get(&number) {
+			if let Err(e) = change.apply(block.fields_mut().state) {
+				warn!("Unable to apply irregular state change at block #{}: {}", number, e);
+				warn!("Your node is now likely out of consensus.");
+			}
+		}
+		if let Err(e) = push_last_hash(self, block) {
+			warn!("Unable to record the parent hash at block #{}: {}", number, e);
+			warn!("Your node is now likely out of consensus.");
 		}
 	}
 
@@ -325,6 +385,12 @@ impl Engine for Ethash {
 				Mismatch { expected: self.seal_fields(), found: header.seal().len() }
 			)));
 		}
+		// the hash covers the seal, so a cached entry proves this exact proof-of-work.
+		let hash = header.hash();
+		if self.verified_seals.lock().get_mut(&hash).is_some() {
+			self.verified_seals_hits.fetch_add(1, AtomicOrdering::Relaxed);
+			return Ok(());
+		}
 		let result = self.pow.compute_light(header.number() as u64, &header.bare_hash().0, header.nonce().low_u64());
 		let mix = H256(result.mix_hash);
 		let difficulty = Ethash::boundary_to_difficulty(&H256(result.value));
@@ -335,6 +401,7 @@ impl Engine for Ethash {
 		if &difficulty < header.difficulty() {
 			return Err(From::from(BlockError::InvalidProofOfWork(OutOfBounds { min: Some(header.difficulty().clone()), max: None, found: difficulty })));
 		}
+		self.verified_seals.lock().insert(hash, ());
 		Ok(())
 	}
 
@@ -367,11 +434,7 @@ impl Engine for Ethash {
 			t.check_low_s()?;
 		}
 
-		if let Some(n) = t.network_id() {
-			if header.number() < self.ethash_params.eip155_transition || n != self.params().chain_id {
-				return Err(TransactionError::InvalidNetworkId.into())
-			}
-		}
+		self.replay_protection.verify(t, header.number())?;
 
 		if header.number() >= self.ethash_params.min_gas_price_transition && t.gas_price < self.ethash_params.min_gas_price {
 			return Err(TransactionError::InsufficientGasPrice { minimal: self.ethash_params.min_gas_price, got: t.gas_price }.into());
@@ -501,6 +564,7 @@ mod tests {
 	use header::Header;
 	use super::super::{new_morden, new_homestead_test};
 	use super::{Ethash, EthashParams, PARITY_GAS_LIMIT_DETERMINANT};
+	use super::super::irregular::{IrregularStateChange, StorageWrite};
 	use rlp;
 
 	#[test]
@@ -516,6 +580,45 @@ mod tests {
 		assert_eq!(b.state().balance(&Address::zero()).unwrap(), U256::from_str("4563918244f40000").unwrap());
 	}
 
+	#[test]
+	fn on_new_block_applies_irregular_state_changes() {
+		let spec = new_homestead_test();
+		let mut ethparams = get_default_ethash_params();
+		ethparams.irregular_state_changes.insert(1, IrregularStateChange {
+			balance_moves: vec![],
+			storage_writes: vec![StorageWrite { address: Address::from(10), key: H256::from(1), value: H256::from(2) }],
+		});
+		let engine = Ethash::new(spec.params.clone(), ethparams, BTreeMap::new());
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_state_db();
+		let db = spec.ensure_db_good(db_result.take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(&engine, Default::default(), false, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close();
+		assert_eq!(b.state().storage_at(&Address::from(10), &H256::from(1)).unwrap(), H256::from(2));
+		assert_eq!(engine.additional_params().get("irregularStateChanges"), Some(&"1".to_owned()));
+	}
+
+	#[test]
+	fn on_new_block_records_parent_hash() {
+		let spec = new_homestead_test();
+		let mut params = spec.params.clone();
+		params.eip210_transition = 1;
+		params.eip210_contract_address = Address::from(0xf0);
+		// stores the first word of call data
+		params.eip210_contract_code = "600035600055".from_hex().unwrap();
+		let engine = Ethash::new(params, get_default_ethash_params(), BTreeMap::new());
+		let genesis_header = spec.genesis_header();
+		let mut db_result = get_temp_state_db();
+		let db = spec.ensure_db_good(db_result.take(), &Default::default()).unwrap();
+		let last_hashes = Arc::new(vec![genesis_header.hash()]);
+		let b = OpenBlock::new(&engine, Default::default(), false, db, &genesis_header, last_hashes, Address::zero(), (3141562.into(), 31415620.into()), vec![]).unwrap();
+		let b = b.close();
+		assert_eq!(b.state().storage_at(&Address::from(0xf0), &H256::new()).unwrap(), genesis_header.hash());
+		assert_eq!(b.system_calls().len(), 1);
+		assert_eq!(b.system_calls()[0].contract, Address::from(0xf0));
+	}
+
 	#[test]
 	fn on_close_block_with_uncle() {
 		let spec = new_morden();
@@ -660,6 +763,26 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn caches_verified_seals() {
+		let spec = new_homestead_test();
+		let ethash = Ethash::new(spec.params, get_default_ethash_params(), BTreeMap::new());
+		let mut header: Header = Header::default();
+		header.set_difficulty(U256::one());
+		let result = ::ethash::EthashManager::new().compute_light(header.number(), &header.bare_hash().0, 0);
+		header.set_nonce_and_mix_hash(&H64::zero(), &H256(result.mix_hash));
+
+		assert!(ethash.verify_block_unordered(&header, None).is_ok());
+		assert_eq!(ethash.verified_seals_hits(), 0);
+		assert!(ethash.verify_block_unordered(&header, None).is_ok());
+		assert_eq!(ethash.verified_seals_hits(), 1);
+
+		// a different seal on the same header must be verified again.
+		header.set_nonce_and_mix_hash(&H64::from(1), &H256(result.mix_hash));
+		assert!(ethash.verify_block_unordered(&header, None).is_err());
+		assert_eq!(ethash.verified_seals_hits(), 1);
+	}
+
 	#[test]
 	fn can_verify_block_family_genesis_fail() {
 		let engine = new_morden().engine;
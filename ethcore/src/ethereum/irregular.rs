@@ -0,0 +1,163 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! One-off state changes defined in the chain spec (e.g. the DAO hard-fork refund).
+
+use util::{Address, H256, U256};
+use util::trie;
+use state::{State, Backend, CleanupMode};
+use ethjson;
+
+/// Balance moved by an irregular state change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceMove {
+	/// Account to take the balance from.
+	pub from: Address,
+	/// Account to credit.
+	pub to: Address,
+	/// Amount to move. Whole balance of `from` if `None`.
+	pub value: Option<U256>,
+}
+
+/// Storage slot overwritten by an irregular state change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageWrite {
+	/// Account owning the storage.
+	pub address: Address,
+	/// Storage key.
+	pub key: H256,
+	/// New value.
+	pub value: H256,
+}
+
+/// State changes applied at the beginning of a specific block.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IrregularStateChange {
+	/// Balance moves, applied first.
+	pub balance_moves: Vec<BalanceMove>,
+	/// Storage writes.
+	pub storage_writes: Vec<StorageWrite>,
+}
+
+impl IrregularStateChange {
+	/// Apply the changes to given state. Either all changes are applied or, if any of them
+	/// fails (e.g. moves more than the balance of an account), none.
+	pub fn apply<B: Backend>(&self, state: &mut State<B>) -> Result<(), String> {
+		state.checkpoint();
+		match self.apply_all(state) {
+			Ok(()) => {
+				state.discard_checkpoint();
+				Ok(())
+			},
+			Err(e) => {
+				state.revert_to_checkpoint();
+				Err(e)
+			},
+		}
+	}
+
+	fn apply_all<B: Backend>(&self, state: &mut State<B>) -> Result<(), String> {
+		for m in &self.balance_moves {
+			let balance = state.balance(&m.from).map_err(trie_error)?;
+			let value = match m.value {
+				Some(value) if value > balance => return Err(format!("Account {} has balance {}, can't move {}", m.from, balance, value)),
+				Some(value) => value,
+				None => balance,
+			};
+			state.transfer_balance(&m.from, &m.to, &value, CleanupMode::NoEmpty).map_err(trie_error)?;
+		}
+		for w in &self.storage_writes {
+			state.set_storage(&w.address, w.key.clone(), w.value.clone()).map_err(trie_error)?;
+		}
+		Ok(())
+	}
+}
+
+fn trie_error(e: Box<trie::TrieError>) -> String {
+	format!("{}", e)
+}
+
+impl From<ethjson::spec::IrregularStateChange> for IrregularStateChange {
+	fn from(c: ethjson::spec::IrregularStateChange) -> Self {
+		IrregularStateChange {
+			balance_moves: c.balance_moves.unwrap_or_else(Vec::new).into_iter().map(|m| BalanceMove {
+				from: m.from.into(),
+				to: m.to.into(),
+				value: m.value.map(Into::into),
+			}).collect(),
+			storage_writes: c.storage_writes.unwrap_or_else(Vec::new).into_iter().map(|w| StorageWrite {
+				address: w.address.into(),
+				key: w.key.into(),
+				value: w.value.into(),
+			}).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::{Address, H256, U256};
+	use state::CleanupMode;
+	use tests::helpers::get_temp_state;
+	use super::{IrregularStateChange, BalanceMove, StorageWrite};
+
+	#[test]
+	fn should_apply_balance_moves_and_storage_writes() {
+		let mut state_result = get_temp_state();
+		let state = state_result.reference_mut();
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		state.add_balance(&a, &U256::from(100), CleanupMode::NoEmpty).unwrap();
+		state.add_balance(&b, &U256::from(50), CleanupMode::NoEmpty).unwrap();
+
+		let change = IrregularStateChange {
+			balance_moves: vec![
+				BalanceMove { from: a, to: c, value: None },
+				BalanceMove { from: b, to: c, value: Some(U256::from(20)) },
+			],
+			storage_writes: vec![
+				StorageWrite { address: c, key: H256::from(1), value: H256::from(2) },
+			],
+		};
+		change.apply(&mut *state).unwrap();
+
+		assert_eq!(state.balance(&a).unwrap(), U256::zero());
+		assert_eq!(state.balance(&b).unwrap(), U256::from(30));
+		assert_eq!(state.balance(&c).unwrap(), U256::from(120));
+		assert_eq!(state.storage_at(&c, &H256::from(1)).unwrap(), H256::from(2));
+	}
+
+	#[test]
+	fn should_not_move_more_than_balance() {
+		let mut state_result = get_temp_state();
+		let state = state_result.reference_mut();
+		let (a, b, c) = (Address::from(1), Address::from(2), Address::from(3));
+		state.add_balance(&a, &U256::from(100), CleanupMode::NoEmpty).unwrap();
+		state.add_balance(&b, &U256::from(50), CleanupMode::NoEmpty).unwrap();
+
+		let change = IrregularStateChange {
+			balance_moves: vec![
+				BalanceMove { from: a, to: c, value: None },
+				BalanceMove { from: b, to: c, value: Some(U256::from(51)) },
+			],
+			storage_writes: vec![],
+		};
+		assert!(change.apply(&mut *state).is_err());
+
+		assert_eq!(state.balance(&a).unwrap(), U256::from(100));
+		assert_eq!(state.balance(&b).unwrap(), U256::from(50));
+		assert_eq!(state.balance(&c).unwrap(), U256::zero());
+	}
+}
@@ -23,6 +23,8 @@
 pub mod ethash;
 /// Export the denominations module.
 pub mod denominations;
+/// Export the irregular state changes module.
+pub mod irregular;
 
 pub use self::ethash::{Ethash};
 pub use self::denominations::*;
@@ -17,7 +17,7 @@
 //! Evm interface.
 
 use std::{ops, cmp, fmt};
-use util::{U128, U256, U512, Uint, trie};
+use util::{U128, U256, U512, Uint, Bytes, trie};
 use action_params::ActionParams;
 use evm::Ext;
 
@@ -59,6 +59,13 @@ pub enum Error {
 		/// What was the stack limit
 		limit: usize
 	},
+	/// `OutOfBounds` is returned when `RETURNDATACOPY` reads past the end of the return data buffer.
+	OutOfBounds,
+	/// Execution was reverted with `REVERT` instruction. Unused gas is kept,
+	/// but state changes are discarded.
+	Reverted,
+	/// Returned when a state-modifying instruction is executed within a static call context.
+	MutableCallInStaticContext,
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal(String),
@@ -79,6 +86,9 @@ impl fmt::Display for Error {
 			BadInstruction { .. } => "Bad instruction",
 			StackUnderflow { .. } => "Stack underflow",
 			OutOfStack { .. } => "Out of stack",
+			OutOfBounds => "Out of bounds",
+			Reverted => "Reverted",
+			MutableCallInStaticContext => "Mutable call in static context",
 			Internal(ref msg) => msg,
 		};
 		message.fmt(f)
@@ -94,8 +104,26 @@ pub type Result<T> = ::std::result::Result<T, Error>;
 pub enum GasLeft<'a> {
 	/// Known gas left
 	Known(U256),
-	/// Return instruction must be processed.
-	NeedsReturn(U256, &'a [u8]),
+	/// Return or revert instruction must be processed.
+	NeedsReturn {
+		/// Amount of gas left.
+		gas_left: U256,
+		/// Return data.
+		data: &'a [u8],
+		/// Whether state changes should be applied (`false` for `REVERT`).
+		apply_state: bool,
+	},
+}
+
+/// Result of finalized execution.
+#[derive(Debug, PartialEq)]
+pub struct FinalizationResult {
+	/// Final amount of gas left.
+	pub gas_left: U256,
+	/// Whether state changes should be applied.
+	pub apply_state: bool,
+	/// Data returned by `RETURN` or `REVERT`.
+	pub return_data: Bytes,
 }
 
 /// Types that can be "finalized" using an EVM.
@@ -104,14 +132,15 @@ pub enum GasLeft<'a> {
 /// `Reult<GasLeft<'a>>`.
 pub trait Finalize {
 	/// Consume the externalities, call return if necessary, and produce a final amount of gas left.
-	fn finalize<E: Ext>(self, ext: E) -> Result<U256>;
+	fn finalize<E: Ext>(self, ext: E) -> Result<FinalizationResult>;
 }
 
 impl<'a> Finalize for Result<GasLeft<'a>> {
-	fn finalize<E: Ext>(self, ext: E) -> Result<U256> {
+	fn finalize<E: Ext>(self, ext: E) -> Result<FinalizationResult> {
 		match self {
-			Ok(GasLeft::Known(gas)) => Ok(gas),
-			Ok(GasLeft::NeedsReturn(gas, ret_code)) => ext.ret(&gas, ret_code),
+			Ok(GasLeft::Known(gas_left)) => Ok(FinalizationResult { gas_left: gas_left, apply_state: true, return_data: Bytes::new() }),
+			Ok(GasLeft::NeedsReturn { gas_left, data, apply_state }) => ext.ret(&gas_left, data, apply_state)
+				.map(|gas_left| FinalizationResult { gas_left: gas_left, apply_state: apply_state, return_data: data.to_vec() }),
 			Err(err) => Err(err),
 		}
 	}
@@ -26,6 +26,9 @@ pub enum ContractCreateResult {
 	/// Returned when creation was successfull.
 	/// Contains an address of newly created contract and gas left.
 	Created(Address, U256),
+	/// Returned when contract creation was reverted.
+	/// Contains gas left and return data.
+	Reverted(U256, Bytes),
 	/// Returned when contract creation failed.
 	/// VM doesn't have to know the reason.
 	Failed
@@ -34,8 +37,11 @@ pub enum ContractCreateResult {
 /// Result of externalities call function.
 pub enum MessageCallResult {
 	/// Returned when message call was successfull.
-	/// Contains gas left.
-	Success(U256),
+	/// Contains gas left and return data.
+	Success(U256, Bytes),
+	/// Returned when message call was reverted.
+	/// Contains gas left and return data.
+	Reverted(U256, Bytes),
 	/// Returned when message call failed.
 	/// VM doesn't have to know the reason.
 	Failed
@@ -96,9 +102,10 @@ pub trait Ext {
 	/// Creates log entry with given topics and data
 	fn log(&mut self, topics: Vec<H256>, data: &[u8]);
 
-	/// Should be called when transaction calls `RETURN` opcode.
+	/// Should be called when transaction calls `RETURN` or `REVERT` opcode.
 	/// Returns gas_left if cost of returning the data is not too high.
-	fn ret(self, gas: &U256, data: &[u8]) -> evm::Result<U256> where Self: Sized;
+	/// Code of a created contract is only stored if `apply_state` is set.
+	fn ret(self, gas: &U256, data: &[u8], apply_state: bool) -> evm::Result<U256> where Self: Sized;
 
 	/// Should be called when contract commits suicide.
 	/// Address to which funds should be refunded.
@@ -116,6 +123,9 @@ pub trait Ext {
 	/// then A depth is 0, B is 1, C is 2 and so on.
 	fn depth(&self) -> usize;
 
+	/// Whether the code is executed within a static call, where state modifications are forbidden.
+	fn is_static(&self) -> bool;
+
 	/// Increments sstore refunds count by 1.
 	fn inc_sstore_clears(&mut self);
 
@@ -84,8 +84,16 @@ enum InstructionResult<Gas> {
 	Ok,
 	UnusedGas(Gas),
 	JumpToPosition(U256),
-	// gas left, init_orf, init_size
-	StopExecutionNeedsReturn(Gas, U256, U256),
+	StopExecutionNeedsReturn {
+		/// Gas left.
+		gas: Gas,
+		/// Return data offset.
+		init_off: U256,
+		/// Return data size.
+		init_size: U256,
+		/// Apply or revert state changes.
+		apply: bool,
+	},
 	StopExecution,
 }
 
@@ -93,6 +101,7 @@ enum InstructionResult<Gas> {
 /// Intepreter EVM implementation
 pub struct Interpreter<Cost: CostType> {
 	mem: Vec<u8>,
+	return_data: Bytes,
 	cache: Arc<SharedCache>,
 	_type: PhantomData<Cost>,
 }
@@ -100,6 +109,7 @@ pub struct Interpreter<Cost: CostType> {
 impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 	fn exec(&mut self, params: ActionParams, ext: &mut evm::Ext) -> evm::Result<GasLeft> {
 		self.mem.clear();
+		self.return_data.clear();
 
 		let mut informant = informant::EvmInformant::new(ext.depth());
 
@@ -156,9 +166,13 @@ impl<Cost: CostType> evm::Evm for Interpreter<Cost> {
 					let pos = self.verify_jump(position, &valid_jump_destinations)?;
 					reader.position = pos;
 				},
-				InstructionResult::StopExecutionNeedsReturn(gas, off, size) => {
+				InstructionResult::StopExecutionNeedsReturn {gas, init_off, init_size, apply} => {
 					informant.done();
-					return Ok(GasLeft::NeedsReturn(gas.as_u256(), self.mem.read_slice(off, size)));
+					return Ok(GasLeft::NeedsReturn {
+						gas_left: gas.as_u256(),
+						data: self.mem.read_slice(init_off, init_size),
+						apply_state: apply
+					});
 				},
 				InstructionResult::StopExecution => break,
 				_ => {},
@@ -174,6 +188,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 	pub fn new(cache: Arc<SharedCache>) -> Interpreter<Cost> {
 		Interpreter {
 			mem: Vec::new(),
+			return_data: Vec::new(),
 			cache: cache,
 			_type: PhantomData::default(),
 		}
@@ -182,7 +197,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 	fn verify_instruction(&self, ext: &evm::Ext, instruction: Instruction, info: &InstructionInfo, stack: &Stack<U256>) -> evm::Result<()> {
 		let schedule = ext.schedule();
 
-		if !schedule.have_delegate_call && instruction == instructions::DELEGATECALL {
+		if (!schedule.have_delegate_call && instruction == instructions::DELEGATECALL) ||
+			(!schedule.have_revert && instruction == instructions::REVERT) ||
+			(!schedule.have_return_data && (instruction == instructions::RETURNDATASIZE || instruction == instructions::RETURNDATACOPY)) ||
+			(!schedule.have_static_call && instruction == instructions::STATICCALL) {
 			return Err(evm::Error::BadInstruction {
 				instruction: instruction
 			});
@@ -206,11 +224,21 @@ impl<Cost: CostType> Interpreter<Cost> {
 				wanted: info.ret - info.args,
 				limit: schedule.stack_limit
 			})
+		} else if ext.is_static() && Self::modifies_state(instruction, stack) {
+			Err(evm::Error::MutableCallInStaticContext)
 		} else {
 			Ok(())
 		}
 	}
 
+	fn modifies_state(instruction: Instruction, stack: &Stack<U256>) -> bool {
+		match instruction {
+			instructions::SSTORE | instructions::CREATE | instructions::SUICIDE | instructions::LOG0...instructions::LOG4 => true,
+			instructions::CALL => !stack.peek(2).is_zero(),
+			_ => false,
+		}
+	}
+
 	fn mem_written(
 		instruction: Instruction,
 		stack: &Stack<U256>
@@ -218,10 +246,10 @@ impl<Cost: CostType> Interpreter<Cost> {
 		match instruction {
 			instructions::MSTORE | instructions::MLOAD => Some((stack.peek(0).low_u64() as usize, 32)),
 			instructions::MSTORE8 => Some((stack.peek(0).low_u64() as usize, 1)),
-			instructions::CALLDATACOPY | instructions::CODECOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
+			instructions::CALLDATACOPY | instructions::CODECOPY | instructions::RETURNDATACOPY => Some((stack.peek(0).low_u64() as usize, stack.peek(2).low_u64() as usize)),
 			instructions::EXTCODECOPY => Some((stack.peek(1).low_u64() as usize, stack.peek(3).low_u64() as usize)),
 			instructions::CALL | instructions::CALLCODE => Some((stack.peek(5).low_u64() as usize, stack.peek(6).low_u64() as usize)),
-			instructions::DELEGATECALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
+			instructions::DELEGATECALL | instructions::STATICCALL => Some((stack.peek(4).low_u64() as usize, stack.peek(5).low_u64() as usize)),
 			_ => None,
 		}
 	}
@@ -272,6 +300,8 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let init_size = stack.pop_back();
 				let create_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is `CREATE`; qed");
 
+				self.return_data.clear();
+
 				let contract_code = self.mem.read_slice(init_off, init_size);
 				let can_create = ext.balance(&params.address)? >= endowment && ext.depth() < ext.schedule().max_depth;
 
@@ -286,23 +316,29 @@ impl<Cost: CostType> Interpreter<Cost> {
 						stack.push(address_to_u256(address));
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
 					},
+					ContractCreateResult::Reverted(gas_left, return_data) => {
+						self.return_data = return_data;
+						stack.push(U256::zero());
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater.")))
+					},
 					ContractCreateResult::Failed => {
 						stack.push(U256::zero());
 						Ok(InstructionResult::Ok)
 					}
 				};
 			},
-			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL => {
+			instructions::CALL | instructions::CALLCODE | instructions::DELEGATECALL | instructions::STATICCALL => {
 				assert!(ext.schedule().call_value_transfer_gas > ext.schedule().call_stipend, "overflow possible");
 				stack.pop_back();
-				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`; qed");
+				let call_gas = provided.expect("`provided` comes through Self::exec from `Gasometer::get_gas_cost_mem`; `gas_gas_mem_cost` guarantees `Some` when instruction is `CALL`/`CALLCODE`/`DELEGATECALL`/`CREATE`; this is one of `CALL`/`CALLCODE`/`DELEGATECALL`/`STATICCALL`; qed");
 				let code_address = stack.pop_back();
 				let code_address = u256_to_address(&code_address);
 
-				let value = if instruction == instructions::DELEGATECALL {
-					None
-				} else {
-					Some(stack.pop_back())
+				let value = match instruction {
+					instructions::DELEGATECALL => None,
+					// static calls never carry value, the callee sees zero rather than the caller's value
+					instructions::STATICCALL => Some(U256::zero()),
+					_ => Some(stack.pop_back()),
 				};
 
 				let in_off = stack.pop_back();
@@ -310,6 +346,8 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let out_off = stack.pop_back();
 				let out_size = stack.pop_back();
 
+				self.return_data.clear();
+
 				// Add stipend (only CALL|CALLCODE when value > 0)
 				let call_gas = call_gas + value.map_or_else(|| Cost::from(0), |val| match val.is_zero() {
 					false => Cost::from(ext.schedule().call_stipend),
@@ -327,6 +365,7 @@ impl<Cost: CostType> Interpreter<Cost> {
 						(&params.address, &params.address, has_balance, CallType::CallCode)
 					},
 					instructions::DELEGATECALL => (&params.sender, &params.address, true, CallType::DelegateCall),
+					instructions::STATICCALL => (&params.address, &code_address, true, CallType::StaticCall),
 					_ => panic!(format!("Unexpected instruction {} in CALL branch.", instruction))
 				};
 
@@ -345,10 +384,16 @@ impl<Cost: CostType> Interpreter<Cost> {
 				};
 
 				return match call_result {
-					MessageCallResult::Success(gas_left) => {
+					MessageCallResult::Success(gas_left, return_data) => {
+						self.return_data = return_data;
 						stack.push(U256::one());
 						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
 					},
+					MessageCallResult::Reverted(gas_left, return_data) => {
+						self.return_data = return_data;
+						stack.push(U256::zero());
+						Ok(InstructionResult::UnusedGas(Cost::from_u256(gas_left).expect("Gas left cannot be greater then current one")))
+					},
 					MessageCallResult::Failed  => {
 						stack.push(U256::zero());
 						Ok(InstructionResult::Ok)
@@ -359,7 +404,13 @@ impl<Cost: CostType> Interpreter<Cost> {
 				let init_off = stack.pop_back();
 				let init_size = stack.pop_back();
 
-				return Ok(InstructionResult::StopExecutionNeedsReturn(gas, init_off, init_size))
+				return Ok(InstructionResult::StopExecutionNeedsReturn {gas: gas, init_off: init_off, init_size: init_size, apply: true})
+			},
+			instructions::REVERT => {
+				let init_off = stack.pop_back();
+				let init_size = stack.pop_back();
+
+				return Ok(InstructionResult::StopExecutionNeedsReturn {gas: gas, init_off: init_off, init_size: init_size, apply: false})
 			},
 			instructions::STOP => {
 				return Ok(InstructionResult::StopExecution);
@@ -483,6 +534,21 @@ impl<Cost: CostType> Interpreter<Cost> {
 			instructions::CODECOPY => {
 				self.copy_data_to_memory(stack, params.code.as_ref().map_or_else(|| &[] as &[u8], |c| &**c as &[u8]));
 			},
+			instructions::RETURNDATASIZE => {
+				stack.push(U256::from(self.return_data.len()));
+			},
+			instructions::RETURNDATACOPY => {
+				// reading past the end of the buffer is an exceptional halt, unlike other copies
+				let source_offset = *stack.peek(1);
+				let size = *stack.peek(2);
+				let (end, overflow) = source_offset.overflowing_add(size);
+				if overflow || end > U256::from(self.return_data.len()) {
+					return Err(evm::Error::OutOfBounds);
+				}
+				let return_data = ::std::mem::replace(&mut self.return_data, Vec::new());
+				self.copy_data_to_memory(stack, &return_data);
+				self.return_data = return_data;
+			},
 			instructions::EXTCODECOPY => {
 				let address = u256_to_address(&stack.pop_back());
 				let code = ext.extcode(&address)?;
@@ -16,12 +16,21 @@
 
 //! Cost schedule and other parameterisations for the EVM.
 
+use std::collections::BTreeMap;
+
 /// Definition of the cost schedule and other parameterisations for the EVM.
+#[derive(Debug, PartialEq)]
 pub struct Schedule {
 	/// Does it support exceptional failed code deposit
 	pub exceptional_failed_code_deposit: bool,
 	/// Does it have a delegate cal
 	pub have_delegate_call: bool,
+	/// Does it have a REVERT instruction
+	pub have_revert: bool,
+	/// Does it have RETURNDATASIZE and RETURNDATACOPY instructions
+	pub have_return_data: bool,
+	/// Does it have a STATICCALL instruction
+	pub have_static_call: bool,
 	/// VM stack limit
 	pub stack_limit: usize,
 	/// Max number of nested calls/creates
@@ -117,6 +126,9 @@ impl Schedule {
 		Schedule {
 			exceptional_failed_code_deposit: true,
 			have_delegate_call: true,
+			have_revert: false,
+			have_return_data: false,
+			have_static_call: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -162,6 +174,9 @@ impl Schedule {
 		Schedule {
 			exceptional_failed_code_deposit: efcd,
 			have_delegate_call: hdc,
+			have_revert: false,
+			have_return_data: false,
+			have_static_call: false,
 			stack_limit: 1024,
 			max_depth: 1024,
 			tier_step_gas: [0, 2, 3, 5, 8, 10, 20, 0],
@@ -204,6 +219,182 @@ impl Schedule {
 	}
 }
 
+macro_rules! schedule_override {
+	($($(#[$doc:meta])* $field:ident: $t:ty,)*) => {
+		/// Changes to the schedule activated by a fork. Fields left unset keep their previous values.
+		#[derive(Debug, Default, Clone, PartialEq)]
+		pub struct ScheduleOverride {
+			$($(#[$doc])* pub $field: Option<$t>,)*
+		}
+
+		impl ScheduleOverride {
+			/// Apply the changes to given schedule.
+			pub fn apply(&self, schedule: &mut Schedule) {
+				$(if let Some(value) = self.$field {
+					schedule.$field = value.into();
+				})*
+			}
+
+			/// Names of the changed schedule fields mapped to their new values.
+			pub fn changes(&self) -> BTreeMap<String, String> {
+				let mut changes = BTreeMap::new();
+				$(if let Some(value) = self.$field {
+					changes.insert(stringify!($field).to_owned(), value.to_string());
+				})*
+				changes
+			}
+		}
+	}
+}
+
+schedule_override! {
+	/// See `Schedule::exceptional_failed_code_deposit`.
+	exceptional_failed_code_deposit: bool,
+	/// See `Schedule::have_delegate_call`.
+	have_delegate_call: bool,
+	/// See `Schedule::stack_limit`.
+	stack_limit: usize,
+	/// See `Schedule::max_depth`.
+	max_depth: usize,
+	/// See `Schedule::exp_gas`.
+	exp_gas: usize,
+	/// See `Schedule::exp_byte_gas`.
+	exp_byte_gas: usize,
+	/// See `Schedule::sha3_gas`.
+	sha3_gas: usize,
+	/// See `Schedule::sha3_word_gas`.
+	sha3_word_gas: usize,
+	/// See `Schedule::sload_gas`.
+	sload_gas: usize,
+	/// See `Schedule::sstore_set_gas`.
+	sstore_set_gas: usize,
+	/// See `Schedule::sstore_reset_gas`.
+	sstore_reset_gas: usize,
+	/// See `Schedule::sstore_refund_gas`.
+	sstore_refund_gas: usize,
+	/// See `Schedule::jumpdest_gas`.
+	jumpdest_gas: usize,
+	/// See `Schedule::log_gas`.
+	log_gas: usize,
+	/// See `Schedule::log_data_gas`.
+	log_data_gas: usize,
+	/// See `Schedule::log_topic_gas`.
+	log_topic_gas: usize,
+	/// See `Schedule::create_gas`.
+	create_gas: usize,
+	/// See `Schedule::call_gas`.
+	call_gas: usize,
+	/// See `Schedule::call_stipend`.
+	call_stipend: usize,
+	/// See `Schedule::call_value_transfer_gas`.
+	call_value_transfer_gas: usize,
+	/// See `Schedule::call_new_account_gas`.
+	call_new_account_gas: usize,
+	/// See `Schedule::suicide_refund_gas`.
+	suicide_refund_gas: usize,
+	/// See `Schedule::memory_gas`.
+	memory_gas: usize,
+	/// See `Schedule::create_data_gas`.
+	create_data_gas: usize,
+	/// See `Schedule::create_data_limit`.
+	create_data_limit: usize,
+	/// See `Schedule::tx_gas`.
+	tx_gas: usize,
+	/// See `Schedule::tx_create_gas`.
+	tx_create_gas: usize,
+	/// See `Schedule::tx_data_zero_gas`.
+	tx_data_zero_gas: usize,
+	/// See `Schedule::tx_data_non_zero_gas`.
+	tx_data_non_zero_gas: usize,
+	/// See `Schedule::copy_gas`.
+	copy_gas: usize,
+	/// See `Schedule::extcodesize_gas`.
+	extcodesize_gas: usize,
+	/// See `Schedule::extcodecopy_base_gas`.
+	extcodecopy_base_gas: usize,
+	/// See `Schedule::balance_gas`.
+	balance_gas: usize,
+	/// See `Schedule::suicide_gas`.
+	suicide_gas: usize,
+	/// See `Schedule::suicide_to_new_account_cost`.
+	suicide_to_new_account_cost: usize,
+	/// Enables the all-but-one-64th rule (or another divisor) for `CALL` and `CREATE` gas.
+	sub_gas_cap_divisor: usize,
+	/// See `Schedule::no_empty`.
+	no_empty: bool,
+	/// See `Schedule::kill_empty`.
+	kill_empty: bool,
+}
+
+impl ScheduleOverride {
+	/// Changes of the Homestead fork.
+	pub fn homestead() -> Self {
+		ScheduleOverride {
+			exceptional_failed_code_deposit: Some(true),
+			have_delegate_call: Some(true),
+			tx_create_gas: Some(53000),
+			..Default::default()
+		}
+	}
+
+	/// Changes of the EIP-150 gas repricing.
+	pub fn eip150(max_code_size: usize) -> Self {
+		ScheduleOverride {
+			sload_gas: Some(200),
+			call_gas: Some(700),
+			create_data_limit: Some(max_code_size),
+			extcodesize_gas: Some(700),
+			extcodecopy_base_gas: Some(700),
+			balance_gas: Some(400),
+			suicide_gas: Some(5000),
+			suicide_to_new_account_cost: Some(25000),
+			sub_gas_cap_divisor: Some(64),
+			..Default::default()
+		}
+	}
+}
+
+/// Builds the schedule of a block by applying overrides activated at or before it,
+/// in block order, on top of the Frontier schedule.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ScheduleBuilder {
+	transitions: Vec<(u64, ScheduleOverride)>,
+}
+
+impl ScheduleBuilder {
+	/// Builder with no overrides.
+	pub fn new() -> Self {
+		ScheduleBuilder::default()
+	}
+
+	/// Activate the override at given block. Overrides activated at the same block
+	/// are applied in the order they were added.
+	pub fn transition(mut self, block: u64, changes: ScheduleOverride) -> Self {
+		let position = self.transitions.iter().position(|&(b, _)| b > block).unwrap_or(self.transitions.len());
+		self.transitions.insert(position, (block, changes));
+		self
+	}
+
+	/// Overrides with the blocks they activate at, in block order.
+	pub fn transitions(&self) -> &[(u64, ScheduleOverride)] {
+		&self.transitions
+	}
+
+	/// Apply overrides active at given block to the schedule.
+	pub fn apply(&self, number: u64, schedule: &mut Schedule) {
+		for &(_, ref changes) in self.transitions.iter().take_while(|&&(block, _)| block <= number) {
+			changes.apply(schedule);
+		}
+	}
+
+	/// Build the schedule for given block.
+	pub fn build(&self, number: u64) -> Schedule {
+		let mut schedule = Schedule::new_frontier();
+		self.apply(number, &mut schedule);
+		schedule
+	}
+}
+
 #[test]
 #[cfg(test)]
 fn schedule_evm_assumptions() {
@@ -214,3 +405,29 @@ fn schedule_evm_assumptions() {
 	assert_eq!(s1.quad_coeff_div, 512);
 	assert_eq!(s2.quad_coeff_div, 512);
 }
+
+#[test]
+#[cfg(test)]
+fn schedule_builder_matches_eras() {
+	let builder = ScheduleBuilder::new()
+		.transition(10, ScheduleOverride::homestead())
+		.transition(20, ScheduleOverride::eip150(24576))
+		.transition(20, ScheduleOverride { exp_byte_gas: Some(50), no_empty: Some(true), kill_empty: Some(true), ..Default::default() });
+
+	assert_eq!(builder.build(9), Schedule::new_frontier());
+	assert_eq!(builder.build(10), Schedule::new_homestead());
+	assert_eq!(builder.build(20), Schedule::new_post_eip150(24576, true, true, true));
+}
+
+#[test]
+#[cfg(test)]
+fn schedule_builder_applies_in_block_order() {
+	let builder = ScheduleBuilder::new()
+		.transition(20, ScheduleOverride { sload_gas: Some(800), ..Default::default() })
+		.transition(10, ScheduleOverride { sload_gas: Some(400), call_gas: Some(1000), ..Default::default() });
+
+	assert_eq!(builder.build(15).sload_gas, 400);
+	let schedule = builder.build(20);
+	assert_eq!(schedule.sload_gas, 800);
+	assert_eq!(schedule.call_gas, 1000);
+}
@@ -58,13 +58,15 @@ pub struct FakeExt {
 	schedule: Schedule,
 	balances: HashMap<Address, U256>,
 	calls: HashSet<FakeCall>,
+	call_return_data: Bytes,
+	is_static: bool,
 }
 
 // similar to the normal `finalize` function, but ignoring NeedsReturn.
 fn test_finalize(res: Result<GasLeft, evm::Error>) -> Result<U256, evm::Error> {
 	match res {
 		Ok(GasLeft::Known(gas)) => Ok(gas),
-		Ok(GasLeft::NeedsReturn(_, _)) => unimplemented!(), // since ret is unimplemented.
+		Ok(GasLeft::NeedsReturn { .. }) => unimplemented!(), // since ret is unimplemented.
 		Err(e) => Err(e),
 	}
 }
@@ -144,7 +146,7 @@ impl Ext for FakeExt {
 			data: data.to_vec(),
 			code_address: Some(code_address.clone())
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, self.call_return_data.clone())
 	}
 
 	fn extcode(&self, address: &Address) -> trie::Result<Arc<Bytes>> {
@@ -162,7 +164,7 @@ impl Ext for FakeExt {
 		});
 	}
 
-	fn ret(self, _gas: &U256, _data: &[u8]) -> evm::Result<U256> {
+	fn ret(self, _gas: &U256, _data: &[u8], _apply_state: bool) -> evm::Result<U256> {
 		unimplemented!();
 	}
 
@@ -182,6 +184,10 @@ impl Ext for FakeExt {
 		self.depth
 	}
 
+	fn is_static(&self) -> bool {
+		self.is_static
+	}
+
 	fn inc_sstore_clears(&mut self) {
 		self.sstore_clears += 1;
 	}
@@ -847,6 +853,153 @@ fn test_badinstruction_int() {
 	}
 }
 
+#[test] // JIT doesn't support REVERT
+fn test_revert_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "602a60005260206000fd".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_revert = true;
+
+	{
+		let mut vm = factory.create(params.gas);
+		match vm.exec(params.clone(), &mut ext) {
+			Ok(GasLeft::NeedsReturn { gas_left, data, apply_state }) => {
+				assert_eq!(gas_left, U256::from(99_982));
+				assert_eq!(data, &*"000000000000000000000000000000000000000000000000000000000000002a".from_hex().unwrap());
+				assert!(!apply_state);
+			},
+			other => panic!("Expected revert, got {:?}", other),
+		}
+	}
+
+	// not activated yet
+	let mut ext = FakeExt::new();
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+	assert_eq!(err, evm::Error::BadInstruction { instruction: 0xfd });
+}
+
+#[test] // JIT doesn't support return data
+fn test_return_data_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "60006000600060006000610998611000f1503d6000556020600860003e600051600155".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_return_data = true;
+	ext.balances.insert(params.address.clone(), U256::zero());
+	ext.call_return_data = (0..40).collect();
+
+	{
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap();
+	}
+
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000028");
+	assert_store(&ext, 1, "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627");
+}
+
+#[test] // JIT doesn't support return data
+fn test_return_data_out_of_bounds_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "60006000600060006000610998611000f1506020600960003e".from_hex().unwrap();
+
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	let mut ext = FakeExt::new();
+	ext.schedule.have_return_data = true;
+	ext.balances.insert(params.address.clone(), U256::zero());
+	ext.call_return_data = (0..40).collect();
+
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+
+	assert_eq!(err, evm::Error::OutOfBounds);
+}
+
+#[test] // JIT doesn't support STATICCALL
+fn test_static_call_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+	let code = "6000600060006000610998611000fa600055".from_hex().unwrap();
+
+	let address = Address::from(0x155);
+	let code_address = Address::from(0x998);
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new(code));
+	params.address = address.clone();
+	let mut ext = FakeExt::new();
+	ext.schedule.have_static_call = true;
+
+	{
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params.clone(), &mut ext)).unwrap();
+	}
+
+	assert_set_contains(&ext.calls, &FakeCall {
+		call_type: FakeCallType::Call,
+		gas: U256::from(0x1000),
+		sender_address: Some(address.clone()),
+		receive_address: Some(code_address.clone()),
+		value: Some(U256::zero()),
+		data: vec!(),
+		code_address: Some(code_address.clone())
+	});
+	assert_store(&ext, 0, "0000000000000000000000000000000000000000000000000000000000000001");
+
+	// not activated yet
+	let mut ext = FakeExt::new();
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+	assert_eq!(err, evm::Error::BadInstruction { instruction: 0xfa });
+}
+
+#[test] // JIT doesn't support static context
+fn test_static_context_int() {
+	let factory = super::Factory::new(VMType::Interpreter, 1024 * 32);
+
+	// CALL without value is allowed
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new("60006000600060006000610998611000f1".from_hex().unwrap()));
+	let mut ext = FakeExt::new();
+	ext.is_static = true;
+	ext.balances.insert(params.address.clone(), U256::zero());
+
+	{
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap();
+	}
+	assert_eq!(ext.calls.len(), 1);
+
+	// SSTORE is not
+	let mut params = ActionParams::default();
+	params.gas = U256::from(100_000);
+	params.code = Some(Arc::new("6001600055".from_hex().unwrap()));
+	let mut ext = FakeExt::new();
+	ext.is_static = true;
+
+	let err = {
+		let mut vm = factory.create(params.gas);
+		test_finalize(vm.exec(params, &mut ext)).unwrap_err()
+	};
+	assert_eq!(err, evm::Error::MutableCallInStaticContext);
+	assert!(ext.store.is_empty());
+}
+
 evm_test!{test_pop: test_pop_jit, test_pop_int}
 fn test_pop(factory: super::Factory) {
 	let code = "60f060aa50600055".from_hex().unwrap();
@@ -22,7 +22,7 @@ use engines::Engine;
 use types::executed::CallType;
 use env_info::EnvInfo;
 use error::ExecutionError;
-use evm::{self, Ext, Factory, Finalize};
+use evm::{self, Ext, Factory, Finalize, FinalizationResult};
 use externalities::*;
 use trace::{FlatTrace, Tracer, NoopTracer, ExecutiveTracer, VMTrace, VMTracer, ExecutiveVMTracer, NoopVMTracer};
 use transaction::{Action, SignedTransaction};
@@ -62,6 +62,7 @@ pub struct Executive<'a, B: 'a + StateBackend> {
 	engine: &'a Engine,
 	vm_factory: &'a Factory,
 	depth: usize,
+	static_flag: bool,
 }
 
 impl<'a, B: 'a + StateBackend> Executive<'a, B> {
@@ -73,17 +74,19 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: 0,
+			static_flag: false,
 		}
 	}
 
 	/// Populates executive from parent properties. Increments executive depth.
-	pub fn from_parent(state: &'a mut State<B>, info: &'a EnvInfo, engine: &'a Engine, vm_factory: &'a Factory, parent_depth: usize) -> Self {
+	pub fn from_parent(state: &'a mut State<B>, info: &'a EnvInfo, engine: &'a Engine, vm_factory: &'a Factory, parent_depth: usize, static_flag: bool) -> Self {
 		Executive {
 			state: state,
 			info: info,
 			engine: engine,
 			vm_factory: vm_factory,
 			depth: parent_depth + 1,
+			static_flag: static_flag,
 		}
 	}
 
@@ -94,9 +97,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		substate: &'any mut Substate,
 		output: OutputPolicy<'any, 'any>,
 		tracer: &'any mut T,
-		vm_tracer: &'any mut V
+		vm_tracer: &'any mut V,
+		static_call: bool,
 	) -> Externalities<'any, T, V, B> where T: Tracer, V: VMTracer {
-		Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer)
+		let is_static = self.static_flag || static_call;
+		Externalities::new(self.state, self.info, self.engine, self.vm_factory, self.depth, origin_info, substate, output, tracer, vm_tracer, is_static)
 	}
 
 	/// This function should be used to execute transaction.
@@ -213,14 +218,15 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		output_policy: OutputPolicy,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 
+		let static_call = params.call_type == CallType::StaticCall;
 		let depth_threshold = ::io::LOCAL_STACK_SIZE.with(|sz| sz.get() / STACK_SIZE_PER_DEPTH);
 
 		// Ordinary execution - keep VM in same thread
 		if (self.depth + 1) % depth_threshold != 0 {
 			let vm_factory = self.vm_factory;
-			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
+			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer, static_call);
 			trace!(target: "executive", "ext.schedule.have_delegate_call: {}", ext.schedule().have_delegate_call);
 			return vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext);
 		}
@@ -230,7 +236,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		// https://github.com/aturon/crossbeam/issues/16
 		crossbeam::scope(|scope| {
 			let vm_factory = self.vm_factory;
-			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer);
+			let mut ext = self.as_externalities(OriginInfo::from(&params), unconfirmed_substate, output_policy, tracer, vm_tracer, static_call);
 
 			scope.spawn(move || {
 				vm_factory.create(params.gas).exec(params, &mut ext).finalize(ext)
@@ -241,7 +247,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 	/// Calls contract function with given contract params.
 	/// NOTE. It does not finalize the transaction (doesn't do refunds, nor suicides).
 	/// Modifies the substate and the output.
-	/// Returns either gas_left or `evm::Error`.
+	/// Returns either gas_left and whether state changes were applied, or `evm::Error`.
 	pub fn call<T, V>(
 		&mut self,
 		params: ActionParams,
@@ -249,7 +255,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		mut output: BytesRef,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
@@ -276,7 +282,9 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 			let cost = builtin.cost(data);
 			if cost <= params.gas {
-				builtin.execute(data, &mut output);
+				let mut builtin_output = Vec::new();
+				builtin.execute(data, &mut BytesRef::Flexible(&mut builtin_output));
+				output.write(0, &builtin_output);
 				self.state.discard_checkpoint();
 
 				// trace only top level calls to builtins to avoid DDoS attacks
@@ -294,7 +302,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					);
 				}
 
-				Ok(params.gas - cost)
+				Ok(FinalizationResult { gas_left: params.gas - cost, apply_state: true, return_data: builtin_output })
 			} else {
 				// just drain the whole gas
 				self.state.revert_to_checkpoint();
@@ -327,12 +335,13 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 
 				let traces = subtracer.traces();
 				match res {
-					Ok(ref gas_left) => tracer.trace_call(
+					Ok(FinalizationResult { gas_left, apply_state: true, .. }) => tracer.trace_call(
 						trace_info,
-						gas - *gas_left,
+						gas - gas_left,
 						trace_output,
 						traces
 					),
+					Ok(_) => tracer.trace_failed_call(trace_info, traces, evm::Error::Reverted.into()),
 					Err(ref e) => tracer.trace_failed_call(trace_info, traces, e.into()),
 				};
 
@@ -346,7 +355,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 				self.state.discard_checkpoint();
 
 				tracer.trace_call(trace_info, U256::zero(), trace_output, vec![]);
-				Ok(params.gas)
+				Ok(FinalizationResult { gas_left: params.gas, apply_state: true, return_data: Bytes::new() })
 			}
 		}
 	}
@@ -360,7 +369,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		substate: &mut Substate,
 		tracer: &mut T,
 		vm_tracer: &mut V
-	) -> evm::Result<U256> where T: Tracer, V: VMTracer {
+	) -> evm::Result<FinalizationResult> where T: Tracer, V: VMTracer {
 		// backup used in case of running out of gas
 		self.state.checkpoint();
 
@@ -393,13 +402,14 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		vm_tracer.done_subtrace(subvmtracer);
 
 		match res {
-			Ok(ref gas_left) => tracer.trace_create(
+			Ok(FinalizationResult { gas_left, apply_state: true, .. }) => tracer.trace_create(
 				trace_info,
-				gas - *gas_left,
+				gas - gas_left,
 				trace_output,
 				created,
 				subtracer.traces()
 			),
+			Ok(_) => tracer.trace_failed_create(trace_info, subtracer.traces(), evm::Error::Reverted.into()),
 			Err(ref e) => tracer.trace_failed_create(trace_info, subtracer.traces(), e.into())
 		};
 
@@ -412,7 +422,7 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		&mut self,
 		t: &SignedTransaction,
 		mut substate: Substate,
-		result: evm::Result<U256>,
+		result: evm::Result<FinalizationResult>,
 		output: Bytes,
 		trace: Vec<FlatTrace>,
 		vm_trace: Option<VMTrace>
@@ -426,8 +436,11 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		let refunds_bound = sstore_refunds + suicide_refunds;
 
 		// real ammount to refund
-		let gas_left_prerefund = match result { Ok(x) => x, _ => 0.into() };
-		let refunded = cmp::min(refunds_bound, (t.gas - gas_left_prerefund) >> 1);
+		let gas_left_prerefund = match result { Ok(FinalizationResult { gas_left, .. }) => gas_left, _ => 0.into() };
+		let refunded = match result {
+			Ok(FinalizationResult { apply_state: true, .. }) => cmp::min(refunds_bound, (t.gas - gas_left_prerefund) >> 1),
+			_ => U256::zero(),
+		};
 		let gas_left = gas_left_prerefund + refunded;
 
 		let gas_used = t.gas - gas_left;
@@ -473,6 +486,21 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 					state_diff: None,
 				})
 			},
+			Ok(FinalizationResult { apply_state: false, .. }) => {
+				Ok(Executed {
+					exception: Some(evm::Error::Reverted),
+					gas: t.gas,
+					gas_used: gas_used,
+					refunded: refunded,
+					cumulative_gas_used: self.info.gas_used + gas_used,
+					logs: vec![],
+					contracts_created: vec![],
+					output: output,
+					trace: trace,
+					vm_trace: vm_trace,
+					state_diff: None,
+				})
+			},
 			_ => {
 				Ok(Executed {
 					exception: None,
@@ -491,13 +519,17 @@ impl<'a, B: 'a + StateBackend> Executive<'a, B> {
 		}
 	}
 
-	fn enact_result(&mut self, result: &evm::Result<U256>, substate: &mut Substate, un_substate: Substate) {
+	fn enact_result(&mut self, result: &evm::Result<FinalizationResult>, substate: &mut Substate, un_substate: Substate) {
 		match *result {
 			Err(evm::Error::OutOfGas)
 				| Err(evm::Error::BadJumpDestination {..})
 				| Err(evm::Error::BadInstruction {.. })
 				| Err(evm::Error::StackUnderflow {..})
-				| Err(evm::Error::OutOfStack {..}) => {
+				| Err(evm::Error::OutOfStack {..})
+				| Err(evm::Error::OutOfBounds)
+				| Err(evm::Error::Reverted)
+				| Err(evm::Error::MutableCallInStaticContext)
+				| Ok(FinalizationResult { apply_state: false, .. }) => {
 					self.state.revert_to_checkpoint();
 			},
 			Ok(_) | Err(evm::Error::Internal(_)) => {
@@ -518,7 +550,7 @@ mod tests {
 	use util::bytes::BytesRef;
 	use action_params::{ActionParams, ActionValue};
 	use env_info::EnvInfo;
-	use evm::{Factory, VMType};
+	use evm::{self, Factory, VMType};
 	use error::ExecutionError;
 	use state::{Substate, CleanupMode};
 	use tests::helpers::*;
@@ -556,7 +588,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
+			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(79_975));
@@ -615,7 +647,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
+			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(62_976));
@@ -677,7 +709,7 @@ mod tests {
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
 			let output = BytesRef::Fixed(&mut[0u8;0]);
-			ex.call(params, &mut substate, output, &mut tracer, &mut vm_tracer).unwrap()
+			ex.call(params, &mut substate, output, &mut tracer, &mut vm_tracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(44_752));
@@ -787,7 +819,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.create(params.clone(), &mut substate, &mut tracer, &mut vm_tracer).unwrap()
+			ex.create(params.clone(), &mut substate, &mut tracer, &mut vm_tracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(96_776));
@@ -873,7 +905,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap()
+			ex.create(params, &mut substate, &mut NoopTracer, &mut NoopVMTracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(62_976));
@@ -986,7 +1018,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(73_237));
@@ -1031,7 +1063,7 @@ mod tests {
 
 		let gas_left = {
 			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
-			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap()
+			ex.call(params, &mut substate, BytesRef::Fixed(&mut []), &mut NoopTracer, &mut NoopVMTracer).unwrap().gas_left
 		};
 
 		assert_eq!(gas_left, U256::from(59_870));
@@ -1080,6 +1112,183 @@ mod tests {
 		assert_eq!(state.storage_at(&contract, &H256::new()).unwrap(), H256::from(&U256::from(1)));
 	}
 
+	#[test] // JIT doesn't support REVERT
+	fn test_transact_revert_int() {
+		// code:
+		//
+		// 60 01 - push 1
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 2a - push 42
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 32
+		// 60 00 - push 0
+		// fd - revert
+		let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+		let code = "6001600055602a60005260206000fd".from_hex().unwrap();
+		let address = Address::from_str("1000000000000000000000000000000000000001").unwrap();
+		let keypair = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(address.clone()),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::one(),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+		let sender = t.sender();
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address, code).unwrap();
+		state.add_balance(&sender, &U256::from(100_000), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+		let engine = TestEngine::new_metropolis(0);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		assert_eq!(executed.exception, Some(evm::Error::Reverted));
+		assert_eq!(executed.output, "000000000000000000000000000000000000000000000000000000000000002a".from_hex().unwrap());
+		// only the gas spent before `REVERT` is charged, the rest goes back to the sender
+		assert_eq!(executed.gas_used, U256::from(41_024));
+		assert_eq!(executed.refunded, U256::zero());
+		assert_eq!(executed.logs.len(), 0);
+		assert_eq!(state.balance(&sender).unwrap(), U256::from(100_000 - 41_024));
+		assert_eq!(state.nonce(&sender).unwrap(), U256::one());
+		assert_eq!(state.storage_at(&address, &H256::new()).unwrap(), H256::new());
+	}
+
+	#[test] // JIT doesn't support REVERT
+	fn test_call_reverted_int() {
+		// code a:
+		//
+		// 60 20 - push 32 (out size)
+		// 60 00 - push 0 (out offset)
+		// 60 00 - push 0 (in size)
+		// 60 00 - push 0 (in offset)
+		// 60 00 - push 0 (value)
+		// 73 2000000000000000000000000000000000000002 - push address b
+		// 61 c350 - push 50000 (gas)
+		// f1 - call
+		// 60 00 - push 0
+		// 55 - sstore
+		// 60 00 - push 0
+		// 51 - mload
+		// 60 01 - push 1
+		// 55 - sstore
+		//
+		// code b stores 1 and reverts with 42, as in `test_transact_revert_int`
+		let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+		let code_a = "60206000600060006000732000000000000000000000000000000000000002\
+			61c350f1600055600051600155".from_hex().unwrap();
+		let code_b = "6001600055602a60005260206000fd".from_hex().unwrap();
+		let address_a = Address::from_str("1000000000000000000000000000000000000001").unwrap();
+		let address_b = Address::from_str("2000000000000000000000000000000000000002").unwrap();
+		let keypair = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(address_a.clone()),
+			value: U256::zero(),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address_a, code_a).unwrap();
+		state.init_code(&address_b, code_b).unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+		let engine = TestEngine::new_metropolis(5);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		assert_eq!(executed.exception, None);
+		// b spends 20_024 of the 50_000 it was given, the rest goes back to a
+		assert_eq!(executed.gas_used, U256::from(66_100));
+		// the call failed, but its output was copied
+		assert_eq!(state.storage_at(&address_a, &H256::new()).unwrap(), H256::new());
+		assert_eq!(state.storage_at(&address_a, &H256::from(&U256::one())).unwrap(), H256::from(&U256::from(0x2a)));
+		// b's changes were discarded
+		assert_eq!(state.storage_at(&address_b, &H256::new()).unwrap(), H256::new());
+	}
+
+	#[test] // JIT doesn't support STATICCALL
+	fn test_static_call_value_int() {
+		// code a:
+		//
+		// 60 20 - push 32 (out size)
+		// 60 00 - push 0 (out offset)
+		// 60 00 - push 0 (in size)
+		// 60 00 - push 0 (in offset)
+		// 73 2000000000000000000000000000000000000002 - push address b
+		// 61 c350 - push 50000 (gas)
+		// fa - staticcall
+		// 60 01 - push 1
+		// 55 - sstore
+		// 60 00 - push 0
+		// 51 - mload
+		// 60 00 - push 0
+		// 55 - sstore
+		//
+		// code b:
+		//
+		// 34 - callvalue
+		// 60 00 - push 0
+		// 52 - mstore
+		// 60 20 - push 32
+		// 60 00 - push 0
+		// f3 - return
+		let factory = Factory::new(VMType::Interpreter, 1024 * 32);
+		let code_a = "602060006000600073200000000000000000000000000000000000000261c350\
+			fa600155600051600055".from_hex().unwrap();
+		let code_b = "3460005260206000f3".from_hex().unwrap();
+		let address_a = Address::from_str("1000000000000000000000000000000000000001").unwrap();
+		let address_b = Address::from_str("2000000000000000000000000000000000000002").unwrap();
+		let keypair = Random.generate().unwrap();
+		let t = Transaction {
+			action: Action::Call(address_a.clone()),
+			value: U256::from(17),
+			data: vec![],
+			gas: U256::from(100_000),
+			gas_price: U256::zero(),
+			nonce: U256::zero()
+		}.sign(keypair.secret(), None);
+		let sender = t.sender();
+
+		let mut state_result = get_temp_state();
+		let mut state = state_result.reference_mut();
+		state.init_code(&address_a, code_a).unwrap();
+		state.init_code(&address_b, code_b).unwrap();
+		state.add_balance(&sender, &U256::from(17), CleanupMode::NoEmpty).unwrap();
+		let mut info = EnvInfo::default();
+		info.gas_limit = U256::from(100_000);
+		let engine = TestEngine::new_metropolis(5);
+
+		let executed = {
+			let mut ex = Executive::new(&mut state, &info, &engine, &factory);
+			let opts = TransactOptions { check_nonce: true, tracing: false, vm_tracing: false };
+			ex.transact(&t, opts).unwrap()
+		};
+
+		assert_eq!(executed.exception, None);
+		assert_eq!(state.balance(&address_a).unwrap(), U256::from(17));
+		// the call succeeded and b saw no value
+		assert_eq!(state.storage_at(&address_a, &H256::from(&U256::one())).unwrap(), H256::from(&U256::one()));
+		assert_eq!(state.storage_at(&address_a, &H256::new()).unwrap(), H256::new());
+	}
+
 	evm_test!{test_transact_invalid_nonce: test_transact_invalid_nonce_jit, test_transact_invalid_nonce_int}
 	fn test_transact_invalid_nonce(factory: Factory) {
 		let keypair = Random.generate().unwrap();
@@ -77,7 +77,7 @@ impl<'a, T: 'a, V: 'a, B: 'a> TestExt<'a, T, V, B>
 	) -> trie::Result<Self> {
 		Ok(TestExt {
 			contract_address: contract_address(&address, &state.nonce(&address)?),
-			ext: Externalities::new(state, info, engine, vm_factory, depth, origin_info, substate, output, tracer, vm_tracer),
+			ext: Externalities::new(state, info, engine, vm_factory, depth, origin_info, substate, output, tracer, vm_tracer, false),
 			callcreates: vec![]
 		})
 	}
@@ -140,7 +140,7 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 			gas_limit: *gas,
 			value: value.unwrap()
 		});
-		MessageCallResult::Success(*gas)
+		MessageCallResult::Success(*gas, Bytes::new())
 	}
 
 	fn extcode(&self, address: &Address) -> trie::Result<Arc<Bytes>>  {
@@ -155,8 +155,8 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 		self.ext.log(topics, data)
 	}
 
-	fn ret(self, gas: &U256, data: &[u8]) -> Result<U256, evm::Error> {
-		self.ext.ret(gas, data)
+	fn ret(self, gas: &U256, data: &[u8], apply_state: bool) -> Result<U256, evm::Error> {
+		self.ext.ret(gas, data, apply_state)
 	}
 
 	fn suicide(&mut self, refund_address: &Address) -> trie::Result<()> {
@@ -175,6 +175,10 @@ impl<'a, T: 'a, V: 'a, B: 'a> Ext for TestExt<'a, T, V, B>
 		0
 	}
 
+	fn is_static(&self) -> bool {
+		self.ext.is_static()
+	}
+
 	fn inc_sstore_clears(&mut self) {
 		self.ext.inc_sstore_clears()
 	}
@@ -252,8 +256,9 @@ fn do_json_test_for(vm_type: &VMType, json_data: &[u8]) -> Vec<String> {
 
 		match res {
 			Err(_) => fail_unless(out_of_gas, "didn't expect to run out of gas."),
-			Ok(gas_left) => {
+			Ok(res) => {
 				fail_unless(!out_of_gas, "expected to run out of gas.");
+				let gas_left = res.gas_left;
 				fail_unless(Some(gas_left) == vm.gas_left.map(Into::into), "gas_left is incorrect");
 				let vm_output: Option<Vec<u8>> = vm.output.map(Into::into);
 				fail_unless(Some(output) == vm_output, "output is incorrect");
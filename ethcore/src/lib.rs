@@ -143,6 +143,7 @@ pub mod db;
 pub mod verification;
 pub mod state;
 pub mod env_info;
+pub mod cht;
 #[macro_use] pub mod evm;
 
 mod cache_manager;
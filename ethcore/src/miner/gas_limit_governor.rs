@@ -0,0 +1,139 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas limit targets governed by an on-chain contract.
+
+use types::ids::BlockId;
+use client::MiningBlockChainClient;
+use util::{Address, H256, U256, Mutex};
+
+/// Reads gas floor and ceiling targets from a contract, caching them per chain head.
+pub struct GasLimitGovernor {
+	contract: provider::Contract,
+	/// Targets read at given best block.
+	cache: Mutex<Option<(H256, Option<(U256, U256)>)>>,
+}
+
+impl GasLimitGovernor {
+	/// Create new governor reading targets from contract at given address.
+	pub fn new(address: Address) -> Self {
+		GasLimitGovernor {
+			contract: provider::Contract::new(address),
+			cache: Mutex::new(None),
+		}
+	}
+
+	/// Returns `(floor, ceil)` targets at the current best block, bounded so that
+	/// `min_gas_limit <= floor <= ceil`.
+	/// Returns `None` if the contract can't be queried or doesn't set any targets.
+	pub fn targets(&self, client: &MiningBlockChainClient, min_gas_limit: U256) -> Option<(U256, U256)> {
+		let best_hash = client.chain_info().best_block_hash;
+		let mut cache = self.cache.lock();
+		match *cache {
+			Some((ref hash, targets)) if hash == &best_hash => return targets,
+			_ => {},
+		}
+
+		let do_call = |a, d| client.call_contract(BlockId::Latest, a, d);
+		let targets = self.contract.gas_floor_target(&do_call)
+			.and_then(|floor| self.contract.gas_ceil_target(&do_call).map(|ceil| (floor, ceil)))
+			.map_err(|e| warn!(target: "miner", "Unable to read gas limit targets from contract: {}", e))
+			.ok()
+			.and_then(|targets| bound_targets(targets, min_gas_limit));
+
+		trace!(target: "miner", "Gas limit targets from contract at {}: {:?}", best_hash, targets);
+		*cache = Some((best_hash, targets));
+		targets
+	}
+}
+
+/// Keep targets within sane bounds. Zero floor means the contract doesn't govern targets.
+fn bound_targets((floor, ceil): (U256, U256), min_gas_limit: U256) -> Option<(U256, U256)> {
+	if floor.is_zero() {
+		return None;
+	}
+	let floor = ::std::cmp::max(floor, min_gas_limit);
+	let ceil = ::std::cmp::max(ceil, floor);
+	Some((floor, ceil))
+}
+
+mod provider {
+	// Autogenerated from JSON contract definition using Rust contract convertor.
+	// Command line: --jsonabi=GasLimitTargets.abi --explicit-do-call
+	#![allow(unused_imports)]
+	use std::string::String;
+	use std::result::Result;
+	use std::fmt;
+	use {util, ethabi};
+	use util::{Uint};
+
+	pub struct Contract {
+		contract: ethabi::Contract,
+		address: util::Address,
+
+	}
+	impl Contract {
+		pub fn new(address: util::Address) -> Self
+			{
+			Contract {
+				contract: ethabi::Contract::new(ethabi::Interface::load(b"[{\"constant\":true,\"inputs\":[],\"name\":\"gasFloorTarget\",\"outputs\":[{\"name\":\"\",\"type\":\"uint256\"}],\"payable\":false,\"type\":\"function\"},{\"constant\":true,\"inputs\":[],\"name\":\"gasCeilTarget\",\"outputs\":[{\"name\":\"\",\"type\":\"uint256\"}],\"payable\":false,\"type\":\"function\"}]").expect("JSON is autogenerated; qed")),
+				address: address,
+
+			}
+		}
+		fn as_string<T: fmt::Debug>(e: T) -> String { format!("{:?}", e) }
+
+		/// Auto-generated from: `{"constant":true,"inputs":[],"name":"gasFloorTarget","outputs":[{"name":"","type":"uint256"}],"payable":false,"type":"function"}`
+		#[allow(dead_code)]
+		pub fn gas_floor_target<F>(&self, do_call: &F) -> Result<util::U256, String>
+			where F: Fn(util::Address, Vec<u8>) -> Result<Vec<u8>, String> + Send  {
+			let call = self.contract.function("gasFloorTarget".into()).map_err(Self::as_string)?;
+			let data = call.encode_call(
+				vec![]
+			).map_err(Self::as_string)?;
+			let output = call.decode_output((do_call)(self.address.clone(), data)?).map_err(Self::as_string)?;
+			let mut result = output.into_iter().rev().collect::<Vec<_>>();
+			Ok(({ let r = result.pop().ok_or("Invalid return arity")?; let r = r.to_uint().ok_or("Invalid type returned")?; util::U256::from(r.as_ref()) }))
+		}
+
+		/// Auto-generated from: `{"constant":true,"inputs":[],"name":"gasCeilTarget","outputs":[{"name":"","type":"uint256"}],"payable":false,"type":"function"}`
+		#[allow(dead_code)]
+		pub fn gas_ceil_target<F>(&self, do_call: &F) -> Result<util::U256, String>
+			where F: Fn(util::Address, Vec<u8>) -> Result<Vec<u8>, String> + Send  {
+			let call = self.contract.function("gasCeilTarget".into()).map_err(Self::as_string)?;
+			let data = call.encode_call(
+				vec![]
+			).map_err(Self::as_string)?;
+			let output = call.decode_output((do_call)(self.address.clone(), data)?).map_err(Self::as_string)?;
+			let mut result = output.into_iter().rev().collect::<Vec<_>>();
+			Ok(({ let r = result.pop().ok_or("Invalid return arity")?; let r = r.to_uint().ok_or("Invalid type returned")?; util::U256::from(r.as_ref()) }))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use super::bound_targets;
+
+	#[test]
+	fn should_bound_targets() {
+		let min = U256::from(5000);
+		assert_eq!(bound_targets((0.into(), 10_000.into()), min), None);
+		assert_eq!(bound_targets((1000.into(), 10_000.into()), min), Some((min, 10_000.into())));
+		assert_eq!(bound_targets((8000.into(), 6000.into()), min), Some((8000.into(), 8000.into())));
+	}
+}
@@ -19,8 +19,9 @@ use std::time::{Instant, Duration};
 use util::*;
 use util::using_queue::{UsingQueue, GetAction};
 use account_provider::{AccountProvider, SignError as AccountError};
-use state::{State, CleanupMode};
-use client::{MiningBlockChainClient, Executive, Executed, EnvInfo, TransactOptions, BlockId, CallAnalytics, TransactionId};
+use state::State;
+use client::{MiningBlockChainClient, Executed, EnvInfo, EnvOverrides, BlockId, CallAnalytics, TransactionId};
+use client::call;
 use client::TransactionImportResult;
 use executive::contract_address;
 use block::{ClosedBlock, IsBlock, Block};
@@ -30,13 +31,14 @@ use receipt::{Receipt, RichReceipt};
 use spec::Spec;
 use engines::{Engine, Seal};
 use miner::{MinerService, MinerStatus, TransactionQueue, TransactionQueueDetailsProvider, PrioritizationStrategy,
-	AccountDetails, TransactionOrigin};
+	AccountDetails, TransactionOrigin, TransactionEvent, PendingSnapshot};
 use miner::banning_queue::{BanningTransactionQueue, Threshold};
 use miner::work_notify::{WorkPoster, NotifyWork};
+use miner::gas_limit_governor::GasLimitGovernor;
 use miner::price_info::PriceInfo;
 use miner::local_transactions::{Status as LocalTransactionStatus};
 use miner::service_transaction_checker::ServiceTransactionChecker;
-use header::BlockNumber;
+use header::{BlockNumber, Header};
 
 /// Different possible definitions for pending transaction set.
 #[derive(Debug, PartialEq)]
@@ -96,6 +98,10 @@ pub struct MinerOptions {
 	pub tx_gas_limit: U256,
 	/// Maximum size of the transaction queue.
 	pub tx_queue_size: usize,
+	/// Maximum distance between sender's nonce and nonce of a queued external transaction.
+	pub tx_queue_nonce_gap: U256,
+	/// Maximum total size (in bytes) of external transactions queued from a single sender.
+	pub tx_queue_sender_memory_limit: usize,
 	/// Strategy to use for prioritizing transactions in the queue.
 	pub tx_queue_strategy: PrioritizationStrategy,
 	/// Whether we should fallback to providing all the queue's transactions or just pending.
@@ -110,6 +116,14 @@ pub struct MinerOptions {
 	pub tx_queue_banning: Banning,
 	/// Do we refuse to accept service transactions even if sender is certified.
 	pub refuse_service_transactions: bool,
+	/// Prepare the next pending block as soon as a new chain head arrives, before the queue is updated.
+	pub prepare_block_ahead: bool,
+	/// Contract to read gas floor and ceiling targets from. Overrides configured targets when set.
+	pub gas_limit_contract: Option<Address>,
+	/// Maximal size of contract creation code accepted to the queue.
+	pub tx_queue_max_init_code_size: Option<usize>,
+	/// Percentage of the block gas limit only local transactions may use.
+	pub local_gas_reserve: u8,
 }
 
 impl Default for MinerOptions {
@@ -121,6 +135,8 @@ impl Default for MinerOptions {
 			reseal_on_own_tx: true,
 			tx_gas_limit: !U256::zero(),
 			tx_queue_size: 1024,
+			tx_queue_nonce_gap: !U256::zero(),
+			tx_queue_sender_memory_limit: usize::max_value(),
 			tx_queue_gas_limit: GasLimit::Auto,
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
 			pending_set: PendingSet::AlwaysQueue,
@@ -130,6 +146,10 @@ impl Default for MinerOptions {
 			enable_resubmission: true,
 			tx_queue_banning: Banning::Disabled,
 			refuse_service_transactions: false,
+			prepare_block_ahead: false,
+			gas_limit_contract: None,
+			tx_queue_max_init_code_size: None,
+			local_gas_reserve: 0,
 		}
 	}
 }
@@ -208,6 +228,41 @@ struct SealingWork {
 	enabled: bool,
 }
 
+/// Pending block calls at `pending` are executed against. It's only taken again once
+/// the pending block changed, so that repeated calls see the same state.
+struct CallSnapshot {
+	info: PendingSnapshot,
+	// hash of the pending block the snapshot was taken of, `None` for an empty block.
+	source: Option<H256>,
+	env_info: EnvInfo,
+	state: State<::state_db::StateDB>,
+}
+
+impl CallSnapshot {
+	fn new(chain: &MiningBlockChainClient, ordinal: u64, source: Option<H256>, header: &Header, transactions: usize, state: State<::state_db::StateDB>) -> Self {
+		trace!(target: "miner", "Call snapshot {} taken on {} with {} transactions", ordinal, header.parent_hash(), transactions);
+		CallSnapshot {
+			info: PendingSnapshot {
+				ordinal: ordinal,
+				number: header.number(),
+				parent_hash: *header.parent_hash(),
+				transactions: transactions,
+			},
+			source: source,
+			env_info: EnvInfo {
+				number: header.number(),
+				author: *header.author(),
+				timestamp: header.timestamp(),
+				difficulty: *header.difficulty(),
+				last_hashes: Arc::new(chain.last_hashes()),
+				gas_used: U256::zero(),
+				gas_limit: *header.gas_limit(),
+			},
+			state: state,
+		}
+	}
+}
+
 /// Keeps track of transactions using priority queue and holds currently mined block.
 /// Handles preparing work for "work sealing" or seals "internally" if Engine does not require work.
 pub struct Miner {
@@ -217,6 +272,7 @@ pub struct Miner {
 	next_allowed_reseal: Mutex<Instant>,
 	next_mandatory_reseal: RwLock<Instant>,
 	sealing_block_last_request: Mutex<u64>,
+	call_snapshot: Mutex<Option<CallSnapshot>>,
 	// for sealing...
 	options: MinerOptions,
 
@@ -229,6 +285,7 @@ pub struct Miner {
 	notifiers: RwLock<Vec<Box<NotifyWork>>>,
 	gas_pricer: Mutex<GasPricer>,
 	service_transaction_action: ServiceTransactionAction,
+	gas_limit_governor: Option<GasLimitGovernor>,
 }
 
 impl Miner {
@@ -238,6 +295,12 @@ impl Miner {
 		self.sealing_work.lock().enabled = true;
 	}
 
+	/// Register a listener notified about transactions entering, moving within and leaving the queue.
+	/// It's called with the queue locked, so it must not call back into the miner.
+	pub fn add_transaction_listener<F>(&self, listener: F) where F: Fn(&TransactionEvent) + Send + Sync + 'static {
+		self.transaction_queue.write().add_listener(Box::new(listener));
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(options: MinerOptions, gas_pricer: GasPricer, spec: &Spec, accounts: Option<Arc<AccountProvider>>) -> Arc<Miner> {
 		Arc::new(Miner::new_raw(options, gas_pricer, spec, accounts))
@@ -250,7 +313,9 @@ impl Miner {
 			_ => !U256::zero(),
 		};
 
-		let txq = TransactionQueue::with_limits(options.tx_queue_strategy, options.tx_queue_size, gas_limit, options.tx_gas_limit);
+		let mut txq = TransactionQueue::with_limits(options.tx_queue_strategy, options.tx_queue_size, gas_limit, options.tx_gas_limit);
+		txq.set_max_nonce_gap(options.tx_queue_nonce_gap);
+		txq.set_per_sender_memory_limit(options.tx_queue_sender_memory_limit);
 		let txq = match options.tx_queue_banning {
 			Banning::Disabled => BanningTransactionQueue::new(txq, Threshold::NeverBan, Duration::from_secs(180)),
 			Banning::Enabled { ban_duration, min_offends, .. } => BanningTransactionQueue::new(
@@ -265,6 +330,8 @@ impl Miner {
 			false => vec![Box::new(WorkPoster::new(&options.new_work_notify))],
 		};
 
+		let gas_limit_governor = options.gas_limit_contract.map(GasLimitGovernor::new);
+
 		let service_transaction_action = match options.refuse_service_transactions {
 			true => ServiceTransactionAction::Refuse,
 			false => ServiceTransactionAction::Check(ServiceTransactionChecker::default()),
@@ -275,6 +342,7 @@ impl Miner {
 			next_allowed_reseal: Mutex::new(Instant::now()),
 			next_mandatory_reseal: RwLock::new(Instant::now() + options.reseal_max_period),
 			sealing_block_last_request: Mutex::new(0),
+			call_snapshot: Mutex::new(None),
 			sealing_work: Mutex::new(SealingWork{
 				queue: UsingQueue::new(options.work_queue_size),
 				enabled: options.force_sealing
@@ -283,13 +351,14 @@ impl Miner {
 			}),
 			gas_range_target: RwLock::new((U256::zero(), U256::zero())),
 			author: RwLock::new(Address::default()),
-			extra_data: RwLock::new(Vec::new()),
+			extra_data: RwLock::new(spec.engine.params().extra_data_prefix.clone()),
 			options: options,
 			accounts: accounts,
 			engine: spec.engine.clone(),
 			notifiers: RwLock::new(notifiers),
 			gas_pricer: Mutex::new(gas_pricer),
 			service_transaction_action: service_transaction_action,
+			gas_limit_governor: gas_limit_governor,
 		}
 	}
 
@@ -322,13 +391,59 @@ impl Miner {
 		self.sealing_work.lock().queue.peek_last_ref().map(|b| b.to_base())
 	}
 
+	/// Run `f` with the call snapshot, taking it again only if the pending block changed since.
+	/// Without a pending block on top of the best block, the snapshot is of an empty block.
+	fn with_call_snapshot<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> T where F: FnOnce(&CallSnapshot) -> T {
+		let best_hash = chain.chain_info().best_block_hash;
+		let mut snapshot = self.call_snapshot.lock();
+		let (source, parent_hash) = match snapshot.as_ref() {
+			Some(s) => (s.source, Some(s.info.parent_hash)),
+			None => (None, None),
+		};
+		let ordinal = snapshot.as_ref().map_or(1, |s| s.info.ordinal + 1);
+
+		// `Some(None)` if the pending block is the one the snapshot was taken of.
+		let pending = {
+			let sealing_work = self.sealing_work.lock();
+			match sealing_work.queue.peek_last_ref() {
+				Some(b) if *b.header().parent_hash() == best_hash => {
+					let hash = b.header().hash();
+					match source == Some(hash) {
+						true => Some(None),
+						false => Some(Some(CallSnapshot::new(chain, ordinal, Some(hash), b.header(), b.transactions().len(), b.state().clone()))),
+					}
+				},
+				_ => None,
+			}
+		};
+
+		match pending {
+			Some(None) => {},
+			Some(Some(taken)) => *snapshot = Some(taken),
+			None => if source.is_some() || parent_hash != Some(best_hash) {
+				let block = chain.prepare_open_block(self.author(), self.gas_range_target_for(chain), self.extra_data()).close();
+				*snapshot = Some(CallSnapshot::new(chain, ordinal, None, block.header(), 0, block.state().clone()));
+			},
+		}
+		f(snapshot.as_ref().expect("snapshot is taken above if missing; qed"))
+	}
+
 	#[cfg_attr(feature="dev", allow(match_same_arms))]
 	/// Prepares new block for sealing including top transactions from queue.
 	fn prepare_block(&self, chain: &MiningBlockChainClient) -> (ClosedBlock, Option<H256>) {
 		let _timer = PerfTimer::new("prepare_block");
 		let chain_info = chain.chain_info();
 		let (transactions, mut open_block, original_work_hash) = {
-			let transactions = {self.transaction_queue.read().top_transactions_at(chain_info.best_block_number, chain_info.best_block_timestamp)};
+			let transactions = {
+				let queue = self.transaction_queue.read();
+				queue.top_transactions_at(chain_info.best_block_number, chain_info.best_block_timestamp)
+					.into_iter()
+					.map(|tx| {
+						let local = queue.is_local_transaction(&tx.hash());
+						(tx, local)
+					})
+					.collect::<Vec<_>>()
+			};
 			let mut sealing_work = self.sealing_work.lock();
 			let last_work_hash = sealing_work.queue.peek_last_ref().map(|pb| pb.block().fields().header.hash());
 			let best_hash = chain_info.best_block_hash;
@@ -351,7 +466,7 @@ impl Miner {
 					trace!(target: "miner", "prepare_block: No existing work - making new block");
 					chain.prepare_open_block(
 						self.author(),
-						(self.gas_floor_target(), self.gas_ceil_target()),
+						self.gas_range_target_for(chain),
 						self.extra_data()
 					)
 				}
@@ -363,11 +478,22 @@ impl Miner {
 		let mut transactions_to_penalize = HashSet::new();
 		let block_number = open_block.block().fields().header.number();
 
+		// gas external transactions may use, leaving the rest of the block to local ones.
+		let external_gas_limit = {
+			let gas_limit = *open_block.block().fields().header.gas_limit();
+			gas_limit - gas_limit * U256::from(self.options.local_gas_reserve as u64) / U256::from(100)
+		};
+
 		// TODO Push new uncles too.
 		let mut tx_count: usize = 0;
 		let tx_total = transactions.len();
-		for tx in transactions {
+		for (tx, local) in transactions {
 			let hash = tx.hash();
+			if !local && open_block.env_info().gas_used + tx.gas > external_gas_limit {
+				trace!(target: "miner", "Skipping external tx {:?}: gas reserved for local transactions", hash);
+				continue;
+			}
+
 			let start = Instant::now();
 			let result = open_block.push_transaction(tx, None);
 			let took = start.elapsed();
@@ -439,6 +565,13 @@ impl Miner {
 		(block, original_work_hash)
 	}
 
+	/// Gas floor and ceiling targets for new blocks; taken from the governing contract if configured.
+	fn gas_range_target_for(&self, chain: &MiningBlockChainClient) -> (U256, U256) {
+		self.gas_limit_governor.as_ref()
+			.and_then(|governor| governor.targets(chain, self.engine.params().min_gas_limit))
+			.unwrap_or_else(|| *self.gas_range_target.read())
+	}
+
 	/// Asynchronously updates minimal gas price for transaction queue
 	pub fn recalibrate_minimal_gas_price(&self) {
 		debug!(target: "miner", "minimal_gas_price: recalibrating...");
@@ -606,12 +739,18 @@ impl Miner {
 		condition: Option<TransactionCondition>,
 		transaction_queue: &mut BanningTransactionQueue,
 	) -> Vec<Result<TransactionImportResult, Error>> {
+		if client.is_read_only() {
+			debug!(target: "miner", "Rejected {} transactions: client is read-only", transactions.len());
+			return transactions.into_iter().map(|_| Err(Error::Transaction(TransactionError::ReadOnly))).collect();
+		}
+
 		let accounts = self.accounts.as_ref()
 			.and_then(|provider| provider.accounts().ok())
 			.map(|accounts| accounts.into_iter().collect::<HashSet<_>>());
 
 		let best_block_header = client.best_block_header().decode();
 		let insertion_time = client.chain_info().best_block_number;
+		let schedule = client.latest_schedule();
 
 		transactions.into_iter()
 			.map(|tx| {
@@ -620,6 +759,10 @@ impl Miner {
 					debug!(target: "miner", "Rejected tx {:?}: already in the blockchain", hash);
 					return Err(Error::Transaction(TransactionError::AlreadyImported));
 				}
+				if let Err(e) = tx.verify_static(&schedule, self.options.tx_queue_max_init_code_size) {
+					debug!(target: "miner", "Rejected tx {:?}: {}", hash, e);
+					return Err(Error::Transaction(e));
+				}
 				match self.engine.verify_transaction_basic(&tx, &best_block_header)
 					.and_then(|_| self.engine.verify_transaction(tx, &best_block_header))
 				{
@@ -697,48 +840,18 @@ impl MinerService for Miner {
 		}
 	}
 
-	fn call(&self, client: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
-		let sealing_work = self.sealing_work.lock();
-		match sealing_work.queue.peek_last_ref() {
-			Some(work) => {
-				let block = work.block();
-
-				// TODO: merge this code with client.rs's fn call somwhow.
-				let header = block.header();
-				let last_hashes = Arc::new(client.last_hashes());
-				let env_info = EnvInfo {
-					number: header.number(),
-					author: *header.author(),
-					timestamp: header.timestamp(),
-					difficulty: *header.difficulty(),
-					last_hashes: last_hashes,
-					gas_used: U256::zero(),
-					gas_limit: U256::max_value(),
-				};
-				// that's just a copy of the state.
-				let mut state = block.state().clone();
-				let original_state = if analytics.state_diffing { Some(state.clone()) } else { None };
-
-				let sender = t.sender();
-				let balance = state.balance(&sender).map_err(ExecutionError::from)?;
-				let needed_balance = t.value + t.gas * t.gas_price;
-				if balance < needed_balance {
-					// give the sender a sufficient balance
-					state.add_balance(&sender, &(needed_balance - balance), CleanupMode::NoEmpty)
-						.map_err(ExecutionError::from)?;
-				}
-				let options = TransactOptions { tracing: analytics.transaction_tracing, vm_tracing: analytics.vm_tracing, check_nonce: false };
-				let mut ret = Executive::new(&mut state, &env_info, &*self.engine, client.vm_factory()).transact(t, options)?;
+	fn pending_snapshot(&self, chain: &MiningBlockChainClient) -> PendingSnapshot {
+		self.with_call_snapshot(chain, |snapshot| snapshot.info.clone())
+	}
 
-				// TODO gav move this into Executive.
-				if let Some(original) = original_state {
-					ret.state_diff = Some(state.diff_from(original).map_err(ExecutionError::from)?);
-				}
+	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError> {
+		let (state, env_info) = self.with_call_snapshot(chain, |snapshot| (snapshot.state.clone(), snapshot.env_info.clone()));
+		call::call(&state, env_info, &EnvOverrides::default(), &*self.engine, chain.vm_factory(), t, analytics)
+	}
 
-				Ok(ret)
-			},
-			None => client.call(t, BlockId::Latest, analytics)
-		}
+	fn estimate_gas(&self, chain: &MiningBlockChainClient, t: &SignedTransaction) -> Result<U256, CallError> {
+		let (state, env_info) = self.with_call_snapshot(chain, |snapshot| (snapshot.state.clone(), snapshot.env_info.clone()));
+		call::estimate_gas(&state, env_info, &*self.engine, chain.vm_factory(), t)
 	}
 
 	// TODO: The `chain.latest_x` actually aren't infallible, they just panic on corruption.
@@ -804,8 +917,24 @@ impl MinerService for Miner {
 		Ok(())
 	}
 
-	fn set_extra_data(&self, extra_data: Bytes) {
-		*self.extra_data.write() = extra_data;
+	fn propose_signer(&self, address: Address, authorize: bool) -> bool {
+		self.engine.propose_signer(address, authorize)
+	}
+
+	fn discard_signer_proposal(&self, address: &Address) -> bool {
+		self.engine.discard_signer_proposal(address)
+	}
+
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError> {
+		let prefix = &self.engine.params().extra_data_prefix;
+		let maximum = self.engine.maximum_extra_data_size();
+		let len = prefix.len() + extra_data.len();
+		if len > maximum {
+			return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum), found: len }));
+		}
+
+		*self.extra_data.write() = prefix.iter().chain(extra_data.iter()).cloned().collect();
+		Ok(())
 	}
 
 	/// Set the gas limit we wish to target when sealing a new block.
@@ -834,6 +963,10 @@ impl MinerService for Miner {
 		self.gas_range_target.read().0 / 5.into()
 	}
 
+	fn max_init_code_size(&self) -> Option<usize> {
+		self.options.tx_queue_max_init_code_size
+	}
+
 	fn transactions_limit(&self) -> usize {
 		self.transaction_queue.read().limit()
 	}
@@ -1085,6 +1218,10 @@ impl MinerService for Miner {
 	fn update_sealing(&self, chain: &MiningBlockChainClient) {
 		trace!(target: "miner", "update_sealing");
 
+		if chain.is_read_only() {
+			return;
+		}
+
 		if self.requires_reseal(chain.chain_info().best_block_number) {
 			// --------------------------------------------------------------------------
 			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
@@ -1114,6 +1251,9 @@ impl MinerService for Miner {
 
 	fn map_sealing_work<F, T>(&self, chain: &MiningBlockChainClient, f: F) -> Option<T> where F: FnOnce(&ClosedBlock) -> T {
 		trace!(target: "miner", "map_sealing_work: entering");
+		if chain.is_read_only() {
+			return None;
+		}
 		self.prepare_work_sealing(chain);
 		trace!(target: "miner", "map_sealing_work: sealing prepared");
 		let mut sealing_work = self.sealing_work.lock();
@@ -1161,6 +1301,19 @@ impl MinerService for Miner {
 		// First update gas limit in transaction queue
 		self.update_gas_limit(chain);
 
+		// Prepare work on top of the new head straight away, using current transaction ordering.
+		// Transactions included in enacted blocks are skipped when pushed to the block.
+		// Retracted transactions are not in the queue yet, so after a reorg the block is prepared
+		// once the queue is updated.
+		let prepared_ahead = self.options.prepare_block_ahead && !enacted.is_empty() && retracted.is_empty();
+		if prepared_ahead {
+			// --------------------------------------------------------------------------
+			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
+			// | Make sure to release the locks before calling that method.             |
+			// --------------------------------------------------------------------------
+			self.update_sealing(chain);
+		}
+
 		// Update minimal gas price
 		self.recalibrate_minimal_gas_price();
 
@@ -1189,7 +1342,7 @@ impl MinerService for Miner {
 			transaction_queue.remove_old(&fetch_account, time);
 		}
 
-		if enacted.len() > 0 {
+		if enacted.len() > 0 && !prepared_ahead {
 			// --------------------------------------------------------------------------
 			// | NOTE Code below requires transaction_queue and sealing_work locks.     |
 			// | Make sure to release the locks before calling that method.             |
@@ -1256,7 +1409,7 @@ impl<'a> TransactionQueueDetailsProvider for TransactionDetailsProvider<'a> {
 #[cfg(test)]
 mod tests {
 
-	use std::sync::Arc;
+	use std::sync::{Arc, Weak};
 	use std::time::Duration;
 	use super::super::{MinerService, PrioritizationStrategy};
 	use super::*;
@@ -1280,6 +1433,17 @@ mod tests {
 		assert!(sealing_work.is_some(), "Expected closed block");
 	}
 
+	#[test]
+	fn should_limit_extra_data_size() {
+		let miner = Miner::with_spec(&Spec::new_test());
+
+		assert!(miner.set_extra_data(vec![1; 32]).is_ok());
+		assert_eq!(miner.extra_data(), vec![1; 32]);
+		assert_eq!(miner.set_extra_data(vec![2; 33]),
+			Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(32), found: 33 })));
+		assert_eq!(miner.extra_data(), vec![1; 32]);
+	}
+
 	#[test]
 	fn should_still_work_after_a_couple_of_blocks() {
 		// given
@@ -1312,6 +1476,8 @@ mod tests {
 				reseal_max_period: Duration::from_secs(120),
 				tx_gas_limit: !U256::zero(),
 				tx_queue_size: 1024,
+				tx_queue_nonce_gap: !U256::zero(),
+				tx_queue_sender_memory_limit: usize::max_value(),
 				tx_queue_gas_limit: GasLimit::None,
 				tx_queue_strategy: PrioritizationStrategy::GasFactorAndGasPrice,
 				pending_set: PendingSet::AlwaysSealing,
@@ -1319,6 +1485,10 @@ mod tests {
 				enable_resubmission: true,
 				tx_queue_banning: Banning::Disabled,
 				refuse_service_transactions: false,
+				prepare_block_ahead: false,
+				gas_limit_contract: None,
+				tx_queue_max_init_code_size: None,
+				local_gas_reserve: 0,
 			},
 			GasPricer::new_fixed(0u64.into()),
 			&Spec::new_test(),
@@ -1338,6 +1508,64 @@ mod tests {
 		}.sign(keypair.secret(), None)
 	}
 
+	#[test]
+	fn should_prepare_block_with_governed_gas_limit() {
+		use ethereum;
+		use util::Hashable;
+
+		let spec = ethereum::new_morden();
+		let client = TestBlockChainClient::new_with_spec(ethereum::new_morden());
+		let contract = Address::from(0x100);
+		let target = |name: &str, value: u64| {
+			let mut output = [0u8; 32];
+			U256::from(value).to_big_endian(&mut output);
+			((contract, name.as_bytes().sha3()[..4].to_vec()), output.to_vec())
+		};
+		client.contract_calls.write().extend(vec![target("gasFloorTarget()", 5_000_000), target("gasCeilTarget()", 8_000_000)]);
+
+		let governed = Miner::new(MinerOptions { gas_limit_contract: Some(contract), ..Default::default() }, GasPricer::new_fixed(0u64.into()), &spec, None);
+		let ungoverned = Miner::new(MinerOptions::default(), GasPricer::new_fixed(0u64.into()), &spec, None);
+
+		let parent_gas_limit = *spec.genesis_header().gas_limit();
+		let governed_gas_limit = *governed.prepare_block(&client).0.header().gas_limit();
+		let ungoverned_gas_limit = *ungoverned.prepare_block(&client).0.header().gas_limit();
+
+		// the gas limit moves towards the governed floor, otherwise towards the configured (zero) targets.
+		assert!(governed_gas_limit > parent_gas_limit);
+		assert!(governed_gas_limit <= 5_000_000.into());
+		assert!(ungoverned_gas_limit < parent_gas_limit);
+	}
+
+	#[test]
+	fn should_freeze_pending_snapshot_until_pending_block_changes() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = miner();
+		let first = miner.pending_snapshot(&client);
+		assert_eq!(first.ordinal, 1);
+		assert_eq!(first.transactions, 0);
+		assert_eq!(miner.pending_snapshot(&client), first);
+
+		// when
+		let res = miner.import_own_transaction(&client, PendingTransaction::new(transaction(), None));
+		assert_eq!(res.unwrap(), TransactionImportResult::Current);
+
+		// then
+		let second = miner.pending_snapshot(&client);
+		assert_eq!(second.ordinal, 2);
+		assert_eq!(second.transactions, 1);
+		assert_eq!(miner.pending_snapshot(&client), second);
+
+		client.add_blocks(1, EachBlockWith::Nothing);
+		let third = miner.pending_snapshot(&client);
+		assert_eq!(third.ordinal, 3);
+		assert_eq!(third.parent_hash, client.chain_info().best_block_hash);
+
+		// queued transactions not making it into a pending block don't change it.
+		miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap().unwrap();
+		assert_eq!(miner.pending_snapshot(&client), third);
+	}
+
 	#[test]
 	fn should_make_pending_block_when_importing_own_transaction() {
 		// given
@@ -1396,6 +1624,97 @@ mod tests {
 		assert!(miner.prepare_work_sealing(&client));
 	}
 
+	#[test]
+	fn should_prepare_block_ahead_on_new_chain_head() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new_raw(
+			MinerOptions { force_sealing: true, prepare_block_ahead: true, ..Default::default() },
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None,
+		);
+		miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap().unwrap();
+
+		// when
+		client.add_blocks(1, EachBlockWith::Nothing);
+		let best_hash = client.chain_info().best_block_hash;
+		miner.chain_new_blocks(&client, &[best_hash], &[], &[best_hash], &[]);
+
+		// then
+		let pending = miner.pending_block().expect("pending block should be prepared ahead");
+		assert_eq!(pending.header.parent_hash(), &best_hash);
+		assert_eq!(pending.transactions.len(), 1);
+	}
+
+	#[test]
+	fn should_reserve_gas_for_local_transactions() {
+		// given
+		let client = TestBlockChainClient::default();
+		let miner = Miner::new_raw(
+			MinerOptions { force_sealing: true, local_gas_reserve: 100, ..Default::default() },
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None,
+		);
+
+		// when
+		miner.import_external_transactions(&client, vec![transaction().into()]).pop().unwrap().unwrap();
+		miner.import_own_transaction(&client, PendingTransaction::new(transaction(), None)).unwrap();
+		miner.update_sealing(&client);
+
+		// then
+		let pending = miner.pending_block().expect("pending block should be prepared");
+		assert_eq!(pending.transactions.len(), 1);
+		assert!(miner.transaction_queue.read().is_local_transaction(&pending.transactions[0].hash()));
+	}
+
+	struct PendingQueueProbe {
+		miner: Weak<Miner>,
+		pending: Arc<Mutex<Vec<usize>>>,
+	}
+
+	impl NotifyWork for PendingQueueProbe {
+		fn notify(&self, _pow_hash: H256, _difficulty: U256, _number: u64) {
+			if let Some(miner) = self.miner.upgrade() {
+				self.pending.lock().push(miner.status().transactions_in_pending_queue);
+			}
+		}
+	}
+
+	fn pending_queue_on_new_work(prepare_block_ahead: bool) -> Vec<usize> {
+		let client = TestBlockChainClient::default();
+		let miner = Arc::new(Miner::new_raw(
+			MinerOptions { force_sealing: true, prepare_block_ahead: prepare_block_ahead, ..Default::default() },
+			GasPricer::new_fixed(0u64.into()),
+			&Spec::new_test(),
+			None,
+		));
+		let transaction = transaction();
+		let sender = transaction.sender();
+		miner.import_external_transactions(&client, vec![transaction.into()]).pop().unwrap().unwrap();
+
+		let pending = Arc::new(Mutex::new(Vec::new()));
+		miner.push_notifier(Box::new(PendingQueueProbe { miner: Arc::downgrade(&miner), pending: pending.clone() }));
+
+		// a new head including the transaction
+		client.set_nonce(sender, 1.into());
+		client.add_blocks(1, EachBlockWith::Nothing);
+		let best_hash = client.chain_info().best_block_hash;
+		miner.chain_new_blocks(&client, &[best_hash], &[], &[best_hash], &[]);
+
+		let pending = pending.lock();
+		pending.clone()
+	}
+
+	#[test]
+	fn should_prepare_block_once_per_new_chain_head() {
+		// work is prepared before the queue is updated, and not prepared again afterwards
+		assert_eq!(pending_queue_on_new_work(true), vec![1]);
+		// work is prepared once the included transaction is removed from the queue
+		assert_eq!(pending_queue_on_new_work(false), vec![0]);
+	}
+
 	#[test]
 	fn should_not_seal_unless_enabled() {
 		let miner = miner();
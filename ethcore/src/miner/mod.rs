@@ -43,8 +43,10 @@
 
 mod banning_queue;
 mod external;
+mod gas_limit_governor;
 mod local_transactions;
 mod miner;
+mod pool_bundle;
 mod price_info;
 mod service_transaction_checker;
 mod transaction_queue;
@@ -55,8 +57,9 @@ pub use self::external::{ExternalMiner, ExternalMinerService};
 
 pub use self::miner::{Miner, MinerOptions, Banning, PendingSet, GasPricer, GasPriceCalibratorOptions, GasLimit};
 pub use self::transaction_queue::{TransactionQueue, TransactionDetailsProvider as TransactionQueueDetailsProvider,
-	PrioritizationStrategy, AccountDetails, TransactionOrigin};
+	PrioritizationStrategy, AccountDetails, TransactionOrigin, TransactionEvent, TransactionListener, DropReason};
 pub use self::local_transactions::{Status as LocalTransactionStatus};
+pub use self::pool_bundle::{PoolBundle, PooledTransaction, POOL_BUNDLE_VERSION};
 pub use client::TransactionImportResult;
 pub use self::work_notify::NotifyWork;
 pub use self::stratum::{Stratum, Error as StratumError, Options as StratumOptions};
@@ -67,7 +70,7 @@ use client::{MiningBlockChainClient, Executed, CallAnalytics};
 use block::ClosedBlock;
 use header::BlockNumber;
 use receipt::{RichReceipt, Receipt};
-use error::{Error, CallError};
+use error::{Error, CallError, BlockError};
 use transaction::{UnverifiedTransaction, PendingTransaction, SignedTransaction};
 
 /// Miner client API
@@ -85,11 +88,19 @@ pub trait MinerService : Send + Sync {
 	/// Set info necessary to sign consensus messages.
 	fn set_engine_signer(&self, address: Address, password: String) -> Result<(), ::account_provider::SignError>;
 
+	/// Vote on authorizing (`true`) or deauthorizing (`false`) a signer in sealed blocks.
+	/// Returns `false` if the engine doesn't vote on signers.
+	fn propose_signer(&self, address: Address, authorize: bool) -> bool;
+
+	/// Stop voting on a signer. Returns `false` if the engine doesn't vote on signers.
+	fn discard_signer_proposal(&self, address: &Address) -> bool;
+
 	/// Get the extra_data that we will seal blocks with.
 	fn extra_data(&self) -> Bytes;
 
-	/// Set the extra_data that we will seal blocks with.
-	fn set_extra_data(&self, extra_data: Bytes);
+	/// Set the extra_data that we will seal blocks with, following the chain's extra data prefix.
+	/// Fails if the whole doesn't fit into the engine's maximum extra data size.
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError>;
 
 	/// Get current minimal gas price for transactions accepted to queue.
 	fn minimal_gas_price(&self) -> U256;
@@ -184,12 +195,22 @@ pub trait MinerService : Send + Sync {
 	/// Suggested gas limit.
 	fn sensible_gas_limit(&self) -> U256 { 21000.into() }
 
+	/// Maximal size of contract creation code accepted to the queue.
+	fn max_init_code_size(&self) -> Option<usize> { None }
+
 	/// Latest account balance in pending state.
 	fn balance(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<U256>;
 
-	/// Call into contract code using pending state.
+	/// Snapshot of the pending block calls and gas estimates at `pending` are executed against.
+	/// It's taken again only once the pending block changed.
+	fn pending_snapshot(&self, chain: &MiningBlockChainClient) -> PendingSnapshot;
+
+	/// Call into contract code using the pending block snapshot.
 	fn call(&self, chain: &MiningBlockChainClient, t: &SignedTransaction, analytics: CallAnalytics) -> Result<Executed, CallError>;
 
+	/// Estimate gas required by a transaction using the pending block snapshot.
+	fn estimate_gas(&self, chain: &MiningBlockChainClient, t: &SignedTransaction) -> Result<U256, CallError>;
+
 	/// Get storage value in pending state.
 	fn storage_at(&self, chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256>;
 
@@ -200,6 +221,19 @@ pub trait MinerService : Send + Sync {
 	fn code(&self, chain: &MiningBlockChainClient, address: &Address) -> Option<Option<Bytes>>;
 }
 
+/// Pending block snapshot summary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingSnapshot {
+	/// Increases every time the snapshot is rebuilt.
+	pub ordinal: u64,
+	/// Number of the pending block.
+	pub number: BlockNumber,
+	/// Hash of the block the pending block is built on.
+	pub parent_hash: H256,
+	/// Number of transactions in the pending block.
+	pub transactions: usize,
+}
+
 /// Mining status
 #[derive(Debug)]
 pub struct MinerStatus {
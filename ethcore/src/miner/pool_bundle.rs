@@ -0,0 +1,136 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pool bundles: the contents of the transaction queue, exported so they can be
+//! imported into another node, e.g. one replacing a node taken down for maintenance.
+
+use rlp::{Encodable, Decodable, RlpStream, UntrustedRlp, DecoderError};
+use transaction::{UnverifiedTransaction, Condition};
+
+/// Version of the bundle format.
+pub const POOL_BUNDLE_VERSION: u8 = 1;
+
+/// Transaction taken from the queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PooledTransaction {
+	/// The signed transaction.
+	pub transaction: UnverifiedTransaction,
+	/// Whether the transaction was submitted locally.
+	pub local: bool,
+	/// Activation condition of the transaction.
+	pub condition: Option<Condition>,
+}
+
+impl Encodable for PooledTransaction {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(3)
+			.append(&self.transaction)
+			.append(&(self.local as u8));
+		match self.condition {
+			None => { s.begin_list(0); },
+			Some(Condition::Number(number)) => { s.begin_list(2).append(&0u8).append(&number); },
+			Some(Condition::Timestamp(timestamp)) => { s.begin_list(2).append(&1u8).append(&timestamp); },
+		}
+	}
+}
+
+impl Decodable for PooledTransaction {
+	fn decode(rlp: &UntrustedRlp) -> Result<Self, DecoderError> {
+		let condition = rlp.at(2)?;
+		let condition = match condition.item_count()? {
+			0 => None,
+			2 => match condition.val_at::<u8>(0)? {
+				0 => Some(Condition::Number(condition.val_at(1)?)),
+				1 => Some(Condition::Timestamp(condition.val_at(1)?)),
+				_ => return Err(DecoderError::Custom("Unknown transaction condition")),
+			},
+			_ => return Err(DecoderError::RlpIncorrectListLen),
+		};
+
+		Ok(PooledTransaction {
+			transaction: rlp.val_at(0)?,
+			local: rlp.val_at::<u8>(1)? != 0,
+			condition: condition,
+		})
+	}
+}
+
+/// Transactions of a queue, in the order they were queued for inclusion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolBundle {
+	/// Transactions, highest priority first.
+	pub transactions: Vec<PooledTransaction>,
+}
+
+impl PoolBundle {
+	/// Read a bundle from its RLP.
+	pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+		let rlp = UntrustedRlp::new(bytes);
+		let version: u8 = rlp.val_at(0).map_err(|e| format!("Invalid pool bundle: {:?}", e))?;
+		if version != POOL_BUNDLE_VERSION {
+			return Err(format!("Unsupported pool bundle version {}", version));
+		}
+		rlp.list_at(1)
+			.map(|transactions| PoolBundle { transactions: transactions })
+			.map_err(|e| format!("Invalid pool bundle: {:?}", e))
+	}
+}
+
+impl Encodable for PoolBundle {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		s.begin_list(2).append(&POOL_BUNDLE_VERSION);
+		s.append_list(&self.transactions);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{Random, Generator};
+	use rlp;
+	use transaction::{Transaction, Condition};
+	use super::{PoolBundle, PooledTransaction};
+
+	fn transaction(nonce: u64) -> PooledTransaction {
+		let keypair = Random.generate().unwrap();
+		let tx = Transaction { nonce: nonce.into(), ..Default::default() };
+		PooledTransaction {
+			transaction: tx.sign(keypair.secret(), None).into(),
+			local: false,
+			condition: None,
+		}
+	}
+
+	#[test]
+	fn encodes_and_decodes_bundle() {
+		let mut local = transaction(1);
+		local.local = true;
+		local.condition = Some(Condition::Number(100));
+		let mut scheduled = transaction(2);
+		scheduled.condition = Some(Condition::Timestamp(1_500_000_000));
+
+		let bundle = PoolBundle { transactions: vec![transaction(0), local, scheduled] };
+		assert_eq!(PoolBundle::decode(&rlp::encode(&bundle)).unwrap(), bundle);
+	}
+
+	#[test]
+	fn rejects_unknown_version() {
+		let mut stream = rlp::RlpStream::new_list(2);
+		stream.append(&2u8).begin_list(0);
+
+		assert!(PoolBundle::decode(&stream.out()).is_err());
+		assert!(PoolBundle::decode(&[0xc0]).is_err());
+	}
+}
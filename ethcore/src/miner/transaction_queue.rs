@@ -105,6 +105,7 @@ use std::cmp::Ordering;
 use std::cmp;
 use std::collections::{HashSet, HashMap, BTreeSet, BTreeMap};
 use linked_hash_map::LinkedHashMap;
+use rlp::Encodable;
 use util::{Address, H256, Uint, U256};
 use util::table::Table;
 use transaction::*;
@@ -278,15 +279,19 @@ struct VerifiedTransaction {
 	insertion_time: QueuingInstant,
 	/// Delay until specified condition is met.
 	condition: Option<Condition>,
+	/// Size of RLP-encoded transaction.
+	size: usize,
 }
 
 impl VerifiedTransaction {
 	fn new(transaction: SignedTransaction, origin: TransactionOrigin, time: QueuingInstant, condition: Option<Condition>) -> Self {
+		let size = transaction.rlp_bytes().len();
 		VerifiedTransaction {
 			transaction: transaction,
 			origin: origin,
 			insertion_time: time,
 			condition: condition,
+			size: size,
 		}
 	}
 
@@ -388,7 +393,7 @@ impl TransactionSet {
 	///
 	/// It drops transactions from this set but also removes associated `VerifiedTransaction`.
 	/// Returns addresses and lowest nonces of transactions removed because of limit.
-	fn enforce_limit(&mut self, by_hash: &mut HashMap<H256, VerifiedTransaction>, local: &mut LocalTransactionsList) -> Option<HashMap<Address, U256>> {
+	fn enforce_limit(&mut self, by_hash: &mut HashMap<H256, VerifiedTransaction>, local: &mut LocalTransactionsList, listeners: &Listeners) -> Option<HashMap<Address, U256>> {
 		let mut count = 0;
 		let mut gas: U256 = 0.into();
 		let to_drop : Vec<(Address, U256)> = {
@@ -417,6 +422,7 @@ impl TransactionSet {
 
 				let order = by_hash.remove(&order.hash)
 					.expect("hash is in `by_priorty`; all hashes in `by_priority` must be in `by_hash`; qed");
+				listeners.notify(TransactionEvent::Dropped(order.hash(), DropReason::Limit));
 
 				if order.origin.is_local() {
 					local.mark_dropped(order.transaction);
@@ -476,6 +482,49 @@ pub struct TransactionQueueStatus {
 	pub future: usize,
 }
 
+/// Reason a transaction was dropped from the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+	/// The queue (or sender's share of it) was full and the transaction had the lowest priority.
+	Limit,
+	/// The transaction became invalid (e.g. sender can no longer pay for it).
+	Invalid,
+	/// The transaction occupied the queue for too long.
+	Expired,
+	/// The whole queue was cleared.
+	Cleared,
+}
+
+/// Change of a transaction's status in the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionEvent {
+	/// Transaction was imported; `true` if it's ready to be included in a block.
+	Added(H256, bool),
+	/// Transaction was moved from `future` to `current` and is now ready to be included in a block.
+	Promoted(H256),
+	/// Transaction was removed because its nonce has been used in the chain.
+	Mined(H256),
+	/// Transaction was dropped from the queue.
+	Dropped(H256, DropReason),
+	/// Transaction (first) lost its slot to another one with the same sender and nonce (second).
+	Replaced(H256, H256),
+}
+
+/// Receives transaction queue events.
+pub type TransactionListener = Box<Fn(&TransactionEvent) + Send + Sync>;
+
+/// Listeners notified about transaction queue events.
+#[derive(Default)]
+struct Listeners(Vec<TransactionListener>);
+
+impl Listeners {
+	fn notify(&self, event: TransactionEvent) {
+		for listener in &self.0 {
+			listener(&event);
+		}
+	}
+}
+
 /// Details of account
 pub struct AccountDetails {
 	/// Most recent account nonce
@@ -548,6 +597,12 @@ pub struct TransactionQueue {
 	last_nonces: HashMap<Address, U256>,
 	/// List of local transactions and their statuses.
 	local_transactions: LocalTransactionsList,
+	/// Maximal difference between transaction nonce and sender's state nonce (defaults to !0)
+	max_nonce_gap: U256,
+	/// Maximal total size (in bytes) of transactions from a single sender (defaults to no limit)
+	per_sender_memory_limit: usize,
+	/// Listeners notified about transactions entering, moving within and leaving the queue.
+	listeners: Listeners,
 }
 
 impl Default for TransactionQueue {
@@ -591,16 +646,26 @@ impl TransactionQueue {
 			by_hash: HashMap::new(),
 			last_nonces: HashMap::new(),
 			local_transactions: LocalTransactionsList::default(),
+			max_nonce_gap: !U256::zero(),
+			per_sender_memory_limit: usize::max_value(),
+			listeners: Default::default(),
 		}
 	}
 
+	/// Register a listener notified about every transaction entering, moving within or leaving the queue.
+	///
+	/// NOTE listeners are called with the queue borrowed, so they must not call back into it.
+	pub fn add_listener(&mut self, listener: TransactionListener) {
+		self.listeners.0.push(listener);
+	}
+
 	/// Set the new limit for `current` and `future` queue.
 	pub fn set_limit(&mut self, limit: usize) {
 		self.current.set_limit(limit);
 		self.future.set_limit(limit);
 		// And ensure the limits
-		self.current.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
-		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+		self.current.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
+		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
 	}
 
 	/// Returns current limit of transactions in the queue.
@@ -640,7 +705,7 @@ impl TransactionQueue {
 	pub fn set_total_gas_limit(&mut self, gas_limit: U256) {
 		self.future.gas_limit = gas_limit;
 		self.current.gas_limit = gas_limit;
-		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
 	}
 
 	/// Set the new limit for the amount of gas any individual transaction may have.
@@ -649,6 +714,18 @@ impl TransactionQueue {
 		self.tx_gas_limit = limit;
 	}
 
+	/// Sets how far ahead of the sender's state nonce an external transaction may be queued.
+	/// Any transaction already imported to the queue is not affected.
+	pub fn set_max_nonce_gap(&mut self, gap: U256) {
+		self.max_nonce_gap = gap;
+	}
+
+	/// Sets the maximal total size (in bytes) of external transactions queued from a single sender.
+	/// A sender above the new limit is trimmed the next time one of its transactions is imported.
+	pub fn set_per_sender_memory_limit(&mut self, limit: usize) {
+		self.per_sender_memory_limit = limit;
+	}
+
 	/// Returns current status for this queue
 	pub fn status(&self) -> TransactionQueueStatus {
 		TransactionQueueStatus {
@@ -844,6 +921,10 @@ impl TransactionQueue {
 
 	/// Always updates future and moves transactions from current to future.
 	fn cull_internal(&mut self, sender: Address, client_nonce: U256) {
+		// Transactions moved back to current are not reported as promoted
+		let was_current = self.current.by_address.row(&sender)
+			.map(|row| row.values().map(|order| order.hash).collect::<HashSet<_>>())
+			.unwrap_or_else(HashSet::new);
 		// We will either move transaction to future or remove it completely
 		// so there will be no transactions from this sender in current
 		self.last_nonces.remove(&sender);
@@ -853,7 +934,11 @@ impl TransactionQueue {
 		self.move_all_to_future(&sender, client_nonce);
 		// And now lets check if there is some batch of transactions in future
 		// that should be placed in current. It should also update last_nonces.
-		self.move_matching_future_to_current(sender, client_nonce, client_nonce);
+		for hash in self.move_matching_future_to_current(sender, client_nonce, client_nonce) {
+			if !was_current.contains(&hash) {
+				self.listeners.notify(TransactionEvent::Promoted(hash));
+			}
+		}
 		assert_eq!(self.future.by_priority.len() + self.current.by_priority.len(), self.by_hash.len());
 	}
 
@@ -878,13 +963,13 @@ impl TransactionQueue {
 			.map(|(hash, tx)| (hash, tx, current_time.saturating_sub(tx.insertion_time)))
 			.filter_map(|(hash, tx, time_diff)| {
 				if time_diff > max_time {
-					return Some(*hash);
+					return Some((*hash, DropReason::Expired));
 				}
 
 				if time_diff > balance_check {
 					return match senders.get(&tx.sender()) {
 						Some(details) if tx.cost() > details.balance => {
-							Some(*hash)
+							Some((*hash, DropReason::Invalid))
 						},
 						_ => None,
 					};
@@ -896,8 +981,8 @@ impl TransactionQueue {
 		let fetch_nonce = |a: &Address| senders.get(a)
 			.expect("We fetch details for all senders from both current and future")
 			.nonce;
-		for hash in invalid {
-			self.remove_invalid(&hash, &fetch_nonce);
+		for (hash, reason) in invalid {
+			self.remove(&hash, &fetch_nonce, reason);
 		}
 	}
 
@@ -947,6 +1032,12 @@ impl TransactionQueue {
 	/// If gap is introduced marks subsequent transactions as future
 	pub fn remove_invalid<F>(&mut self, transaction_hash: &H256, fetch_nonce: &F)
 		where F: Fn(&Address) -> U256 {
+		self.remove(transaction_hash, fetch_nonce, DropReason::Invalid)
+	}
+
+	/// Removes transaction identified by hash from queue, reporting it as dropped for given reason.
+	fn remove<F>(&mut self, transaction_hash: &H256, fetch_nonce: &F, reason: DropReason)
+		where F: Fn(&Address) -> U256 {
 
 		assert_eq!(self.future.by_priority.len() + self.current.by_priority.len(), self.by_hash.len());
 		let transaction = self.by_hash.remove(transaction_hash);
@@ -961,6 +1052,7 @@ impl TransactionQueue {
 		let current_nonce = fetch_nonce(&sender);
 
 		trace!(target: "txqueue", "Removing invalid transaction: {:?}", transaction.hash());
+		self.listeners.notify(TransactionEvent::Dropped(*transaction_hash, reason));
 
 		// Mark in locals
 		if self.local_transactions.contains(transaction_hash) {
@@ -973,7 +1065,7 @@ impl TransactionQueue {
 			self.update_future(&sender, current_nonce);
 			// And now lets check if there is some chain of transactions in future
 			// that should be placed in current
-			self.move_matching_future_to_current(sender, current_nonce, current_nonce);
+			self.promote_matching_future(sender, current_nonce, current_nonce);
 			assert_eq!(self.future.by_priority.len() + self.current.by_priority.len(), self.by_hash.len());
 			return;
 		}
@@ -1031,6 +1123,7 @@ impl TransactionQueue {
 				trace!(target: "txqueue", "Removing old transaction: {:?} (nonce: {} < {})", order.hash, k, current_nonce);
 				// Remove the transaction completely
 				self.by_hash.remove(&order.hash).expect("All transactions in `future` are also in `by_hash`");
+				self.listeners.notify(TransactionEvent::Mined(order.hash));
 			}
 		}
 	}
@@ -1053,17 +1146,18 @@ impl TransactionQueue {
 					self.local_transactions.mark_future(order.hash);
 				}
 				if let Some(old) = self.future.insert(*sender, k, order.clone()) {
-					Self::replace_orders(*sender, k, old, order, &mut self.future, &mut self.by_hash, &mut self.local_transactions);
+					Self::replace_orders(*sender, k, old, order, &mut self.future, &mut self.by_hash, &mut self.local_transactions, &self.listeners);
 				}
 			} else {
 				trace!(target: "txqueue", "Removing old transaction: {:?} (nonce: {} < {})", order.hash, k, current_nonce);
 				let tx = self.by_hash.remove(&order.hash).expect("All transactions in `future` are also in `by_hash`");
+				self.listeners.notify(TransactionEvent::Mined(order.hash));
 				if tx.origin.is_local() {
 					self.local_transactions.mark_mined(tx.transaction);
 				}
 			}
 		}
-		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+		self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
 	}
 
 	/// Returns top transactions from the queue ordered by priority.
@@ -1131,6 +1225,11 @@ impl TransactionQueue {
 			.collect()
 	}
 
+	/// Returns true if given queued transaction was submitted locally.
+	pub fn is_local_transaction(&self, hash: &H256) -> bool {
+		self.by_hash.get(hash).map_or(false, |tx| tx.origin.is_local())
+	}
+
 	/// Returns true if there is at least one local transaction pending
 	pub fn has_local_pending_transactions(&self) -> bool {
 		self.current.by_priority.iter().any(|tx| tx.origin == TransactionOrigin::Local)
@@ -1143,6 +1242,9 @@ impl TransactionQueue {
 
 	/// Removes all elements (in any state) from the queue
 	pub fn clear(&mut self) {
+		for hash in self.by_hash.keys() {
+			self.listeners.notify(TransactionEvent::Dropped(*hash, DropReason::Cleared));
+		}
 		self.current.clear();
 		self.future.clear();
 		self.by_hash.clear();
@@ -1154,14 +1256,24 @@ impl TransactionQueue {
 		self.last_nonces.get(address).cloned()
 	}
 
+	/// Moves matching transactions from `future` to `current` and notifies listeners about them.
+	fn promote_matching_future(&mut self, address: Address, current_nonce: U256, first_nonce: U256) {
+		for hash in self.move_matching_future_to_current(address, current_nonce, first_nonce) {
+			self.listeners.notify(TransactionEvent::Promoted(hash));
+		}
+	}
+
 	/// Checks if there are any transactions in `future` that should actually be promoted to `current`
 	/// (because nonce matches).
-	fn move_matching_future_to_current(&mut self, address: Address, mut current_nonce: U256, first_nonce: U256) {
+	///
+	/// Returns hashes of moved transactions.
+	fn move_matching_future_to_current(&mut self, address: Address, mut current_nonce: U256, first_nonce: U256) -> Vec<H256> {
 		let mut update_last_nonce_to = None;
+		let mut moved = Vec::new();
 		{
 			let by_nonce = self.future.by_address.row_mut(&address);
 			if by_nonce.is_none() {
-				return;
+				return moved;
 			}
 			let mut by_nonce = by_nonce.expect("None is tested in early-exit condition above; qed");
 			while let Some(order) = by_nonce.remove(&current_nonce) {
@@ -1173,8 +1285,9 @@ impl TransactionQueue {
 				if order.origin.is_local() {
 					self.local_transactions.mark_pending(order.hash);
 				}
+				moved.push(order.hash);
 				if let Some(old) = self.current.insert(address, current_nonce, order.clone()) {
-					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions);
+					Self::replace_orders(address, current_nonce, old, order, &mut self.current, &mut self.by_hash, &mut self.local_transactions, &self.listeners);
 				}
 				update_last_nonce_to = Some(current_nonce);
 				current_nonce = current_nonce + U256::one();
@@ -1185,6 +1298,7 @@ impl TransactionQueue {
 			// Update last inserted nonce
 			self.last_nonces.insert(address, x);
 		}
+		moved
 	}
 
 	/// Adds VerifiedTransaction to this queue.
@@ -1219,10 +1333,19 @@ impl TransactionQueue {
 			return Err(TransactionError::Old);
 		}
 
+		// Don't let external transactions reserve nonces too far in the future.
+		if !tx.origin.is_local() && nonce - state_nonce > self.max_nonce_gap {
+			trace!(target: "txqueue", "Dropping too distant transaction: {:?} (nonce: {}, state nonce: {})", tx.hash(), nonce, state_nonce);
+			return Err(TransactionError::NonceGapTooLarge {
+				limit: self.max_nonce_gap,
+				got: nonce - state_nonce,
+			});
+		}
+
 		// Update nonces of transactions in future (remove old transactions)
 		self.update_future(&address, state_nonce);
 		// State nonce could be updated. Maybe there are some more items waiting in future?
-		self.move_matching_future_to_current(address, state_nonce, state_nonce);
+		self.promote_matching_future(address, state_nonce, state_nonce);
 		// Check the next expected nonce (might be updated by move above)
 		let next_nonce = self.last_nonces
 			.get(&address)
@@ -1238,12 +1361,16 @@ impl TransactionQueue {
 			// We have a gap - put to future.
 			// Insert transaction (or replace old one with lower gas price)
 			check_too_cheap(
-				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions)
+				Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.future, &mut self.by_hash, &mut self.local_transactions, &self.listeners)
 			)?;
+			self.listeners.notify(TransactionEvent::Added(hash, false));
 			// Enforce limit in Future
-			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+			let removed = self.future.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
 			// Return an error if this transaction was not imported because of limit.
 			check_if_removed(&address, &nonce, removed)?;
+			// Enforce sender's memory limit
+			let removed = self.enforce_sender_memory_limit(&address, state_nonce);
+			check_if_removed_by_sender_limit(&address, &nonce, removed)?;
 
 			debug!(target: "txqueue", "Importing transaction to future: {:?}", hash);
 			debug!(target: "txqueue", "status: {:?}", self.status());
@@ -1251,29 +1378,98 @@ impl TransactionQueue {
 		}
 
 		// We might have filled a gap - move some more transactions from future
-		self.move_matching_future_to_current(address, nonce, state_nonce);
-		self.move_matching_future_to_current(address, nonce + U256::one(), state_nonce);
+		self.promote_matching_future(address, nonce, state_nonce);
+		self.promote_matching_future(address, nonce + U256::one(), state_nonce);
 
 		// Replace transaction if any
 		check_too_cheap(
-			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions)
+			Self::replace_transaction(tx, state_nonce, min_gas_price, &mut self.current, &mut self.by_hash, &mut self.local_transactions, &self.listeners)
 		)?;
+		self.listeners.notify(TransactionEvent::Added(hash, true));
 		// Keep track of highest nonce stored in current
 		let new_max = self.last_nonces.get(&address).map_or(nonce, |n| cmp::max(nonce, *n));
 		self.last_nonces.insert(address, new_max);
 
 		// Also enforce the limit
-		let removed = self.current.enforce_limit(&mut self.by_hash, &mut self.local_transactions);
+		let removed = self.current.enforce_limit(&mut self.by_hash, &mut self.local_transactions, &self.listeners);
 		// If some transaction were removed because of limit we need to update last_nonces also.
 		self.update_last_nonces(&removed);
 		// Trigger error if the transaction we are importing was removed.
 		check_if_removed(&address, &nonce, removed)?;
+		// Enforce sender's memory limit
+		let removed = self.enforce_sender_memory_limit(&address, state_nonce);
+		check_if_removed_by_sender_limit(&address, &nonce, removed)?;
+		// Transaction could have been moved to future if one below it was dropped
+		if self.future.by_address.get(&address, &nonce).is_some() {
+			debug!(target: "txqueue", "Imported transaction to future: {:?}", hash);
+			return Ok(TransactionImportResult::Future);
+		}
 
 		debug!(target: "txqueue", "Imported transaction to current: {:?}", hash);
 		debug!(target: "txqueue", "status: {:?}", self.status());
 		Ok(TransactionImportResult::Current)
 	}
 
+	/// Drops the most distant (highest nonce) external transactions of `sender` until the total size
+	/// of its transactions fits into `per_sender_memory_limit`. Local transactions and transactions
+	/// of retracted blocks are never dropped.
+	///
+	/// Transactions of `sender` above a dropped one are moved back to future.
+	///
+	/// Returns the sender and lowest nonce of removed transactions (if any).
+	fn enforce_sender_memory_limit(&mut self, sender: &Address, state_nonce: U256) -> Option<HashMap<Address, U256>> {
+		let mut orders: Vec<(U256, usize, bool)> = {
+			let by_hash = &self.by_hash;
+			let collect = |set: &TransactionSet, in_current: bool| set.by_address.row(sender)
+				.into_iter()
+				.flat_map(|row| row.iter())
+				.map(|(nonce, order)| {
+					let tx = by_hash.get(&order.hash)
+						.expect("All transactions in `self.by_address` are kept in sync with `by_hash`; qed");
+					(*nonce, tx.size, in_current)
+				})
+				.collect::<Vec<_>>();
+			let mut orders = collect(&self.current, true);
+			orders.extend(collect(&self.future, false));
+			orders
+		};
+
+		let mut total = orders.iter().fold(0usize, |acc, &(_, size, _)| acc.saturating_add(size));
+		if total <= self.per_sender_memory_limit {
+			return None;
+		}
+
+		// Most distant nonces are dropped first.
+		orders.sort_by(|a, b| b.0.cmp(&a.0));
+		let mut removed_current = false;
+		let mut removed = HashMap::new();
+		for (nonce, size, in_current) in orders {
+			if total <= self.per_sender_memory_limit {
+				break;
+			}
+			let set = if in_current { &mut self.current } else { &mut self.future };
+			if set.by_address.get(sender, &nonce).map_or(true, |order| order.origin.is_local() || order.origin == TransactionOrigin::RetractedBlock) {
+				continue;
+			}
+			let order = set.drop(sender, &nonce)
+				.expect("Transaction has just been found in `by_address`; qed");
+			trace!(target: "txqueue", "Dropped transaction above sender's memory limit: {:?}", order.hash);
+			self.by_hash.remove(&order.hash);
+			self.listeners.notify(TransactionEvent::Dropped(order.hash, DropReason::Limit));
+			total -= size;
+
+			removed_current |= in_current;
+			removed.insert(*sender, nonce);
+		}
+
+		// Kept transactions above a dropped one can't be mined until it is resubmitted.
+		// Move them to future, just like when an invalid transaction is removed.
+		if removed_current {
+			self.cull_internal(*sender, state_nonce);
+		}
+		Some(removed)
+	}
+
 	/// Updates
 	fn update_last_nonces(&mut self, removed_min_nonces: &Option<HashMap<Address, U256>>) {
 		if let Some(ref min_nonces) = *removed_min_nonces {
@@ -1301,6 +1497,7 @@ impl TransactionQueue {
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
+		listeners: &Listeners,
 	) -> bool {
 		let order = TransactionOrder::for_transaction(&tx, base_nonce, min_gas_price.0, min_gas_price.1);
 		let hash = tx.hash();
@@ -1313,7 +1510,7 @@ impl TransactionQueue {
 		trace!(target: "txqueue", "Inserting: {:?}", order);
 
 		if let Some(old) = set.insert(address, nonce, order.clone()) {
-			Self::replace_orders(address, nonce, old, order, set, by_hash, local)
+			Self::replace_orders(address, nonce, old, order, set, by_hash, local, listeners)
 		} else {
 			true
 		}
@@ -1327,6 +1524,7 @@ impl TransactionQueue {
 		set: &mut TransactionSet,
 		by_hash: &mut HashMap<H256, VerifiedTransaction>,
 		local: &mut LocalTransactionsList,
+		listeners: &Listeners,
 	) -> bool {
 		// There was already transaction in queue. Let's check which one should stay
 		let old_hash = old.hash;
@@ -1339,6 +1537,7 @@ impl TransactionQueue {
 			set.insert(address, nonce, old);
 			// and remove new one
 			let order = by_hash.remove(&order.hash).expect("The hash has been just inserted and no other line is altering `by_hash`.");
+			listeners.notify(TransactionEvent::Replaced(new_hash, old_hash));
 			if order.origin.is_local() {
 				local.mark_replaced(order.transaction, old_fee, old_hash);
 			}
@@ -1347,6 +1546,7 @@ impl TransactionQueue {
 			trace!(target: "txqueue", "Replaced transaction: {:?} with transaction with higher gas price: {:?}", old.hash, order.hash);
 			// Make sure we remove old transaction entirely
 			let old = by_hash.remove(&old.hash).expect("The hash is coming from `future` so it has to be in `by_hash`.");
+			listeners.notify(TransactionEvent::Replaced(old_hash, new_hash));
 			if old.origin.is_local() {
 				local.mark_replaced(old.transaction, new_fee, new_hash);
 			}
@@ -1375,6 +1575,12 @@ fn check_if_removed(sender: &Address, nonce: &U256, dropped: Option<HashMap<Addr
 	}
 }
 
+fn check_if_removed_by_sender_limit(sender: &Address, nonce: &U256, dropped: Option<HashMap<Address, U256>>) -> Result<(), TransactionError> {
+	match check_if_removed(sender, nonce, dropped) {
+		Err(TransactionError::LimitReached) => Err(TransactionError::SenderLimitReached),
+		result => result,
+	}
+}
 
 #[cfg(test)]
 pub mod test {
@@ -1609,7 +1815,7 @@ pub mod test {
 		assert_eq!(set.by_address.len(), 2);
 
 		// when
-		set.enforce_limit(&mut by_hash, &mut local);
+		set.enforce_limit(&mut by_hash, &mut local, &Default::default());
 
 		// then
 		assert_eq!(by_hash.len(), 1);
@@ -2346,6 +2552,78 @@ pub mod test {
 		assert_eq!(txq.status().future, 1);
 	}
 
+	#[test]
+	fn should_reject_transactions_beyond_nonce_gap() {
+		// given
+		let mut txq = TransactionQueue::default();
+		txq.set_max_nonce_gap(5.into());
+		let (_, tx1) = new_tx_pair_default(5.into(), 0.into());
+		let (tx2, tx3) = new_tx_pair_default(6.into(), 0.into());
+
+		// when
+		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider());
+		// local transactions are not limited
+		txq.add(tx2.clone(), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx3.clone(), TransactionOrigin::Local, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::NonceGapTooLarge { limit: 5.into(), got: 6.into() });
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.status().future, 2);
+	}
+
+	#[test]
+	fn should_drop_most_distant_transactions_above_sender_memory_limit() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let keypair = Random.generate().unwrap();
+		let new_tx = |nonce: U256| new_unsigned_tx(nonce, default_gas_val(), default_gas_price()).sign(keypair.secret(), None);
+		let (tx1, tx2, tx3) = (new_tx(default_nonce()), new_tx(default_nonce() + 1.into()), new_tx(default_nonce() + 2.into()));
+		let size = VerifiedTransaction::new(tx1.clone(), TransactionOrigin::External, 0, None).size;
+		txq.set_per_sender_memory_limit(size * 5 / 2);
+		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		assert_eq!(txq.status().future, 1);
+
+		// when
+		txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		let res = txq.add(tx3.clone(), TransactionOrigin::External, 0, None, &default_tx_provider());
+		// other senders are not affected
+		txq.add(new_tx_default(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(unwrap_tx_err(res), TransactionError::SenderLimitReached);
+		assert_eq!(txq.status().pending, 3);
+		assert_eq!(txq.status().future, 0);
+		assert_eq!(txq.last_nonce(&tx2.sender()), Some(tx2.nonce));
+	}
+
+	#[test]
+	fn should_move_transactions_above_dropped_one_to_future() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let keypair = Random.generate().unwrap();
+		let new_tx = |nonce: U256| new_unsigned_tx(nonce, default_gas_val(), default_gas_price()).sign(keypair.secret(), None);
+		let (tx1, tx2, tx3) = (new_tx(default_nonce()), new_tx(default_nonce() + 1.into()), new_tx(default_nonce() + 2.into()));
+		let size = VerifiedTransaction::new(tx1.clone(), TransactionOrigin::External, 0, None).size;
+		txq.set_per_sender_memory_limit(size * 5 / 2);
+		txq.add(tx1.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// when
+		// transactions of retracted blocks are kept, so `tx2` is dropped instead
+		let res = txq.add(tx3.clone(), TransactionOrigin::RetractedBlock, 0, None, &default_tx_provider());
+
+		// then
+		assert_eq!(res.unwrap(), TransactionImportResult::Future);
+		assert_eq!(txq.status().pending, 1);
+		assert_eq!(txq.status().future, 1);
+		assert_eq!(txq.last_nonce(&tx1.sender()), Some(tx1.nonce));
+		assert_eq!(txq.top_transactions(), vec![tx1]);
+		assert!(txq.find(&tx2.hash()).is_none());
+	}
+
 	#[test]
 	fn should_limit_by_gas() {
 		let mut txq = TransactionQueue::with_limits(PrioritizationStrategy::GasPriceOnly, 100, default_gas_val() * U256::from(2), !U256::zero());
@@ -2793,4 +3071,91 @@ pub mod test {
 		// then
 		assert_eq!(txq.top_transactions().len(), 1);
 	}
+
+	fn listen(txq: &mut TransactionQueue) -> Arc<Mutex<Vec<TransactionEvent>>> {
+		let events = Arc::new(Mutex::new(Vec::new()));
+		let e = events.clone();
+		txq.add_listener(Box::new(move |event| e.lock().push(event.clone())));
+		events
+	}
+
+	#[test]
+	fn should_notify_listeners_about_added_and_promoted_transactions() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let events = listen(&mut txq);
+		let kp = Random.generate().unwrap();
+		let secret = kp.secret();
+		let tx: SignedTransaction = new_unsigned_tx(123.into(), default_gas_val(), 1.into()).sign(secret, None).into();
+		let tx1: SignedTransaction = new_unsigned_tx(124.into(), default_gas_val(), 1.into()).sign(secret, None).into();
+		let tx2: SignedTransaction = new_unsigned_tx(125.into(), default_gas_val(), 1.into()).sign(secret, None).into();
+		let (hash, hash1, hash2) = (tx.hash(), tx1.hash(), tx2.hash());
+
+		// when
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx1, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(*events.lock(), vec![
+			TransactionEvent::Added(hash, true),
+			TransactionEvent::Added(hash2, false),
+			TransactionEvent::Promoted(hash2),
+			TransactionEvent::Added(hash1, true),
+		]);
+	}
+
+	#[test]
+	fn should_notify_listeners_about_removed_transactions() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let events = listen(&mut txq);
+		let (tx, tx2) = new_tx_pair_default(1.into(), 0.into());
+		let other = new_tx_default();
+		let sender = tx.sender();
+		let (hash, hash2) = (tx.hash(), tx2.hash());
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		events.lock().clear();
+
+		// when
+		txq.cull(sender, default_nonce() + U256::one());
+		txq.remove_invalid(&hash2, &|_| default_nonce() + U256::one());
+		txq.add(other.clone(), TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.clear();
+
+		// then
+		assert_eq!(*events.lock(), vec![
+			TransactionEvent::Mined(hash),
+			TransactionEvent::Dropped(hash2, DropReason::Invalid),
+			TransactionEvent::Added(other.hash(), true),
+			TransactionEvent::Dropped(other.hash(), DropReason::Cleared),
+		]);
+	}
+
+	#[test]
+	fn should_notify_listeners_about_replaced_transactions() {
+		// given
+		let mut txq = TransactionQueue::default();
+		let events = listen(&mut txq);
+		let keypair = Random.generate().unwrap();
+		let tx = new_unsigned_tx(123.into(), default_gas_val(), 1.into()).sign(keypair.secret(), None);
+		let tx2 = {
+			let mut tx2 = (**tx).clone();
+			tx2.gas_price = U256::from(200);
+			tx2.sign(keypair.secret(), None)
+		};
+		let (hash, hash2) = (tx.hash(), tx2.hash());
+
+		// when
+		txq.add(tx, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+		txq.add(tx2, TransactionOrigin::External, 0, None, &default_tx_provider()).unwrap();
+
+		// then
+		assert_eq!(*events.lock(), vec![
+			TransactionEvent::Added(hash, true),
+			TransactionEvent::Replaced(hash, hash2),
+			TransactionEvent::Added(hash2, true),
+		]);
+	}
 }
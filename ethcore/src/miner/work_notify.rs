@@ -32,6 +32,10 @@ pub trait NotifyWork : Send + Sync {
 	fn notify(&self, pow_hash: H256, difficulty: U256, number: u64);
 }
 
+/// Pushes new work packages to a set of HTTP endpoints.
+///
+/// Each notification is a JSON `POST` with the same `result` layout as `eth_getWork`:
+/// `[pow_hash, seed_hash, boundary, block_number]`.
 pub struct WorkPoster {
 	urls: Vec<Url>,
 	client: Mutex<Client<PostHandler>>,
@@ -39,6 +43,7 @@ pub struct WorkPoster {
 }
 
 impl WorkPoster {
+	/// Create a new poster for the given URLs. Unparseable URLs are skipped with a warning.
 	pub fn new(urls: &[String]) -> Self {
 		let urls = urls.into_iter().filter_map(|u| {
 			match Url::parse(u) {
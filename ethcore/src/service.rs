@@ -48,7 +48,9 @@ pub enum ClientIoMessage {
 	/// Take a snapshot for the block with given number.
 	TakeSnapshot(u64),
 	/// New consensus message received.
-	NewMessage(Bytes)
+	NewMessage(Bytes),
+	/// Compact the client database.
+	CompactDatabase,
 }
 
 /// Client service setup. Creates and registers client and network services with the IO subsystem.
@@ -87,7 +89,11 @@ impl ClientService {
 		}
 
 		db_config.compaction = config.db_compaction.compaction_profile(client_path);
+		if let Some(rate_limit) = config.db_compaction_rate_limit {
+			db_config.compaction.write_rate_limit = Some(rate_limit);
+		}
 		db_config.wal = config.db_wal;
+		db_config.read_only = config.read_only;
 
 		let db = Arc::new(Database::open(
 			&db_config,
@@ -222,6 +228,18 @@ impl IoHandler<ClientIoMessage> for ClientIoHandler {
 					debug!(target: "snapshot", "Failed to initialize periodic snapshot thread: {:?}", e);
 				}
 			},
+			ClientIoMessage::CompactDatabase => {
+				let client = self.client.clone();
+
+				let res = thread::Builder::new().name("Database Compaction".into()).spawn(move || {
+					client.compact_db();
+				});
+
+				if let Err(e) = res {
+					debug!(target: "client", "Failed to initialize database compaction thread: {:?}", e);
+					self.client.compaction_aborted();
+				}
+			},
 			ClientIoMessage::NewMessage(ref message) => if let Err(e) = self.client.engine().handle_message(message) {
 				trace!(target: "poa", "Invalid message received: {}", e);
 			},
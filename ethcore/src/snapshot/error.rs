@@ -47,6 +47,8 @@ pub enum Error {
 	UnrecognizedCodeState(u8),
 	/// Restoration aborted.
 	RestorationAborted,
+	/// Chunk doesn't match its hash: (expected, found).
+	ChunkHashMismatch(H256, H256),
 	/// Trie error.
 	Trie(TrieError),
 	/// Decoder error.
@@ -70,6 +72,7 @@ impl fmt::Display for Error {
 			Error::MissingCode(ref missing) => write!(f, "Incomplete snapshot: {} contract codes not found.", missing.len()),
 			Error::UnrecognizedCodeState(state) => write!(f, "Unrecognized code encoding ({})", state),
 			Error::RestorationAborted => write!(f, "Snapshot restoration aborted."),
+			Error::ChunkHashMismatch(ref expected, ref found) => write!(f, "Snapshot chunk has wrong hash. Expected {:?}, got {:?}", expected, found),
 			Error::Io(ref err) => err.fmt(f),
 			Error::Decoder(ref err) => err.fmt(f),
 			Error::Trie(ref err) => err.fmt(f),
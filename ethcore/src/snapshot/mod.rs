@@ -35,6 +35,7 @@ use util::Mutex;
 use util::hash::{H256};
 use util::journaldb::{self, Algorithm, JournalDB};
 use util::kvdb::Database;
+use util::memorydb::MemoryDB;
 use util::trie::{TrieDB, TrieDBMut, Trie, TrieMut};
 use util::sha3::SHA3_NULL_RLP;
 use rlp::{RlpStream, UntrustedRlp};
@@ -42,6 +43,7 @@ use bloom_journal::Bloom;
 
 use self::block::AbridgedBlock;
 use self::io::SnapshotWriter;
+use self::restoration_progress::RestorationProgress;
 
 use super::state_db::StateDB;
 use super::state::Account as StateAccount;
@@ -63,6 +65,7 @@ pub mod service;
 mod account;
 mod block;
 mod error;
+mod restoration_progress;
 mod watcher;
 
 #[cfg(test)]
@@ -404,25 +407,57 @@ impl StateRebuilder {
 		}
 	}
 
-	/// Feed an uncompressed state chunk into the rebuilder.
-	pub fn feed(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
+	/// Rebuild the accounts of an uncompressed state chunk into a database of its own.
+	/// This doesn't touch any rebuilder, so chunks can be rebuilt in parallel and then
+	/// applied one by one.
+	pub fn rebuild_chunk(chunk: &[u8], flag: &AtomicBool) -> Result<RebuiltChunk, ::error::Error> {
 		let rlp = UntrustedRlp::new(chunk);
-		let empty_rlp = StateAccount::new_basic(U256::zero(), U256::zero()).rlp();
+		let mut db = MemoryDB::new();
 		let mut pairs = Vec::with_capacity(rlp.item_count()?);
 
 		// initialize the pairs vector with empty values so we have slots to write into.
 		pairs.resize(rlp.item_count()?, (H256::new(), Vec::new()));
 
-		let status = rebuild_accounts(
-			self.db.as_hashdb_mut(),
-			rlp,
-			&mut pairs,
-			&self.known_code,
-			flag
-		)?;
+		// code of earlier chunks isn't available here, it's copied over when the chunk is applied.
+		let status = rebuild_accounts(&mut db, rlp, &mut pairs, &HashMap::new(), flag)?;
+
+		Ok(RebuiltChunk {
+			db: db,
+			pairs: pairs,
+			status: status,
+		})
+	}
+
+	/// Feed an uncompressed state chunk into the rebuilder.
+	pub fn feed(&mut self, chunk: &[u8], flag: &AtomicBool) -> Result<(), ::error::Error> {
+		let rebuilt = Self::rebuild_chunk(chunk, flag)?;
+		self.apply(rebuilt, flag)
+	}
+
+	/// Apply a rebuilt state chunk to the state being restored.
+	pub fn apply(&mut self, rebuilt: RebuiltChunk, flag: &AtomicBool) -> Result<(), ::error::Error> {
+		let RebuiltChunk { mut db, pairs, status } = rebuilt;
+		let empty_rlp = StateAccount::new_basic(U256::zero(), U256::zero()).rlp();
+
+		// move the storage and code of the chunk's accounts over.
+		for (key, (value, rc)) in db.drain() {
+			for _ in 0..rc {
+				self.db.as_hashdb_mut().emplace(key, value.clone());
+			}
+		}
 
 		for (addr_hash, code_hash) in status.missing_code {
-			self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(addr_hash);
+			// see if this code has already been included inline by an earlier chunk.
+			match self.known_code.get(&code_hash).cloned() {
+				Some(first_with) => {
+					let code = AccountDB::from_hash(self.db.as_hashdb(), first_with)
+						.get(&code_hash)
+						.ok_or_else(|| Error::MissingCode(vec![first_with]))?;
+
+					AccountDBMut::from_hash(self.db.as_hashdb_mut(), addr_hash).emplace(code_hash, code);
+				}
+				None => self.missing_code.entry(code_hash).or_insert_with(Vec::new).push(addr_hash),
+			}
 		}
 
 		// patch up all missing code. must be done after collecting all new missing code entries.
@@ -476,6 +511,27 @@ impl StateRebuilder {
 
 	/// Get the state root of the rebuilder.
 	pub fn state_root(&self) -> H256 { self.state_root }
+
+	/// Note the in-memory state of the rebuilder in the restoration progress.
+	pub fn note_progress(&self, progress: &mut RestorationProgress) {
+		progress.state_root = self.state_root;
+		progress.known_code = self.known_code.iter().map(|(k, v)| (*k, *v)).collect();
+		progress.missing_code = self.missing_code.iter().map(|(k, v)| (*k, v.clone())).collect();
+	}
+
+	/// Continue rebuilding from the restoration progress of an interrupted restoration.
+	pub fn resume(&mut self, progress: &RestorationProgress) {
+		self.state_root = progress.state_root;
+		self.known_code = progress.known_code.iter().cloned().collect();
+		self.missing_code = progress.missing_code.iter().cloned().collect();
+	}
+}
+
+/// Accounts of a state chunk rebuilt by `StateRebuilder::rebuild_chunk`.
+pub struct RebuiltChunk {
+	db: MemoryDB,
+	pairs: Vec<(H256, Bytes)>,
+	status: RebuiltStatus,
 }
 
 #[derive(Default)]
@@ -671,6 +727,18 @@ impl BlockRebuilder {
 		Ok(num_blocks)
 	}
 
+	/// Note the in-memory state of the rebuilder in the restoration progress.
+	pub fn note_progress(&self, progress: &mut RestorationProgress) {
+		progress.disconnected = self.disconnected.clone();
+		progress.fed_blocks = self.fed_blocks;
+	}
+
+	/// Continue rebuilding from the restoration progress of an interrupted restoration.
+	pub fn resume(&mut self, progress: &RestorationProgress) {
+		self.disconnected = progress.disconnected.clone();
+		self.fed_blocks = progress.fed_blocks;
+	}
+
 	/// Glue together any disconnected chunks and check that the chain is complete.
 	pub fn finalize(self, canonical: HashMap<u64, H256>) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
@@ -0,0 +1,172 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Progress of a snapshot restoration, persisted next to the restoration
+//! database so that an interrupted restoration can be resumed.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use util::{Bytes, H256};
+use rlp::{RlpStream, UntrustedRlp, DecoderError};
+
+/// Everything needed to pick up a restoration where it was left, as of the
+/// last database flush.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RestorationProgress {
+	/// Hash of the RLP of the manifest being restored.
+	pub manifest_hash: H256,
+	/// Whether the restored chunks are also written out as a new snapshot.
+	pub recover: bool,
+	/// State chunks fed so far.
+	pub state_chunks_done: Vec<H256>,
+	/// Block chunks fed so far.
+	pub block_chunks_done: Vec<H256>,
+	/// Root of the partially rebuilt state trie.
+	pub state_root: H256,
+	/// Code hashes mapped to the first account with this code.
+	pub known_code: Vec<(H256, H256)>,
+	/// Code hashes mapped to accounts still missing that code.
+	pub missing_code: Vec<(H256, Vec<H256>)>,
+	/// First block number and hash of block chunks not yet connected to their parent.
+	pub disconnected: Vec<(u64, H256)>,
+	/// Number of blocks fed so far.
+	pub fed_blocks: u64,
+}
+
+impl RestorationProgress {
+	/// Encode the progress to RLP.
+	pub fn to_rlp(&self) -> Bytes {
+		let mut stream = RlpStream::new_list(9);
+		stream.append(&self.manifest_hash);
+		stream.append(&self.recover);
+		stream.append_list(&self.state_chunks_done);
+		stream.append_list(&self.block_chunks_done);
+		stream.append(&self.state_root);
+
+		stream.begin_list(self.known_code.len());
+		for &(ref code_hash, ref first_with) in &self.known_code {
+			stream.begin_list(2).append(code_hash).append(first_with);
+		}
+
+		stream.begin_list(self.missing_code.len());
+		for &(ref code_hash, ref accounts) in &self.missing_code {
+			stream.begin_list(2).append(code_hash).append_list(accounts);
+		}
+
+		stream.begin_list(self.disconnected.len());
+		for &(ref number, ref hash) in &self.disconnected {
+			stream.begin_list(2).append(number).append(hash);
+		}
+
+		stream.append(&self.fed_blocks);
+		stream.out()
+	}
+
+	/// Decode the progress from RLP.
+	pub fn from_rlp(raw: &[u8]) -> Result<Self, DecoderError> {
+		let rlp = UntrustedRlp::new(raw);
+
+		let known_code = rlp.at(5)?.iter()
+			.map(|pair| Ok((pair.val_at(0)?, pair.val_at(1)?)))
+			.collect::<Result<_, DecoderError>>()?;
+		let missing_code = rlp.at(6)?.iter()
+			.map(|pair| Ok((pair.val_at(0)?, pair.list_at(1)?)))
+			.collect::<Result<_, DecoderError>>()?;
+		let disconnected = rlp.at(7)?.iter()
+			.map(|pair| Ok((pair.val_at(0)?, pair.val_at(1)?)))
+			.collect::<Result<_, DecoderError>>()?;
+
+		Ok(RestorationProgress {
+			manifest_hash: rlp.val_at(0)?,
+			recover: rlp.val_at(1)?,
+			state_chunks_done: rlp.list_at(2)?,
+			block_chunks_done: rlp.list_at(3)?,
+			state_root: rlp.val_at(4)?,
+			known_code: known_code,
+			missing_code: missing_code,
+			disconnected: disconnected,
+			fed_blocks: rlp.val_at(8)?,
+		})
+	}
+
+	/// Read the progress from a file. Yields `None` if the file is missing or unreadable.
+	pub fn read(path: &Path) -> Option<Self> {
+		let mut bytes = Vec::new();
+		if File::open(path).and_then(|mut file| file.read_to_end(&mut bytes)).is_err() {
+			return None;
+		}
+
+		match RestorationProgress::from_rlp(&bytes) {
+			Ok(progress) => Some(progress),
+			Err(e) => {
+				warn!(target: "snapshot", "Ignoring corrupt restoration progress at {}: {}", path.display(), e);
+				None
+			}
+		}
+	}
+
+	/// Write the progress to a file, replacing it atomically.
+	pub fn write(&self, path: &Path) -> ::std::io::Result<()> {
+		let temp_path = path.with_extension("tmp");
+		{
+			let mut file = File::create(&temp_path)?;
+			file.write_all(&self.to_rlp())?;
+			file.sync_all()?;
+		}
+		fs::rename(&temp_path, path)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use devtools::RandomTempPath;
+	use util::H256;
+	use super::RestorationProgress;
+
+	#[test]
+	fn rlp_roundtrip() {
+		let progress = RestorationProgress {
+			manifest_hash: H256::random(),
+			recover: true,
+			state_chunks_done: vec![H256::random(), H256::random()],
+			block_chunks_done: vec![H256::random()],
+			state_root: H256::random(),
+			known_code: vec![(H256::random(), H256::random())],
+			missing_code: vec![(H256::random(), vec![H256::random(), H256::random()])],
+			disconnected: vec![(1000, H256::random())],
+			fed_blocks: 2000,
+		};
+
+		assert_eq!(RestorationProgress::from_rlp(&progress.to_rlp()).unwrap(), progress);
+	}
+
+	#[test]
+	fn write_and_read() {
+		let path = RandomTempPath::new();
+		assert!(RestorationProgress::read(path.as_path()).is_none());
+
+		let progress = RestorationProgress {
+			manifest_hash: H256::random(),
+			state_chunks_done: vec![H256::random()],
+			..Default::default()
+		};
+
+		progress.write(path.as_path()).unwrap();
+		assert_eq!(RestorationProgress::read(path.as_path()), Some(progress));
+	}
+}
@@ -22,24 +22,32 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use super::{ManifestData, StateRebuilder, BlockRebuilder, RestorationStatus, SnapshotService};
+use super::{ManifestData, StateRebuilder, RebuiltChunk, BlockRebuilder, RestorationStatus, SnapshotService};
 use super::io::{SnapshotReader, LooseReader, SnapshotWriter, LooseWriter};
+use super::restoration_progress::RestorationProgress;
 
 use blockchain::BlockChain;
 use client::{BlockChainClient, Client};
 use engines::Engine;
 use error::Error;
 use ids::BlockId;
+use snapshot::Error as SnapshotError;
 use service::ClientIoMessage;
 
 use io::IoChannel;
 
-use util::{Bytes, H256, Mutex, RwLock, RwLockReadGuard, UtilError};
+use util::{Bytes, H256, Hashable, Mutex, RwLock, RwLockReadGuard, UtilError};
 use util::journaldb::Algorithm;
 use util::kvdb::{Database, DatabaseConfig};
 use util::snappy;
 
+// number of chunks fed after which the restoration progress is saved.
+const PROGRESS_SAVE_CHUNKS: usize = 32;
+// maximal time between saves of the restoration progress, in seconds.
+const PROGRESS_SAVE_INTERVAL_SECS: u64 = 30;
+
 /// Helper for removing directories in case of error.
 struct Guard(bool, PathBuf);
 
@@ -71,11 +79,14 @@ struct Restoration {
 	state: StateRebuilder,
 	blocks: BlockRebuilder,
 	writer: Option<LooseWriter>,
-	snappy_buffer: Bytes,
 	final_state_root: H256,
 	guard: Guard,
 	canonical_hashes: HashMap<u64, H256>,
 	db: Arc<Database>,
+	manifest_hash: H256,
+	progress_path: PathBuf,
+	unsaved_chunks: usize,
+	last_save: Instant,
 }
 
 struct RestorationParams<'a> {
@@ -86,65 +97,129 @@ struct RestorationParams<'a> {
 	writer: Option<LooseWriter>, // writer for recovered snapshot.
 	genesis: &'a [u8], // genesis block of the chain.
 	guard: Guard, // guard for the restoration directory.
+	progress_path: PathBuf, // path of the restoration progress file.
+	resume: Option<RestorationProgress>, // progress of an interrupted restoration to continue.
 }
 
 impl Restoration {
 	// make a new restoration using the given parameters.
 	fn new(params: RestorationParams) -> Result<Self, Error> {
 		let manifest = params.manifest;
+		let manifest_hash = manifest.clone().into_rlp().sha3();
 
-		let state_chunks = manifest.state_hashes.iter().cloned().collect();
-		let block_chunks = manifest.block_hashes.iter().cloned().collect();
+		let mut state_chunks: HashSet<_> = manifest.state_hashes.iter().cloned().collect();
+		let mut block_chunks: HashSet<_> = manifest.block_hashes.iter().cloned().collect();
 
 		let raw_db = Arc::new(Database::open(params.db_config, &*params.db_path.to_string_lossy())
 			.map_err(UtilError::SimpleString)?);
 
 		let chain = BlockChain::new(Default::default(), params.genesis, raw_db.clone());
-		let blocks = BlockRebuilder::new(chain, raw_db.clone(), &manifest)?;
+		let mut blocks = BlockRebuilder::new(chain, raw_db.clone(), &manifest)?;
+		let mut state = StateRebuilder::new(raw_db.clone(), params.pruning);
+
+		if let Some(progress) = params.resume {
+			for hash in &progress.state_chunks_done {
+				state_chunks.remove(hash);
+			}
+			for hash in &progress.block_chunks_done {
+				block_chunks.remove(hash);
+			}
+			state.resume(&progress);
+			blocks.resume(&progress);
+		}
 
 		let root = manifest.state_root.clone();
 		Ok(Restoration {
 			manifest: manifest,
 			state_chunks_left: state_chunks,
 			block_chunks_left: block_chunks,
-			state: StateRebuilder::new(raw_db.clone(), params.pruning),
+			state: state,
 			blocks: blocks,
 			writer: params.writer,
-			snappy_buffer: Vec::new(),
 			final_state_root: root,
 			guard: params.guard,
 			canonical_hashes: HashMap::new(),
 			db: raw_db,
+			manifest_hash: manifest_hash,
+			progress_path: params.progress_path,
+			unsaved_chunks: 0,
+			last_save: Instant::now(),
 		})
 	}
 
-	// feeds a state chunk, aborts early if `flag` becomes false.
-	fn feed_state(&mut self, hash: H256, chunk: &[u8], flag: &AtomicBool) -> Result<(), Error> {
-		if self.state_chunks_left.remove(&hash) {
-			let len = snappy::decompress_into(chunk, &mut self.snappy_buffer)?;
+	// feeds a rebuilt state chunk, aborts early if `flag` becomes false.
+	// returns false if the chunk isn't needed anymore.
+	fn feed_state(&mut self, hash: H256, chunk: RebuiltChunk, raw_chunk: &[u8], flag: &AtomicBool) -> Result<bool, Error> {
+		if !self.state_chunks_left.remove(&hash) { return Ok(false) }
 
-			self.state.feed(&self.snappy_buffer[..len], flag)?;
+		self.state.apply(chunk, flag)?;
 
-			if let Some(ref mut writer) = self.writer.as_mut() {
-				writer.write_state_chunk(hash, chunk)?;
-			}
+		if let Some(ref mut writer) = self.writer.as_mut() {
+			writer.write_state_chunk(hash, raw_chunk)?;
+		}
+
+		Ok(true)
+	}
+
+	// feeds a decompressed block chunk.
+	// returns false if the chunk isn't needed anymore.
+	fn feed_blocks(&mut self, hash: H256, chunk: &[u8], raw_chunk: &[u8], engine: &Engine, flag: &AtomicBool) -> Result<bool, Error> {
+		if !self.block_chunks_left.remove(&hash) { return Ok(false) }
+
+		self.blocks.feed(chunk, engine, flag)?;
+
+		if let Some(ref mut writer) = self.writer.as_mut() {
+			 writer.write_block_chunk(hash, raw_chunk)?;
+		}
+
+		Ok(true)
+	}
+
+	// note a fed chunk, saving the progress once enough chunks or time have passed since the last save.
+	fn checkpoint(&mut self) -> Result<(), Error> {
+		self.unsaved_chunks += 1;
+
+		let interval = Duration::from_secs(PROGRESS_SAVE_INTERVAL_SECS);
+		if self.unsaved_chunks < PROGRESS_SAVE_CHUNKS && self.last_save.elapsed() < interval {
+			return Ok(());
 		}
 
+		self.save_progress()?;
+		self.unsaved_chunks = 0;
+		self.last_save = Instant::now();
 		Ok(())
 	}
 
-	// feeds a block chunk
-	fn feed_blocks(&mut self, hash: H256, chunk: &[u8], engine: &Engine, flag: &AtomicBool) -> Result<(), Error> {
-		if self.block_chunks_left.remove(&hash) {
-			let len = snappy::decompress_into(chunk, &mut self.snappy_buffer)?;
+	// flush the database and persist the progress, so that everything fed so far
+	// survives a restart.
+	fn save_progress(&self) -> Result<(), Error> {
+		self.db.flush().map_err(UtilError::SimpleString)?;
+
+		let mut progress = RestorationProgress {
+			manifest_hash: self.manifest_hash,
+			recover: self.writer.is_some(),
+			state_chunks_done: self.manifest.state_hashes.iter().filter(|h| !self.state_chunks_left.contains(h)).cloned().collect(),
+			block_chunks_done: self.manifest.block_hashes.iter().filter(|h| !self.block_chunks_left.contains(h)).cloned().collect(),
+			..Default::default()
+		};
+		self.state.note_progress(&mut progress);
+		self.blocks.note_progress(&mut progress);
 
-			self.blocks.feed(&self.snappy_buffer[..len], engine, flag)?;
-			if let Some(ref mut writer) = self.writer.as_mut() {
-				 writer.write_block_chunk(hash, chunk)?;
+		progress.write(&self.progress_path)?;
+		Ok(())
+	}
+
+	// keep the restoration directory when dropped if there's progress to resume from.
+	fn suspend(self) {
+		if self.unsaved_chunks != 0 {
+			if let Err(e) = self.save_progress() {
+				warn!(target: "snapshot", "Failed to save restoration progress: {}", e);
 			}
 		}
 
-		Ok(())
+		if self.progress_path.exists() {
+			self.guard.disarm();
+		}
 	}
 
 	// note canonical hashes.
@@ -255,10 +330,12 @@ impl Service {
 			}
 		}
 
-		// delete the temporary restoration dir if it does exist.
-		if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
-			if e.kind() != ErrorKind::NotFound {
-				return Err(e.into())
+		// delete the temporary restoration dir if it does exist, unless the restoration can be resumed.
+		if !service.restoration_progress_path().exists() {
+			if let Err(e) = fs::remove_dir_all(service.restoration_dir()) {
+				if e.kind() != ErrorKind::NotFound {
+					return Err(e.into())
+				}
 			}
 		}
 
@@ -310,6 +387,20 @@ impl Service {
 		dir
 	}
 
+	// restoration progress file path.
+	fn restoration_progress_path(&self) -> PathBuf {
+		let mut path = self.restoration_dir();
+		path.push("progress");
+		path
+	}
+
+	// progress of an interrupted restoration of the given manifest, if there is one.
+	fn resumable_progress(&self, manifest: &ManifestData) -> Option<RestorationProgress> {
+		let manifest_hash = manifest.clone().into_rlp().sha3();
+		RestorationProgress::read(&self.restoration_progress_path())
+			.and_then(|progress| if progress.manifest_hash == manifest_hash { Some(progress) } else { None })
+	}
+
 	// replace one the client's database with our own.
 	fn replace_client_db(&self) -> Result<(), Error> {
 		let our_db = self.restoration_db();
@@ -398,18 +489,36 @@ impl Service {
 		self.state_chunks.store(0, Ordering::SeqCst);
 		self.block_chunks.store(0, Ordering::SeqCst);
 
-		// tear down existing restoration.
-		*res = None;
+		// tear down existing restoration, keeping its files around in case it's the one being restarted.
+		if let Some(rest) = res.take() {
+			rest.suspend();
+		}
 
-		// delete and restore the restoration dir.
-		if let Err(e) = fs::remove_dir_all(&rest_dir) {
-			match e.kind() {
-				ErrorKind::NotFound => {},
-				_ => return Err(e.into()),
+		let resume = self.resumable_progress(&manifest).and_then(|progress| match progress.recover == recover {
+			true => Some(progress),
+			false => None,
+		});
+
+		match resume {
+			Some(ref progress) => {
+				info!("Resuming snapshot restoration: {} state and {} block chunks already restored",
+					progress.state_chunks_done.len(), progress.block_chunks_done.len());
+
+				self.state_chunks.store(progress.state_chunks_done.len(), Ordering::SeqCst);
+				self.block_chunks.store(progress.block_chunks_done.len(), Ordering::SeqCst);
 			}
-		}
+			None => {
+				// delete and restore the restoration dir.
+				if let Err(e) = fs::remove_dir_all(&rest_dir) {
+					match e.kind() {
+						ErrorKind::NotFound => {},
+						_ => return Err(e.into()),
+					}
+				}
 
-		fs::create_dir_all(&rest_dir)?;
+				fs::create_dir_all(&rest_dir)?;
+			}
+		}
 
 		// make new restoration.
 		let writer = match recover {
@@ -425,6 +534,8 @@ impl Service {
 			writer: writer,
 			genesis: &self.genesis_block,
 			guard: Guard::new(rest_dir),
+			progress_path: self.restoration_progress_path(),
+			resume: resume,
 		};
 
 		let state_chunks = params.manifest.state_hashes.len();
@@ -480,48 +591,63 @@ impl Service {
 
 	/// Feed a chunk of either kind. no-op if no restoration or status is wrong.
 	fn feed_chunk(&self, hash: H256, chunk: &[u8], is_state: bool) -> Result<(), Error> {
+		match self.status() {
+			RestorationStatus::Inactive | RestorationStatus::Failed => return Ok(()),
+			RestorationStatus::Ongoing { .. } => {},
+		}
+
+		// verification and decompression don't touch the restoration, so chunks
+		// handed to different IO workers go through them in parallel.
+		let found = chunk.sha3();
+		if found != hash {
+			return Err(SnapshotError::ChunkHashMismatch(hash, found).into());
+		}
+		let decompressed = snappy::decompress(chunk)?;
+
+		// state chunks are rebuilt into databases of their own in parallel as well,
+		// only applying them to the restoration is serialized.
+		let rebuilt = match is_state {
+			true => Some(StateRebuilder::rebuild_chunk(&decompressed, &self.restoring_snapshot)?),
+			false => None,
+		};
+
 		// TODO: be able to process block chunks and state chunks at same time?
-		let (result, db) = {
-			let mut restoration = self.restoration.lock();
-
-			match self.status() {
-				RestorationStatus::Inactive | RestorationStatus::Failed => return Ok(()),
-				RestorationStatus::Ongoing { .. } => {
-					let (res, db) = {
-						let rest = match *restoration {
-							Some(ref mut r) => r,
-							None => return Ok(()),
-						};
-
-						(match is_state {
-							true => rest.feed_state(hash, chunk, &self.restoring_snapshot),
-							false => rest.feed_blocks(hash, chunk, &*self.engine, &self.restoring_snapshot),
-						}.map(|_| rest.is_done()), rest.db.clone())
-					};
-
-					let res = match res {
-						Ok(is_done) => {
-							match is_state {
-								true => self.state_chunks.fetch_add(1, Ordering::SeqCst),
-								false => self.block_chunks.fetch_add(1, Ordering::SeqCst),
-							};
-
-							match is_done {
-								true => {
-									db.flush().map_err(::util::UtilError::SimpleString)?;
-									drop(db);
-									return self.finalize_restoration(&mut *restoration);
-								},
-								false => Ok(())
-							}
-						}
-						other => other.map(drop),
-					};
-					(res, db)
+		let mut restoration = self.restoration.lock();
+		let is_done = {
+			let rest = match *restoration {
+				Some(ref mut r) => r,
+				None => return Ok(()),
+			};
+
+			let fed = match rebuilt {
+				Some(rebuilt) => rest.feed_state(hash, rebuilt, chunk, &self.restoring_snapshot)?,
+				None => rest.feed_blocks(hash, &decompressed, chunk, &*self.engine, &self.restoring_snapshot)?,
+			};
+
+			// duplicate or already restored chunk.
+			if !fed { return Ok(()) }
+
+			match is_state {
+				true => self.state_chunks.fetch_add(1, Ordering::SeqCst),
+				false => self.block_chunks.fetch_add(1, Ordering::SeqCst),
+			};
+
+			match rest.is_done() {
+				true => {
+					rest.db.flush().map_err(UtilError::SimpleString)?;
+					true
+				},
+				false => {
+					rest.checkpoint()?;
+					false
 				}
 			}
 		};
-		result.and_then(|_| db.flush().map_err(|e| ::util::UtilError::SimpleString(e).into()))
+
+		match is_done {
+			true => self.finalize_restoration(&mut *restoration),
+			false => Ok(()),
+		}
 	}
 
 	/// Feed a state chunk to be processed synchronously.
@@ -601,10 +727,21 @@ impl SnapshotService for Service {
 			rest.note_canonical(canonical);
 		}
 	}
+
+	fn completed_chunks(&self, manifest: ManifestData) -> Vec<H256> {
+		match self.resumable_progress(&manifest) {
+			Some(progress) => progress.state_chunks_done.into_iter().chain(progress.block_chunks_done).collect(),
+			None => Vec::new(),
+		}
+	}
 }
 
 impl Drop for Service {
 	fn drop(&mut self) {
+		// wait for the chunk being fed, then keep the restoration on disk to resume it on the next start.
+		if let Some(rest) = self.restoration.lock().take() {
+			rest.suspend();
+		}
 		self.abort_restore();
 	}
 }
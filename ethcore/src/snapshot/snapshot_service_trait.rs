@@ -52,6 +52,10 @@ pub trait SnapshotService : Sync + Send {
 	/// Give the restoration in-progress some canonical block hashes for
 	/// extra verification (performed at the end)
 	fn provide_canon_hashes(&self, canonical: &[(u64, H256)]);
+
+	/// Chunks of the given manifest already restored by an interrupted or ongoing
+	/// restoration. They don't need to be fed again.
+	fn completed_chunks(&self, manifest: ManifestData) -> Vec<H256>;
 }
 
 impl IpcConfig for SnapshotService { }
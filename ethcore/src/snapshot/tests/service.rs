@@ -103,6 +103,77 @@ fn restored_is_equivalent() {
 	}
 }
 
+#[test]
+fn resumes_interrupted_restoration() {
+	const NUM_BLOCKS: u32 = 400;
+
+	let client = generate_dummy_client_with_spec_and_data(Spec::new_null, NUM_BLOCKS, 5, &[1.into()]);
+
+	let path = RandomTempPath::create_dir();
+	let mut path = path.as_path().clone();
+	let mut client_db = path.clone();
+
+	client_db.push("client_db");
+	path.push("snapshot");
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Database::open(&db_config, client_db.to_str().unwrap()).unwrap();
+
+	let spec = Spec::new_null();
+	let client2 = Client::new(
+		Default::default(),
+		&spec,
+		Arc::new(client_db),
+		Arc::new(::miner::Miner::with_spec(&spec)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	let service_params = || ServiceParams {
+		engine: spec.engine.clone(),
+		genesis_block: spec.genesis_block(),
+		db_config: db_config.clone(),
+		pruning: ::util::journaldb::Algorithm::Archive,
+		channel: IoChannel::disconnected(),
+		snapshot_root: path.clone(),
+		db_restore: client2.clone(),
+	};
+
+	let service = Service::new(service_params()).unwrap();
+	service.take_snapshot(&client, NUM_BLOCKS as u64).unwrap();
+
+	let manifest = service.manifest().unwrap();
+	service.init_restore(manifest.clone(), true).unwrap();
+
+	for hash in &manifest.state_hashes {
+		let chunk = service.chunk(*hash).unwrap();
+		service.feed_state_chunk(*hash, &chunk);
+	}
+
+	let block_chunks: Vec<_> = manifest.block_hashes.iter().map(|h| (*h, service.chunk(*h).unwrap())).collect();
+
+	// interrupt the restoration after the state chunks.
+	drop(service);
+
+	let service = Service::new(service_params()).unwrap();
+	assert_eq!(service.completed_chunks(manifest.clone()), manifest.state_hashes);
+
+	service.init_restore(manifest.clone(), true).unwrap();
+	match service.status() {
+		::snapshot::RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
+			assert_eq!(state_chunks_done as usize, manifest.state_hashes.len());
+			assert_eq!(block_chunks_done, 0);
+		},
+		_ => panic!("restoration should be ongoing"),
+	}
+
+	for (hash, chunk) in block_chunks {
+		service.feed_block_chunk(hash, &chunk);
+	}
+
+	assert_eq!(service.status(), ::snapshot::RestorationStatus::Inactive);
+	assert_eq!(client2.chain_info().best_block_number, NUM_BLOCKS as u64);
+}
+
 #[test]
 fn guards_delete_folders() {
 	let spec = Spec::new_null();
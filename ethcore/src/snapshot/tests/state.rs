@@ -143,6 +143,58 @@ fn get_code_from_prev_chunk() {
 	rebuilder.check_missing().unwrap();
 }
 
+#[test]
+fn applies_chunks_rebuilt_ahead() {
+	use std::collections::HashSet;
+	use rlp::RlpStream;
+	use util::{DBValue, HashDB, H256, U256, Hashable};
+
+	use account_db::{AccountDBMut, AccountDB};
+
+	let code = b"this is definitely code";
+	let mut used_code = HashSet::new();
+	let mut acc_stream = RlpStream::new_list(4);
+	acc_stream.append(&U256::default())
+		.append(&U256::default())
+		.append(&SHA3_NULL_RLP)
+		.append(&code.sha3());
+
+	let (h1, h2) = (H256::random(), H256::random());
+	let thin_rlp = acc_stream.out();
+	let acc: BasicAccount = ::rlp::decode(&thin_rlp);
+
+	let mut make_chunk = |acc, hash| {
+		let mut db = MemoryDB::new();
+		AccountDBMut::from_hash(&mut db, hash).insert(&code[..]);
+
+		let fat_rlp = account::to_fat_rlp(&acc, &AccountDB::from_hash(&db, hash), &mut used_code).unwrap();
+
+		let mut stream = RlpStream::new_list(1);
+		stream.begin_list(2).append(&hash).append_raw(&fat_rlp, 1);
+		stream.out()
+	};
+
+	let chunk1 = make_chunk(acc.clone(), h1);
+	let chunk2 = make_chunk(acc, h2);
+
+	let db_path = RandomTempPath::create_dir();
+	let db_cfg = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let new_db = Arc::new(Database::open(&db_cfg, &db_path.to_string_lossy()).unwrap());
+
+	let mut rebuilder = StateRebuilder::new(new_db.clone(), Algorithm::Archive);
+	let flag = AtomicBool::new(true);
+
+	// the second chunk is rebuilt before the one carrying the code is applied.
+	let rebuilt1 = StateRebuilder::rebuild_chunk(&chunk1, &flag).unwrap();
+	let rebuilt2 = StateRebuilder::rebuild_chunk(&chunk2, &flag).unwrap();
+	rebuilder.apply(rebuilt1, &flag).unwrap();
+	rebuilder.apply(rebuilt2, &flag).unwrap();
+	rebuilder.check_missing().unwrap();
+
+	let state_db = journaldb::new(new_db, Algorithm::Archive, ::db::COL_STATE);
+	assert_eq!(AccountDB::from_hash(state_db.as_hashdb(), h2).get(&code.sha3()).unwrap(), DBValue::from_slice(code));
+}
+
 #[test]
 fn checks_flag() {
 	let mut producer = StateProducer::new();
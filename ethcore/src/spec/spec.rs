@@ -18,8 +18,10 @@
 
 use util::*;
 use builtin::Builtin;
-use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint};
+use engines::{Engine, NullEngine, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, Clique, ExternalSeal, ChainIdProtection};
 use factory::Factories;
+use evm::Schedule;
+use evm::schedule::{ScheduleBuilder, ScheduleOverride};
 use executive::Executive;
 use trace::{NoopTracer, NoopVMTracer};
 use action_params::{ActionValue, ActionParams};
@@ -43,6 +45,8 @@ pub struct CommonParams {
 	pub account_start_nonce: U256,
 	/// Maximum size of extra data.
 	pub maximum_extra_data_size: usize,
+	/// Prefix of the extra data of all blocks but genesis, counted towards its maximum size.
+	pub extra_data_prefix: Bytes,
 	/// Network id.
 	pub network_id: u64,
 	/// Chain id.
@@ -55,22 +59,111 @@ pub struct CommonParams {
 	pub fork_block: Option<(BlockNumber, H256)>,
 	/// Number of first block where EIP-98 rules begin.
 	pub eip98_transition: BlockNumber,
+	/// Number of first block where EIP-140 rules (`REVERT` instruction) begin.
+	pub eip140_transition: BlockNumber,
+	/// Number of first block where EIP-211 rules (return data buffer) begin.
+	pub eip211_transition: BlockNumber,
+	/// Number of first block where EIP-214 rules (`STATICCALL` instruction) begin.
+	pub eip214_transition: BlockNumber,
+	/// Number of first block where EIP-210 rules (parent hashes kept in a contract) begin.
+	pub eip210_transition: BlockNumber,
+	/// Address of the contract keeping parent hashes.
+	pub eip210_contract_address: Address,
+	/// Code of the contract keeping parent hashes, installed at the transition block.
+	pub eip210_contract_code: Bytes,
+	/// Gas available to the system call recording the parent hash.
+	pub eip210_contract_gas: U256,
 	/// Validate block receipts root.
 	pub validate_receipts: bool,
+	/// Chain ID replay protection; engines may override it.
+	pub replay_protection: ChainIdProtection,
+	/// Schedule changes activated at given blocks, applied after the engine's own.
+	pub schedule_overrides: ScheduleBuilder,
+}
+
+impl CommonParams {
+	/// Apply rules activated by the transitions to the schedule of given block.
+	pub fn update_schedule(&self, block_number: BlockNumber, schedule: &mut Schedule) {
+		schedule.have_revert = block_number >= self.eip140_transition;
+		schedule.have_return_data = block_number >= self.eip211_transition;
+		schedule.have_static_call = block_number >= self.eip214_transition;
+		self.schedule_overrides.apply(block_number, schedule);
+	}
 }
 
 impl From<ethjson::spec::Params> for CommonParams {
 	fn from(p: ethjson::spec::Params) -> Self {
+		let chain_id = if let Some(n) = p.chain_id { n.into() } else { p.network_id.into() };
 		CommonParams {
 			account_start_nonce: p.account_start_nonce.map_or_else(U256::zero, Into::into),
 			maximum_extra_data_size: p.maximum_extra_data_size.into(),
+			extra_data_prefix: p.extra_data_prefix.map_or_else(Bytes::new, Into::into),
 			network_id: p.network_id.into(),
-			chain_id: if let Some(n) = p.chain_id { n.into() } else { p.network_id.into() },
+			chain_id: chain_id,
 			subprotocol_name: p.subprotocol_name.unwrap_or_else(|| "eth".to_owned()),
 			min_gas_limit: p.min_gas_limit.into(),
 			fork_block: if let (Some(n), Some(h)) = (p.fork_block, p.fork_hash) { Some((n.into(), h.into())) } else { None },
 			eip98_transition: p.eip98_transition.map_or(0, Into::into),
+			eip140_transition: p.eip140_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip211_transition: p.eip211_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip214_transition: p.eip214_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip210_transition: p.eip210_transition.map_or(BlockNumber::max_value(), Into::into),
+			eip210_contract_address: p.eip210_contract_address.map_or(0xf0.into(), Into::into),
+			eip210_contract_code: p.eip210_contract_code.map_or_else(Bytes::new, Into::into),
+			eip210_contract_gas: p.eip210_contract_gas.map_or(1000000.into(), Into::into),
 			validate_receipts: p.validate_receipts.unwrap_or(true),
+			replay_protection: ChainIdProtection {
+				chain_id: chain_id,
+				transition: p.eip155_transition.map(Into::into),
+				required_transition: p.require_chain_id_transition.map(Into::into),
+			},
+			schedule_overrides: p.schedule_overrides.unwrap_or_default().into_iter()
+				.fold(ScheduleBuilder::new(), |builder, o| builder.transition(o.block.into(), o.into())),
+		}
+	}
+}
+
+impl From<ethjson::spec::ScheduleOverride> for ScheduleOverride {
+	fn from(o: ethjson::spec::ScheduleOverride) -> Self {
+		ScheduleOverride {
+			exceptional_failed_code_deposit: o.exceptional_failed_code_deposit,
+			have_delegate_call: o.have_delegate_call,
+			stack_limit: o.stack_limit.map(Into::into),
+			max_depth: o.max_depth.map(Into::into),
+			exp_gas: o.exp_gas.map(Into::into),
+			exp_byte_gas: o.exp_byte_gas.map(Into::into),
+			sha3_gas: o.sha3_gas.map(Into::into),
+			sha3_word_gas: o.sha3_word_gas.map(Into::into),
+			sload_gas: o.sload_gas.map(Into::into),
+			sstore_set_gas: o.sstore_set_gas.map(Into::into),
+			sstore_reset_gas: o.sstore_reset_gas.map(Into::into),
+			sstore_refund_gas: o.sstore_refund_gas.map(Into::into),
+			jumpdest_gas: o.jumpdest_gas.map(Into::into),
+			log_gas: o.log_gas.map(Into::into),
+			log_data_gas: o.log_data_gas.map(Into::into),
+			log_topic_gas: o.log_topic_gas.map(Into::into),
+			create_gas: o.create_gas.map(Into::into),
+			call_gas: o.call_gas.map(Into::into),
+			call_stipend: o.call_stipend.map(Into::into),
+			call_value_transfer_gas: o.call_value_transfer_gas.map(Into::into),
+			call_new_account_gas: o.call_new_account_gas.map(Into::into),
+			suicide_refund_gas: o.suicide_refund_gas.map(Into::into),
+			memory_gas: o.memory_gas.map(Into::into),
+			create_data_gas: o.create_data_gas.map(Into::into),
+			create_data_limit: o.create_data_limit.map(Into::into),
+			tx_gas: o.tx_gas.map(Into::into),
+			tx_create_gas: o.tx_create_gas.map(Into::into),
+			tx_data_zero_gas: o.tx_data_zero_gas.map(Into::into),
+			tx_data_non_zero_gas: o.tx_data_non_zero_gas.map(Into::into),
+			copy_gas: o.copy_gas.map(Into::into),
+			extcodesize_gas: o.extcodesize_gas.map(Into::into),
+			extcodecopy_base_gas: o.extcodecopy_base_gas.map(Into::into),
+			balance_gas: o.balance_gas.map(Into::into),
+			suicide_gas: o.suicide_gas.map(Into::into),
+			suicide_to_new_account_cost: o.suicide_to_new_account_cost.map(Into::into),
+			sub_gas_cap_divisor: o.sub_gas_cap_divisor.map(Into::into),
+			no_empty: o.no_empty,
+			kill_empty: o.kill_empty,
 		}
 	}
 }
@@ -80,6 +173,8 @@ impl From<ethjson::spec::Params> for CommonParams {
 pub struct Spec {
 	/// User friendly spec name
 	pub name: String,
+	/// Hash of the JSON the spec was loaded from.
+	pub hash: H256,
 	/// What engine are we using for this?
 	pub engine: Arc<Engine>,
 	/// Name of the subdir inside the main data dir to use for chain data and settings.
@@ -122,14 +217,15 @@ pub struct Spec {
 	genesis_state: PodState,
 }
 
-impl From<ethjson::spec::Spec> for Spec {
-	fn from(s: ethjson::spec::Spec) -> Self {
+impl Spec {
+	fn from_json(s: ethjson::spec::Spec, hash: H256) -> Self {
 		let builtins = s.accounts.builtins().into_iter().map(|p| (p.0.into(), From::from(p.1))).collect();
 		let g = Genesis::from(s.genesis);
 		let GenericSeal(seal_rlp) = g.seal.into();
 		let params = CommonParams::from(s.params);
 		Spec {
 			name: s.name.clone().into(),
+			hash: hash,
 			params: params.clone(),
 			engine: Spec::engine(s.engine, params, builtins),
 			data_dir: s.data_dir.unwrap_or(s.name).into(),
@@ -168,13 +264,16 @@ impl Spec {
 			ethjson::spec::Engine::BasicAuthority(basic_authority) => Arc::new(BasicAuthority::new(params, From::from(basic_authority.params), builtins)),
 			ethjson::spec::Engine::AuthorityRound(authority_round) => AuthorityRound::new(params, From::from(authority_round.params), builtins).expect("Failed to start AuthorityRound consensus engine."),
 			ethjson::spec::Engine::Tendermint(tendermint) => Tendermint::new(params, From::from(tendermint.params), builtins).expect("Failed to start the Tendermint consensus engine."),
+			ethjson::spec::Engine::Clique(clique) => Arc::new(Clique::new(params, From::from(clique.params), builtins)),
+			ethjson::spec::Engine::ExternalSeal(external) => Arc::new(ExternalSeal::new(params, From::from(external.params), builtins)),
 		}
 	}
 
 	/// Return the state root for the genesis state, memoising accordingly.
 	pub fn state_root(&self) -> H256 {
 		if self.state_root_memo.read().is_none() {
-			*self.state_root_memo.write() = Some(self.genesis_state.root());
+			let root = self.genesis_state_root();
+			*self.state_root_memo.write() = Some(root);
 		}
 		self.state_root_memo.read().as_ref().cloned()
 			.expect("state root memo ensured to be set at this point; qed")
@@ -251,15 +350,44 @@ impl Spec {
 
 	/// Returns `false` if the memoized state root is invalid. `true` otherwise.
 	pub fn is_state_root_valid(&self) -> bool {
-		self.state_root_memo.read().clone().map_or(true, |sr| sr == self.genesis_state.root())
+		self.state_root_memo.read().clone().map_or(true, |sr| sr == self.genesis_state_root())
+	}
+
+	/// Compute the genesis state root: the accounts given in the spec with all
+	/// contract constructors executed on top of them.
+	fn genesis_state_root(&self) -> H256 {
+		if self.constructors.is_empty() {
+			return self.genesis_state.root();
+		}
+
+		let db = Arc::new(kvdb::in_memory(::db::NUM_COLUMNS.unwrap_or(0)));
+		let state_db = StateDB::new(journaldb::new(db, journaldb::Algorithm::Archive, ::db::COL_STATE), 0);
+		match self.run_constructors(state_db, &Default::default()) {
+			Ok((root, _)) => root,
+			Err(e) => {
+				warn!(target: "spec", "Unable to compute genesis state root: {}.", e);
+				self.genesis_state.root()
+			}
+		}
 	}
 
 	/// Ensure that the given state DB has the trie nodes in for the genesis state.
-	pub fn ensure_db_good(&self, mut db: StateDB, factories: &Factories) -> Result<StateDB, Box<TrieError>> {
+	pub fn ensure_db_good(&self, db: StateDB, factories: &Factories) -> Result<StateDB, Box<TrieError>> {
 		if db.as_hashdb().contains(&self.state_root()) {
 			return Ok(db)
 		}
 		trace!(target: "spec", "ensure_db_good: Fresh database? Cannot find state root {}", self.state_root());
+		let (root, db) = self.run_constructors(db, factories)?;
+		if root != self.state_root() {
+			warn!(target: "spec", "Genesis state root {} doesn't match the one given in the spec ({}).", root, self.state_root());
+			*self.state_root_memo.write() = Some(root);
+		}
+		Ok(db)
+	}
+
+	/// Populate the given state DB with the genesis accounts and execute contract
+	/// constructors. Returns the resulting state root.
+	fn run_constructors(&self, mut db: StateDB, factories: &Factories) -> Result<(H256, StateDB), Box<TrieError>> {
 		let mut root = H256::new();
 
 		{
@@ -269,7 +397,7 @@ impl Spec {
 			}
 		}
 
-		trace!(target: "spec", "ensure_db_good: Populated sec trie; root is {}", root);
+		trace!(target: "spec", "run_constructors: Populated sec trie; root is {}", root);
 		for (address, account) in self.genesis_state.get().iter() {
 			db.note_non_null_account(address);
 			account.insert_additional(&mut AccountDBMut::new(db.as_hashdb_mut(), address), &factories.trie);
@@ -291,7 +419,7 @@ impl Spec {
 		let mut state = State::from_existing(db, root, start_nonce, factories.clone())?;
 		// Mutate the state with each constructor.
 		for &(ref address, ref constructor) in self.constructors.iter() {
-			trace!(target: "spec", "ensure_db_good: Creating a contract at {}.", address);
+			trace!(target: "spec", "run_constructors: Creating a contract at {}.", address);
 			let params = ActionParams {
 				code_address: address.clone(),
 				code_hash: constructor.sha3(),
@@ -316,16 +444,15 @@ impl Spec {
 				warn!(target: "spec", "Genesis constructor trie commit at {} failed: {}.", address, e);
 			}
 		}
-		let (root, db) = state.drop();
-
-		*self.state_root_memo.write() = Some(root);
-		Ok(db)
+		Ok(state.drop())
 	}
 
 	/// Loads spec from json file.
-	pub fn load<R>(reader: R) -> Result<Self, String> where R: Read {
-		match ethjson::spec::Spec::load(reader) {
-			Ok(spec) => Ok(spec.into()),
+	pub fn load<R>(mut reader: R) -> Result<Self, String> where R: Read {
+		let mut json = Vec::new();
+		reader.read_to_end(&mut json).map_err(|e| format!("Unable to read spec json: {}", e))?;
+		match ethjson::spec::Spec::load(&json[..]) {
+			Ok(spec) => Ok(Spec::from_json(spec, json.sha3())),
 			Err(e) => Err(format!("Spec json is invalid: {}", e)),
 		}
 	}
@@ -350,6 +477,14 @@ impl Spec {
 	/// Account "0".sha3() and "1".sha3() are a authorities.
 	pub fn new_test_tendermint() -> Self { load_bundled!("tendermint") }
 
+	/// Create a new Spec with Clique consensus which does internal sealing (not requiring work).
+	/// Accounts with secrets "0".sha3() and "1".sha3() are the initial signers.
+	pub fn new_test_clique() -> Self { load_bundled!("clique") }
+
+	/// Create a new Spec with ExternalSeal consensus which accepts blocks signed by an external producer.
+	/// Account with secret "0".sha3() is the producer.
+	pub fn new_test_external_seal() -> Self { load_bundled!("external_seal") }
+
 	/// TestList.sol used in both specs: https://github.com/ethcore/contracts/pull/30/files
 	/// Accounts with secrets "0".sha3() and "1".sha3() are initially the validators.
 	/// Create a new Spec with BasicAuthority which uses a contract at address 5 to determine the current validators using `getValidators`.
@@ -372,6 +507,7 @@ mod tests {
 	use views::*;
 	use tests::helpers::get_temp_state_db;
 	use state::State;
+	use types::chain_spec_info::ChainSpecInfo;
 	use super::*;
 
 	// https://github.com/ethcore/parity/issues/1840
@@ -380,6 +516,44 @@ mod tests {
 		assert!(Spec::load(&[] as &[u8]).is_err());
 	}
 
+	#[test]
+	fn spec_hash_is_hash_of_json() {
+		let json: &[u8] = include_bytes!("../../res/null_morden.json");
+		let spec = Spec::load(json).unwrap();
+
+		assert_eq!(spec.hash, json.sha3());
+		assert!(Spec::new_null().hash != spec.hash);
+	}
+
+	#[test]
+	fn chain_spec_info_lists_evm_transitions_and_schedule_overrides() {
+		let json = include_str!("../../res/null_morden.json").replace(
+			r#""networkID" : "0x2""#,
+			r#""networkID" : "0x2",
+			"eip140Transition": "0x10",
+			"eip211Transition": "0x11",
+			"eip214Transition": "0x12",
+			"eip210Transition": "0x13",
+			"scheduleOverrides": [
+				{ "block": "0x20", "sloadGas": "0x320" },
+				{ "block": "0x20", "haveDelegateCall": false },
+				{ "block": "0x30", "sloadGas": "0x3e8" }
+			]"#
+		);
+		let spec = Spec::load(json.as_bytes()).unwrap();
+
+		let info = ChainSpecInfo::new(spec.name.clone(), &*spec.engine);
+
+		assert_eq!(info.transitions["eip140"], 0x10);
+		assert_eq!(info.transitions["eip211"], 0x11);
+		assert_eq!(info.transitions["eip214"], 0x12);
+		assert_eq!(info.transitions["eip210"], 0x13);
+		assert_eq!(info.schedule_overrides, map![
+			0x20 => map!["sload_gas".to_owned() => "800".to_owned(), "have_delegate_call".to_owned() => "false".to_owned()],
+			0x30 => map!["sload_gas".to_owned() => "1000".to_owned()]
+		]);
+	}
+
 	#[test]
 	fn test_chain() {
 		let test_spec = Spec::new_test();
@@ -392,8 +566,11 @@ mod tests {
 	#[test]
 	fn genesis_constructor() {
 		let spec = Spec::new_test_constructor();
+		let genesis_root = spec.state_root();
 		let mut db_result = get_temp_state_db();
 		let db = spec.ensure_db_good(db_result.take(), &Default::default()).unwrap();
+		assert_eq!(spec.state_root(), genesis_root);
+		assert!(genesis_root != Spec::new_test_constructor().genesis_state.root());
 		let state = State::from_existing(db.boxed_clone(), spec.state_root(), spec.engine.account_start_nonce(), Default::default()).unwrap();
 		let expected = H256::from_str("0000000000000000000000000000000000000000000000000000000000000001").unwrap();
 		assert_eq!(state.storage_at(&Address::from_str("0000000000000000000000000000000000000005").unwrap(), &H256::zero()).unwrap(), expected);
@@ -28,6 +28,7 @@ use env_info::EnvInfo;
 use error::Error;
 use executive::{Executive, TransactOptions};
 use factory::Factories;
+use evm::Factory as EvmFactory;
 use trace::FlatTrace;
 use pod_account::*;
 use pod_state::{self, PodState};
@@ -620,6 +621,11 @@ impl<B: Backend> State<B> {
 		Executive::new(self, env_info, engine, &vm_factory).transact(t, options)
 	}
 
+	/// Factory for VMs executing code on top of this state.
+	pub fn vm_factory(&self) -> EvmFactory {
+		self.factories.vm.clone()
+	}
+
 
 	/// Commit accounts to SecTrieDBMut. This is similar to cpp-ethereum's dev::eth::commit.
 	/// `accounts` is mutable because we may need to commit the code or storage and record that.
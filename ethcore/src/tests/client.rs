@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::{Duration, Instant};
 use io::IoChannel;
 use client::{BlockChainClient, MiningBlockChainClient, Client, ClientConfig, BlockId};
 use state::{self, State, CleanupMode};
@@ -49,6 +50,60 @@ fn imports_from_empty() {
 	client.flush_queue();
 }
 
+#[test]
+fn read_only_client_serves_queries_without_importing() {
+	use error::{Error, ImportError, TransactionError};
+	use types::block_import_error::BlockImportError;
+
+	let dir = RandomTempPath::new();
+	let path = dir.as_path().to_str().unwrap();
+	let spec = get_test_spec();
+	let mut db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let read_only_config = ClientConfig { read_only: true, ..Default::default() };
+
+	db_config.read_only = true;
+	assert!(Database::open(&db_config, path).is_err());
+
+	db_config.read_only = false;
+	let client_db = Arc::new(Database::open(&db_config, path).unwrap());
+	let writer = Client::new(ClientConfig::default(), &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	db_config.read_only = true;
+	let client_db = Arc::new(Database::open(&db_config, path).unwrap());
+	let client = Client::new(read_only_config, &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	assert!(client.is_read_only());
+	assert_eq!(client.chain_info().best_block_hash, writer.chain_info().best_block_hash);
+	match client.import_block(get_good_dummy_block()) {
+		Err(BlockImportError::Import(ImportError::ReadOnly)) => {},
+		other => panic!("Unexpected import result: {:?}", other),
+	}
+
+	let key = KeyPair::from_secret(Secret::from_slice(&"test".sha3()).unwrap()).unwrap();
+	let tx = PendingTransaction::new(Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 21000.into(),
+		action: Action::Call(Address::default()),
+		value: 0.into(),
+		data: Vec::new(),
+	}.sign(key.secret(), None), None);
+	match client.miner().import_own_transaction(&*client, tx) {
+		Err(Error::Transaction(TransactionError::ReadOnly)) => {},
+		other => panic!("Unexpected import result: {:?}", other),
+	}
+
+	writer.import_block(get_good_dummy_block()).unwrap();
+	writer.flush_queue();
+	writer.import_verified_blocks();
+	let marker = writer.db_flush_marker();
+	assert_eq!((marker.sequence, marker.best_block_number), (1, 1));
+
+	assert!(client.block_header(BlockId::Number(1)).is_none());
+	client.reopen_db().unwrap();
+	assert_eq!(client.chain_info().best_block_hash, marker.best_block_hash);
+}
+
 #[test]
 fn should_return_registrar() {
 	let dir = RandomTempPath::new();
@@ -80,6 +135,48 @@ fn returns_state_root_basic() {
 	assert!(client.state_data(genesis_header.state_root()).is_some());
 }
 
+#[test]
+fn drains_queued_blocks_but_refuses_new_ones_on_shutdown() {
+	use error::ImportError;
+	use types::block_import_error::BlockImportError;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let client = Client::new(ClientConfig::default(), &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	client.import_block(get_good_dummy_block()).unwrap();
+	client.begin_shutdown();
+	assert!(client.is_read_only());
+	match client.import_block(get_good_dummy_block()) {
+		Err(BlockImportError::Import(ImportError::ReadOnly)) => {},
+		other => panic!("Unexpected import result: {:?}", other),
+	}
+
+	assert!(client.flush_queue_until(Instant::now() + Duration::from_secs(60)));
+	client.flush_db();
+	assert!(client.queue_info().is_empty());
+	assert_eq!(client.chain_info().best_block_number, 1);
+	assert_eq!(client.db_flush_marker().best_block_number, 1);
+}
+
+#[test]
+fn drops_queued_blocks_past_the_deadline() {
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let client = Client::new(ClientConfig::default(), &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	client.import_block(get_good_dummy_block()).unwrap();
+	client.begin_shutdown();
+
+	assert!(!client.flush_queue_until(Instant::now()));
+	assert!(client.queue_info().is_empty());
+	assert_eq!(client.chain_info().best_block_number, 0);
+}
+
 #[test]
 fn imports_good_block() {
 	let dir = RandomTempPath::new();
@@ -132,6 +229,73 @@ fn query_bad_block() {
 	assert!(bad_block.is_none());
 }
 
+#[test]
+fn remembers_bad_blocks_across_restarts() {
+	use client::BlockStatus;
+	use error::ImportError;
+	use types::block_import_error::BlockImportError;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let bad_block = get_bad_state_dummy_block();
+	let hash = BlockView::new(&bad_block).header().hash();
+
+	{
+		let client = Client::new(ClientConfig::default(), &spec, client_db.clone(), Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+		client.import_block(bad_block.clone()).unwrap();
+		client.flush_queue();
+		client.import_verified_blocks();
+		assert_eq!(client.block_status(BlockId::Hash(hash)), BlockStatus::Bad);
+	}
+
+	let client = Client::new(ClientConfig::default(), &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+	assert_eq!(client.block_status(BlockId::Hash(hash)), BlockStatus::Bad);
+	match client.import_block(bad_block.clone()) {
+		Err(BlockImportError::Import(ImportError::KnownBad)) => {},
+		other => panic!("Unexpected import result: {:?}", other),
+	}
+
+	client.clear_bad_blocks();
+	assert_eq!(client.block_status(BlockId::Hash(hash)), BlockStatus::Unknown);
+	assert!(client.import_block(bad_block).is_ok());
+}
+
+#[test]
+fn reports_buffered_future_blocks_as_queued() {
+	use std::time::{SystemTime, UNIX_EPOCH};
+	use client::BlockStatus;
+	use error::ImportError;
+	use header::Header;
+	use types::block_import_error::BlockImportError;
+	use verification::MAX_TIMESTAMP_DRIFT;
+
+	let dir = RandomTempPath::new();
+	let spec = get_test_spec();
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_db = Arc::new(Database::open(&db_config, dir.as_path().to_str().unwrap()).unwrap());
+	let config = ClientConfig { future_block_gap: 60, ..Default::default() };
+	let client = Client::new(config, &spec, client_db, Arc::new(Miner::with_spec(&spec)), IoChannel::disconnected()).unwrap();
+
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+	let mut header = Header::new();
+	header.set_gas_limit(spec.engine.params().min_gas_limit);
+	header.set_difficulty(U256::from(0x20000));
+	header.set_timestamp(now + MAX_TIMESTAMP_DRIFT + 30);
+	header.set_number(1);
+	header.set_parent_hash(spec.genesis_header().hash());
+	header.set_state_root(spec.genesis_header().state_root().clone());
+	let block = create_test_block(&header);
+
+	assert_eq!(client.import_block(block.clone()).unwrap(), header.hash());
+	assert_eq!(client.block_status(BlockId::Hash(header.hash())), BlockStatus::Queued);
+	match client.import_block(block) {
+		Err(BlockImportError::Import(ImportError::AlreadyQueued)) => {},
+		other => panic!("Unexpected import result: {:?}", other),
+	}
+}
+
 #[test]
 fn returns_chain_info() {
 	let dummy_block = get_good_dummy_block();
@@ -367,7 +531,7 @@ fn transaction_proof() {
 		data: Vec::new(),
 	}.fake_sign(address);
 
-	let proof = client.prove_transaction(transaction.clone(), BlockId::Latest).unwrap();
+	let (output, proof) = client.prove_transaction(transaction.clone(), BlockId::Latest).unwrap();
 	let backend = state::backend::ProofCheck::new(&proof);
 
 	let mut factories = ::factory::Factories::default();
@@ -375,9 +539,99 @@ fn transaction_proof() {
 	let root = client.best_block_header().state_root();
 
 	let mut state = State::from_existing(backend, root, 0.into(), factories.clone()).unwrap();
-	Executive::new(&mut state, &client.latest_env_info(), &*test_spec.engine, &factories.vm)
+	let executed = Executive::new(&mut state, &client.latest_env_info(), &*test_spec.engine, &factories.vm)
 		.transact(&transaction, Default::default()).unwrap();
 
+	assert_eq!(executed.output, output);
 	assert_eq!(state.balance(&Address::default()).unwrap(), 5.into());
 	assert_eq!(state.balance(&address).unwrap(), 95.into());
 }
+
+#[test]
+fn call_overrides_gas_limit() {
+	use env_info::EnvOverrides;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let test_spec = Spec::new_test();
+	let contract = Address::from(0x10);
+
+	// GASLIMIT PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+	b.block_mut().fields_mut().state.init_code(&contract, vec![0x45, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]).unwrap();
+	b.block_mut().fields_mut().state.commit().unwrap();
+	let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+	client.import_sealed_block(b).unwrap();
+
+	let transaction = Transaction {
+		nonce: 0.into(),
+		gas_price: 0.into(),
+		gas: 100_000.into(),
+		action: Action::Call(contract),
+		value: 0.into(),
+		data: Vec::new(),
+	}.fake_sign(Address::default());
+
+	let executed = client.call(&transaction, BlockId::Latest, Default::default()).unwrap();
+	assert_eq!(U256::from(&*executed.output), U256::max_value());
+
+	let env = EnvOverrides { gas_limit: Some(1_000_000.into()), ..Default::default() };
+	let executed = client.call_with_env(&transaction, BlockId::Latest, Default::default(), &env).unwrap();
+	assert_eq!(U256::from(&*executed.output), 1_000_000.into());
+}
+
+#[test]
+fn state_diff_between_blocks() {
+	use types::account_diff::Diff;
+
+	let client_result = generate_dummy_client(0);
+	let client = client_result.reference();
+	let address = Address::random();
+	let untouched = Address::random();
+	let test_spec = Spec::new_test();
+	for _ in 0..3 {
+		let mut b = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		b.block_mut().fields_mut().state.add_balance(&address, &5.into(), CleanupMode::NoEmpty).unwrap();
+		b.block_mut().fields_mut().state.commit().unwrap();
+		let b = b.close_and_lock().seal(&*test_spec.engine, vec![]).unwrap();
+		client.import_sealed_block(b).unwrap();
+	}
+
+	let accounts = [address, untouched];
+	let diff = client.state_diff(&accounts, BlockId::Number(0), BlockId::Number(1)).unwrap();
+	assert_eq!(diff.len(), 1);
+	assert_eq!(diff[&address].balance, Diff::Born(5.into()));
+
+	let diff = client.state_diff(&accounts, BlockId::Number(1), BlockId::Latest).unwrap();
+	assert_eq!(diff.len(), 1);
+	assert_eq!(diff[&address].balance, Diff::Changed(5.into(), 15.into()));
+	assert!(diff[&address].nonce.is_same());
+
+	assert!(client.state_diff(&accounts, BlockId::Latest, BlockId::Latest).unwrap().is_empty());
+	assert!(client.state_diff(&accounts, BlockId::Number(0), BlockId::Number(10)).is_none());
+}
+
+#[test]
+fn replays_bundle_only_with_the_same_chain_spec() {
+	use client::replay::ReplayBundle;
+
+	let client_result = generate_dummy_client(1);
+	let client = client_result.reference();
+	let genesis = client.block_header(BlockId::Number(0)).unwrap().decode();
+	let mut bundle = ReplayBundle {
+		version: "Parity/v1.7.0".into(),
+		genesis_hash: genesis.hash(),
+		spec_hash: Spec::new_test().hash,
+		parent_hash: genesis.hash(),
+		parent_state_root: genesis.state_root().clone(),
+		block: client.block(BlockId::Number(1)).unwrap().into_inner(),
+		error: "Block(InvalidStateRoot)".into(),
+	};
+
+	let report = client.replay_bundle(&bundle).unwrap();
+	assert!(report.produced.is_ok());
+	assert!(report.mismatches().is_empty());
+
+	bundle.spec_hash = Spec::new_null().hash;
+	assert!(client.replay_bundle(&bundle).unwrap_err().contains("chain spec"));
+}
@@ -49,6 +49,7 @@ pub enum ChainEra {
 pub struct TestEngine {
 	engine: Arc<Engine>,
 	max_depth: usize,
+	metropolis: bool,
 }
 
 impl TestEngine {
@@ -56,6 +57,15 @@ impl TestEngine {
 		TestEngine {
 			engine: ethereum::new_frontier_test().engine,
 			max_depth: max_depth,
+			metropolis: false,
+		}
+	}
+
+	/// Frontier rules with `REVERT`, `RETURNDATA*` and `STATICCALL` enabled.
+	pub fn new_metropolis(max_depth: usize) -> TestEngine {
+		TestEngine {
+			metropolis: true,
+			..TestEngine::new(max_depth)
 		}
 	}
 }
@@ -76,6 +86,9 @@ impl Engine for TestEngine {
 	fn schedule(&self, _env_info: &EnvInfo) -> Schedule {
 		let mut schedule = Schedule::new_frontier();
 		schedule.max_depth = self.max_depth;
+		schedule.have_revert = self.metropolis;
+		schedule.have_return_data = self.metropolis;
+		schedule.have_static_call = self.metropolis;
 		schedule
 	}
 }
@@ -443,8 +456,7 @@ pub fn get_default_ethash_params() -> EthashParams{
 		registrar: "0000000000000000000000000000000000000001".into(),
 		homestead_transition: 1150000,
 		dao_hardfork_transition: u64::max_value(),
-		dao_hardfork_beneficiary: "0000000000000000000000000000000000000001".into(),
-		dao_hardfork_accounts: vec![],
+		irregular_state_changes: BTreeMap::new(),
 		difficulty_hardfork_transition: u64::max_value(),
 		difficulty_hardfork_bound_divisor: U256::from(0),
 		bomb_defuse_transition: u64::max_value(),
@@ -179,6 +179,36 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		});
 	}
 
+	/// Whether traces of the block with given hash are stored.
+	pub fn has_block_traces(&self, block_hash: &H256) -> bool {
+		self.traces.read().contains_key(block_hash) ||
+			self.tracesdb.get(db::COL_TRACE, block_hash).map_or(false, |traces| traces.is_some())
+	}
+
+	/// Removes traces of the canonical blocks `from..=to` with given hashes and their blooms,
+	/// e.g. when the chain head is rolled back.
+	pub fn remove_traces(&self, batch: &mut DBTransaction, from: BlockNumber, to: BlockNumber, hashes: &[H256]) {
+		{
+			let mut traces = self.traces.write();
+			for hash in hashes {
+				traces.remove(hash);
+				batch.delete(db::COL_TRACE, &Key::<FlatBlockTraces>::key(hash));
+			}
+		}
+
+		if from > to {
+			return;
+		}
+
+		let chain = BloomGroupChain::new(self.bloom_config, self);
+		let blooms_to_insert = chain.replace(&(from as Number..to as Number), vec![]).into_iter()
+			.map(|p| (From::from(p.0), From::from(p.1)))
+			.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
+
+		let mut blooms = self.blooms.write();
+		batch.extend_with_cache(db::COL_TRACE, &mut *blooms, blooms_to_insert, CacheUpdatePolicy::Remove);
+	}
+
 	/// Returns traces for block with hash.
 	fn traces(&self, block_hash: &H256) -> Option<FlatBlockTraces> {
 		let result = self.tracesdb.read_with_cache(db::COL_TRACE, &self.traces, block_hash);
@@ -655,6 +685,15 @@ mod tests {
 
 		assert_eq!(tracedb.trace(1, 0, vec![]).unwrap(), create_simple_localized_trace(1, block_1.clone(), tx_1.clone()));
 		assert_eq!(tracedb.trace(2, 0, vec![]).unwrap(), create_simple_localized_trace(2, block_2.clone(), tx_2.clone()));
+
+		// roll back block 2
+		let mut batch = DBTransaction::new();
+		tracedb.remove_traces(&mut batch, 2, 2, &[block_2.clone()]);
+		db.write(batch).unwrap();
+
+		assert!(!tracedb.has_block_traces(&block_2));
+		let traces = tracedb.filter(&filter);
+		assert_eq!(traces, vec![create_simple_localized_trace(1, block_1.clone(), tx_1.clone())]);
 	}
 
 	#[test]
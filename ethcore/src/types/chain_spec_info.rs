@@ -0,0 +1,94 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Active chain spec parameters.
+
+use std::collections::BTreeMap;
+use util::{U256, H256, Address};
+use header::BlockNumber;
+use engines::Engine;
+
+/// Normalized parameters of the chain spec the client enforces.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct ChainSpecInfo {
+	/// Spec name.
+	pub name: String,
+	/// Name of the consensus engine.
+	pub engine: String,
+	/// Network id.
+	pub network_id: u64,
+	/// Chain id.
+	pub chain_id: u64,
+	/// Main subprotocol name.
+	pub subprotocol_name: String,
+	/// Account start nonce.
+	pub account_start_nonce: U256,
+	/// Maximum size of extra data.
+	pub maximum_extra_data_size: u64,
+	/// Minimum gas limit.
+	pub min_gas_limit: U256,
+	/// Fork block to check.
+	pub fork_block: Option<BlockNumber>,
+	/// Hash of the fork block to check.
+	pub fork_hash: Option<H256>,
+	/// Whether block receipts root is validated.
+	pub validate_receipts: bool,
+	/// Rule transitions mapped to the first block they are enforced at.
+	pub transitions: BTreeMap<String, BlockNumber>,
+	/// Schedule changes of the spec mapped to the block they activate at.
+	pub schedule_overrides: BTreeMap<BlockNumber, BTreeMap<String, String>>,
+	/// Builtin contracts mapped to their activation block.
+	pub builtins: BTreeMap<Address, BlockNumber>,
+	/// Additional engine parameters (e.g. validator set definition).
+	pub engine_params: BTreeMap<String, String>,
+}
+
+impl ChainSpecInfo {
+	/// Collect the parameters enforced by given engine.
+	pub fn new(name: String, engine: &Engine) -> Self {
+		let params = engine.params();
+		let mut transitions = engine.transitions();
+		transitions.insert("eip98".into(), params.eip98_transition);
+		transitions.insert("eip140".into(), params.eip140_transition);
+		transitions.insert("eip211".into(), params.eip211_transition);
+		transitions.insert("eip214".into(), params.eip214_transition);
+		transitions.insert("eip210".into(), params.eip210_transition);
+
+		let mut schedule_overrides = BTreeMap::new();
+		for &(block, ref changes) in params.schedule_overrides.transitions() {
+			schedule_overrides.entry(block).or_insert_with(BTreeMap::new).extend(changes.changes());
+		}
+
+		ChainSpecInfo {
+			name: name,
+			engine: engine.name().into(),
+			network_id: params.network_id,
+			chain_id: params.chain_id,
+			subprotocol_name: params.subprotocol_name.clone(),
+			account_start_nonce: params.account_start_nonce,
+			maximum_extra_data_size: params.maximum_extra_data_size as u64,
+			min_gas_limit: params.min_gas_limit,
+			fork_block: params.fork_block.map(|(number, _)| number),
+			fork_hash: params.fork_block.map(|(_, hash)| hash),
+			validate_receipts: params.validate_receipts,
+			transitions: transitions,
+			schedule_overrides: schedule_overrides,
+			builtins: engine.builtins().iter().map(|(address, builtin)| (*address, builtin.activate_at())).collect(),
+			engine_params: engine.additional_params().into_iter().collect(),
+		}
+	}
+}
@@ -0,0 +1,31 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database compaction status.
+
+/// Progress of the latest database compaction.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct CompactionStatus {
+	/// Whether a compaction is running.
+	pub in_progress: bool,
+	/// Number of columns compacted so far by the current or last compaction.
+	pub columns_compacted: u32,
+	/// Number of columns to compact.
+	pub columns_total: u32,
+	/// UNIX timestamp of the end of the last finished compaction, if any.
+	pub last_finished: Option<u64>,
+}
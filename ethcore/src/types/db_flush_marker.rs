@@ -0,0 +1,33 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database flush marker.
+
+use util::H256;
+use header::BlockNumber;
+
+/// Identifies the latest state of the client database written to disk.
+/// Processes sharing the database read-only reopen it when the marker changes.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct DbFlushMarker {
+	/// Number of database flushes since the client was started.
+	pub sequence: u64,
+	/// Best block number at the time of the flush.
+	pub best_block_number: BlockNumber,
+	/// Best block hash at the time of the flush.
+	pub best_block_hash: H256,
+}
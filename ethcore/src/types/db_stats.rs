@@ -0,0 +1,39 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database column statistics.
+
+/// Estimated statistics of a client database column.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct DbColumnStats {
+	/// Name of the column, e.g. `state` or `headers`.
+	pub name: String,
+	/// Total size of the column's table files on disk, in bytes.
+	pub size_on_disk: u64,
+	/// Estimated number of keys.
+	pub estimated_keys: u64,
+	/// Memory used by the column's block cache, in bytes.
+	pub cache_usage: u64,
+	/// Capacity of the column's block cache, in bytes.
+	pub cache_capacity: u64,
+	/// Estimated number of bytes compaction needs to rewrite.
+	pub pending_compaction_bytes: u64,
+	/// Lookups answered by the client's in-memory cache of the column.
+	pub cache_hits: u64,
+	/// Lookups of the client's in-memory cache of the column which had to read the database.
+	pub cache_misses: u64,
+}
@@ -28,8 +28,10 @@ use header::{BlockNumber, Header as FullHeader};
 use transaction::UnverifiedTransaction;
 use views;
 
+use std::cmp::Ordering;
+
 use util::{Address, Hashable, H256, H2048, U256, HeapSizeOf};
-use rlp::Rlp;
+use rlp::{Rlp, UntrustedRlp, DecoderError};
 
 /// Owning header view.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -59,6 +61,54 @@ impl Header {
 
 	/// Consume the view and return the raw bytes.
 	pub fn into_inner(self) -> Vec<u8> { self.0 }
+
+	/// Check that untrusted RLP has the structure of a header without decoding it:
+	/// at least the 13 header fields, each of valid size for its type.
+	/// Seal fields aren't checked.
+	pub fn check_rlp(rlp: &UntrustedRlp) -> Result<(), DecoderError> {
+		if !rlp.is_list() {
+			return Err(DecoderError::RlpExpectedToBeList);
+		}
+		if rlp.item_count()? < 13 {
+			return Err(DecoderError::RlpIncorrectListLen);
+		}
+
+		for i in 0..13 {
+			let item = rlp.at(i)?;
+			match i {
+				2 => check_fixed_size(&item, 20)?,
+				6 => check_fixed_size(&item, 256)?,
+				0...5 => check_fixed_size(&item, 32)?,
+				8 => check_scalar(&item, 8)?,
+				7...11 => check_scalar(&item, 32)?,
+				_ => item.decoder().decode_value(|_| Ok(()))?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+// check a hash-like field of exactly `size` bytes.
+fn check_fixed_size(item: &UntrustedRlp, size: usize) -> Result<(), DecoderError> {
+	item.decoder().decode_value(|bytes| match bytes.len().cmp(&size) {
+		Ordering::Less => Err(DecoderError::RlpIsTooShort),
+		Ordering::Greater => Err(DecoderError::RlpIsTooBig),
+		Ordering::Equal => Ok(()),
+	})
+}
+
+// check a canonically encoded integer of at most `max_size` bytes.
+fn check_scalar(item: &UntrustedRlp, max_size: usize) -> Result<(), DecoderError> {
+	item.decoder().decode_value(|bytes| {
+		if !bytes.is_empty() && bytes[0] == 0 {
+			Err(DecoderError::RlpInvalidIndirection)
+		} else if bytes.len() > max_size {
+			Err(DecoderError::RlpIsTooBig)
+		} else {
+			Ok(())
+		}
+	})
 }
 
 // forwarders to borrowed view.
@@ -283,3 +333,38 @@ impl Block {
 	/// Hash of each uncle.
 	pub fn uncle_hashes(&self) -> Vec<H256> { self.view().uncle_hashes() }
 }
+
+#[cfg(test)]
+mod tests {
+	use header::Header as FullHeader;
+	use rlp::{self, RlpStream, UntrustedRlp, DecoderError};
+	use super::Header;
+
+	#[test]
+	fn check_header_rlp() {
+		let mut header = FullHeader::default();
+		header.set_number(1_000_000);
+		header.set_extra_data(b"extra".to_vec());
+		header.set_seal(vec![rlp::encode(&5u64).to_vec()]);
+		let encoded = rlp::encode(&header);
+		assert_eq!(Header::check_rlp(&UntrustedRlp::new(&encoded)), Ok(()));
+
+		// number doesn't fit u64
+		let mut stream = RlpStream::new_list(13);
+		for i in 0..13 {
+			let item = UntrustedRlp::new(&encoded).at(i).unwrap();
+			match i {
+				8 => { stream.append_raw(&rlp::encode(&::util::U256::max_value()), 1); },
+				_ => { stream.append_raw(item.as_raw(), 1); },
+			}
+		}
+		assert_eq!(Header::check_rlp(&UntrustedRlp::new(&stream.out())), Err(DecoderError::RlpIsTooBig));
+
+		// too few fields
+		let mut stream = RlpStream::new_list(12);
+		for i in 0..12 {
+			stream.append_raw(UntrustedRlp::new(&encoded).at(i).unwrap().as_raw(), 1);
+		}
+		assert_eq!(Header::check_rlp(&UntrustedRlp::new(&stream.out())), Err(DecoderError::RlpIncorrectListLen));
+	}
+}
@@ -0,0 +1,75 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas usage and gas prices of a block.
+
+use util::U256;
+
+/// Gas usage of a block and gas prices of its transactions.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "ipc", binary)]
+pub struct BlockFeeStats {
+	/// Block number.
+	pub number: u64,
+	/// Gas used by the block.
+	pub gas_used: U256,
+	/// Gas limit of the block.
+	pub gas_limit: U256,
+	/// Number of transactions in the block.
+	pub transaction_count: u64,
+	/// Lowest gas price of the block's transactions, `None` if there are none.
+	pub min_gas_price: Option<U256>,
+	/// Median gas price of the block's transactions, `None` if there are none.
+	pub median_gas_price: Option<U256>,
+	/// Highest gas price of the block's transactions, `None` if there are none.
+	pub max_gas_price: Option<U256>,
+}
+
+impl BlockFeeStats {
+	/// Statistics of a block with given number, gas used and limit and transaction gas prices.
+	pub fn new(number: u64, gas_used: U256, gas_limit: U256, mut gas_prices: Vec<U256>) -> Self {
+		gas_prices.sort();
+		BlockFeeStats {
+			number: number,
+			gas_used: gas_used,
+			gas_limit: gas_limit,
+			transaction_count: gas_prices.len() as u64,
+			min_gas_price: gas_prices.first().cloned(),
+			median_gas_price: gas_prices.get(gas_prices.len() / 2).cloned(),
+			max_gas_price: gas_prices.last().cloned(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::U256;
+	use super::BlockFeeStats;
+
+	#[test]
+	fn computes_price_statistics() {
+		let prices = vec![30.into(), 10.into(), 50.into(), 20.into()];
+		let stats = BlockFeeStats::new(7, 21000.into(), 4700000.into(), prices);
+		assert_eq!(stats.transaction_count, 4);
+		assert_eq!(stats.min_gas_price, Some(U256::from(10)));
+		assert_eq!(stats.median_gas_price, Some(U256::from(30)));
+		assert_eq!(stats.max_gas_price, Some(U256::from(50)));
+
+		let empty = BlockFeeStats::new(8, 0.into(), 4700000.into(), Vec::new());
+		assert_eq!(empty.transaction_count, 0);
+		assert_eq!(empty.median_gas_price, None);
+	}
+}
@@ -35,6 +35,12 @@ pub enum Error {
 	StackUnderflow,
 	/// When execution would exceed defined Stack Limit
 	OutOfStack,
+	/// `RETURNDATACOPY` read past the end of the return data buffer.
+	OutOfBounds,
+	/// Execution was reverted with `REVERT` instruction.
+	Reverted,
+	/// State-modifying instruction executed within a static call.
+	MutableCallInStaticContext,
 	/// Returned on evm internal error. Should never be ignored during development.
 	/// Likely to cause consensus issues.
 	Internal,
@@ -48,6 +54,9 @@ impl<'a> From<&'a EvmError> for Error {
 			EvmError::BadInstruction { .. } => Error::BadInstruction,
 			EvmError::StackUnderflow { .. } => Error::StackUnderflow,
 			EvmError::OutOfStack { .. } => Error::OutOfStack,
+			EvmError::OutOfBounds => Error::OutOfBounds,
+			EvmError::Reverted => Error::Reverted,
+			EvmError::MutableCallInStaticContext => Error::MutableCallInStaticContext,
 			EvmError::Internal(_) => Error::Internal,
 		}
 	}
@@ -68,6 +77,9 @@ impl fmt::Display for Error {
 			BadInstruction => "Bad instruction",
 			StackUnderflow => "Stack underflow",
 			OutOfStack => "Out of stack",
+			OutOfBounds => "Out of bounds",
+			Reverted => "Reverted",
+			MutableCallInStaticContext => "Mutable call in static context",
 			Internal => "Internal error",
 		};
 		message.fmt(f)
@@ -84,6 +96,9 @@ impl Encodable for Error {
 			StackUnderflow => 3,
 			OutOfStack => 4,
 			Internal => 5,
+			Reverted => 6,
+			OutOfBounds => 7,
+			MutableCallInStaticContext => 8,
 		};
 
 		s.append_internal(&value);
@@ -101,6 +116,9 @@ impl Decodable for Error {
 			3 => Ok(StackUnderflow),
 			4 => Ok(OutOfStack),
 			5 => Ok(Internal),
+			6 => Ok(Reverted),
+			7 => Ok(OutOfBounds),
+			8 => Ok(MutableCallInStaticContext),
 			_ => Err(DecoderError::Custom("Invalid error type")),
 		}
 	}
@@ -214,6 +214,21 @@ impl Transaction {
 	pub fn gas_required(&self, schedule: &Schedule) -> u64 {
 		Self::gas_required_for(match self.action{Action::Create=>true, Action::Call(_)=>false}, &self.data, schedule)
 	}
+
+	/// Checks which don't require state: the gas limit has to cover the intrinsic cost
+	/// and init code of contract creations can't exceed `max_init_code_size` (if any).
+	pub fn verify_static(&self, schedule: &Schedule, max_init_code_size: Option<usize>) -> Result<(), TransactionError> {
+		let intrinsic_gas = U256::from(self.gas_required(schedule));
+		if self.gas < intrinsic_gas {
+			return Err(TransactionError::InsufficientGas { minimal: intrinsic_gas, got: self.gas });
+		}
+
+		match (&self.action, max_init_code_size) {
+			(&Action::Create, Some(limit)) if self.data.len() > limit =>
+				Err(TransactionError::InitCodeTooLarge { limit: limit, got: self.data.len() }),
+			_ => Ok(()),
+		}
+	}
 }
 
 /// Signed transaction information.
@@ -517,6 +532,28 @@ fn signing() {
 	assert_eq!(t.network_id(), None);
 }
 
+#[test]
+fn should_verify_intrinsic_gas_and_init_code_size() {
+	let schedule = Schedule::new_homestead();
+	let t = Transaction {
+		action: Action::Create,
+		nonce: U256::from(42),
+		gas_price: U256::from(3000),
+		gas: U256::from(53_000),
+		value: U256::from(1),
+		data: vec![0]
+	};
+	assert_eq!(t.gas_required(&schedule), 53_004);
+	assert_eq!(t.verify_static(&schedule, None), Err(TransactionError::InsufficientGas { minimal: 53_004.into(), got: 53_000.into() }));
+
+	let t = Transaction { gas: U256::from(60_000), data: vec![0; 10], ..t };
+	assert_eq!(t.verify_static(&schedule, None), Ok(()));
+	assert_eq!(t.verify_static(&schedule, Some(5)), Err(TransactionError::InitCodeTooLarge { limit: 5, got: 10 }));
+
+	let t = Transaction { action: Action::Call(Address::default()), ..t };
+	assert_eq!(t.verify_static(&schedule, Some(5)), Ok(()));
+}
+
 #[test]
 fn fake_signing() {
 	let t = Transaction {
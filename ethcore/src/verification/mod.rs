@@ -21,11 +21,13 @@ pub mod verifier;
 pub mod queue;
 mod canon_verifier;
 mod noop_verifier;
+mod seal_policy;
 
 pub use self::verification::*;
 pub use self::verifier::Verifier;
 pub use self::canon_verifier::CanonVerifier;
 pub use self::noop_verifier::NoopVerifier;
+pub use self::seal_policy::{SealPolicy, SealVerifier};
 pub use self::queue::{BlockQueue, Config as QueueConfig, VerificationQueue, QueueInfo};
 
 /// Verifier type.
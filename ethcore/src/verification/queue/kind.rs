@@ -18,7 +18,9 @@
 
 use engines::Engine;
 use error::Error;
+use verification::SealVerifier;
 
+use header::BlockNumber;
 use util::{HeapSizeOf, H256, U256};
 
 pub use self::blocks::Blocks;
@@ -32,6 +34,9 @@ pub trait BlockLike {
 	/// Get the hash of this item's parent.
 	fn parent_hash(&self) -> H256;
 
+	/// Get the number of this item.
+	fn number(&self) -> BlockNumber;
+
 	/// Get the difficulty of this item.
 	fn difficulty(&self) -> U256;
 }
@@ -59,8 +64,8 @@ pub trait Kind: 'static + Sized + Send + Sync {
 	/// Attempt to create the `Unverified` item from the input.
 	fn create(input: Self::Input, engine: &Engine) -> Result<Self::Unverified, Error>;
 
-	/// Attempt to verify the `Unverified` item using the given engine.
-	fn verify(unverified: Self::Unverified, engine: &Engine, check_seal: bool) -> Result<Self::Verified, Error>;
+	/// Attempt to verify the `Unverified` item using the given engine, checking the seal as the verifier dictates.
+	fn verify(unverified: Self::Unverified, engine: &Engine, seal_verifier: &SealVerifier) -> Result<Self::Verified, Error>;
 }
 
 /// The blocks verification module.
@@ -69,8 +74,8 @@ pub mod blocks {
 
 	use engines::Engine;
 	use error::Error;
-	use header::Header;
-	use verification::{PreverifiedBlock, verify_block_basic, verify_block_unordered};
+	use header::{BlockNumber, Header};
+	use verification::{PreverifiedBlock, SealVerifier, verify_block_basic, verify_block_unordered};
 
 	use util::{Bytes, HeapSizeOf, H256, U256};
 
@@ -92,9 +97,11 @@ pub mod blocks {
 			}
 		}
 
-		fn verify(un: Self::Unverified, engine: &Engine, check_seal: bool) -> Result<Self::Verified, Error> {
+		fn verify(un: Self::Unverified, engine: &Engine, seal_verifier: &SealVerifier) -> Result<Self::Verified, Error> {
 			let hash = un.hash();
-			match verify_block_unordered(un.header, un.bytes, engine, check_seal) {
+			let result = seal_verifier.check_seal(&un.header)
+				.and_then(|check_seal| verify_block_unordered(un.header, un.bytes, engine, check_seal));
+			match result {
 				Ok(verified) => Ok(verified),
 				Err(e) => {
 					warn!(target: "client", "Stage 2 block verification failed for {}: {:?}", hash, e);
@@ -121,6 +128,11 @@ pub mod blocks {
 				bytes: bytes,
 			}
 		}
+
+		/// Header of the block.
+		pub fn header(&self) -> &Header {
+			&self.header
+		}
 	}
 
 	impl HeapSizeOf for Unverified {
@@ -138,6 +150,10 @@ pub mod blocks {
 			self.header.parent_hash().clone()
 		}
 
+		fn number(&self) -> BlockNumber {
+			self.header.number()
+		}
+
 		fn difficulty(&self) -> U256 {
 			self.header.difficulty().clone()
 		}
@@ -152,6 +168,10 @@ pub mod blocks {
 			self.header.parent_hash().clone()
 		}
 
+		fn number(&self) -> BlockNumber {
+			self.header.number()
+		}
+
 		fn difficulty(&self) -> U256 {
 			self.header.difficulty().clone()
 		}
@@ -164,8 +184,8 @@ pub mod headers {
 
 	use engines::Engine;
 	use error::Error;
-	use header::Header;
-	use verification::verify_header_params;
+	use header::{BlockNumber, Header};
+	use verification::{SealVerifier, verify_header_params};
 
 	use util::hash::H256;
 	use util::U256;
@@ -173,6 +193,7 @@ pub mod headers {
 	impl BlockLike for Header {
 		fn hash(&self) -> H256 { self.hash() }
 		fn parent_hash(&self) -> H256 { self.parent_hash().clone() }
+		fn number(&self) -> BlockNumber { self.number() }
 		fn difficulty(&self) -> U256 { self.difficulty().clone() }
 	}
 
@@ -188,8 +209,8 @@ pub mod headers {
 			verify_header_params(&input, engine, true).map(|_| input)
 		}
 
-		fn verify(unverified: Self::Unverified, engine: &Engine, check_seal: bool) -> Result<Self::Verified, Error> {
-			match check_seal {
+		fn verify(unverified: Self::Unverified, engine: &Engine, seal_verifier: &SealVerifier) -> Result<Self::Verified, Error> {
+			match seal_verifier.check_seal(&unverified)? {
 				true => engine.verify_block_unordered(&unverified, None).map(|_| unverified),
 				false => Ok(unverified),
 			}
@@ -25,6 +25,7 @@ use io::*;
 use error::*;
 use engines::Engine;
 use service::*;
+use verification::{SealPolicy, SealVerifier};
 
 use self::kind::{BlockLike, Kind};
 
@@ -190,20 +191,22 @@ struct Verification<K: Kind> {
 	verifying: Mutex<VecDeque<Verifying<K>>>,
 	verified: Mutex<VecDeque<K::Verified>>,
 	bad: Mutex<HashSet<H256>>,
+	failed: Mutex<Vec<H256>>,
 	more_to_verify: SMutex<()>,
 	empty: SMutex<()>,
 	sizes: Sizes,
-	check_seal: bool,
+	seal_verifier: SealVerifier,
 }
 
 impl<K: Kind> VerificationQueue<K> {
 	/// Creates a new queue instance.
-	pub fn new(config: Config, engine: Arc<Engine>, message_channel: IoChannel<ClientIoMessage>, check_seal: bool) -> Self {
+	pub fn new(config: Config, engine: Arc<Engine>, message_channel: IoChannel<ClientIoMessage>, seal_policy: SealPolicy) -> Self {
 		let verification = Arc::new(Verification {
 			unverified: Mutex::new(VecDeque::new()),
 			verifying: Mutex::new(VecDeque::new()),
 			verified: Mutex::new(VecDeque::new()),
 			bad: Mutex::new(HashSet::new()),
+			failed: Mutex::new(Vec::new()),
 			more_to_verify: SMutex::new(()),
 			empty: SMutex::new(()),
 			sizes: Sizes {
@@ -211,7 +214,7 @@ impl<K: Kind> VerificationQueue<K> {
 				verifying: AtomicUsize::new(0),
 				verified: AtomicUsize::new(0),
 			},
-			check_seal: check_seal,
+			seal_verifier: SealVerifier::new(seal_policy),
 		});
 		let more_to_verify = Arc::new(SCondvar::new());
 		let deleting = Arc::new(AtomicBool::new(false));
@@ -350,7 +353,7 @@ impl<K: Kind> VerificationQueue<K> {
 			};
 
 			let hash = item.hash();
-			let is_ready = match K::verify(item, &*engine, verification.check_seal) {
+			let is_ready = match K::verify(item, &*engine, &verification.seal_verifier) {
 				Ok(verified) => {
 					let mut verifying = verification.verifying.lock();
 					let mut idx = None;
@@ -374,12 +377,15 @@ impl<K: Kind> VerificationQueue<K> {
 						false
 					}
 				},
-				Err(_) => {
+				Err(e) => {
 					let mut verifying = verification.verifying.lock();
 					let mut verified = verification.verified.lock();
 					let mut bad = verification.bad.lock();
 
 					bad.insert(hash.clone());
+					if e.is_invalid_block() {
+						verification.failed.lock().push(hash.clone());
+					}
 					verifying.retain(|e| e.hash != hash);
 
 					if verifying.front().map_or(false, |x| x.output.is_some()) {
@@ -441,6 +447,11 @@ impl<K: Kind> VerificationQueue<K> {
 		self.processing.write().clear();
 	}
 
+	/// Forget all items marked as bad, so they may be imported again.
+	pub fn clear_bad(&self) {
+		self.verification.bad.lock().clear();
+	}
+
 	/// Wait for unverified queue to be empty
 	pub fn flush(&self) {
 		let mut lock = self.verification.empty.lock().unwrap();
@@ -483,6 +494,7 @@ impl<K: Kind> VerificationQueue<K> {
 			Ok(item) => {
 				self.verification.sizes.unverified.fetch_add(item.heap_size_of_children(), AtomicOrdering::SeqCst);
 
+				self.verification.seal_verifier.note(item.number(), &h, &item.parent_hash());
 				self.processing.write().insert(h.clone(), item.difficulty());
 				{
 					let mut td = self.total_difficulty.write();
@@ -498,6 +510,9 @@ impl<K: Kind> VerificationQueue<K> {
 					Error::Block(BlockError::InvalidTimestamp(ref e)) if e.max.is_some() => {},
 					_ => {
 						self.verification.bad.lock().insert(h.clone());
+						if err.is_invalid_block() {
+							self.verification.failed.lock().push(h.clone());
+						}
 					}
 				}
 				Err(err)
@@ -505,6 +520,13 @@ impl<K: Kind> VerificationQueue<K> {
 		}
 	}
 
+	/// Take hashes of items which failed verification in the queue since the last call.
+	/// Items marked as bad only because of a bad parent, or failing for reasons other than
+	/// being invalid, are not included.
+	pub fn take_failed(&self) -> Vec<H256> {
+		::std::mem::replace(&mut *self.verification.failed.lock(), Vec::new())
+	}
+
 	/// Mark given item and all its children as bad. pauses verification
 	/// until complete.
 	pub fn mark_as_bad(&self, hashes: &[H256]) {
@@ -730,6 +752,7 @@ mod tests {
 	use io::*;
 	use spec::*;
 	use super::{BlockQueue, Config, State};
+	use verification::SealPolicy;
 	use super::kind::blocks::Unverified;
 	use tests::helpers::*;
 	use error::*;
@@ -743,7 +766,7 @@ mod tests {
 
 		let mut config = Config::default();
 		config.verifier_settings.scale_verifiers = auto_scale;
-		BlockQueue::new(config, engine, IoChannel::disconnected(), true)
+		BlockQueue::new(config, engine, IoChannel::disconnected(), SealPolicy::Full)
 	}
 
 	#[test]
@@ -751,7 +774,7 @@ mod tests {
 		// TODO better test
 		let spec = Spec::new_test();
 		let engine = spec.engine;
-		let _ = BlockQueue::new(Config::default(), engine, IoChannel::disconnected(), true);
+		let _ = BlockQueue::new(Config::default(), engine, IoChannel::disconnected(), SealPolicy::Full);
 	}
 
 	#[test]
@@ -781,6 +804,20 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn reports_failed_blocks() {
+		use header::Header;
+
+		let queue = get_test_queue(false);
+		let mut header = Header::default();
+		header.set_gas_limit(1.into());
+		header.set_gas_used(2.into());
+		assert!(queue.import(Unverified::new(create_test_block(&header))).is_err());
+
+		assert_eq!(queue.take_failed(), vec![header.hash()]);
+		assert!(queue.take_failed().is_empty());
+	}
+
 	#[test]
 	fn returns_total_difficulty() {
 		let queue = get_test_queue(false);
@@ -831,7 +868,7 @@ mod tests {
 		let engine = spec.engine;
 		let mut config = Config::default();
 		config.max_mem_use = super::MIN_MEM_LIMIT;  // empty queue uses about 15000
-		let queue = BlockQueue::new(config, engine, IoChannel::disconnected(), true);
+		let queue = BlockQueue::new(config, engine, IoChannel::disconnected(), SealPolicy::Full);
 		assert!(!queue.queue_info().is_full());
 		let mut blocks = get_good_dummy_block_seq(50);
 		for b in blocks.drain(..) {
@@ -0,0 +1,288 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Policy deciding which block seals are verified on import.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use time::get_time;
+use util::{H256, Mismatch, Mutex};
+use error::{Error, BlockError};
+use header::{Header, BlockNumber};
+
+/// Which block seals are verified on import.
+///
+/// Everything but the seal is always verified; skipping seals only saves the
+/// (costly) proof-of-work or signature checks for chains the operator trusts.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SealPolicy {
+	/// Verify every seal.
+	Full,
+	/// Verify the seal of every `interval`-th block and of every block younger than `recent` seconds.
+	Sampled {
+		/// Distance between sampled blocks.
+		interval: u64,
+		/// Age in seconds under which all seals are verified.
+		recent: u64,
+	},
+	/// Trust the checkpoint block and its ancestors without verifying their seals.
+	/// The block at the checkpoint number must have the checkpoint hash.
+	Checkpoint {
+		/// Number of the checkpoint block.
+		number: BlockNumber,
+		/// Hash of the checkpoint block.
+		hash: H256,
+	},
+	/// Don't verify seals at all.
+	None,
+}
+
+impl Default for SealPolicy {
+	fn default() -> Self {
+		SealPolicy::Full
+	}
+}
+
+/// Maximal number of headers below a checkpoint remembered while their link to it is unknown.
+const MAX_UNLINKED_HEADERS: usize = 100_000;
+
+/// Ancestry of a checkpoint, learned by walking back parent hashes from the checkpoint header.
+#[derive(Default)]
+struct Ancestry {
+	/// Parent hashes of headers below the checkpoint not linked to it yet.
+	unlinked: HashMap<H256, H256>,
+	/// Ancestors of the checkpoint (and the checkpoint itself) with seals not checked yet.
+	trusted: HashSet<H256>,
+	/// Parent of the lowest known ancestor, once the checkpoint header was seen.
+	next: Option<H256>,
+}
+
+impl Ancestry {
+	fn note(&mut self, hash: H256, parent_hash: H256, is_checkpoint: bool) {
+		if !is_checkpoint && self.next.as_ref() != Some(&hash) {
+			if self.unlinked.len() < MAX_UNLINKED_HEADERS {
+				self.unlinked.insert(hash, parent_hash);
+			}
+			return;
+		}
+
+		self.trusted.insert(hash);
+		let mut next = parent_hash;
+		while let Some(parent) = self.unlinked.remove(&next) {
+			self.trusted.insert(next);
+			next = parent;
+		}
+		self.next = Some(next);
+	}
+}
+
+/// Applies a `SealPolicy` to the items of a verification queue.
+pub struct SealVerifier {
+	policy: SealPolicy,
+	ancestry: Mutex<Ancestry>,
+}
+
+impl SealVerifier {
+	/// Create a new verifier following given policy.
+	pub fn new(policy: SealPolicy) -> Self {
+		SealVerifier {
+			policy: policy,
+			ancestry: Mutex::new(Ancestry::default()),
+		}
+	}
+
+	/// Note a header entering the queue, so that the ancestry of a checkpoint can be followed.
+	pub fn note(&self, number: BlockNumber, hash: &H256, parent_hash: &H256) {
+		if let SealPolicy::Checkpoint { number: checkpoint, hash: ref checkpoint_hash } = self.policy {
+			let is_checkpoint = number == checkpoint && hash == checkpoint_hash;
+			if number < checkpoint || is_checkpoint {
+				self.ancestry.lock().note(hash.clone(), parent_hash.clone(), is_checkpoint);
+			}
+		}
+	}
+
+	/// Whether the seal of the given header should be verified.
+	/// Fails if the header contradicts a trusted checkpoint.
+	pub fn check_seal(&self, header: &Header) -> Result<bool, Error> {
+		match self.policy {
+			SealPolicy::Full => Ok(true),
+			SealPolicy::Sampled { interval, recent } => {
+				let now = get_time().sec as u64;
+				// a header timestamp far in the future is verified rather than overflowing.
+				let sampled = header.number().checked_rem(interval).map_or(true, |r| r == 0);
+				Ok(sampled || header.timestamp().saturating_add(recent) >= now)
+			},
+			SealPolicy::Checkpoint { number, ref hash } => {
+				if header.number() > number {
+					return Ok(true);
+				}
+				let header_hash = header.hash();
+				if header.number() == number && header_hash != *hash {
+					return Err(BlockError::CheckpointMismatch(Mismatch { expected: hash.clone(), found: header_hash }).into());
+				}
+				// blocks of other forks, or not linked to the checkpoint yet, are verified fully.
+				Ok(!self.ancestry.lock().trusted.remove(&header_hash))
+			},
+			SealPolicy::None => Ok(false),
+		}
+	}
+}
+
+impl FromStr for SealPolicy {
+	type Err = String;
+
+	/// Parses `full`, `none`, `sampled:INTERVAL[:RECENT_SECS]` or `checkpoint:NUMBER:HASH`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("Invalid seal verification policy: {}", s);
+		let parts: Vec<_> = s.split(':').collect();
+		match parts[0] {
+			"full" if parts.len() == 1 => Ok(SealPolicy::Full),
+			"none" if parts.len() == 1 => Ok(SealPolicy::None),
+			"sampled" if parts.len() == 2 || parts.len() == 3 => {
+				let interval: u64 = parts[1].parse().map_err(|_| invalid())?;
+				if interval == 0 {
+					return Err(invalid());
+				}
+				let recent = match parts.get(2) {
+					Some(recent) => recent.parse().map_err(|_| invalid())?,
+					None => 3600,
+				};
+				Ok(SealPolicy::Sampled { interval: interval, recent: recent })
+			},
+			"checkpoint" if parts.len() == 3 => Ok(SealPolicy::Checkpoint {
+				number: parts[1].parse().map_err(|_| invalid())?,
+				hash: parts[2].trim_left_matches("0x").parse().map_err(|_| invalid())?,
+			}),
+			_ => Err(invalid()),
+		}
+	}
+}
+
+impl fmt::Display for SealPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			SealPolicy::Full => write!(f, "full"),
+			SealPolicy::Sampled { interval, recent } => write!(f, "sampled:{}:{}", interval, recent),
+			SealPolicy::Checkpoint { number, ref hash } => write!(f, "checkpoint:{}:0x{:?}", number, hash),
+			SealPolicy::None => write!(f, "none"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use time::get_time;
+	use util::H256;
+	use header::Header;
+	use super::{SealPolicy, SealVerifier};
+
+	fn header(number: u64, timestamp: u64) -> Header {
+		let mut header = Header::default();
+		header.set_number(number);
+		header.set_timestamp(timestamp);
+		header
+	}
+
+	#[test]
+	fn parses_policies() {
+		assert_eq!("full".parse(), Ok(SealPolicy::Full));
+		assert_eq!("none".parse(), Ok(SealPolicy::None));
+		assert_eq!("sampled:100".parse(), Ok(SealPolicy::Sampled { interval: 100, recent: 3600 }));
+		assert_eq!("sampled:100:60".parse(), Ok(SealPolicy::Sampled { interval: 100, recent: 60 }));
+		assert_eq!("checkpoint:10:0x0000000000000000000000000000000000000000000000000000000000000001".parse(),
+			Ok(SealPolicy::Checkpoint { number: 10, hash: H256::from(1) }));
+		assert!("sampled:0".parse::<SealPolicy>().is_err());
+		assert!("checkpoint:10".parse::<SealPolicy>().is_err());
+		assert!("full:1".parse::<SealPolicy>().is_err());
+	}
+
+	#[test]
+	fn samples_old_blocks_and_checks_recent_ones() {
+		let policy = SealVerifier::new(SealPolicy::Sampled { interval: 10, recent: 60 });
+		assert!(policy.check_seal(&header(20, 0)).unwrap());
+		assert!(!policy.check_seal(&header(21, 0)).unwrap());
+		assert!(policy.check_seal(&header(21, get_time().sec as u64)).unwrap());
+	}
+
+	#[test]
+	fn checks_seals_of_extreme_headers() {
+		let policy = SealVerifier::new(SealPolicy::Sampled { interval: 10, recent: 60 });
+		assert!(policy.check_seal(&header(21, u64::max_value())).unwrap());
+		let policy = SealVerifier::new(SealPolicy::Sampled { interval: 0, recent: 60 });
+		assert!(policy.check_seal(&header(21, 0)).unwrap());
+	}
+
+	fn chain(from: &Header, len: u64, timestamp: u64) -> Vec<Header> {
+		let mut headers = Vec::new();
+		let mut parent = from.clone();
+		for _ in 0..len {
+			let mut header = header(parent.number() + 1, timestamp);
+			header.set_parent_hash(parent.hash());
+			headers.push(header.clone());
+			parent = header;
+		}
+		headers
+	}
+
+	#[test]
+	fn trusts_ancestors_of_checkpoint() {
+		let canon = chain(&header(0, 0), 10, 0);
+		let checkpoint = canon[9].clone();
+		let verifier = SealVerifier::new(SealPolicy::Checkpoint { number: 10, hash: checkpoint.hash() });
+		for header in &canon {
+			verifier.note(header.number(), &header.hash(), header.parent_hash());
+		}
+
+		for header in &canon {
+			assert!(!verifier.check_seal(header).unwrap());
+		}
+		assert!(verifier.check_seal(&chain(&checkpoint, 1, 0)[0]).unwrap());
+		assert!(verifier.check_seal(&header(10, 1)).is_err());
+	}
+
+	#[test]
+	fn verifies_seals_of_other_forks_below_checkpoint() {
+		let canon = chain(&header(0, 0), 10, 0);
+		let fork = chain(&canon[2], 5, 1);
+		let verifier = SealVerifier::new(SealPolicy::Checkpoint { number: 10, hash: canon[9].hash() });
+		for header in fork.iter().chain(canon.iter()) {
+			verifier.note(header.number(), &header.hash(), header.parent_hash());
+		}
+
+		for header in &fork {
+			assert!(verifier.check_seal(header).unwrap());
+		}
+		assert!(!verifier.check_seal(&canon[5]).unwrap());
+	}
+
+	#[test]
+	fn verifies_seals_before_checkpoint_is_known() {
+		let canon = chain(&header(0, 0), 10, 0);
+		let verifier = SealVerifier::new(SealPolicy::Checkpoint { number: 10, hash: canon[9].hash() });
+		for header in &canon[..9] {
+			verifier.note(header.number(), &header.hash(), header.parent_hash());
+		}
+
+		assert!(verifier.check_seal(&canon[3]).unwrap());
+
+		// ancestors noted before the checkpoint are trusted once it's linked.
+		verifier.note(10, &canon[9].hash(), canon[9].parent_hash());
+		assert!(!verifier.check_seal(&canon[4]).unwrap());
+		// the seal of each block is skipped once, blocks imported again are verified.
+		assert!(verifier.check_seal(&canon[4]).unwrap());
+	}
+}
@@ -31,6 +31,9 @@ use transaction::SignedTransaction;
 use views::BlockView;
 use time::get_time;
 
+/// Number of seconds a block's timestamp may be ahead of the local clock.
+pub const MAX_TIMESTAMP_DRIFT: u64 = 30;
+
 /// Preprocessed block data gathered in `verify_block_unordered` call
 pub struct PreverifiedBlock {
 	/// Populated block header
@@ -210,8 +213,11 @@ pub fn verify_header_params(header: &Header, engine: &Engine, is_full: bool) ->
 	if header.number() != 0 && header.extra_data().len() > maximum_extra_data_size {
 		return Err(From::from(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(maximum_extra_data_size), found: header.extra_data().len() })));
 	}
+	if header.number() != 0 && !header.extra_data().starts_with(&engine.params().extra_data_prefix) {
+		return Err(From::from(BlockError::ExtraDataMissingPrefix));
+	}
 	if is_full {
-		let max_time = get_time().sec as u64 + 30;
+		let max_time = get_time().sec as u64 + MAX_TIMESTAMP_DRIFT;
 		if header.timestamp() > max_time {
 			return Err(From::from(BlockError::InvalidTimestamp(OutOfBounds { max: Some(max_time), min: None, found: header.timestamp() })))
 		}
@@ -259,7 +265,7 @@ mod tests {
 	use error::BlockError::*;
 	use views::*;
 	use blockchain::*;
-	use engines::Engine;
+	use engines::{Engine, NullEngine};
 	use spec::*;
 	use transaction::*;
 	use tests::helpers::*;
@@ -556,4 +562,25 @@ mod tests {
 
 		// TODO: some additional uncle checks
 	}
+
+	#[test]
+	fn test_verify_extra_data_prefix() {
+		let params = CommonParams {
+			maximum_extra_data_size: 32,
+			extra_data_prefix: b"private".to_vec(),
+			..Default::default()
+		};
+		let engine = NullEngine::new(params, Default::default());
+
+		let mut header = Header::new();
+		header.set_number(10);
+		header.set_extra_data(b"private-parity".to_vec());
+		check_ok(verify_header_params(&header, &engine, false));
+
+		header.set_extra_data(b"parity".to_vec());
+		check_fail(verify_header_params(&header, &engine, false), ExtraDataMissingPrefix);
+
+		header.set_number(0);
+		check_ok(verify_header_params(&header, &engine, false));
+	}
 }
@@ -133,6 +133,17 @@ pub fn derive_mac(derived_left_bits: &[u8], cipher_text: &[u8]) -> Vec<u8> {
 	mac
 }
 
+/// HMAC-SHA256 of `data` keyed with `key`.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+	use rcrypto::mac::Mac;
+
+	let mut hmac = Hmac::new(Sha256::new(), key);
+	hmac.input(data);
+	let mut mac = [0u8; 32];
+	hmac.raw_result(&mut mac);
+	mac
+}
+
 /// AES encryption
 pub mod aes {
 	use rcrypto::blockmodes::{CtrMode, CbcDecryptor, PkcsPadding};
@@ -354,6 +365,17 @@ pub mod ecies {
 mod tests {
 	use ethkey::{Random, Generator};
 	use ecies;
+	use hmac_sha256;
+
+	#[test]
+	fn hmac_sha256_rfc4231() {
+		let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+		let expected = [
+			0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75, 0xc7,
+			0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+		];
+		assert_eq!(mac, expected);
+	}
 
 	#[test]
 	fn ecies_shared() {
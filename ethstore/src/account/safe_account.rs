@@ -129,6 +129,12 @@ impl SafeAccount {
 		sign(&secret, message).map_err(From::from)
 	}
 
+	/// Sign multiple messages, decrypting the secret only once.
+	pub fn sign_many(&self, password: &str, messages: &[Message]) -> Result<Vec<Result<Signature, Error>>, Error> {
+		let secret = self.crypto.secret(password)?;
+		Ok(messages.iter().map(|message| sign(&secret, message).map_err(From::from)).collect())
+	}
+
 	/// Decrypt a message.
 	pub fn decrypt(&self, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
 		let secret = self.crypto.secret(password)?;
@@ -177,6 +183,21 @@ mod tests {
 		assert!(verify_public(keypair.public(), &signature, &message).unwrap());
 	}
 
+	#[test]
+	fn sign_many_and_verify_public() {
+		let keypair = Random.generate().unwrap();
+		let password = "hello world";
+		let messages = vec![Message::from(1), Message::from(2)];
+		let account = SafeAccount::create(&keypair, [0u8; 16], password, 10240, "Test".to_owned(), "{}".to_owned());
+		assert!(account.sign_many("wrong", &messages).is_err());
+
+		let signatures = account.sign_many(password, &messages).unwrap();
+		assert_eq!(signatures.len(), 2);
+		for (signature, message) in signatures.into_iter().zip(&messages) {
+			assert!(verify_public(keypair.public(), &signature.unwrap(), message).unwrap());
+		}
+	}
+
 	#[test]
 	fn change_password() {
 		let keypair = Random.generate().unwrap();
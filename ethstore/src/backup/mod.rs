@@ -0,0 +1,300 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encrypted backups of the keys directory (including vaults) to remote targets.
+//!
+//! Every backup consists of two objects: the encrypted list and content of all key files
+//! and a manifest with the hash of the encrypted content. Nothing about the backed up files
+//! is stored in plain text. The manifest is stored last, so a backup is only listed once
+//! it's complete.
+
+mod target;
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rustc_serialize::hex::{ToHex, FromHex};
+use serde_json;
+use account::Crypto;
+use crypto::Keccak256;
+use {json, Error};
+
+pub use self::target::{BackupTarget, LocalTarget, SftpTarget, S3Target, target_from_str};
+
+const MANIFEST_SUFFIX: &'static str = ".manifest.json";
+const CONTENT_SUFFIX: &'static str = ".keys.json";
+
+/// File included in a backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestFile {
+	/// Path relative to the keys directory, with `/` separators.
+	pub path: String,
+	/// Size in bytes.
+	pub size: u64,
+	/// Hex-encoded Keccak hash of the file.
+	pub hash: String,
+}
+
+/// Integrity manifest of a backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+	/// Backup identifier, the Unix timestamp of its creation.
+	pub id: u64,
+	/// Hex-encoded Keccak hash of the encrypted content.
+	pub content_hash: String,
+}
+
+/// Encrypted content of a backup.
+#[derive(Serialize, Deserialize)]
+struct Content {
+	/// Backed up files.
+	files: Vec<ManifestFile>,
+	/// Hex-encoded data of the files, in the order of `files`.
+	data: Vec<String>,
+}
+
+/// Back up the keys directory, unless it's unchanged since the latest backup.
+/// Returns the manifest of the new backup and the backed up files.
+pub fn backup(dir: &Path, target: &BackupTarget, password: &str, iterations: u32) -> Result<Option<(Manifest, Vec<ManifestFile>)>, Error> {
+	let files = read_files(dir)?;
+	let manifest_files = files.iter().map(|&(ref path, ref content)| manifest_file(path, content)).collect::<Vec<_>>();
+
+	if let Some(latest) = list(target)?.last() {
+		// the file list is encrypted, so the latest backup has to be decrypted to compare it.
+		match read_content(target, *latest, password) {
+			Ok(ref content) if content.files == manifest_files => {
+				trace!(target: "backup", "Keys unchanged since backup {}", latest);
+				return Ok(None);
+			},
+			Ok(_) => {},
+			Err(e) => debug!(target: "backup", "Unable to read backup {}: {}", latest, e),
+		}
+	}
+
+	let plain = Content {
+		files: manifest_files.clone(),
+		data: files.into_iter().map(|(_, content)| content.to_hex()).collect(),
+	};
+	let plain = serde_json::to_vec(&plain).map_err(|e| Error::Custom(format!("{}", e)))?;
+	let crypto: json::Crypto = Crypto::with_plain(&plain, password, iterations).into();
+	let content = serde_json::to_vec(&crypto).map_err(|e| Error::Custom(format!("{}", e)))?;
+
+	let manifest = Manifest {
+		id: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+		content_hash: content.keccak256().to_hex(),
+	};
+	let raw_manifest = serde_json::to_vec(&manifest).map_err(|e| Error::Custom(format!("{}", e)))?;
+
+	target.put(&format!("{}{}", manifest.id, CONTENT_SUFFIX), &content)?;
+	target.put(&format!("{}{}", manifest.id, MANIFEST_SUFFIX), &raw_manifest)?;
+	Ok(Some((manifest, manifest_files)))
+}
+
+/// Identifiers of complete backups stored in the target, oldest first.
+pub fn list(target: &BackupTarget) -> Result<Vec<u64>, Error> {
+	let mut ids = target.list()?.into_iter()
+		.filter(|name| name.ends_with(MANIFEST_SUFFIX))
+		.filter_map(|name| name[..name.len() - MANIFEST_SUFFIX.len()].parse().ok())
+		.collect::<Vec<u64>>();
+	ids.sort();
+	Ok(ids)
+}
+
+/// Read manifest of given backup.
+pub fn manifest(target: &BackupTarget, id: u64) -> Result<Manifest, Error> {
+	let raw = target.get(&format!("{}{}", id, MANIFEST_SUFFIX))?;
+	serde_json::from_slice(&raw).map_err(|e| Error::Custom(format!("Invalid manifest of backup {}: {}", id, e)))
+}
+
+/// Restore given backup (the latest one if `None`) to the keys directory.
+/// Files which already exist are left untouched. Returns paths of restored files.
+pub fn restore(dir: &Path, target: &BackupTarget, id: Option<u64>, password: &str) -> Result<Vec<String>, Error> {
+	let id = match id {
+		Some(id) => id,
+		None => match list(target)?.last() {
+			Some(id) => *id,
+			None => return Err(Error::Custom("No backups found".into())),
+		},
+	};
+
+	let content = read_content(target, id, password)?;
+	if content.files.len() != content.data.len() {
+		return Err(Error::Custom(format!("Backup {} is corrupted: files don't match their list", id)));
+	}
+
+	let mut decoded = Vec::with_capacity(content.files.len());
+	for (file, data) in content.files.into_iter().zip(content.data) {
+		let data = data.from_hex().map_err(|e| Error::Custom(format!("Invalid backup {}: {}", id, e)))?;
+		if manifest_file(&file.path, &data) != file {
+			return Err(Error::Custom(format!("Backup {} is corrupted: files don't match their list", id)));
+		}
+		decoded.push((file.path, data));
+	}
+
+	let mut restored = Vec::new();
+	for (path, content) in decoded {
+		let file_path = dir.join(relative_path(&path)?);
+		if file_path.exists() {
+			continue;
+		}
+
+		if let Some(parent) = file_path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		fs::File::create(&file_path)?.write_all(&content)?;
+		restored.push(path);
+	}
+	Ok(restored)
+}
+
+/// Read and decrypt the content of given backup, checking it against the manifest.
+fn read_content(target: &BackupTarget, id: u64, password: &str) -> Result<Content, Error> {
+	let manifest = manifest(target, id)?;
+	let content = target.get(&format!("{}{}", id, CONTENT_SUFFIX))?;
+	if content.keccak256().to_hex() != manifest.content_hash {
+		return Err(Error::Custom(format!("Backup {} is corrupted: content hash mismatch", id)));
+	}
+
+	let crypto: json::Crypto = serde_json::from_slice(&content).map_err(|e| Error::Custom(format!("Invalid backup {}: {}", id, e)))?;
+	let plain = Crypto::from(crypto).decrypt(password)?;
+	serde_json::from_slice(&plain).map_err(|e| Error::Custom(format!("Invalid backup {}: {}", id, e)))
+}
+
+fn manifest_file(path: &str, content: &[u8]) -> ManifestFile {
+	ManifestFile {
+		path: path.into(),
+		size: content.len() as u64,
+		hash: content.keccak256().to_hex(),
+	}
+}
+
+/// Key files in the directory and in its vault subdirectories, sorted by path.
+fn read_files(dir: &Path) -> Result<Vec<(String, Vec<u8>)>, Error> {
+	fn visible_entries(dir: &Path) -> Result<Vec<(String, PathBuf, bool)>, Error> {
+		Ok(fs::read_dir(dir)?
+			.flat_map(Result::ok)
+			.filter_map(|entry| {
+				let name = entry.file_name().to_string_lossy().into_owned();
+				let is_dir = entry.metadata().ok().map_or(false, |m| m.is_dir());
+				match name.starts_with(".") {
+					true => None,
+					false => Some((name, entry.path(), is_dir)),
+				}
+			})
+			.collect())
+	}
+
+	fn read(path: &Path) -> Result<Vec<u8>, Error> {
+		let mut content = Vec::new();
+		fs::File::open(path)?.read_to_end(&mut content)?;
+		Ok(content)
+	}
+
+	let mut files = Vec::new();
+	for (name, path, is_dir) in visible_entries(dir)? {
+		if !is_dir {
+			files.push((name, read(&path)?));
+			continue;
+		}
+
+		for (vault_name, vault_path, is_dir) in visible_entries(&path)? {
+			if !is_dir {
+				files.push((format!("{}/{}", name, vault_name), read(&vault_path)?));
+			}
+		}
+	}
+
+	files.sort_by(|a, b| a.0.cmp(&b.0));
+	Ok(files)
+}
+
+/// Convert a path from a backup to a relative path which can't escape the keys directory.
+fn relative_path(path: &str) -> Result<PathBuf, Error> {
+	let relative = PathBuf::from(path);
+	match relative.components().all(|c| match c { Component::Normal(_) => true, _ => false }) {
+		true => Ok(relative),
+		false => Err(Error::Custom(format!("Invalid path in backup: {}", path))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::io::{Read, Write};
+	use devtools::RandomTempPath;
+	use super::{LocalTarget, backup, restore, list, relative_path};
+
+	fn write(path: &::std::path::Path, content: &[u8]) {
+		fs::create_dir_all(path.parent().unwrap()).unwrap();
+		fs::File::create(path).unwrap().write_all(content).unwrap();
+	}
+
+	#[test]
+	fn should_backup_and_restore_keys() {
+		// given
+		let keys = RandomTempPath::create_dir();
+		let remote = RandomTempPath::create_dir();
+		let target = LocalTarget::new(remote.as_path());
+		write(&keys.as_path().join("key1"), b"key1");
+		write(&keys.as_path().join("vault/key2"), b"key2");
+		write(&keys.as_path().join("vault/vault.json"), b"meta");
+
+		// when
+		let (manifest, files) = backup(keys.as_path(), &target, "password", 1024).unwrap().unwrap();
+
+		// then
+		assert_eq!(files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec!["key1", "vault/key2", "vault/vault.json"]);
+		assert_eq!(list(&target).unwrap(), vec![manifest.id]);
+		assert!(backup(keys.as_path(), &target, "password", 1024).unwrap().is_none());
+
+		// file names aren't stored in plain text
+		for entry in fs::read_dir(remote.as_path()).unwrap() {
+			let mut stored = String::new();
+			fs::File::open(entry.unwrap().path()).unwrap().read_to_string(&mut stored).unwrap();
+			assert!(!stored.contains("vault"));
+		}
+
+		let restored = RandomTempPath::create_dir();
+		assert!(restore(restored.as_path(), &target, None, "wrong").is_err());
+		let files = restore(restored.as_path(), &target, None, "password").unwrap();
+		assert_eq!(files.len(), 3);
+		assert_eq!(fs::read_dir(restored.as_path().join("vault")).unwrap().count(), 2);
+		assert!(restore(restored.as_path(), &target, Some(manifest.id), "password").unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_detect_corrupted_backup() {
+		let keys = RandomTempPath::create_dir();
+		let remote = RandomTempPath::create_dir();
+		let target = LocalTarget::new(remote.as_path());
+		write(&keys.as_path().join("key1"), b"key1");
+		let (manifest, _) = backup(keys.as_path(), &target, "password", 1024).unwrap().unwrap();
+
+		write(&remote.as_path().join(format!("{}.keys.json", manifest.id)), b"{}");
+
+		let restored = RandomTempPath::create_dir();
+		assert!(restore(restored.as_path(), &target, None, "password").is_err());
+	}
+
+	#[test]
+	fn should_reject_escaping_paths() {
+		assert!(relative_path("vault/key").is_ok());
+		assert!(relative_path("../key").is_err());
+		assert!(relative_path("/etc/key").is_err());
+	}
+}
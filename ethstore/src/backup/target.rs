@@ -0,0 +1,312 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage targets of key backups.
+
+use std::env;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use random::random_string;
+use Error;
+
+/// Storage of backup objects.
+pub trait BackupTarget: Send + Sync {
+	/// Store an object under given name.
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error>;
+	/// Read an object.
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error>;
+	/// Names of all stored objects.
+	fn list(&self) -> Result<Vec<String>, Error>;
+}
+
+/// Parse a backup target. Supported forms are `sftp://[USER@]HOST/PATH`,
+/// `s3://BUCKET[/PREFIX][?endpoint=URL]` and a local path (optionally prefixed by `file://`).
+pub fn target_from_str(s: &str) -> Result<Box<BackupTarget>, String> {
+	if s.starts_with("sftp://") {
+		let rest = &s["sftp://".len()..];
+		let (host, path) = match rest.find('/') {
+			Some(pos) => (&rest[..pos], &rest[pos..]),
+			None => return Err(format!("Invalid SFTP backup target: {}. Expected sftp://[USER@]HOST/PATH", s)),
+		};
+		if host.is_empty() {
+			return Err(format!("Invalid SFTP backup target: {}. Expected sftp://[USER@]HOST/PATH", s));
+		}
+		Ok(Box::new(SftpTarget::new(host, path)))
+	} else if s.starts_with("s3://") {
+		let rest = &s["s3://".len()..];
+		let (location, endpoint) = match rest.find("?endpoint=") {
+			Some(pos) => (&rest[..pos], Some(&rest[pos + "?endpoint=".len()..])),
+			None => (rest, None),
+		};
+		let mut parts = location.splitn(2, '/');
+		let bucket = parts.next().unwrap_or("");
+		if bucket.is_empty() {
+			return Err(format!("Invalid S3 backup target: {}. Expected s3://BUCKET[/PREFIX][?endpoint=URL]", s));
+		}
+		Ok(Box::new(S3Target::new(bucket, parts.next().unwrap_or(""), endpoint.map(Into::into))))
+	} else if s.starts_with("file://") {
+		Ok(Box::new(LocalTarget::new(&s["file://".len()..])))
+	} else {
+		Ok(Box::new(LocalTarget::new(s)))
+	}
+}
+
+/// Backups in a local (or mounted) directory.
+pub struct LocalTarget {
+	path: PathBuf,
+}
+
+impl LocalTarget {
+	/// Store backups in given directory.
+	pub fn new<P: AsRef<Path>>(path: P) -> Self {
+		LocalTarget {
+			path: path.as_ref().to_path_buf(),
+		}
+	}
+}
+
+impl BackupTarget for LocalTarget {
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+		fs::create_dir_all(&self.path)?;
+		let temp_path = self.path.join(format!(".{}", name));
+		{
+			let mut file = fs::File::create(&temp_path)?;
+			file.write_all(data)?;
+			file.sync_all()?;
+		}
+		fs::rename(&temp_path, self.path.join(name))?;
+		Ok(())
+	}
+
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error> {
+		let mut data = Vec::new();
+		fs::File::open(self.path.join(name))?.read_to_end(&mut data)?;
+		Ok(data)
+	}
+
+	fn list(&self) -> Result<Vec<String>, Error> {
+		if !self.path.exists() {
+			return Ok(Vec::new());
+		}
+
+		Ok(fs::read_dir(&self.path)?
+			.flat_map(Result::ok)
+			.map(|entry| entry.file_name().to_string_lossy().into_owned())
+			.filter(|name| !name.starts_with("."))
+			.collect())
+	}
+}
+
+/// Backups on a remote host, transferred with the system `sftp` client.
+/// Authentication relies on the user's SSH configuration (keys or agent).
+pub struct SftpTarget {
+	host: String,
+	path: String,
+}
+
+impl SftpTarget {
+	/// Store backups in given directory of a host.
+	pub fn new(host: &str, path: &str) -> Self {
+		SftpTarget {
+			host: host.into(),
+			path: path.trim_right_matches('/').into(),
+		}
+	}
+
+	fn batch(&self, commands: &str) -> Result<Vec<u8>, Error> {
+		run(Command::new("sftp").arg("-q").arg("-b").arg("-").arg(&self.host), Some(commands.as_bytes()))
+	}
+}
+
+impl BackupTarget for SftpTarget {
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+		let local = TempFile::new()?;
+		local.write(data)?;
+		self.batch(&format!("-mkdir \"{}\"\nput \"{}\" \"{}/{}\"\n", self.path, local.path.display(), self.path, name))?;
+		Ok(())
+	}
+
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error> {
+		let local = TempFile::new()?;
+		self.batch(&format!("get \"{}/{}\" \"{}\"\n", self.path, name, local.path.display()))?;
+		local.read()
+	}
+
+	fn list(&self) -> Result<Vec<String>, Error> {
+		let output = self.batch(&format!("-mkdir \"{}\"\nls -1 \"{}\"\n", self.path, self.path))?;
+		Ok(String::from_utf8_lossy(&output).lines()
+			.filter(|line| !line.starts_with("sftp>"))
+			.filter_map(|line| line.trim().rsplit('/').next().map(str::to_owned))
+			.filter(|name| !name.is_empty())
+			.collect())
+	}
+}
+
+/// Backups in an S3-compatible bucket, transferred with the `aws` command line client.
+/// Credentials are taken from the standard AWS environment variables and configuration.
+pub struct S3Target {
+	bucket: String,
+	prefix: String,
+	endpoint: Option<String>,
+}
+
+impl S3Target {
+	/// Store backups under given prefix of a bucket, at a custom endpoint if given.
+	pub fn new(bucket: &str, prefix: &str, endpoint: Option<String>) -> Self {
+		S3Target {
+			bucket: bucket.into(),
+			prefix: prefix.trim_matches('/').into(),
+			endpoint: endpoint,
+		}
+	}
+
+	fn url(&self, name: &str) -> String {
+		match self.prefix.is_empty() {
+			true => format!("s3://{}/{}", self.bucket, name),
+			false => format!("s3://{}/{}/{}", self.bucket, self.prefix, name),
+		}
+	}
+
+	fn command(&self) -> Command {
+		let mut command = Command::new("aws");
+		if let Some(ref endpoint) = self.endpoint {
+			command.arg("--endpoint-url").arg(endpoint);
+		}
+		command.arg("s3");
+		command
+	}
+}
+
+impl BackupTarget for S3Target {
+	fn put(&self, name: &str, data: &[u8]) -> Result<(), Error> {
+		run(self.command().arg("cp").arg("-").arg(self.url(name)), Some(data)).map(|_| ())
+	}
+
+	fn get(&self, name: &str) -> Result<Vec<u8>, Error> {
+		run(self.command().arg("cp").arg(self.url(name)).arg("-"), None)
+	}
+
+	fn list(&self) -> Result<Vec<String>, Error> {
+		let mut command = self.command();
+		command.arg("ls").arg(self.url(""));
+		let output = spawn(&mut command, None)?;
+
+		// `ls` of a prefix without any objects exits with 1 without an error message,
+		// any other failure (credentials, network, missing bucket) is reported.
+		let no_objects = output.status.code() == Some(1) && String::from_utf8_lossy(&output.stderr).trim().is_empty();
+		let output = match output.status.success() {
+			true => output.stdout,
+			false if no_objects => return Ok(Vec::new()),
+			false => return Err(failure(&command, &output.stderr)),
+		};
+
+		// lines are `DATE TIME SIZE NAME` or `PRE NAME/` for nested prefixes
+		Ok(String::from_utf8_lossy(&output).lines()
+			.filter(|line| !line.trim_left().starts_with("PRE "))
+			.filter_map(|line| line.split_whitespace().nth(3).map(str::to_owned))
+			.collect())
+	}
+}
+
+/// Run a command feeding it given input, returns its standard output.
+fn run(command: &mut Command, input: Option<&[u8]>) -> Result<Vec<u8>, Error> {
+	let output = spawn(command, input)?;
+	match output.status.success() {
+		true => Ok(output.stdout),
+		false => Err(failure(command, &output.stderr)),
+	}
+}
+
+/// Run a command feeding it given input, returns its output regardless of the exit status.
+fn spawn(command: &mut Command, input: Option<&[u8]>) -> Result<Output, Error> {
+	let mut child = command
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.map_err(|e| Error::Custom(format!("Unable to run {:?}: {}", command, e)))?;
+
+	if let Some(input) = input {
+		child.stdin.take().expect("stdin is piped; qed").write_all(input)?;
+	}
+	drop(child.stdin.take());
+
+	Ok(child.wait_with_output()?)
+}
+
+fn failure(command: &Command, stderr: &[u8]) -> Error {
+	Error::Custom(format!("{:?} failed: {}", command, String::from_utf8_lossy(stderr).trim()))
+}
+
+/// Temporary file removed when dropped.
+struct TempFile {
+	path: PathBuf,
+}
+
+impl TempFile {
+	fn new() -> Result<Self, Error> {
+		Ok(TempFile {
+			path: env::temp_dir().join(format!("parity-backup-{}", random_string(16))),
+		})
+	}
+
+	fn write(&self, data: &[u8]) -> Result<(), Error> {
+		fs::File::create(&self.path)?.write_all(data)?;
+		Ok(())
+	}
+
+	fn read(&self) -> Result<Vec<u8>, Error> {
+		let mut data = Vec::new();
+		fs::File::open(&self.path)?.read_to_end(&mut data)?;
+		Ok(data)
+	}
+}
+
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		let _ = fs::remove_file(&self.path);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use devtools::RandomTempPath;
+	use super::{BackupTarget, LocalTarget, target_from_str};
+
+	#[test]
+	fn should_store_objects_locally() {
+		let path = RandomTempPath::new();
+		let target = LocalTarget::new(path.as_path());
+		assert!(target.list().unwrap().is_empty());
+
+		target.put("object", b"data").unwrap();
+
+		assert_eq!(target.list().unwrap(), vec!["object".to_owned()]);
+		assert_eq!(target.get("object").unwrap(), b"data".to_vec());
+	}
+
+	#[test]
+	fn should_parse_targets() {
+		assert!(target_from_str("sftp://user@host/backups").is_ok());
+		assert!(target_from_str("sftp://host").is_err());
+		assert!(target_from_str("s3://bucket/prefix?endpoint=https://s3.example.com").is_ok());
+		assert!(target_from_str("s3://").is_err());
+		assert!(target_from_str("/mnt/backups").is_ok());
+	}
+}
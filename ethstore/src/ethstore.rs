@@ -91,6 +91,10 @@ impl SimpleSecretStore for EthStore {
 		self.get(account)?.sign(password, message)
 	}
 
+	fn sign_many(&self, account: &StoreAccountRef, password: &str, messages: &[Message]) -> Result<Vec<Result<Signature, Error>>, Error> {
+		self.get(account)?.sign_many(password, messages)
+	}
+
 	fn sign_derived(&self, account_ref: &StoreAccountRef, password: &str, derivation: Derivation, message: &Message)
 		-> Result<Signature, Error>
 	{
@@ -497,6 +501,14 @@ impl SimpleSecretStore for EthMultiStore {
 		Err(Error::InvalidPassword)
 	}
 
+	fn sign_many(&self, account: &StoreAccountRef, password: &str, messages: &[Message]) -> Result<Vec<Result<Signature, Error>>, Error> {
+		let accounts = self.get_matching(account, password)?;
+		for account in accounts {
+			return account.sign_many(password, messages);
+		}
+		Err(Error::InvalidPassword)
+	}
+
 	fn decrypt(&self, account: &StoreAccountRef, password: &str, shared_mac: &[u8], message: &[u8]) -> Result<Vec<u8>, Error> {
 		let accounts = self.get_matching(account, password)?;
 		for account in accounts {
@@ -44,6 +44,7 @@ extern crate lazy_static;
 #[macro_use]
 extern crate serde_derive;
 
+pub mod backup;
 pub mod dir;
 pub mod ethkey;
 
@@ -55,6 +55,8 @@ pub trait SimpleSecretStore: Send + Sync {
 	fn generate_derived(&self, account_ref: &StoreAccountRef, password: &str, derivation: Derivation) -> Result<Address, Error>;
 	/// Sign a message with given account.
 	fn sign(&self, account: &StoreAccountRef, password: &str, message: &Message) -> Result<Signature, Error>;
+	/// Sign multiple messages with given account, checking the password once. Returns a result per message.
+	fn sign_many(&self, account: &StoreAccountRef, password: &str, messages: &[Message]) -> Result<Vec<Result<Signature, Error>>, Error>;
 	/// Sign a message with derived account.
 	fn sign_derived(&self, account_ref: &StoreAccountRef, password: &str, derivation: Derivation, message: &Message) -> Result<Signature, Error>;
 	/// Decrypt a messages with given account.
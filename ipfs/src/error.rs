@@ -35,10 +35,12 @@ pub enum Error {
 	CidParsingFailed,
 	UnsupportedHash,
 	UnsupportedCid,
+	UnknownCid,
 	BlockNotFound,
 	TransactionNotFound,
 	StateRootNotFound,
 	ContractNotFound,
+	InvalidHeaderRange,
 }
 
 /// Convert Error into Out, handy when switching from Rust's Result-based
@@ -48,13 +50,15 @@ impl From<Error> for Out {
 		use self::Error::*;
 
 		match err {
-			UnsupportedHash => Out::Bad("Hash must be Keccak-256"),
+			UnsupportedHash => Out::Bad("Hash must be Keccak-256 or SHA2-256"),
+			UnknownCid => Out::NotFound("CID was not served by this node"),
 			UnsupportedCid => Out::Bad("CID codec not supported"),
 			CidParsingFailed => Out::Bad("CID parsing failed"),
 			BlockNotFound => Out::NotFound("Block not found"),
 			TransactionNotFound => Out::NotFound("Transaction not found"),
 			StateRootNotFound => Out::NotFound("State root not found"),
 			ContractNotFound => Out::NotFound("Contract not found"),
+			InvalidHeaderRange => Out::Bad("Invalid header range"),
 		}
 	}
 }
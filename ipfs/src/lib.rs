@@ -18,10 +18,12 @@
 extern crate mime;
 extern crate multihash;
 extern crate cid;
+extern crate lru_cache;
 
 extern crate rlp;
 extern crate ethcore;
 extern crate ethcore_util as util;
+extern crate ethcore_logger;
 extern crate jsonrpc_http_server as http;
 
 pub mod error;
@@ -30,15 +32,19 @@ mod route;
 use std::io::Write;
 use std::sync::Arc;
 use std::net::{SocketAddr, IpAddr};
+use std::time::Instant;
 use error::ServerError;
-use route::Out;
+use route::{Out, CidIndex};
 use http::hyper::server::{Listening, Handler, Request, Response};
 use http::hyper::net::HttpStream;
 use http::hyper::header::{self, Vary, ContentLength, ContentType};
 use http::hyper::{Next, Encoder, Decoder, Method, RequestUri, StatusCode};
 use ethcore::client::BlockChainClient;
+use ethcore_logger::{AccessLog, AccessLogEntry};
 
-pub use http::{AccessControlAllowOrigin, Host, DomainsValidation};
+pub use http::{AccessControlAllowOrigin, DomainsValidation};
+pub use util::hosts::AllowedHosts;
+pub use multihash::Hash as CidHash;
 
 /// Request/response handler
 pub struct IpfsHandler {
@@ -51,9 +57,16 @@ pub struct IpfsHandler {
 	/// Allowed CORS domains
 	cors_domains: Option<Vec<AccessControlAllowOrigin>>,
 	/// Hostnames allowed in the `Host` request header
-	allowed_hosts: Option<Vec<Host>>,
+	allowed_hosts: AllowedHosts,
+	/// Hash function used for the canonical CID of served content
+	cid_hash: CidHash,
+	/// Hashes addressed by served non-Keccak CIDs, shared by all requests
+	cid_index: Arc<CidIndex>,
 	/// Reference to the Blockchain Client
 	client: Arc<BlockChainClient>,
+	/// Optional access log and the entry for the request currently served
+	access_log: Option<Arc<AccessLog>>,
+	access_entry: Option<(Instant, AccessLogEntry)>,
 }
 
 impl IpfsHandler {
@@ -61,14 +74,33 @@ impl IpfsHandler {
 		&*self.client
 	}
 
-	pub fn new(cors: DomainsValidation<AccessControlAllowOrigin>, hosts: DomainsValidation<Host>, client: Arc<BlockChainClient>) -> Self {
+	pub fn cid_hash(&self) -> CidHash {
+		self.cid_hash
+	}
+
+	pub fn cid_index(&self) -> &CidIndex {
+		&*self.cid_index
+	}
+
+	pub fn new(
+		cors: DomainsValidation<AccessControlAllowOrigin>,
+		hosts: AllowedHosts,
+		cid_hash: CidHash,
+		cid_index: Arc<CidIndex>,
+		client: Arc<BlockChainClient>,
+		access_log: Option<Arc<AccessLog>>,
+	) -> Self {
 		IpfsHandler {
 			out: Out::Bad("Invalid Request"),
 			out_progress: 0,
 			cors_header: None,
 			cors_domains: cors.into(),
-			allowed_hosts: hosts.into(),
+			allowed_hosts: hosts,
+			cid_hash: cid_hash,
+			cid_index: cid_index,
 			client: client,
+			access_log: access_log,
+			access_entry: None,
 		}
 	}
 }
@@ -76,12 +108,21 @@ impl IpfsHandler {
 /// Implement Hyper's HTTP handler
 impl Handler<HttpStream> for IpfsHandler {
 	fn on_request(&mut self, req: Request<HttpStream>) -> Next {
+		if let Some(ref log) = self.access_log {
+			self.access_entry = Some((Instant::now(), log.entry(&req)));
+		}
+
 		if *req.method() != Method::Get {
 			return Next::write();
 		}
 
 
-		if !http::is_host_allowed(&req, &self.allowed_hosts) {
+		let host = req.headers().get::<header::Host>().map(|host| match host.port {
+			Some(port) => format!("{}:{}", host.hostname, port),
+			None => host.hostname.clone(),
+		});
+
+		if !self.allowed_hosts.is_allowed(host.as_ref().map(String::as_str)) {
 			self.out = Out::Bad("Disallowed Host header");
 
 			return Next::write();
@@ -113,7 +154,7 @@ impl Handler<HttpStream> for IpfsHandler {
 		use Out::*;
 
 		match self.out {
-			OctetStream(ref bytes) => {
+			OctetStream(ref bytes, ref cid) => {
 				use mime::{Mime, TopLevel, SubLevel};
 
 				// `OctetStream` is not a valid variant, so need to construct
@@ -127,6 +168,13 @@ impl Handler<HttpStream> for IpfsHandler {
 				res.headers_mut().set(ContentLength(bytes.len() as u64));
 				res.headers_mut().set(ContentType(content_type));
 
+				if let Some(ref cid) = *cid {
+					res.headers_mut().set_raw("X-Ipfs-Cid", cid.clone());
+				}
+			},
+			Json(ref json) => {
+				res.headers_mut().set(ContentLength(json.len() as u64));
+				res.headers_mut().set(ContentType(mime!(Application/Json)));
 			},
 			NotFound(reason) => {
 				res.set_status(StatusCode::NotFound);
@@ -147,6 +195,15 @@ impl Handler<HttpStream> for IpfsHandler {
 			res.headers_mut().set(Vary::Items(vec!["Origin".into()]));
 		}
 
+		if let Some((_, ref mut entry)) = self.access_entry {
+			entry.status = res.status().to_u16();
+			entry.bytes = match self.out {
+				OctetStream(ref bytes, _) => bytes.len(),
+				Json(ref json) => json.len(),
+				NotFound(reason) | Bad(reason) => reason.len(),
+			};
+		}
+
 		Next::write()
 	}
 
@@ -155,11 +212,21 @@ impl Handler<HttpStream> for IpfsHandler {
 
 		// Get the data to write as a byte slice
 		let data = match self.out {
-			OctetStream(ref bytes) => &bytes,
+			OctetStream(ref bytes, _) => &bytes,
+			Json(ref json) => json.as_bytes(),
 			NotFound(reason) | Bad(reason) => reason.as_bytes(),
 		};
 
-		write_chunk(transport, &mut self.out_progress, data)
+		let next = write_chunk(transport, &mut self.out_progress, data);
+
+		if self.out_progress == data.len() {
+			if let (Some(log), Some((started, mut entry))) = (self.access_log.as_ref(), self.access_entry.take()) {
+				entry.duration = started.elapsed();
+				log.log(&entry);
+			}
+		}
+
+		next
 	}
 }
 
@@ -185,11 +252,11 @@ fn write_chunk<W: Write>(transport: &mut W, progress: &mut usize, data: &[u8]) -
 }
 
 /// Add current interface (default: "127.0.0.1:5001") to list of allowed hosts
-fn include_current_interface(mut hosts: Vec<Host>, interface: String, port: u16) -> Vec<Host> {
-	hosts.push(match port {
+fn include_current_interface(mut hosts: AllowedHosts, interface: String, port: u16) -> AllowedHosts {
+	hosts.add(&match port {
 		80 => interface,
 		_ => format!("{}:{}", interface, port),
-	}.into());
+	});
 
 	hosts
 }
@@ -198,18 +265,20 @@ pub fn start_server(
 	port: u16,
 	interface: String,
 	cors: DomainsValidation<AccessControlAllowOrigin>,
-	hosts: DomainsValidation<Host>,
-	client: Arc<BlockChainClient>
+	hosts: AllowedHosts,
+	cid_hash: CidHash,
+	client: Arc<BlockChainClient>,
+	access_log: Option<Arc<AccessLog>>,
 ) -> Result<Listening, ServerError> {
 
 	let ip: IpAddr = interface.parse().map_err(|_| ServerError::InvalidInterface)?;
 	let addr = SocketAddr::new(ip, port);
-	let hosts: Option<Vec<_>> = hosts.into();
-	let hosts: DomainsValidation<_> = hosts.map(move |hosts| include_current_interface(hosts, interface, port)).into();
+	let hosts = include_current_interface(hosts, interface, port);
+	let cid_index = Arc::new(CidIndex::default());
 
 	Ok(
 		http::hyper::Server::http(&addr)?
-			.handle(move |_| IpfsHandler::new(cors.clone(), hosts.clone(), client.clone()))
+			.handle(move |_| IpfsHandler::new(cors.clone(), hosts.clone(), cid_hash, cid_index.clone(), client.clone(), access_log.clone()))
 			.map(|(listening, srv)| {
 
 				::std::thread::spawn(move || {
@@ -16,18 +16,43 @@
 
 use {rlp, multihash, IpfsHandler};
 use error::{Error, Result};
-use cid::{ToCid, Codec};
+use cid::{Cid, ToCid, Codec, Version};
 
+use lru_cache::LruCache;
 use multihash::Hash;
-use util::{Bytes, H256};
+use rlp::RlpStream;
+use util::{Bytes, H256, Mutex};
 use ethcore::client::{BlockId, TransactionId};
 
 type Reason = &'static str;
 
+/// Maximal number of headers returned by a single `headers/get` request.
+pub const MAX_HEADERS: u64 = 512;
+
+/// Number of served SHA2-256 CIDs remembered by `CidIndex`.
+const CID_INDEX_SIZE: usize = 65536;
+
+/// Maps the SHA2-256 digests of recently served content to the Keccak-256 hashes
+/// it is stored under, so that SHA2-256 CIDs returned by the node can be fetched back.
+pub struct CidIndex {
+	hashes: Mutex<LruCache<H256, H256>>,
+}
+
+impl Default for CidIndex {
+	fn default() -> Self {
+		CidIndex {
+			hashes: Mutex::new(LruCache::new(CID_INDEX_SIZE)),
+		}
+	}
+}
+
 /// Keeps the state of the response to send out
 #[derive(Debug, PartialEq)]
 pub enum Out {
-	OctetStream(Bytes),
+	/// Raw content, along with its canonical CID (if it is content-addressed)
+	OctetStream(Bytes, Option<String>),
+	/// JSON document
+	Json(String),
 	NotFound(Reason),
 	Bad(Reason),
 }
@@ -42,6 +67,25 @@ impl IpfsHandler {
 				self.route_cid(arg).unwrap_or_else(Into::into)
 			},
 
+			"/api/v0/headers/get" => {
+				let start = query.and_then(|q| get_param(q, "start"));
+				let count = query.and_then(|q| get_param(q, "count"));
+
+				self.route_headers(start, count).unwrap_or_else(Into::into)
+			},
+
+			"/api/v0/tx/get" => {
+				let arg = query.and_then(|q| get_param(q, "arg")).unwrap_or("");
+
+				self.route_tx(arg).unwrap_or_else(Into::into)
+			},
+
+			"/api/v0/tx/meta" => {
+				let arg = query.and_then(|q| get_param(q, "arg")).unwrap_or("");
+
+				self.route_tx_meta(arg).unwrap_or_else(Into::into)
+			},
+
 			_ => Out::NotFound("Route not found")
 		}
 	}
@@ -49,62 +93,150 @@ impl IpfsHandler {
 	/// Attempt to read Content ID from `arg` query parameter, get a hash and
 	/// route further by the CID's codec.
 	fn route_cid(&self, cid: &str) -> Result<Out> {
-		let cid = cid.to_cid()?;
-
-		let mh = multihash::decode(&cid.hash)?;
+		let (codec, hash) = self.decode_cid(cid)?;
 
-		if mh.alg != Hash::Keccak256 { return Err(Error::UnsupportedHash); }
-
-		let hash: H256 = mh.digest.into();
-
-		match cid.codec {
+		let data = match codec {
 			Codec::EthereumBlock => self.block(hash),
 			Codec::EthereumBlockList => self.block_list(hash),
 			Codec::EthereumTx => self.transaction(hash),
 			Codec::EthereumStateTrie => self.state_trie(hash),
 			Codec::Raw => self.contract_code(hash),
 			_ => return Err(Error::UnsupportedCid),
+		}?;
+
+		let canonical = self.canonical_cid(codec, &data, hash)?;
+
+		Ok(Out::OctetStream(data, Some(canonical)))
+	}
+
+	/// Get the raw signed transaction for an `eth-tx` CID.
+	fn route_tx(&self, cid: &str) -> Result<Out> {
+		let hash = self.decode_tx_cid(cid)?;
+		let data = self.transaction(hash)?;
+		let canonical = self.canonical_cid(Codec::EthereumTx, &data, hash)?;
+
+		Ok(Out::OctetStream(data, Some(canonical)))
+	}
+
+	/// Get the location of the transaction for an `eth-tx` CID as a JSON sidecar
+	/// to the raw transaction, so it can be verified against its block.
+	fn route_tx_meta(&self, cid: &str) -> Result<Out> {
+		let hash = self.decode_tx_cid(cid)?;
+		let tx = self.client().transaction(TransactionId::Hash(hash)).ok_or(Error::TransactionNotFound)?;
+
+		Ok(Out::Json(tx_meta_json(&hash, &tx.block_hash, tx.block_number, tx.transaction_index)))
+	}
+
+	/// Get up to `count` consecutive block headers starting at block number `start`,
+	/// returned as a single RLP list. The list is cut short at the best block.
+	fn route_headers(&self, start: Option<&str>, count: Option<&str>) -> Result<Out> {
+		let start: u64 = start.and_then(|s| s.parse().ok()).ok_or(Error::InvalidHeaderRange)?;
+		let count: u64 = count.and_then(|c| c.parse().ok()).ok_or(Error::InvalidHeaderRange)?;
+
+		if count == 0 || count > MAX_HEADERS {
+			return Err(Error::InvalidHeaderRange);
+		}
+
+		let headers: Vec<_> = (start..start.saturating_add(count))
+			.map(|number| self.client().block_header(BlockId::Number(number)))
+			.take_while(Option::is_some)
+			.filter_map(|header| header)
+			.collect();
+
+		if headers.is_empty() {
+			return Err(Error::BlockNotFound);
+		}
+
+		let mut stream = RlpStream::new_list(headers.len());
+		for header in headers {
+			stream.append_raw(&header.into_inner(), 1);
+		}
+
+		Ok(Out::OctetStream(stream.out(), None))
+	}
+
+	/// Build the CIDv1 of `data` using the configured hash function. `hash` is the
+	/// Keccak-256 hash `data` was looked up by, remembered for SHA2-256 CIDs.
+	fn canonical_cid(&self, codec: Codec, data: &[u8], hash: H256) -> Result<String> {
+		let mh = multihash::encode(self.cid_hash(), data)?;
+
+		if self.cid_hash() == Hash::SHA2256 {
+			let digest = multihash::decode(&mh)?.digest.into();
+			self.cid_index().hashes.lock().insert(digest, hash);
+		}
+
+		Ok(Cid::new(codec, Version::V1, &mh).to_string())
+	}
+
+	/// Parse a Keccak-256 or SHA2-256 CID into its codec and the Keccak-256 hash it addresses.
+	/// SHA2-256 CIDs are only known if they were served by this node.
+	fn decode_cid(&self, cid: &str) -> Result<(Codec, H256)> {
+		let cid = cid.to_cid()?;
+
+		let mh = multihash::decode(&cid.hash)?;
+
+		let hash = match mh.alg {
+			Hash::Keccak256 => mh.digest.into(),
+			Hash::SHA2256 => {
+				let digest: H256 = mh.digest.into();
+				self.cid_index().hashes.lock().get_mut(&digest).cloned().ok_or(Error::UnknownCid)?
+			},
+			_ => return Err(Error::UnsupportedHash),
+		};
+
+		Ok((cid.codec, hash))
+	}
+
+	/// Parse an `eth-tx` CID into the transaction hash.
+	fn decode_tx_cid(&self, cid: &str) -> Result<H256> {
+		match self.decode_cid(cid)? {
+			(Codec::EthereumTx, hash) => Ok(hash),
+			_ => Err(Error::UnsupportedCid),
 		}
 	}
 
 	/// Get block header by hash as raw binary.
-	fn block(&self, hash: H256) -> Result<Out> {
+	fn block(&self, hash: H256) -> Result<Bytes> {
 		let block_id = BlockId::Hash(hash);
 		let block = self.client().block_header(block_id).ok_or(Error::BlockNotFound)?;
 
-		Ok(Out::OctetStream(block.into_inner()))
+		Ok(block.into_inner())
 	}
 
 	/// Get list of block ommers by hash as raw binary.
-	fn block_list(&self, hash: H256) -> Result<Out> {
+	fn block_list(&self, hash: H256) -> Result<Bytes> {
 		let uncles = self.client().find_uncles(&hash).ok_or(Error::BlockNotFound)?;
 
-		Ok(Out::OctetStream(rlp::encode_list(&uncles).to_vec()))
+		Ok(rlp::encode_list(&uncles).to_vec())
 	}
 
 	/// Get transaction by hash and return as raw binary.
-	fn transaction(&self, hash: H256) -> Result<Out> {
+	fn transaction(&self, hash: H256) -> Result<Bytes> {
 		let tx_id = TransactionId::Hash(hash);
 		let tx = self.client().transaction(tx_id).ok_or(Error::TransactionNotFound)?;
 
-		Ok(Out::OctetStream(rlp::encode(&*tx).to_vec()))
+		Ok(rlp::encode(&*tx).to_vec())
 	}
 
 	/// Get state trie node by hash and return as raw binary.
-	fn state_trie(&self, hash: H256) -> Result<Out> {
-		let data = self.client().state_data(&hash).ok_or(Error::StateRootNotFound)?;
-
-		Ok(Out::OctetStream(data))
+	fn state_trie(&self, hash: H256) -> Result<Bytes> {
+		self.client().state_data(&hash).ok_or(Error::StateRootNotFound)
 	}
 
 	/// Get state trie node by hash and return as raw binary.
-	fn contract_code(&self, hash: H256) -> Result<Out> {
-		let data = self.client().state_data(&hash).ok_or(Error::ContractNotFound)?;
-
-		Ok(Out::OctetStream(data))
+	fn contract_code(&self, hash: H256) -> Result<Bytes> {
+		self.client().state_data(&hash).ok_or(Error::ContractNotFound)
 	}
 }
 
+/// JSON sidecar describing where a transaction was included.
+fn tx_meta_json(hash: &H256, block_hash: &H256, block_number: u64, index: usize) -> String {
+	format!(
+		r#"{{"transactionHash":"0x{}","blockHash":"0x{}","blockNumber":{},"transactionIndex":{}}}"#,
+		hash.hex(), block_hash.hex(), block_number, index
+	)
+}
+
 /// Get a query parameter's value by name.
 fn get_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
 	query.split('&')
@@ -116,10 +248,11 @@ fn get_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
 mod tests {
 	use std::sync::Arc;
 	use super::*;
-	use ethcore::client::TestBlockChainClient;
+	use ethcore::client::{BlockChainClient, TestBlockChainClient, EachBlockWith};
+	use AllowedHosts;
 
 	fn get_mocked_handler() -> IpfsHandler {
-		IpfsHandler::new(None, None, Arc::new(TestBlockChainClient::new()))
+		IpfsHandler::new(None, AllowedHosts::any(), Hash::Keccak256, Arc::new(CidIndex::default()), Arc::new(TestBlockChainClient::new()), None)
 	}
 
 	#[test]
@@ -207,6 +340,60 @@ mod tests {
 		assert_eq!(Err(Error::UnsupportedCid), handler.route_cid(&cid));
 	}
 
+	#[test]
+	fn canonical_cid_keccak() {
+		let handler = get_mocked_handler();
+
+		// `eth-block` CIDv1 of the empty string with Keccak-256
+		let cid = handler.canonical_cid(Codec::EthereumBlock, &[], ::util::sha3::SHA3_EMPTY).unwrap();
+		let parsed = cid.to_cid().unwrap();
+		let mh = multihash::decode(&parsed.hash).unwrap();
+
+		assert_eq!(parsed.version, Version::V1);
+		assert_eq!(parsed.codec, Codec::EthereumBlock);
+		assert_eq!(mh.alg, Hash::Keccak256);
+		assert_eq!(H256::from(mh.digest), ::util::sha3::SHA3_EMPTY);
+	}
+
+	#[test]
+	fn canonical_cid_sha2() {
+		let handler = IpfsHandler::new(None, AllowedHosts::any(), Hash::SHA2256, Arc::new(CidIndex::default()), Arc::new(TestBlockChainClient::new()), None);
+
+		let cid = handler.canonical_cid(Codec::EthereumTx, b"foobar", 5.into()).unwrap();
+		let parsed = cid.to_cid().unwrap();
+		let mh = multihash::decode(&parsed.hash).unwrap();
+
+		assert_eq!(parsed.version, Version::V1);
+		assert_eq!(parsed.codec, Codec::EthereumTx);
+		assert_eq!(mh.alg, Hash::SHA2256);
+	}
+
+	#[test]
+	fn sha2_cid_round_trip() {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(1, EachBlockWith::Nothing);
+		let handler = IpfsHandler::new(None, AllowedHosts::any(), Hash::SHA2256, Arc::new(CidIndex::default()), client.clone(), None);
+		let header = client.block_header(BlockId::Number(1)).unwrap().into_inner();
+		let keccak_cid = Cid::new(Codec::EthereumBlock, Version::V1, &multihash::encode(Hash::Keccak256, &header).unwrap()).to_string();
+
+		let sha2_cid = match handler.route_cid(&keccak_cid) {
+			Ok(Out::OctetStream(_, Some(cid))) => cid,
+			other => panic!("Unexpected response: {:?}", other),
+		};
+
+		assert_eq!(handler.route_cid(&sha2_cid), Ok(Out::OctetStream(header, Some(sha2_cid.clone()))));
+	}
+
+	#[test]
+	fn sha2_cid_not_served() {
+		let handler = get_mocked_handler();
+
+		let mh = multihash::encode(Hash::SHA2256, b"foobar").unwrap();
+		let cid = Cid::new(Codec::EthereumTx, Version::V1, &mh).to_string();
+
+		assert_eq!(Err(Error::UnknownCid), handler.route_cid(&cid));
+	}
+
 	#[test]
 	fn route_block() {
 		let handler = get_mocked_handler();
@@ -234,6 +421,95 @@ mod tests {
 		assert_eq!(out, Out::Bad("CID parsing failed"));
 	}
 
+	#[test]
+	fn route_headers() {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let handler = IpfsHandler::new(None, AllowedHosts::any(), Hash::Keccak256, Arc::new(CidIndex::default()), client.clone(), None);
+
+		let out = handler.route("/api/v0/headers/get", Some("start=3&count=4"));
+
+		let expected: Vec<_> = (3..7)
+			.map(|n| client.block_header(BlockId::Number(n)).unwrap().into_inner())
+			.collect();
+		let mut stream = RlpStream::new_list(4);
+		for header in &expected {
+			stream.append_raw(header, 1);
+		}
+
+		assert_eq!(out, Out::OctetStream(stream.out(), None));
+	}
+
+	#[test]
+	fn route_headers_truncated_at_best_block() {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(5, EachBlockWith::Nothing);
+		let handler = IpfsHandler::new(None, AllowedHosts::any(), Hash::Keccak256, Arc::new(CidIndex::default()), client, None);
+
+		match handler.route("/api/v0/headers/get", Some("start=4&count=10")) {
+			Out::OctetStream(bytes, None) => assert_eq!(::rlp::Rlp::new(&bytes).item_count(), 2),
+			other => panic!("Unexpected response: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn route_headers_invalid_range() {
+		let handler = get_mocked_handler();
+
+		assert_eq!(handler.route("/api/v0/headers/get", None), Out::Bad("Invalid header range"));
+		assert_eq!(handler.route("/api/v0/headers/get", Some("start=0&count=0")), Out::Bad("Invalid header range"));
+		assert_eq!(handler.route("/api/v0/headers/get", Some("start=0&count=513")), Out::Bad("Invalid header range"));
+		assert_eq!(handler.route("/api/v0/headers/get", Some("start=100&count=1")), Out::NotFound("Block not found"));
+	}
+
+	#[test]
+	fn route_tx() {
+		let handler = get_mocked_handler();
+
+		// `eth-tx` with Keccak-256
+		let arg = "arg=z44VCrqbpbPcb8SUBc8Tba4EaKuoDz2grdEoQXx4TP7WYh9ZGBu";
+
+		assert_eq!(handler.route("/api/v0/tx/get", Some(arg)), Out::NotFound("Transaction not found"));
+		assert_eq!(handler.route("/api/v0/tx/meta", Some(arg)), Out::NotFound("Transaction not found"));
+	}
+
+	#[test]
+	fn route_tx_found() {
+		let client = Arc::new(TestBlockChainClient::new());
+		client.add_blocks(2, EachBlockWith::Transaction);
+		let handler = IpfsHandler::new(None, AllowedHosts::any(), Hash::Keccak256, Arc::new(CidIndex::default()), client.clone(), None);
+
+		let block_hash = client.block_hash(BlockId::Number(1)).unwrap();
+		let tx = client.block(BlockId::Number(1)).unwrap().transactions().remove(0);
+		let raw = ::rlp::encode(&tx).to_vec();
+		let cid = Cid::new(Codec::EthereumTx, Version::V1, &multihash::encode(Hash::Keccak256, &raw).unwrap()).to_string();
+		let arg = format!("arg={}", cid);
+
+		assert_eq!(handler.route("/api/v0/tx/get", Some(&arg)), Out::OctetStream(raw, Some(cid.clone())));
+		assert_eq!(handler.route("/api/v0/tx/meta", Some(&arg)), Out::Json(tx_meta_json(&tx.hash(), &block_hash, 1, 0)));
+	}
+
+	#[test]
+	fn route_tx_invalid_codec() {
+		let handler = get_mocked_handler();
+
+		// `eth-block` with Keccak-256
+		let arg = "arg=z43AaGF5tmkT9SEX6urrhwpEW5ZSaACY73Vw357ZXTsur2fR8BM";
+
+		assert_eq!(handler.route("/api/v0/tx/get", Some(arg)), Out::Bad("CID codec not supported"));
+		assert_eq!(handler.route("/api/v0/tx/meta", Some(arg)), Out::Bad("CID codec not supported"));
+	}
+
+	#[test]
+	fn tx_meta_sidecar() {
+		let json = tx_meta_json(&H256::from(1), &H256::from(2), 3, 4);
+
+		assert_eq!(json, format!(
+			r#"{{"transactionHash":"0x{}","blockHash":"0x{}","blockNumber":3,"transactionIndex":4}}"#,
+			H256::from(1).hex(), H256::from(2).hex()
+		));
+	}
+
 	#[test]
 	fn route_invalid_route() {
 		let handler = get_mocked_handler();
@@ -55,6 +55,42 @@ export default {
     }
   },
 
+  chainSpec: {
+    section: SECTION_NET,
+    desc: 'Returns the normalized parameters of the chain spec the node enforces.',
+    params: [],
+    returns: {
+      type: Object,
+      desc: 'The chain spec object',
+      details: {
+        name: {
+          type: String,
+          desc: 'Spec name'
+        },
+        engine: {
+          type: String,
+          desc: 'Name of the consensus engine'
+        },
+        networkId: {
+          type: Quantity,
+          desc: 'Network id'
+        },
+        transitions: {
+          type: Object,
+          desc: 'Rule transitions mapped to the first block they are enforced at'
+        },
+        builtins: {
+          type: Object,
+          desc: 'Builtin contract addresses mapped to their activation block'
+        },
+        engineParams: {
+          type: Object,
+          desc: 'Additional engine parameters, e.g. validator set definition'
+        }
+      }
+    }
+  },
+
   chainStatus: {
     section: SECTION_NET,
     desc: 'Returns the information on warp sync blocks',
@@ -16,7 +16,7 @@
 
 //! Engine deserialization.
 
-use super::{Ethash, InstantSeal, BasicAuthority, AuthorityRound, Tendermint};
+use super::{Ethash, InstantSeal, BasicAuthority, AuthorityRound, Tendermint, Clique, ExternalSeal};
 
 /// Engine deserialization.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -37,7 +37,13 @@ pub enum Engine {
 	AuthorityRound(AuthorityRound),
 	/// Tendermint engine.
 	#[serde(rename="tendermint")]
-	Tendermint(Tendermint)
+	Tendermint(Tendermint),
+	/// Clique engine.
+	#[serde(rename="clique")]
+	Clique(Clique),
+	/// Engine accepting blocks signed by an authorized external producer.
+	#[serde(rename="externalSeal")]
+	ExternalSeal(ExternalSeal),
 }
 
 #[cfg(test)]
@@ -78,6 +84,27 @@ mod tests {
 		}"#;
 
 		let _deserialized: Engine = serde_json::from_str(s).unwrap();
+
+		let s = r#"{
+			"clique": {
+				"params": {
+					"gasLimitBoundDivisor": "0x0400",
+					"period": 1,
+					"epoch": 30000
+				}
+			}
+		}"#;
+
+		let _deserialized: Engine = serde_json::from_str(s).unwrap();
+
+		let s = r#"{
+			"externalSeal": {
+				"params": {
+					"producers": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+				}
+			}
+		}"#;
+
+		let _deserialized: Engine = serde_json::from_str(s).unwrap();
 	}
 }
-
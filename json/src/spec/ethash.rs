@@ -18,6 +18,7 @@
 
 use uint::Uint;
 use hash::Address;
+use spec::IrregularStateChange;
 
 /// Deserializable doppelganger of EthashParams.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -112,6 +113,10 @@ pub struct EthashParams {
 	/// See main EthashParams docs.
 	#[serde(rename="minGasPrice")]
 	pub min_gas_price: Option<Uint>,
+
+	/// See main EthashParams docs.
+	#[serde(rename="irregularStateChanges")]
+	pub irregular_state_changes: Option<Vec<IrregularStateChange>>,
 }
 
 /// Ethash engine deserialization.
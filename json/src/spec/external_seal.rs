@@ -0,0 +1,54 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! External seal params deserialization.
+
+use hash::Address;
+
+/// External seal params deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ExternalSealParams {
+	/// Accounts authorized to produce blocks.
+	pub producers: Vec<Address>,
+}
+
+/// External seal engine deserialization.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ExternalSeal {
+	/// External seal params.
+	pub params: ExternalSealParams,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use hash::Address;
+	use std::str::FromStr;
+	use util::H160;
+	use spec::external_seal::ExternalSeal;
+
+	#[test]
+	fn external_seal_deserialization() {
+		let s = r#"{
+			"params": {
+				"producers": ["0xc6d9d2cd449a754c494264e1809c50e34d64562b"]
+			}
+		}"#;
+
+		let deserialized: ExternalSeal = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.params.producers, vec![Address(H160::from_str("c6d9d2cd449a754c494264e1809c50e34d64562b").unwrap())]);
+	}
+}
@@ -0,0 +1,85 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Irregular state change deserialization.
+
+use hash::{Address, H256};
+use uint::Uint;
+
+/// Balance moved by an irregular state change.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct BalanceMove {
+	/// Account to take the balance from.
+	pub from: Address,
+	/// Account to credit.
+	pub to: Address,
+	/// Amount to move. Whole balance of `from` if not given.
+	pub value: Option<Uint>,
+}
+
+/// Storage slot overwritten by an irregular state change.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct StorageWrite {
+	/// Account owning the storage.
+	pub address: Address,
+	/// Storage key.
+	pub key: H256,
+	/// New value.
+	pub value: H256,
+}
+
+/// One-off state changes applied at the beginning of a block.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct IrregularStateChange {
+	/// Block number the changes are applied at.
+	pub block: Uint,
+	/// Balance moves.
+	#[serde(rename="balanceMoves")]
+	pub balance_moves: Option<Vec<BalanceMove>>,
+	/// Storage writes.
+	#[serde(rename="storageWrites")]
+	pub storage_writes: Option<Vec<StorageWrite>>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use spec::irregular::IrregularStateChange;
+
+	#[test]
+	fn irregular_state_change_deserialization() {
+		let s = r#"{
+			"block": "0x1d4c00",
+			"balanceMoves": [{
+				"from": "0x304a554a310c7e546dfe434669c62820b7d83490",
+				"to": "0xbf4ed7b27f1d666546e30d74d50d173d20bca754"
+			}, {
+				"from": "0x914d1b8b43e92723e64fd0a06f5bdb8dd9b10c79",
+				"to": "0xbf4ed7b27f1d666546e30d74d50d173d20bca754",
+				"value": "0x0de0b6b3a7640000"
+			}],
+			"storageWrites": [{
+				"address": "0xbf4ed7b27f1d666546e30d74d50d173d20bca754",
+				"key": "0x0000000000000000000000000000000000000000000000000000000000000001",
+				"value": "0x0000000000000000000000000000000000000000000000000000000000000002"
+			}]
+		}"#;
+
+		let deserialized: IrregularStateChange = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.balance_moves.unwrap().len(), 2);
+		assert_eq!(deserialized.storage_writes.unwrap().len(), 1);
+	}
+}
@@ -20,28 +20,36 @@ pub mod account;
 pub mod builtin;
 pub mod genesis;
 pub mod params;
+pub mod schedule;
 pub mod spec;
 pub mod seal;
 pub mod engine;
 pub mod state;
 pub mod ethash;
+pub mod irregular;
 pub mod validator_set;
 pub mod instant_seal;
 pub mod basic_authority;
 pub mod authority_round;
 pub mod tendermint;
+pub mod clique;
+pub mod external_seal;
 
 pub use self::account::Account;
 pub use self::builtin::{Builtin, Pricing, Linear};
 pub use self::genesis::Genesis;
 pub use self::params::Params;
+pub use self::schedule::ScheduleOverride;
 pub use self::spec::Spec;
 pub use self::seal::{Seal, Ethereum, AuthorityRoundSeal, TendermintSeal};
 pub use self::engine::Engine;
 pub use self::state::State;
 pub use self::ethash::{Ethash, EthashParams};
+pub use self::irregular::{IrregularStateChange, BalanceMove, StorageWrite};
 pub use self::validator_set::ValidatorSet;
 pub use self::instant_seal::{InstantSeal, InstantSealParams};
 pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
 pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
 pub use self::tendermint::{Tendermint, TendermintParams};
+pub use self::clique::{Clique, CliqueParams};
+pub use self::external_seal::{ExternalSeal, ExternalSealParams};
@@ -17,7 +17,9 @@
 //! Spec params deserialization.
 
 use uint::Uint;
-use hash::H256;
+use hash::{H256, Address};
+use bytes::Bytes;
+use spec::ScheduleOverride;
 
 /// Spec params.
 #[derive(Debug, PartialEq, Deserialize)]
@@ -28,6 +30,9 @@ pub struct Params {
 	/// Maximum size of extra data.
 	#[serde(rename="maximumExtraDataSize")]
 	pub maximum_extra_data_size: Uint,
+	/// Prefix the extra data of all blocks but genesis must start with.
+	#[serde(rename="extraDataPrefix")]
+	pub extra_data_prefix: Option<Bytes>,
 	/// Minimum gas limit.
 	#[serde(rename="minGasLimit")]
 	pub min_gas_limit: Uint,
@@ -54,8 +59,39 @@ pub struct Params {
 	#[serde(rename="eip98Transition")]
 	pub eip98_transition: Option<Uint>,
 	/// See `CommonParams` docs.
+	#[serde(rename="eip140Transition")]
+	pub eip140_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip211Transition")]
+	pub eip211_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip214Transition")]
+	pub eip214_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip210Transition")]
+	pub eip210_transition: Option<Uint>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip210ContractAddress")]
+	pub eip210_contract_address: Option<Address>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip210ContractCode")]
+	pub eip210_contract_code: Option<Bytes>,
+	/// See `CommonParams` docs.
+	#[serde(rename="eip210ContractGas")]
+	pub eip210_contract_gas: Option<Uint>,
+	/// See `CommonParams` docs.
 	#[serde(rename="validateReceipts")]
 	pub validate_receipts: Option<bool>,
+	/// First block transactions may commit to the chain ID in (EIP-155).
+	/// Ethash chains take it from the engine params instead.
+	#[serde(rename="eip155Transition")]
+	pub eip155_transition: Option<Uint>,
+	/// First block transactions must commit to the chain ID in.
+	#[serde(rename="requireChainIdTransition")]
+	pub require_chain_id_transition: Option<Uint>,
+	/// Schedule changes activated at given blocks.
+	#[serde(rename="scheduleOverrides")]
+	pub schedule_overrides: Option<Vec<ScheduleOverride>>,
 }
 
 #[cfg(test)]
@@ -68,11 +104,22 @@ mod tests {
 		let s = r#"{
 			"homesteadTransition": "0x118c30",
 			"maximumExtraDataSize": "0x20",
+			"extraDataPrefix": "0x7072697661746500",
 			"networkID" : "0x1",
 			"chainID" : "0x15",
 			"subprotocolName" : "exp",
 			"minGasLimit": "0x1388",
-			"accountStartNonce": "0x00"
+			"accountStartNonce": "0x00",
+			"eip155Transition": "0x0",
+			"requireChainIdTransition": "0x0",
+			"eip140Transition": "0x0",
+			"eip211Transition": "0x0",
+			"eip214Transition": "0x0",
+			"eip210Transition": "0x0",
+			"eip210ContractAddress": "0x00000000000000000000000000000000000000f0",
+			"eip210ContractCode": "0x600035600055",
+			"eip210ContractGas": "0xf4240",
+			"scheduleOverrides": [{ "block": "0x10", "sloadGas": "0x320" }]
 		}"#;
 
 		let _deserialized: Params = serde_json::from_str(s).unwrap();
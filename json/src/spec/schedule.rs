@@ -0,0 +1,163 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Schedule overrides deserialization.
+
+use uint::Uint;
+
+/// Schedule changes activated at given block. Fields left out keep their previous values.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct ScheduleOverride {
+	/// Block the changes activate at.
+	pub block: Uint,
+	/// See `Schedule` docs.
+	#[serde(rename="exceptionalFailedCodeDeposit")]
+	pub exceptional_failed_code_deposit: Option<bool>,
+	/// See `Schedule` docs.
+	#[serde(rename="haveDelegateCall")]
+	pub have_delegate_call: Option<bool>,
+	/// See `Schedule` docs.
+	#[serde(rename="stackLimit")]
+	pub stack_limit: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="maxDepth")]
+	pub max_depth: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="expGas")]
+	pub exp_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="expByteGas")]
+	pub exp_byte_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sha3Gas")]
+	pub sha3_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sha3WordGas")]
+	pub sha3_word_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sloadGas")]
+	pub sload_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sstoreSetGas")]
+	pub sstore_set_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sstoreResetGas")]
+	pub sstore_reset_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="sstoreRefundGas")]
+	pub sstore_refund_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="jumpdestGas")]
+	pub jumpdest_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="logGas")]
+	pub log_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="logDataGas")]
+	pub log_data_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="logTopicGas")]
+	pub log_topic_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="createGas")]
+	pub create_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="callGas")]
+	pub call_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="callStipend")]
+	pub call_stipend: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="callValueTransferGas")]
+	pub call_value_transfer_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="callNewAccountGas")]
+	pub call_new_account_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="suicideRefundGas")]
+	pub suicide_refund_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="memoryGas")]
+	pub memory_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="createDataGas")]
+	pub create_data_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="createDataLimit")]
+	pub create_data_limit: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="txGas")]
+	pub tx_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="txCreateGas")]
+	pub tx_create_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="txDataZeroGas")]
+	pub tx_data_zero_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="txDataNonZeroGas")]
+	pub tx_data_non_zero_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="copyGas")]
+	pub copy_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="extcodesizeGas")]
+	pub extcodesize_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="extcodecopyBaseGas")]
+	pub extcodecopy_base_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="balanceGas")]
+	pub balance_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="suicideGas")]
+	pub suicide_gas: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="suicideToNewAccountCost")]
+	pub suicide_to_new_account_cost: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="subGasCapDivisor")]
+	pub sub_gas_cap_divisor: Option<Uint>,
+	/// See `Schedule` docs.
+	#[serde(rename="noEmpty")]
+	pub no_empty: Option<bool>,
+	/// See `Schedule` docs.
+	#[serde(rename="killEmpty")]
+	pub kill_empty: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use uint::Uint;
+	use util::U256;
+	use spec::schedule::ScheduleOverride;
+
+	#[test]
+	fn schedule_override_deserialization() {
+		let s = r#"{
+			"block": "0x10",
+			"sloadGas": "0x320",
+			"noEmpty": true
+		}"#;
+
+		let deserialized: ScheduleOverride = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.block, Uint(U256::from(0x10)));
+		assert_eq!(deserialized.sload_gas, Some(Uint(U256::from(800))));
+		assert_eq!(deserialized.no_empty, Some(true));
+		assert_eq!(deserialized.call_gas, None);
+	}
+}
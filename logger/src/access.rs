@@ -0,0 +1,279 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HTTP access log shared by the RPC and IPFS servers.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
+use hyper::{header, RequestUri};
+use hyper::net::HttpStream;
+use hyper::server::Request;
+use parking_lot::Mutex;
+use time;
+
+/// Line format of the access log.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AccessLogFormat {
+	/// NCSA common log format.
+	Common,
+	/// NCSA combined log format (common + referer and user agent).
+	Combined,
+}
+
+impl Default for AccessLogFormat {
+	fn default() -> Self {
+		AccessLogFormat::Combined
+	}
+}
+
+impl FromStr for AccessLogFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"common" => Ok(AccessLogFormat::Common),
+			"combined" => Ok(AccessLogFormat::Combined),
+			other => Err(format!("Invalid access log format given: {}", other)),
+		}
+	}
+}
+
+/// Single served request.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AccessLogEntry {
+	/// Client identifier (address or origin).
+	pub client: Option<String>,
+	/// HTTP method.
+	pub method: String,
+	/// Requested path (including query).
+	pub path: String,
+	/// Response status code.
+	pub status: u16,
+	/// Response body size in bytes.
+	pub bytes: usize,
+	/// `Referer` request header.
+	pub referer: Option<String>,
+	/// `User-Agent` request header.
+	pub user_agent: Option<String>,
+	/// Time taken to serve the request.
+	pub duration: Duration,
+}
+
+/// Appends served requests to a file, one line per request.
+///
+/// With a size limit the file is rotated once it grows above it: the current file
+/// is renamed to `<path>.1` (replacing the previous one) and a new one is started.
+///
+/// Clients are identified by the peer address of the connection. The `X-Forwarded-For`
+/// header is only used for requests coming from the trusted proxy.
+pub struct AccessLog {
+	format: AccessLogFormat,
+	path: String,
+	max_size: Option<u64>,
+	trusted_proxy: Option<IpAddr>,
+	file: Mutex<(File, u64)>,
+}
+
+impl AccessLog {
+	/// Open (or create) the access log at `path`, rotated when bigger than `max_size` bytes.
+	pub fn open(path: &str, format: AccessLogFormat, max_size: Option<u64>) -> Result<Self, String> {
+		Ok(AccessLog {
+			format: format,
+			path: path.into(),
+			max_size: max_size,
+			trusted_proxy: None,
+			file: Mutex::new(Self::open_file(path)?),
+		})
+	}
+
+	/// Identify clients by `X-Forwarded-For` of requests coming from given proxy.
+	pub fn with_trusted_proxy(mut self, proxy: Option<IpAddr>) -> Self {
+		self.trusted_proxy = proxy;
+		self
+	}
+
+	/// Start an entry from the request line and headers, completed once the request is answered.
+	pub fn entry(&self, req: &Request<HttpStream>) -> AccessLogEntry {
+		let path = match *req.uri() {
+			RequestUri::AbsolutePath { ref path, query: Some(ref query) } => format!("{}?{}", path, query),
+			RequestUri::AbsolutePath { ref path, query: None } => path.clone(),
+			ref other => other.to_string(),
+		};
+		let forwarded_for = req.headers().get_raw("X-Forwarded-For")
+			.and_then(|raw| raw.one())
+			.map(|raw| String::from_utf8_lossy(raw).into_owned());
+
+		AccessLogEntry {
+			client: client_address(req.transport().0.peer_addr().ok(), forwarded_for.as_ref().map(String::as_str), self.trusted_proxy),
+			method: req.method().to_string(),
+			path: path,
+			status: 0,
+			bytes: 0,
+			referer: req.headers().get::<header::Referer>().map(|referer| referer.to_string()),
+			user_agent: req.headers().get::<header::UserAgent>().map(|agent| agent.to_string()),
+			duration: Default::default(),
+		}
+	}
+
+	fn open_file(path: &str) -> Result<(File, u64), String> {
+		let file = fs::OpenOptions::new()
+			.append(true).create(true).open(path)
+			.map_err(|_| format!("Cannot write to access log file given: {}", path))?;
+		let size = file.metadata().map(|meta| meta.len()).unwrap_or(0);
+		Ok((file, size))
+	}
+
+	/// Append an entry to the log.
+	pub fn log(&self, entry: &AccessLogEntry) {
+		let timestamp = time::strftime("%d/%b/%Y:%H:%M:%S %z", &time::now())
+			.expect("Format string is valid; qed");
+		let line = format_entry(self.format, entry, &timestamp);
+
+		let mut file = self.file.lock();
+		// ignore errors - there's nothing we can do
+		if writeln!(&mut file.0, "{}", line).is_ok() {
+			file.1 += line.len() as u64 + 1;
+		}
+
+		// keep appending to the current file if it can't be rotated
+		if self.max_size.map_or(false, |max| file.1 > max) {
+			let _ = self.rotate(&mut file);
+		}
+	}
+
+	fn rotate(&self, file: &mut (File, u64)) -> Result<(), String> {
+		let rotated = format!("{}.1", self.path);
+		fs::rename(&self.path, &rotated)
+			.map_err(|e| format!("Cannot rotate access log {}: {}", self.path, e))?;
+		*file = Self::open_file(&self.path)?;
+		Ok(())
+	}
+}
+
+/// Address of the client of a request received from `peer`.
+///
+/// For requests from the trusted proxy it's the last `X-Forwarded-For` entry, added by the proxy itself;
+/// the preceding ones are supplied by the client and can't be trusted.
+fn client_address(peer: Option<SocketAddr>, forwarded_for: Option<&str>, trusted_proxy: Option<IpAddr>) -> Option<String> {
+	let peer = peer.map(|peer| peer.ip());
+	let forwarded = match forwarded_for {
+		Some(forwarded) if peer.is_some() && peer == trusted_proxy => forwarded.rsplit(',').next().map(str::trim),
+		_ => None,
+	};
+
+	match forwarded {
+		Some(client) if !client.is_empty() => Some(client.into()),
+		_ => peer.map(|peer| peer.to_string()),
+	}
+}
+
+fn format_entry(format: AccessLogFormat, entry: &AccessLogEntry, timestamp: &str) -> String {
+	let micros = entry.duration.as_secs() * 1_000_000 + entry.duration.subsec_nanos() as u64 / 1_000;
+	let common = format!(
+		"{} - - [{}] \"{} {} HTTP/1.1\" {} {}",
+		entry.client.as_ref().map_or("-", |c| c.as_str()),
+		timestamp,
+		entry.method,
+		entry.path,
+		entry.status,
+		entry.bytes,
+	);
+
+	match format {
+		AccessLogFormat::Common => format!("{} {}", common, micros),
+		AccessLogFormat::Combined => format!(
+			"{} \"{}\" \"{}\" {}",
+			common,
+			entry.referer.as_ref().map_or("-", |r| r.as_str()),
+			entry.user_agent.as_ref().map_or("-", |u| u.as_str()),
+			micros,
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::{AccessLog, AccessLogEntry, AccessLogFormat, client_address, format_entry};
+
+	fn entry() -> AccessLogEntry {
+		AccessLogEntry {
+			client: Some("127.0.0.1".into()),
+			method: "GET".into(),
+			path: "/api/v0/block/get?arg=foo".into(),
+			status: 200,
+			bytes: 512,
+			referer: None,
+			user_agent: Some("curl/7.47.0".into()),
+			duration: Duration::from_millis(3),
+		}
+	}
+
+	#[test]
+	fn should_format_common_entry() {
+		let line = format_entry(AccessLogFormat::Common, &entry(), "10/Oct/2017:13:55:36 +0000");
+
+		assert_eq!(line, "127.0.0.1 - - [10/Oct/2017:13:55:36 +0000] \"GET /api/v0/block/get?arg=foo HTTP/1.1\" 200 512 3000");
+	}
+
+	#[test]
+	fn should_format_combined_entry() {
+		let line = format_entry(AccessLogFormat::Combined, &entry(), "10/Oct/2017:13:55:36 +0000");
+
+		assert_eq!(line, "127.0.0.1 - - [10/Oct/2017:13:55:36 +0000] \"GET /api/v0/block/get?arg=foo HTTP/1.1\" 200 512 \"-\" \"curl/7.47.0\" 3000");
+	}
+
+	#[test]
+	fn should_trust_forwarded_for_only_from_proxy() {
+		let proxy = "10.0.0.1".parse().unwrap();
+		let client = "192.168.1.5:40000".parse().ok();
+		let through_proxy = "10.0.0.1:40000".parse().ok();
+
+		assert_eq!(client_address(client, None, Some(proxy)), Some("192.168.1.5".into()));
+		assert_eq!(client_address(client, Some("1.2.3.4"), None), Some("192.168.1.5".into()));
+		assert_eq!(client_address(client, Some("1.2.3.4"), Some(proxy)), Some("192.168.1.5".into()));
+		assert_eq!(client_address(through_proxy, Some("1.2.3.4, 5.6.7.8"), Some(proxy)), Some("5.6.7.8".into()));
+		assert_eq!(client_address(through_proxy, None, Some(proxy)), Some("10.0.0.1".into()));
+		assert_eq!(client_address(None, Some("1.2.3.4"), Some(proxy)), None);
+	}
+
+	#[test]
+	fn should_rotate_above_max_size() {
+		let dir = ::std::env::temp_dir().join(format!("parity-access-log-{}", ::time::precise_time_ns()));
+		::std::fs::create_dir_all(&dir).unwrap();
+		let path = dir.join("access.log");
+		let path = path.to_str().unwrap();
+
+		let log = AccessLog::open(path, AccessLogFormat::Common, Some(150)).unwrap();
+		log.log(&entry());
+		assert!(!::std::path::Path::new(&format!("{}.1", path)).exists());
+		log.log(&entry());
+
+		assert!(::std::path::Path::new(&format!("{}.1", path)).exists());
+		assert_eq!(::std::fs::metadata(path).unwrap().len(), 0);
+		::std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn should_parse_format() {
+		assert_eq!("common".parse(), Ok(AccessLogFormat::Common));
+		assert_eq!("combined".parse(), Ok(AccessLogFormat::Combined));
+		assert!("apache".parse::<AccessLogFormat>().is_err());
+	}
+}
@@ -26,7 +26,9 @@ extern crate time;
 extern crate lazy_static;
 extern crate parking_lot;
 extern crate ansi_term;
+extern crate hyper;
 
+mod access;
 mod rotating;
 
 use std::{env, thread, fs};
@@ -38,6 +40,7 @@ use regex::Regex;
 use ansi_term::Colour;
 use parking_lot::Mutex;
 
+pub use access::{AccessLog, AccessLogEntry, AccessLogFormat};
 pub use rotating::{RotatingLogger, init_log};
 
 #[derive(Debug, PartialEq, Clone)]
@@ -0,0 +1,101 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ABIs of contracts published through the on-chain registry.
+//!
+//! A contract's ABI is looked up under the registry name its address reverse-resolves to:
+//! the `ABI` entry of that name holds the Keccak hash of the ABI JSON, whose content
+//! is resolved through GithubHint and checked against the hash.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::sync::Arc;
+
+use ethcore::client::{BlockChainClient, BlockId, Client};
+use ethcore_rpc::abi_registry::AbiSource;
+use hash_fetch::{self, HashFetch};
+use hash_fetch::fetch::Client as FetchClient;
+use hash_fetch::urlhint::ContractClient;
+use parity_reactor::Remote;
+use util::{Address, Bytes, Hashable};
+
+/// Registry key of the ABI hash.
+const ABI_KEY: &'static str = "ABI";
+
+/// Calls contracts of the registry at the latest block.
+struct Contracts {
+	client: Arc<Client>,
+}
+
+impl ContractClient for Contracts {
+	fn registrar(&self) -> Result<Address, String> {
+		self.client.registrar_address().ok_or_else(|| "Registrar not available".into())
+	}
+
+	fn call(&self, address: Address, data: Bytes) -> Result<Bytes, String> {
+		self.client.call_contract(BlockId::Latest, address, data)
+	}
+}
+
+/// ABIs fetched through the on-chain registry.
+pub struct RegistryAbis {
+	client: Arc<Client>,
+	fetch: hash_fetch::Client,
+}
+
+impl RegistryAbis {
+	pub fn new(client: Arc<Client>, fetch: FetchClient, remote: Remote) -> Self {
+		let contracts = Arc::new(Contracts { client: client.clone() });
+		RegistryAbis {
+			client: client,
+			fetch: hash_fetch::Client::with_fetch(contracts, fetch, remote),
+		}
+	}
+}
+
+impl AbiSource for RegistryAbis {
+	fn fetch(&self, address: &Address, on_done: Box<Fn(Result<Vec<u8>, String>) + Send>) {
+		let hash = match *self.client.registrar() {
+			Some(ref registrar) => registrar.reverse(address)
+				.and_then(|name| match name.is_empty() {
+					true => Err(format!("No registry name for {:?}", address)),
+					false => registrar.get_data(&name.as_bytes().sha3(), ABI_KEY),
+				})
+				.and_then(|hash| match hash.is_zero() {
+					true => Err(format!("No ABI registered for {:?}", address)),
+					false => Ok(hash),
+				}),
+			None => Err("Registrar not available".into()),
+		};
+
+		let hash = match hash {
+			Ok(hash) => hash,
+			Err(e) => return on_done(Err(e)),
+		};
+
+		self.fetch.fetch(hash, Box::new(move |result| {
+			let result = result
+				.map_err(|e| format!("Couldn't fetch ABI: {:?}", e))
+				.and_then(|path| {
+					let mut json = Vec::new();
+					let read = File::open(&path).and_then(|mut file| file.read_to_end(&mut json));
+					let _ = fs::remove_file(&path);
+					read.map(|_| json).map_err(|e| format!("Couldn't read ABI: {}", e))
+				});
+			on_done(result);
+		}));
+	}
+}
@@ -16,8 +16,9 @@
 
 use std::path::PathBuf;
 use ethcore::ethstore::{EthStore, SecretStore, import_accounts, read_geth_accounts};
-use ethcore::ethstore::dir::RootDiskDirectory;
+use ethcore::ethstore::dir::{RootDiskDirectory, KeyDirectory};
 use ethcore::ethstore::SecretVaultRef;
+use ethcore::ethstore::backup;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
 use helpers::{password_prompt, password_from_file};
 use params::SpecType;
@@ -27,7 +28,8 @@ pub enum AccountCmd {
 	New(NewAccount),
 	List(ListAccounts),
 	Import(ImportAccounts),
-	ImportFromGeth(ImportFromGethAccounts)
+	ImportFromGeth(ImportFromGethAccounts),
+	Restore(RestoreAccounts),
 }
 
 #[derive(Debug, PartialEq)]
@@ -61,12 +63,26 @@ pub struct ImportFromGethAccounts {
 	pub spec: SpecType,
 }
 
+/// Parameters for restoring keys from a backup.
+#[derive(Debug, PartialEq)]
+pub struct RestoreAccounts {
+	/// backup target, see `--keys-backup`
+	pub target: String,
+	/// backup to restore, the latest one if `None`
+	pub backup: Option<u64>,
+	/// directory to restore keys to
+	pub to: String,
+	pub spec: SpecType,
+	pub password_file: Option<String>,
+}
+
 pub fn execute(cmd: AccountCmd) -> Result<String, String> {
 	match cmd {
 		AccountCmd::New(new_cmd) => new(new_cmd),
 		AccountCmd::List(list_cmd) => list(list_cmd),
 		AccountCmd::Import(import_cmd) => import(import_cmd),
-		AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd)
+		AccountCmd::ImportFromGeth(import_geth_cmd) => import_geth(import_geth_cmd),
+		AccountCmd::Restore(restore_cmd) => restore(restore_cmd),
 	}
 }
 
@@ -133,3 +149,17 @@ fn import_geth(i: ImportFromGethAccounts) -> Result<String, String> {
 		Err(err) => Err(format!("Import geth accounts failed. {}", err))
 	}
 }
+
+fn restore(r: RestoreAccounts) -> Result<String, String> {
+	let password: String = match r.password_file {
+		Some(file) => password_from_file(file)?,
+		None => password_prompt()?,
+	};
+
+	let target = backup::target_from_str(&r.target)?;
+	let dir = keys_dir(r.to, r.spec)?;
+	let path = dir.path().cloned().expect("disk directory always has a path; qed");
+	let restored = backup::restore(&path, &*target, r.backup, &password)
+		.map_err(|e| format!("Restoring keys failed: {}", e))?;
+	Ok(format!("{} file(s) restored", restored.len()))
+}
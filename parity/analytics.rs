@@ -0,0 +1,409 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Export of chain data into flat, columnar files for analytics tools.
+//!
+//! Every table is written into its own file inside the output directory.
+//! Progress is tracked in a manifest, so an interrupted export can be resumed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use rustc_serialize::json;
+use rustc_serialize::hex::ToHex;
+use util::{H256, U256, Address};
+use ethcore::client::{BlockChainClient, BlockId, TransactionId};
+use ethcore::transaction::{Action, SignedTransaction};
+use ethcore::trace::trace;
+
+/// Version of the table layouts. Bump on any column change.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &'static str = "manifest.json";
+/// Number of blocks exported between manifest updates.
+const CHECKPOINT_INTERVAL: u64 = 1000;
+
+/// Output file format.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum AnalyticsFormat {
+	Csv,
+}
+
+impl Default for AnalyticsFormat {
+	fn default() -> Self {
+		AnalyticsFormat::Csv
+	}
+}
+
+impl FromStr for AnalyticsFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"csv" => Ok(AnalyticsFormat::Csv),
+			"parquet" => Err("Parquet output is not supported by this build. Use 'csv'.".into()),
+			x => Err(format!("Invalid analytics format: {}", x)),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Table {
+	Blocks,
+	Transactions,
+	Receipts,
+	Logs,
+	Traces,
+}
+
+impl Table {
+	fn all() -> [Table; 5] {
+		[Table::Blocks, Table::Transactions, Table::Receipts, Table::Logs, Table::Traces]
+	}
+
+	fn name(&self) -> &'static str {
+		match *self {
+			Table::Blocks => "blocks",
+			Table::Transactions => "transactions",
+			Table::Receipts => "receipts",
+			Table::Logs => "logs",
+			Table::Traces => "traces",
+		}
+	}
+
+	fn columns(&self) -> &'static [&'static str] {
+		match *self {
+			Table::Blocks => &["number", "hash", "parent_hash", "timestamp", "author", "difficulty", "gas_limit", "gas_used", "transaction_count", "uncle_count", "size"],
+			Table::Transactions => &["block_number", "block_hash", "transaction_index", "hash", "from", "to", "value", "gas", "gas_price", "nonce", "input"],
+			Table::Receipts => &["block_number", "transaction_hash", "transaction_index", "gas_used", "cumulative_gas_used", "contract_address", "log_count"],
+			Table::Logs => &["block_number", "transaction_hash", "transaction_index", "log_index", "address", "topic0", "topic1", "topic2", "topic3", "data"],
+			Table::Traces => &["block_number", "transaction_hash", "transaction_index", "trace_address", "type", "from", "to", "value", "gas", "gas_used", "error"],
+		}
+	}
+}
+
+/// Export progress, written next to the exported tables.
+#[derive(Debug, PartialEq, RustcEncodable, RustcDecodable)]
+struct Manifest {
+	schema_version: u32,
+	format: String,
+	/// Last block fully written to all tables.
+	last_block: u64,
+	/// Size of every table file at `last_block`, in `Table::all()` order.
+	offsets: Vec<u64>,
+}
+
+/// Export blocks `from..=to` into `dir`. If `dir` contains an unfinished export of the
+/// same schema, it's continued after the last completed block.
+pub fn export(client: &BlockChainClient, dir: &Path, from: u64, to: u64, format: AnalyticsFormat, with_traces: bool) -> Result<(), String> {
+	fs::create_dir_all(dir).map_err(|e| format!("Cannot create export directory {}: {}", dir.display(), e))?;
+
+	let manifest = read_manifest(dir)?;
+	let start = match manifest {
+		Some(ref manifest) if manifest.last_block >= from => {
+			info!("Resuming analytics export after block #{}", manifest.last_block);
+			manifest.last_block + 1
+		},
+		Some(_) => return Err(format!("Export in {} ends before block #{}; use a new directory", dir.display(), from)),
+		None => from,
+	};
+
+	let mut writer = TableWriter::open(dir, format, manifest.as_ref())?;
+
+	for number in start..(to + 1) {
+		if number % 10000 == 0 {
+			info!("#{}", number);
+		}
+		export_block(client, number, with_traces, &mut writer)?;
+
+		if number % CHECKPOINT_INTERVAL == 0 || number == to {
+			writer.checkpoint(number)?;
+		}
+	}
+
+	info!("Analytics export completed.");
+	Ok(())
+}
+
+fn export_block(client: &BlockChainClient, number: u64, with_traces: bool, writer: &mut TableWriter) -> Result<(), String> {
+	let id = BlockId::Number(number);
+	let block = client.block(id).ok_or("Error exporting incomplete chain")?;
+	let hash = block.hash();
+	let transactions = block.transactions();
+
+	writer.row(Table::Blocks, &[
+		number.to_string(),
+		hex_hash(&hash),
+		hex_hash(&block.parent_hash()),
+		block.timestamp().to_string(),
+		hex_address(&block.author()),
+		block.difficulty().to_string(),
+		block.gas_limit().to_string(),
+		block.gas_used().to_string(),
+		transactions.len().to_string(),
+		block.uncles_count().to_string(),
+		block.rlp().as_raw().len().to_string(),
+	])?;
+
+	for (index, tx) in transactions.into_iter().enumerate() {
+		let tx_hash = tx.hash();
+		let tx = SignedTransaction::new(tx)
+			.map_err(|e| format!("Invalid transaction {:?} in block #{}: {}", tx_hash, number, e))?;
+		let to = match tx.action {
+			Action::Create => String::new(),
+			Action::Call(ref address) => hex_address(address),
+		};
+		writer.row(Table::Transactions, &[
+			number.to_string(),
+			hex_hash(&hash),
+			index.to_string(),
+			hex_hash(&tx_hash),
+			hex_address(&tx.sender()),
+			to,
+			tx.value.to_string(),
+			tx.gas.to_string(),
+			tx.gas_price.to_string(),
+			tx.nonce.to_string(),
+			format!("0x{}", tx.data.to_hex()),
+		])?;
+
+		let receipt = client.transaction_receipt(TransactionId::Location(id, index))
+			.ok_or_else(|| format!("Missing receipt for transaction {:?}", tx_hash))?;
+		writer.row(Table::Receipts, &[
+			number.to_string(),
+			hex_hash(&tx_hash),
+			index.to_string(),
+			receipt.gas_used.to_string(),
+			receipt.cumulative_gas_used.to_string(),
+			receipt.contract_address.as_ref().map_or_else(String::new, hex_address),
+			receipt.logs.len().to_string(),
+		])?;
+
+		for log in receipt.logs {
+			let topic = |i: usize| log.entry.topics.get(i).map_or_else(String::new, hex_hash);
+			writer.row(Table::Logs, &[
+				number.to_string(),
+				hex_hash(&tx_hash),
+				index.to_string(),
+				log.log_index.to_string(),
+				hex_address(&log.entry.address),
+				topic(0),
+				topic(1),
+				topic(2),
+				topic(3),
+				format!("0x{}", log.entry.data.to_hex()),
+			])?;
+		}
+	}
+
+	if with_traces {
+		let traces = client.block_traces(id).ok_or_else(|| format!(
+			"Traces of block #{} are not available. Tracing must be enabled (--tracing on) to export traces, use --tracing off to export without them.", number))?;
+		for trace in traces {
+			let (kind, from, to, value, gas) = match trace.action {
+				trace::Action::Call(ref call) => ("call", hex_address(&call.from), hex_address(&call.to), call.value, call.gas),
+				trace::Action::Create(ref create) => ("create", hex_address(&create.from), String::new(), create.value, create.gas),
+				trace::Action::Suicide(ref suicide) => ("suicide", hex_address(&suicide.address), hex_address(&suicide.refund_address), suicide.balance, U256::zero()),
+			};
+			let (gas_used, error) = match trace.result {
+				trace::Res::Call(ref result) => (result.gas_used.to_string(), String::new()),
+				trace::Res::Create(ref result) => (result.gas_used.to_string(), String::new()),
+				trace::Res::FailedCall(ref err) | trace::Res::FailedCreate(ref err) => (String::new(), err.to_string()),
+				trace::Res::None => (String::new(), String::new()),
+			};
+			writer.row(Table::Traces, &[
+				number.to_string(),
+				hex_hash(&trace.transaction_hash),
+				trace.transaction_number.to_string(),
+				trace.trace_address.iter().map(ToString::to_string).collect::<Vec<_>>().join("."),
+				kind.into(),
+				from,
+				to,
+				value.to_string(),
+				gas.to_string(),
+				gas_used,
+				error,
+			])?;
+		}
+	}
+
+	Ok(())
+}
+
+fn hex_hash(hash: &H256) -> String {
+	format!("0x{}", hash.hex())
+}
+
+fn hex_address(address: &Address) -> String {
+	format!("0x{}", address.hex())
+}
+
+fn read_manifest(dir: &Path) -> Result<Option<Manifest>, String> {
+	let path = dir.join(MANIFEST_FILE);
+	let mut contents = String::new();
+	match File::open(&path) {
+		Ok(mut file) => file.read_to_string(&mut contents).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?,
+		Err(_) => return Ok(None),
+	};
+
+	let manifest: Manifest = json::decode(&contents).map_err(|e| format!("Invalid export manifest {}: {}", path.display(), e))?;
+	if manifest.schema_version != SCHEMA_VERSION {
+		return Err(format!("Export in {} uses schema version {}, current is {}; use a new directory", dir.display(), manifest.schema_version, SCHEMA_VERSION));
+	}
+	if manifest.offsets.len() != Table::all().len() {
+		return Err(format!("Invalid export manifest {}", path.display()));
+	}
+	Ok(Some(manifest))
+}
+
+/// Writes rows to per-table files.
+struct TableWriter {
+	dir: PathBuf,
+	format: AnalyticsFormat,
+	files: Vec<File>,
+}
+
+impl TableWriter {
+	/// Open table files. When resuming, anything written after the last checkpoint is discarded.
+	fn open(dir: &Path, format: AnalyticsFormat, manifest: Option<&Manifest>) -> Result<Self, String> {
+		let mut files = Vec::new();
+		for (i, table) in Table::all().iter().enumerate() {
+			let path = dir.join(format!("{}.csv", table.name()));
+			let mut file = OpenOptions::new().write(true).create(true).open(&path)
+				.map_err(|e| format!("Cannot write to {}: {}", path.display(), e))?;
+			let offset = manifest.map_or(0, |m| m.offsets[i]);
+			file.set_len(offset).and_then(|_| file.seek(SeekFrom::Start(offset)))
+				.map_err(|e| format!("Cannot write to {}: {}", path.display(), e))?;
+			if offset == 0 {
+				writeln!(file, "{}", table.columns().join(",")).map_err(|e| format!("Cannot write to {}: {}", path.display(), e))?;
+			}
+			files.push(file);
+		}
+
+		Ok(TableWriter {
+			dir: dir.to_owned(),
+			format: format,
+			files: files,
+		})
+	}
+
+	fn row<S: AsRef<str>>(&mut self, table: Table, values: &[S]) -> Result<(), String> {
+		debug_assert_eq!(values.len(), table.columns().len());
+		let line = values.iter().map(|v| csv_escape(v.as_ref())).collect::<Vec<_>>().join(",");
+		writeln!(self.files[table as usize], "{}", line).map_err(|e| format!("Cannot write {} table: {}", table.name(), e))
+	}
+
+	/// Flush all tables and record that blocks up to `last_block` are complete.
+	fn checkpoint(&mut self, last_block: u64) -> Result<(), String> {
+		let mut offsets = Vec::new();
+		for file in &mut self.files {
+			file.sync_data().map_err(|e| format!("Cannot flush export: {}", e))?;
+			offsets.push(file.seek(SeekFrom::Current(0)).map_err(|e| format!("Cannot flush export: {}", e))?);
+		}
+
+		let manifest = Manifest {
+			schema_version: SCHEMA_VERSION,
+			format: match self.format { AnalyticsFormat::Csv => "csv".into() },
+			last_block: last_block,
+			offsets: offsets,
+		};
+		let encoded = json::encode(&manifest).expect("Manifest is always serializable; qed");
+
+		// write to a temporary file first so that the manifest is never left half-written
+		let path = self.dir.join(MANIFEST_FILE);
+		let tmp = self.dir.join(format!("{}.tmp", MANIFEST_FILE));
+		File::create(&tmp)
+			.and_then(|mut file| file.write_all(encoded.as_bytes()).and_then(|_| file.sync_all()))
+			.and_then(|_| fs::rename(&tmp, &path))
+			.map_err(|e| format!("Cannot write export manifest {}: {}", path.display(), e))
+	}
+}
+
+/// Quote a CSV field if needed.
+fn csv_escape(value: &str) -> String {
+	if value.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+		format!("\"{}\"", value.replace('"', "\"\""))
+	} else {
+		value.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{File, OpenOptions};
+	use std::io::{Read, Write};
+	use devtools::RandomTempPath;
+	use ethcore::client::{TestBlockChainClient, EachBlockWith};
+	use super::*;
+
+	fn read(path: &Path) -> String {
+		let mut s = String::new();
+		File::open(path).unwrap().read_to_string(&mut s).unwrap();
+		s
+	}
+
+	#[test]
+	fn should_escape_csv_fields() {
+		assert_eq!(csv_escape("0x00"), "0x00");
+		assert_eq!(csv_escape("a,b"), "\"a,b\"");
+		assert_eq!(csv_escape("out of \"gas\""), "\"out of \"\"gas\"\"\"");
+	}
+
+	#[test]
+	fn should_reject_parquet_format() {
+		assert_eq!("csv".parse(), Ok(AnalyticsFormat::Csv));
+		assert!("parquet".parse::<AnalyticsFormat>().is_err());
+	}
+
+	#[test]
+	fn should_export_and_resume() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let temp = RandomTempPath::new();
+		let dir = temp.as_path();
+
+		export(&client, dir, 1, 4, AnalyticsFormat::Csv, false).unwrap();
+		assert_eq!(read_manifest(dir).unwrap().unwrap().last_block, 4);
+
+		// rows written after the last checkpoint are discarded on resume
+		let mut blocks = OpenOptions::new().append(true).open(dir.join("blocks.csv")).unwrap();
+		writeln!(blocks, "5,interrupted").unwrap();
+		export(&client, dir, 1, 10, AnalyticsFormat::Csv, false).unwrap();
+
+		let blocks = read(&dir.join("blocks.csv"));
+		let lines: Vec<_> = blocks.lines().collect();
+		assert_eq!(lines.len(), 11);
+		assert!(lines[0].starts_with("number,hash,"));
+		assert!(lines[1].starts_with("1,"));
+		assert!(lines[5].starts_with("5,0x"));
+		assert!(lines[10].starts_with("10,"));
+		assert_eq!(read_manifest(dir).unwrap().unwrap().last_block, 10);
+	}
+
+	#[test]
+	fn should_require_tracing_to_export_traces() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(2, EachBlockWith::Nothing);
+		let temp = RandomTempPath::new();
+
+		let err = export(&client, temp.as_path(), 1, 2, AnalyticsFormat::Csv, true).unwrap_err();
+		assert!(err.contains("Tracing must be enabled"));
+
+		*client.traces.write() = Some(Vec::new());
+		export(&client, temp.as_path(), 1, 2, AnalyticsFormat::Csv, true).unwrap();
+	}
+}
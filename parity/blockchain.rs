@@ -15,7 +15,8 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::{FromStr, from_utf8};
-use std::{io, fs};
+use std::{io, fs, fmt, cmp};
+use std::path::Path;
 use std::io::{BufReader, BufRead};
 use std::time::{Instant, Duration};
 use std::thread::sleep;
@@ -23,19 +24,30 @@ use std::sync::Arc;
 use rustc_serialize::hex::FromHex;
 use io::{PanicHandler, ForwardPanic};
 use util::{ToPretty, Uint, U256, H256, Address, Hashable};
+use util::journaldb::Algorithm;
 use rlp::PayloadInfo;
 use ethcore::service::ClientService;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId};
+use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, BlockChainClient, BlockId, SealPolicy};
+use ethcore::client::replay::ReplayBundle;
 use ethcore::error::ImportError;
+use ethcore::filter::Filter;
 use ethcore::miner::Miner;
+use ethcore::spec::Spec;
 use ethcore::verification::queue::VerifierSettings;
 use cache::CacheConfig;
 use informant::{Informant, MillisecondDuration};
 use params::{SpecType, Pruning, Switch, tracing_switch_to_bool, fatdb_switch_to_bool};
 use helpers::{to_client_config, execute_upgrades};
-use dir::Directories;
+use dir::{Directories, DatabaseDirectories};
 use user_defaults::UserDefaults;
 use fdlimit;
+use analytics::{self, AnalyticsFormat};
+
+// number of blocks searched for logs at a time.
+const LOGS_EXPORT_BATCH: u64 = 1000;
+
+// number of blocks re-executed between progress reports of an archive migration.
+const MIGRATION_PROGRESS_INTERVAL: u64 = 10000;
 
 #[derive(Debug, PartialEq)]
 pub enum DataFormat {
@@ -67,6 +79,13 @@ pub enum BlockchainCmd {
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
+	ExportAnalytics(ExportAnalytics),
+	ExportLogs(ExportLogs),
+	ReindexTransactions(ReindexTransactions),
+	MigrateToArchive(MigrateToArchive),
+	BackfillTraces(BackfillTraces),
+	Replay(ReplayBlock),
+	RebuildCht(RebuildCht),
 }
 
 #[derive(Debug, PartialEq)]
@@ -92,6 +111,7 @@ pub struct ImportBlockchain {
 	pub fat_db: Switch,
 	pub vm_type: VMType,
 	pub check_seal: bool,
+	pub seal_policy: SealPolicy,
 	pub with_color: bool,
 	pub verifier_settings: VerifierSettings,
 }
@@ -136,12 +156,160 @@ pub struct ExportState {
 	pub max_balance: Option<U256>,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct ExportAnalytics {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub dir_path: Option<String>,
+	pub format: AnalyticsFormat,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+}
+
+/// Position of a log in the chain, written next to every exported log so
+/// that an interrupted export can be resumed right after it.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub struct LogCursor {
+	pub block: u64,
+	pub transaction: usize,
+	pub log: usize,
+}
+
+impl FromStr for LogCursor {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let invalid = || format!("Invalid log cursor: {}. Expected BLOCK:TRANSACTION:LOG", s);
+		let parts = s.split(':').map(|p| p.parse::<u64>().map_err(|_| invalid())).collect::<Result<Vec<_>, _>>()?;
+		if parts.len() != 3 {
+			return Err(invalid());
+		}
+
+		Ok(LogCursor {
+			block: parts[0],
+			transaction: parts[1] as usize,
+			log: parts[2] as usize,
+		})
+	}
+}
+
+impl fmt::Display for LogCursor {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}:{}:{}", self.block, self.transaction, self.log)
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExportLogs {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub file_path: Option<String>,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+	pub addresses: Option<Vec<Address>>,
+	pub topics: Option<Vec<H256>>,
+	pub resume: Option<LogCursor>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReindexTransactions {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+	pub tracing: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+	pub workers: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ReplayBlock {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub file_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RebuildCht {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BackfillTraces {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+	pub workers: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MigrateToArchive {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+}
+
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
 		BlockchainCmd::Import(import_cmd) => execute_import(import_cmd),
 		BlockchainCmd::Export(export_cmd) => execute_export(export_cmd),
 		BlockchainCmd::ExportState(export_cmd) => execute_export_state(export_cmd),
+		BlockchainCmd::ExportAnalytics(export_cmd) => execute_export_analytics(export_cmd),
+		BlockchainCmd::ExportLogs(export_cmd) => execute_export_logs(export_cmd),
+		BlockchainCmd::ReindexTransactions(reindex_cmd) => execute_reindex_transactions(reindex_cmd),
+		BlockchainCmd::MigrateToArchive(migrate_cmd) => execute_migrate_to_archive(migrate_cmd),
+		BlockchainCmd::BackfillTraces(backfill_cmd) => execute_backfill_traces(backfill_cmd),
+		BlockchainCmd::Replay(replay_cmd) => execute_replay(replay_cmd),
+		BlockchainCmd::RebuildCht(rebuild_cmd) => execute_rebuild_cht(rebuild_cmd),
 	}
 }
 
@@ -205,6 +373,7 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.seal_policy = cmd.seal_policy;
 
 	// build client
 	let service = ClientService::start(
@@ -338,16 +507,33 @@ fn start_client(
 	// load user defaults
 	let user_defaults = UserDefaults::load(&user_defaults_path)?;
 
+	// check if tracing is on
+	let tracing = tracing_switch_to_bool(tracing, &user_defaults)?;
+
+	open_client(dirs, spec, db_dirs, &user_defaults, pruning, pruning_history, pruning_memory, tracing, fat_db, compaction, wal, cache_config)
+}
+
+fn open_client(
+	dirs: Directories,
+	spec: Spec,
+	db_dirs: DatabaseDirectories,
+	user_defaults: &UserDefaults,
+	pruning: Pruning,
+	pruning_history: u64,
+	pruning_memory: usize,
+	tracing: bool,
+	fat_db: Switch,
+	compaction: DatabaseCompactionProfile,
+	wal: bool,
+	cache_config: CacheConfig
+) -> Result<ClientService, String> {
 	fdlimit::raise_fd_limit();
 
 	// select pruning algorithm
-	let algorithm = pruning.to_algorithm(&user_defaults);
-
-	// check if tracing is on
-	let tracing = tracing_switch_to_bool(tracing, &user_defaults)?;
+	let algorithm = pruning.to_algorithm(user_defaults);
 
 	// check if fatdb is on
-	let fat_db = fatdb_switch_to_bool(fat_db, &user_defaults, algorithm)?;
+	let fat_db = fatdb_switch_to_bool(fat_db, user_defaults, algorithm)?;
 
 	// prepare client and snapshot paths.
 	let client_path = db_dirs.client_path(algorithm);
@@ -432,6 +618,355 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 	Ok(())
 }
 
+fn execute_export_analytics(cmd: ExportAnalytics) -> Result<(), String> {
+	let dir_path = cmd.dir_path.ok_or("Analytics export requires an output directory")?;
+	let with_traces = cmd.tracing != Switch::Off;
+
+	// Setup panic handler
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	analytics::export(&*client, Path::new(&dir_path), from, to, cmd.format, with_traces)
+}
+
+fn execute_export_logs(cmd: ExportLogs) -> Result<(), String> {
+	// Setup panic handler
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let mut from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+	if let Some(ref resume) = cmd.resume {
+		from = cmp::max(from, resume.block);
+	}
+
+	// a resumed export appends to what was written before.
+	let mut out: Box<io::Write> = match cmd.file_path {
+		Some(f) => Box::new(fs::OpenOptions::new().write(true).create(true).append(cmd.resume.is_some()).truncate(cmd.resume.is_none()).open(&f)
+			.map_err(|_| format!("Cannot write to file given: {}", f))?),
+		None => Box::new(io::stdout()),
+	};
+
+	let mut exported = 0usize;
+	let mut block = from;
+	while block <= to {
+		let end = cmp::min(block + LOGS_EXPORT_BATCH - 1, to);
+		let filter = Filter {
+			from_block: BlockId::Number(block),
+			to_block: BlockId::Number(end),
+			address: cmd.addresses.clone(),
+			topics: vec![cmd.topics.clone(), None, None, None],
+			limit: None,
+		};
+
+		for log in client.logs(filter) {
+			let cursor = LogCursor {
+				block: log.block_number,
+				transaction: log.transaction_index,
+				log: log.log_index,
+			};
+			if cmd.resume.map_or(false, |resume| cursor <= resume) {
+				continue;
+			}
+
+			let topics = log.topics.iter().map(|t| format!("\"0x{}\"", t.hex())).collect::<Vec<_>>().join(",");
+			writeln!(out, "{{\"cursor\":\"{}\",\"blockNumber\":{},\"blockHash\":\"0x{}\",\"transactionIndex\":{},\"transactionHash\":\"0x{}\",\"logIndex\":{},\"address\":\"0x{}\",\"topics\":[{}],\"data\":\"0x{}\"}}",
+				cursor, log.block_number, log.block_hash.hex(), log.transaction_index, log.transaction_hash.hex(),
+				log.log_index, log.address.hex(), topics, log.data.to_hex()).expect("Write error");
+			exported += 1;
+		}
+
+		out.flush().expect("Write error");
+		info!("#{}: {} logs", end, exported);
+		block = end + 1;
+	}
+
+	info!("Export completed.");
+	Ok(())
+}
+
+fn execute_reindex_transactions(cmd: ReindexTransactions) -> Result<(), String> {
+	let timer = Instant::now();
+
+	// Setup panic handler
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		cmd.tracing,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	info!("Reindexing transactions of blocks #{}..#{} using {} workers", from, to, cmd.workers);
+	let indexed = client.reindex_transaction_addresses(from, to, cmd.workers, |number| {
+		info!("#{}", number);
+	});
+
+	let ms = timer.elapsed().as_milliseconds();
+	info!("Reindex completed in {} seconds, {} transactions", ms / 1000, indexed);
+	Ok(())
+}
+
+fn execute_replay(cmd: ReplayBlock) -> Result<(), String> {
+	let bundle = ReplayBundle::read(Path::new(&cmd.file_path))?;
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::Off,
+		Switch::Auto,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	info!("Replaying bundle {} captured by {}", bundle.file_name(), bundle.version);
+	info!("Original error: {}", bundle.error);
+	let report = client.replay_bundle(&bundle)?;
+
+	for (index, &(ref hash, ref result)) in report.transactions.iter().enumerate() {
+		println!("Transaction {} ({:?})", index, hash);
+		match *result {
+			Ok(ref executed) => {
+				println!("  gas used: {}, refunded: {}, cumulative gas used: {}", executed.gas_used, executed.refunded, executed.cumulative_gas_used);
+				println!("  exception: {:?}", executed.exception);
+				println!("  output: 0x{}", executed.output.to_hex());
+				println!("  call trace: {:#?}", executed.trace);
+				println!("  VM trace: {:#?}", executed.vm_trace);
+			},
+			Err(ref e) => println!("  failed: {}", e),
+		}
+	}
+
+	match report.produced {
+		Ok(_) => {
+			let mismatches = report.mismatches();
+			if mismatches.is_empty() {
+				info!("Replayed block matches its header; the failure did not reproduce");
+			}
+			for mismatch in mismatches {
+				info!("Mismatch in {}", mismatch);
+			}
+		},
+		Err(ref e) => info!("Block execution failed: {}", e),
+	}
+	Ok(())
+}
+
+fn execute_rebuild_cht(cmd: RebuildCht) -> Result<(), String> {
+	let timer = Instant::now();
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::Off,
+		Switch::Auto,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let generated = client.rebuild_cht_roots(|number| {
+		if number % 100 == 0 {
+			info!("#{}", number);
+		}
+	});
+
+	let ms = timer.elapsed().as_milliseconds();
+	info!("Rebuilt {} CHT roots in {} seconds", generated, ms / 1000);
+	Ok(())
+}
+
+fn execute_backfill_traces(cmd: BackfillTraces) -> Result<(), String> {
+	let timer = Instant::now();
+
+	// the client is opened with tracing on regardless of the user defaults, which are only
+	// updated once the backfill succeeded. An interrupted backfill is resumed by running
+	// the command again.
+	let spec = cmd.spec.spec()?;
+	let db_dirs = cmd.dirs.database(spec.genesis_header().hash(), None, spec.data_dir.clone());
+	let user_defaults_path = db_dirs.user_defaults_path();
+	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+
+	let service = open_client(
+		cmd.dirs,
+		spec,
+		db_dirs,
+		&user_defaults,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		true,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+
+	panic_handler.forward_from(&service);
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	info!("Backfilling traces of blocks #{}..#{} using {} workers", from, to, cmd.workers);
+	let traced = client.backfill_traces(from, to, cmd.workers, |number| {
+		info!("#{}", number);
+	})?;
+
+	// blocks imported from now on are traced as well.
+	if !user_defaults.tracing {
+		info!("Enabling tracing");
+		user_defaults.tracing = true;
+		user_defaults.save(&user_defaults_path)?;
+	}
+
+	let ms = timer.elapsed().as_milliseconds();
+	info!("Backfill completed in {} seconds, {} blocks traced", ms / 1000, traced);
+	Ok(())
+}
+
+fn execute_migrate_to_archive(cmd: MigrateToArchive) -> Result<(), String> {
+	let timer = Instant::now();
+
+	let spec = cmd.spec.spec()?;
+	let db_dirs = cmd.dirs.database(spec.genesis_header().hash(), None, spec.data_dir.clone());
+	let user_defaults_path = db_dirs.user_defaults_path();
+	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+	if algorithm == Algorithm::Archive {
+		return Err("The database is already an archive. Use --pruning to select the database to migrate.".into());
+	}
+	drop(spec);
+
+	// the archive database is built next to the pruned one, which is left untouched.
+	let source = start_client(
+		cmd.dirs.clone(),
+		cmd.spec.clone(),
+		Pruning::Specific(algorithm),
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::Auto,
+		Switch::Auto,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config.clone(),
+	)?;
+	let target = start_client(
+		cmd.dirs,
+		cmd.spec,
+		Pruning::Specific(Algorithm::Archive),
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::Auto,
+		Switch::Auto,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+	)?;
+	let panic_handler = PanicHandler::new_in_arc();
+	panic_handler.forward_from(&source);
+	panic_handler.forward_from(&target);
+
+	let source_client = source.client();
+	let client = target.client();
+
+	// an interrupted migration resumes from the best block of the archive.
+	let from = client.chain_info().best_block_number + 1;
+	let to = source_client.chain_info().best_block_number;
+	info!("Re-executing blocks #{}..#{} into an archive database", from, to);
+
+	for number in from..to + 1 {
+		let block = source_client.block(BlockId::Number(number))
+			.ok_or_else(|| format!("Block #{} is missing from the {} database. Warp-synced databases can't be migrated; sync with --no-warp.", number, algorithm.as_str()))?;
+
+		while client.queue_info().is_full() { sleep(Duration::from_secs(1)); }
+		match client.import_block(block.into_inner()) {
+			Err(BlockImportError::Import(ImportError::AlreadyInChain)) => {
+				trace!("Skipping block already in chain.");
+			},
+			Err(e) => return Err(format!("Cannot import block #{}: {:?}", number, e)),
+			Ok(_) => {},
+		}
+
+		if number % MIGRATION_PROGRESS_INTERVAL == 0 {
+			let imported = client.chain_info().best_block_number;
+			info!("Re-executed #{} of #{} ({:.1}%)", imported, to, imported as f64 * 100.0 / to as f64);
+		}
+	}
+	client.flush_queue();
+
+	user_defaults.pruning = Algorithm::Archive;
+	user_defaults.save(&user_defaults_path)?;
+
+	let ms = timer.elapsed().as_milliseconds();
+	info!("Migration completed in {} seconds. The {} database at {} is no longer used; remove it with `parity db kill --pruning {}`.",
+		ms / 1000,
+		algorithm.as_str(),
+		db_dirs.db_path(algorithm).display(),
+		algorithm.as_str(),
+	);
+	Ok(())
+}
+
 fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	// Setup panic handler
 	let service = start_client(
@@ -539,7 +1074,7 @@ pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 
 #[cfg(test)]
 mod test {
-	use super::DataFormat;
+	use super::{DataFormat, LogCursor};
 
 	#[test]
 	fn test_data_format_parsing() {
@@ -547,4 +1082,13 @@ mod test {
 		assert_eq!(DataFormat::Binary, "bin".parse().unwrap());
 		assert_eq!(DataFormat::Hex, "hex".parse().unwrap());
 	}
+
+	#[test]
+	fn test_log_cursor() {
+		let cursor: LogCursor = "1000:2:7".parse().unwrap();
+		assert_eq!(cursor, LogCursor { block: 1000, transaction: 2, log: 7 });
+		assert_eq!(cursor.to_string(), "1000:2:7");
+		assert!(cursor < LogCursor { block: 1000, transaction: 3, log: 0 });
+		assert!("1000:2".parse::<LogCursor>().is_err());
+	}
 }
@@ -0,0 +1,190 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Management of the chain databases kept side by side under the database directory.
+//!
+//! Each chain lives in `<db path>/<spec data dir>/db/<genesis hash>`, so chains never
+//! share data. The chain selected with `parity chains use` or run last is remembered
+//! and picked by `--chain last`.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use informant::format_bytes;
+use params::SpecType;
+
+const LAST_CHAIN_FILE: &'static str = "last_chain";
+/// Chain used by `--chain last` when none was selected yet.
+pub const DEFAULT_CHAIN: &'static str = "foundation";
+
+#[derive(Debug, PartialEq)]
+pub enum ChainsCmd {
+	List(ListChains),
+	Use(UseChain),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ListChains {
+	pub db_path: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UseChain {
+	pub db_path: String,
+	pub chain: String,
+}
+
+/// A chain database found under the database directory.
+#[derive(Debug, PartialEq)]
+pub struct ChainDatabase {
+	/// Data directory of the chain spec.
+	pub data_dir: String,
+	/// Abridged genesis hash the database is keyed by.
+	pub genesis: String,
+	/// Pruning algorithms with a database.
+	pub pruning: Vec<String>,
+	/// Size on disk, in bytes.
+	pub size: u64,
+}
+
+pub fn execute(cmd: ChainsCmd) -> Result<String, String> {
+	match cmd {
+		ChainsCmd::List(cmd) => list_chains(cmd),
+		ChainsCmd::Use(cmd) => use_chain(cmd),
+	}
+}
+
+fn list_chains(cmd: ListChains) -> Result<String, String> {
+	let selected = last_chain(&cmd.db_path)
+		.and_then(|chain| chain.parse::<SpecType>().ok())
+		.and_then(|spec| spec.spec().ok())
+		.map(|spec| spec.data_dir.clone());
+
+	let lines: Vec<_> = find_databases(Path::new(&cmd.db_path)).into_iter()
+		.map(|db| format!("{} {} {} [{}] {}",
+			if selected.as_ref() == Some(&db.data_dir) { "*" } else { " " },
+			db.data_dir,
+			db.genesis,
+			db.pruning.join(", "),
+			format_bytes(db.size as usize),
+		))
+		.collect();
+	Ok(lines.join("\n"))
+}
+
+fn use_chain(cmd: UseChain) -> Result<String, String> {
+	// make sure the chain can be started before selecting it.
+	let spec = cmd.chain.parse::<SpecType>()?.spec()?;
+	set_last_chain(&cmd.db_path, &cmd.chain)?;
+	Ok(format!("Selected chain {} ({}); start with --chain last to use it.", cmd.chain, spec.name))
+}
+
+/// Chain selected last in given database directory.
+pub fn last_chain(db_path: &str) -> Option<String> {
+	let mut chain = String::new();
+	File::open(Path::new(db_path).join(LAST_CHAIN_FILE))
+		.and_then(|mut file| file.read_to_string(&mut chain))
+		.ok()
+		.map(|_| chain.trim().to_owned())
+		.and_then(|chain| if chain.is_empty() { None } else { Some(chain) })
+}
+
+/// Remember given chain as the last one selected in given database directory.
+pub fn set_last_chain(db_path: &str, chain: &str) -> Result<(), String> {
+	fs::create_dir_all(db_path)
+		.and_then(|_| File::create(Path::new(db_path).join(LAST_CHAIN_FILE)))
+		.and_then(|mut file| file.write_all(chain.as_bytes()))
+		.map_err(|e| format!("Unable to save selected chain: {}", e))
+}
+
+/// Chain databases under given database directory, ordered by data directory.
+pub fn find_databases(db_path: &Path) -> Vec<ChainDatabase> {
+	let mut databases = Vec::new();
+	for (data_dir, spec_root) in subdirectories(db_path) {
+		for (genesis, db_root) in subdirectories(&spec_root.join("db")) {
+			let pruning = subdirectories(&db_root).into_iter()
+				.map(|(name, _)| name)
+				.filter(|name| name != "snapshot")
+				.collect();
+			databases.push(ChainDatabase {
+				data_dir: data_dir.clone(),
+				genesis: genesis,
+				pruning: pruning,
+				size: disk_usage(&db_root),
+			});
+		}
+	}
+	databases
+}
+
+fn subdirectories(path: &Path) -> Vec<(String, ::std::path::PathBuf)> {
+	let mut dirs: Vec<_> = match fs::read_dir(path) {
+		Ok(entries) => entries.filter_map(Result::ok)
+			.filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+			.filter_map(|entry| entry.file_name().into_string().ok().map(|name| (name, entry.path())))
+			.collect(),
+		Err(_) => Vec::new(),
+	};
+	dirs.sort();
+	dirs
+}
+
+fn disk_usage(path: &Path) -> u64 {
+	match fs::read_dir(path) {
+		Ok(entries) => entries.filter_map(Result::ok)
+			.map(|entry| match entry.file_type() {
+				Ok(ref t) if t.is_dir() => disk_usage(&entry.path()),
+				_ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+			})
+			.sum(),
+		Err(_) => 0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::{self, File};
+	use std::io::Write;
+	use devtools::RandomTempPath;
+	use super::{ChainDatabase, find_databases, last_chain, set_last_chain};
+
+	#[test]
+	fn remembers_last_chain() {
+		let dir = RandomTempPath::new();
+		let db_path = dir.as_str();
+		assert_eq!(last_chain(db_path), None);
+
+		set_last_chain(db_path, "kovan").unwrap();
+		assert_eq!(last_chain(db_path), Some("kovan".into()));
+	}
+
+	#[test]
+	fn finds_chain_databases() {
+		let dir = RandomTempPath::new();
+		let root = dir.as_path();
+		fs::create_dir_all(root.join("ethereum/db/906a34e69aec8c0d/overlayrecent/db")).unwrap();
+		fs::create_dir_all(root.join("ethereum/db/906a34e69aec8c0d/snapshot")).unwrap();
+		fs::create_dir_all(root.join("kovan/db/a0a76e0ff5d2d6ba/archive")).unwrap();
+		fs::create_dir_all(root.join("kovan/network")).unwrap();
+		File::create(root.join("kovan/db/a0a76e0ff5d2d6ba/archive/000001.sst")).unwrap().write_all(&[0u8; 100]).unwrap();
+
+		assert_eq!(find_databases(root), vec![
+			ChainDatabase { data_dir: "ethereum".into(), genesis: "906a34e69aec8c0d".into(), pruning: vec!["overlayrecent".into()], size: 0 },
+			ChainDatabase { data_dir: "kovan".into(), genesis: "a0a76e0ff5d2d6ba".into(), pruning: vec!["archive".into()], size: 100 },
+		]);
+	}
+}
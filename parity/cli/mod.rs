@@ -29,6 +29,8 @@ usage! {
 		cmd_export: bool,
 		cmd_blocks: bool,
 		cmd_state: bool,
+		cmd_analytics: bool,
+		cmd_logs: bool,
 		cmd_import: bool,
 		cmd_signer: bool,
 		cmd_new_token: bool,
@@ -42,12 +44,20 @@ usage! {
 		cmd_hash: bool,
 		cmd_kill: bool,
 		cmd_db: bool,
+		cmd_reindex_transactions: bool,
+		cmd_migrate_to_archive: bool,
+		cmd_backfill_traces: bool,
+		cmd_replay: bool,
+		cmd_rebuild_cht: bool,
+		cmd_chains: bool,
+		cmd_use: bool,
 
 		// Arguments
 		arg_pid_file: String,
 		arg_file: Option<String>,
 		arg_path: Vec<String>,
 		arg_id: Option<usize>,
+		arg_chain: Option<String>,
 
 		// Flags
 		// -- Legacy Options
@@ -93,6 +103,9 @@ usage! {
 		flag_chain: String = "foundation", or |c: &Config| otry!(c.parity).chain.clone(),
 		flag_keys_path: String = "$BASE/keys", or |c: &Config| otry!(c.parity).keys_path.clone(),
 		flag_identity: String = "", or |c: &Config| otry!(c.parity).identity.clone(),
+		flag_read_only: bool = false, or |c: &Config| otry!(c.parity).read_only.clone(),
+		flag_shared_db_primary: Option<String> = None,
+			or |c: &Config| otry!(c.parity).shared_db_primary.clone().map(Some),
 
 		// -- Account Options
 		flag_unlock: Option<String> = None,
@@ -103,6 +116,12 @@ usage! {
 			or |c: &Config| otry!(c.account).keys_iterations.clone(),
 		flag_no_hardware_wallets: bool = false,
 			or |c: &Config| otry!(c.account).disable_hardware.clone(),
+		flag_keys_backup: Option<String> = None,
+			or |c: &Config| otry!(c.account).backup.clone().map(Some),
+		flag_keys_backup_interval: String = "daily",
+			or |c: &Config| otry!(c.account).backup_interval.clone(),
+		flag_keys_backup_password: Option<String> = None,
+			or |c: &Config| otry!(c.account).backup_password.clone().map(Some),
 
 
 		flag_force_ui: bool = false,
@@ -115,6 +134,8 @@ usage! {
 			or |c: &Config| otry!(c.ui).interface.clone(),
 		flag_ui_path: String = "$BASE/signer",
 			or |c: &Config| otry!(c.ui).path.clone(),
+		flag_ui_apis: Option<String> = None,
+			or |c: &Config| otry!(c.ui).apis.as_ref().map(|vec| Some(vec.join(","))),
 		// NOTE [todr] For security reasons don't put this to config files
 		flag_ui_no_validation: bool = false, or |_| None,
 
@@ -148,6 +169,12 @@ usage! {
 		flag_reserved_only: bool = false,
 			or |c: &Config| otry!(c.network).reserved_only.clone(),
 		flag_no_ancient_blocks: bool = false, or |_| None,
+		flag_head_stall_timeout: u64 = 120u64,
+			or |c: &Config| otry!(c.network).head_stall_timeout.clone(),
+		flag_request_parallelism: String = "auto",
+			or |c: &Config| otry!(c.network).request_parallelism.clone(),
+		flag_ancient_receipts_depth: u64 = 0u64,
+			or |c: &Config| otry!(c.network).ancient_receipts_depth.clone(),
 
 		// -- API and Console Options
 		// RPC
@@ -161,8 +188,42 @@ usage! {
 			or |c: &Config| otry!(c.rpc).cors.clone().map(Some),
 		flag_jsonrpc_apis: String = "web3,eth,net,parity,traces,rpc",
 			or |c: &Config| otry!(c.rpc).apis.as_ref().map(|vec| vec.join(",")),
+		flag_api_policies: Option<String> = None,
+			or |c: &Config| otry!(c.rpc).api_policies.clone().map(Some),
 		flag_jsonrpc_hosts: String = "none",
 			or |c: &Config| otry!(c.rpc).hosts.as_ref().map(|vec| vec.join(",")),
+		flag_jsonrpc_hosts_ignore_port: bool = false,
+			or |c: &Config| otry!(c.rpc).hosts_ignore_port.clone(),
+		flag_jsonrpc_allow_unsafe_apis: bool = false,
+			or |c: &Config| otry!(c.rpc).allow_unsafe_apis.clone(),
+		flag_trusted_rpc: bool = false,
+			or |c: &Config| otry!(c.rpc).trusted.clone(),
+		flag_trusted_rpc_port: u16 = 8547u16,
+			or |c: &Config| otry!(c.rpc).trusted_port.clone(),
+		flag_trusted_rpc_apis: String = "web3,eth,net,parity,traces,rpc,personal,signer,parity_accounts,parity_set",
+			or |c: &Config| otry!(c.rpc).trusted_apis.as_ref().map(|vec| vec.join(",")),
+		flag_jsonrpc_access_log: Option<String> = None,
+			or |c: &Config| otry!(c.rpc).access_log.clone().map(Some),
+		flag_jsonrpc_signing_audit: Option<String> = None,
+			or |c: &Config| otry!(c.rpc).signing_audit.clone().map(Some),
+		flag_jsonrpc_abi_dir: Option<String> = None,
+			or |c: &Config| otry!(c.rpc).abi_dir.clone().map(Some),
+		flag_jsonrpc_call_cache_ttl: u64 = 0u64,
+			or |c: &Config| otry!(c.rpc).call_cache_ttl.clone(),
+		flag_jsonrpc_call_cache_size: usize = 65536usize,
+			or |c: &Config| otry!(c.rpc).call_cache_size.clone(),
+		flag_jsonrpc_missing_cache_ttl: u64 = 10u64,
+			or |c: &Config| otry!(c.rpc).missing_cache_ttl.clone(),
+		flag_jsonrpc_max_response: String = "none",
+			or |c: &Config| otry!(c.rpc).max_response.clone(),
+		flag_jsonrpc_max_concurrent: usize = 0usize,
+			or |c: &Config| otry!(c.rpc).max_concurrent.clone(),
+		flag_jsonrpc_max_header_size: usize = 32768usize,
+			or |c: &Config| otry!(c.rpc).max_header_size.clone(),
+		flag_jsonrpc_max_connections: usize = 0usize,
+			or |c: &Config| otry!(c.rpc).max_connections.clone(),
+		flag_jsonrpc_keep_alive_timeout: u64 = 10u64,
+			or |c: &Config| otry!(c.rpc).keep_alive_timeout.clone(),
 
 		// IPC
 		flag_no_ipc: bool = false,
@@ -171,6 +232,8 @@ usage! {
 			or |c: &Config| otry!(c.ipc).path.clone(),
 		flag_ipc_apis: String = "web3,eth,net,parity,parity_accounts,traces,rpc",
 			or |c: &Config| otry!(c.ipc).apis.as_ref().map(|vec| vec.join(",")),
+		flag_ipc_max_concurrent: usize = 16usize,
+			or |c: &Config| otry!(c.ipc).max_concurrent.clone(),
 
 		// DAPPS
 		flag_no_dapps: bool = false,
@@ -212,12 +275,22 @@ usage! {
 			or |c: &Config| otry!(c.ipfs).cors.clone().map(Some),
 		flag_ipfs_api_hosts: String = "none",
 			or |c: &Config| otry!(c.ipfs).hosts.as_ref().map(|vec| vec.join(",")),
+		flag_ipfs_api_hosts_ignore_port: bool = false,
+			or |c: &Config| otry!(c.ipfs).hosts_ignore_port.clone(),
+		flag_ipfs_api_cid_hash: String = "keccak-256",
+			or |c: &Config| otry!(c.ipfs).cid_hash.clone(),
+		flag_ipfs_api_access_log: Option<String> = None,
+			or |c: &Config| otry!(c.ipfs).access_log.clone().map(Some),
 
 		// -- Sealing/Mining Options
 		flag_author: Option<String> = None,
 			or |c: &Config| otry!(c.mining).author.clone().map(Some),
 		flag_engine_signer: Option<String> = None,
 			or |c: &Config| otry!(c.mining).engine_signer.clone().map(Some),
+		flag_authorize_signers: Option<String> = None,
+			or |c: &Config| otry!(c.mining).authorize_signers.as_ref().map(|vec| Some(vec.join(","))),
+		flag_deauthorize_signers: Option<String> = None,
+			or |c: &Config| otry!(c.mining).deauthorize_signers.as_ref().map(|vec| Some(vec.join(","))),
 		flag_force_sealing: bool = false,
 			or |c: &Config| otry!(c.mining).force_sealing.clone(),
 		flag_reseal_on_txs: String = "own",
@@ -244,12 +317,20 @@ usage! {
 			or |c: &Config| otry!(c.mining).gas_floor_target.clone(),
 		flag_gas_cap: String = "6283184",
 			or |c: &Config| otry!(c.mining).gas_cap.clone(),
+		flag_gas_limit_contract: Option<String> = None,
+			or |c: &Config| otry!(c.mining).gas_limit_contract.clone().map(Some),
 		flag_extra_data: Option<String> = None,
 			or |c: &Config| otry!(c.mining).extra_data.clone().map(Some),
 		flag_tx_queue_size: usize = 1024usize,
 			or |c: &Config| otry!(c.mining).tx_queue_size.clone(),
 		flag_tx_queue_gas: String = "auto",
 			or |c: &Config| otry!(c.mining).tx_queue_gas.clone(),
+		flag_tx_queue_nonce_gap: Option<u64> = None,
+			or |c: &Config| otry!(c.mining).tx_queue_nonce_gap.clone().map(Some),
+		flag_tx_queue_per_sender: Option<usize> = None,
+			or |c: &Config| otry!(c.mining).tx_queue_per_sender.clone().map(Some),
+		flag_tx_queue_max_init_code: Option<usize> = None,
+			or |c: &Config| otry!(c.mining).tx_queue_max_init_code.clone().map(Some),
 		flag_tx_queue_strategy: String = "gas_price",
 			or |c: &Config| otry!(c.mining).tx_queue_strategy.clone(),
 		flag_tx_queue_ban_count: u16 = 1u16,
@@ -262,6 +343,14 @@ usage! {
 			or |c: &Config| otry!(c.mining).notify_work.as_ref().map(|vec| Some(vec.join(","))),
 		flag_refuse_service_transactions: bool = false,
 			or |c: &Config| otry!(c.mining).refuse_service_transactions.clone(),
+		flag_prepare_block_ahead: bool = false,
+			or |c: &Config| otry!(c.mining).prepare_block_ahead.clone(),
+		flag_local_gas_reserve: u8 = 0u8,
+			or |c: &Config| otry!(c.mining).local_gas_reserve.clone(),
+		flag_work_auth_secret: Option<String> = None,
+			or |c: &Config| otry!(c.mining).work_auth_secret.clone().map(Some),
+		flag_work_submit_limit: Option<u32> = None,
+			or |c: &Config| otry!(c.mining).work_submit_limit.clone().map(Some),
 
 		flag_stratum: bool = false,
 			or |c: &Config| Some(c.stratum.is_some()),
@@ -281,6 +370,8 @@ usage! {
 			or |c: &Config| otry!(c.footprint).pruning_history.clone(),
 		flag_pruning_memory: usize = 75usize,
 			or |c: &Config| otry!(c.footprint).pruning_memory.clone(),
+		flag_select_node_mode: String = "recommend",
+			or |c: &Config| otry!(c.footprint).select_node_mode.clone(),
 		flag_cache_size_db: u32 = 64u32,
 			or |c: &Config| otry!(c.footprint).cache_size_db.clone(),
 		flag_cache_size_blocks: u32 = 8u32,
@@ -295,12 +386,32 @@ usage! {
 			or |c: &Config| otry!(c.footprint).fast_and_loose.clone(),
 		flag_db_compaction: String = "auto",
 			or |c: &Config| otry!(c.footprint).db_compaction.clone(),
+		flag_db_compaction_interval: u64 = 0u64,
+			or |c: &Config| otry!(c.footprint).db_compaction_interval.clone(),
+		flag_db_compaction_rate_limit: Option<u64> = None,
+			or |c: &Config| otry!(c.footprint).db_compaction_rate_limit.clone().map(Some),
 		flag_fat_db: String = "auto",
 			or |c: &Config| otry!(c.footprint).fat_db.clone(),
 		flag_scale_verifiers: bool = false,
 			or |c: &Config| otry!(c.footprint).scale_verifiers.clone(),
 		flag_num_verifiers: Option<usize> = None,
 			or |c: &Config| otry!(c.footprint).num_verifiers.clone().map(Some),
+		flag_dedup_bodies: bool = false,
+			or |c: &Config| otry!(c.footprint).dedup_bodies.clone(),
+		flag_seal_verification: String = "full",
+			or |c: &Config| otry!(c.footprint).seal_verification.clone(),
+		flag_light_checkpoint: Option<String> = None,
+			or |c: &Config| otry!(c.footprint).light_checkpoint.clone().map(Some),
+		flag_future_block_gap: u64 = 0u64,
+			or |c: &Config| otry!(c.footprint).future_block_gap.clone(),
+		flag_replay_bundle_dir: Option<String> = None,
+			or |c: &Config| otry!(c.footprint).replay_bundle_dir.clone().map(Some),
+		flag_clear_bad_blocks: bool = false,
+			or |c: &Config| otry!(c.footprint).clear_bad_blocks.clone(),
+		flag_tx_index_history: Option<u64> = None,
+			or |c: &Config| otry!(c.footprint).tx_index_history.clone().map(Some),
+		flag_tx_index_watch: Option<String> = None,
+			or |c: &Config| otry!(c.footprint).tx_index_watch.as_ref().map(|vec| Some(vec.join(","))),
 
 		// -- Import/Export Options
 		flag_from: String = "1", or |_| None,
@@ -311,6 +422,10 @@ usage! {
 		flag_no_code: bool = false, or |_| None,
 		flag_min_balance: Option<String> = None, or |_| None,
 		flag_max_balance: Option<String> = None, or |_| None,
+		flag_reindex_workers: Option<usize> = None, or |_| None,
+		flag_log_address: Option<String> = None, or |_| None,
+		flag_log_topic: Option<String> = None, or |_| None,
+		flag_resume_after: Option<String> = None, or |_| None,
 
 		// -- Snapshot Optons
 		flag_at: String = "latest", or |_| None,
@@ -327,8 +442,24 @@ usage! {
 			or |c: &Config| otry!(c.misc).logging.clone().map(Some),
 		flag_log_file: Option<String> = None,
 			or |c: &Config| otry!(c.misc).log_file.clone().map(Some),
+		flag_access_log_format: String = "combined",
+			or |c: &Config| otry!(c.misc).access_log_format.clone(),
+		flag_access_log_max_size: u64 = 0u64,
+			or |c: &Config| otry!(c.misc).access_log_max_size.clone(),
+		flag_access_log_trusted_proxy: Option<String> = None,
+			or |c: &Config| otry!(c.misc).access_log_trusted_proxy.clone().map(Some),
 		flag_no_color: bool = false,
 			or |c: &Config| otry!(c.misc).color.map(|c| !c).clone(),
+		flag_ntp_servers: String = "0.pool.ntp.org:123,1.pool.ntp.org:123,2.pool.ntp.org:123",
+			or |c: &Config| otry!(c.misc).ntp_servers.as_ref().map(|vec| vec.join(",")),
+		flag_head_lag_warning: u64 = 300u64,
+			or |c: &Config| otry!(c.misc).head_lag_warning.clone(),
+		flag_webhook_urls: Option<String> = None,
+			or |c: &Config| otry!(c.misc).webhook_urls.as_ref().map(|vec| Some(vec.join(","))),
+		flag_webhook_addresses: Option<String> = None,
+			or |c: &Config| otry!(c.misc).webhook_addresses.as_ref().map(|vec| Some(vec.join(","))),
+		flag_webhook_key: Option<String> = None,
+			or |c: &Config| otry!(c.misc).webhook_key.clone().map(Some),
 	}
 	{
 		// Values with optional default value.
@@ -372,6 +503,8 @@ struct Operating {
 	db_path: Option<String>,
 	keys_path: Option<String>,
 	identity: Option<String>,
+	read_only: Option<bool>,
+	shared_db_primary: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -380,6 +513,9 @@ struct Account {
 	password: Option<Vec<String>>,
 	keys_iterations: Option<u32>,
 	disable_hardware: Option<bool>,
+	backup: Option<String>,
+	backup_interval: Option<String>,
+	backup_password: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -389,6 +525,7 @@ struct Ui {
 	port: Option<u16>,
 	interface: Option<String>,
 	path: Option<String>,
+	apis: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -407,6 +544,9 @@ struct Network {
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
+	head_stall_timeout: Option<u64>,
+	request_parallelism: Option<String>,
+	ancient_receipts_depth: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -417,6 +557,23 @@ struct Rpc {
 	cors: Option<String>,
 	apis: Option<Vec<String>>,
 	hosts: Option<Vec<String>>,
+	hosts_ignore_port: Option<bool>,
+	allow_unsafe_apis: Option<bool>,
+	trusted: Option<bool>,
+	trusted_port: Option<u16>,
+	trusted_apis: Option<Vec<String>>,
+	access_log: Option<String>,
+	signing_audit: Option<String>,
+	abi_dir: Option<String>,
+	call_cache_ttl: Option<u64>,
+	call_cache_size: Option<usize>,
+	missing_cache_ttl: Option<u64>,
+	max_response: Option<String>,
+	max_concurrent: Option<usize>,
+	max_header_size: Option<usize>,
+	max_connections: Option<usize>,
+	keep_alive_timeout: Option<u64>,
+	api_policies: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -424,6 +581,7 @@ struct Ipc {
 	disable: Option<bool>,
 	path: Option<String>,
 	apis: Option<Vec<String>>,
+	max_concurrent: Option<usize>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -453,12 +611,17 @@ struct Ipfs {
 	interface: Option<String>,
 	cors: Option<String>,
 	hosts: Option<Vec<String>>,
+	hosts_ignore_port: Option<bool>,
+	cid_hash: Option<String>,
+	access_log: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
 struct Mining {
 	author: Option<String>,
 	engine_signer: Option<String>,
+	authorize_signers: Option<Vec<String>>,
+	deauthorize_signers: Option<Vec<String>>,
 	force_sealing: Option<bool>,
 	reseal_on_txs: Option<String>,
 	reseal_min_period: Option<u64>,
@@ -472,15 +635,23 @@ struct Mining {
 	price_update_period: Option<String>,
 	gas_floor_target: Option<String>,
 	gas_cap: Option<String>,
+	gas_limit_contract: Option<String>,
 	extra_data: Option<String>,
 	tx_queue_size: Option<usize>,
 	tx_queue_gas: Option<String>,
+	tx_queue_nonce_gap: Option<u64>,
+	tx_queue_per_sender: Option<usize>,
+	tx_queue_max_init_code: Option<usize>,
 	tx_queue_strategy: Option<String>,
 	tx_queue_ban_count: Option<u16>,
 	tx_queue_ban_time: Option<u16>,
 	remove_solved: Option<bool>,
 	notify_work: Option<Vec<String>>,
 	refuse_service_transactions: Option<bool>,
+	prepare_block_ahead: Option<bool>,
+	local_gas_reserve: Option<u8>,
+	work_auth_secret: Option<String>,
+	work_submit_limit: Option<u32>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -496,6 +667,7 @@ struct Footprint {
 	pruning: Option<String>,
 	pruning_history: Option<u64>,
 	pruning_memory: Option<usize>,
+	select_node_mode: Option<String>,
 	fast_and_loose: Option<bool>,
 	cache_size: Option<u32>,
 	cache_size_db: Option<u32>,
@@ -503,9 +675,19 @@ struct Footprint {
 	cache_size_queue: Option<u32>,
 	cache_size_state: Option<u32>,
 	db_compaction: Option<String>,
+	db_compaction_interval: Option<u64>,
+	db_compaction_rate_limit: Option<u64>,
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	dedup_bodies: Option<bool>,
+	seal_verification: Option<String>,
+	light_checkpoint: Option<String>,
+	future_block_gap: Option<u64>,
+	replay_bundle_dir: Option<String>,
+	clear_bad_blocks: Option<bool>,
+	tx_index_history: Option<u64>,
+	tx_index_watch: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, PartialEq, RustcDecodable)]
@@ -522,7 +704,15 @@ struct VM {
 struct Misc {
 	logging: Option<String>,
 	log_file: Option<String>,
+	access_log_format: Option<String>,
+	access_log_max_size: Option<u64>,
+	access_log_trusted_proxy: Option<String>,
 	color: Option<bool>,
+	ntp_servers: Option<Vec<String>>,
+	head_lag_warning: Option<u64>,
+	webhook_urls: Option<Vec<String>>,
+	webhook_addresses: Option<Vec<String>>,
+	webhook_key: Option<String>,
 }
 
 #[cfg(test)]
@@ -597,6 +787,8 @@ mod tests {
 			cmd_export: false,
 			cmd_state: false,
 			cmd_blocks: false,
+			cmd_analytics: false,
+			cmd_logs: false,
 			cmd_import: false,
 			cmd_signer: false,
 			cmd_sign: false,
@@ -610,6 +802,13 @@ mod tests {
 			cmd_hash: false,
 			cmd_db: false,
 			cmd_kill: false,
+			cmd_reindex_transactions: false,
+			cmd_migrate_to_archive: false,
+			cmd_backfill_traces: false,
+			cmd_replay: false,
+			cmd_rebuild_cht: false,
+			cmd_chains: false,
+			cmd_use: false,
 
 			// Arguments
 			arg_pid_file: "".into(),
@@ -630,12 +829,17 @@ mod tests {
 			flag_db_path: Some("$HOME/.parity/chains".into()),
 			flag_keys_path: "$HOME/.parity/keys".into(),
 			flag_identity: "".into(),
+			flag_read_only: false,
+			flag_shared_db_primary: None,
 
 			// -- Account Options
 			flag_unlock: Some("0xdeadbeefcafe0000000000000000000000000000".into()),
 			flag_password: vec!["~/.safe/password.file".into()],
 			flag_keys_iterations: 10240u32,
 			flag_no_hardware_wallets: false,
+			flag_keys_backup: None,
+			flag_keys_backup_interval: "daily".into(),
+			flag_keys_backup_password: None,
 
 			flag_force_ui: false,
 			flag_no_ui: false,
@@ -643,6 +847,7 @@ mod tests {
 			flag_ui_interface: "127.0.0.1".into(),
 			flag_ui_path: "$HOME/.parity/signer".into(),
 			flag_ui_no_validation: false,
+			flag_ui_apis: None,
 
 			// -- Networking Options
 			flag_no_warp: false,
@@ -660,6 +865,9 @@ mod tests {
 			flag_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
+			flag_head_stall_timeout: 120u64,
+			flag_request_parallelism: "auto".into(),
+			flag_ancient_receipts_depth: 0u64,
 
 			// -- API and Console Options
 			// RPC
@@ -668,12 +876,30 @@ mod tests {
 			flag_jsonrpc_interface: "local".into(),
 			flag_jsonrpc_cors: Some("null".into()),
 			flag_jsonrpc_apis: "web3,eth,net,parity,traces,rpc".into(),
+			flag_api_policies: None,
 			flag_jsonrpc_hosts: "none".into(),
+			flag_jsonrpc_hosts_ignore_port: false,
+			flag_jsonrpc_allow_unsafe_apis: false,
+			flag_trusted_rpc: false,
+			flag_trusted_rpc_port: 8547u16,
+			flag_trusted_rpc_apis: "web3,eth,net,parity,traces,rpc,personal,signer,parity_accounts,parity_set".into(),
+			flag_jsonrpc_access_log: None,
+			flag_jsonrpc_signing_audit: None,
+			flag_jsonrpc_abi_dir: None,
+			flag_jsonrpc_call_cache_ttl: 0u64,
+			flag_jsonrpc_call_cache_size: 65536usize,
+			flag_jsonrpc_missing_cache_ttl: 10u64,
+			flag_jsonrpc_max_response: "none".into(),
+			flag_jsonrpc_max_concurrent: 0usize,
+			flag_jsonrpc_max_header_size: 32768usize,
+			flag_jsonrpc_max_connections: 0usize,
+			flag_jsonrpc_keep_alive_timeout: 10u64,
 
 			// IPC
 			flag_no_ipc: false,
 			flag_ipc_path: "$HOME/.parity/jsonrpc.ipc".into(),
 			flag_ipc_apis: "web3,eth,net,parity,parity_accounts,personal,traces,rpc".into(),
+			flag_ipc_max_concurrent: 16usize,
 
 			// DAPPS
 			flag_no_dapps: false,
@@ -697,10 +923,15 @@ mod tests {
 			flag_ipfs_api_interface: "local".into(),
 			flag_ipfs_api_cors: Some("null".into()),
 			flag_ipfs_api_hosts: "none".into(),
+			flag_ipfs_api_hosts_ignore_port: false,
+			flag_ipfs_api_cid_hash: "keccak-256".into(),
+			flag_ipfs_api_access_log: None,
 
 			// -- Sealing/Mining Options
 			flag_author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 			flag_engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+			flag_authorize_signers: None,
+			flag_deauthorize_signers: None,
 			flag_force_sealing: true,
 			flag_reseal_on_txs: "all".into(),
 			flag_reseal_min_period: 4000u64,
@@ -714,15 +945,23 @@ mod tests {
 			flag_price_update_period: "hourly".into(),
 			flag_gas_floor_target: "4700000".into(),
 			flag_gas_cap: "6283184".into(),
+			flag_gas_limit_contract: None,
 			flag_extra_data: Some("Parity".into()),
 			flag_tx_queue_size: 1024usize,
 			flag_tx_queue_gas: "auto".into(),
+			flag_tx_queue_nonce_gap: Some(1024u64),
+			flag_tx_queue_per_sender: Some(256usize),
+			flag_tx_queue_max_init_code: None,
 			flag_tx_queue_strategy: "gas_factor".into(),
 			flag_tx_queue_ban_count: 1u16,
 			flag_tx_queue_ban_time: 180u16,
 			flag_remove_solved: false,
 			flag_notify_work: Some("http://localhost:3001".into()),
 			flag_refuse_service_transactions: false,
+			flag_prepare_block_ahead: false,
+			flag_local_gas_reserve: 10u8,
+			flag_work_auth_secret: None,
+			flag_work_submit_limit: Some(60u32),
 
 			flag_stratum: false,
 			flag_stratum_interface: "local".to_owned(),
@@ -734,6 +973,7 @@ mod tests {
 			flag_pruning: "auto".into(),
 			flag_pruning_history: 64u64,
 			flag_pruning_memory: 500usize,
+			flag_select_node_mode: "recommend".into(),
 			flag_cache_size_db: 64u32,
 			flag_cache_size_blocks: 8u32,
 			flag_cache_size_queue: 50u32,
@@ -741,9 +981,19 @@ mod tests {
 			flag_cache_size: Some(128),
 			flag_fast_and_loose: false,
 			flag_db_compaction: "ssd".into(),
+			flag_db_compaction_interval: 0u64,
+			flag_db_compaction_rate_limit: None,
 			flag_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			flag_num_verifiers: Some(6),
+			flag_dedup_bodies: false,
+			flag_seal_verification: "full".into(),
+			flag_light_checkpoint: None,
+			flag_future_block_gap: 0u64,
+			flag_replay_bundle_dir: None,
+			flag_clear_bad_blocks: false,
+			flag_tx_index_history: None,
+			flag_tx_index_watch: None,
 
 			// -- Import/Export Options
 			flag_from: "1".into(),
@@ -754,6 +1004,10 @@ mod tests {
 			flag_no_storage: false,
 			flag_min_balance: None,
 			flag_max_balance: None,
+			flag_reindex_workers: None,
+			flag_log_address: None,
+			flag_log_topic: None,
+			flag_resume_after: None,
 
 			// -- Snapshot Optons
 			flag_at: "latest".into(),
@@ -795,7 +1049,15 @@ mod tests {
 			flag_config: "$BASE/config.toml".into(),
 			flag_logging: Some("own_tx=trace".into()),
 			flag_log_file: Some("/var/log/parity.log".into()),
+			flag_access_log_format: "combined".into(),
+			flag_access_log_max_size: 0,
+			flag_access_log_trusted_proxy: None,
 			flag_no_color: false,
+			flag_ntp_servers: "0.pool.ntp.org:123,1.pool.ntp.org:123,2.pool.ntp.org:123".into(),
+			flag_head_lag_warning: 300u64,
+			flag_webhook_urls: None,
+			flag_webhook_addresses: None,
+			flag_webhook_key: None,
 			flag_no_config: false,
 		});
 	}
@@ -832,12 +1094,17 @@ mod tests {
 				db_path: None,
 				keys_path: None,
 				identity: None,
+				read_only: None,
+				shared_db_primary: None,
 			}),
 			account: Some(Account {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
 				password: Some(vec!["passwdfile path".into()]),
 				keys_iterations: None,
 				disable_hardware: None,
+				backup: None,
+				backup_interval: None,
+				backup_password: None,
 			}),
 			ui: Some(Ui {
 				force: None,
@@ -845,6 +1112,7 @@ mod tests {
 				port: None,
 				interface: None,
 				path: None,
+				apis: None,
 			}),
 			network: Some(Network {
 				warp: Some(false),
@@ -861,6 +1129,9 @@ mod tests {
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
+				head_stall_timeout: None,
+				request_parallelism: None,
+				ancient_receipts_depth: None,
 			}),
 			rpc: Some(Rpc {
 				disable: Some(true),
@@ -869,11 +1140,29 @@ mod tests {
 				cors: None,
 				apis: None,
 				hosts: None,
+				hosts_ignore_port: None,
+				allow_unsafe_apis: None,
+				trusted: None,
+				trusted_port: None,
+				trusted_apis: None,
+				access_log: None,
+				signing_audit: None,
+				abi_dir: None,
+				call_cache_ttl: None,
+				call_cache_size: None,
+				missing_cache_ttl: None,
+				max_response: None,
+				max_concurrent: None,
+				max_header_size: None,
+				max_connections: None,
+				keep_alive_timeout: None,
+				api_policies: None,
 			}),
 			ipc: Some(Ipc {
 				disable: None,
 				path: None,
 				apis: Some(vec!["rpc".into(), "eth".into()]),
+				max_concurrent: None,
 			}),
 			dapps: Some(Dapps {
 				disable: None,
@@ -897,10 +1186,15 @@ mod tests {
 				interface: None,
 				cors: None,
 				hosts: None,
+				hosts_ignore_port: None,
+				cid_hash: None,
+				access_log: None,
 			}),
 			mining: Some(Mining {
 				author: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
 				engine_signer: Some("0xdeadbeefcafe0000000000000000000000000001".into()),
+				authorize_signers: None,
+				deauthorize_signers: None,
 				force_sealing: Some(true),
 				reseal_on_txs: Some("all".into()),
 				reseal_min_period: Some(4000),
@@ -912,8 +1206,12 @@ mod tests {
 				price_update_period: Some("hourly".into()),
 				gas_floor_target: None,
 				gas_cap: None,
+				gas_limit_contract: None,
 				tx_queue_size: Some(1024),
 				tx_queue_gas: Some("auto".into()),
+				tx_queue_nonce_gap: None,
+				tx_queue_per_sender: None,
+				tx_queue_max_init_code: None,
 				tx_queue_strategy: None,
 				tx_queue_ban_count: None,
 				tx_queue_ban_time: None,
@@ -923,12 +1221,17 @@ mod tests {
 				remove_solved: None,
 				notify_work: None,
 				refuse_service_transactions: None,
+				prepare_block_ahead: None,
+				local_gas_reserve: None,
+				work_auth_secret: None,
+				work_submit_limit: None,
 			}),
 			footprint: Some(Footprint {
 				tracing: Some("on".into()),
 				pruning: Some("fast".into()),
 				pruning_history: Some(64),
 				pruning_memory: None,
+				select_node_mode: None,
 				fast_and_loose: None,
 				cache_size: None,
 				cache_size_db: Some(128),
@@ -936,9 +1239,19 @@ mod tests {
 				cache_size_queue: Some(100),
 				cache_size_state: Some(25),
 				db_compaction: Some("ssd".into()),
+				db_compaction_interval: None,
+				db_compaction_rate_limit: None,
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				dedup_bodies: None,
+				seal_verification: None,
+				light_checkpoint: None,
+				future_block_gap: None,
+				replay_bundle_dir: None,
+				clear_bad_blocks: None,
+				tx_index_history: None,
+				tx_index_watch: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
@@ -949,7 +1262,15 @@ mod tests {
 			misc: Some(Misc {
 				logging: Some("own_tx=trace".into()),
 				log_file: Some("/var/log/parity.log".into()),
+				access_log_format: None,
+				access_log_max_size: None,
+				access_log_trusted_proxy: None,
 				color: Some(true),
+				ntp_servers: None,
+				head_lag_warning: None,
+				webhook_urls: None,
+				webhook_addresses: None,
+				webhook_key: None,
 			}),
 			stratum: None,
 		});
@@ -16,37 +16,44 @@
 
 use std::time::Duration;
 use std::io::{Read, Write, stderr};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::cmp::max;
 use cli::{Args, ArgsError};
 use util::{Hashable, H256, U256, Uint, Bytes, version_data, Address};
 use util::journaldb::Algorithm;
 use util::Colour;
-use ethsync::{NetworkConfiguration, is_valid_node_url, AllowIP};
+use ethsync::{NetworkConfiguration, is_valid_node_url, AllowIP, MAX_REQUEST_PARALLELISM};
 use ethcore::ethstore::ethkey::Secret;
 use ethcore::client::{VMType};
 use ethcore::miner::{MinerOptions, Banning, StratumOptions};
 use ethcore::verification::queue::VerifierSettings;
 
-use rpc::{IpcConfiguration, HttpConfiguration};
+use rpc::{IpcConfiguration, IpcLimits, HttpConfiguration, HttpLimits};
+use rpc_apis::{ApiSet, ApiPolicies};
 use ethcore_rpc::NetworkSettings;
 use cache::CacheConfig;
 use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, replace_home, replace_home_for_db,
-geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_gas_limit, to_queue_strategy};
+geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_hashes, to_address, to_gas_limit, to_queue_strategy,
+password_from_file};
 use params::{SpecType, ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, Pruning, Switch};
 use ethcore_logger::Config as LogConfig;
 use dir::{self, Directories, default_hypervisor_path, default_local_path, default_data_path};
 use dapps::Configuration as DappsConfiguration;
 use ipfs::Configuration as IpfsConfiguration;
-use signer::{Configuration as SignerConfiguration};
+use keys_backup::Configuration as KeysBackupConfiguration;
+use webhooks::Configuration as WebhooksConfiguration;
+use parity_ipfs_api::CidHash;
+use signer::{Configuration as SignerConfiguration, TrustedConfiguration};
 use secretstore::Configuration as SecretStoreConfiguration;
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, ExportAnalytics, ExportLogs, ReindexTransactions, MigrateToArchive, BackfillTraces, ReplayBlock, RebuildCht, DataFormat};
+use analytics::AnalyticsFormat;
 use presale::ImportWallet;
-use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
+use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts, RestoreAccounts};
 use snapshot::{self, SnapshotCommand};
+use chains::{self, ChainsCmd, ListChains, UseChain};
 
 const AUTHCODE_FILENAME: &'static str = "authcodes";
 
@@ -75,6 +82,7 @@ pub enum Cmd {
 	},
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
+	Chains(ChainsCmd),
 }
 
 pub struct Execute {
@@ -132,10 +140,14 @@ impl Configuration {
 		let geth_compatibility = self.args.flag_geth;
 		let ui_address = self.ui_port().map(|port| (self.ui_interface(), port));
 		let dapps_conf = self.dapps_config();
-		let ipfs_conf = self.ipfs_config();
-		let signer_conf = self.signer_config();
+		let ipfs_conf = self.ipfs_config()?;
+		let signer_conf = self.signer_config()?;
 		let secretstore_conf = self.secretstore_config();
-		let format = self.format()?;
+		// analytics export has its own set of formats
+		let format = match self.args.cmd_analytics {
+			true => None,
+			false => self.format()?,
+		};
 
 		let cmd = if self.args.flag_version {
 			Cmd::Version
@@ -177,6 +189,83 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_reindex_transactions {
+			Cmd::Blockchain(BlockchainCmd::ReindexTransactions(ReindexTransactions {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				tracing: tracing,
+				fat_db: fat_db,
+				from_block: to_block_id(&self.args.flag_from)?,
+				to_block: to_block_id(&self.args.flag_to)?,
+				workers: self.args.flag_reindex_workers.unwrap_or_else(::num_cpus::get),
+			}))
+		} else if self.args.cmd_db && self.args.cmd_replay {
+			Cmd::Blockchain(BlockchainCmd::Replay(ReplayBlock {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				file_path: self.args.arg_file.clone().ok_or("Replay bundle file is required")?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_rebuild_cht {
+			Cmd::Blockchain(BlockchainCmd::RebuildCht(RebuildCht {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_backfill_traces {
+			Cmd::Blockchain(BlockchainCmd::BackfillTraces(BackfillTraces {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				fat_db: fat_db,
+				from_block: to_block_id(&self.args.flag_from)?,
+				to_block: to_block_id(&self.args.flag_to)?,
+				workers: self.args.flag_reindex_workers.unwrap_or_else(::num_cpus::get),
+			}))
+		} else if self.args.cmd_db && self.args.cmd_migrate_to_archive {
+			Cmd::Blockchain(BlockchainCmd::MigrateToArchive(MigrateToArchive {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.flag_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+			}))
+		} else if self.args.cmd_chains {
+			let chains_cmd = if self.args.cmd_use {
+				ChainsCmd::Use(UseChain {
+					db_path: dirs.db,
+					chain: self.args.arg_chain.clone().ok_or("Chain to use is required")?,
+				})
+			} else {
+				ChainsCmd::List(ListChains {
+					db_path: dirs.db,
+				})
+			};
+			Cmd::Chains(chains_cmd)
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_new {
 				let new_acc = NewAccount {
@@ -199,6 +288,15 @@ impl Configuration {
 					spec: spec,
 				};
 				AccountCmd::Import(import_acc)
+			} else if self.args.cmd_restore {
+				let restore_acc = RestoreAccounts {
+					target: self.args.arg_path.first().cloned().unwrap_or_default(),
+					backup: self.args.arg_id.map(|id| id as u64),
+					to: dirs.keys,
+					spec: spec,
+					password_file: self.args.flag_keys_backup_password.clone(),
+				};
+				AccountCmd::Restore(restore_acc)
 			} else {
 				unreachable!();
 			};
@@ -237,6 +335,7 @@ impl Configuration {
 				fat_db: fat_db,
 				vm_type: vm_type,
 				check_seal: !self.args.flag_no_seal_check,
+				seal_policy: self.args.flag_seal_verification.parse()?,
 				with_color: logger_config.color,
 				verifier_settings: self.verifier_settings(),
 			};
@@ -282,6 +381,53 @@ impl Configuration {
 					max_balance: self.args.flag_max_balance.and_then(|s| to_u256(&s).ok()),
 				};
 				Cmd::Blockchain(BlockchainCmd::ExportState(export_cmd))
+			} else if self.args.cmd_analytics {
+				let export_cmd = ExportAnalytics {
+					spec: spec,
+					cache_config: cache_config,
+					dirs: dirs,
+					dir_path: self.args.arg_file.clone(),
+					format: self.analytics_format()?,
+					pruning: pruning,
+					pruning_history: pruning_history,
+					pruning_memory: self.args.flag_pruning_memory,
+					compaction: compaction,
+					wal: wal,
+					tracing: tracing,
+					fat_db: fat_db,
+					from_block: to_block_id(&self.args.flag_from)?,
+					to_block: to_block_id(&self.args.flag_to)?,
+				};
+				Cmd::Blockchain(BlockchainCmd::ExportAnalytics(export_cmd))
+			} else if self.args.cmd_logs {
+				let export_cmd = ExportLogs {
+					spec: spec,
+					cache_config: cache_config,
+					dirs: dirs,
+					file_path: self.args.arg_file.clone(),
+					pruning: pruning,
+					pruning_history: pruning_history,
+					pruning_memory: self.args.flag_pruning_memory,
+					compaction: compaction,
+					wal: wal,
+					tracing: tracing,
+					fat_db: fat_db,
+					from_block: to_block_id(&self.args.flag_from)?,
+					to_block: to_block_id(&self.args.flag_to)?,
+					addresses: match self.args.flag_log_address {
+						Some(_) => Some(to_addresses(&self.args.flag_log_address)?),
+						None => None,
+					},
+					topics: match self.args.flag_log_topic {
+						Some(_) => Some(to_hashes(&self.args.flag_log_topic)?),
+						None => None,
+					},
+					resume: match self.args.flag_resume_after {
+						Some(ref cursor) => Some(cursor.parse()?),
+						None => None,
+					},
+				};
+				Cmd::Blockchain(BlockchainCmd::ExportLogs(export_cmd))
 			} else {
 				unreachable!();
 			}
@@ -334,6 +480,7 @@ impl Configuration {
 				_ => (self.gas_pricer_config()?, self.miner_options(self.args.flag_reseal_min_period)?),
 			};
 
+			let replay_bundle_dir = self.args.flag_replay_bundle_dir.as_ref().map(|dir| replace_home(&dirs.base, dir));
 			let run_cmd = RunCmd {
 				cache_config: cache_config,
 				dirs: dirs,
@@ -341,6 +488,7 @@ impl Configuration {
 				pruning: pruning,
 				pruning_history: pruning_history,
 				pruning_memory: self.args.flag_pruning_memory,
+				node_mode: self.args.flag_select_node_mode.parse()?,
 				daemon: daemon,
 				logger_config: logger_config.clone(),
 				miner_options: miner_options,
@@ -361,11 +509,24 @@ impl Configuration {
 				vm_type: vm_type,
 				warp_sync: warp_sync,
 				geth_compatibility: geth_compatibility,
+				call_cache_ttl: self.args.flag_jsonrpc_call_cache_ttl,
+				call_cache_size: self.args.flag_jsonrpc_call_cache_size,
+				missing_cache_ttl: self.args.flag_jsonrpc_missing_cache_ttl,
+				work_auth_secret: self.args.flag_work_auth_secret.clone(),
+				work_submit_limit: self.args.flag_work_submit_limit,
+				response_limits: self.args.flag_jsonrpc_max_response.parse()?,
+				signing_audit: self.args.flag_jsonrpc_signing_audit.clone(),
+				abi_dir: self.args.flag_jsonrpc_abi_dir.clone(),
+				ntp_servers: self.ntp_servers(),
+				head_lag_warning: self.args.flag_head_lag_warning,
 				ui_address: ui_address,
 				net_settings: self.network_settings(),
 				dapps_conf: dapps_conf,
 				ipfs_conf: ipfs_conf,
+				keys_backup_conf: self.keys_backup_config()?,
+				webhooks_conf: self.webhooks_config()?,
 				signer_conf: signer_conf,
+				trusted_rpc_conf: self.trusted_rpc_config()?,
 				secretstore_conf: secretstore_conf,
 				dapp: self.dapp_to_open()?,
 				ui: self.args.cmd_ui,
@@ -374,7 +535,34 @@ impl Configuration {
 				no_periodic_snapshot: self.args.flag_no_periodic_snapshot,
 				check_seal: !self.args.flag_no_seal_check,
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
+				head_stall_timeout: match self.args.flag_head_stall_timeout {
+					0 => None,
+					timeout => Some(timeout),
+				},
+				request_parallelism: self.request_parallelism()?,
+				ancient_receipts_depth: match self.args.flag_ancient_receipts_depth {
+					0 => None,
+					depth => Some(depth),
+				},
 				verifier_settings: verifier_settings,
+				dedup_bodies: self.args.flag_dedup_bodies,
+				seal_policy: self.args.flag_seal_verification.parse()?,
+				light_checkpoint: match self.args.flag_light_checkpoint {
+					Some(ref checkpoint) => Some(checkpoint.parse()?),
+					None => None,
+				},
+				read_only: self.args.flag_read_only || self.args.flag_shared_db_primary.is_some(),
+				shared_db_primary: self.shared_db_primary(),
+				db_compaction_interval: match self.args.flag_db_compaction_interval {
+					0 => None,
+					interval => Some(interval),
+				},
+				db_compaction_rate_limit: self.args.flag_db_compaction_rate_limit,
+				future_block_gap: self.args.flag_future_block_gap,
+				replay_bundle_dir: replay_bundle_dir,
+				clear_bad_blocks: self.args.flag_clear_bad_blocks,
+				tx_index_history: self.args.flag_tx_index_history,
+				tx_index_watch: to_addresses(&self.args.flag_tx_index_watch)?,
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -401,6 +589,7 @@ impl Configuration {
 			gas_ceil_target: to_u256(&self.args.flag_gas_cap)?,
 			transactions_limit: self.args.flag_tx_queue_size,
 			engine_signer: self.engine_signer()?,
+			signer_votes: self.signer_votes()?,
 		};
 
 		Ok(extras)
@@ -414,6 +603,12 @@ impl Configuration {
 		to_address(self.args.flag_engine_signer.clone())
 	}
 
+	fn signer_votes(&self) -> Result<Vec<(Address, bool)>, String> {
+		let authorize = to_addresses(&self.args.flag_authorize_signers)?.into_iter().map(|a| (a, true));
+		let deauthorize = to_addresses(&self.args.flag_deauthorize_signers)?.into_iter().map(|a| (a, false));
+		Ok(authorize.chain(deauthorize).collect())
+	}
+
 	fn format(&self) -> Result<Option<DataFormat>, String> {
 		match self.args.flag_format {
 			Some(ref f) => Ok(Some(f.parse()?)),
@@ -421,6 +616,13 @@ impl Configuration {
 		}
 	}
 
+	fn analytics_format(&self) -> Result<AnalyticsFormat, String> {
+		match self.args.flag_format {
+			Some(ref f) => f.parse(),
+			None => Ok(Default::default()),
+		}
+	}
+
 	fn cache_config(&self) -> CacheConfig {
 		match self.args.flag_cache_size.or(self.args.flag_cache) {
 			Some(size) => CacheConfig::new_with_total_cache_size(size),
@@ -447,6 +649,8 @@ impl Configuration {
 		}
 		else if self.args.flag_testnet {
 			"testnet".to_owned()
+		} else if self.args.flag_chain == "last" {
+			chains::last_chain(&self.db_path()).unwrap_or_else(|| chains::DEFAULT_CHAIN.to_owned())
 		} else {
 			self.args.flag_chain.clone()
 		}
@@ -474,6 +678,16 @@ impl Configuration {
 		self.args.flag_max_pending_peers as u32
 	}
 
+	fn request_parallelism(&self) -> Result<Option<usize>, String> {
+		match self.args.flag_request_parallelism.as_str() {
+			"auto" => Ok(None),
+			value => match value.parse() {
+				Ok(n) if n >= 1 && n <= MAX_REQUEST_PARALLELISM => Ok(Some(n)),
+				_ => Err(format!("Invalid request parallelism: {}. Must be auto or between 1 and {}", value, MAX_REQUEST_PARALLELISM)),
+			},
+		}
+	}
+
 	fn snapshot_peers(&self) -> u32 {
 		self.args.flag_snapshot_peers as u32
 	}
@@ -518,6 +732,8 @@ impl Configuration {
 				None => U256::max_value(),
 			},
 			tx_queue_size: self.args.flag_tx_queue_size,
+			tx_queue_nonce_gap: self.args.flag_tx_queue_nonce_gap.map_or_else(U256::max_value, Into::into),
+			tx_queue_sender_memory_limit: self.args.flag_tx_queue_per_sender.map_or(usize::max_value(), |kb| kb * 1024),
 			tx_queue_gas_limit: to_gas_limit(&self.args.flag_tx_queue_gas)?,
 			tx_queue_strategy: to_queue_strategy(&self.args.flag_tx_queue_strategy)?,
 			pending_set: to_pending_set(&self.args.flag_relay_set)?,
@@ -534,18 +750,49 @@ impl Configuration {
 				None => Banning::Disabled,
 			},
 			refuse_service_transactions: self.args.flag_refuse_service_transactions,
+			prepare_block_ahead: self.args.flag_prepare_block_ahead,
+			gas_limit_contract: match self.args.flag_gas_limit_contract {
+				Some(ref addr) => Some(to_address(Some(addr.clone()))?),
+				None => None,
+			},
+			tx_queue_max_init_code_size: self.args.flag_tx_queue_max_init_code,
+			local_gas_reserve: self.args.flag_local_gas_reserve,
 		};
 
+		if options.local_gas_reserve > 100 {
+			return Err(format!("Invalid local gas reserve: {}%. Must be at most 100%.", options.local_gas_reserve));
+		}
+
 		Ok(options)
 	}
 
-	fn signer_config(&self) -> SignerConfiguration {
-		SignerConfiguration {
+	fn signer_config(&self) -> Result<SignerConfiguration, String> {
+		Ok(SignerConfiguration {
 			enabled: self.ui_enabled(),
 			port: self.args.flag_ui_port,
 			interface: self.ui_interface(),
 			signer_path: self.directories().signer,
 			skip_origin_validation: self.args.flag_ui_no_validation,
+			apis: match self.args.flag_ui_apis {
+				Some(ref apis) => self.api_policies()?.parse_api_set(apis)?,
+				None => ApiSet::SafeContext,
+			},
+		})
+	}
+
+	fn trusted_rpc_config(&self) -> Result<TrustedConfiguration, String> {
+		Ok(TrustedConfiguration {
+			enabled: self.args.flag_trusted_rpc,
+			port: self.args.flag_trusted_rpc_port,
+			signer_path: self.directories().signer,
+			apis: self.api_policies()?.parse_api_set(&self.args.flag_trusted_rpc_apis)?,
+		})
+	}
+
+	fn api_policies(&self) -> Result<ApiPolicies, String> {
+		match self.args.flag_api_policies {
+			Some(ref path) => ApiPolicies::load(path),
+			None => Ok(ApiPolicies::default()),
 		}
 	}
 
@@ -577,14 +824,52 @@ impl Configuration {
 		}
 	}
 
-	fn ipfs_config(&self) -> IpfsConfiguration {
-		IpfsConfiguration {
+	fn ipfs_config(&self) -> Result<IpfsConfiguration, String> {
+		let conf = IpfsConfiguration {
 			enabled: self.args.flag_ipfs_api,
 			port: self.args.flag_ipfs_api_port,
 			interface: self.ipfs_interface(),
 			cors: self.ipfs_cors(),
 			hosts: self.ipfs_hosts(),
-		}
+			hosts_ignore_port: self.args.flag_ipfs_api_hosts_ignore_port,
+			cid_hash: self.ipfs_cid_hash()?,
+			access_log: self.args.flag_ipfs_api_access_log.clone(),
+			access_log_format: self.args.flag_access_log_format.parse()?,
+			access_log_max_size: self.access_log_max_size(),
+			access_log_trusted_proxy: self.access_log_trusted_proxy()?,
+		};
+
+		Ok(conf)
+	}
+
+	fn keys_backup_config(&self) -> Result<KeysBackupConfiguration, String> {
+		let conf = KeysBackupConfiguration {
+			target: self.args.flag_keys_backup.clone(),
+			interval: to_duration(&self.args.flag_keys_backup_interval)?,
+			password_file: self.args.flag_keys_backup_password.clone(),
+			iterations: self.args.flag_keys_iterations,
+		};
+
+		Ok(conf)
+	}
+
+	fn webhooks_config(&self) -> Result<WebhooksConfiguration, String> {
+		let signing_key = match self.args.flag_webhook_key {
+			Some(ref file) => Some(password_from_file(file.clone())?
+				.parse::<Secret>()
+				.map_err(|e| format!("Invalid webhook signing key: {:?}", e))?),
+			None => None,
+		};
+
+		let conf = WebhooksConfiguration {
+			addresses: to_addresses(&self.args.flag_webhook_addresses)?,
+			urls: self.args.flag_webhook_urls.as_ref()
+				.map(|urls| urls.split(',').filter(|s| !s.is_empty()).map(Into::into).collect())
+				.unwrap_or_default(),
+			signing_key: signing_key,
+		};
+
+		Ok(conf)
 	}
 
 	fn dapp_to_open(&self) -> Result<Option<String>, String> {
@@ -708,6 +993,10 @@ impl Configuration {
 		self.args.flag_network_id.or(self.args.flag_networkid)
 	}
 
+	fn ntp_servers(&self) -> Vec<String> {
+		self.args.flag_ntp_servers.split(',').filter(|s| !s.is_empty()).map(Into::into).collect()
+	}
+
 	fn rpc_apis(&self) -> String {
 		let mut apis = self.args.flag_rpcapi.clone().unwrap_or(self.args.flag_jsonrpc_apis.clone());
 		if self.args.flag_geth {
@@ -758,6 +1047,14 @@ impl Configuration {
 		Self::hosts(&self.args.flag_ipfs_api_hosts)
 	}
 
+	fn ipfs_cid_hash(&self) -> Result<CidHash, String> {
+		match self.args.flag_ipfs_api_cid_hash.as_str() {
+			"keccak-256" => Ok(CidHash::Keccak256),
+			"sha2-256" => Ok(CidHash::SHA2256),
+			other => Err(format!("Invalid IPFS CID hash function given: {}", other)),
+		}
+	}
+
 	fn ipc_config(&self) -> Result<IpcConfiguration, String> {
 		let conf = IpcConfiguration {
 			enabled: !(self.args.flag_ipcdisable || self.args.flag_ipc_off || self.args.flag_no_ipc),
@@ -770,21 +1067,59 @@ impl Configuration {
  					}
 					apis.push_str("personal");
 				}
-				apis.parse()?
+				self.api_policies()?.parse_api_set(&apis)?
+			},
+			limits: IpcLimits {
+				max_pending_requests: max(self.args.flag_ipc_max_concurrent, 1),
 			},
 		};
 
 		Ok(conf)
 	}
 
+	fn access_log_max_size(&self) -> Option<u64> {
+		match self.args.flag_access_log_max_size {
+			0 => None,
+			mb => Some(mb * 1024 * 1024),
+		}
+	}
+
+	fn access_log_trusted_proxy(&self) -> Result<Option<IpAddr>, String> {
+		match self.args.flag_access_log_trusted_proxy {
+			Some(ref proxy) => proxy.parse().map(Some).map_err(|_| format!("Invalid access log trusted proxy given: {}", proxy)),
+			None => Ok(None),
+		}
+	}
+
 	fn http_config(&self) -> Result<HttpConfiguration, String> {
 		let conf = HttpConfiguration {
 			enabled: !self.args.flag_jsonrpc_off && !self.args.flag_no_jsonrpc,
 			interface: self.rpc_interface(),
 			port: self.args.flag_rpcport.unwrap_or(self.args.flag_jsonrpc_port),
-			apis: self.rpc_apis().parse()?,
+			apis: self.api_policies()?.parse_api_set(&self.rpc_apis())?,
 			hosts: self.rpc_hosts(),
+			hosts_ignore_port: self.args.flag_jsonrpc_hosts_ignore_port,
+			allow_unsafe_apis: self.args.flag_jsonrpc_allow_unsafe_apis,
 			cors: self.rpc_cors(),
+			access_log: self.args.flag_jsonrpc_access_log.clone(),
+			access_log_format: self.args.flag_access_log_format.parse()?,
+			access_log_max_size: self.access_log_max_size(),
+			access_log_trusted_proxy: self.access_log_trusted_proxy()?,
+			max_concurrent_requests: match self.args.flag_jsonrpc_max_concurrent {
+				0 => None,
+				max => Some(max),
+			},
+			limits: HttpLimits {
+				max_connections: match self.args.flag_jsonrpc_max_connections {
+					0 => None,
+					max => Some(max),
+				},
+				keep_alive_timeout: match self.args.flag_jsonrpc_keep_alive_timeout {
+					0 => None,
+					secs => Some(Duration::from_secs(secs)),
+				},
+				max_header_size: self.args.flag_jsonrpc_max_header_size,
+			},
 		};
 
 		Ok(conf)
@@ -823,12 +1158,12 @@ impl Configuration {
 		})
 	}
 
-	fn directories(&self) -> Directories {
-		use path;
-
-		let local_path = default_local_path();
+	fn data_path(&self) -> String {
 		let base_path = self.args.flag_base_path.as_ref().map_or_else(|| default_data_path(), |s| s.clone());
-		let data_path = replace_home("", self.args.flag_datadir.as_ref().unwrap_or(&base_path));
+		replace_home("", self.args.flag_datadir.as_ref().unwrap_or(&base_path))
+	}
+
+	fn db_path(&self) -> String {
 		let base_db_path = if self.args.flag_base_path.is_some() && self.args.flag_db_path.is_none() {
 			// If base_path is set and db_path is not we default to base path subdir instead of LOCAL.
 			"$BASE/chains"
@@ -836,7 +1171,14 @@ impl Configuration {
 			self.args.flag_db_path.as_ref().map_or(dir::CHAINS_PATH, |s| &s)
 		};
 
-		let db_path = replace_home_for_db(&data_path, &local_path, &base_db_path);
+		replace_home_for_db(&self.data_path(), &default_local_path(), &base_db_path)
+	}
+
+	fn directories(&self) -> Directories {
+		use path;
+
+		let data_path = self.data_path();
+		let db_path = self.db_path();
 		let keys_path = replace_home(&data_path, &self.args.flag_keys_path);
 		let dapps_path = replace_home(&data_path, &self.args.flag_dapps_path);
 		let secretstore_path = replace_home(&data_path, &self.args.flag_secretstore_path);
@@ -875,6 +1217,10 @@ impl Configuration {
 		}
 	}
 
+	fn shared_db_primary(&self) -> Option<String> {
+		self.args.flag_shared_db_primary.as_ref().map(|path| parity_ipc_path(&self.directories().base, path))
+	}
+
 	fn ui_port(&self) -> Option<u16> {
 		if !self.ui_enabled() {
 			None
@@ -960,16 +1306,19 @@ mod tests {
 	use super::*;
 	use cli::Args;
 	use ethcore_rpc::NetworkSettings;
-	use ethcore::client::{VMType, BlockId};
+	use ethcore::client::{VMType, BlockId, SealPolicy};
+	use light::client::Checkpoint;
 	use ethcore::miner::{MinerOptions, PrioritizationStrategy};
 	use helpers::{default_network_config};
 	use run::RunCmd;
 	use dir::{Directories, default_hypervisor_path};
 	use signer::{Configuration as SignerConfiguration};
-	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState};
+	use rpc_apis::Api;
+	use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, DataFormat, ExportState, ExportAnalytics, ExportLogs, LogCursor, ReindexTransactions, MigrateToArchive, BackfillTraces, ReplayBlock, RebuildCht};
+	use analytics::AnalyticsFormat;
 	use presale::ImportWallet;
 	use params::SpecType;
-	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts};
+	use account::{AccountCmd, NewAccount, ImportAccounts, ListAccounts, RestoreAccounts};
 	use devtools::{RandomTempPath};
 	use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 	use std::io::Write;
@@ -1016,6 +1365,32 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn test_command_chains() {
+		let args = vec!["parity", "chains", "list"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Chains(ChainsCmd::List(ListChains {
+			db_path: Directories::default().db,
+		})));
+
+		let args = vec!["parity", "chains", "use", "kovan"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Chains(ChainsCmd::Use(UseChain {
+			db_path: Directories::default().db,
+			chain: "kovan".into(),
+		})));
+	}
+
+	#[test]
+	fn test_last_chain() {
+		let temp = RandomTempPath::new();
+		let args = vec!["parity", "--chain", "last", "--db-path", temp.as_str()];
+		assert_eq!(parse(&args).chain(), "foundation");
+
+		chains::set_last_chain(temp.as_str(), "kovan").unwrap();
+		assert_eq!(parse(&args).chain(), "kovan");
+	}
+
 	#[test]
 	fn test_command_account_import() {
 		let args = vec!["parity", "account", "import", "my_dir", "another_dir"];
@@ -1027,6 +1402,19 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_account_restore() {
+		let args = vec!["parity", "account", "restore", "/mnt/backups", "1500000000", "--keys-backup-password", "pwd"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Account(AccountCmd::Restore(RestoreAccounts {
+			target: "/mnt/backups".into(),
+			backup: Some(1500000000),
+			to: Directories::default().keys,
+			spec: SpecType::default(),
+			password_file: Some("pwd".into()),
+		})));
+	}
+
 	#[test]
 	fn test_command_wallet_import() {
 		let args = vec!["parity", "wallet", "import", "my_wallet.json", "--password", "pwd"];
@@ -1059,6 +1447,7 @@ mod tests {
 			fat_db: Default::default(),
 			vm_type: VMType::Interpreter,
 			check_seal: true,
+			seal_policy: SealPolicy::Full,
 			with_color: !cfg!(windows),
 			verifier_settings: Default::default(),
 		})));
@@ -1112,6 +1501,143 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_analytics_export() {
+		let args = vec!["parity", "export", "analytics", "--format", "csv", "--from", "100", "analytics"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ExportAnalytics(ExportAnalytics {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			dir_path: Some("analytics".into()),
+			format: AnalyticsFormat::Csv,
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			from_block: BlockId::Number(100),
+			to_block: BlockId::Latest,
+		})));
+	}
+
+	#[test]
+	fn test_command_logs_export() {
+		let args = vec!["parity", "export", "logs", "--from", "100", "--log-address", "0xD9A111feda3f362f55Ef1744347CDC8Dd9964a41",
+			"--log-topic", "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef", "--resume-after", "120:1:3", "logs.ndjson"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ExportLogs(ExportLogs {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			file_path: Some("logs.ndjson".into()),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			from_block: BlockId::Number(100),
+			to_block: BlockId::Latest,
+			addresses: Some(vec!["D9A111feda3f362f55Ef1744347CDC8Dd9964a41".parse().unwrap()]),
+			topics: Some(vec!["ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".parse().unwrap()]),
+			resume: Some(LogCursor { block: 120, transaction: 1, log: 3 }),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_reindex_transactions() {
+		let args = vec!["parity", "db", "reindex-transactions", "--from", "1000", "--reindex-workers", "3"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::ReindexTransactions(ReindexTransactions {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+			tracing: Default::default(),
+			fat_db: Default::default(),
+			from_block: BlockId::Number(1000),
+			to_block: BlockId::Latest,
+			workers: 3,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_replay() {
+		let args = vec!["parity", "db", "replay", "bundle.rlp"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Replay(ReplayBlock {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+			file_path: "bundle.rlp".into(),
+		})));
+	}
+
+	#[test]
+	fn test_command_db_rebuild_cht() {
+		let args = vec!["parity", "db", "rebuild-cht"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::RebuildCht(RebuildCht {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_backfill_traces() {
+		let args = vec!["parity", "db", "backfill-traces", "--from", "1000", "--to", "2000", "--reindex-workers", "3"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::BackfillTraces(BackfillTraces {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+			fat_db: Default::default(),
+			from_block: BlockId::Number(1000),
+			to_block: BlockId::Number(2000),
+			workers: 3,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_migrate_to_archive() {
+		let args = vec!["parity", "db", "migrate-to-archive", "--pruning", "fast"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::MigrateToArchive(MigrateToArchive {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Pruning::Specific(Algorithm::OverlayRecent),
+			pruning_history: 64,
+			pruning_memory: 75,
+			compaction: Default::default(),
+			wal: true,
+		})));
+	}
+
 	#[test]
 	fn test_command_blockchain_export_with_custom_format() {
 		let args = vec!["parity", "export", "blocks", "--format", "hex", "blockchain.json"];
@@ -1146,6 +1672,7 @@ mod tests {
 			interface: "127.0.0.1".into(),
 			port: 8180,
 			skip_origin_validation: false,
+			apis: ApiSet::SafeContext,
 		}));
 	}
 
@@ -1160,6 +1687,7 @@ mod tests {
 			pruning: Default::default(),
 			pruning_history: 64,
 			pruning_memory: 75,
+			node_mode: Default::default(),
 			daemon: None,
 			logger_config: Default::default(),
 			miner_options: Default::default(),
@@ -1178,11 +1706,24 @@ mod tests {
 			wal: true,
 			vm_type: Default::default(),
 			geth_compatibility: false,
+			call_cache_ttl: 0,
+			call_cache_size: 65536,
+			missing_cache_ttl: 10,
+			work_auth_secret: None,
+			work_submit_limit: None,
+			response_limits: Default::default(),
+			signing_audit: None,
+			abi_dir: None,
+			ntp_servers: vec!["0.pool.ntp.org:123".into(), "1.pool.ntp.org:123".into(), "2.pool.ntp.org:123".into()],
+			head_lag_warning: 300,
 			ui_address: Some(("127.0.0.1".into(), 8180)),
 			net_settings: Default::default(),
 			dapps_conf: Default::default(),
 			ipfs_conf: Default::default(),
+			keys_backup_conf: Default::default(),
+			webhooks_conf: Default::default(),
 			signer_conf: Default::default(),
+			trusted_rpc_conf: Default::default(),
 			secretstore_conf: Default::default(),
 			ui: false,
 			dapp: None,
@@ -1193,7 +1734,22 @@ mod tests {
 			stratum: None,
 			check_seal: true,
 			download_old_blocks: true,
+			head_stall_timeout: Some(120),
+			request_parallelism: None,
+			ancient_receipts_depth: None,
 			verifier_settings: Default::default(),
+			dedup_bodies: false,
+			seal_policy: SealPolicy::Full,
+			light_checkpoint: None,
+			read_only: false,
+			shared_db_primary: None,
+			db_compaction_interval: None,
+			db_compaction_rate_limit: None,
+			future_block_gap: 0,
+			replay_bundle_dir: None,
+			clear_bad_blocks: false,
+			tx_index_history: None,
+			tx_index_watch: Vec::new(),
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
 		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Run(expected));
@@ -1221,6 +1777,16 @@ mod tests {
 		assert_eq!(conf3.miner_options(min_period).unwrap(), mining_options);
 	}
 
+	#[test]
+	fn should_parse_local_gas_reserve() {
+		let conf0 = parse(&["parity", "--local-gas-reserve", "25"]);
+		let conf1 = parse(&["parity", "--local-gas-reserve", "101"]);
+
+		let min_period = conf0.args.flag_reseal_min_period;
+		assert_eq!(conf0.miner_options(min_period).unwrap().local_gas_reserve, 25);
+		assert!(conf1.miner_options(min_period).is_err());
+	}
+
 	#[test]
 	fn should_parse_updater_options() {
 		// when
@@ -1302,6 +1868,63 @@ mod tests {
 		assert_eq!(conf3.rpc_hosts(), Some(vec!["ethcore.io".into(), "something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_api_sets_with_policies() {
+		// given
+		let temp = RandomTempPath::new();
+		let mut file = File::create(temp.as_path()).unwrap();
+		file.write_all(b"[policy.readonly]\napis = [\"web3\", \"eth\"]\n").unwrap();
+		let policies = temp.as_str().to_owned();
+
+		// when
+		let conf0 = parse(&["parity", "--api-policies", &policies, "--jsonrpc-apis", "policy:readonly", "--ui-apis", "policy:readonly,net"]);
+		let conf1 = parse(&["parity", "--jsonrpc-apis", "policy:readonly"]);
+
+		// then
+		let readonly = ApiSet::List(vec![Api::Web3, Api::Eth].into_iter().collect());
+		assert_eq!(conf0.http_config().unwrap().apis, readonly);
+		assert_eq!(conf0.signer_config().unwrap().apis, ApiSet::List(vec![Api::Web3, Api::Eth, Api::Net].into_iter().collect()));
+		assert!(conf1.http_config().is_err());
+	}
+
+	#[test]
+	fn should_not_allow_unsafe_http_apis_by_default() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--jsonrpc-apis", "personal"]);
+		let conf1 = parse(&["parity", "--jsonrpc-apis", "personal", "--jsonrpc-allow-unsafe-apis"]);
+
+		// then
+		assert!(!conf0.http_config().unwrap().allow_unsafe_apis);
+		assert!(conf1.http_config().unwrap().allow_unsafe_apis);
+	}
+
+	#[test]
+	fn should_parse_rpc_limits() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--jsonrpc-max-concurrent", "16", "--jsonrpc-max-header-size", "1024",
+			"--jsonrpc-max-connections", "64", "--jsonrpc-keep-alive-timeout", "0"]);
+
+		// then
+		let http0 = conf0.http_config().unwrap();
+		let http1 = conf1.http_config().unwrap();
+		assert_eq!(http0.max_concurrent_requests, None);
+		assert_eq!(http0.limits, HttpLimits::default());
+		assert_eq!(http1.max_concurrent_requests, Some(16));
+		assert_eq!(http1.limits, HttpLimits {
+			max_connections: Some(64),
+			keep_alive_timeout: None,
+			max_header_size: 1024,
+		});
+		assert_eq!(conf0.ipc_config().unwrap().limits, IpcLimits::default());
+		assert_eq!(parse(&["parity", "--ipc-max-concurrent", "8"]).ipc_config().unwrap().limits, IpcLimits { max_pending_requests: 8 });
+		assert_eq!(parse(&["parity", "--ipc-max-concurrent", "0"]).ipc_config().unwrap().limits, IpcLimits { max_pending_requests: 1 });
+	}
+
 	#[test]
 	fn should_parse_dapps_hosts() {
 		// given
@@ -1351,6 +1974,21 @@ mod tests {
 		assert_eq!(conf2.ipfs_cors(), Some(vec!["http://ethcore.io".into(),"http://something.io".into()]));
 	}
 
+	#[test]
+	fn should_parse_ipfs_cid_hash() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--ipfs-api-cid-hash", "sha2-256"]);
+		let conf2 = parse(&["parity", "--ipfs-api-cid-hash", "md5"]);
+
+		// then
+		assert_eq!(conf0.ipfs_cid_hash(), Ok(CidHash::Keccak256));
+		assert_eq!(conf1.ipfs_cid_hash(), Ok(CidHash::SHA2256));
+		assert!(conf2.ipfs_cid_hash().is_err());
+	}
+
 	#[test]
 	fn should_disable_signer_in_geth_compat() {
 		// given
@@ -1375,6 +2013,43 @@ mod tests {
 		assert_eq!(conf0.ui_enabled(), false);
 	}
 
+	#[test]
+	fn should_parse_trusted_rpc_configuration() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity", "--ui-path", "signer"]);
+		let conf1 = parse(&["parity", "--ui-path", "signer", "--trusted-rpc", "--trusted-rpc-port", "3124", "--trusted-rpc-apis", "personal,eth"]);
+
+		// then
+		assert_eq!(conf0.trusted_rpc_config().unwrap(), TrustedConfiguration {
+			signer_path: "signer".into(),
+			..Default::default()
+		});
+		assert_eq!(conf1.trusted_rpc_config().unwrap(), TrustedConfiguration {
+			enabled: true,
+			port: 3124,
+			signer_path: "signer".into(),
+			apis: ApiSet::List(vec![Api::Personal, Api::Eth].into_iter().collect()),
+		});
+	}
+
+	#[test]
+	fn should_parse_access_log_trusted_proxy() {
+		// given
+
+		// when
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--access-log-trusted-proxy", "10.0.0.1"]);
+		let conf2 = parse(&["parity", "--access-log-trusted-proxy", "proxy.local"]);
+
+		// then
+		assert_eq!(conf0.http_config().unwrap().access_log_trusted_proxy, None);
+		assert_eq!(conf1.http_config().unwrap().access_log_trusted_proxy, Some("10.0.0.1".parse().unwrap()));
+		assert_eq!(conf1.ipfs_config().unwrap().access_log_trusted_proxy, Some("10.0.0.1".parse().unwrap()));
+		assert!(conf2.http_config().is_err());
+	}
+
 	#[test]
 	fn should_parse_signer_configration() {
 		// given
@@ -1386,33 +2061,37 @@ mod tests {
 		let conf3 = parse(&["parity", "--ui-path", "signer", "--ui-interface", "test"]);
 
 		// then
-		assert_eq!(conf0.signer_config(), SignerConfiguration {
+		assert_eq!(conf0.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
 			interface: "127.0.0.1".into(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
+			apis: ApiSet::SafeContext,
 		});
-		assert_eq!(conf1.signer_config(), SignerConfiguration {
+		assert_eq!(conf1.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
 			interface: "127.0.0.1".into(),
 			signer_path: "signer".into(),
 			skip_origin_validation: true,
+			apis: ApiSet::SafeContext,
 		});
-		assert_eq!(conf2.signer_config(), SignerConfiguration {
+		assert_eq!(conf2.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 3123,
 			interface: "127.0.0.1".into(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
+			apis: ApiSet::SafeContext,
 		});
-		assert_eq!(conf3.signer_config(), SignerConfiguration {
+		assert_eq!(conf3.signer_config().unwrap(), SignerConfiguration {
 			enabled: true,
 			port: 8180,
 			interface: "test".into(),
 			signer_path: "signer".into(),
 			skip_origin_validation: false,
+			apis: ApiSet::SafeContext,
 		});
 	}
 
@@ -1443,6 +2122,60 @@ mod tests {
 		assert!(conf.init_reserved_nodes().is_ok());
 	}
 
+	#[test]
+	fn test_seal_verification_policy() {
+		let args = vec!["parity", "--seal-verification", "sampled:1000"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => assert_eq!(c.seal_policy, SealPolicy::Sampled { interval: 1000, recent: 3600 }),
+			_ => panic!("Should be Cmd::Run"),
+		}
+
+		let args = vec!["parity", "--seal-verification", "sometimes"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
+	#[test]
+	fn test_light_checkpoint() {
+		let args = vec!["parity", "--light-checkpoint", "4000000:0x0000000000000000000000000000000000000000000000000000000000000001"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => {
+				assert_eq!(c.light_checkpoint, Some(Checkpoint { number: 4000000, hash: 1.into() }));
+				assert_eq!(c.light_client_config().checkpoint, c.light_checkpoint);
+			},
+			_ => panic!("Should be Cmd::Run"),
+		}
+
+		let args = vec!["parity", "--light-checkpoint", "4000000"];
+		assert!(parse(&args).into_command().is_err());
+	}
+
+	#[test]
+	fn test_request_parallelism() {
+		let conf = parse(&["parity", "--request-parallelism", "4"]);
+		assert_eq!(conf.request_parallelism(), Ok(Some(4)));
+
+		let conf = parse(&["parity"]);
+		assert_eq!(conf.request_parallelism(), Ok(None));
+
+		assert!(parse(&["parity", "--request-parallelism", "0"]).request_parallelism().is_err());
+		assert!(parse(&["parity", "--request-parallelism", "9"]).request_parallelism().is_err());
+	}
+
+	#[test]
+	fn test_db_compaction_schedule() {
+		let args = vec!["parity", "--db-compaction-interval", "86400", "--db-compaction-rate-limit", "16"];
+		let conf = parse(&args);
+		match conf.into_command().unwrap().cmd {
+			Cmd::Run(c) => {
+				assert_eq!(c.db_compaction_interval, Some(86400));
+				assert_eq!(c.db_compaction_rate_limit, Some(16));
+			},
+			_ => panic!("Should be Cmd::Run"),
+		}
+	}
+
 	#[test]
 	fn test_dev_chain() {
 		let args = vec!["parity", "--chain", "dev"];
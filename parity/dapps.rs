@@ -182,7 +182,7 @@ mod server {
 		} else {
 			rpc_apis::ApiSet::UnsafeContext
 		};
-		let apis = rpc_apis::setup_rpc(deps.stats, deps.apis.clone(), api_set);
+		let apis = rpc_apis::setup_rpc(deps.stats, deps.apis.clone(), api_set, None);
 		let start_result = match auth {
 			None => {
 				server.start_unsecured_http(url, apis, deps.remote)
@@ -38,7 +38,7 @@ use app_dirs::{AppInfo, get_app_root, AppDataType};
 // but we still use it for backwards compatibility
 const LEGACY_CLIENT_DB_VER_STR: &'static str = "5.3";
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Directories {
 	pub base: String,
 	pub db: String,
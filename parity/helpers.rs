@@ -18,7 +18,7 @@ use std::{io, env};
 use std::io::{Write, BufReader, BufRead};
 use std::time::Duration;
 use std::fs::File;
-use util::{clean_0x, U256, Uint, Address, CompactionProfile};
+use util::{clean_0x, U256, H256, Uint, Address, CompactionProfile};
 use util::journaldb::Algorithm;
 use ethcore::client::{Mode, BlockId, VMType, DatabaseCompactionProfile, ClientConfig, VerifierType};
 use ethcore::miner::{PendingSet, GasLimit, PrioritizationStrategy};
@@ -127,6 +127,15 @@ pub fn to_addresses(s: &Option<String>) -> Result<Vec<Address>, String> {
 	}
 }
 
+pub fn to_hashes(s: &Option<String>) -> Result<Vec<H256>, String> {
+	match *s {
+		Some(ref hashes) if !hashes.is_empty() => hashes.split(',')
+			.map(|h| clean_0x(h).parse().map_err(|_| format!("Invalid hash: {:?}", h)))
+			.collect(),
+		_ => Ok(Vec::new()),
+	}
+}
+
 /// Tries to parse string as a price.
 pub fn to_price(s: &str) -> Result<f32, String> {
 	s.parse::<f32>().map_err(|_| format!("Invalid transaciton price 's' given. Must be a decimal number."))
@@ -344,7 +353,7 @@ mod tests {
 	use util::{U256};
 	use ethcore::client::{Mode, BlockId};
 	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_hashes, to_price, geth_ipc_path, to_bootnodes, password_from_file};
 
 	#[test]
 	fn test_to_duration() {
@@ -427,6 +436,20 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_to_hashes() {
+		let hashes = to_hashes(&Some("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef,8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925".into())).unwrap();
+		assert_eq!(
+			hashes,
+			vec![
+				"ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef".parse().unwrap(),
+				"8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925".parse().unwrap(),
+			]
+		);
+		assert!(to_hashes(&Some("0x01".into())).is_err());
+		assert_eq!(to_hashes(&None).unwrap(), Vec::new());
+	}
+
 	#[test]
 	fn test_password() {
 		let path = RandomTempPath::new();
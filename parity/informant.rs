@@ -32,6 +32,8 @@ use ethcore::snapshot::{RestorationStatus, SnapshotService as SS};
 use number_prefix::{binary_prefix, Standalone, Prefixed};
 use ethcore_rpc::{is_major_importing};
 use ethcore_rpc::informant::RpcStats;
+use ethcore_rpc::head_lag::HeadLag;
+use ethcore_rpc::sync_events::SyncEvents;
 
 pub struct Informant {
 	report: RwLock<Option<ClientReport>>,
@@ -42,6 +44,8 @@ pub struct Informant {
 	sync: Option<Arc<SyncProvider>>,
 	net: Option<Arc<ManageNetwork>>,
 	rpc_stats: Option<Arc<RpcStats>>,
+	sync_events: Option<Arc<SyncEvents>>,
+	head_lag: Option<Arc<HeadLag>>,
 	last_import: Mutex<Instant>,
 	skipped: AtomicUsize,
 	skipped_txs: AtomicUsize,
@@ -87,6 +91,8 @@ impl Informant {
 			sync: sync,
 			net: net,
 			rpc_stats: rpc_stats,
+			sync_events: None,
+			head_lag: None,
 			last_import: Mutex::new(Instant::now()),
 			skipped: AtomicUsize::new(0),
 			skipped_txs: AtomicUsize::new(0),
@@ -94,6 +100,30 @@ impl Informant {
 		}
 	}
 
+	/// Notify sync status subscribers about sync state transitions.
+	pub fn with_sync_events(mut self, sync_events: Arc<SyncEvents>) -> Self {
+		self.sync_events = Some(sync_events);
+		self
+	}
+
+	/// Periodically verify the local clock and warn when the best block trails the current time.
+	pub fn with_head_lag(mut self, head_lag: Arc<HeadLag>) -> Self {
+		self.head_lag = Some(head_lag);
+		self
+	}
+
+	/// Measure the local clock offset off the IO thread, as NTP queries may block.
+	fn update_clock_offset(&self) {
+		if let Some(head_lag) = self.head_lag.clone() {
+			let spawned = ::std::thread::Builder::new()
+				.name("ntp".into())
+				.spawn(move || head_lag.update_clock_offset());
+			if let Err(e) = spawned {
+				warn!("Unable to spawn NTP query thread: {}", e);
+			}
+		}
+	}
+
 	/// Signal that we're shutting down; no more output necessary.
 	pub fn shutdown(&self) {
 		self.in_shutdown.store(true, ::std::sync::atomic::Ordering::SeqCst);
@@ -241,15 +271,39 @@ impl ChainNotify for Informant {
 }
 
 const INFO_TIMER: TimerToken = 0;
+const SYNC_EVENTS_TIMER: TimerToken = 1;
+const HEAD_LAG_TIMER: TimerToken = 2;
+const CLOCK_OFFSET_TIMER: TimerToken = 3;
 
 impl IoHandler<ClientIoMessage> for Informant {
 	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
 		io.register_timer(INFO_TIMER, 5000).expect("Error registering timer");
+		if self.sync_events.is_some() {
+			io.register_timer(SYNC_EVENTS_TIMER, 1000).expect("Error registering timer");
+		}
+		if self.head_lag.is_some() {
+			io.register_timer(HEAD_LAG_TIMER, 10_000).expect("Error registering timer");
+			io.register_timer(CLOCK_OFFSET_TIMER, 30 * 60 * 1000).expect("Error registering timer");
+			self.update_clock_offset();
+		}
 	}
 
 	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
-		if timer == INFO_TIMER && !self.in_shutdown.load(AtomicOrdering::SeqCst) {
-			self.tick();
+		if self.in_shutdown.load(AtomicOrdering::SeqCst) {
+			return;
+		}
+
+		match timer {
+			INFO_TIMER => self.tick(),
+			SYNC_EVENTS_TIMER => if let (Some(events), Some(sync)) = (self.sync_events.as_ref(), self.sync.as_ref()) {
+				events.update(&sync.status(), self.client.chain_info().best_block_number);
+			},
+			HEAD_LAG_TIMER => if let Some(head_lag) = self.head_lag.as_ref() {
+				let chain_info = self.client.chain_info();
+				head_lag.check(chain_info.best_block_number, chain_info.best_block_timestamp);
+			},
+			CLOCK_OFFSET_TIMER => self.update_clock_offset(),
+			_ => {},
 		}
 	}
 }
@@ -15,9 +15,10 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
-use parity_ipfs_api::{self, AccessControlAllowOrigin, Host};
-use parity_ipfs_api::error::ServerError;
+use std::net::IpAddr;
+use parity_ipfs_api::{self, AccessControlAllowOrigin, AllowedHosts, CidHash};
 use ethcore::client::BlockChainClient;
+use ethcore_logger::{AccessLog, AccessLogFormat};
 use hyper::server::Listening;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -27,6 +28,12 @@ pub struct Configuration {
 	pub interface: String,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	pub hosts_ignore_port: bool,
+	pub cid_hash: CidHash,
+	pub access_log: Option<String>,
+	pub access_log_format: AccessLogFormat,
+	pub access_log_max_size: Option<u64>,
+	pub access_log_trusted_proxy: Option<IpAddr>,
 }
 
 impl Default for Configuration {
@@ -37,23 +44,38 @@ impl Default for Configuration {
 			interface: "127.0.0.1".into(),
 			cors: None,
 			hosts: Some(Vec::new()),
+			hosts_ignore_port: false,
+			cid_hash: CidHash::Keccak256,
+			access_log: None,
+			access_log_format: Default::default(),
+			access_log_max_size: None,
+			access_log_trusted_proxy: None,
 		}
 	}
 }
 
-pub fn start_server(conf: Configuration, client: Arc<BlockChainClient>) -> Result<Option<Listening>, ServerError> {
+pub fn start_server(conf: Configuration, client: Arc<BlockChainClient>) -> Result<Option<Listening>, String> {
 	if !conf.enabled {
 		return Ok(None);
 	}
 
+	let access_log = match conf.access_log {
+		Some(ref path) => Some(Arc::new(
+			AccessLog::open(path, conf.access_log_format, conf.access_log_max_size)?.with_trusted_proxy(conf.access_log_trusted_proxy)
+		)),
+		None => None,
+	};
+
 	let cors = conf.cors.map(|cors| cors.into_iter().map(AccessControlAllowOrigin::from).collect());
-	let hosts = conf.hosts.map(|hosts| hosts.into_iter().map(Host::from).collect());
+	let hosts = AllowedHosts::new(conf.hosts, conf.hosts_ignore_port);
 
 	parity_ipfs_api::start_server(
 		conf.port,
 		conf.interface,
 		cors.into(),
-		hosts.into(),
-		client
-	).map(Some)
+		hosts,
+		conf.cid_hash,
+		client,
+		access_log,
+	).map(Some).map_err(Into::into)
 }
@@ -0,0 +1,90 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Scheduled encrypted backups of the keys directory.
+
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use ethcore::ethstore::backup;
+use helpers::password_from_file;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Configuration {
+	pub target: Option<String>,
+	pub interval: Duration,
+	pub password_file: Option<String>,
+	pub iterations: u32,
+}
+
+impl Default for Configuration {
+	fn default() -> Self {
+		Configuration {
+			target: None,
+			interval: Duration::from_secs(24 * 60 * 60),
+			password_file: None,
+			iterations: 10240,
+		}
+	}
+}
+
+/// Backs up the keys directory periodically until dropped.
+pub struct KeysBackup {
+	stop: mpsc::Sender<()>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Drop for KeysBackup {
+	fn drop(&mut self) {
+		let _ = self.stop.send(());
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+pub fn start(conf: Configuration, keys_path: PathBuf) -> Result<Option<KeysBackup>, String> {
+	let target = match conf.target {
+		Some(ref target) => backup::target_from_str(target)?,
+		None => return Ok(None),
+	};
+	let password = match conf.password_file {
+		Some(file) => password_from_file(file)?,
+		None => return Err("Keys backups are encrypted, please provide a password file with --keys-backup-password.".into()),
+	};
+
+	let interval = conf.interval;
+	let iterations = conf.iterations;
+	let (stop, stopped) = mpsc::channel();
+	let thread = thread::Builder::new().name("keys-backup".into()).spawn(move || loop {
+		match backup::backup(&keys_path, &*target, &password, iterations) {
+			Ok(Some((manifest, files))) => info!("Backed up {} key file(s) as backup {}", files.len(), manifest.id),
+			Ok(None) => debug!(target: "backup", "Keys unchanged since the latest backup"),
+			Err(e) => warn!("Keys backup failed: {}", e),
+		}
+
+		match stopped.recv_timeout(interval) {
+			Err(mpsc::RecvTimeoutError::Timeout) => continue,
+			_ => break,
+		}
+	}).map_err(|e| format!("Unable to start keys backup: {}", e))?;
+
+	Ok(Some(KeysBackup {
+		stop: stop,
+		thread: Some(thread),
+	}))
+}
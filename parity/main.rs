@@ -28,6 +28,7 @@ extern crate ctrlc;
 extern crate docopt;
 extern crate env_logger;
 extern crate fdlimit;
+extern crate futures;
 extern crate hyper;
 extern crate isatty;
 extern crate jsonrpc_core;
@@ -77,6 +78,7 @@ extern crate ethcore_dapps;
 
 #[cfg(windows)] extern crate ws2_32;
 #[cfg(windows)] extern crate winapi;
+#[cfg(not(windows))] extern crate libc;
 
 macro_rules! dependency {
 	($dep_ty:ident, $url:expr) => {
@@ -90,19 +92,24 @@ macro_rules! dependency {
 	}
 }
 
+mod abi_source;
 mod account;
+mod analytics;
 mod blockchain;
 mod cache;
+mod chains;
 mod cli;
 mod configuration;
 mod dapps;
 mod ipfs;
+mod keys_backup;
 mod deprecated;
 mod dir;
 mod helpers;
 mod informant;
 mod migration;
 mod modules;
+mod node_mode;
 mod params;
 mod presale;
 mod rpc;
@@ -111,9 +118,12 @@ mod run;
 mod signer;
 mod snapshot;
 mod secretstore;
+mod shared_db;
+mod shutdown;
 mod upgrade;
 mod url;
 mod user_defaults;
+mod webhooks;
 
 #[cfg(feature="ipc")]
 mod boot;
@@ -169,6 +179,7 @@ fn execute(command: Execute, can_restart: bool) -> Result<PostExecutionAction, S
 		Cmd::SignerList { port, authfile } => rpc_cli::signer_list(port, authfile).map(|s| PostExecutionAction::Print(s)),
 		Cmd::SignerReject { id, port, authfile } => rpc_cli::signer_reject(id, port, authfile).map(|s| PostExecutionAction::Print(s)),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd).map(|s| PostExecutionAction::Print(s)),
+		Cmd::Chains(chains_cmd) => chains::execute(chains_cmd).map(|s| PostExecutionAction::Print(s)),
 	}
 }
 
@@ -170,6 +170,7 @@ fn consolidate_database(
 		compaction: config.compaction_profile,
 		columns: None,
 		wal: true,
+		read_only: false,
 	};
 
 	let old_path_str = old_db_path.to_str().ok_or(Error::MigrationImpossible)?;
@@ -0,0 +1,219 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Selection of pruning and sync modes suited to the resources of the machine.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use util::journaldb::Algorithm;
+use informant::format_bytes;
+
+const GB: u64 = 1024 * 1024 * 1024;
+/// Free disk space required to recommend an archive node.
+const ARCHIVE_MIN_DISK: u64 = 500 * GB;
+/// Physical memory required to recommend an archive node.
+const ARCHIVE_MIN_MEMORY: u64 = 8 * GB;
+
+/// What to do with the detected capabilities when the node has no previous pruning choice.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Policy {
+	/// Don't inspect the machine.
+	Off,
+	/// Log the recommended modes but keep the configured ones.
+	Recommend,
+	/// Use the recommended modes.
+	Auto,
+}
+
+impl Default for Policy {
+	fn default() -> Self {
+		Policy::Recommend
+	}
+}
+
+impl FromStr for Policy {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"off" => Ok(Policy::Off),
+			"recommend" => Ok(Policy::Recommend),
+			"auto" => Ok(Policy::Auto),
+			other => Err(format!("Invalid node mode selection: {}", other)),
+		}
+	}
+}
+
+/// Resources available to the node. `None` if they couldn't be determined.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Capabilities {
+	/// Free space on the disk holding the database, in bytes.
+	pub disk_available: Option<u64>,
+	/// Total physical memory, in bytes.
+	pub memory: Option<u64>,
+}
+
+impl Capabilities {
+	/// Inspect the machine the database at given path lives on.
+	pub fn detect(db_path: &Path) -> Self {
+		Capabilities {
+			disk_available: available_disk_space(db_path),
+			memory: total_memory(),
+		}
+	}
+}
+
+/// Modes suited to given capabilities.
+#[derive(Debug, PartialEq)]
+pub struct Recommendation {
+	pub pruning: Algorithm,
+	pub warp: bool,
+}
+
+/// Recommend an archive node only when there's certainly enough disk and memory for it,
+/// a warp-synced fast node otherwise.
+pub fn recommend(caps: &Capabilities) -> Recommendation {
+	let enough = |value: Option<u64>, min: u64| value.map_or(false, |value| value >= min);
+	match enough(caps.disk_available, ARCHIVE_MIN_DISK) && enough(caps.memory, ARCHIVE_MIN_MEMORY) {
+		true => Recommendation { pruning: Algorithm::Archive, warp: false },
+		false => Recommendation { pruning: Algorithm::OverlayRecent, warp: true },
+	}
+}
+
+/// Select pruning algorithm and whether to warp sync for a database without a previous choice.
+pub fn select(policy: Policy, db_path: &Path, pruning: Algorithm, warp: bool) -> (Algorithm, bool) {
+	if policy == Policy::Off {
+		return (pruning, warp);
+	}
+
+	let caps = Capabilities::detect(db_path);
+	let recommendation = recommend(&caps);
+	let describe = |value: Option<u64>| value.map_or_else(|| "unknown".to_owned(), |value| format_bytes(value as usize));
+	info!("Detected {} of free disk space and {} of memory; recommended pruning: {}",
+		describe(caps.disk_available),
+		describe(caps.memory),
+		recommendation.pruning.as_str(),
+	);
+
+	match policy {
+		Policy::Auto => (recommendation.pruning, warp && recommendation.warp),
+		_ => {
+			if recommendation.pruning != pruning {
+				info!("Re-run with --pruning {} or --select-node-mode auto to follow the recommendation.", recommendation.pruning.as_str());
+			}
+			(pruning, warp)
+		},
+	}
+}
+
+#[cfg(not(windows))]
+fn available_disk_space(path: &Path) -> Option<u64> {
+	use std::ffi::CString;
+	use std::mem;
+	use std::os::unix::ffi::OsStrExt;
+	use libc;
+
+	// the database directory is created later; measure the disk it's going to be on.
+	let mut path = path;
+	while !path.exists() {
+		path = match path.parent() {
+			Some(parent) => parent,
+			None => return None,
+		};
+	}
+
+	let path = match CString::new(path.as_os_str().as_bytes()) {
+		Ok(path) => path,
+		Err(_) => return None,
+	};
+
+	unsafe {
+		let mut stat: libc::statvfs = mem::zeroed();
+		match libc::statvfs(path.as_ptr(), &mut stat) {
+			0 => Some(stat.f_bavail as u64 * stat.f_frsize as u64),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(windows)]
+fn available_disk_space(_path: &Path) -> Option<u64> {
+	None
+}
+
+#[cfg(target_os = "linux")]
+fn total_memory() -> Option<u64> {
+	use std::fs::File;
+	use std::io::{BufRead, BufReader};
+
+	let file = match File::open("/proc/meminfo") {
+		Ok(file) => file,
+		Err(_) => return None,
+	};
+
+	BufReader::new(file).lines()
+		.filter_map(Result::ok)
+		.filter_map(|line| parse_mem_total(&line))
+		.next()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn total_memory() -> Option<u64> {
+	None
+}
+
+#[cfg(any(test, target_os = "linux"))]
+fn parse_mem_total(line: &str) -> Option<u64> {
+	let mut parts = line.split_whitespace();
+	match (parts.next(), parts.next(), parts.next()) {
+		(Some("MemTotal:"), Some(kb), Some("kB")) => kb.parse::<u64>().ok().map(|kb| kb * 1024),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::journaldb::Algorithm;
+	use super::{Policy, Capabilities, Recommendation, GB, recommend, parse_mem_total};
+
+	#[test]
+	fn parses_policy() {
+		assert_eq!("off".parse::<Policy>(), Ok(Policy::Off));
+		assert_eq!("recommend".parse::<Policy>(), Ok(Policy::Recommend));
+		assert_eq!("auto".parse::<Policy>(), Ok(Policy::Auto));
+		assert!("archive".parse::<Policy>().is_err());
+	}
+
+	#[test]
+	fn recommends_archive_only_with_enough_resources() {
+		let fast = Recommendation { pruning: Algorithm::OverlayRecent, warp: true };
+		let archive = Recommendation { pruning: Algorithm::Archive, warp: false };
+
+		assert_eq!(recommend(&Capabilities::default()), fast);
+		assert_eq!(recommend(&Capabilities { disk_available: Some(2000 * GB), memory: None }), fast);
+		assert_eq!(recommend(&Capabilities { disk_available: Some(2000 * GB), memory: Some(4 * GB) }), fast);
+		assert_eq!(recommend(&Capabilities { disk_available: Some(100 * GB), memory: Some(32 * GB) }), fast);
+		assert_eq!(recommend(&Capabilities { disk_available: Some(2000 * GB), memory: Some(32 * GB) }), archive);
+	}
+
+	#[test]
+	fn parses_meminfo() {
+		assert_eq!(parse_mem_total("MemTotal:       16318120 kB"), Some(16318120 * 1024));
+		assert_eq!(parse_mem_total("MemFree:         1218120 kB"), None);
+		assert_eq!(parse_mem_total("MemTotal: lots"), None);
+	}
+}
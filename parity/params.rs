@@ -24,7 +24,7 @@ use ethcore::client::Mode;
 use ethcore::miner::{GasPricer, GasPriceCalibratorOptions};
 use user_defaults::UserDefaults;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum SpecType {
 	Foundation,
 	Morden,
@@ -245,6 +245,7 @@ pub struct MinerExtras {
 	pub gas_ceil_target: U256,
 	pub transactions_limit: usize,
 	pub engine_signer: Address,
+	pub signer_votes: Vec<(Address, bool)>,
 }
 
 impl Default for MinerExtras {
@@ -256,6 +257,7 @@ impl Default for MinerExtras {
 			gas_ceil_target: U256::from(6_283_184),
 			transactions_limit: 1024,
 			engine_signer: Default::default(),
+			signer_votes: Vec::new(),
 		}
 	}
 }
@@ -16,12 +16,15 @@
 
 use std::fmt;
 use std::sync::Arc;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::io;
 
+use ansi_term::Colour;
 use dir::default_data_path;
-use ethcore_rpc::{self as rpc, HttpServerError, Metadata, Origin, AccessControlAllowOrigin, Host};
+use ethcore_rpc::{self as rpc, HttpServerError, Metadata, Origin, AccessControlAllowOrigin};
 use ethcore_rpc::informant::{RpcStats, Middleware};
+use ethcore_logger::{AccessLog, AccessLogFormat};
+use util::hosts::AllowedHosts;
 use helpers::parity_ipc_path;
 use hyper;
 use jsonrpc_core::MetaIoHandler;
@@ -29,7 +32,7 @@ use rpc_apis;
 use rpc_apis::ApiSet;
 use parity_reactor::TokioRemote;
 
-pub use ethcore_rpc::{IpcServer, HttpServer};
+pub use ethcore_rpc::{IpcServer, IpcLimits, HttpServer, HttpLimits};
 
 #[derive(Debug, PartialEq)]
 pub struct HttpConfiguration {
@@ -39,6 +42,18 @@ pub struct HttpConfiguration {
 	pub apis: ApiSet,
 	pub cors: Option<Vec<String>>,
 	pub hosts: Option<Vec<String>>,
+	pub hosts_ignore_port: bool,
+	/// Serve unsafe APIs over HTTP without a deprecation warning.
+	pub allow_unsafe_apis: bool,
+	pub access_log: Option<String>,
+	pub access_log_format: AccessLogFormat,
+	pub access_log_max_size: Option<u64>,
+	/// Proxy whose `X-Forwarded-For` header identifies clients in the access log.
+	pub access_log_trusted_proxy: Option<IpAddr>,
+	/// Maximal number of requests processed at the same time (unlimited if `None`).
+	pub max_concurrent_requests: Option<usize>,
+	/// Connection, keep-alive and header size limits of the transport.
+	pub limits: HttpLimits,
 }
 
 impl Default for HttpConfiguration {
@@ -50,6 +65,14 @@ impl Default for HttpConfiguration {
 			apis: ApiSet::UnsafeContext,
 			cors: None,
 			hosts: Some(Vec::new()),
+			hosts_ignore_port: false,
+			allow_unsafe_apis: false,
+			access_log: None,
+			access_log_format: Default::default(),
+			access_log_max_size: None,
+			access_log_trusted_proxy: None,
+			max_concurrent_requests: None,
+			limits: HttpLimits::default(),
 		}
 	}
 }
@@ -59,6 +82,8 @@ pub struct IpcConfiguration {
 	pub enabled: bool,
 	pub socket_addr: String,
 	pub apis: ApiSet,
+	/// Pending requests limit of a single connection.
+	pub limits: IpcLimits,
 }
 
 impl Default for IpcConfiguration {
@@ -68,6 +93,7 @@ impl Default for IpcConfiguration {
 			enabled: true,
 			socket_addr: parity_ipc_path(&data_dir, "$BASE/jsonrpc.ipc"),
 			apis: ApiSet::IpcContext,
+			limits: IpcLimits::default(),
 		}
 	}
 }
@@ -101,10 +127,9 @@ impl rpc::HttpMetaExtractor<Metadata> for RpcExtractor {
 }
 
 impl rpc::IpcMetaExtractor<Metadata> for RpcExtractor {
-	fn extract(&self, _req: &rpc::IpcRequestContext) -> Metadata {
+	fn extract(&self, req: &rpc::IpcRequestContext) -> Metadata {
 		let mut metadata = Metadata::default();
-		// TODO [ToDr] Extract proper session id when it's available in context.
-		metadata.origin = Origin::Ipc(1.into());
+		metadata.origin = Origin::Ipc(req.connection_id.into());
 		metadata
 	}
 }
@@ -116,25 +141,43 @@ pub fn new_http(conf: HttpConfiguration, deps: &Dependencies) -> Result<Option<H
 
 	let url = format!("{}:{}", conf.interface, conf.port);
 	let addr = url.parse().map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url))?;
-	Ok(Some(setup_http_rpc_server(deps, &addr, conf.cors, conf.hosts, conf.apis)?))
+	let unsafe_apis = conf.apis.unsafe_apis();
+	if !unsafe_apis.is_empty() {
+		warn!("{} {} over HTTP. Any website or process able to reach {} can use them.",
+			Colour::Red.bold().paint("Unsafe APIs exposed:"), unsafe_apis.join(", "), url);
+		if !conf.allow_unsafe_apis {
+			warn!("Exposing unsafe APIs over HTTP is deprecated and will be refused in a future release. Serve them with the token-authenticated trusted RPC transport (--trusted-rpc) instead, or pass --jsonrpc-allow-unsafe-apis to keep them on HTTP.");
+		}
+	}
+	let access_log = match conf.access_log {
+		Some(ref path) => Some(Arc::new(
+			AccessLog::open(path, conf.access_log_format, conf.access_log_max_size)?.with_trusted_proxy(conf.access_log_trusted_proxy)
+		)),
+		None => None,
+	};
+	let mut allowed_hosts = AllowedHosts::new(conf.hosts, conf.hosts_ignore_port);
+	allowed_hosts.add(&url);
+	Ok(Some(setup_http_rpc_server(deps, &addr, conf.cors, allowed_hosts, conf.apis, access_log, conf.max_concurrent_requests, conf.limits)?))
 }
 
-fn setup_apis(apis: ApiSet, deps: &Dependencies) -> MetaIoHandler<Metadata, Middleware> {
-	rpc_apis::setup_rpc(deps.stats.clone(), deps.apis.clone(), apis)
+fn setup_apis(apis: ApiSet, deps: &Dependencies, max_concurrent_requests: Option<usize>) -> MetaIoHandler<Metadata, Middleware> {
+	rpc_apis::setup_rpc(deps.stats.clone(), deps.apis.clone(), apis, max_concurrent_requests)
 }
 
 pub fn setup_http_rpc_server(
 	dependencies: &Dependencies,
 	url: &SocketAddr,
 	cors_domains: Option<Vec<String>>,
-	allowed_hosts: Option<Vec<String>>,
-	apis: ApiSet
+	allowed_hosts: AllowedHosts,
+	apis: ApiSet,
+	access_log: Option<Arc<AccessLog>>,
+	max_concurrent_requests: Option<usize>,
+	limits: HttpLimits,
 ) -> Result<HttpServer, String> {
-	let handler = setup_apis(apis, dependencies);
+	let handler = setup_apis(apis, dependencies, max_concurrent_requests);
 	let remote = dependencies.remote.clone();
 	let cors_domains: Option<Vec<_>> = cors_domains.map(|domains| domains.into_iter().map(AccessControlAllowOrigin::from).collect());
-	let allowed_hosts: Option<Vec<_>> = allowed_hosts.map(|hosts| hosts.into_iter().map(Host::from).collect());
-	let start_result = rpc::start_http(url, cors_domains.into(), allowed_hosts.into(), handler, remote, RpcExtractor);
+	let start_result = rpc::start_http(url, cors_domains.into(), allowed_hosts, handler, remote, RpcExtractor, limits, access_log);
 	match start_result {
 		Err(HttpServerError::IoError(err)) => match err.kind() {
 			io::ErrorKind::AddrInUse => Err(format!("RPC address {} is already in use, make sure that another instance of an Ethereum client is not running or change the address using the --jsonrpc-port and --jsonrpc-interface options.", url)),
@@ -147,13 +190,18 @@ pub fn setup_http_rpc_server(
 
 pub fn new_ipc(conf: IpcConfiguration, deps: &Dependencies) -> Result<Option<IpcServer>, String> {
 	if !conf.enabled { return Ok(None); }
-	Ok(Some(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis)?))
+	Ok(Some(setup_ipc_rpc_server(deps, &conf.socket_addr, conf.apis, conf.limits)?))
 }
 
-pub fn setup_ipc_rpc_server(dependencies: &Dependencies, addr: &str, apis: ApiSet) -> Result<IpcServer, String> {
-	let handler = setup_apis(apis, dependencies);
+pub fn setup_ipc_rpc_server(
+	dependencies: &Dependencies,
+	addr: &str,
+	apis: ApiSet,
+	limits: IpcLimits,
+) -> Result<IpcServer, String> {
+	let handler = setup_apis(apis, dependencies, None);
 	let remote = dependencies.remote.clone();
-	match rpc::start_ipc(addr, handler, remote, RpcExtractor) {
+	match rpc::start_ipc(addr, handler, remote, RpcExtractor, limits) {
 		Err(io_error) => Err(format!("RPC io error: {}", io_error)),
 		Ok(server) => Ok(server)
 	}
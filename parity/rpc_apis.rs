@@ -17,10 +17,22 @@
 use std::cmp::PartialEq;
 use std::collections::BTreeMap;
 use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
 use std::str::FromStr;
 use std::sync::Arc;
 
 pub use ethcore_rpc::SignerService;
+pub use ethcore_rpc::call_cache::CallCache;
+pub use ethcore_rpc::missing_cache::MissingCache;
+pub use ethcore_rpc::work_auth::WorkAuth;
+pub use ethcore_rpc::work_tracker::WorkTracker;
+pub use ethcore_rpc::abi_registry::AbiRegistry;
+pub use ethcore_rpc::response_limits::ResponseLimits;
+pub use ethcore_rpc::signing_audit::{SigningAudit, AUDIT_MEMORY_LIMIT as SIGNING_AUDIT_MEMORY_LIMIT};
+pub use ethcore_rpc::head_lag::HeadLag;
+pub use ethcore_rpc::sync_events::SyncEvents;
+pub use ethcore_rpc::transaction_events::TransactionEvents;
 
 use ethcore::account_provider::AccountProvider;
 use ethcore::client::Client;
@@ -32,9 +44,14 @@ use ethcore_rpc::dispatch::FullDispatcher;
 use ethsync::{ManageNetwork, SyncProvider};
 use hash_fetch::fetch::Client as FetchClient;
 use jsonrpc_core::{MetaIoHandler};
+use rustc_serialize::Decodable;
+use toml;
 use updater::Updater;
 use ethcore_logger::RotatingLogger;
 
+/// Prefix of API set entries referring to a named policy.
+pub const POLICY_PREFIX: &'static str = "policy:";
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash)]
 pub enum Api {
 	/// Web3 (Safe)
@@ -81,7 +98,17 @@ impl FromStr for Api {
 	}
 }
 
-#[derive(Debug)]
+impl Api {
+	/// Returns true if the API exposes passwords, accounts or methods with side effects.
+	pub fn is_unsafe(&self) -> bool {
+		match *self {
+			Api::Personal | Api::Signer | Api::ParityAccounts | Api::ParitySet => true,
+			_ => false,
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
 pub enum ApiSet {
 	SafeContext,
 	UnsafeContext,
@@ -112,6 +139,83 @@ impl FromStr for ApiSet {
 	}
 }
 
+#[derive(Debug, RustcDecodable)]
+struct PoliciesFile {
+	policy: BTreeMap<String, PolicyEntry>,
+}
+
+#[derive(Debug, RustcDecodable)]
+struct PolicyEntry {
+	apis: Vec<String>,
+	allow_unsafe: Option<Vec<String>>,
+}
+
+/// Named API sets defined by the operator.
+///
+/// ```toml
+/// [policy.readonly]
+/// apis = ["web3", "eth", "net"]
+///
+/// [policy.admin]
+/// apis = ["web3", "eth", "parity", "parity_set"]
+/// allow_unsafe = ["parity_set"]
+/// ```
+///
+/// Unsafe namespaces have to be listed in `allow_unsafe` to be enabled by a policy.
+#[derive(Debug, Default, PartialEq)]
+pub struct ApiPolicies {
+	policies: BTreeMap<String, HashSet<Api>>,
+}
+
+impl ApiPolicies {
+	/// Load policies from a TOML file.
+	pub fn load(path: &str) -> Result<Self, String> {
+		let mut content = String::new();
+		File::open(path).and_then(|mut file| file.read_to_string(&mut content))
+			.map_err(|e| format!("Unable to read API policies file {}: {}", path, e))?;
+		Self::from_toml(&content).map_err(|e| format!("Invalid API policies file {}: {}", path, e))
+	}
+
+	/// Parse policies from a TOML string.
+	pub fn from_toml(content: &str) -> Result<Self, String> {
+		let mut parser = toml::Parser::new(content);
+		let table = parser.parse().ok_or_else(|| format!("{:?}", parser.errors))?;
+		let mut decoder = toml::Decoder::new(toml::Value::Table(table));
+		let file = PoliciesFile::decode(&mut decoder).map_err(|e| format!("{}", e))?;
+
+		let mut policies = BTreeMap::new();
+		for (name, entry) in file.policy {
+			let apis = entry.apis.iter().map(|api| api.parse()).collect::<Result<HashSet<Api>, _>>()?;
+			let allow_unsafe = entry.allow_unsafe.unwrap_or_default().iter()
+				.map(|api| api.parse())
+				.collect::<Result<HashSet<Api>, _>>()?;
+			if let Some(api) = apis.iter().find(|api| api.is_unsafe() && !allow_unsafe.contains(api)) {
+				return Err(format!("Policy {} enables unsafe API {:?} without listing it in allow_unsafe.", name, api));
+			}
+			policies.insert(name, apis);
+		}
+
+		Ok(ApiPolicies {
+			policies: policies,
+		})
+	}
+
+	/// Parse comma-separated API names and `policy:NAME` references into a set of APIs.
+	pub fn parse_api_set(&self, s: &str) -> Result<ApiSet, String> {
+		let mut apis = HashSet::new();
+		for entry in s.split(',') {
+			if entry.starts_with(POLICY_PREFIX) {
+				let name = &entry[POLICY_PREFIX.len()..];
+				let policy = self.policies.get(name).ok_or_else(|| format!("Unknown API policy: {}", name))?;
+				apis.extend(policy.iter().cloned());
+			} else {
+				apis.insert(entry.parse()?);
+			}
+		}
+		Ok(ApiSet::List(apis))
+	}
+}
+
 pub struct Dependencies {
 	pub signer_service: Arc<SignerService>,
 	pub client: Arc<Client>,
@@ -126,6 +230,16 @@ pub struct Dependencies {
 	pub net_service: Arc<ManageNetwork>,
 	pub updater: Arc<Updater>,
 	pub geth_compatibility: bool,
+	pub call_cache: Option<Arc<CallCache>>,
+	pub missing_cache: Option<Arc<MissingCache>>,
+	pub work_auth: Option<Arc<WorkAuth>>,
+	pub work_tracker: Arc<WorkTracker>,
+	pub abi_registry: Option<Arc<AbiRegistry>>,
+	pub response_limits: Option<Arc<ResponseLimits>>,
+	pub sync_events: Arc<SyncEvents>,
+	pub transaction_events: Arc<TransactionEvents>,
+	pub head_lag: Arc<HeadLag>,
+	pub signing_audit: Arc<SigningAudit>,
 	pub dapps_interface: Option<String>,
 	pub dapps_port: Option<u16>,
 	pub fetch: FetchClient,
@@ -152,6 +266,16 @@ fn to_modules(apis: &[Api]) -> BTreeMap<String, String> {
 }
 
 impl ApiSet {
+	/// Unsafe APIs included in the set, sorted by name.
+	pub fn unsafe_apis(&self) -> Vec<String> {
+		let mut apis = self.list_apis().into_iter()
+			.filter(Api::is_unsafe)
+			.map(|api| to_modules(&[api]).into_iter().map(|(name, _)| name).collect())
+			.collect::<Vec<String>>();
+		apis.sort();
+		apis
+	}
+
 	pub fn list_apis(&self) -> HashSet<Api> {
 		let mut safe_list = vec![Api::Web3, Api::Net, Api::Eth, Api::Parity, Api::Traces, Api::Rpc]
 			.into_iter().collect();
@@ -187,12 +311,20 @@ macro_rules! add_signing_methods {
 	}
 }
 
-pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) -> MetaIoHandler<Metadata, Middleware> {
+pub fn setup_rpc(
+	stats: Arc<RpcStats>,
+	deps: Arc<Dependencies>,
+	apis: ApiSet,
+	max_concurrent_requests: Option<usize>,
+) -> MetaIoHandler<Metadata, Middleware> {
 	use ethcore_rpc::v1::*;
 
-	let mut handler = MetaIoHandler::with_middleware(Middleware::new(stats, ClientNotifier {
+	let middleware = Middleware::new(stats, ClientNotifier {
 		client: deps.client.clone(),
-	}));
+	}).with_response_limits(deps.response_limits.clone())
+		.with_signing_audit(Some(deps.signing_audit.clone()))
+		.with_max_concurrent_requests(max_concurrent_requests);
+	let mut handler = MetaIoHandler::with_middleware(middleware);
 
 	// it's turned into vector, cause ont of the cases requires &[]
 	let apis = apis.list_apis().into_iter().collect::<Vec<_>>();
@@ -218,11 +350,19 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 						pending_nonce_from_queue: deps.geth_compatibility,
 						allow_pending_receipt_query: !deps.geth_compatibility,
 						send_block_number_in_get_work: !deps.geth_compatibility,
+						call_cache: deps.call_cache.clone(),
+						missing_cache: deps.missing_cache.clone(),
+						work_auth: deps.work_auth.clone(),
+						work_tracker: Some(deps.work_tracker.clone()),
+						response_limits: deps.response_limits.clone(),
 					}
 				);
 				handler.extend_with(client.to_delegate());
 
-				let filter_client = EthFilterClient::new(&deps.client, &deps.miner);
+				let mut filter_client = EthFilterClient::new(&deps.client, &deps.miner);
+				if let Some(ref limits) = deps.response_limits {
+					filter_client = filter_client.with_response_limits(limits.clone());
+				}
 				handler.extend_with(filter_client.to_delegate());
 
 				add_signing_methods!(EthSigning, handler, deps);
@@ -250,13 +390,18 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 					signer,
 					deps.dapps_interface.clone(),
 					deps.dapps_port,
+					deps.head_lag.clone(),
+					deps.work_tracker.clone(),
+					deps.abi_registry.clone(),
+					deps.call_cache.clone(),
 				).to_delegate());
+				handler.extend_with(PubSubClient::new(deps.sync_events.clone(), deps.transaction_events.clone()).to_delegate());
 
 				add_signing_methods!(EthSigning, handler, deps);
 				add_signing_methods!(ParitySigning, handler, deps);
 			},
 			Api::ParityAccounts => {
-				handler.extend_with(ParityAccountsClient::new(&deps.secret_store).to_delegate());
+				handler.extend_with(ParityAccountsClient::new(&deps.secret_store, deps.signing_audit.clone()).to_delegate());
 			},
 			Api::ParitySet => {
 				handler.extend_with(ParitySetClient::new(
@@ -268,7 +413,11 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 				).to_delegate())
 			},
 			Api::Traces => {
-				handler.extend_with(TracesClient::new(&deps.client, &deps.miner).to_delegate())
+				let mut traces = TracesClient::new(&deps.client, &deps.miner);
+				if let Some(ref limits) = deps.response_limits {
+					traces = traces.with_response_limits(limits.clone());
+				}
+				handler.extend_with(traces.to_delegate())
 			},
 			Api::Rpc => {
 				let modules = to_modules(&apis);
@@ -281,7 +430,7 @@ pub fn setup_rpc(stats: Arc<RpcStats>, deps: Arc<Dependencies>, apis: ApiSet) ->
 
 #[cfg(test)]
 mod test {
-	use super::{Api, ApiSet};
+	use super::{Api, ApiSet, ApiPolicies};
 
 	#[test]
 	fn test_api_parsing() {
@@ -340,4 +489,39 @@ mod test {
 		].into_iter().collect();
 		assert_eq!(ApiSet::SafeContext.list_apis(), expected);
 	}
+
+	#[test]
+	fn test_api_set_unsafe_apis() {
+		assert!(ApiSet::UnsafeContext.unsafe_apis().is_empty());
+		assert_eq!(ApiSet::SafeContext.unsafe_apis(), vec!["parity_accounts".to_owned(), "parity_set".into(), "signer".into()]);
+	}
+
+	#[test]
+	fn test_api_policies_parsing() {
+		let policies = ApiPolicies::from_toml(r#"
+			[policy.readonly]
+			apis = ["web3", "eth"]
+
+			[policy.admin]
+			apis = ["parity", "parity_set"]
+			allow_unsafe = ["parity_set"]
+		"#).unwrap();
+
+		assert_eq!(policies.parse_api_set("policy:readonly").unwrap(), ApiSet::List(vec![Api::Web3, Api::Eth].into_iter().collect()));
+		assert_eq!(policies.parse_api_set("policy:admin,net").unwrap(), ApiSet::List(vec![Api::Parity, Api::ParitySet, Api::Net].into_iter().collect()));
+		assert_eq!(policies.parse_api_set("web3").unwrap(), ApiSet::List(vec![Api::Web3].into_iter().collect()));
+		assert!(policies.parse_api_set("policy:unknown").is_err());
+	}
+
+	#[test]
+	fn test_api_policies_require_unsafe_flag() {
+		assert!(ApiPolicies::from_toml(r#"
+			[policy.admin]
+			apis = ["eth", "personal"]
+		"#).is_err());
+		assert!(ApiPolicies::from_toml(r#"
+			[policy.readonly]
+			apis = ["eth", "unknown"]
+		"#).is_err());
+	}
 }
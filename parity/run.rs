@@ -15,21 +15,24 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use std::time::Duration;
+use std::path::PathBuf;
 use std::net::{TcpListener};
 use ctrlc::CtrlC;
 use fdlimit::raise_fd_limit;
 use ethcore_rpc::{NetworkSettings, informant, is_major_importing};
 use ethsync::NetworkConfiguration;
-use util::{Colour, version, Mutex, Condvar};
+use util::{Colour, version, Mutex, Condvar, Address};
 use io::{MayPanic, ForwardPanic, PanicHandler};
 use ethcore_logger::{Config as LogConfig, RotatingLogger};
 use ethcore::miner::{StratumOptions, Stratum};
-use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient};
+use ethcore::client::{Client, Mode, DatabaseCompactionProfile, VMType, BlockChainClient, SealPolicy};
 use ethcore::service::ClientService;
 use ethcore::account_provider::{AccountProvider, AccountProviderSettings};
 use ethcore::miner::{Miner, MinerService, ExternalMiner, MinerOptions};
 use ethcore::snapshot;
 use ethcore::verification::queue::VerifierSettings;
+use light::client::{Checkpoint, Config as LightClientConfig};
 use ethsync::SyncConfig;
 use informant::Informant;
 use updater::{UpdatePolicy, Updater};
@@ -46,10 +49,17 @@ use upgrade::upgrade_key_location;
 use dir::Directories;
 use cache::CacheConfig;
 use user_defaults::UserDefaults;
+use abi_source;
 use dapps;
 use ipfs;
+use keys_backup;
+use node_mode;
+use chains;
+use webhooks;
 use signer;
 use secretstore;
+use shared_db;
+use shutdown;
 use modules;
 use rpc_apis;
 use rpc;
@@ -61,6 +71,9 @@ const SNAPSHOT_PERIOD: u64 = 10000;
 // how many blocks to wait before starting a periodic snapshot.
 const SNAPSHOT_HISTORY: u64 = 100;
 
+// how long sync may make no progress before subscribers are notified it's stalled.
+const SYNC_STALL_TIMEOUT_SECS: u64 = 60;
+
 // Pops along with error messages when a password is missing or invalid.
 const VERIFY_PASSWORD_HINT: &'static str = "Make sure valid password is present in files passed using `--password` or in the configuration file.";
 
@@ -72,6 +85,7 @@ pub struct RunCmd {
 	pub pruning: Pruning,
 	pub pruning_history: u64,
 	pub pruning_memory: usize,
+	pub node_mode: node_mode::Policy,
 	/// Some if execution should be daemonized. Contains pid_file path.
 	pub daemon: Option<String>,
 	pub logger_config: LogConfig,
@@ -92,11 +106,24 @@ pub struct RunCmd {
 	pub wal: bool,
 	pub vm_type: VMType,
 	pub geth_compatibility: bool,
+	pub call_cache_ttl: u64,
+	pub call_cache_size: usize,
+	pub missing_cache_ttl: u64,
+	pub work_auth_secret: Option<String>,
+	pub work_submit_limit: Option<u32>,
+	pub response_limits: rpc_apis::ResponseLimits,
+	pub signing_audit: Option<String>,
+	pub abi_dir: Option<String>,
+	pub ntp_servers: Vec<String>,
+	pub head_lag_warning: u64,
 	pub ui_address: Option<(String, u16)>,
 	pub net_settings: NetworkSettings,
 	pub dapps_conf: dapps::Configuration,
 	pub ipfs_conf: ipfs::Configuration,
+	pub keys_backup_conf: keys_backup::Configuration,
+	pub webhooks_conf: webhooks::Configuration,
 	pub signer_conf: signer::Configuration,
+	pub trusted_rpc_conf: signer::TrustedConfiguration,
 	pub secretstore_conf: secretstore::Configuration,
 	pub dapp: Option<String>,
 	pub ui: bool,
@@ -106,7 +133,22 @@ pub struct RunCmd {
 	pub no_periodic_snapshot: bool,
 	pub check_seal: bool,
 	pub download_old_blocks: bool,
+	pub head_stall_timeout: Option<u64>,
+	pub request_parallelism: Option<usize>,
+	pub ancient_receipts_depth: Option<u64>,
 	pub verifier_settings: VerifierSettings,
+	pub dedup_bodies: bool,
+	pub seal_policy: SealPolicy,
+	pub light_checkpoint: Option<Checkpoint>,
+	pub read_only: bool,
+	pub shared_db_primary: Option<String>,
+	pub db_compaction_interval: Option<u64>,
+	pub db_compaction_rate_limit: Option<u64>,
+	pub future_block_gap: u64,
+	pub replay_bundle_dir: Option<String>,
+	pub clear_bad_blocks: bool,
+	pub tx_index_history: Option<u64>,
+	pub tx_index_watch: Vec<Address>,
 }
 
 pub fn open_ui(dapps_conf: &dapps::Configuration, signer_conf: &signer::Configuration) -> Result<(), String> {
@@ -136,6 +178,16 @@ pub fn open_dapp(dapps_conf: &dapps::Configuration, dapp: &str) -> Result<(), St
 	Ok(())
 }
 
+impl RunCmd {
+	/// Configuration of a light client following the command, whose header chain is pinned to `--light-checkpoint`.
+	pub fn light_client_config(&self) -> LightClientConfig {
+		LightClientConfig {
+			checkpoint: self.light_checkpoint.clone(),
+			..Default::default()
+		}
+	}
+}
+
 // node info fetcher for the local store.
 struct FullNodeInfo {
 	miner: Arc<Miner>, // TODO: only TXQ needed, just use that after decoupling.
@@ -176,14 +228,22 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// database paths
 	let db_dirs = cmd.dirs.database(genesis_hash, cmd.spec.legacy_fork_name(), spec.data_dir.clone());
 
+	// remember the chain for `--chain last`
+	if let Err(e) = chains::set_last_chain(&cmd.dirs.db, &cmd.spec.to_string()) {
+		warn!("{}", e);
+	}
+
 	// user defaults path
 	let user_defaults_path = db_dirs.user_defaults_path();
 
 	// load user defaults
 	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
 
-	// select pruning algorithm
-	let algorithm = cmd.pruning.to_algorithm(&user_defaults);
+	// select pruning algorithm; without a previous choice it may be suggested by the machine's resources.
+	let (algorithm, warp_sync) = match cmd.pruning == Pruning::Auto && user_defaults.is_first_launch {
+		true => node_mode::select(cmd.node_mode, &db_dirs.db_root_path(), cmd.pruning.to_algorithm(&user_defaults), cmd.warp_sync),
+		false => (cmd.pruning.to_algorithm(&user_defaults), cmd.warp_sync),
+	};
 
 	// check if tracing is on
 	let tracing = tracing_switch_to_bool(cmd.tracing, &user_defaults)?;
@@ -194,7 +254,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// get the mode
 	let mode = mode_switch_to_bool(cmd.mode, &user_defaults)?;
 	trace!(target: "mode", "mode is {:?}", mode);
-	let network_enabled = match mode { Mode::Dark(_) | Mode::Off => false, _ => true, };
+	let network_enabled = !cmd.read_only && match mode { Mode::Dark(_) | Mode::Off => false, _ => true, };
 
 	// get the update policy
 	let update_policy = cmd.update_policy;
@@ -203,11 +263,13 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let client_path = db_dirs.client_path(algorithm);
 	let snapshot_path = db_dirs.snapshot_path();
 
-	// execute upgrades
-	execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	// execute upgrades; a read-only instance relies on the writer to upgrade the database.
+	if !cmd.read_only {
+		execute_upgrades(&cmd.dirs.base, &db_dirs, algorithm, cmd.compaction.compaction_profile(db_dirs.db_root_path().as_path()))?;
+	}
 
 	// create dirs used by parity
-	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.signer_conf.enabled, cmd.secretstore_conf.enabled)?;
+	cmd.dirs.create_dirs(cmd.dapps_conf.enabled, cmd.signer_conf.enabled || cmd.trusted_rpc_conf.enabled, cmd.secretstore_conf.enabled)?;
 
 	// run in daemon mode
 	if let Some(pid_file) = cmd.daemon {
@@ -228,6 +290,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		}
 	);
 	info!("Operating mode: {}", Colour::White.bold().paint(format!("{}", mode)));
+	if cmd.read_only {
+		info!("Database opened {}: not syncing, mining or accepting transactions.", Colour::White.bold().paint("read-only"));
+	}
 
 	// display warning about using experimental journaldb alorithm
 	if !algorithm.is_stable() {
@@ -246,13 +311,17 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		sync_config.subprotocol_name.clone_from_slice(spec.subprotocol_name().as_bytes());
 	}
 	sync_config.fork_block = spec.fork_block();
-	sync_config.warp_sync = cmd.warp_sync;
+	sync_config.warp_sync = warp_sync;
 	sync_config.download_old_blocks = cmd.download_old_blocks;
+	sync_config.head_stall_timeout = cmd.head_stall_timeout;
+	sync_config.request_parallelism = cmd.request_parallelism;
+	sync_config.ancient_receipts_depth = cmd.ancient_receipts_depth;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
 	// prepare account provider
 	let account_provider = Arc::new(prepare_account_provider(&cmd.spec, &cmd.dirs, &spec.data_dir, cmd.acc_conf, &passwords)?);
+	let keys_path = cmd.dirs.keys_path(&spec.data_dir);
 
 	// create miner
 	let initial_min_gas_price = cmd.gas_pricer.initial_min();
@@ -260,7 +329,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	miner.set_author(cmd.miner_extras.author);
 	miner.set_gas_floor_target(cmd.miner_extras.gas_floor_target);
 	miner.set_gas_ceil_target(cmd.miner_extras.gas_ceil_target);
-	miner.set_extra_data(cmd.miner_extras.extra_data);
+	miner.set_extra_data(cmd.miner_extras.extra_data).unwrap_or_else(|e| warn!("Couldn't set extra data: {}. Ignoring.", e));
 	miner.set_transactions_limit(cmd.miner_extras.transactions_limit);
 	miner.set_minimal_gas_price(initial_min_gas_price);
 	miner.recalibrate_minimal_gas_price();
@@ -283,6 +352,12 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		}
 	}
 
+	for (address, authorize) in cmd.miner_extras.signer_votes {
+		if !miner.propose_signer(address, authorize) {
+			return Err(format!("Cannot vote on signer {}: the chain doesn't vote on signers in block headers.", address));
+		}
+	}
+
 	// create client config
 	let mut client_config = to_client_config(
 		&cmd.cache_config,
@@ -301,6 +376,20 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.blockchain.dedup_transactions = cmd.dedup_bodies;
+	client_config.blockchain.tx_index_history = cmd.tx_index_history;
+	client_config.blockchain.tx_index_watch = cmd.tx_index_watch.iter().cloned().collect();
+	client_config.seal_policy = cmd.seal_policy;
+	if cmd.light_checkpoint.is_some() {
+		warn!("--light-checkpoint only applies to light clients and is ignored by a full node.");
+	}
+	client_config.read_only = cmd.read_only;
+	client_config.future_block_gap = cmd.future_block_gap;
+	client_config.replay_bundle_dir = cmd.replay_bundle_dir.map(PathBuf::from);
+	client_config.clear_bad_blocks = cmd.clear_bad_blocks;
+	client_config.db_compaction_interval = cmd.db_compaction_interval.map(Duration::from_secs);
+	// in bytes
+	client_config.db_compaction_rate_limit = cmd.db_compaction_rate_limit.map(|mb| mb * 1024 * 1024);
 
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -334,32 +423,40 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	let client = service.client();
 	let snapshot_service = service.snapshot_service();
 
-	// initialize the local node information store.
-	let store = {
-		let db = service.db();
-		let node_info = FullNodeInfo {
-			miner: miner.clone(),
-		};
-
-		let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
+	// keep up with the instance writing the database.
+	let shared_db_follower = match cmd.shared_db_primary {
+		Some(ref path) => Some(shared_db::Follower::start(path.clone(), client.clone(), genesis_hash)?),
+		None => None,
+	};
 
-		// re-queue pending transactions.
-		match store.pending_transactions() {
-			Ok(pending) => {
-				for pending_tx in pending {
-					if let Err(e) = miner.import_own_transaction(&*client, pending_tx) {
-						warn!("Error importing saved transaction: {}", e)
+	// initialize the local node information store.
+	if !cmd.read_only {
+		let store = {
+			let db = service.db();
+			let node_info = FullNodeInfo {
+				miner: miner.clone(),
+			};
+
+			let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
+
+			// re-queue pending transactions.
+			match store.pending_transactions() {
+				Ok(pending) => {
+					for pending_tx in pending {
+						if let Err(e) = miner.import_own_transaction(&*client, pending_tx) {
+							warn!("Error importing saved transaction: {}", e)
+						}
 					}
 				}
+				Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 			}
-			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
-		}
 
-		Arc::new(store)
-	};
+			Arc::new(store)
+		};
 
-	// register it as an IO service to update periodically.
-	service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
+		// register it as an IO service to update periodically.
+		service.register_io_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
+	}
 
 	// create external miner
 	let external_miner = Arc::new(ExternalMiner::default());
@@ -407,6 +504,20 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	// set up dependencies for rpc servers
 	let rpc_stats = Arc::new(informant::RpcStats::default());
 	let signer_path = cmd.signer_conf.signer_path.clone();
+	let sync_events = Arc::new(rpc_apis::SyncEvents::new(Duration::from_secs(SYNC_STALL_TIMEOUT_SECS)));
+	let transaction_events = Arc::new(rpc_apis::TransactionEvents::default());
+	let head_lag = Arc::new(rpc_apis::HeadLag::new(cmd.ntp_servers.clone(), cmd.head_lag_warning));
+	let events = transaction_events.clone();
+	miner.add_transaction_listener(move |event| events.notify(event));
+	let signing_audit = match cmd.signing_audit {
+		Some(ref path) => rpc_apis::SigningAudit::open(path, rpc_apis::SIGNING_AUDIT_MEMORY_LIMIT)?,
+		None => rpc_apis::SigningAudit::new(rpc_apis::SIGNING_AUDIT_MEMORY_LIMIT),
+	};
+	let mut abi_registry = match cmd.abi_dir {
+		Some(ref dir) => rpc_apis::AbiRegistry::load(dir)?,
+		None => rpc_apis::AbiRegistry::default(),
+	};
+	abi_registry.set_source(Arc::new(abi_source::RegistryAbis::new(client.clone(), fetch.clone(), event_loop.remote())));
 	let deps_for_rpc_apis = Arc::new(rpc_apis::Dependencies {
 		signer_service: Arc::new(rpc_apis::SignerService::new(move || {
 			signer::generate_new_token(signer_path.clone()).map_err(|e| format!("{:?}", e))
@@ -423,6 +534,26 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		net_service: manage_network.clone(),
 		updater: updater.clone(),
 		geth_compatibility: cmd.geth_compatibility,
+		call_cache: match cmd.call_cache_ttl {
+			0 => None,
+			ttl => Some(Arc::new(rpc_apis::CallCache::new(Duration::from_secs(ttl), cmd.call_cache_size))),
+		},
+		missing_cache: match cmd.missing_cache_ttl {
+			0 => None,
+			ttl => Some(Arc::new(rpc_apis::MissingCache::new(Duration::from_secs(ttl)))),
+		},
+		work_auth: cmd.work_auth_secret.as_ref().map(|secret| {
+			let auth = rpc_apis::WorkAuth::new(secret, cmd.work_submit_limit);
+			info!("Work packages are signed by 0x{:?}", auth.signer());
+			Arc::new(auth)
+		}),
+		work_tracker: Arc::new(rpc_apis::WorkTracker::default()),
+		abi_registry: Some(Arc::new(abi_registry)),
+		response_limits: Some(Arc::new(cmd.response_limits.clone())),
+		sync_events: sync_events.clone(),
+		transaction_events: transaction_events,
+		head_lag: head_lag.clone(),
+		signing_audit: Arc::new(signing_audit),
 		dapps_interface: match cmd.dapps_conf.enabled {
 			true => Some(cmd.dapps_conf.interface.clone()),
 			false => None,
@@ -464,12 +595,27 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	};
 	let signer_server = signer::start(cmd.signer_conf.clone(), signer_deps)?;
 
+	// the trusted rpc server
+	let trusted_rpc_deps = signer::Dependencies {
+		apis: deps_for_rpc_apis.clone(),
+		remote: event_loop.raw_remote(),
+		rpc_stats: rpc_stats.clone(),
+	};
+	let trusted_rpc_server = signer::start_trusted(cmd.trusted_rpc_conf, trusted_rpc_deps)?;
+
 	// secret store key server
 	let secretstore_deps = secretstore::Dependencies { };
 	let secretstore_key_server = secretstore::start(cmd.secretstore_conf.clone(), secretstore_deps);
 
 	// the ipfs server
 	let ipfs_server = ipfs::start_server(cmd.ipfs_conf.clone(), client.clone())?;
+	let keys_backup = keys_backup::start(cmd.keys_backup_conf.clone(), keys_path)?;
+
+	// webhook notifications about watched addresses
+	let webhooks = webhooks::start(cmd.webhooks_conf.clone(), service.client(), fetch.clone())?;
+	if let Some(ref webhooks) = webhooks {
+		service.add_notify(webhooks.clone());
+	}
 
 	// the informant
 	let informant = Arc::new(Informant::new(
@@ -479,7 +625,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 		Some(snapshot_service.clone()),
 		Some(rpc_stats.clone()),
 		cmd.logger_config.color,
-	));
+	).with_sync_events(sync_events).with_head_lag(head_lag));
 	service.add_notify(informant.clone());
 	service.register_io_handler(informant.clone()).map_err(|_| "Unable to register informant handler".to_owned())?;
 
@@ -488,7 +634,9 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	user_defaults.tracing = tracing;
 	user_defaults.fat_db = fat_db;
 	user_defaults.mode = mode;
-	user_defaults.save(&user_defaults_path)?;
+	if !cmd.read_only {
+		user_defaults.save(&user_defaults_path)?;
+	}
 
 	// tell client how to save the default mode if it gets changed.
 	client.on_user_defaults_change(move |mode: Option<Mode>| {
@@ -499,7 +647,7 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	});
 
 	// the watcher must be kept alive.
-	let _watcher = match cmd.no_periodic_snapshot {
+	let _watcher = match cmd.no_periodic_snapshot || cmd.read_only {
 		true => None,
 		false => {
 			let sync = sync_provider.clone();
@@ -527,13 +675,16 @@ pub fn execute(cmd: RunCmd, can_restart: bool, logger: Arc<RotatingLogger>) -> R
 	}
 
 	// Handle exit
-	let restart = wait_for_exit(panic_handler, Some(updater), Some(client), can_restart);
-
-	// drop this stuff as soon as exit detected.
-	drop((http_server, ipc_server, dapps_server, signer_server, secretstore_key_server, ipfs_server, event_loop));
+	let restart = wait_for_exit(panic_handler, Some(updater), Some(client.clone()), can_restart);
 
 	info!("Finishing work, please wait...");
 
+	// refuse writes and get the chain safely to disk while RPC queries are still served.
+	shutdown::execute(&client, &*manage_network);
+
+	// drop this stuff as soon as the chain is flushed.
+	drop((http_server, ipc_server, dapps_server, signer_server, trusted_rpc_server, secretstore_key_server, ipfs_server, keys_backup, webhooks, event_loop, shared_db_follower));
+
 	// to make sure timer does not spawn requests while shutdown is in progress
 	informant.shutdown();
 	// just Arc is dropping here, to allow other reference release in its default time
@@ -0,0 +1,211 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Following the database of another instance from a read-only one.
+//!
+//! The primary instance flushes its database and bumps a flush marker after every
+//! import round. A follower performs a handshake over the primary's IPC socket,
+//! polls the marker and reopens its read-only database whenever the marker changes.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{self, Map, Value};
+use ethcore::client::Client;
+use util::H256;
+
+// how often the primary's flush marker is polled.
+const POLL_INTERVAL_MS: u64 = 1000;
+// how long to wait before reconnecting to the primary.
+const RECONNECT_INTERVAL_SECS: u64 = 5;
+// how long to wait for a single response from the primary.
+const RESPONSE_TIMEOUT_SECS: u64 = 30;
+
+trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// Keeps a read-only client in step with the instance writing its database.
+pub struct Follower {
+	exit: Arc<AtomicBool>,
+	handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Follower {
+	/// Start following the instance listening on the given IPC socket.
+	pub fn start(primary_ipc: String, client: Arc<Client>, genesis_hash: H256) -> Result<Follower, String> {
+		if !client.is_read_only() {
+			return Err("Following another instance requires the client to be read-only.".into());
+		}
+
+		let exit = Arc::new(AtomicBool::new(false));
+		let thread_exit = exit.clone();
+		let handle = thread::Builder::new()
+			.name("shared-db".into())
+			.spawn(move || {
+				let mut last_marker = None;
+				while !thread_exit.load(Ordering::SeqCst) {
+					match follow(&primary_ipc, &*client, genesis_hash, &mut last_marker, &*thread_exit) {
+						Ok(()) => break,
+						Err(e) => {
+							warn!(target: "shared_db", "Lost track of the instance at {}: {}. Retrying in {}s.", primary_ipc, e, RECONNECT_INTERVAL_SECS);
+							thread::sleep(Duration::from_secs(RECONNECT_INTERVAL_SECS));
+						}
+					}
+				}
+			})
+			.map_err(|e| format!("Could not spawn shared database thread: {}", e))?;
+
+		Ok(Follower {
+			exit: exit,
+			handle: Some(handle),
+		})
+	}
+}
+
+impl Drop for Follower {
+	fn drop(&mut self) {
+		self.exit.store(true, Ordering::SeqCst);
+		if let Some(handle) = self.handle.take() {
+			let _ = handle.join();
+		}
+	}
+}
+
+/// Follow the primary until exit is requested or the connection fails.
+fn follow(path: &str, client: &Client, genesis_hash: H256, last_marker: &mut Option<Value>, exit: &AtomicBool) -> Result<(), String> {
+	let mut connection = Connection::new(connect(path)?);
+	let mut marker = connection.call("parity_sharedDbHandshake", vec![Value::String(format!("0x{:?}", genesis_hash))])?;
+	info!("Following the database of the instance at {}", path);
+
+	loop {
+		if last_marker.as_ref() != Some(&marker) {
+			client.reopen_db().map_err(|e| format!("Could not reopen database: {:?}", e))?;
+			trace!(target: "shared_db", "Reopened database at flush marker {}", marker);
+			*last_marker = Some(marker);
+		}
+
+		if exit.load(Ordering::SeqCst) {
+			return Ok(());
+		}
+
+		thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+		marker = connection.call("parity_dbFlushMarker", vec![])?;
+	}
+}
+
+#[cfg(unix)]
+fn connect(path: &str) -> Result<Box<Stream>, String> {
+	use std::os::unix::net::UnixStream;
+
+	let stream = UnixStream::connect(path).map_err(|e| format!("{}", e))?;
+	stream.set_read_timeout(Some(Duration::from_secs(RESPONSE_TIMEOUT_SECS))).map_err(|e| format!("{}", e))?;
+	Ok(Box::new(stream))
+}
+
+#[cfg(not(unix))]
+fn connect(_path: &str) -> Result<Box<Stream>, String> {
+	Err("Following another instance is only supported on Unix.".into())
+}
+
+/// Minimal JSON-RPC client over a stream socket.
+struct Connection {
+	stream: BufReader<Box<Stream>>,
+	next_id: u64,
+}
+
+impl Connection {
+	fn new(stream: Box<Stream>) -> Self {
+		Connection {
+			stream: BufReader::new(stream),
+			next_id: 0,
+		}
+	}
+
+	fn call(&mut self, method: &str, params: Vec<Value>) -> Result<Value, String> {
+		self.next_id += 1;
+		let mut request = Map::new();
+		request.insert("jsonrpc".into(), Value::String("2.0".into()));
+		request.insert("method".into(), Value::String(method.into()));
+		request.insert("params".into(), Value::Array(params));
+		request.insert("id".into(), Value::from(self.next_id));
+
+		{
+			let stream = self.stream.get_mut();
+			serde_json::to_writer(&mut *stream, &Value::Object(request)).map_err(|e| format!("{}", e))?;
+			stream.flush().map_err(|e| format!("{}", e))?;
+		}
+
+		let response = self.read_message().map_err(|e| format!("{}", e))?;
+		let response = response.as_object().ok_or_else(|| "Malformed response".to_owned())?;
+		match (response.get("result"), response.get("error")) {
+			(_, Some(error)) => Err(format!("{} failed: {}", method, error)),
+			(Some(result), None) => Ok(result.clone()),
+			(None, None) => Err("Malformed response".into()),
+		}
+	}
+
+	/// Read a single JSON object; the server doesn't delimit its responses.
+	fn read_message(&mut self) -> io::Result<Value> {
+		let mut message = Vec::new();
+		let mut depth = 0usize;
+		let mut in_string = false;
+		let mut escaped = false;
+
+		loop {
+			let (consumed, done) = {
+				let buf = self.stream.fill_buf()?;
+				if buf.is_empty() {
+					return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Connection closed"));
+				}
+
+				let mut consumed = 0;
+				let mut done = false;
+				for &b in buf {
+					consumed += 1;
+					// skip anything between messages.
+					if depth == 0 && b != b'{' {
+						continue;
+					}
+
+					message.push(b);
+					match b {
+						_ if escaped => escaped = false,
+						b'\\' if in_string => escaped = true,
+						b'"' => in_string = !in_string,
+						b'{' if !in_string => depth += 1,
+						b'}' if !in_string => {
+							depth -= 1;
+							if depth == 0 {
+								done = true;
+								break;
+							}
+						},
+						_ => {},
+					}
+				}
+				(consumed, done)
+			};
+
+			self.stream.consume(consumed);
+			if done {
+				return serde_json::from_slice(&message).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+			}
+		}
+	}
+}
@@ -0,0 +1,69 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Coordinated shutdown of a full node.
+//!
+//! Phases run in order, each reported when it starts and finishes:
+//! 1. Refuse new blocks and transactions, including those submitted over RPC.
+//! 2. Import the blocks already in the import queue, dropping those left after a timeout.
+//! 3. Write journaled state and block extras to disk.
+//! 4. Stop the network, closing its listeners and peer connections.
+//!
+//! RPC servers are closed by the caller afterwards, so queries are answered until the
+//! chain is safely on disk. The import is bounded so that the flush happens before service
+//! managers give up on the process (e.g. systemd's 90 seconds `TimeoutStopSec`); dropped
+//! blocks are downloaded again after restart.
+
+use std::time::{Duration, Instant};
+
+use ethcore::client::{BlockChainClient, Client};
+use ethsync::ManageNetwork;
+use informant::MillisecondDuration;
+
+/// Time spent importing queued blocks before the rest of the queue is dropped.
+const QUEUE_IMPORT_TIMEOUT_SECS: u64 = 30;
+
+fn phase<F>(number: usize, name: &str, f: F) where F: FnOnce() {
+	info!("Shutdown {}/4: {}...", number, name);
+	let started = Instant::now();
+	f();
+	info!("Shutdown {}/4: {} done in {}ms", number, name, started.elapsed().as_milliseconds());
+}
+
+/// Bring the client and network to a stop, leaving a consistent database behind.
+pub fn execute(client: &Client, network: &ManageNetwork) {
+	// a read-only database has nothing to flush.
+	let read_only = client.is_read_only();
+
+	phase(1, "refusing new blocks and transactions", || client.begin_shutdown());
+	phase(2, "importing queued blocks", || {
+		let queued = client.queue_info().total_queue_size();
+		if queued > 0 {
+			info!("Importing {} queued blocks", queued);
+		}
+		let deadline = Instant::now() + Duration::from_secs(QUEUE_IMPORT_TIMEOUT_SECS);
+		if !client.flush_queue_until(deadline) {
+			warn!("Dropped the blocks left in the import queue after {}s", QUEUE_IMPORT_TIMEOUT_SECS);
+		}
+	});
+	phase(3, "flushing state and extras to disk", || if !read_only { client.flush_db() });
+	phase(4, "stopping network", || network.stop_network());
+
+	if !read_only {
+		let marker = client.db_flush_marker();
+		info!("Database flushed at block #{} ({})", marker.best_block_number, marker.best_block_hash);
+	}
+}
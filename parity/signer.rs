@@ -32,6 +32,7 @@ use path::restrict_permissions_owner;
 use util::H256;
 
 const CODES_FILENAME: &'static str = "authcodes";
+const TRUSTED_CODES_FILENAME: &'static str = "trusted_authcodes";
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Configuration {
@@ -40,6 +41,7 @@ pub struct Configuration {
 	pub interface: String,
 	pub signer_path: String,
 	pub skip_origin_validation: bool,
+	pub apis: rpc_apis::ApiSet,
 }
 
 impl Default for Configuration {
@@ -51,6 +53,33 @@ impl Default for Configuration {
 			interface: "127.0.0.1".into(),
 			signer_path: replace_home(&data_dir, "$BASE/signer"),
 			skip_origin_validation: false,
+			apis: rpc_apis::ApiSet::SafeContext,
+		}
+	}
+}
+
+/// Configuration of the trusted RPC transport: a localhost WebSocket server
+/// accepting every token from its codes file for a single connection.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TrustedConfiguration {
+	pub enabled: bool,
+	pub port: u16,
+	pub signer_path: String,
+	pub apis: rpc_apis::ApiSet,
+}
+
+impl Default for TrustedConfiguration {
+	fn default() -> Self {
+		let data_dir = default_data_path();
+		TrustedConfiguration {
+			enabled: false,
+			port: 8547,
+			signer_path: replace_home(&data_dir, "$BASE/signer"),
+			apis: rpc_apis::ApiSet::List(vec![
+				rpc_apis::Api::Web3, rpc_apis::Api::Net, rpc_apis::Api::Eth, rpc_apis::Api::Parity,
+				rpc_apis::Api::Traces, rpc_apis::Api::Rpc, rpc_apis::Api::Personal, rpc_apis::Api::Signer,
+				rpc_apis::Api::ParityAccounts, rpc_apis::Api::ParitySet,
+			].into_iter().collect()),
 		}
 	}
 }
@@ -70,9 +99,10 @@ pub struct NewToken {
 #[derive(Debug, Default, Clone)]
 pub struct StandardExtractor;
 impl signer::MetaExtractor<ethcore_rpc::Metadata> for StandardExtractor {
-	fn extract_metadata(&self, session: &H256) -> ethcore_rpc::Metadata {
+	fn extract_metadata(&self, session: &H256, push: signer::Push) -> ethcore_rpc::Metadata {
 		let mut metadata = ethcore_rpc::Metadata::default();
 		metadata.origin = ethcore_rpc::Origin::Signer((*session).into());
+		metadata.sink = Some(ethcore_rpc::Sink::new(move |message| push.send(message)));
 		metadata
 	}
 }
@@ -92,6 +122,12 @@ fn codes_path(path: String) -> PathBuf {
 	p
 }
 
+fn trusted_codes_path(path: String) -> PathBuf {
+	let mut p = PathBuf::from(path);
+	p.push(TRUSTED_CODES_FILENAME);
+	p
+}
+
 pub fn execute(cmd: Configuration) -> Result<String, String> {
 	Ok(generate_token_and_url(&cmd)?.message)
 }
@@ -141,7 +177,7 @@ fn do_start(conf: Configuration, deps: Dependencies) -> Result<SignerServer, Str
 		}
 		let server = server.skip_origin_validation(conf.skip_origin_validation);
 		let server = server.stats(deps.rpc_stats.clone());
-		let handler = rpc_apis::setup_rpc(deps.rpc_stats, deps.apis, rpc_apis::ApiSet::SafeContext);
+		let handler = rpc_apis::setup_rpc(deps.rpc_stats, deps.apis, conf.apis, None);
 		let remote = deps.remote.clone();
 		server.start_with_extractor(addr, handler, remote, StandardExtractor)
 	};
@@ -156,4 +192,46 @@ fn do_start(conf: Configuration, deps: Dependencies) -> Result<SignerServer, Str
 	}
 }
 
+pub fn start_trusted(conf: TrustedConfiguration, deps: Dependencies) -> Result<Option<SignerServer>, String> {
+	if !conf.enabled {
+		return Ok(None);
+	}
 
+	let addr = format!("127.0.0.1:{}", conf.port)
+		.parse()
+		.map_err(|_| format!("Invalid trusted RPC port specified: {}", conf.port))?;
+
+	// make sure there is a token for the first connection
+	let path = trusted_codes_path(conf.signer_path);
+	let mut codes = signer::AuthCodes::from_file(&path).map_err(|e| format!("Error reading trusted RPC tokens: {}", e))?;
+	codes.clear_garbage();
+	if codes.is_empty() {
+		codes.generate_new().map_err(|e| format!("Error generating trusted RPC token: {}", e))?;
+	}
+	codes.to_file(&path).map_err(|e| format!("Error writing trusted RPC tokens: {}", e))?;
+	restrict_permissions_owner(&path, true, false).map_err(|e| format!("Error restricting trusted RPC tokens file permissions: {}", e))?;
+
+	let start_result = {
+		let server = signer::ServerBuilder::new(
+			deps.apis.signer_service.queue(),
+			path.clone(),
+		);
+		let server = server.one_time_tokens(true);
+		let server = server.stats(deps.rpc_stats.clone());
+		let handler = rpc_apis::setup_rpc(deps.rpc_stats, deps.apis, conf.apis, None);
+		let remote = deps.remote.clone();
+		server.start_with_extractor(addr, handler, remote, StandardExtractor)
+	};
+
+	match start_result {
+		Err(signer::ServerError::IoError(err)) => match err.kind() {
+			io::ErrorKind::AddrInUse => Err(format!("Trusted RPC address {} is already in use, make sure that another instance of an Ethereum client is not running or change the port using the --trusted-rpc-port option.", addr)),
+			_ => Err(format!("Trusted RPC io error: {}", err)),
+		},
+		Err(e) => Err(format!("Trusted RPC Error: {:?}", e)),
+		Ok(server) => {
+			info!("Trusted RPC listening on {}. Every token in {} authorizes a single connection.", addr, path.display());
+			Ok(Some(server))
+		},
+	}
+}
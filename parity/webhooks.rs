@@ -0,0 +1,378 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Notifications about transactions and logs of watched addresses, POSTed to webhooks.
+//!
+//! Every canonical block touching a watched address results in one JSON notification,
+//! sent again with `"removed": true` if the block is retracted. Notifications are
+//! signed with the configured key: the `X-Parity-Signature` header holds the signature
+//! of the Keccak hash of the body. Failed deliveries are retried with exponential backoff.
+
+use std::cmp;
+use std::collections::HashSet;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ethcore::client::{BlockChainClient, BlockId, ChainNotify, Client};
+use ethcore::ethstore::ethkey::{self, Secret};
+use ethcore::receipt::Receipt;
+use ethcore::transaction::{Action, SignedTransaction};
+use futures::Future;
+use hash_fetch::fetch::Client as FetchClient;
+use rlp::UntrustedRlp;
+use rustc_serialize::hex::ToHex;
+use serde_json::{self, Map, Value};
+use util::{Address, Bytes, H256, Mutex};
+use util::sha3::Hashable;
+
+/// Number of delivery attempts of a notification before it's dropped.
+const MAX_ATTEMPTS: u32 = 8;
+/// Delay before the first retry, doubled with every further attempt.
+const INITIAL_BACKOFF_SECS: u64 = 2;
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Configuration {
+	pub addresses: Vec<Address>,
+	pub urls: Vec<String>,
+	pub signing_key: Option<Secret>,
+}
+
+struct Delivery {
+	url: String,
+	body: Bytes,
+	signature: Option<String>,
+	attempt: u32,
+	due: Instant,
+}
+
+fn backoff(attempt: u32) -> Duration {
+	Duration::from_secs(INITIAL_BACKOFF_SECS << cmp::min(attempt, 10))
+}
+
+fn hex_hash(hash: &H256) -> String {
+	format!("0x{}", hash.hex())
+}
+
+fn hex_address(address: &Address) -> String {
+	format!("0x{}", address.hex())
+}
+
+fn is_topic_of(topic: &H256, address: &Address) -> bool {
+	topic[..12].iter().all(|b| *b == 0) && &topic[12..] == &address[..]
+}
+
+/// Collect the activity of watched addresses in a block.
+fn block_events(watched: &HashSet<Address>, transactions: &[SignedTransaction], receipts: &[Receipt]) -> Vec<Value> {
+	let mut events = Vec::new();
+	let mut log_index = 0;
+
+	for (index, (tx, receipt)) in transactions.iter().zip(receipts).enumerate() {
+		let to = match tx.action {
+			Action::Call(ref to) => Some(to),
+			Action::Create => None,
+		};
+		let sender = tx.sender();
+		let outgoing = watched.contains(&sender);
+		let incoming = to.map_or(false, |to| watched.contains(to));
+
+		if outgoing || incoming {
+			let mut event = Map::new();
+			event.insert("type".into(), Value::String("transaction".into()));
+			event.insert("incoming".into(), Value::Bool(incoming));
+			event.insert("outgoing".into(), Value::Bool(outgoing));
+			event.insert("transactionHash".into(), Value::String(hex_hash(&tx.hash())));
+			event.insert("transactionIndex".into(), Value::from(index as u64));
+			event.insert("from".into(), Value::String(hex_address(&sender)));
+			event.insert("to".into(), to.map_or(Value::Null, |to| Value::String(hex_address(to))));
+			event.insert("value".into(), Value::String(format!("0x{}", tx.value.to_hex())));
+			events.push(Value::Object(event));
+		}
+
+		for log in &receipt.logs {
+			let matches = watched.contains(&log.address) ||
+				log.topics.iter().any(|topic| watched.iter().any(|address| is_topic_of(topic, address)));
+
+			if matches {
+				let mut event = Map::new();
+				event.insert("type".into(), Value::String("log".into()));
+				event.insert("transactionHash".into(), Value::String(hex_hash(&tx.hash())));
+				event.insert("transactionIndex".into(), Value::from(index as u64));
+				event.insert("logIndex".into(), Value::from(log_index as u64));
+				event.insert("address".into(), Value::String(hex_address(&log.address)));
+				event.insert("topics".into(), Value::Array(log.topics.iter().map(|t| Value::String(hex_hash(t))).collect()));
+				event.insert("data".into(), Value::String(format!("0x{}", log.data.to_hex())));
+				events.push(Value::Object(event));
+			}
+			log_index += 1;
+		}
+	}
+
+	events
+}
+
+/// A block entering or leaving the canonical chain.
+struct BlockChange {
+	hash: H256,
+	removed: bool,
+}
+
+/// Builds the notifications. Lives on the delivery thread, so that recovering the
+/// senders of each block's transactions doesn't hold up block import.
+struct Notifier {
+	client: Arc<Client>,
+	addresses: HashSet<Address>,
+	urls: Vec<String>,
+	signing_key: Option<Secret>,
+}
+
+impl Notifier {
+	fn deliveries(&self, change: BlockChange) -> Vec<Delivery> {
+		let BlockChange { hash, removed } = change;
+		let block = match self.client.block(BlockId::Hash(hash)) {
+			Some(block) => block,
+			None => return Vec::new(),
+		};
+		let receipts = self.client.block_receipts(&hash)
+			.and_then(|raw| UntrustedRlp::new(&raw).as_list::<Receipt>().ok())
+			.unwrap_or_default();
+		let transactions: Vec<_> = block.transactions().into_iter()
+			.filter_map(|tx| SignedTransaction::new(tx).ok())
+			.collect();
+
+		let events = block_events(&self.addresses, &transactions, &receipts);
+		if events.is_empty() {
+			return Vec::new();
+		}
+
+		let mut notification = Map::new();
+		notification.insert("blockHash".into(), Value::String(hex_hash(&hash)));
+		notification.insert("blockNumber".into(), Value::from(block.number()));
+		notification.insert("removed".into(), Value::Bool(removed));
+		notification.insert("events".into(), Value::Array(events));
+		let body = serde_json::to_vec(&Value::Object(notification)).expect("Serialization of a JSON value can't fail; qed");
+
+		let signature = match self.signing_key {
+			Some(ref key) => match ethkey::sign(key, &body.sha3()) {
+				Ok(signature) => Some(format!("0x{}", signature)),
+				Err(e) => {
+					warn!(target: "webhooks", "Unable to sign notification: {}", e);
+					return Vec::new();
+				},
+			},
+			None => None,
+		};
+
+		self.urls.iter().map(|url| Delivery {
+			url: url.clone(),
+			body: body.clone(),
+			signature: signature.clone(),
+			attempt: 0,
+			due: Instant::now(),
+		}).collect()
+	}
+}
+
+/// Sends notifications about watched addresses' activity in blocks as they are imported.
+pub struct Webhooks {
+	changes: Mutex<Option<mpsc::Sender<BlockChange>>>,
+	thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl Drop for Webhooks {
+	fn drop(&mut self) {
+		// closing the channel stops the delivery thread.
+		self.changes.lock().take();
+		if let Some(thread) = self.thread.lock().take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Webhooks {
+	fn notify_block(&self, hash: H256, removed: bool) {
+		if let Some(ref changes) = *self.changes.lock() {
+			let _ = changes.send(BlockChange { hash: hash, removed: removed });
+		}
+	}
+}
+
+impl ChainNotify for Webhooks {
+	fn new_blocks(
+		&self,
+		_imported: Vec<H256>,
+		_invalid: Vec<H256>,
+		enacted: Vec<H256>,
+		retracted: Vec<H256>,
+		_sealed: Vec<H256>,
+		_proposed: Vec<Bytes>,
+		_duration: u64)
+	{
+		for hash in retracted {
+			self.notify_block(hash, true);
+		}
+		for hash in enacted {
+			self.notify_block(hash, false);
+		}
+	}
+}
+
+// builds and delivers notifications until the channel is closed.
+fn deliver(notifier: Notifier, fetch: FetchClient, changes: mpsc::Receiver<BlockChange>) {
+	let mut pending: Vec<Delivery> = Vec::new();
+
+	loop {
+		let now = Instant::now();
+		let received = match pending.iter().map(|d| d.due).min() {
+			Some(due) if due <= now => Ok(None),
+			Some(due) => match changes.recv_timeout(due - now) {
+				Ok(change) => Ok(Some(change)),
+				Err(mpsc::RecvTimeoutError::Timeout) => Ok(None),
+				Err(mpsc::RecvTimeoutError::Disconnected) => Err(()),
+			},
+			None => changes.recv().map(Some).map_err(|_| ()),
+		};
+
+		match received {
+			Ok(Some(change)) => pending.extend(notifier.deliveries(change)),
+			Ok(None) => {},
+			Err(()) => break,
+		}
+
+		let now = Instant::now();
+		let (due, waiting): (Vec<_>, Vec<_>) = pending.drain(..).partition(|d| d.due <= now);
+		pending = waiting;
+
+		for mut delivery in due {
+			let headers = delivery.signature.iter().map(|s| ("X-Parity-Signature", s.clone())).collect();
+			let result = fetch.post_json(&delivery.url, delivery.body.clone(), headers).wait();
+			match result {
+				Ok(ref response) if response.status().is_success() => {
+					trace!(target: "webhooks", "Delivered notification to {}", delivery.url);
+					continue;
+				},
+				Ok(response) => debug!(target: "webhooks", "Webhook {} responded with {}", delivery.url, response.status()),
+				Err(e) => debug!(target: "webhooks", "Unable to reach webhook {}: {:?}", delivery.url, e),
+			}
+
+			delivery.attempt += 1;
+			if delivery.attempt >= MAX_ATTEMPTS {
+				warn!(target: "webhooks", "Dropping notification for {} after {} failed attempts", delivery.url, MAX_ATTEMPTS);
+				continue;
+			}
+			delivery.due = Instant::now() + backoff(delivery.attempt - 1);
+			pending.push(delivery);
+		}
+	}
+
+	if !pending.is_empty() {
+		warn!(target: "webhooks", "{} webhook notification(s) not delivered before shutdown", pending.len());
+	}
+}
+
+pub fn start(conf: Configuration, client: Arc<Client>, fetch: FetchClient) -> Result<Option<Arc<Webhooks>>, String> {
+	if conf.urls.is_empty() || conf.addresses.is_empty() {
+		return Ok(None);
+	}
+
+	let notifier = Notifier {
+		client: client,
+		addresses: conf.addresses.into_iter().collect(),
+		urls: conf.urls,
+		signing_key: conf.signing_key,
+	};
+	let (changes, receiver) = mpsc::channel();
+	let thread = thread::Builder::new().name("webhooks".into())
+		.spawn(move || deliver(notifier, fetch, receiver))
+		.map_err(|e| format!("Unable to start webhook notifications: {}", e))?;
+
+	let webhooks = Arc::new(Webhooks {
+		changes: Mutex::new(Some(changes)),
+		thread: Mutex::new(Some(thread)),
+	});
+	Ok(Some(webhooks))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+	use std::time::Duration;
+	use ethcore::log_entry::LogEntry;
+	use ethcore::receipt::Receipt;
+	use ethcore::transaction::{Action, Transaction, SignedTransaction};
+	use ethcore::ethstore::ethkey::{Generator, Random};
+	use serde_json::Value;
+	use util::{Address, H256};
+	use super::{block_events, backoff};
+
+	fn field<'a>(event: &'a Value, name: &str) -> &'a Value {
+		event.as_object().and_then(|event| event.get(name)).expect("event has the field")
+	}
+
+	fn transaction(action: Action) -> SignedTransaction {
+		let keypair = Random.generate().unwrap();
+		Transaction {
+			action: action,
+			value: 0x10.into(),
+			data: Vec::new(),
+			gas: 21_000.into(),
+			gas_price: 0.into(),
+			nonce: 0.into(),
+		}.sign(keypair.secret(), None)
+	}
+
+	#[test]
+	fn finds_watched_transactions_and_logs() {
+		let watched = Address::from(0x42);
+		let addresses: HashSet<_> = vec![watched].into_iter().collect();
+
+		let mut topic = H256::default();
+		topic[12..].copy_from_slice(&watched);
+		let transfer = LogEntry { address: Address::from(1), topics: vec![H256::from(7), topic], data: vec![] };
+		let unrelated = LogEntry { address: Address::from(1), topics: vec![H256::from(7)], data: vec![] };
+
+		let transactions = vec![transaction(Action::Call(watched)), transaction(Action::Call(Address::from(1)))];
+		let receipts = vec![
+			Receipt::new(None, 21_000.into(), vec![]),
+			Receipt::new(None, 42_000.into(), vec![unrelated, transfer]),
+		];
+
+		let events = block_events(&addresses, &transactions, &receipts);
+		assert_eq!(events.len(), 2);
+		assert_eq!(field(&events[0], "type"), &Value::String("transaction".into()));
+		assert_eq!(field(&events[0], "incoming"), &Value::Bool(true));
+		assert_eq!(field(&events[0], "outgoing"), &Value::Bool(false));
+		assert_eq!(field(&events[1], "type"), &Value::String("log".into()));
+		assert_eq!(field(&events[1], "transactionIndex"), &Value::from(1u64));
+		assert_eq!(field(&events[1], "logIndex"), &Value::from(1u64));
+	}
+
+	#[test]
+	fn ignores_unrelated_blocks() {
+		let addresses: HashSet<_> = vec![Address::from(0x42)].into_iter().collect();
+		let transactions = vec![transaction(Action::Create)];
+		let receipts = vec![Receipt::new(None, 21_000.into(), vec![])];
+
+		assert!(block_events(&addresses, &transactions, &receipts).is_empty());
+	}
+
+	#[test]
+	fn backs_off_exponentially() {
+		assert_eq!(backoff(0), Duration::from_secs(2));
+		assert_eq!(backoff(1), Duration::from_secs(4));
+		assert_eq!(backoff(3), Duration::from_secs(16));
+	}
+}
@@ -0,0 +1,313 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HTTP transport with connection, keep-alive and header size limits and `Host` validation.
+
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::thread;
+
+use jsonrpc_core;
+use http::{self, hyper, HttpMetaExtractor, AccessControlAllowOrigin};
+use http::hyper::{header, server, Decoder, Encoder, Next};
+use http::hyper::net::HttpStream;
+use http::hyper::status::StatusCode;
+use http::tokio_core::reactor::Remote;
+use ethcore_logger::{AccessLog, AccessLogEntry};
+use util::hosts::AllowedHosts;
+
+/// Limits of the HTTP transport, protecting public endpoints against slow clients.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpLimits {
+	/// Maximal number of open connections (transport default if `None`).
+	pub max_connections: Option<usize>,
+	/// Time after which idle keep-alive connections are closed (keep-alive disabled if `None`).
+	pub keep_alive_timeout: Option<Duration>,
+	/// Maximal total size of request headers in bytes.
+	pub max_header_size: usize,
+}
+
+impl Default for HttpLimits {
+	fn default() -> Self {
+		HttpLimits {
+			max_connections: None,
+			keep_alive_timeout: Some(Duration::from_secs(10)),
+			max_header_size: 32 * 1024,
+		}
+	}
+}
+
+/// Running HTTP server, closed when dropped.
+pub struct HttpServer {
+	server: Option<server::Listening>,
+}
+
+impl HttpServer {
+	/// Addresses the server is listening on.
+	pub fn addrs(&self) -> &[SocketAddr] {
+		self.server.as_ref()
+			.expect("server is always Some at the start; it's consumed only when object is dropped; qed")
+			.addrs()
+	}
+}
+
+impl Drop for HttpServer {
+	fn drop(&mut self) {
+		self.server.take().expect("server is always Some until dropped; qed").close()
+	}
+}
+
+/// Start the HTTP server with given transport limits.
+///
+/// Requests with a `Host` header not matching `allowed_hosts` are answered with `403 Forbidden`.
+/// All answered requests, rejected ones included, are recorded in the access log if given.
+#[cfg_attr(feature="nightly", allow(too_many_arguments))]
+pub fn start<M, S, T>(
+	addr: &SocketAddr,
+	cors_domains: Option<Vec<AccessControlAllowOrigin>>,
+	allowed_hosts: AllowedHosts,
+	handler: jsonrpc_core::MetaIoHandler<M, S>,
+	remote: Remote,
+	extractor: T,
+	limits: HttpLimits,
+	access_log: Option<Arc<AccessLog>>,
+) -> Result<HttpServer, http::Error> where
+	M: jsonrpc_core::Metadata,
+	S: jsonrpc_core::Middleware<M>,
+	T: HttpMetaExtractor<M> + 'static,
+{
+	let handler = Arc::new(handler);
+	let extractor: Arc<HttpMetaExtractor<M>> = Arc::new(extractor);
+	let middleware = Arc::new(RequestValidation {
+		max_header_size: limits.max_header_size,
+		allowed_hosts: allowed_hosts,
+	});
+
+	let mut server = hyper::Server::http(addr).map_err(into_server_error)?
+		.keep_alive(limits.keep_alive_timeout.is_some());
+	if let Some(timeout) = limits.keep_alive_timeout {
+		server = server.idle_timeout(timeout);
+	}
+	if let Some(max) = limits.max_connections {
+		server = server.max_sockets(max);
+	}
+
+	server
+		.handle(move |control| AccessLogged {
+			handler: http::ServerHandler::new(
+				http::Rpc {
+					handler: handler.clone(),
+					remote: remote.clone(),
+					extractor: extractor.clone(),
+				},
+				cors_domains.clone(),
+				// `Host` header is validated by `RequestValidation`, so that wildcard patterns are supported.
+				None,
+				middleware.clone(),
+				control,
+			),
+			access_log: access_log.clone(),
+			entry: None,
+		})
+		.map(|(listening, server)| {
+			thread::spawn(move || server.run());
+			HttpServer {
+				server: Some(listening),
+			}
+		})
+		.map_err(into_server_error)
+}
+
+fn into_server_error(err: hyper::Error) -> http::Error {
+	match err {
+		hyper::Error::Io(err) => http::Error::IoError(err),
+		other => http::Error::Other(other),
+	}
+}
+
+/// Total size of header names and values in bytes.
+fn headers_size(headers: &header::Headers) -> usize {
+	headers.iter().map(|header| header.name().len() + header.value_string().len()).sum()
+}
+
+/// `Host` header of the request as `hostname[:port]`.
+fn request_host(headers: &header::Headers) -> Option<String> {
+	headers.get::<header::Host>().map(|host| match host.port {
+		Some(port) => format!("{}:{}", host.hostname, port),
+		None => host.hostname.clone(),
+	})
+}
+
+/// Answers requests with too large headers or a disallowed `Host` before their body is read or any RPC handling starts.
+struct RequestValidation {
+	max_header_size: usize,
+	allowed_hosts: AllowedHosts,
+}
+
+impl http::RequestMiddleware for RequestValidation {
+	fn on_request(&self, request: &server::Request<HttpStream>) -> http::RequestMiddlewareAction {
+		let size = headers_size(request.headers());
+		if size > self.max_header_size {
+			debug!(target: "rpc", "Rejecting HTTP request with {} bytes of headers (limit: {} bytes)", size, self.max_header_size);
+			return http::RequestMiddlewareAction::Respond {
+				should_validate_hosts: false,
+				handler: Box::new(Reject::headers_too_large()),
+			};
+		}
+
+		let host = request_host(request.headers());
+		if !self.allowed_hosts.is_allowed(host.as_ref().map(String::as_str)) {
+			debug!(target: "rpc", "Rejecting HTTP request with disallowed host: {:?}", host);
+			return http::RequestMiddlewareAction::Respond {
+				should_validate_hosts: false,
+				handler: Box::new(Reject::host_not_allowed()),
+			};
+		}
+
+		http::RequestMiddlewareAction::Proceed {
+			should_continue_on_invalid_cors: request.headers().get::<header::Origin>().is_none(),
+		}
+	}
+}
+
+/// Responds with given error status and a short plain text body, then closes the connection.
+struct Reject {
+	status: StatusCode,
+	body: &'static [u8],
+}
+
+impl Reject {
+	/// `431 Request Header Fields Too Large`
+	fn headers_too_large() -> Self {
+		Reject {
+			status: StatusCode::RequestHeaderFieldsTooLarge,
+			body: b"",
+		}
+	}
+
+	/// `403 Forbidden` for a `Host` header that is not whitelisted.
+	fn host_not_allowed() -> Self {
+		Reject {
+			status: StatusCode::Forbidden,
+			body: b"Provided Host header is not whitelisted.\n",
+		}
+	}
+}
+
+impl server::Handler<HttpStream> for Reject {
+	fn on_request(&mut self, _request: server::Request<HttpStream>) -> Next {
+		Next::write()
+	}
+
+	fn on_request_readable(&mut self, _decoder: &mut Decoder<HttpStream>) -> Next {
+		Next::write()
+	}
+
+	fn on_response(&mut self, res: &mut server::Response) -> Next {
+		res.set_status(self.status);
+		res.headers_mut().set(header::ContentLength(self.body.len() as u64));
+		res.headers_mut().set(header::Connection::close());
+		if self.body.is_empty() {
+			Next::end()
+		} else {
+			res.headers_mut().set(header::ContentType::plaintext());
+			Next::write()
+		}
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		match encoder.write(self.body) {
+			Ok(written) if written < self.body.len() => {
+				self.body = &self.body[written..];
+				Next::write()
+			},
+			_ => Next::end(),
+		}
+	}
+}
+
+/// Records the status and size of responses of the wrapped handler in the access log.
+struct AccessLogged<H> {
+	handler: H,
+	access_log: Option<Arc<AccessLog>>,
+	entry: Option<(Instant, AccessLogEntry)>,
+}
+
+impl<H: server::Handler<HttpStream>> server::Handler<HttpStream> for AccessLogged<H> {
+	fn on_request(&mut self, request: server::Request<HttpStream>) -> Next {
+		if let Some(ref log) = self.access_log {
+			self.entry = Some((Instant::now(), log.entry(&request)));
+		}
+		self.handler.on_request(request)
+	}
+
+	fn on_request_readable(&mut self, decoder: &mut Decoder<HttpStream>) -> Next {
+		self.handler.on_request_readable(decoder)
+	}
+
+	fn on_response(&mut self, res: &mut server::Response) -> Next {
+		let next = self.handler.on_response(res);
+		if let (Some(log), Some((started, mut entry))) = (self.access_log.as_ref(), self.entry.take()) {
+			entry.status = res.status().to_u16();
+			entry.bytes = res.headers().get::<header::ContentLength>().map_or(0, |length| length.0 as usize);
+			entry.duration = started.elapsed();
+			log.log(&entry);
+		}
+		next
+	}
+
+	fn on_response_writable(&mut self, encoder: &mut Encoder<HttpStream>) -> Next {
+		self.handler.on_response_writable(encoder)
+	}
+
+	fn on_error(&mut self, err: hyper::Error) -> Next {
+		self.handler.on_error(err)
+	}
+
+	fn on_remove(self, transport: HttpStream) {
+		self.handler.on_remove(transport)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use http::hyper::header::{Headers, Host, UserAgent};
+	use super::{headers_size, request_host};
+
+	#[test]
+	fn should_sum_header_names_and_values() {
+		let mut headers = Headers::new();
+		headers.set(Host { hostname: "localhost".into(), port: None });
+		headers.set(UserAgent("parity".into()));
+
+		// "Host" + "localhost" + "User-Agent" + "parity"
+		assert_eq!(headers_size(&headers), 4 + 9 + 10 + 6);
+	}
+
+	#[test]
+	fn should_read_request_host_with_port() {
+		let mut headers = Headers::new();
+		assert_eq!(request_host(&headers), None);
+
+		headers.set(Host { hostname: "localhost".into(), port: None });
+		assert_eq!(request_host(&headers), Some("localhost".into()));
+
+		headers.set(Host { hostname: "localhost".into(), port: Some(8545) });
+		assert_eq!(request_host(&headers), Some("localhost:8545".into()));
+	}
+}
@@ -0,0 +1,219 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! IPC transport with a bounded number of pending requests per connection.
+//!
+//! Every connection is served by its own task. Once a connection has the maximal number
+//! of requests being processed or waiting for their responses to be written, no more
+//! requests are read from it, so a client that stops reading its responses only stalls itself.
+
+use std::io;
+use std::str;
+use std::sync::Arc;
+use std::sync::mpsc;
+
+use futures::{future, Future, Stream, Sink};
+use futures::sync::oneshot;
+use jsonrpc_core;
+use parity_tokio_ipc::Endpoint;
+use http::tokio_core::io::{Codec, EasyBuf, Io};
+use http::tokio_core::reactor::Remote;
+
+/// Limits of the IPC transport.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IpcLimits {
+	/// Maximal number of requests of a single connection being processed or waiting to be written.
+	pub max_pending_requests: usize,
+}
+
+impl Default for IpcLimits {
+	fn default() -> Self {
+		IpcLimits {
+			max_pending_requests: 16,
+		}
+	}
+}
+
+/// IPC connection requests are received on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+	/// Identifier of the connection, unique for the server.
+	pub connection_id: u64,
+}
+
+/// Extracts metadata of requests received on an IPC connection.
+pub trait MetaExtractor<M: jsonrpc_core::Metadata>: Send + Sync + 'static {
+	/// Metadata of all requests received on given connection.
+	fn extract(&self, context: &RequestContext) -> M;
+}
+
+/// Running IPC server, stops accepting connections when dropped.
+pub struct IpcServer {
+	close: Option<oneshot::Sender<()>>,
+}
+
+impl Drop for IpcServer {
+	fn drop(&mut self) {
+		let _ = self.close.take().expect("close is always Some until dropped; qed").send(());
+	}
+}
+
+/// Start the IPC server at given path with given transport limits.
+pub fn start<M, S, T>(
+	path: &str,
+	handler: jsonrpc_core::MetaIoHandler<M, S>,
+	remote: Remote,
+	extractor: T,
+	limits: IpcLimits,
+) -> io::Result<IpcServer> where
+	M: jsonrpc_core::Metadata,
+	S: jsonrpc_core::Middleware<M>,
+	T: MetaExtractor<M>,
+{
+	let handler = Arc::new(handler);
+	let path = path.to_owned();
+	let max_pending = ::std::cmp::max(limits.max_pending_requests, 1);
+	let (started_tx, started_rx) = mpsc::channel();
+	let (close, closed) = oneshot::channel::<()>();
+
+	remote.spawn(move |handle| -> Box<Future<Item = (), Error = ()>> {
+		let endpoint = match Endpoint::new(path, handle) {
+			Ok(endpoint) => endpoint,
+			Err(err) => {
+				let _ = started_tx.send(Err(err));
+				return Box::new(future::ok(()));
+			},
+		};
+		let _ = started_tx.send(Ok(()));
+
+		let handle = handle.clone();
+		let mut connection_id = 0u64;
+		let server = endpoint.incoming().for_each(move |(stream, _)| {
+			connection_id += 1;
+			trace!(target: "rpc", "Accepted IPC connection {}", connection_id);
+
+			let meta = extractor.extract(&RequestContext { connection_id: connection_id });
+			let handler = handler.clone();
+			let (writer, reader) = stream.framed(StreamCodec).split();
+			let responses = reader
+				.map(move |request| handler.handle_request(&request, meta.clone())
+					.map_err(|_| io::Error::new(io::ErrorKind::Other, "IPC request handler failed")))
+				.buffer_unordered(max_pending)
+				.filter_map(|response| response);
+
+			let id = connection_id;
+			handle.spawn(writer.send_all(responses).then(move |result| {
+				match result {
+					Ok(_) => trace!(target: "rpc", "IPC connection {} closed", id),
+					Err(err) => debug!(target: "rpc", "IPC connection {} closed: {}", id, err),
+				}
+				Ok(())
+			}));
+			Ok(())
+		});
+
+		Box::new(server
+			.map_err(|err| warn!(target: "rpc", "IPC server stopped accepting connections: {}", err))
+			.select(closed.then(|_| Ok(())))
+			.then(|_| Ok(())))
+	});
+
+	match started_rx.recv() {
+		Ok(Ok(())) => Ok(IpcServer { close: Some(close) }),
+		Ok(Err(err)) => Err(err),
+		Err(_) => Err(io::Error::new(io::ErrorKind::Other, "IPC event loop stopped before the server started")),
+	}
+}
+
+/// Splits incoming data into requests at the end of every complete JSON object or array.
+/// Responses are terminated with a newline.
+struct StreamCodec;
+
+impl Codec for StreamCodec {
+	type In = String;
+	type Out = String;
+
+	fn decode(&mut self, buf: &mut EasyBuf) -> io::Result<Option<String>> {
+		let end = match request_end(buf.as_slice()) {
+			Some(end) => end,
+			None => return Ok(None),
+		};
+
+		let request = buf.drain_to(end);
+		str::from_utf8(request.as_slice())
+			.map(|request| Some(request.trim().to_owned()))
+			.map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "IPC request is not valid UTF-8"))
+	}
+
+	fn encode(&mut self, response: String, buf: &mut Vec<u8>) -> io::Result<()> {
+		buf.extend_from_slice(response.as_bytes());
+		buf.push(b'\n');
+		Ok(())
+	}
+}
+
+/// Length of the first complete JSON object or array in `buf`, including any data preceding it.
+fn request_end(buf: &[u8]) -> Option<usize> {
+	let mut depth = 0usize;
+	let mut in_string = false;
+	let mut escaped = false;
+
+	for (idx, byte) in buf.iter().enumerate() {
+		if in_string {
+			match *byte {
+				_ if escaped => escaped = false,
+				b'\\' => escaped = true,
+				b'"' => in_string = false,
+				_ => {},
+			}
+			continue;
+		}
+
+		match *byte {
+			b'{' | b'[' => depth += 1,
+			b'}' | b']' if depth > 0 => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(idx + 1);
+				}
+			},
+			b'"' if depth > 0 => in_string = true,
+			_ => {},
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::request_end;
+
+	#[test]
+	fn should_find_end_of_complete_requests() {
+		assert_eq!(request_end(b""), None);
+		assert_eq!(request_end(br#"{"id":1"#), None);
+		assert_eq!(request_end(br#"{"id":1}"#), Some(8));
+		assert_eq!(request_end(br#" [{"id":1},{"id":2}]{"id":3}"#), Some(20));
+		assert_eq!(request_end(b"{\"id\":1}\n{\"id\":2}"), Some(8));
+	}
+
+	#[test]
+	fn should_ignore_brackets_in_strings() {
+		assert_eq!(request_end(br#"{"params":["}"]"#), None);
+		assert_eq!(request_end(br#"{"params":["\"}"]}"#), Some(18));
+	}
+}
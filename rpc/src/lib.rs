@@ -30,8 +30,8 @@ extern crate transient_hashmap;
 
 extern crate jsonrpc_core;
 pub extern crate jsonrpc_http_server as http;
-pub extern crate jsonrpc_ipc_server as ipc;
 
+extern crate ethabi;
 extern crate ethash;
 extern crate ethcore;
 extern crate ethcore_io as io;
@@ -42,6 +42,7 @@ extern crate ethkey;
 extern crate ethstore;
 extern crate ethsync;
 extern crate ethcore_logger;
+extern crate parity_tokio_ipc;
 extern crate fetch;
 extern crate parity_reactor;
 extern crate parity_updater as updater;
@@ -62,53 +63,57 @@ extern crate ethjson;
 #[cfg(test)]
 extern crate ethcore_devtools as devtools;
 
+mod http_server;
+mod ipc_server;
 pub mod v1;
 
-pub use ipc::{Server as IpcServer, MetaExtractor as IpcMetaExtractor, RequestContext as IpcRequestContext};
-pub use http::{HttpMetaExtractor, Server as HttpServer, Error as HttpServerError, AccessControlAllowOrigin, Host};
+pub use http_server::{HttpServer, HttpLimits};
+pub use ipc_server::{IpcServer, IpcLimits, MetaExtractor as IpcMetaExtractor, RequestContext as IpcRequestContext};
+pub use http::{HttpMetaExtractor, Error as HttpServerError, AccessControlAllowOrigin, Host};
 
-pub use v1::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, Metadata, Origin, informant, dispatch};
+pub use v1::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, Metadata, Sink, Origin, abi_registry, informant, dispatch, call_cache, missing_cache, head_lag, request_budget, response_limits, signing_audit, sync_events, transaction_events, trusted_fallback, work_auth, work_tracker};
 pub use v1::block_import::is_major_importing;
 
 use std::net::SocketAddr;
+use std::sync::Arc;
 use http::tokio_core;
 
 /// Start http server asynchronously and returns result with `Server` handle on success or an error.
+///
+/// Connection count, keep-alive and request header size are limited by the transport.
+#[cfg_attr(feature="nightly", allow(too_many_arguments))]
 pub fn start_http<M, S, H, T>(
 	addr: &SocketAddr,
 	cors_domains: http::DomainsValidation<http::AccessControlAllowOrigin>,
-	allowed_hosts: http::DomainsValidation<http::Host>,
+	allowed_hosts: util::hosts::AllowedHosts,
 	handler: H,
 	remote: tokio_core::reactor::Remote,
 	extractor: T,
+	limits: HttpLimits,
+	access_log: Option<Arc<ethcore_logger::AccessLog>>,
 ) -> Result<HttpServer, HttpServerError> where
 	M: jsonrpc_core::Metadata,
 	S: jsonrpc_core::Middleware<M>,
 	H: Into<jsonrpc_core::MetaIoHandler<M, S>>,
-	T: HttpMetaExtractor<M>,
+	T: HttpMetaExtractor<M> + 'static,
 {
-	http::ServerBuilder::new(handler)
-		.event_loop_remote(remote)
-		.meta_extractor(extractor)
-		.cors(cors_domains.into())
-		.allowed_hosts(allowed_hosts.into())
-		.start_http(addr)
+	http_server::start(addr, cors_domains.into(), allowed_hosts, handler.into(), remote, extractor, limits, access_log)
 }
 
 /// Start ipc server asynchronously and returns result with `Server` handle on success or an error.
+///
+/// Requests pending on a single connection are limited by the transport.
 pub fn start_ipc<M, S, H, T>(
 	addr: &str,
 	handler: H,
 	remote: tokio_core::reactor::Remote,
 	extractor: T,
-) -> ::std::io::Result<ipc::Server> where
+	limits: IpcLimits,
+) -> ::std::io::Result<IpcServer> where
 	M: jsonrpc_core::Metadata,
 	S: jsonrpc_core::Middleware<M>,
 	H: Into<jsonrpc_core::MetaIoHandler<M, S>>,
 	T: IpcMetaExtractor<M>,
 {
-	ipc::ServerBuilder::new(handler)
-		.event_loop_remote(remote)
-		.session_metadata_extractor(extractor)
-		.start(addr)
+	ipc_server::start(addr, handler.into(), remote, extractor, limits)
 }
@@ -0,0 +1,407 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Registry of contract ABIs used to decode event logs.
+//!
+//! ABIs are read from JSON files in a directory. A file named after an address
+//! (`0x<address>.json`) describes the contract at that address; any other file is named
+//! after an entry of the on-chain registry (e.g. `githubhint.json`) and describes
+//! the contract registered under that name. ABIs of other contracts are fetched
+//! from an `AbiSource`, e.g. the on-chain registry.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ethabi::{Interface, Contract, Token};
+use rustc_serialize::hex::ToHex;
+use serde_json::{self, Value};
+use util::{Address, H256, Hashable, RwLock};
+
+/// Maximal number of contracts with ABIs fetched from the source.
+const MAX_FETCHED: usize = 1024;
+
+/// Entry of a contract's ABI.
+#[derive(Deserialize)]
+struct AbiItem {
+	#[serde(rename="type")]
+	kind: String,
+	#[serde(default)]
+	name: String,
+	#[serde(default)]
+	inputs: Vec<AbiInput>,
+	#[serde(default)]
+	anonymous: bool,
+}
+
+#[derive(Deserialize)]
+struct AbiInput {
+	#[serde(rename="type")]
+	kind: String,
+}
+
+/// Event of a contract's ABI.
+struct EventAbi {
+	name: String,
+	signature: String,
+	/// Contract with just this event, so that overloaded events decode with their own definition.
+	contract: Contract,
+}
+
+struct Abi {
+	/// Events by the topic identifying them.
+	events: HashMap<H256, EventAbi>,
+}
+
+impl Abi {
+	fn load(json: &[u8]) -> Result<Self, String> {
+		Interface::load(json).map_err(|e| format!("Invalid ABI: {:?}", e))?;
+		let items: Vec<Value> = serde_json::from_slice(json).map_err(|e| format!("Invalid ABI: {}", e))?;
+
+		let mut events = HashMap::new();
+		for value in items {
+			let item: AbiItem = serde_json::from_value(value.clone()).map_err(|e| format!("Invalid ABI: {}", e))?;
+			if item.kind != "event" || item.anonymous {
+				continue;
+			}
+
+			let types: Vec<_> = item.inputs.into_iter().map(|input| input.kind).collect();
+			let signature = format!("{}({})", item.name, types.join(","));
+			let json = serde_json::to_vec(&vec![value]).map_err(|e| format!("Invalid ABI: {}", e))?;
+			let interface = Interface::load(&json).map_err(|e| format!("Invalid ABI: {:?}", e))?;
+			events.insert(signature.as_bytes().sha3(), EventAbi {
+				name: item.name,
+				signature: signature,
+				contract: Contract::new(interface),
+			});
+		}
+
+		Ok(Abi {
+			events: events,
+		})
+	}
+
+	fn decode(&self, topics: &[H256], data: &[u8]) -> Option<DecodedEvent> {
+		let abi = match topics.first().and_then(|topic| self.events.get(topic)) {
+			Some(abi) => abi,
+			None => return None,
+		};
+		let event = match abi.contract.event(abi.name.clone()) {
+			Ok(event) => event,
+			Err(_) => return None,
+		};
+		let topics = topics.iter().map(|topic| topic.0).collect();
+		match event.decode_log(topics, data.to_vec()) {
+			Ok(log) => Some(DecodedEvent {
+				name: abi.name.clone(),
+				signature: abi.signature.clone(),
+				params: log.params.into_iter().map(|param| (param.name, token_to_value(param.value))).collect(),
+			}),
+			Err(e) => {
+				trace!(target: "rpc", "Couldn't decode {} log: {:?}", abi.signature, e);
+				None
+			}
+		}
+	}
+}
+
+/// Source of ABIs of contracts not described by files.
+pub trait AbiSource: Send + Sync {
+	/// Fetch the ABI JSON of the contract at given address, calling `on_done` with the result.
+	fn fetch(&self, address: &Address, on_done: Box<Fn(Result<Vec<u8>, String>) + Send>);
+}
+
+/// State of an ABI requested from the source.
+enum Fetched {
+	Pending,
+	Failed,
+	Done(Abi),
+}
+
+/// Event decoded from a log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+	/// Event name.
+	pub name: String,
+	/// Event signature, e.g. `Transfer(address,address,uint256)`.
+	pub signature: String,
+	/// Names and values of the event parameters, in order.
+	pub params: Vec<(String, Value)>,
+}
+
+/// Known contract ABIs.
+#[derive(Default)]
+pub struct AbiRegistry {
+	by_address: HashMap<Address, Abi>,
+	by_name: HashMap<String, Abi>,
+	/// Registry names resolved to addresses, with the best block they were resolved at.
+	resolved: RwLock<(H256, HashMap<Address, String>)>,
+	source: Option<Arc<AbiSource>>,
+	/// ABIs requested from the source, failed requests are retried after the best block changes.
+	fetched: Arc<RwLock<HashMap<Address, Fetched>>>,
+}
+
+impl AbiRegistry {
+	/// Load all `.json` files in given directory.
+	pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+		let dir = dir.as_ref();
+		let entries = fs::read_dir(dir).map_err(|e| format!("Cannot read ABI directory {}: {}", dir.display(), e))?;
+
+		let mut registry = AbiRegistry::default();
+		for entry in entries.filter_map(Result::ok) {
+			let path = entry.path();
+			if path.extension().map_or(true, |ext| ext != "json") {
+				continue;
+			}
+			let stem = match path.file_stem().and_then(|stem| stem.to_str()) {
+				Some(stem) => stem.to_owned(),
+				None => continue,
+			};
+
+			let mut json = Vec::new();
+			File::open(&path).and_then(|mut file| file.read_to_end(&mut json))
+				.map_err(|e| format!("Cannot read ABI file {}: {}", path.display(), e))?;
+			registry.insert(&stem, &json).map_err(|e| format!("{}: {}", path.display(), e))?;
+		}
+
+		info!(target: "rpc", "Loaded {} contract ABIs from {}", registry.by_address.len() + registry.by_name.len(), dir.display());
+		Ok(registry)
+	}
+
+	/// Add the ABI of a contract given by its address or registry name.
+	pub fn insert(&mut self, key: &str, json: &[u8]) -> Result<(), String> {
+		let abi = Abi::load(json)?;
+		let address = match key.starts_with("0x") {
+			true => &key[2..],
+			false => key,
+		};
+
+		match address.len() == 40 {
+			true => { self.by_address.insert(Address::from_str(address).map_err(|e| format!("Invalid address: {:?}", e))?, abi); },
+			false => { self.by_name.insert(key.to_owned(), abi); },
+		}
+		Ok(())
+	}
+
+	/// Fetch ABIs of contracts unknown otherwise from given source.
+	pub fn set_source(&mut self, source: Arc<AbiSource>) {
+		self.source = Some(source);
+	}
+
+	/// Resolve the registry names of ABIs to addresses, unless already done at current best block `head`.
+	pub fn refresh<F>(&self, head: &H256, resolve: F) where F: Fn(&str) -> Option<Address> {
+		if &self.resolved.read().0 == head {
+			return;
+		}
+
+		{
+			let mut fetched = self.fetched.write();
+			let failed: Vec<_> = fetched.iter()
+				.filter(|&(_, entry)| match *entry { Fetched::Failed => true, _ => false })
+				.map(|(address, _)| address.clone())
+				.collect();
+			for address in failed {
+				fetched.remove(&address);
+			}
+		}
+
+		let resolved = self.by_name.keys()
+			.filter_map(|name| resolve(name).map(|address| (address, name.clone())))
+			.collect();
+		*self.resolved.write() = (head.clone(), resolved);
+	}
+
+	/// Decode a log of the contract at given address.
+	pub fn decode(&self, address: &Address, topics: &[H256], data: &[u8]) -> Option<DecodedEvent> {
+		if let Some(abi) = self.by_address.get(address) {
+			return abi.decode(topics, data);
+		}
+
+		if let Some(name) = self.resolved.read().1.get(address) {
+			return self.by_name.get(name).and_then(|abi| abi.decode(topics, data));
+		}
+
+		self.decode_fetched(address, topics, data)
+	}
+
+	/// Decode a log with the ABI fetched from the source, requesting the ABI if not done yet.
+	fn decode_fetched(&self, address: &Address, topics: &[H256], data: &[u8]) -> Option<DecodedEvent> {
+		let source = match self.source {
+			Some(ref source) => source,
+			None => return None,
+		};
+
+		{
+			let mut fetched = self.fetched.write();
+			match fetched.get(address) {
+				Some(&Fetched::Done(ref abi)) => return abi.decode(topics, data),
+				Some(_) => return None,
+				None if fetched.len() >= MAX_FETCHED => return None,
+				None => {},
+			}
+			fetched.insert(address.clone(), Fetched::Pending);
+		}
+
+		let fetched = self.fetched.clone();
+		let contract = address.clone();
+		source.fetch(address, Box::new(move |result| {
+			let entry = match result.and_then(|json| Abi::load(&json)) {
+				Ok(abi) => Fetched::Done(abi),
+				Err(e) => {
+					debug!(target: "rpc", "Couldn't fetch the ABI of {:?}: {}", contract, e);
+					Fetched::Failed
+				},
+			};
+			fetched.write().insert(contract.clone(), entry);
+		}));
+		None
+	}
+}
+
+fn token_to_value(token: Token) -> Value {
+	match token {
+		Token::Address(address) => Value::String(format!("0x{}", address.to_hex())),
+		Token::FixedBytes(bytes) | Token::Bytes(bytes) => Value::String(format!("0x{}", bytes.to_hex())),
+		// two's complement for signed integers.
+		Token::Int(word) | Token::Uint(word) => Value::String(format!("0x{}", word.to_hex())),
+		Token::Bool(b) => Value::Bool(b),
+		Token::String(s) => Value::String(s),
+		Token::FixedArray(tokens) | Token::Array(tokens) => Value::Array(tokens.into_iter().map(token_to_value).collect()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use rustc_serialize::hex::ToHex;
+	use serde_json::Value;
+	use util::{Address, H256, Hashable, Mutex};
+	use super::{AbiRegistry, AbiSource};
+
+	const TOKEN_ABI: &'static [u8] = br#"[
+		{"anonymous":false,"inputs":[{"indexed":true,"name":"from","type":"address"},{"indexed":true,"name":"to","type":"address"},{"indexed":false,"name":"value","type":"uint256"}],"name":"Transfer","type":"event"},
+		{"constant":true,"inputs":[],"name":"totalSupply","outputs":[{"name":"","type":"uint256"}],"payable":false,"type":"function"}
+	]"#;
+
+	fn transfer_log() -> (Vec<H256>, Vec<u8>) {
+		let topics = vec![
+			"Transfer(address,address,uint256)".as_bytes().sha3(),
+			H256::from(Address::from(1)),
+			H256::from(Address::from(2)),
+		];
+		(topics, H256::from(5).to_vec())
+	}
+
+	#[test]
+	fn decodes_logs_of_contracts_by_address() {
+		let mut registry = AbiRegistry::default();
+		registry.insert("0x0000000000000000000000000000000000000010", TOKEN_ABI).unwrap();
+		let (topics, data) = transfer_log();
+
+		let event = registry.decode(&Address::from(0x10), &topics, &data).unwrap();
+		assert_eq!(event.name, "Transfer");
+		assert_eq!(event.signature, "Transfer(address,address,uint256)");
+		assert_eq!(event.params, vec![
+			("from".to_owned(), Value::String("0x0000000000000000000000000000000000000001".into())),
+			("to".to_owned(), Value::String("0x0000000000000000000000000000000000000002".into())),
+			("value".to_owned(), Value::String(format!("0x{}", H256::from(5).to_hex()))),
+		]);
+
+		assert!(registry.decode(&Address::from(0x11), &topics, &data).is_none());
+		assert!(registry.decode(&Address::from(0x10), &topics[1..], &data).is_none());
+	}
+
+	#[test]
+	fn resolves_contracts_by_registry_name() {
+		let mut registry = AbiRegistry::default();
+		registry.insert("token", TOKEN_ABI).unwrap();
+		let (topics, data) = transfer_log();
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+
+		registry.refresh(&H256::from(1), |name| match name {
+			"token" => Some(Address::from(0x10)),
+			_ => None,
+		});
+		assert_eq!(registry.decode(&Address::from(0x10), &topics, &data).unwrap().name, "Transfer");
+
+		// kept until the best block changes.
+		registry.refresh(&H256::from(1), |_| None);
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_some());
+		registry.refresh(&H256::from(2), |_| None);
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+	}
+
+	#[test]
+	fn decodes_overloaded_events_by_signature() {
+		let mut registry = AbiRegistry::default();
+		registry.insert("0x0000000000000000000000000000000000000010", br#"[
+			{"anonymous":false,"inputs":[{"indexed":true,"name":"from","type":"address"},{"indexed":true,"name":"to","type":"address"},{"indexed":false,"name":"value","type":"uint256"}],"name":"Transfer","type":"event"},
+			{"anonymous":false,"inputs":[{"indexed":false,"name":"approved","type":"bool"}],"name":"Transfer","type":"event"}
+		]"#).unwrap();
+
+		let topics = vec!["Transfer(bool)".as_bytes().sha3()];
+		let event = registry.decode(&Address::from(0x10), &topics, &H256::from(1).to_vec()).unwrap();
+		assert_eq!(event.signature, "Transfer(bool)");
+		assert_eq!(event.params, vec![("approved".to_owned(), Value::Bool(true))]);
+
+		let (topics, data) = transfer_log();
+		assert_eq!(registry.decode(&Address::from(0x10), &topics, &data).unwrap().params.len(), 3);
+	}
+
+	struct TestSource {
+		requests: Mutex<Vec<Address>>,
+		abi: Option<&'static [u8]>,
+	}
+
+	impl AbiSource for TestSource {
+		fn fetch(&self, address: &Address, on_done: Box<Fn(Result<Vec<u8>, String>) + Send>) {
+			self.requests.lock().push(address.clone());
+			on_done(self.abi.map(|abi| abi.to_vec()).ok_or_else(|| "Not registered".to_owned()));
+		}
+	}
+
+	#[test]
+	fn fetches_unknown_abis_from_source() {
+		let source = Arc::new(TestSource { requests: Mutex::new(Vec::new()), abi: Some(TOKEN_ABI) });
+		let mut registry = AbiRegistry::default();
+		registry.set_source(source.clone());
+		let (topics, data) = transfer_log();
+
+		// the first log triggers the request.
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+		assert_eq!(registry.decode(&Address::from(0x10), &topics, &data).unwrap().name, "Transfer");
+		assert_eq!(*source.requests.lock(), vec![Address::from(0x10)]);
+	}
+
+	#[test]
+	fn retries_failed_fetches_at_new_best_block() {
+		let source = Arc::new(TestSource { requests: Mutex::new(Vec::new()), abi: None });
+		let mut registry = AbiRegistry::default();
+		registry.set_source(source.clone());
+		let (topics, data) = transfer_log();
+
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+		assert_eq!(source.requests.lock().len(), 1);
+
+		registry.refresh(&H256::from(1), |_| None);
+		assert!(registry.decode(&Address::from(0x10), &topics, &data).is_none());
+		assert_eq!(source.requests.lock().len(), 2);
+	}
+}
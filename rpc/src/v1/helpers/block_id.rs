@@ -0,0 +1,133 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resolution of RPC block identifiers to blocks of the local chain.
+//!
+//! All endpoints taking a block identifier resolve it here, so they agree on which
+//! blocks are unknown and which have their state pruned.
+
+use ethcore::client::{BlockChainClient, BlockId};
+use ethcore::encoded;
+use jsonrpc_core::Error;
+
+use v1::helpers::errors;
+use v1::types::BlockNumber;
+
+/// Block an RPC block identifier refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedBlock {
+	/// The pending block, whose state is kept by the miner.
+	Pending,
+	/// A block of the chain.
+	Block {
+		/// Identifier the block was requested by.
+		id: BlockId,
+		/// Header of the block.
+		header: encoded::Header,
+	},
+}
+
+impl ResolvedBlock {
+	/// Identifier to query the block's state with.
+	pub fn id(&self) -> BlockId {
+		match *self {
+			ResolvedBlock::Pending => BlockId::Pending,
+			ResolvedBlock::Block { ref id, .. } => id.clone(),
+		}
+	}
+}
+
+/// Resolves RPC block identifiers against a client.
+pub struct BlockIdResolver<'a, C: 'a + ?Sized> {
+	client: &'a C,
+}
+
+impl<'a, C: 'a + ?Sized + BlockChainClient> BlockIdResolver<'a, C> {
+	/// Create a resolver for given client.
+	pub fn new(client: &'a C) -> Self {
+		BlockIdResolver {
+			client: client,
+		}
+	}
+
+	/// Resolve the block, failing if it isn't known.
+	pub fn resolve(&self, number: BlockNumber) -> Result<ResolvedBlock, Error> {
+		match number {
+			BlockNumber::Pending => Ok(ResolvedBlock::Pending),
+			number => {
+				let id: BlockId = number.into();
+				self.client.block_header(id.clone())
+					.map(|header| ResolvedBlock::Block { id: id, header: header })
+					.ok_or_else(errors::unknown_block)
+			},
+		}
+	}
+
+	/// Resolve the block, failing if it isn't known or its state was pruned.
+	pub fn resolve_state(&self, number: BlockNumber) -> Result<ResolvedBlock, Error> {
+		let block = self.resolve(number)?;
+		if let ResolvedBlock::Block { ref header, .. } = block {
+			if header.number() < self.client.pruning_info().earliest_state {
+				return Err(errors::state_pruned());
+			}
+		}
+		Ok(block)
+	}
+
+	/// Resolve a block of the chain, failing for the pending block.
+	pub fn resolve_chain(&self, number: BlockNumber) -> Result<encoded::Header, Error> {
+		match self.resolve(number)? {
+			ResolvedBlock::Block { header, .. } => Ok(header),
+			ResolvedBlock::Pending => Err(errors::invalid_params("blockNumber", "The pending block isn't supported here.")),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockChainClient, BlockId};
+	use v1::types::BlockNumber;
+	use super::{BlockIdResolver, ResolvedBlock};
+
+	#[test]
+	fn resolves_blocks_by_number_and_hash() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let hash = client.block_hash(BlockId::Number(5)).unwrap();
+		let resolver = BlockIdResolver::new(&client);
+
+		assert_eq!(resolver.resolve_chain(BlockNumber::Num(5)).unwrap().hash(), hash);
+		assert_eq!(resolver.resolve(BlockNumber::Hash(hash)).unwrap().id(), BlockId::Hash(hash));
+		assert_eq!(resolver.resolve(BlockNumber::Latest).unwrap().id(), BlockId::Latest);
+		assert_eq!(resolver.resolve(BlockNumber::Pending).unwrap(), ResolvedBlock::Pending);
+		assert_eq!(resolver.resolve_chain(BlockNumber::Latest).unwrap().number(), 10);
+		assert!(resolver.resolve_chain(BlockNumber::Pending).is_err());
+		assert!(resolver.resolve(BlockNumber::Num(11)).is_err());
+		assert!(resolver.resolve(BlockNumber::Hash(5.into())).is_err());
+	}
+
+	#[test]
+	fn rejects_blocks_with_pruned_state() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		client.set_history(Some(4));
+		let resolver = BlockIdResolver::new(&client);
+
+		assert!(resolver.resolve_state(BlockNumber::Num(6)).is_ok());
+		assert_eq!(resolver.resolve_state(BlockNumber::Num(5)).unwrap_err(), ::v1::helpers::errors::state_pruned());
+		assert!(resolver.resolve(BlockNumber::Num(5)).is_ok());
+	}
+}
@@ -0,0 +1,228 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Short-lived cache of `eth_call` and `eth_estimateGas` results.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use ethcore::transaction::SignedTransaction;
+use rlp::RlpStream;
+use util::{H256, U256, Bytes, Mutex};
+use util::sha3::Hashable;
+
+/// Maximal number of cached results.
+const MAX_ENTRIES: usize = 4096;
+
+/// Kind of cached request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CallKind {
+	/// `eth_call`
+	Call,
+	/// `eth_estimateGas`
+	EstimateGas,
+}
+
+/// Cached result.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallResult {
+	/// Output of `eth_call`.
+	Output(Bytes),
+	/// Result of `eth_estimateGas`.
+	Gas(U256),
+}
+
+impl CallResult {
+	fn size(&self) -> usize {
+		match *self {
+			CallResult::Output(ref output) => output.len(),
+			CallResult::Gas(_) => 32,
+		}
+	}
+}
+
+/// Cache usage statistics.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CallCacheStats {
+	/// Requests served from cache.
+	pub hits: usize,
+	/// Requests not found in cache.
+	pub misses: usize,
+	/// Results not cached because they exceeded the entry size limit.
+	pub oversized: usize,
+	/// Number of times the cache was cleared because of a new head.
+	pub invalidations: usize,
+	/// Currently cached results.
+	pub entries: usize,
+	/// Bytes used by currently cached results.
+	pub size: usize,
+}
+
+struct Entries {
+	head: H256,
+	results: HashMap<(H256, CallKind, H256), (Instant, CallResult)>,
+}
+
+/// Cache of call results keyed by block hash and request hash.
+/// All entries are dropped whenever the best block changes.
+pub struct CallCache {
+	ttl: Duration,
+	max_entry_size: usize,
+	entries: Mutex<Entries>,
+	hits: AtomicUsize,
+	misses: AtomicUsize,
+	oversized: AtomicUsize,
+	invalidations: AtomicUsize,
+}
+
+impl CallCache {
+	/// Create new cache. Results live for at most `ttl`; results bigger than `max_entry_size` bytes are not cached.
+	pub fn new(ttl: Duration, max_entry_size: usize) -> Self {
+		CallCache {
+			ttl: ttl,
+			max_entry_size: max_entry_size,
+			entries: Mutex::new(Entries {
+				head: H256::default(),
+				results: HashMap::new(),
+			}),
+			hits: AtomicUsize::new(0),
+			misses: AtomicUsize::new(0),
+			oversized: AtomicUsize::new(0),
+			invalidations: AtomicUsize::new(0),
+		}
+	}
+
+	/// Get result of `request` executed at `block`, given current best block `head`.
+	pub fn get(&self, head: &H256, block: &H256, kind: CallKind, request: &H256) -> Option<CallResult> {
+		let mut entries = self.entries.lock();
+		self.check_head(&mut entries, head);
+
+		let key = (block.clone(), kind, request.clone());
+		let result = match entries.results.get(&key) {
+			Some(&(ref at, ref result)) if at.elapsed() < self.ttl => Some(result.clone()),
+			_ => None,
+		};
+
+		match result {
+			Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+			None => {
+				entries.results.remove(&key);
+				self.misses.fetch_add(1, Ordering::Relaxed)
+			},
+		};
+		result
+	}
+
+	/// Store result of `request` executed at `block`, given current best block `head`.
+	pub fn insert(&self, head: &H256, block: &H256, kind: CallKind, request: &H256, result: CallResult) {
+		if result.size() > self.max_entry_size {
+			self.oversized.fetch_add(1, Ordering::Relaxed);
+			return;
+		}
+
+		let mut entries = self.entries.lock();
+		self.check_head(&mut entries, head);
+
+		if entries.results.len() >= MAX_ENTRIES {
+			let ttl = self.ttl;
+			entries.results.retain(|_, &mut (ref at, _)| at.elapsed() < ttl);
+			if entries.results.len() >= MAX_ENTRIES {
+				return;
+			}
+		}
+
+		entries.results.insert((block.clone(), kind, request.clone()), (Instant::now(), result));
+	}
+
+	/// Current statistics.
+	pub fn stats(&self) -> CallCacheStats {
+		let entries = self.entries.lock();
+		CallCacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			misses: self.misses.load(Ordering::Relaxed),
+			oversized: self.oversized.load(Ordering::Relaxed),
+			invalidations: self.invalidations.load(Ordering::Relaxed),
+			entries: entries.results.len(),
+			size: entries.results.values().map(|&(_, ref result)| result.size()).sum(),
+		}
+	}
+
+	fn check_head(&self, entries: &mut Entries, head: &H256) {
+		if &entries.head == head {
+			return;
+		}
+
+		entries.head = head.clone();
+		if !entries.results.is_empty() {
+			trace!(target: "rpc", "Call cache invalidated by new head {} ({} hits, {} misses so far)",
+				head, self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed));
+			entries.results.clear();
+			self.invalidations.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+}
+
+/// Hash identifying a (fake-signed) call request.
+pub fn request_hash(signed: &SignedTransaction) -> H256 {
+	// fake signatures are all the same, so the sender has to be included explicitly
+	let mut stream = RlpStream::new_list(2);
+	stream.append(&signed.sender());
+	stream.append(&signed.hash());
+	stream.out().sha3()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use util::H256;
+	use super::{CallCache, CallKind, CallResult};
+
+	#[test]
+	fn should_cache_results_until_new_head() {
+		let cache = CallCache::new(Duration::from_secs(60), 1024);
+		let (head, request) = (H256::from(1), H256::from(2));
+
+		assert_eq!(cache.get(&head, &head, CallKind::Call, &request), None);
+		cache.insert(&head, &head, CallKind::Call, &request, CallResult::Output(vec![1, 2, 3]));
+		assert_eq!(cache.get(&head, &head, CallKind::Call, &request), Some(CallResult::Output(vec![1, 2, 3])));
+		assert_eq!(cache.get(&head, &head, CallKind::EstimateGas, &request), None);
+
+		let new_head = H256::from(3);
+		assert_eq!(cache.get(&new_head, &head, CallKind::Call, &request), None);
+
+		let stats = cache.stats();
+		assert_eq!((stats.hits, stats.misses, stats.invalidations, stats.entries, stats.size), (1, 3, 1, 0, 0));
+
+		cache.insert(&new_head, &new_head, CallKind::Call, &request, CallResult::Output(vec![1, 2, 3]));
+		cache.insert(&new_head, &new_head, CallKind::EstimateGas, &request, CallResult::Gas(21_000.into()));
+		let stats = cache.stats();
+		assert_eq!((stats.entries, stats.size), (2, 35));
+	}
+
+	#[test]
+	fn should_not_cache_oversized_or_expired_results() {
+		let cache = CallCache::new(Duration::from_secs(60), 2);
+		let (head, request) = (H256::from(1), H256::from(2));
+		cache.insert(&head, &head, CallKind::Call, &request, CallResult::Output(vec![1, 2, 3]));
+		assert_eq!(cache.get(&head, &head, CallKind::Call, &request), None);
+		assert_eq!(cache.stats().oversized, 1);
+
+		let cache = CallCache::new(Duration::from_secs(0), 1024);
+		cache.insert(&head, &head, CallKind::EstimateGas, &request, CallResult::Gas(21_000.into()));
+		assert_eq!(cache.get(&head, &head, CallKind::EstimateGas, &request), None);
+	}
+}
@@ -24,31 +24,124 @@ use std::fmt;
 use rlp::DecoderError;
 use ethcore::error::{Error as EthcoreError, CallError, TransactionError};
 use ethcore::account_provider::{SignError as AccountError};
+use ethcore::evm;
 use jsonrpc_core::{Error, ErrorCode, Value};
+use serde_json::Map;
 
-mod codes {
+/// Registry of server error codes. Codes are stable: they are never renumbered or reused.
+pub mod codes {
 	// NOTE [ToDr] Codes from [-32099, -32000]
 	pub const UNSUPPORTED_REQUEST: i64 = -32000;
 	pub const NO_WORK: i64 = -32001;
 	pub const NO_AUTHOR: i64 = -32002;
 	pub const NO_NEW_WORK: i64 = -32003;
+	pub const WORKER_UNAUTHORIZED: i64 = -32005;
+	pub const WORKER_RATE_LIMITED: i64 = -32006;
+	pub const STALE_WORK: i64 = -32007;
 	pub const UNKNOWN_ERROR: i64 = -32009;
 	pub const TRANSACTION_ERROR: i64 = -32010;
 	pub const EXECUTION_ERROR: i64 = -32015;
 	pub const EXCEPTION_ERROR: i64 = -32016;
 	pub const DATABASE_ERROR: i64 = -32017;
+	pub const EXECUTION_REVERTED: i64 = -32018;
 	pub const ACCOUNT_LOCKED: i64 = -32020;
 	pub const PASSWORD_INVALID: i64 = -32021;
 	pub const ACCOUNT_ERROR: i64 = -32023;
 	pub const REQUEST_REJECTED: i64 = -32040;
 	pub const REQUEST_REJECTED_LIMIT: i64 = -32041;
 	pub const REQUEST_NOT_FOUND: i64 = -32042;
+	pub const RESPONSE_TOO_LARGE: i64 = -32046;
 	pub const ENCRYPTION_ERROR: i64 = -32055;
 	pub const FETCH_ERROR: i64 = -32060;
 	pub const NO_LIGHT_PEERS: i64 = -32065;
+	pub const TOO_EXPENSIVE: i64 = -32066;
+	pub const TRUSTED_FALLBACK_ERROR: i64 = -32067;
 	pub const DEPRECATED: i64 = -32070;
 }
 
+/// Machine-readable error reason, sent as `data.reason` so that clients
+/// can branch on errors without parsing messages. Reasons refine the error
+/// code, which stays the same as for errors sent before reasons existed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+	/// Requested state is not available on a pruning node.
+	StatePruned,
+	/// Requested block is not known.
+	UnknownBlock,
+	/// Not enough data to answer the request.
+	NotEnoughData,
+	/// Transaction is already in the queue.
+	TransactionAlreadyImported,
+	/// Transaction gas price is below the minimum or too low to replace a queued transaction.
+	TransactionUnderpriced,
+	/// Transaction nonce is already used.
+	NonceTooLow,
+	/// Transaction nonce is too far in the future.
+	NonceTooHigh,
+	/// Sender cannot cover the transaction cost.
+	InsufficientFunds,
+	/// Transaction gas is below intrinsic gas or above the block gas limit.
+	InvalidGas,
+	/// Transaction was rejected by the local queue.
+	TransactionRejected,
+	/// Execution was reverted by the `REVERT` instruction.
+	ExecutionReverted,
+	/// Response exceeds the size limit of the method.
+	ResponseTooLarge,
+	/// Solution is for a work package which was superseded or already solved.
+	StaleWork,
+}
+
+impl Reason {
+	/// Identifier sent in `data.reason`.
+	pub fn as_str(&self) -> &'static str {
+		match *self {
+			Reason::StatePruned => "statePruned",
+			Reason::UnknownBlock => "unknownBlock",
+			Reason::NotEnoughData => "notEnoughData",
+			Reason::TransactionAlreadyImported => "transactionAlreadyImported",
+			Reason::TransactionUnderpriced => "transactionUnderpriced",
+			Reason::NonceTooLow => "nonceTooLow",
+			Reason::NonceTooHigh => "nonceTooHigh",
+			Reason::InsufficientFunds => "insufficientFunds",
+			Reason::InvalidGas => "invalidGas",
+			Reason::TransactionRejected => "transactionRejected",
+			Reason::ExecutionReverted => "executionReverted",
+			Reason::ResponseTooLarge => "responseTooLarge",
+			Reason::StaleWork => "staleWork",
+		}
+	}
+
+	/// Error code of errors with this reason.
+	pub fn code(&self) -> ErrorCode {
+		match *self {
+			Reason::StatePruned | Reason::NotEnoughData => ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+			Reason::UnknownBlock => ErrorCode::InvalidParams,
+			Reason::TransactionAlreadyImported | Reason::TransactionUnderpriced | Reason::NonceTooLow |
+			Reason::NonceTooHigh | Reason::InsufficientFunds | Reason::InvalidGas | Reason::TransactionRejected =>
+				ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+			Reason::ExecutionReverted => ErrorCode::ServerError(codes::EXECUTION_REVERTED),
+			Reason::ResponseTooLarge => ErrorCode::ServerError(codes::RESPONSE_TOO_LARGE),
+			Reason::StaleWork => ErrorCode::ServerError(codes::STALE_WORK),
+		}
+	}
+}
+
+/// Error with given reason and optional details, sent as `data.reason` and `data.details`.
+fn with_reason<T: Into<String>>(reason: Reason, message: T, details: Option<Value>) -> Error {
+	let mut data = Map::new();
+	data.insert("reason".into(), Value::String(reason.as_str().into()));
+	if let Some(details) = details {
+		data.insert("details".into(), details);
+	}
+
+	Error {
+		code: reason.code(),
+		message: message.into(),
+		data: Some(Value::Object(data)),
+	}
+}
+
 pub fn unimplemented(details: Option<String>) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -57,6 +150,22 @@ pub fn unimplemented(details: Option<String>) -> Error {
 	}
 }
 
+pub fn subscriptions_unsupported() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Subscriptions are only supported over persistent connections (WebSockets).".into(),
+		data: None,
+	}
+}
+
+pub fn signer_voting_unsupported() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "The consensus engine of this chain doesn't vote on signers.".into(),
+		data: None,
+	}
+}
+
 pub fn light_unimplemented(details: Option<String>) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
@@ -65,6 +174,14 @@ pub fn light_unimplemented(details: Option<String>) -> Error {
 	}
 }
 
+pub fn light_only() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "This request is only supported by light clients.".into(),
+		data: None,
+	}
+}
+
 pub fn request_not_found() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_NOT_FOUND),
@@ -81,6 +198,28 @@ pub fn request_rejected() -> Error {
 	}
 }
 
+pub fn server_busy(limit: usize) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("Server is busy processing {} requests. Try again later.", limit),
+		data: None,
+	}
+}
+
+pub fn response_too_large(method: &str, size: usize, limit: usize, hint: &str) -> Error {
+	let mut details = Map::new();
+	details.insert("method".into(), Value::String(method.into()));
+	details.insert("size".into(), Value::from(size as u64));
+	details.insert("limit".into(), Value::from(limit as u64));
+	details.insert("hint".into(), Value::String(hint.into()));
+
+	with_reason(
+		Reason::ResponseTooLarge,
+		format!("Response too large ({} bytes, limit {} bytes). {}", size, limit, hint),
+		Some(Value::Object(details)),
+	)
+}
+
 pub fn request_rejected_limit() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
@@ -116,6 +255,10 @@ pub fn invalid_params<T: fmt::Debug>(param: &str, details: T) -> Error {
 	}
 }
 
+pub fn env_overrides_unsupported() -> Error {
+	invalid_params("env", "Block environment overrides are only supported by calls at mined blocks.")
+}
+
 pub fn execution<T: fmt::Debug>(data: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::EXECUTION_ERROR),
@@ -125,11 +268,11 @@ pub fn execution<T: fmt::Debug>(data: T) -> Error {
 }
 
 pub fn state_pruned() -> Error {
-	Error {
-		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
-		message: "This request is not supported because your node is running with state pruning. Run with --pruning=archive.".into(),
-		data: None
-	}
+	with_reason(
+		Reason::StatePruned,
+		"This request is not supported because your node is running with state pruning. Run with --pruning=archive.",
+		None,
+	)
 }
 
 pub fn state_corrupt() -> Error {
@@ -144,6 +287,14 @@ pub fn exceptional() -> Error {
 	}
 }
 
+/// Execution ending with given VM exception. Only reverts have their own code and reason.
+pub fn vm_exception(error: &evm::Error) -> Error {
+	match *error {
+		evm::Error::Reverted => with_reason(Reason::ExecutionReverted, "The execution was reverted.", None),
+		_ => exceptional(),
+	}
+}
+
 pub fn no_work() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_WORK),
@@ -168,14 +319,30 @@ pub fn no_author() -> Error {
 	}
 }
 
-pub fn not_enough_data() -> Error {
+pub fn worker_unauthorized() -> Error {
 	Error {
-		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
-		message: "The node does not have enough data to compute the given statistic.".into(),
-		data: None,
+		code: ErrorCode::ServerError(codes::WORKER_UNAUTHORIZED),
+		message: "Invalid worker credentials.".into(),
+		data: None
 	}
 }
 
+pub fn worker_rate_limited(limit: u32) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::WORKER_RATE_LIMITED),
+		message: format!("Worker exceeded {} submissions per minute.", limit),
+		data: None
+	}
+}
+
+pub fn stale_work() -> Error {
+	with_reason(Reason::StaleWork, "Work package was superseded by newer work.", None)
+}
+
+pub fn not_enough_data() -> Error {
+	with_reason(Reason::NotEnoughData, "The node does not have enough data to compute the given statistic.", None)
+}
+
 pub fn token(e: String) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::UNKNOWN_ERROR),
@@ -260,6 +427,12 @@ pub fn transaction_message(error: TransactionError) -> String {
 		LimitReached => {
 			"There are too many transactions in the queue. Your transaction was dropped due to limit. Try increasing the fee.".into()
 		},
+		SenderLimitReached => {
+			"There are too many transactions from this sender in the queue. Your transaction was dropped due to limit. Try waiting for pending transactions to be mined.".into()
+		},
+		NonceGapTooLarge { limit, got } => {
+			format!("Transaction nonce is too far in the future (allowed gap: {}, got: {}). Try decreasing the nonce.", limit, got)
+		},
 		InsufficientGas { minimal, got } => {
 			format!("Transaction gas is too low. There is not enough gas to cover minimal cost of the transaction (minimal: {}, got: {}). Try increasing supplied gas.", minimal, got)
 		},
@@ -273,21 +446,57 @@ pub fn transaction_message(error: TransactionError) -> String {
 			format!("Transaction cost exceeds current gas limit. Limit: {}, got: {}. Try decreasing supplied gas.", limit, got)
 		},
 		InvalidNetworkId => "Invalid network id.".into(),
+		MissingNetworkId => "Transaction must be signed with the network id of this chain (EIP-155).".into(),
 		InvalidGasLimit(_) => "Supplied gas is beyond limit.".into(),
 		SenderBanned => "Sender is banned in local queue.".into(),
 		RecipientBanned => "Recipient is banned in local queue.".into(),
 		CodeBanned => "Code is banned in local queue.".into(),
+		InitCodeTooLarge { limit, got } => {
+			format!("Contract creation code is too large (limit: {} bytes, got: {} bytes). Try deploying a smaller contract.", limit, got)
+		},
+		ReadOnly => "This node is read-only and doesn't accept transactions.".into(),
+	}
+}
+
+/// Reason and machine-readable details of a transaction error.
+fn transaction_reason(error: &TransactionError) -> (Reason, Option<Value>) {
+	use ethcore::error::TransactionError::*;
+
+	fn details(fields: &[(&str, String)]) -> Option<Value> {
+		let mut details = Map::new();
+		for &(key, ref value) in fields {
+			details.insert(key.into(), Value::String(value.clone()));
+		}
+		Some(Value::Object(details))
+	}
+
+	match *error {
+		AlreadyImported => (Reason::TransactionAlreadyImported, None),
+		Old => (Reason::NonceTooLow, None),
+		TooCheapToReplace => (Reason::TransactionUnderpriced, None),
+		NonceGapTooLarge { ref limit, ref got } =>
+			(Reason::NonceTooHigh, details(&[("limit", format!("{:#x}", limit)), ("got", format!("{:#x}", got))])),
+		InsufficientGasPrice { ref minimal, ref got } =>
+			(Reason::TransactionUnderpriced, details(&[("minimal", format!("{:#x}", minimal)), ("got", format!("{:#x}", got))])),
+		InsufficientBalance { ref balance, ref cost } =>
+			(Reason::InsufficientFunds, details(&[("balance", format!("{:#x}", balance)), ("cost", format!("{:#x}", cost))])),
+		InsufficientGas { ref minimal, ref got } =>
+			(Reason::InvalidGas, details(&[("minimal", format!("{:#x}", minimal)), ("got", format!("{:#x}", got))])),
+		GasLimitExceeded { ref limit, ref got } =>
+			(Reason::InvalidGas, details(&[("limit", format!("{:#x}", limit)), ("got", format!("{:#x}", got))])),
+		InvalidGasLimit(_) => (Reason::InvalidGas, None),
+		InitCodeTooLarge { limit, got } =>
+			(Reason::TransactionRejected, details(&[("limit", format!("{:#x}", limit)), ("got", format!("{:#x}", got))])),
+		LimitReached | SenderLimitReached | InvalidNetworkId | MissingNetworkId | SenderBanned | RecipientBanned | CodeBanned | ReadOnly =>
+			(Reason::TransactionRejected, None),
 	}
 }
 
 pub fn from_transaction_error(error: EthcoreError) -> Error {
 
 	if let EthcoreError::Transaction(e) = error {
-		Error {
-			code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
-			message: transaction_message(e),
-			data: None,
-		}
+		let (reason, details) = transaction_reason(&e);
+		with_reason(reason, transaction_message(e), details)
 	} else {
 		Error {
 			code: ErrorCode::ServerError(codes::UNKNOWN_ERROR),
@@ -316,11 +525,7 @@ pub fn from_call_error(error: CallError) -> Error {
 }
 
 pub fn unknown_block() -> Error {
-	Error {
-		code: ErrorCode::InvalidParams,
-		message: "Unknown block number".into(),
-		data: None,
-	}
+	with_reason(Reason::UnknownBlock, "Unknown block number", None)
 }
 
 pub fn no_light_peers() -> Error {
@@ -331,6 +536,22 @@ pub fn no_light_peers() -> Error {
 	}
 }
 
+pub fn too_expensive(details: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TOO_EXPENSIVE),
+		message: "Query too expensive for light mode.".into(),
+		data: Some(Value::String(details)),
+	}
+}
+
+pub fn trusted_fallback(details: String) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TRUSTED_FALLBACK_ERROR),
+		message: "Trusted fallback node failed to answer the request.".into(),
+		data: Some(Value::String(details)),
+	}
+}
+
 pub fn deprecated<T: Into<Option<String>>>(message: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::DEPRECATED),
@@ -338,3 +559,53 @@ pub fn deprecated<T: Into<Option<String>>>(message: T) -> Error {
 		data: message.into().map(Value::String),
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use jsonrpc_core::ErrorCode;
+	use ethcore::error::{Error as EthcoreError, TransactionError};
+	use ethcore::evm;
+	use super::{codes, execution, from_transaction_error, state_pruned, vm_exception};
+
+	#[test]
+	fn should_attach_reason_to_transaction_errors() {
+		let error = from_transaction_error(EthcoreError::Transaction(TransactionError::InsufficientGasPrice {
+			minimal: 20.into(),
+			got: 1.into(),
+		}));
+
+		assert_eq!(error.code, ErrorCode::ServerError(codes::TRANSACTION_ERROR));
+		assert_eq!(
+			serde_json::to_string(&error.data).unwrap(),
+			r#"{"details":{"got":"0x1","minimal":"0x14"},"reason":"transactionUnderpriced"}"#
+		);
+	}
+
+	#[test]
+	fn should_attach_reason_to_state_errors() {
+		let error = state_pruned();
+
+		assert_eq!(error.code, ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST));
+		assert_eq!(serde_json::to_string(&error.data).unwrap(), r#"{"reason":"statePruned"}"#);
+	}
+
+	#[test]
+	fn should_keep_execution_error_details_a_string() {
+		let error = execution("Out of gas");
+
+		assert_eq!(error.code, ErrorCode::ServerError(codes::EXECUTION_ERROR));
+		assert_eq!(serde_json::to_string(&error.data).unwrap(), r#""\"Out of gas\"""#);
+	}
+
+	#[test]
+	fn should_attach_reason_only_to_reverts() {
+		let error = vm_exception(&evm::Error::Reverted);
+		assert_eq!(error.code, ErrorCode::ServerError(codes::EXECUTION_REVERTED));
+		assert_eq!(serde_json::to_string(&error.data).unwrap(), r#"{"reason":"executionReverted"}"#);
+
+		let error = vm_exception(&evm::Error::OutOfGas);
+		assert_eq!(error.code, ErrorCode::ServerError(codes::EXCEPTION_ERROR));
+		assert_eq!(error.data, None);
+	}
+}
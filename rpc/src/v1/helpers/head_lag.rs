@@ -0,0 +1,179 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Lag of the chain head behind the NTP-verified wall clock.
+
+use std::io;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ethcore::header::BlockNumber;
+use util::RwLock;
+
+/// Seconds between the NTP (1900) and UNIX (1970) epochs.
+const NTP_UNIX_EPOCH_OFFSET: i64 = 2_208_988_800;
+const NTP_PACKET_SIZE: usize = 48;
+const NTP_TIMEOUT_SECS: u64 = 3;
+
+fn unix_millis(time: SystemTime) -> i64 {
+	match time.duration_since(UNIX_EPOCH) {
+		Ok(d) => (d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000) as i64,
+		Err(e) => {
+			let d = e.duration();
+			-((d.as_secs() * 1000 + d.subsec_nanos() as u64 / 1_000_000) as i64)
+		}
+	}
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+	(bytes[0] as u32) << 24 | (bytes[1] as u32) << 16 | (bytes[2] as u32) << 8 | bytes[3] as u32
+}
+
+// convert 64-bit NTP timestamp to UNIX milliseconds.
+fn ntp_to_unix_millis(bytes: &[u8]) -> i64 {
+	let secs = read_u32(&bytes[0..4]) as i64 - NTP_UNIX_EPOCH_OFFSET;
+	let fraction = read_u32(&bytes[4..8]) as i64;
+	secs * 1000 + ((fraction * 1000) >> 32)
+}
+
+/// Query an SNTP server (`host:port`) for the offset of the local clock in milliseconds.
+/// A positive offset means the local clock is behind.
+pub fn query_clock_offset(server: &str, timeout: Duration) -> io::Result<i64> {
+	let socket = UdpSocket::bind("0.0.0.0:0")?;
+	socket.set_read_timeout(Some(timeout))?;
+	socket.connect(server)?;
+
+	// version 3, client mode
+	let mut request = [0u8; NTP_PACKET_SIZE];
+	request[0] = 0x1b;
+
+	let sent_at = unix_millis(SystemTime::now());
+	socket.send(&request)?;
+	let mut response = [0u8; NTP_PACKET_SIZE];
+	let len = socket.recv(&mut response)?;
+	let received_at = unix_millis(SystemTime::now());
+
+	// expect a server mode reply
+	if len < NTP_PACKET_SIZE || response[0] & 0x07 != 4 {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid SNTP response"));
+	}
+
+	let server_received = ntp_to_unix_millis(&response[32..40]);
+	let server_sent = ntp_to_unix_millis(&response[40..48]);
+	Ok(((server_received - sent_at) + (server_sent - received_at)) / 2)
+}
+
+/// Measures how far the best block is behind the current time, using the local clock
+/// corrected by NTP. Independent of heights reported by peers.
+pub struct HeadLag {
+	servers: Vec<String>,
+	warn_after: u64,
+	clock_offset: RwLock<Option<i64>>,
+	trailing: AtomicBool,
+}
+
+impl HeadLag {
+	/// Create new measurement using given SNTP servers. The head is considered to be
+	/// trailing the network when it's more than `warn_after` seconds old.
+	pub fn new(servers: Vec<String>, warn_after: u64) -> Self {
+		HeadLag {
+			servers: servers,
+			warn_after: warn_after,
+			clock_offset: RwLock::new(None),
+			trailing: AtomicBool::new(false),
+		}
+	}
+
+	/// Measure the local clock offset again, using the first server which responds.
+	/// Blocks for up to a few seconds per unresponsive server.
+	pub fn update_clock_offset(&self) {
+		for server in &self.servers {
+			match query_clock_offset(server, Duration::from_secs(NTP_TIMEOUT_SECS)) {
+				Ok(offset) => {
+					trace!(target: "ntp", "Local clock offset from {}: {}ms", server, offset);
+					self.set_clock_offset(Some(offset));
+					return;
+				},
+				Err(e) => debug!(target: "ntp", "Unable to query {}: {}", server, e),
+			}
+		}
+		if !self.servers.is_empty() {
+			warn!(target: "ntp", "Unable to verify the local clock, none of the NTP servers responded.");
+		}
+	}
+
+	/// Set the local clock offset in milliseconds, `None` if it's unknown.
+	pub fn set_clock_offset(&self, offset: Option<i64>) {
+		*self.clock_offset.write() = offset;
+	}
+
+	/// Offset of the local clock from NTP time in milliseconds, if it was measured.
+	pub fn clock_offset(&self) -> Option<i64> {
+		*self.clock_offset.read()
+	}
+
+	/// Seconds a block with given timestamp is behind the corrected current time.
+	/// `None` until the local clock is verified.
+	pub fn lag(&self, timestamp: u64) -> Option<u64> {
+		self.clock_offset().map(|offset| {
+			let now = (unix_millis(SystemTime::now()) + offset) / 1000;
+			if now > timestamp as i64 { now as u64 - timestamp } else { 0 }
+		})
+	}
+
+	/// Whether given lag means the node is trailing the network.
+	pub fn is_trailing(&self, lag: u64) -> bool {
+		lag > self.warn_after
+	}
+
+	/// Check the best block, warning when the node starts trailing the network.
+	pub fn check(&self, number: BlockNumber, timestamp: u64) {
+		let lag = self.lag(timestamp);
+		let trailing = lag.map_or(false, |lag| self.is_trailing(lag));
+		match (self.trailing.swap(trailing, Ordering::Relaxed), trailing) {
+			(false, true) => warn!("Best block #{} is {}s older than the current time. The node appears to be trailing the network.", number, lag.unwrap_or(0)),
+			(true, false) => info!("Best block #{} is up to date with the current time again.", number),
+			_ => {},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::{Duration, UNIX_EPOCH};
+	use super::{HeadLag, ntp_to_unix_millis, unix_millis};
+
+	#[test]
+	fn converts_ntp_timestamps() {
+		// 2017-01-01 00:00:00.5 UTC
+		let ntp = [0xdc, 0x12, 0xc5, 0x00, 0x80, 0x00, 0x00, 0x00];
+		assert_eq!(ntp_to_unix_millis(&ntp), 1_483_228_800_500);
+		assert_eq!(unix_millis(UNIX_EPOCH + Duration::from_millis(1_483_228_800_500)), 1_483_228_800_500);
+	}
+
+	#[test]
+	fn lag_requires_verified_clock() {
+		let head_lag = HeadLag::new(Vec::new(), 60);
+		assert_eq!(head_lag.lag(0), None);
+
+		head_lag.set_clock_offset(Some(0));
+		assert!(head_lag.lag(0).unwrap() > 1_000_000_000);
+		assert_eq!(head_lag.lag(u64::max_value() / 2), Some(0));
+		assert!(head_lag.is_trailing(61));
+		assert!(!head_lag.is_trailing(60));
+	}
+}
@@ -20,9 +20,13 @@ use std::fmt;
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
 use std::time;
-use futures::Future;
+use futures::{self, Future};
 use jsonrpc_core as rpc;
 use order_stat;
+use v1::Metadata;
+use v1::helpers::errors;
+use v1::helpers::response_limits::ResponseLimits;
+use v1::helpers::signing_audit::SigningAudit;
 use util::RwLock;
 
 const RATE_SECONDS: usize = 10;
@@ -184,6 +188,10 @@ pub trait ActivityNotifier: Send + Sync + 'static {
 pub struct Middleware<T: ActivityNotifier = ClientNotifier> {
 	stats: Arc<RpcStats>,
 	notifier: T,
+	response_limits: Option<Arc<ResponseLimits>>,
+	signing_audit: Option<Arc<SigningAudit>>,
+	max_concurrent_requests: Option<usize>,
+	in_flight: Arc<AtomicUsize>,
 }
 
 impl<T: ActivityNotifier> Middleware<T> {
@@ -192,31 +200,107 @@ impl<T: ActivityNotifier> Middleware<T> {
 		Middleware {
 			stats: stats,
 			notifier: notifier,
+			response_limits: None,
+			signing_audit: None,
+			max_concurrent_requests: None,
+			in_flight: Arc::new(AtomicUsize::new(0)),
 		}
 	}
 
+	/// Reject responses exceeding the size limits of their methods.
+	pub fn with_response_limits(mut self, response_limits: Option<Arc<ResponseLimits>>) -> Self {
+		self.response_limits = match response_limits {
+			Some(ref limits) if limits.is_empty() => None,
+			limits => limits,
+		};
+		self
+	}
+
+	/// Record account-affecting calls in given audit log.
+	pub fn with_signing_audit(mut self, signing_audit: Option<Arc<SigningAudit>>) -> Self {
+		self.signing_audit = signing_audit;
+		self
+	}
+
+	/// Reject requests while `max` requests are already being processed.
+	pub fn with_max_concurrent_requests(mut self, max: Option<usize>) -> Self {
+		self.max_concurrent_requests = max;
+		self
+	}
+
 	fn as_micro(dur: time::Duration) -> u32 {
 		(dur.as_secs() * 1_000_000) as u32 + dur.subsec_nanos() / 1_000
 	}
 }
 
-impl<M: rpc::Metadata, T: ActivityNotifier> rpc::Middleware<M> for Middleware<T> {
-	fn on_request<F>(&self, request: rpc::Request, meta: M, process: F) -> rpc::FutureResponse where
-		F: FnOnce(rpc::Request, M) -> rpc::FutureResponse,
+impl<T: ActivityNotifier> rpc::Middleware<Metadata> for Middleware<T> {
+	fn on_request<F>(&self, request: rpc::Request, meta: Metadata, process: F) -> rpc::FutureResponse where
+		F: FnOnce(rpc::Request, Metadata) -> rpc::FutureResponse,
 	{
 		let start = time::Instant::now();
+		let in_flight = InFlight::new(self.in_flight.clone());
+		if let Some(max) = self.max_concurrent_requests {
+			if in_flight.count() > max {
+				return reject(errors::server_busy(max));
+			}
+		}
+
+		let limits = self.response_limits.clone().map(|limits| (limits, ResponseLimits::methods(&request)));
+		let audit = self.signing_audit.clone().and_then(|audit| {
+			let calls = SigningAudit::calls(&request);
+			if calls.is_empty() { None } else { Some((audit, calls, meta.origin.clone())) }
+		});
 		let response = process(request, meta);
 
 		self.notifier.active();
 		let stats = self.stats.clone();
 		stats.count_request();
 		response.map(move |res| {
+			drop(in_flight);
 			stats.add_roundtrip(Self::as_micro(start.elapsed()));
+			let res = match limits {
+				Some((limits, methods)) => limits.enforce(&methods, res),
+				None => res,
+			};
+			if let Some((audit, calls, origin)) = audit {
+				audit.record(calls, &origin, res.as_ref());
+			}
 			res
 		}).boxed()
 	}
 }
 
+/// Counts a request as being processed for as long as it's alive.
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+	fn new(counter: Arc<AtomicUsize>) -> Self {
+		counter.fetch_add(1, atomic::Ordering::SeqCst);
+		InFlight(counter)
+	}
+
+	/// Number of requests being processed, including this one.
+	fn count(&self) -> usize {
+		self.0.load(atomic::Ordering::SeqCst)
+	}
+}
+
+impl Drop for InFlight {
+	fn drop(&mut self) {
+		self.0.fetch_sub(1, atomic::Ordering::SeqCst);
+	}
+}
+
+/// Answer a request with given error without processing it.
+fn reject(error: rpc::Error) -> rpc::FutureResponse {
+	let failure = rpc::Failure {
+		jsonrpc: Some(rpc::Version::V2),
+		error: error,
+		id: rpc::Id::Null,
+	};
+	futures::future::ok(Some(rpc::Response::Single(rpc::Output::Failure(failure)))).boxed()
+}
+
 /// Client Notifier
 pub struct ClientNotifier {
 	/// Client
@@ -232,7 +316,9 @@ impl ActivityNotifier for ClientNotifier {
 #[cfg(test)]
 mod tests {
 
-	use super::{RateCalculator, StatsCalculator, RpcStats};
+	use std::sync::Arc;
+	use std::sync::atomic::AtomicUsize;
+	use super::{RateCalculator, StatsCalculator, RpcStats, InFlight};
 
 	#[test]
 	fn should_calculate_rate() {
@@ -289,6 +375,23 @@ mod tests {
 		assert_eq!(stats.approximated_roundtrip(), 125);
 	}
 
+	#[test]
+	fn should_count_requests_in_flight() {
+		// given
+		let counter = Arc::new(AtomicUsize::new(0));
+
+		// when
+		let first = InFlight::new(counter.clone());
+		let second = InFlight::new(counter.clone());
+		assert_eq!(second.count(), 2);
+		drop(first);
+
+		// then
+		assert_eq!(second.count(), 1);
+		drop(second);
+		assert_eq!(InFlight::new(counter).count(), 1);
+	}
+
 	#[test]
 	fn should_be_sync_and_send() {
 		let stats = RpcStats::default();
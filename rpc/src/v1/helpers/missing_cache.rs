@@ -0,0 +1,167 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Short-lived cache of hashes which were looked up but aren't in the chain.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use util::{H256, Mutex};
+
+/// Maximal number of cached hashes.
+const MAX_ENTRIES: usize = 16384;
+
+/// Kind of lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LookupKind {
+	/// Block by hash.
+	Block,
+	/// Transaction by hash.
+	Transaction,
+	/// Receipt by transaction hash.
+	Receipt,
+}
+
+/// Cache usage statistics.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MissingCacheStats {
+	/// Lookups answered from cache.
+	pub hits: usize,
+	/// Number of times the cache was cleared because of a new head.
+	pub invalidations: usize,
+	/// Currently cached hashes.
+	pub entries: usize,
+}
+
+struct Entries {
+	head: H256,
+	missing: HashMap<(LookupKind, H256), Instant>,
+}
+
+/// Cache of hashes not found in the chain, keyed by lookup kind.
+/// All entries are dropped whenever the best block changes.
+pub struct MissingCache {
+	ttl: Duration,
+	entries: Mutex<Entries>,
+	hits: AtomicUsize,
+	invalidations: AtomicUsize,
+}
+
+impl MissingCache {
+	/// Create new cache. Hashes are remembered for at most `ttl`.
+	pub fn new(ttl: Duration) -> Self {
+		MissingCache {
+			ttl: ttl,
+			entries: Mutex::new(Entries {
+				head: H256::default(),
+				missing: HashMap::new(),
+			}),
+			hits: AtomicUsize::new(0),
+			invalidations: AtomicUsize::new(0),
+		}
+	}
+
+	/// Whether `hash` was recently looked up and found missing, given current best block `head`.
+	pub fn is_missing(&self, head: &H256, kind: LookupKind, hash: &H256) -> bool {
+		let mut entries = self.entries.lock();
+		self.check_head(&mut entries, head);
+
+		let key = (kind, hash.clone());
+		let missing = match entries.missing.get(&key) {
+			Some(at) => at.elapsed() < self.ttl,
+			None => return false,
+		};
+
+		match missing {
+			true => { self.hits.fetch_add(1, Ordering::Relaxed); },
+			false => { entries.missing.remove(&key); },
+		}
+		missing
+	}
+
+	/// Remember that `hash` is missing, given current best block `head`.
+	pub fn note_missing(&self, head: &H256, kind: LookupKind, hash: &H256) {
+		let mut entries = self.entries.lock();
+		self.check_head(&mut entries, head);
+
+		if entries.missing.len() >= MAX_ENTRIES {
+			let ttl = self.ttl;
+			entries.missing.retain(|_, at| at.elapsed() < ttl);
+			if entries.missing.len() >= MAX_ENTRIES {
+				return;
+			}
+		}
+
+		entries.missing.insert((kind, hash.clone()), Instant::now());
+	}
+
+	/// Current statistics.
+	pub fn stats(&self) -> MissingCacheStats {
+		MissingCacheStats {
+			hits: self.hits.load(Ordering::Relaxed),
+			invalidations: self.invalidations.load(Ordering::Relaxed),
+			entries: self.entries.lock().missing.len(),
+		}
+	}
+
+	fn check_head(&self, entries: &mut Entries, head: &H256) {
+		if &entries.head == head {
+			return;
+		}
+
+		entries.head = head.clone();
+		if !entries.missing.is_empty() {
+			trace!(target: "rpc", "Missing hashes cache invalidated by new head {} ({} hits so far)",
+				head, self.hits.load(Ordering::Relaxed));
+			entries.missing.clear();
+			self.invalidations.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use util::H256;
+	use super::{MissingCache, LookupKind};
+
+	#[test]
+	fn should_remember_missing_hashes_until_new_head() {
+		let cache = MissingCache::new(Duration::from_secs(60));
+		let (head, hash) = (H256::from(1), H256::from(2));
+
+		assert!(!cache.is_missing(&head, LookupKind::Transaction, &hash));
+		cache.note_missing(&head, LookupKind::Transaction, &hash);
+		assert!(cache.is_missing(&head, LookupKind::Transaction, &hash));
+		assert!(!cache.is_missing(&head, LookupKind::Receipt, &hash));
+
+		assert!(!cache.is_missing(&H256::from(3), LookupKind::Transaction, &hash));
+
+		let stats = cache.stats();
+		assert_eq!((stats.hits, stats.invalidations, stats.entries), (1, 1, 0));
+	}
+
+	#[test]
+	fn should_forget_expired_hashes() {
+		let cache = MissingCache::new(Duration::from_secs(0));
+		let (head, hash) = (H256::from(1), H256::from(2));
+
+		cache.note_missing(&head, LookupKind::Block, &hash);
+		assert!(!cache.is_missing(&head, LookupKind::Block, &hash));
+		assert_eq!(cache.stats().entries, 0);
+	}
+}
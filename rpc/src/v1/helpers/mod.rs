@@ -17,11 +17,25 @@
 #[macro_use]
 pub mod errors;
 
+pub mod abi_registry;
+pub mod block_id;
 pub mod block_import;
+pub mod call_cache;
 pub mod dispatch;
 pub mod fake_sign;
+pub mod head_lag;
 pub mod informant;
+pub mod missing_cache;
 pub mod oneshot;
+pub mod receipt_proof;
+pub mod request_budget;
+pub mod response_limits;
+pub mod signing_audit;
+pub mod sync_events;
+pub mod transaction_events;
+pub mod trusted_fallback;
+pub mod work_auth;
+pub mod work_tracker;
 
 mod network_settings;
 mod poll_manager;
@@ -0,0 +1,81 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Inclusion proofs of receipts in a block's receipts trie.
+
+use rlp::{self, UntrustedRlp};
+use util::{Bytes, H256, MemoryDB};
+use util::trie::{TrieMut, TrieDBMut, Trie, TrieDB, Recorder};
+
+/// Generate the trie nodes on the path from the receipts root to the receipt
+/// at given index, given the RLP of all the block's receipts.
+/// Returns `None` if there's no such receipt.
+pub fn prove_receipt(block_receipts: &[u8], index: usize) -> Option<Vec<Bytes>> {
+	let mut db = MemoryDB::new();
+	let mut root = H256::default();
+
+	{
+		let mut t = TrieDBMut::new(&mut db, &mut root);
+		for (i, receipt) in UntrustedRlp::new(block_receipts).iter().enumerate() {
+			t.insert(&rlp::encode(&i), receipt.as_raw())
+				.expect("fresh in-memory database is infallible; qed");
+		}
+	}
+
+	let mut recorder = Recorder::new();
+	let found = TrieDB::new(&db, &root)
+		.and_then(|t| t.get_with(&rlp::encode(&index), &mut recorder))
+		.expect("trie was just built in memory; all nodes are present; qed");
+
+	found.map(|_| recorder.drain().into_iter().map(|x| x.data).collect())
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::receipt::Receipt;
+	use rlp::{self, RlpStream};
+	use util::{H256, MemoryDB, HashDB, ordered_trie_root};
+	use util::trie::{Trie, TrieDB};
+	use super::prove_receipt;
+
+	fn receipts() -> Vec<Receipt> {
+		(0..20).map(|i| Receipt::new(Some(H256::from(i)), (i * 21000).into(), vec![])).collect()
+	}
+
+	#[test]
+	fn proof_verifies_against_receipts_root() {
+		let receipts = receipts();
+		let root = ordered_trie_root(receipts.iter().map(|r| rlp::encode(r).to_vec()));
+
+		let mut stream = RlpStream::new();
+		stream.append_list(&receipts);
+		let proof = prove_receipt(&stream.out(), 7).unwrap();
+
+		let mut db = MemoryDB::new();
+		for node in &proof {
+			db.insert(node);
+		}
+		let t = TrieDB::new(&db, &root).unwrap();
+		assert_eq!(&*t.get(&rlp::encode(&7usize)).unwrap().unwrap(), &*rlp::encode(&receipts[7]));
+	}
+
+	#[test]
+	fn no_proof_of_missing_receipt() {
+		let mut stream = RlpStream::new();
+		stream.append_list(&receipts());
+		assert!(prove_receipt(&stream.out(), 20).is_none());
+	}
+}
@@ -0,0 +1,100 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Budget of on-demand network requests for a single light client RPC call.
+//!
+//! The budget is given to the light `EthClient` by whoever builds it; the parity
+//! binary itself doesn't run a light client, so it has no option for it.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use jsonrpc_core::Error;
+use v1::helpers::errors;
+
+/// Limits on the network requests a single light client RPC call may make.
+/// `None` means unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct RequestBudget {
+	/// Maximum number of on-demand requests.
+	pub max_requests: Option<usize>,
+	/// Maximum number of bytes received in responses.
+	pub max_bytes: Option<usize>,
+}
+
+impl RequestBudget {
+	/// Start tracking the spending of a single call.
+	pub fn start(&self) -> Arc<Spending> {
+		Arc::new(Spending {
+			budget: *self,
+			requests: AtomicUsize::new(0),
+			bytes: AtomicUsize::new(0),
+		})
+	}
+}
+
+/// Network requests made by a single call so far.
+#[derive(Debug)]
+pub struct Spending {
+	budget: RequestBudget,
+	requests: AtomicUsize,
+	bytes: AtomicUsize,
+}
+
+impl Spending {
+	/// Account for a request about to be made. Fails if it would exceed the budget.
+	pub fn request(&self) -> Result<(), Error> {
+		let requests = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+		match self.budget.max_requests {
+			Some(max) if requests > max => Err(errors::too_expensive(format!("More than {} network requests needed.", max))),
+			_ => Ok(()),
+		}
+	}
+
+	/// Account for a received response. Fails if the budget has been exceeded.
+	pub fn received(&self, bytes: usize) -> Result<(), Error> {
+		let total = self.bytes.fetch_add(bytes, Ordering::SeqCst) + bytes;
+		match self.budget.max_bytes {
+			Some(max) if total > max => Err(errors::too_expensive(format!("More than {} bytes of responses needed.", max))),
+			_ => Ok(()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::RequestBudget;
+
+	#[test]
+	fn limits_requests_and_bytes() {
+		let spending = RequestBudget { max_requests: Some(2), max_bytes: Some(100) }.start();
+		assert!(spending.request().is_ok());
+		assert!(spending.request().is_ok());
+		assert!(spending.request().is_err());
+
+		assert!(spending.received(100).is_ok());
+		assert!(spending.received(1).is_err());
+	}
+
+	#[test]
+	fn unlimited_by_default() {
+		let spending = RequestBudget::default().start();
+		for _ in 0..1000 {
+			assert!(spending.request().is_ok());
+		}
+		assert!(spending.received(usize::max_value() / 2).is_ok());
+	}
+}
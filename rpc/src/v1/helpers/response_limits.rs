@@ -0,0 +1,262 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-method limits of RPC response sizes.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use jsonrpc_core as rpc;
+use serde_json;
+use v1::helpers::errors;
+use v1::types::Bytes;
+
+/// Maximal response sizes (in bytes) of RPC methods.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ResponseLimits {
+	default: Option<usize>,
+	methods: HashMap<String, usize>,
+}
+
+/// Approximate length of the JSON serialization of an RPC result item.
+pub trait ResponseSize {
+	/// Estimated size in bytes, without serializing the item.
+	fn response_size(&self) -> usize;
+}
+
+/// Length of hex-encoded bytes, including the prefix and quotes.
+pub fn bytes_size(bytes: &Bytes) -> usize {
+	4 + 2 * bytes.0.len()
+}
+
+impl ResponseLimits {
+	/// Limit responses of given methods, and of all other methods to `default` (if any).
+	pub fn new(default: Option<usize>, methods: HashMap<String, usize>) -> Self {
+		ResponseLimits {
+			default: default,
+			methods: methods,
+		}
+	}
+
+	/// Returns true if no method is limited.
+	pub fn is_empty(&self) -> bool {
+		self.default.is_none() && self.methods.is_empty()
+	}
+
+	/// Maximal response size of given method.
+	pub fn limit(&self, method: &str) -> Option<usize> {
+		self.methods.get(method).cloned().or(self.default)
+	}
+
+	/// Checks the estimated serialized size of a list of results of given method against its limit.
+	///
+	/// Unlike `enforce`, which serializes the finished response, this only estimates the size
+	/// of each item, so methods returning long lists can reject them before they are serialized.
+	pub fn check<T: ResponseSize>(&self, method: &str, items: &[T]) -> Result<(), rpc::Error> {
+		let limit = match self.limit(method) {
+			Some(limit) => limit,
+			None => return Ok(()),
+		};
+
+		// opening and closing brackets and separating commas
+		let size = items.iter().fold(2 + items.len().saturating_sub(1), |size, item| size + item.response_size());
+		if size > limit {
+			warn!(target: "rpc", "Rejected {} response of approximately {} bytes (limit: {} bytes)", method, size, limit);
+			return Err(errors::response_too_large(method, size, limit, hint(method)));
+		}
+		Ok(())
+	}
+
+	/// Methods of the calls in a request, by call id.
+	pub fn methods(request: &rpc::Request) -> Vec<(rpc::Id, String)> {
+		fn method(call: &rpc::Call) -> Option<(rpc::Id, String)> {
+			match *call {
+				rpc::Call::MethodCall(ref call) => Some((call.id.clone(), call.method.clone())),
+				_ => None,
+			}
+		}
+
+		match *request {
+			rpc::Request::Single(ref call) => method(call).into_iter().collect(),
+			rpc::Request::Batch(ref calls) => calls.iter().filter_map(method).collect(),
+		}
+	}
+
+	/// Replace results exceeding the limit of their method with an error.
+	pub fn enforce(&self, methods: &[(rpc::Id, String)], response: Option<rpc::Response>) -> Option<rpc::Response> {
+		response.map(|response| match response {
+			rpc::Response::Single(output) => rpc::Response::Single(self.enforce_output(methods, output)),
+			rpc::Response::Batch(outputs) => rpc::Response::Batch(
+				outputs.into_iter().map(|output| self.enforce_output(methods, output)).collect()
+			),
+		})
+	}
+
+	fn enforce_output(&self, methods: &[(rpc::Id, String)], output: rpc::Output) -> rpc::Output {
+		let success = match output {
+			rpc::Output::Success(success) => success,
+			failure => return failure,
+		};
+
+		let method = methods.iter().find(|&&(ref id, _)| *id == success.id).map(|&(_, ref method)| method.as_str());
+		let limit = match method.and_then(|method| self.limit(method)) {
+			Some(limit) => limit,
+			None => return rpc::Output::Success(success),
+		};
+
+		let size = serde_json::to_string(&success.result).map(|result| result.len()).unwrap_or(0);
+		if size <= limit {
+			return rpc::Output::Success(success);
+		}
+
+		let method = method.expect("limit is only found for known methods; qed");
+		warn!(target: "rpc", "Rejected {} response of {} bytes (limit: {} bytes)", method, size, limit);
+		rpc::Output::Failure(rpc::Failure {
+			jsonrpc: success.jsonrpc,
+			error: errors::response_too_large(method, size, limit, hint(method)),
+			id: success.id,
+		})
+	}
+}
+
+/// Checks the estimated size of results of given method, if any limits are configured.
+pub fn check<T: ResponseSize>(limits: Option<&ResponseLimits>, method: &str, items: &[T]) -> Result<(), rpc::Error> {
+	match limits {
+		Some(limits) => limits.check(method, items),
+		None => Ok(()),
+	}
+}
+
+/// How to narrow down a query of given method.
+fn hint(method: &str) -> &'static str {
+	match method {
+		"eth_getLogs" | "eth_getFilterLogs" | "eth_getFilterChanges" | "parity_getLogs" =>
+			"Narrow your query: use a smaller block range or filter by address and topics.",
+		"trace_filter" =>
+			"Narrow your query: use a smaller block range or filter by from/to address.",
+		"trace_block" | "trace_replayTransaction" | "trace_call" | "trace_rawTransaction" =>
+			"Narrow your query: request fewer trace types.",
+		"parity_listAccounts" | "parity_listStorageKeys" =>
+			"Narrow your query: request a smaller page.",
+		_ => "Narrow your query.",
+	}
+}
+
+impl FromStr for ResponseLimits {
+	type Err = String;
+
+	/// Parses comma-separated `method=bytes` pairs; `*=bytes` sets the limit of all other methods.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut limits = ResponseLimits::default();
+		if s.trim() == "none" {
+			return Ok(limits);
+		}
+
+		for entry in s.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+			let mut parts = entry.splitn(2, '=');
+			let (method, size) = match (parts.next(), parts.next()) {
+				(Some(method), Some(size)) => (method.trim(), size.trim()),
+				_ => return Err(format!("Invalid response limit: {}. Expected METHOD=BYTES.", entry)),
+			};
+			let size = size.parse().map_err(|_| format!("Invalid response limit size: {}", size))?;
+
+			match method {
+				"*" => limits.default = Some(size),
+				method => { limits.methods.insert(method.into(), size); },
+			}
+		}
+
+		Ok(limits)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use jsonrpc_core as rpc;
+	use serde_json;
+	use super::{ResponseLimits, ResponseSize};
+	use v1::types::Log;
+
+	#[test]
+	fn should_parse_limits() {
+		let limits: ResponseLimits = "eth_getLogs=100, *=1000".parse().unwrap();
+
+		assert_eq!(limits.limit("eth_getLogs"), Some(100));
+		assert_eq!(limits.limit("eth_call"), Some(1000));
+		assert!("eth_getLogs".parse::<ResponseLimits>().is_err());
+		assert!("eth_getLogs=x".parse::<ResponseLimits>().is_err());
+		assert!("".parse::<ResponseLimits>().unwrap().is_empty());
+		assert!("none".parse::<ResponseLimits>().unwrap().is_empty());
+	}
+
+	#[test]
+	fn should_reject_too_large_responses() {
+		let limits: ResponseLimits = "eth_getLogs=10".parse().unwrap();
+		let request: rpc::Request = serde_json::from_str(r#"[
+			{"jsonrpc":"2.0","method":"eth_getLogs","params":[],"id":1},
+			{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":2}
+		]"#).unwrap();
+		let response: rpc::Response = serde_json::from_str(r#"[
+			{"jsonrpc":"2.0","result":"0x0123456789abcdef","id":1},
+			{"jsonrpc":"2.0","result":"0x0123456789abcdef","id":2}
+		]"#).unwrap();
+
+		let response = limits.enforce(&ResponseLimits::methods(&request), Some(response));
+
+		assert_eq!(serde_json::to_string(&response).unwrap(), r#"[{"jsonrpc":"2.0","error":{"code":-32046,"message":"Response too large (20 bytes, limit 10 bytes). Narrow your query: use a smaller block range or filter by address and topics.","data":{"details":{"hint":"Narrow your query: use a smaller block range or filter by address and topics.","limit":10,"method":"eth_getLogs","size":20},"reason":"responseTooLarge"}},"id":1},{"jsonrpc":"2.0","result":"0x0123456789abcdef","id":2}]"#);
+	}
+
+	#[test]
+	fn should_estimate_at_least_serialized_size() {
+		let log = Log {
+			address: Default::default(),
+			topics: vec![Default::default(); 4],
+			data: vec![0xff; 100].into(),
+			block_hash: Some(Default::default()),
+			block_number: Some(u64::max_value().into()),
+			transaction_hash: Some(Default::default()),
+			transaction_index: Some(u64::max_value().into()),
+			log_index: Some(u64::max_value().into()),
+			transaction_log_index: Some(u64::max_value().into()),
+			log_type: "mined".to_owned(),
+		};
+
+		let size = serde_json::to_string(&log).unwrap().len();
+		assert!(log.response_size() >= size);
+		assert!(log.response_size() < size + 100);
+	}
+
+	#[test]
+	fn should_check_estimated_size() {
+		let limits: ResponseLimits = "eth_getLogs=1000".parse().unwrap();
+		let log = Log {
+			address: Default::default(),
+			topics: vec![],
+			data: vec![].into(),
+			block_hash: None,
+			block_number: None,
+			transaction_hash: None,
+			transaction_index: None,
+			log_index: None,
+			transaction_log_index: None,
+			log_type: "pending".to_owned(),
+		};
+
+		assert!(limits.check("eth_getLogs", &[log.clone(), log.clone()]).is_ok());
+		assert!(limits.check("eth_getLogs", &[log.clone(), log.clone(), log.clone()]).is_err());
+		assert!(limits.check("eth_call", &[log.clone(), log.clone(), log]).is_ok());
+	}
+}
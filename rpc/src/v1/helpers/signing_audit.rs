@@ -0,0 +1,277 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Audit log of RPC calls unlocking, using or modifying accounts.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use jsonrpc_core as rpc;
+use serde_json;
+use util::{Mutex, RwLock};
+use v1::types::{H160, Origin, SigningAuditEntry};
+
+/// Number of most recent entries kept in memory.
+pub const AUDIT_MEMORY_LIMIT: usize = 4096;
+
+/// Audited methods. Parameters are never recorded, since they may contain passwords.
+const AUDITED_METHODS: &'static [&'static str] = &[
+	"eth_sign", "eth_sendTransaction", "eth_signTransaction",
+	"personal_newAccount", "personal_unlockAccount", "personal_sendTransaction", "personal_signAndSendTransaction",
+	"parity_postSign", "parity_postTransaction", "parity_decryptMessage",
+	"parity_newAccountFromPhrase", "parity_newAccountFromWallet", "parity_newAccountFromSecret",
+	"parity_testPassword", "parity_changePassword", "parity_killAccount", "parity_exportAccount",
+	"parity_deriveAddressHash", "parity_deriveAddressIndex", "parity_setEngineSigner",
+	"parity_newVault", "parity_openVault", "parity_closeVault", "parity_changeVaultPassword",
+	"parity_changeVault", "parity_setVaultMeta",
+	"signer_confirmRequest", "signer_confirmRequestWithToken", "signer_confirmRequestRaw", "signer_rejectRequest",
+];
+
+/// Audited call of a request.
+#[derive(Debug)]
+pub struct AuditedCall {
+	id: Option<rpc::Id>,
+	method: String,
+	account: Option<H160>,
+}
+
+/// Append-only log of account-affecting calls, optionally mirrored to a file.
+pub struct SigningAudit {
+	limit: usize,
+	entries: RwLock<VecDeque<SigningAuditEntry>>,
+	file: Option<Mutex<File>>,
+}
+
+impl SigningAudit {
+	/// Create an in-memory log keeping up to `limit` entries.
+	pub fn new(limit: usize) -> Self {
+		SigningAudit {
+			limit: limit,
+			entries: RwLock::new(VecDeque::new()),
+			file: None,
+		}
+	}
+
+	/// Open a log appending to given file. The most recent entries already in the file are loaded.
+	pub fn open(path: &str, limit: usize) -> Result<Self, String> {
+		let audit = SigningAudit::new(limit);
+		if let Ok(file) = File::open(path) {
+			for line in BufReader::new(file).lines().filter_map(Result::ok) {
+				match serde_json::from_str(&line) {
+					Ok(entry) => audit.push(entry),
+					Err(e) => warn!(target: "rpc", "Ignoring malformed signing audit entry: {}", e),
+				}
+			}
+		}
+
+		let file = fs::OpenOptions::new()
+			.append(true).create(true).open(path)
+			.map_err(|_| format!("Cannot write to signing audit log file given: {}", path))?;
+
+		Ok(SigningAudit {
+			file: Some(Mutex::new(file)),
+			..audit
+		})
+	}
+
+	/// Whether calls of given method are recorded.
+	pub fn is_audited(method: &str) -> bool {
+		AUDITED_METHODS.contains(&method)
+	}
+
+	/// Audited calls of a request.
+	pub fn calls(request: &rpc::Request) -> Vec<AuditedCall> {
+		fn call(call: &rpc::Call) -> Option<AuditedCall> {
+			let (id, method, params) = match *call {
+				rpc::Call::MethodCall(ref call) => (Some(call.id.clone()), &call.method, &call.params),
+				rpc::Call::Notification(ref notification) => (None, &notification.method, &notification.params),
+				rpc::Call::Invalid(_) => return None,
+			};
+
+			if !SigningAudit::is_audited(method) {
+				return None;
+			}
+
+			Some(AuditedCall {
+				id: id,
+				method: method.clone(),
+				account: params.as_ref().and_then(account),
+			})
+		}
+
+		match *request {
+			rpc::Request::Single(ref c) => call(c).into_iter().collect(),
+			rpc::Request::Batch(ref calls) => calls.iter().filter_map(call).collect(),
+		}
+	}
+
+	/// Record audited calls of a request together with their outcome.
+	pub fn record(&self, calls: Vec<AuditedCall>, origin: &Origin, response: Option<&rpc::Response>) {
+		let outputs: Vec<&rpc::Output> = match response {
+			Some(&rpc::Response::Single(ref output)) => vec![output],
+			Some(&rpc::Response::Batch(ref outputs)) => outputs.iter().collect(),
+			None => Vec::new(),
+		};
+		let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+		for call in calls {
+			let output = call.id.as_ref().and_then(|id| outputs.iter().find(|output| match ***output {
+				rpc::Output::Success(ref success) => success.id == *id,
+				rpc::Output::Failure(ref failure) => failure.id == *id,
+			}));
+			let (success, error) = match output {
+				Some(&&rpc::Output::Success(_)) => (Some(true), None),
+				Some(&&rpc::Output::Failure(ref failure)) => (Some(false), Some(failure.error.message.clone())),
+				None => (None, None),
+			};
+
+			let entry = SigningAuditEntry {
+				timestamp: timestamp,
+				method: call.method,
+				origin: origin.clone(),
+				account: call.account,
+				success: success,
+				error: error,
+			};
+
+			if let Some(ref file) = self.file {
+				let line = serde_json::to_string(&entry).expect("Audit entries are serializable; qed");
+				if let Err(e) = writeln!(&mut *file.lock(), "{}", line) {
+					warn!(target: "rpc", "Unable to write signing audit entry: {}", e);
+				}
+			}
+			self.push(entry);
+		}
+	}
+
+	/// Most recent entries (up to `count`, all kept in memory if `None`), oldest first.
+	pub fn entries(&self, count: Option<usize>) -> Vec<SigningAuditEntry> {
+		let entries = self.entries.read();
+		let skip = count.map_or(0, |count| entries.len().saturating_sub(count));
+		entries.iter().skip(skip).cloned().collect()
+	}
+
+	fn push(&self, entry: SigningAuditEntry) {
+		let mut entries = self.entries.write();
+		entries.push_back(entry);
+		while entries.len() > self.limit {
+			entries.pop_front();
+		}
+	}
+}
+
+/// Account a call refers to: the first parameter if it's an address, or the `from` field of a request.
+fn account(params: &rpc::Params) -> Option<H160> {
+	let first = match *params {
+		rpc::Params::Array(ref params) => match params.first() {
+			Some(first) => first,
+			None => return None,
+		},
+		_ => return None,
+	};
+
+	let address = match first.as_object().and_then(|request| request.get("from")) {
+		Some(from) => from,
+		None => first,
+	};
+	serde_json::from_value(address.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs::File;
+	use std::io::Read;
+	use devtools::RandomTempPath;
+	use jsonrpc_core as rpc;
+	use serde_json;
+	use v1::types::Origin;
+	use super::SigningAudit;
+
+	fn request() -> rpc::Request {
+		serde_json::from_str(r#"[
+			{"jsonrpc":"2.0","method":"personal_unlockAccount","params":["0x0000000000000000000000000000000000000001","password",null],"id":1},
+			{"jsonrpc":"2.0","method":"eth_blockNumber","params":[],"id":2},
+			{"jsonrpc":"2.0","method":"eth_sendTransaction","params":[{"from":"0x0000000000000000000000000000000000000002"}],"id":3}
+		]"#).unwrap()
+	}
+
+	fn response() -> rpc::Response {
+		serde_json::from_str(r#"[
+			{"jsonrpc":"2.0","result":true,"id":1},
+			{"jsonrpc":"2.0","result":"0x1","id":2},
+			{"jsonrpc":"2.0","error":{"code":-32020,"message":"Your account is locked."},"id":3}
+		]"#).unwrap()
+	}
+
+	#[test]
+	fn should_record_audited_calls() {
+		let audit = SigningAudit::new(10);
+		let request = request();
+
+		audit.record(SigningAudit::calls(&request), &Origin::Rpc("test".into()), Some(&response()));
+
+		let entries = audit.entries(None);
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].method, "personal_unlockAccount");
+		assert_eq!(entries[0].account, Some(1.into()));
+		assert_eq!(entries[0].success, Some(true));
+		assert_eq!(entries[1].method, "eth_sendTransaction");
+		assert_eq!(entries[1].account, Some(2.into()));
+		assert_eq!(entries[1].success, Some(false));
+		assert_eq!(entries[1].error, Some("Your account is locked.".into()));
+		assert_eq!(entries[1].origin, Origin::Rpc("test".into()));
+		assert_eq!(audit.entries(Some(1)), vec![entries[1].clone()]);
+	}
+
+	#[test]
+	fn should_audit_password_and_vault_methods() {
+		for method in &["parity_testPassword", "parity_setEngineSigner", "parity_openVault", "parity_changeVaultPassword"] {
+			assert!(SigningAudit::is_audited(method), "{} should be audited", method);
+		}
+		assert!(!SigningAudit::is_audited("parity_listVaults"));
+	}
+
+	#[test]
+	fn should_keep_limited_entries() {
+		let audit = SigningAudit::new(1);
+		audit.record(SigningAudit::calls(&request()), &Origin::Unknown, None);
+
+		let entries = audit.entries(None);
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].method, "eth_sendTransaction");
+		assert_eq!(entries[0].success, None);
+	}
+
+	#[test]
+	fn should_append_entries_to_file() {
+		let temp = RandomTempPath::new();
+		let path = temp.as_str();
+		{
+			let audit = SigningAudit::open(path, 10).unwrap();
+			audit.record(SigningAudit::calls(&request()), &Origin::Unknown, Some(&response()));
+		}
+
+		let mut content = String::new();
+		File::open(path).unwrap().read_to_string(&mut content).unwrap();
+		assert_eq!(content.lines().count(), 2);
+		assert!(!content.contains("password"));
+
+		let audit = SigningAudit::open(path, 10).unwrap();
+		assert_eq!(audit.entries(None).len(), 2);
+	}
+}
@@ -0,0 +1,300 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sync status change notifications.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ethcore::header::BlockNumber;
+use ethsync::{SyncStatus, SyncState};
+use serde::Serialize;
+use serde_json;
+use util::{Mutex, RwLock};
+use v1::metadata::Sink;
+use v1::types::{SyncEvent, SyncEventKind, U256};
+
+/// Method name of pushed notifications.
+pub const NOTIFICATION_METHOD: &'static str = "parity_subscription";
+
+#[derive(Serialize)]
+struct Notification<'a, T: 'a> {
+	jsonrpc: &'static str,
+	method: &'static str,
+	params: NotificationParams<'a, T>,
+}
+
+#[derive(Serialize)]
+struct NotificationParams<'a, T: 'a> {
+	subscription: U256,
+	result: &'a T,
+}
+
+/// Serialize a notification pushing `result` to subscription `id`.
+pub fn notification<T: Serialize>(id: usize, result: &T) -> String {
+	let notification = Notification {
+		jsonrpc: "2.0",
+		method: NOTIFICATION_METHOD,
+		params: NotificationParams {
+			subscription: id.into(),
+			result: result,
+		},
+	};
+	serde_json::to_string(&notification).expect("Serialization of notification is infallible; qed")
+}
+
+/// Detects sync status transitions from consecutive status snapshots.
+#[derive(Default)]
+struct Tracker {
+	syncing: bool,
+	warp_phase: Option<SyncEventKind>,
+	milestone: u8,
+	last_progress: Option<(BlockNumber, usize, Instant)>,
+	stalled: bool,
+	head_stall_rotations: usize,
+}
+
+impl Tracker {
+	fn transitions(&mut self, status: &SyncStatus, current: BlockNumber, stall_timeout: Duration, now: Instant) -> Vec<SyncEvent> {
+		let event = |kind, percentage| SyncEvent {
+			event: kind,
+			current_block: current.into(),
+			highest_block: status.highest_block_number.map(Into::into),
+			percentage: percentage,
+			warp_chunks_amount: match status.is_snapshot_syncing() {
+				true => Some(status.num_snapshot_chunks.into()),
+				false => None,
+			},
+			warp_chunks_processed: match status.is_snapshot_syncing() {
+				true => Some(status.snapshot_chunks_done.into()),
+				false => None,
+			},
+		};
+
+		let syncing = match status.state {
+			SyncState::Idle | SyncState::NewBlocks => false,
+			// losing all peers doesn't end the sync
+			SyncState::WaitingPeers => self.syncing,
+			_ => true,
+		};
+
+		let mut events = Vec::new();
+		// peers may be rotated by the chain head watchdog regardless of major sync
+		let head_stall_rotations = status.num_head_stall_rotations;
+		if head_stall_rotations > self.head_stall_rotations {
+			events.push(event(SyncEventKind::PeersRotated, None));
+		}
+		self.head_stall_rotations = head_stall_rotations;
+
+		match (self.syncing, syncing) {
+			(false, true) => {
+				*self = Tracker {
+					syncing: true,
+					last_progress: Some((current, status.snapshot_chunks_done, now)),
+					head_stall_rotations: head_stall_rotations,
+					..Default::default()
+				};
+				events.push(event(SyncEventKind::Started, None));
+			},
+			(true, false) => {
+				*self = Tracker {
+					head_stall_rotations: head_stall_rotations,
+					..Default::default()
+				};
+				events.push(event(SyncEventKind::Completed, None));
+				return events;
+			},
+			(false, false) => return events,
+			(true, true) => {},
+		}
+
+		let warp_phase = match status.state {
+			SyncState::SnapshotManifest => Some(SyncEventKind::WarpManifest),
+			SyncState::SnapshotData => Some(SyncEventKind::WarpData),
+			SyncState::SnapshotWaiting => Some(SyncEventKind::WarpRestoring),
+			_ => None,
+		};
+		if let Some(phase) = warp_phase {
+			if self.warp_phase != warp_phase {
+				events.push(event(phase, None));
+			}
+		}
+		self.warp_phase = warp_phase;
+
+		match status.highest_block_number {
+			Some(highest) if warp_phase.is_none() && highest > status.start_block_number => {
+				let done = current.saturating_sub(status.start_block_number);
+				let percentage = cmp::min(100, done * 100 / (highest - status.start_block_number)) as u8;
+				let milestone = percentage / 10 * 10;
+				if milestone > self.milestone {
+					self.milestone = milestone;
+					events.push(event(SyncEventKind::Progress, Some(milestone)));
+				}
+			},
+			_ => {},
+		}
+
+		match self.last_progress {
+			Some((block, chunks, since)) if block == current && chunks == status.snapshot_chunks_done => {
+				if !self.stalled && now.duration_since(since) >= stall_timeout {
+					self.stalled = true;
+					events.push(event(SyncEventKind::Stalled, None));
+				}
+			},
+			_ => {
+				self.last_progress = Some((current, status.snapshot_chunks_done, now));
+				self.stalled = false;
+			},
+		}
+
+		events
+	}
+}
+
+/// Subscriptions to sync status changes.
+pub struct SyncEvents {
+	stall_timeout: Duration,
+	subscribers: RwLock<HashMap<usize, Sink>>,
+	tracker: Mutex<Tracker>,
+}
+
+impl SyncEvents {
+	/// Create new subscriptions registry. Sync is reported as stalled after `stall_timeout` without progress.
+	pub fn new(stall_timeout: Duration) -> Self {
+		SyncEvents {
+			stall_timeout: stall_timeout,
+			subscribers: Default::default(),
+			tracker: Default::default(),
+		}
+	}
+
+	/// Add a subscriber with given subscription id.
+	pub fn subscribe(&self, id: usize, sink: Sink) {
+		self.subscribers.write().insert(id, sink);
+	}
+
+	/// Remove a subscriber, returns `false` if there was no such subscription.
+	pub fn unsubscribe(&self, id: usize) -> bool {
+		self.subscribers.write().remove(&id).is_some()
+	}
+
+	/// Process current sync status and best block number, notifying subscribers about transitions.
+	pub fn update(&self, status: &SyncStatus, best_block: BlockNumber) {
+		let events = self.tracker.lock().transitions(status, best_block, self.stall_timeout, Instant::now());
+		if events.is_empty() || self.subscribers.read().is_empty() {
+			return;
+		}
+
+		let mut closed = Vec::new();
+		for (id, sink) in self.subscribers.read().iter() {
+			for event in &events {
+				if !sink.send(notification(*id, event)) {
+					closed.push(*id);
+					break;
+				}
+			}
+		}
+
+		if !closed.is_empty() {
+			let mut subscribers = self.subscribers.write();
+			for id in closed {
+				trace!(target: "rpc", "Dropping closed sync status subscription {}", id);
+				subscribers.remove(&id);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use std::time::{Duration, Instant};
+	use ethsync::{SyncStatus, SyncState};
+	use util::Mutex;
+	use v1::metadata::Sink;
+	use v1::types::SyncEventKind;
+	use super::{SyncEvents, Tracker};
+
+	fn status(state: SyncState, highest: u64) -> SyncStatus {
+		SyncStatus {
+			state: state,
+			protocol_version: 63,
+			network_id: 1,
+			start_block_number: 0,
+			last_imported_block_number: None,
+			highest_block_number: Some(highest),
+			blocks_total: 0,
+			blocks_received: 0,
+			num_peers: 1,
+			num_active_peers: 1,
+			mem_used: 0,
+			num_snapshot_chunks: 10,
+			snapshot_chunks_done: 0,
+			last_imported_old_block_number: None,
+			num_head_stall_rotations: 0,
+		}
+	}
+
+	fn kinds(tracker: &mut Tracker, state: SyncState, current: u64, now: Instant) -> Vec<SyncEventKind> {
+		tracker.transitions(&status(state, 100), current, Duration::from_secs(10), now)
+			.into_iter()
+			.map(|event| event.event)
+			.collect()
+	}
+
+	#[test]
+	fn should_detect_transitions() {
+		let mut tracker = Tracker::default();
+		let now = Instant::now();
+
+		assert_eq!(kinds(&mut tracker, SyncState::Idle, 0, now), vec![]);
+		assert_eq!(kinds(&mut tracker, SyncState::SnapshotManifest, 0, now), vec![SyncEventKind::Started, SyncEventKind::WarpManifest]);
+		assert_eq!(kinds(&mut tracker, SyncState::SnapshotData, 0, now), vec![SyncEventKind::WarpData]);
+		assert_eq!(kinds(&mut tracker, SyncState::Blocks, 25, now), vec![SyncEventKind::Progress]);
+		assert_eq!(kinds(&mut tracker, SyncState::Blocks, 27, now), vec![]);
+		assert_eq!(kinds(&mut tracker, SyncState::WaitingPeers, 27, now + Duration::from_secs(11)), vec![SyncEventKind::Stalled]);
+		assert_eq!(kinds(&mut tracker, SyncState::Blocks, 27, now + Duration::from_secs(12)), vec![]);
+		assert_eq!(kinds(&mut tracker, SyncState::Idle, 100, now), vec![SyncEventKind::Completed]);
+	}
+
+	#[test]
+	fn should_report_peer_rotations() {
+		let mut tracker = Tracker::default();
+		let now = Instant::now();
+		let mut rotated = status(SyncState::Idle, 100);
+		rotated.num_head_stall_rotations = 1;
+
+		let events: Vec<_> = tracker.transitions(&rotated, 50, Duration::from_secs(10), now).into_iter().map(|e| e.event).collect();
+		assert_eq!(events, vec![SyncEventKind::PeersRotated]);
+		assert!(tracker.transitions(&rotated, 50, Duration::from_secs(10), now).is_empty());
+	}
+
+	#[test]
+	fn should_notify_subscribers() {
+		let events = SyncEvents::new(Duration::from_secs(10));
+		let received = Arc::new(Mutex::new(Vec::new()));
+		let r = received.clone();
+		events.subscribe(1, Sink::new(move |message| { r.lock().push(message); true }));
+		events.subscribe(2, Sink::new(|_| false));
+
+		events.update(&status(SyncState::Blocks, 100), 0);
+
+		assert_eq!(*received.lock(), vec![r#"{"jsonrpc":"2.0","method":"parity_subscription","params":{"subscription":"0x1","result":{"event":"started","currentBlock":"0x0","highestBlock":"0x64","percentage":null,"warpChunksAmount":null,"warpChunksProcessed":null}}}"#.to_owned()]);
+		assert!(!events.unsubscribe(2));
+		assert!(events.unsubscribe(1));
+	}
+}
@@ -0,0 +1,88 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction queue event notifications.
+
+use std::collections::HashMap;
+
+use ethcore::miner::TransactionEvent;
+use util::RwLock;
+use v1::helpers::sync_events::notification;
+use v1::metadata::Sink;
+use v1::types::TransactionQueueEvent;
+
+/// Subscriptions to transaction queue events.
+#[derive(Default)]
+pub struct TransactionEvents {
+	subscribers: RwLock<HashMap<usize, Sink>>,
+}
+
+impl TransactionEvents {
+	/// Add a subscriber with given subscription id.
+	pub fn subscribe(&self, id: usize, sink: Sink) {
+		self.subscribers.write().insert(id, sink);
+	}
+
+	/// Remove a subscriber, returns `false` if there was no such subscription.
+	pub fn unsubscribe(&self, id: usize) -> bool {
+		self.subscribers.write().remove(&id).is_some()
+	}
+
+	/// Push queue event to all subscribers.
+	pub fn notify(&self, event: &TransactionEvent) {
+		if self.subscribers.read().is_empty() {
+			return;
+		}
+
+		let event = TransactionQueueEvent::from(event.clone());
+		let closed = self.subscribers.read().iter()
+			.filter(|&(id, sink)| !sink.send(notification(*id, &event)))
+			.map(|(id, _)| *id)
+			.collect::<Vec<_>>();
+
+		if !closed.is_empty() {
+			let mut subscribers = self.subscribers.write();
+			for id in closed {
+				trace!(target: "rpc", "Dropping closed transaction queue subscription {}", id);
+				subscribers.remove(&id);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethcore::miner::{TransactionEvent, DropReason};
+	use util::Mutex;
+	use v1::metadata::Sink;
+	use super::TransactionEvents;
+
+	#[test]
+	fn should_notify_subscribers() {
+		let events = TransactionEvents::default();
+		let received = Arc::new(Mutex::new(Vec::new()));
+		let r = received.clone();
+		events.subscribe(1, Sink::new(move |message| { r.lock().push(message); true }));
+		events.subscribe(2, Sink::new(|_| false));
+
+		events.notify(&TransactionEvent::Dropped(5.into(), DropReason::Limit));
+
+		assert_eq!(*received.lock(), vec![r#"{"jsonrpc":"2.0","method":"parity_subscription","params":{"subscription":"0x1","result":{"event":"dropped","hash":"0x0000000000000000000000000000000000000000000000000000000000000005","pending":null,"reason":"limit","replacedBy":null}}}"#.to_owned()]);
+		assert!(!events.unsubscribe(2));
+		assert!(events.unsubscribe(1));
+	}
+}
@@ -0,0 +1,149 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Forwarding of queries a light client can't answer to a trusted full node.
+//!
+//! Responses can't be proved against the headers the light client has verified.
+//! Forwarded logs are marked with the `unverified` type, code is checked against
+//! the proved account's code hash, and storage is only forwarded for accounts
+//! proved to have non-empty storage; those values are taken on trust.
+
+use std::io::Read;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use fetch::Client as FetchClient;
+use futures::{Future, BoxFuture};
+use jsonrpc_core::Error;
+use serde::{Serialize, Deserialize};
+use serde_json::{self, Value};
+use v1::helpers::errors;
+
+/// `type` of logs served by the trusted node instead of the network.
+pub const UNVERIFIED_LOG_TYPE: &'static str = "unverified";
+
+#[derive(Serialize)]
+struct Request<'a> {
+	jsonrpc: &'static str,
+	id: usize,
+	method: &'a str,
+	params: Vec<Value>,
+}
+
+/// JSON-RPC endpoint of a full node trusted to answer heavy queries.
+pub struct TrustedFallback {
+	url: String,
+	fetch: FetchClient,
+	next_id: AtomicUsize,
+}
+
+impl TrustedFallback {
+	/// Forward queries to the JSON-RPC endpoint at given URL.
+	pub fn new(url: String, fetch: FetchClient) -> Self {
+		TrustedFallback {
+			url: url,
+			fetch: fetch,
+			next_id: AtomicUsize::new(1),
+		}
+	}
+
+	/// URL of the trusted endpoint.
+	pub fn url(&self) -> &str {
+		&self.url
+	}
+
+	/// Call given method on the trusted node.
+	pub fn call<T>(&self, method: &str, params: Vec<Value>) -> BoxFuture<T, Error> where
+		T: Deserialize + Send + 'static,
+	{
+		let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+		debug!(target: "rpc", "Forwarding {} to trusted node at {}", method, self.url);
+
+		self.fetch.post_json(&self.url, request(id, method, params), Vec::new())
+			.map_err(errors::from_fetch_error)
+			.and_then(|mut response| {
+				if !response.is_success() {
+					return Err(errors::trusted_fallback(format!("HTTP status {}", response.status())));
+				}
+
+				let mut body = Vec::new();
+				response.read_to_end(&mut body).map_err(errors::from_fetch_error)?;
+				parse_response(&body)
+			})
+			.boxed()
+	}
+}
+
+/// Convert a call parameter to JSON.
+pub fn param<T: Serialize>(value: T) -> Value {
+	serde_json::to_value(value).expect("RPC types always serialize; qed")
+}
+
+fn request(id: usize, method: &str, params: Vec<Value>) -> Vec<u8> {
+	serde_json::to_vec(&Request {
+		jsonrpc: "2.0",
+		id: id,
+		method: method,
+		params: params,
+	}).expect("Serialization of a request can't fail; qed")
+}
+
+fn parse_response<T: Deserialize>(body: &[u8]) -> Result<T, Error> {
+	let invalid = |e: serde_json::Error| errors::trusted_fallback(format!("Invalid response: {}", e));
+
+	let mut response = match serde_json::from_slice::<Value>(body).map_err(&invalid)? {
+		Value::Object(response) => response,
+		_ => return Err(errors::trusted_fallback("Invalid response: not an object".into())),
+	};
+
+	if let Some(error) = response.remove("error") {
+		return Err(errors::trusted_fallback(error.to_string()));
+	}
+
+	let result = response.remove("result").unwrap_or(Value::Null);
+	serde_json::from_value(result).map_err(&invalid)
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::Value;
+	use v1::types::U256;
+	use super::{request, parse_response};
+
+	#[test]
+	fn builds_request() {
+		let req = request(5, "eth_getLogs", vec![Value::String("latest".into())]);
+		assert_eq!(String::from_utf8(req).unwrap(), r#"{"jsonrpc":"2.0","id":5,"method":"eth_getLogs","params":["latest"]}"#);
+	}
+
+	#[test]
+	fn parses_result() {
+		let res: U256 = parse_response(br#"{"jsonrpc":"2.0","id":1,"result":"0x10"}"#).unwrap();
+		assert_eq!(res, 16.into());
+	}
+
+	#[test]
+	fn passes_errors_through() {
+		let res = parse_response::<U256>(br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"pruned"}}"#);
+		let err = res.unwrap_err();
+		assert_eq!(err.data, Some(Value::String(r#"{"code":-32000,"message":"pruned"}"#.into())));
+	}
+
+	#[test]
+	fn rejects_garbage() {
+		assert!(parse_response::<U256>(b"not json").is_err());
+		assert!(parse_response::<U256>(br#"{"jsonrpc":"2.0","id":1,"result":"nope"}"#).is_err());
+	}
+}
@@ -0,0 +1,213 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Authentication of mining workers using `eth_getWork` and `eth_submitWork`.
+//!
+//! Each worker is identified by a name and authenticates submissions with its own token
+//! `HMAC-SHA256(secret, "worker:" ++ name)`, so knowing one token doesn't allow to submit
+//! as another worker. Work packages are signed by a key derived from the secret: rigs
+//! check them against the signer's address and don't need the secret itself.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crypto::hmac_sha256;
+use ethkey::{self, Brain, Generator, KeyPair};
+use jsonrpc_core::Error;
+use util::{Address, H256, H520, Hashable, Mutex};
+use v1::helpers::errors;
+
+const RATE_WINDOW_SECS: u64 = 60;
+const WORKER_PREFIX: &'static [u8] = b"worker:";
+const SIGNER_PREFIX: &'static str = "work-signer:";
+
+#[derive(Debug)]
+struct Worker {
+	window_start: Instant,
+	window_submissions: u32,
+	accepted: u64,
+	rejected: u64,
+}
+
+/// Submissions of a worker so far.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkerStats {
+	/// Solutions accepted.
+	pub accepted: u64,
+	/// Solutions rejected.
+	pub rejected: u64,
+}
+
+/// Secret of the node and per-worker bookkeeping.
+pub struct WorkAuth {
+	secret: Vec<u8>,
+	signer: KeyPair,
+	submissions_per_minute: Option<u32>,
+	workers: Mutex<HashMap<String, Worker>>,
+}
+
+impl WorkAuth {
+	/// Authenticate with given secret, allowing each worker at most `submissions_per_minute` if set.
+	pub fn new(secret: &str, submissions_per_minute: Option<u32>) -> Self {
+		WorkAuth {
+			secret: secret.as_bytes().to_vec(),
+			signer: Brain::new(format!("{}{}", SIGNER_PREFIX, secret)).generate()
+				.expect("brain wallet generation is infallible; qed"),
+			submissions_per_minute: submissions_per_minute,
+			workers: Mutex::new(HashMap::new()),
+		}
+	}
+
+	/// Token the given worker authenticates with.
+	pub fn token(&self, worker: &str) -> H256 {
+		let mut data = WORKER_PREFIX.to_vec();
+		data.extend_from_slice(worker.as_bytes());
+		H256::from(hmac_sha256(&self.secret, &data))
+	}
+
+	/// Address of the key signing work packages.
+	pub fn signer(&self) -> Address {
+		self.signer.address()
+	}
+
+	/// Signature of a work package, over the hash of `pow_hash ++ seed_hash ++ target`.
+	pub fn sign_work(&self, pow_hash: &H256, seed_hash: &H256, target: &H256) -> H520 {
+		ethkey::sign(self.signer.secret(), &work_message(pow_hash, seed_hash, target))
+			.expect("the signer's secret is valid and the message is a hash; qed")
+			.into()
+	}
+
+	/// Check the credentials of a submission and count it against the worker's rate limit.
+	pub fn authorize_submission(&self, worker: &str, token: &H256) -> Result<(), Error> {
+		if worker.is_empty() || !constant_time_eq(&self.token(worker), token) {
+			return Err(errors::worker_unauthorized());
+		}
+
+		let now = Instant::now();
+		let mut workers = self.workers.lock();
+		let entry = workers.entry(worker.to_owned()).or_insert_with(|| Worker {
+			window_start: now,
+			window_submissions: 0,
+			accepted: 0,
+			rejected: 0,
+		});
+
+		if now.duration_since(entry.window_start) >= Duration::from_secs(RATE_WINDOW_SECS) {
+			entry.window_start = now;
+			entry.window_submissions = 0;
+		}
+		match self.submissions_per_minute {
+			Some(limit) if entry.window_submissions >= limit => Err(errors::worker_rate_limited(limit)),
+			_ => {
+				entry.window_submissions += 1;
+				Ok(())
+			},
+		}
+	}
+
+	/// Record the outcome of an authorized submission.
+	pub fn note_result(&self, worker: &str, accepted: bool) {
+		if let Some(entry) = self.workers.lock().get_mut(worker) {
+			match accepted {
+				true => entry.accepted += 1,
+				false => entry.rejected += 1,
+			}
+			debug!(target: "miner", "Worker {} submitted a {} solution ({} accepted, {} rejected)",
+				worker, if accepted { "valid" } else { "invalid" }, entry.accepted, entry.rejected);
+		}
+	}
+
+	/// Submissions of given worker.
+	pub fn stats(&self, worker: &str) -> Option<WorkerStats> {
+		self.workers.lock().get(worker).map(|entry| WorkerStats {
+			accepted: entry.accepted,
+			rejected: entry.rejected,
+		})
+	}
+}
+
+/// Message signed for a work package.
+pub fn work_message(pow_hash: &H256, seed_hash: &H256, target: &H256) -> H256 {
+	let mut data = Vec::with_capacity(96);
+	data.extend_from_slice(&**pow_hash);
+	data.extend_from_slice(&**seed_hash);
+	data.extend_from_slice(&**target);
+	data.sha3()
+}
+
+// compare without leaking the position of the first difference through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+	use ethkey::{recover, public_to_address, Signature};
+	use util::H256;
+	use super::{WorkAuth, WorkerStats, work_message, constant_time_eq};
+
+	#[test]
+	fn authenticates_workers() {
+		let auth = WorkAuth::new("secret", None);
+		let token = auth.token("rig1");
+
+		assert!(auth.authorize_submission("rig1", &token).is_ok());
+		assert!(auth.authorize_submission("rig2", &token).is_err());
+		assert!(auth.authorize_submission("", &auth.token("")).is_err());
+		assert!(WorkAuth::new("other", None).authorize_submission("rig1", &token).is_err());
+	}
+
+	#[test]
+	fn signs_work_packages() {
+		let auth = WorkAuth::new("secret", None);
+		let (pow_hash, seed_hash, target) = (H256::from(1), H256::from(2), H256::from(3));
+		let signature = Signature::from(auth.sign_work(&pow_hash, &seed_hash, &target));
+
+		let public = recover(&signature, &work_message(&pow_hash, &seed_hash, &target)).unwrap();
+		assert_eq!(public_to_address(&public), auth.signer());
+		assert!(WorkAuth::new("other", None).signer() != auth.signer());
+	}
+
+	#[test]
+	fn compares_in_constant_time() {
+		assert!(constant_time_eq(b"token", b"token"));
+		assert!(!constant_time_eq(b"token", b"tokem"));
+		assert!(!constant_time_eq(b"token", b"tokens"));
+	}
+
+	#[test]
+	fn limits_submission_rate() {
+		let auth = WorkAuth::new("secret", Some(2));
+		let token = auth.token("rig1");
+
+		assert!(auth.authorize_submission("rig1", &token).is_ok());
+		assert!(auth.authorize_submission("rig1", &token).is_ok());
+		assert!(auth.authorize_submission("rig1", &token).is_err());
+		assert!(auth.authorize_submission("rig2", &auth.token("rig2")).is_ok());
+	}
+
+	#[test]
+	fn counts_results() {
+		let auth = WorkAuth::new("secret", None);
+		auth.authorize_submission("rig1", &auth.token("rig1")).unwrap();
+		auth.note_result("rig1", true);
+		auth.note_result("rig1", false);
+		auth.note_result("rig2", true);
+
+		assert_eq!(auth.stats("rig1"), Some(WorkerStats { accepted: 1, rejected: 1 }));
+		assert_eq!(auth.stats("rig2"), None);
+	}
+}
@@ -0,0 +1,181 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tracking of work packages handed out by `eth_getWork` and of the solutions
+//! workers submit for them.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use util::{H256, Mutex};
+
+/// Number of most recently handed out work packages remembered.
+const MAX_ISSUED: usize = 64;
+/// Number of workers whose submissions are counted. Beyond that the least recently
+/// active worker is forgotten, since worker names are chosen by the submitters.
+const MAX_WORKERS: usize = 1024;
+/// Longest worker name whose submissions are counted.
+const MAX_WORKER_NAME: usize = 64;
+
+/// Outcome of a submitted solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submission {
+	/// Solution sealed a block.
+	Accepted,
+	/// Solution was for a work package which was superseded or already solved.
+	Stale,
+	/// Solution was invalid or for unknown work.
+	Rejected,
+}
+
+/// Submissions of a worker so far.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WorkerSubmissions {
+	/// Solutions accepted.
+	pub accepted: u64,
+	/// Solutions for stale work.
+	pub stale: u64,
+	/// Solutions rejected otherwise.
+	pub rejected: u64,
+}
+
+#[derive(Default)]
+struct Workers {
+	// submissions and the time of the last one, by worker name.
+	entries: BTreeMap<String, (WorkerSubmissions, u64)>,
+	// incremented with every submission.
+	clock: u64,
+}
+
+/// Recently handed out work packages and per-worker submission counters.
+#[derive(Default)]
+pub struct WorkTracker {
+	issued: Mutex<VecDeque<H256>>,
+	workers: Mutex<Workers>,
+}
+
+impl WorkTracker {
+	/// Note that a work package with given PoW hash was handed out.
+	pub fn note_issued(&self, pow_hash: &H256) {
+		let mut issued = self.issued.lock();
+		if issued.back() == Some(pow_hash) {
+			return;
+		}
+		if issued.len() == MAX_ISSUED {
+			issued.pop_front();
+		}
+		issued.push_back(pow_hash.clone());
+	}
+
+	/// Whether a work package with given PoW hash was recently handed out.
+	pub fn was_issued(&self, pow_hash: &H256) -> bool {
+		self.issued.lock().contains(pow_hash)
+	}
+
+	/// Record the outcome of a worker's submission.
+	pub fn note_submission(&self, worker: &str, submission: Submission) {
+		if worker.len() > MAX_WORKER_NAME {
+			trace!(target: "miner", "Not counting submission of worker with {}-byte name", worker.len());
+			return;
+		}
+
+		let mut guard = self.workers.lock();
+		let workers = &mut *guard;
+		workers.clock += 1;
+		let clock = workers.clock;
+
+		if workers.entries.len() == MAX_WORKERS && !workers.entries.contains_key(worker) {
+			let idle = workers.entries.iter()
+				.min_by_key(|&(_, &(_, last_seen))| last_seen)
+				.map(|(name, _)| name.clone())
+				.expect("MAX_WORKERS entries are present; qed");
+			workers.entries.remove(&idle);
+		}
+
+		let &mut (ref mut entry, ref mut last_seen) = workers.entries.entry(worker.to_owned())
+			.or_insert_with(|| (WorkerSubmissions::default(), 0));
+		*last_seen = clock;
+		match submission {
+			Submission::Accepted => entry.accepted += 1,
+			Submission::Stale => entry.stale += 1,
+			Submission::Rejected => entry.rejected += 1,
+		}
+		trace!(target: "miner", "Worker {:?} submitted {:?} solution: {:?}", worker, submission, entry);
+	}
+
+	/// Submissions of all workers, by name.
+	pub fn workers(&self) -> BTreeMap<String, WorkerSubmissions> {
+		self.workers.lock().entries.iter()
+			.map(|(name, &(submissions, _))| (name.clone(), submissions))
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::H256;
+	use super::{WorkTracker, Submission, WorkerSubmissions, MAX_ISSUED, MAX_WORKERS, MAX_WORKER_NAME};
+
+	#[test]
+	fn remembers_recently_issued_work() {
+		let tracker = WorkTracker::default();
+		tracker.note_issued(&H256::from(0));
+		for i in 0..MAX_ISSUED {
+			tracker.note_issued(&H256::from(i as u64 + 1));
+		}
+
+		assert!(!tracker.was_issued(&H256::from(0)));
+		assert!(tracker.was_issued(&H256::from(1)));
+		assert!(tracker.was_issued(&H256::from(MAX_ISSUED as u64)));
+	}
+
+	#[test]
+	fn counts_submissions_per_worker() {
+		let tracker = WorkTracker::default();
+		tracker.note_submission("rig1", Submission::Accepted);
+		tracker.note_submission("rig1", Submission::Stale);
+		tracker.note_submission("rig1", Submission::Stale);
+		tracker.note_submission("rig2", Submission::Rejected);
+
+		let workers = tracker.workers();
+		assert_eq!(workers["rig1"], WorkerSubmissions { accepted: 1, stale: 2, rejected: 0 });
+		assert_eq!(workers["rig2"], WorkerSubmissions { accepted: 0, stale: 0, rejected: 1 });
+	}
+
+	#[test]
+	fn forgets_least_recently_active_workers() {
+		let tracker = WorkTracker::default();
+		for i in 0..MAX_WORKERS {
+			tracker.note_submission(&format!("rig{}", i), Submission::Rejected);
+		}
+		tracker.note_submission("rig0", Submission::Accepted);
+		tracker.note_submission("new", Submission::Accepted);
+
+		let workers = tracker.workers();
+		assert_eq!(workers.len(), MAX_WORKERS);
+		assert!(workers.contains_key("rig0"));
+		assert!(!workers.contains_key("rig1"));
+		assert!(workers.contains_key("new"));
+	}
+
+	#[test]
+	fn ignores_long_worker_names() {
+		let tracker = WorkTracker::default();
+		let name = ::std::iter::repeat('x').take(MAX_WORKER_NAME + 1).collect::<String>();
+		tracker.note_submission(&name, Submission::Rejected);
+
+		assert!(tracker.workers().is_empty());
+	}
+}
@@ -31,6 +31,7 @@ use ethash::SeedHashCompute;
 use ethcore::account_provider::{AccountProvider, DappId};
 use ethcore::block::IsBlock;
 use ethcore::client::{MiningBlockChainClient, BlockId, TransactionId, UncleId};
+use ethcore::error::Error as EthcoreError;
 use ethcore::ethereum::Ethash;
 use ethcore::filter::Filter as EthcoreFilter;
 use ethcore::header::{Header as BlockHeader, BlockNumber as EthBlockNumber};
@@ -44,12 +45,19 @@ use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 
 use v1::helpers::{errors, limit_logs, fake_sign};
+use v1::helpers::call_cache::{self, CallCache, CallKind, CallResult};
 use v1::helpers::dispatch::{Dispatcher, FullDispatcher, default_gas_price};
+use v1::helpers::block_id::{BlockIdResolver, ResolvedBlock};
 use v1::helpers::block_import::is_major_importing;
+use v1::helpers::missing_cache::{MissingCache, LookupKind};
+use v1::helpers::receipt_proof::prove_receipt;
+use v1::helpers::response_limits::{self, ResponseLimits};
+use v1::helpers::work_auth::WorkAuth;
+use v1::helpers::work_tracker::{WorkTracker, Submission};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, ReceiptOptions, ReceiptProof, Work, WorkerCredentials,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -64,6 +72,16 @@ pub struct EthClientOptions {
 	pub allow_pending_receipt_query: bool,
 	/// Send additional block number when asking for work
 	pub send_block_number_in_get_work: bool,
+	/// Cache of `eth_call` and `eth_estimateGas` results (possibly shared between transports).
+	pub call_cache: Option<Arc<CallCache>>,
+	/// Cache of block, transaction and receipt hashes not found in the chain (possibly shared between transports).
+	pub missing_cache: Option<Arc<MissingCache>>,
+	/// Signs work packages and authenticates workers submitting solutions.
+	pub work_auth: Option<Arc<WorkAuth>>,
+	/// Tells stale solutions apart and counts submissions per worker.
+	pub work_tracker: Option<Arc<WorkTracker>>,
+	/// Bounds the size of `eth_getLogs` results while they are being built.
+	pub response_limits: Option<Arc<ResponseLimits>>,
 }
 
 impl EthClientOptions {
@@ -82,6 +100,11 @@ impl Default for EthClientOptions {
 			pending_nonce_from_queue: false,
 			allow_pending_receipt_query: true,
 			send_block_number_in_get_work: true,
+			call_cache: None,
+			missing_cache: None,
+			work_auth: None,
+			work_tracker: None,
+			response_limits: None,
 		}
 	}
 }
@@ -133,6 +156,52 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> EthClient<C, SN, S, M, EM> where
 		}
 	}
 
+	/// Current best block, block the call is executed at and hash of the call request,
+	/// if the call cache is enabled (pending state is never cached).
+	fn call_cache_key(&self, block: &ResolvedBlock, signed: &SignedTransaction) -> Option<(H256, H256, H256)> {
+		let header = match (self.options.call_cache.as_ref(), block) {
+			(Some(_), &ResolvedBlock::Block { ref header, .. }) => header,
+			_ => return None,
+		};
+
+		self.client.upgrade()
+			.map(|client| (client.chain_info().best_block_hash, header.hash(), call_cache::request_hash(signed)))
+	}
+
+	fn cached_call(&self, key: &Option<(H256, H256, H256)>, kind: CallKind) -> Option<CallResult> {
+		match (self.options.call_cache.as_ref(), key.as_ref()) {
+			(Some(cache), Some(&(ref head, ref block, ref request))) => cache.get(head, block, kind, request),
+			_ => None,
+		}
+	}
+
+	fn cache_call(&self, key: &Option<(H256, H256, H256)>, kind: CallKind, result: CallResult) {
+		if let (Some(cache), Some(&(ref head, ref block, ref request))) = (self.options.call_cache.as_ref(), key.as_ref()) {
+			cache.insert(head, block, kind, request, result);
+		}
+	}
+
+	/// Current best block, if the missing hashes cache is enabled.
+	fn missing_cache_head(&self) -> Option<H256> {
+		match self.options.missing_cache {
+			Some(_) => self.client.upgrade().map(|client| client.chain_info().best_block_hash),
+			None => None,
+		}
+	}
+
+	fn is_known_missing(&self, head: &Option<H256>, kind: LookupKind, hash: &H256) -> bool {
+		match (self.options.missing_cache.as_ref(), head.as_ref()) {
+			(Some(cache), Some(head)) => cache.is_missing(head, kind, hash),
+			_ => false,
+		}
+	}
+
+	fn note_missing(&self, head: &Option<H256>, kind: LookupKind, hash: &H256) {
+		if let (Some(cache), Some(head)) = (self.options.missing_cache.as_ref(), head.as_ref()) {
+			cache.note_missing(head, kind, hash);
+		}
+	}
+
 	fn block(&self, id: BlockId, include_txs: bool) -> Result<Option<RichBlock>, Error> {
 		let client = take_weak!(self.client);
 		match (client.block(id.clone()), client.block_total_difficulty(id)) {
@@ -250,15 +319,6 @@ pub fn pending_logs<M>(miner: &M, best_block: EthBlockNumber, filter: &EthcoreFi
 	result
 }
 
-fn check_known<C>(client: &C, number: BlockNumber) -> Result<(), Error> where C: MiningBlockChainClient {
-	use ethcore::block_status::BlockStatus;
-
-	match client.block_status(number.into()) {
-		BlockStatus::InChain => Ok(()),
-		_ => Err(errors::unknown_block()),
-	}
-}
-
 const MAX_QUEUE_SIZE_TO_MINE_ON: usize = 4;	// because uncles go back 6.
 
 impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
@@ -354,16 +414,15 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let address = address.into();
 		let client = take_weakf!(self.client);
 
-		let res = match num.0.clone() {
-			BlockNumber::Pending => {
+		let res = match try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0)) {
+			ResolvedBlock::Pending => {
 				match take_weakf!(self.miner).balance(&*client, &address) {
 					Some(balance) => Ok(balance.into()),
 					None => Err(errors::database_error("latest balance missing"))
 				}
 			}
-			id => {
-				try_bf!(check_known(&*client, id.clone()));
-				match client.balance(&address, id.into()) {
+			block => {
+				match client.balance(&address, block.id()) {
 					Some(balance) => Ok(balance.into()),
 					None => Err(errors::state_pruned()),
 				}
@@ -377,19 +436,17 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let address: Address = RpcH160::into(address);
 		let position: U256 = RpcU256::into(pos);
 
-		let res = match num.0.clone() {
-			BlockNumber::Pending => {
-				let client = take_weakf!(self.client);
+		let client = take_weakf!(self.client);
+
+		let res = match try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0)) {
+			ResolvedBlock::Pending => {
 				match take_weakf!(self.miner).storage_at(&*client, &address, &H256::from(position)) {
 					Some(s) => Ok(s.into()),
 					None => Err(errors::database_error("latest storage missing"))
 				}
 			}
-			id => {
-				let client = take_weakf!(self.client);
-
-				try_bf!(check_known(&*client, id.clone()));
-				match client.storage_at(&address, &H256::from(position), id.into()) {
+			block => {
+				match client.storage_at(&address, &H256::from(position), block.id()) {
 					Some(s) => Ok(s.into()),
 					None => Err(errors::state_pruned()),
 				}
@@ -404,8 +461,8 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let client = take_weakf!(self.client);
 		let miner = take_weakf!(self.miner);
 
-		let res = match num.0.clone() {
-			BlockNumber::Pending if self.options.pending_nonce_from_queue => {
+		let res = match try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0)) {
+			ResolvedBlock::Pending if self.options.pending_nonce_from_queue => {
 				let nonce = miner.last_nonce(&address)
 					.map(|n| n + 1.into())
 					.or_else(|| miner.nonce(&*client, &address));
@@ -414,15 +471,14 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 					None => Err(errors::database_error("latest nonce missing"))
 				}
 			}
-			BlockNumber::Pending => {
+			ResolvedBlock::Pending => {
 				match miner.nonce(&*client, &address) {
 					Some(nonce) => Ok(nonce.into()),
 					None => Err(errors::database_error("latest nonce missing"))
 				}
 			}
-			id => {
-				try_bf!(check_known(&*client, id.clone()));
-				match client.nonce(&address, id.into()) {
+			block => {
+				match client.nonce(&address, block.id()) {
 					Some(nonce) => Ok(nonce.into()),
 					None => Err(errors::state_pruned()),
 				}
@@ -466,19 +522,17 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	fn code_at(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
 		let address: Address = RpcH160::into(address);
 
-		let res = match num.0.clone() {
-			BlockNumber::Pending => {
-				let client = take_weakf!(self.client);
+		let client = take_weakf!(self.client);
+
+		let res = match try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0)) {
+			ResolvedBlock::Pending => {
 				match take_weakf!(self.miner).code(&*client, &address) {
 					Some(code) => Ok(code.map_or_else(Bytes::default, Bytes::new)),
 					None => Err(errors::database_error("latest code missing"))
 				}
 			}
-			id => {
-				let client = take_weakf!(self.client);
-
-				try_bf!(check_known(&*client, id.clone()));
-				match client.code(&address, id.into()) {
+			block => {
+				match client.code(&address, block.id()) {
 					Some(code) => Ok(code.map_or_else(Bytes::default, Bytes::new)),
 					None => Err(errors::state_pruned()),
 				}
@@ -489,7 +543,18 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
-		future::done(self.block(BlockId::Hash(hash.into()), include_txs)).boxed()
+		let hash: H256 = hash.into();
+		// look the head up first, so a block imported meanwhile invalidates the entry.
+		let head = self.missing_cache_head();
+		if self.is_known_missing(&head, LookupKind::Block, &hash) {
+			return future::ok(None).boxed();
+		}
+
+		let block = self.block(BlockId::Hash(hash), include_txs);
+		if let Ok(None) = block {
+			self.note_missing(&head, LookupKind::Block, &hash);
+		}
+		future::done(block).boxed()
 	}
 
 	fn block_by_number(&self, num: BlockNumber, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
@@ -500,7 +565,18 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		let hash: H256 = hash.into();
 		let miner = take_weak!(self.miner);
 		let client = take_weak!(self.client);
-		Ok(self.transaction(TransactionId::Hash(hash))?.or_else(|| miner.transaction(client.chain_info().best_block_number, &hash).map(Into::into)))
+		let head = self.missing_cache_head();
+		let mined = match self.is_known_missing(&head, LookupKind::Transaction, &hash) {
+			true => None,
+			false => {
+				let mined = self.transaction(TransactionId::Hash(hash))?;
+				if mined.is_none() {
+					self.note_missing(&head, LookupKind::Transaction, &hash);
+				}
+				mined
+			},
+		};
+		Ok(mined.or_else(|| miner.transaction(client.chain_info().best_block_number, &hash).map(Into::into)))
 	}
 
 	fn transaction_by_block_hash_and_index(&self, hash: RpcH256, index: Index) -> Result<Option<Transaction>, Error> {
@@ -511,16 +587,47 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 		self.transaction(TransactionId::Location(num.into(), index.value()))
 	}
 
-	fn transaction_receipt(&self, hash: RpcH256) -> Result<Option<Receipt>, Error> {
+	fn transaction_receipt(&self, hash: RpcH256, options: Trailing<ReceiptOptions>) -> Result<Option<Receipt>, Error> {
 		let miner = take_weak!(self.miner);
 		let best_block = take_weak!(self.client).chain_info().best_block_number;
 		let hash: H256 = hash.into();
-		match (miner.pending_receipt(best_block, &hash), self.options.allow_pending_receipt_query) {
+		let with_proof = options.0.with_proof;
+		// pending receipts can't be proven, so they're only returned if no proof is requested.
+		match (miner.pending_receipt(best_block, &hash), self.options.allow_pending_receipt_query && !with_proof) {
 			(Some(receipt), true) => Ok(Some(receipt.into())),
 			_ => {
 				let client = take_weak!(self.client);
-				let receipt = client.transaction_receipt(TransactionId::Hash(hash));
-				Ok(receipt.map(Into::into))
+				let head = self.missing_cache_head();
+				if self.is_known_missing(&head, LookupKind::Receipt, &hash) {
+					return Ok(None);
+				}
+				let receipt = match client.transaction_receipt(TransactionId::Hash(hash)) {
+					Some(receipt) => receipt,
+					None => {
+						self.note_missing(&head, LookupKind::Receipt, &hash);
+						return Ok(None);
+					},
+				};
+
+				let proof = match with_proof {
+					true => {
+						let header = client.block_header(BlockId::Hash(receipt.block_hash));
+						let branch = client.block_receipts(&receipt.block_hash)
+							.and_then(|receipts| prove_receipt(&receipts, receipt.transaction_index));
+						match (header, branch) {
+							(Some(header), Some(branch)) => Some(ReceiptProof {
+								header: header.into_inner().into(),
+								branch: branch.into_iter().map(Into::into).collect(),
+							}),
+							_ => return Err(errors::unknown_block()),
+						}
+					},
+					false => None,
+				};
+
+				let mut receipt: Receipt = receipt.into();
+				receipt.proof = proof;
+				Ok(Some(receipt))
 			}
 		}
 	}
@@ -540,18 +647,20 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	fn logs(&self, filter: Filter) -> Result<Vec<Log>, Error> {
 		let include_pending = filter.to_block == Some(BlockNumber::Pending);
 		let filter: EthcoreFilter = filter.into();
-		let mut logs = take_weak!(self.client).logs(filter.clone())
-			.into_iter()
-			.map(From::from)
-			.collect::<Vec<Log>>();
-
-		if include_pending {
+		let pending = if include_pending {
 			let best_block = take_weak!(self.client).chain_info().best_block_number;
-			let pending = pending_logs(&*take_weak!(self.miner), best_block, &filter);
-			logs.extend(pending);
-		}
+			pending_logs(&*take_weak!(self.miner), best_block, &filter)
+		} else {
+			Vec::new()
+		};
 
+		let logs = take_weak!(self.client).logs(filter.clone())
+			.into_iter()
+			.map(Log::from)
+			.chain(pending)
+			.collect();
 		let logs = limit_logs(logs, filter.limit);
+		response_limits::check(self.options.response_limits.as_ref().map(|l| &**l), "eth_getLogs", &logs)?;
 
 		Ok(logs)
 	}
@@ -581,10 +690,11 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 			warn!(target: "miner", "Cannot give work package - no author is configured. Use --author to configure!");
 			return Err(errors::no_author())
 		}
-		miner.map_sealing_work(&*client, |b| {
+		let work = miner.map_sealing_work(&*client, |b| {
 			let pow_hash = b.hash();
 			let target = Ethash::difficulty_to_boundary(b.block().header().difficulty());
 			let seed_hash = self.seed_compute.lock().get_seedhash(b.block().header().number());
+			let signature = self.options.work_auth.as_ref().map(|auth| auth.sign_work(&pow_hash, &seed_hash, &target).into());
 
 			if no_new_work_timeout > 0 && b.block().header().timestamp() + no_new_work_timeout < get_time().sec as u64 {
 				Err(errors::no_new_work())
@@ -595,28 +705,59 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 					seed_hash: seed_hash.into(),
 					target: target.into(),
 					number: Some(block_number),
+					signature: signature,
 				})
 			} else {
 				Ok(Work {
 					pow_hash: pow_hash.into(),
 					seed_hash: seed_hash.into(),
 					target: target.into(),
-					number: None
+					number: None,
+					signature: signature,
 				})
 			}
-		}).unwrap_or(Err(errors::internal("No work found.", "")))
+		}).unwrap_or(Err(errors::internal("No work found.", "")))?;
+
+		if let Some(ref tracker) = self.options.work_tracker {
+			tracker.note_issued(&work.pow_hash.clone().into());
+		}
+		Ok(work)
 	}
 
-	fn submit_work(&self, nonce: RpcH64, pow_hash: RpcH256, mix_hash: RpcH256) -> Result<bool, Error> {
+	fn submit_work(&self, nonce: RpcH64, pow_hash: RpcH256, mix_hash: RpcH256, credentials: Trailing<WorkerCredentials>) -> Result<bool, Error> {
 		let nonce: H64 = nonce.into();
 		let pow_hash: H256 = pow_hash.into();
 		let mix_hash: H256 = mix_hash.into();
-		trace!(target: "miner", "submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={}", nonce, pow_hash, mix_hash);
+		let credentials = credentials.0;
+		trace!(target: "miner", "submit_work: Decoded: nonce={}, pow_hash={}, mix_hash={}, worker={}", nonce, pow_hash, mix_hash, credentials.worker);
+
+		if let Some(ref auth) = self.options.work_auth {
+			auth.authorize_submission(&credentials.worker, &credentials.token.clone().into())?;
+		}
 
 		let miner = take_weak!(self.miner);
 		let client = take_weak!(self.client);
 		let seal = vec![rlp::encode(&mix_hash).to_vec(), rlp::encode(&nonce).to_vec()];
-		Ok(miner.submit_seal(&*client, pow_hash, seal).is_ok())
+		let submission = match miner.submit_seal(&*client, pow_hash, seal) {
+			Ok(()) => Submission::Accepted,
+			// work we handed out which the miner no longer has was superseded or already solved.
+			Err(EthcoreError::PowHashInvalid) if self.options.work_tracker.as_ref().map_or(false, |tracker| tracker.was_issued(&pow_hash)) =>
+				Submission::Stale,
+			Err(_) => Submission::Rejected,
+		};
+
+		let accepted = submission == Submission::Accepted;
+		if let Some(ref auth) = self.options.work_auth {
+			auth.note_result(&credentials.worker, accepted);
+		}
+		if let Some(ref tracker) = self.options.work_tracker {
+			tracker.note_submission(&credentials.worker, submission);
+		}
+
+		match submission {
+			Submission::Stale => Err(errors::stale_work()),
+			_ => Ok(accepted),
+		}
 	}
 
 	fn submit_hashrate(&self, rate: RpcU256, id: RpcH256) -> Result<bool, Error> {
@@ -640,31 +781,69 @@ impl<C, SN: ?Sized, S: ?Sized, M, EM> Eth for EthClient<C, SN, S, M, EM> where
 	}
 
 	fn call(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
+		let env = request.env_overrides();
 		let request = CallRequest::into(request);
 		let signed = match fake_sign::sign_call(&self.client, &self.miner, request) {
 			Ok(signed) => signed,
 			Err(e) => return future::err(e).boxed(),
 		};
 
-		let result = match num.0 {
-			BlockNumber::Pending => take_weakf!(self.miner).call(&*take_weakf!(self.client), &signed, Default::default()),
-			num => take_weakf!(self.client).call(&signed, num.into(), Default::default()),
+		let client = take_weakf!(self.client);
+		let block = try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0));
+
+		// calls in an overridden environment aren't cached.
+		let cache_key = match env.is_empty() {
+			true => self.call_cache_key(&block, &signed),
+			false => None,
+		};
+		if let Some(CallResult::Output(output)) = self.cached_call(&cache_key, CallKind::Call) {
+			return future::ok(output.into()).boxed();
+		}
+
+		let result = match block {
+			ResolvedBlock::Pending if !env.is_empty() => return future::err(errors::env_overrides_unsupported()).boxed(),
+			ResolvedBlock::Pending => take_weakf!(self.miner).call(&*client, &signed, Default::default()),
+			block => client.call_with_env(&signed, block.id(), Default::default(), &env),
 		};
 
 		future::done(result
-			.map(|b| b.output.into())
+			.map(|b| {
+				self.cache_call(&cache_key, CallKind::Call, CallResult::Output(b.output.clone()));
+				b.output.into()
+			})
 			.map_err(errors::from_call_error)
 		).boxed()
 	}
 
 	fn estimate_gas(&self, request: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
+		if request.env.is_some() {
+			return future::err(errors::env_overrides_unsupported()).boxed();
+		}
+
 		let request = CallRequest::into(request);
 		let signed = match fake_sign::sign_call(&self.client, &self.miner, request) {
 			Ok(signed) => signed,
 			Err(e) => return future::err(e).boxed(),
 		};
-		future::done(take_weakf!(self.client).estimate_gas(&signed, num.0.into())
-			.map(Into::into)
+
+		let client = take_weakf!(self.client);
+		let block = try_bf!(BlockIdResolver::new(&*client).resolve_state(num.0));
+
+		let cache_key = self.call_cache_key(&block, &signed);
+		if let Some(CallResult::Gas(gas)) = self.cached_call(&cache_key, CallKind::EstimateGas) {
+			return future::ok(gas.into()).boxed();
+		}
+
+		let result = match block {
+			ResolvedBlock::Pending => take_weakf!(self.miner).estimate_gas(&*client, &signed),
+			block => client.estimate_gas(&signed, block.id()),
+		};
+
+		future::done(result
+			.map(|gas| {
+				self.cache_call(&cache_key, CallKind::EstimateGas, CallResult::Gas(gas));
+				gas.into()
+			})
 			.map_err(errors::from_call_error)
 		).boxed()
 	}
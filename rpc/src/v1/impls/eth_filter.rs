@@ -26,6 +26,7 @@ use util::Mutex;
 use v1::traits::EthFilter;
 use v1::types::{BlockNumber, Index, Filter, FilterChanges, Log, H256 as RpcH256, U256 as RpcU256};
 use v1::helpers::{PollFilter, PollManager, limit_logs};
+use v1::helpers::response_limits::{self, ResponseLimits};
 use v1::impls::eth::pending_logs;
 
 /// Eth filter rpc implementation.
@@ -36,6 +37,7 @@ pub struct EthFilterClient<C, M> where
 	client: Weak<C>,
 	miner: Weak<M>,
 	polls: Mutex<PollManager<PollFilter>>,
+	response_limits: Option<Arc<ResponseLimits>>,
 }
 
 impl<C, M> EthFilterClient<C, M> where
@@ -48,8 +50,19 @@ impl<C, M> EthFilterClient<C, M> where
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
 			polls: Mutex::new(PollManager::new()),
+			response_limits: None,
 		}
 	}
+
+	/// Reject log results whose estimated size exceeds the configured limits.
+	pub fn with_response_limits(mut self, limits: Arc<ResponseLimits>) -> Self {
+		self.response_limits = Some(limits);
+		self
+	}
+
+	fn check_logs(&self, method: &str, logs: &[Log]) -> Result<(), Error> {
+		response_limits::check(self.response_limits.as_ref().map(|l| &**l), method, logs)
+	}
 }
 
 impl<C, M> EthFilter for EthFilterClient<C, M>
@@ -131,31 +144,36 @@ impl<C, M> EthFilter for EthFilterClient<C, M>
 					filter.from_block = BlockId::Number(*block_number);
 					filter.to_block = BlockId::Latest;
 
-					// retrieve logs in range from_block..min(BlockId::Latest..to_block)
-					let mut logs = client.logs(filter.clone())
-						.into_iter()
-						.map(From::from)
-						.collect::<Vec<Log>>();
-
 					// additionally retrieve pending logs
+					let mut new_pending_logs = Vec::new();
+					let mut retrieved_pending_logs = None;
 					if include_pending {
 						let best_block = take_weak!(self.client).chain_info().best_block_number;
 						let pending_logs = pending_logs(&*take_weak!(self.miner), best_block, &filter);
 
 						// remove logs about which client was already notified about
-						let new_pending_logs: Vec<_> = pending_logs.iter()
+						new_pending_logs = pending_logs.iter()
 							.filter(|p| !previous_logs.contains(p))
 							.cloned()
 							.collect();
 
-						// save all logs retrieved by client
-						*previous_logs = pending_logs.into_iter().collect();
-
-						// append logs array with new pending logs
-						logs.extend(new_pending_logs);
+						retrieved_pending_logs = Some(pending_logs);
 					}
 
+					// retrieve logs in range from_block..min(BlockId::Latest..to_block)
+					// followed by new pending logs
+					let logs = client.logs(filter.clone())
+						.into_iter()
+						.map(Log::from)
+						.chain(new_pending_logs)
+						.collect();
 					let logs = limit_logs(logs, filter.limit);
+					self.check_logs("eth_getFilterChanges", &logs)?;
+
+					// save all pending logs retrieved by client, now that they are returned
+					if let Some(pending_logs) = retrieved_pending_logs {
+						*previous_logs = pending_logs.into_iter().collect();
+					}
 
 					// save the number of the next block as a first block from which
 					// we want to get logs
@@ -173,17 +191,20 @@ impl<C, M> EthFilter for EthFilterClient<C, M>
 			Some(&PollFilter::Logs(ref _block_number, ref _previous_log, ref filter)) => {
 				let include_pending = filter.to_block == Some(BlockNumber::Pending);
 				let filter: EthcoreFilter = filter.clone().into();
-				let mut logs = take_weak!(self.client).logs(filter.clone())
-					.into_iter()
-					.map(From::from)
-					.collect::<Vec<Log>>();
-
-				if include_pending {
+				let pending = if include_pending {
 					let best_block = take_weak!(self.client).chain_info().best_block_number;
-					logs.extend(pending_logs(&*take_weak!(self.miner), best_block, &filter));
-				}
+					pending_logs(&*take_weak!(self.miner), best_block, &filter)
+				} else {
+					Vec::new()
+				};
 
+				let logs = take_weak!(self.client).logs(filter.clone())
+					.into_iter()
+					.map(Log::from)
+					.chain(pending)
+					.collect();
 				let logs = limit_logs(logs, filter.limit);
+				self.check_logs("eth_getFilterLogs", &logs)?;
 
 				Ok(logs)
 			},
@@ -37,7 +37,7 @@ use ethcore::ids::BlockId;
 use ethcore::transaction::{Action, SignedTransaction, Transaction as EthTransaction};
 use ethsync::LightSync;
 use rlp::UntrustedRlp;
-use util::sha3::{SHA3_NULL_RLP, SHA3_EMPTY_LIST_RLP};
+use util::sha3::{Hashable, SHA3_EMPTY, SHA3_NULL_RLP, SHA3_EMPTY_LIST_RLP};
 use util::{RwLock, Mutex, Uint, U256};
 
 use futures::{future, Future, BoxFuture, IntoFuture};
@@ -45,10 +45,12 @@ use futures::sync::oneshot;
 
 use v1::helpers::{CallRequest as CRequest, errors, limit_logs, dispatch};
 use v1::helpers::block_import::is_major_importing;
+use v1::helpers::request_budget::{RequestBudget, Spending};
+use v1::helpers::trusted_fallback::{self, TrustedFallback, UNVERIFIED_LOG_TYPE};
 use v1::traits::Eth;
 use v1::types::{
 	RichBlock, Block, BlockTransactions, BlockNumber, Bytes, SyncStatus, SyncInfo,
-	Transaction, CallRequest, Index, Filter, Log, Receipt, Work,
+	Transaction, CallRequest, Index, Filter, Log, Receipt, ReceiptOptions, Work, WorkerCredentials,
 	H64 as RpcH64, H256 as RpcH256, H160 as RpcH160, U256 as RpcU256,
 };
 use v1::metadata::Metadata;
@@ -63,6 +65,8 @@ pub struct EthClient {
 	transaction_queue: Arc<RwLock<TransactionQueue>>,
 	accounts: Arc<AccountProvider>,
 	cache: Arc<Mutex<LightDataCache>>,
+	budget: RequestBudget,
+	fallback: Option<Arc<TrustedFallback>>,
 }
 
 // helper for internal error: on demand sender cancelled.
@@ -70,11 +74,27 @@ fn err_premature_cancel(_cancel: oneshot::Canceled) -> Error {
 	errors::internal("on-demand sender prematurely cancelled", "")
 }
 
+// helper for fetching a block body from the network, charged to the call's budget.
+fn fetch_block(sync: &LightSync, on_demand: &OnDemand, spending: Arc<Spending>, hdr: encoded::Header) -> BoxFuture<encoded::Block, Error> {
+	if let Err(e) = spending.request() {
+		return future::err(e).boxed()
+	}
+
+	sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(hdr)))
+		.map(|x| x.map_err(err_premature_cancel)
+			.and_then(move |b| spending.received(b.rlp().as_raw().len()).map(|_| b))
+			.boxed())
+		.unwrap_or_else(|| future::err(errors::network_disabled()).boxed())
+}
+
 type ExecutionResult = Result<Executed, ExecutionError>;
 
 impl EthClient {
 	/// Create a new `EthClient` with a handle to the light sync instance, client,
 	/// and on-demand request service, which is assumed to be attached as a handler.
+	/// Each call may make network requests only within the given `budget`.
+	/// Queries which can't be answered from the network are forwarded to the
+	/// `fallback` node, if any.
 	pub fn new(
 		sync: Arc<LightSync>,
 		client: Arc<LightClient>,
@@ -82,6 +102,8 @@ impl EthClient {
 		transaction_queue: Arc<RwLock<TransactionQueue>>,
 		accounts: Arc<AccountProvider>,
 		cache: Arc<Mutex<LightDataCache>>,
+		budget: RequestBudget,
+		fallback: Option<Arc<TrustedFallback>>,
 	) -> Self {
 		EthClient {
 			sync: sync,
@@ -90,11 +112,13 @@ impl EthClient {
 			transaction_queue: transaction_queue,
 			accounts: accounts,
 			cache: cache,
+			budget: budget,
+			fallback: fallback,
 		}
 	}
 
 	/// Get a block header from the on demand service or client, or error.
-	fn header(&self, id: BlockId) -> BoxFuture<Option<encoded::Header>, Error> {
+	fn header(&self, id: BlockId, spending: &Arc<Spending>) -> BoxFuture<Option<encoded::Header>, Error> {
 		if let Some(h) = self.client.block_header(id) {
 			return future::ok(Some(h)).boxed()
 		}
@@ -105,18 +129,27 @@ impl EthClient {
 				match cht_root {
 					None => return future::ok(None).boxed(),
 					Some(root) => {
+						if let Err(e) = spending.request() {
+							return future::err(e).boxed()
+						}
+
 						let req = request::HeaderProof::new(n, root)
 							.expect("only fails for 0; client always stores genesis; client already queried; qed");
 
-						let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+						let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), spending.clone());
 						self.sync.with_context(|ctx| {
 							let fut = self.on_demand.hash_by_number(ctx, req)
 								.map(request::HeaderByHash)
 								.map_err(err_premature_cancel);
 
 							fut.and_then(move |req| {
+								if let Err(e) = spending.request() {
+									return future::err(e).boxed()
+								}
 								match sync.with_context(|ctx| on_demand.header_by_hash(ctx, req)) {
-									Some(fut) => fut.map_err(err_premature_cancel).boxed(),
+									Some(fut) => fut.map_err(err_premature_cancel)
+										.and_then(move |h| spending.received(h.rlp().as_raw().len()).map(|_| h))
+										.boxed(),
 									None => future::err(errors::network_disabled()).boxed(),
 								}
 							}).map(Some).boxed()
@@ -125,10 +158,15 @@ impl EthClient {
 				}
 			}
 			BlockId::Hash(h) => {
+				if let Err(e) = spending.request() {
+					return future::err(e).boxed()
+				}
+
+				let spending = spending.clone();
 				self.sync.with_context(|ctx|
 					self.on_demand.header_by_hash(ctx, request::HeaderByHash(h))
-						.then(|res| future::done(match res {
-							Ok(h) => Ok(Some(h)),
+						.then(move |res| future::done(match res {
+							Ok(h) => spending.received(h.rlp().as_raw().len()).map(|_| Some(h)),
 							Err(e) => Err(err_premature_cancel(e)),
 						}))
 						.boxed()
@@ -144,15 +182,19 @@ impl EthClient {
 	}
 
 	// helper for getting account info at a given block.
-	fn account(&self, address: Address, id: BlockId) -> BoxFuture<Option<BasicAccount>, Error> {
-		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+	fn account(&self, address: Address, id: BlockId, spending: &Arc<Spending>) -> BoxFuture<Option<BasicAccount>, Error> {
+		let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), spending.clone());
 
-		self.header(id).and_then(move |header| {
+		self.header(id, &spending).and_then(move |header| {
 			let header = match header {
 				None => return future::ok(None).boxed(),
 				Some(hdr) => hdr,
 			};
 
+			if let Err(e) = spending.request() {
+				return future::err(e).boxed()
+			}
+
 			sync.with_context(|ctx| on_demand.account(ctx, request::Account {
 				header: header,
 				address: address,
@@ -163,18 +205,24 @@ impl EthClient {
 	}
 
 	// helper for getting proved execution.
-	fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<ExecutionResult, Error> {
+	fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>, spending: &Arc<Spending>) -> BoxFuture<ExecutionResult, Error> {
 		const DEFAULT_GAS_PRICE: U256 = U256([0, 0, 0, 21_000_000]);
 
 
+		// proofs are checked against the environment of the block they're made at.
+		if req.env.is_some() {
+			return future::err(errors::env_overrides_unsupported()).boxed();
+		}
+
 		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
+		let proof_spending = spending.clone();
 		let req: CRequest = req.into();
 		let id = num.0.into();
 
 		let from = req.from.unwrap_or(Address::zero());
 		let nonce_fut = match req.nonce {
 			Some(nonce) => future::ok(Some(nonce)).boxed(),
-			None => self.account(from, id).map(|acc| acc.map(|a| a.nonce)).boxed(),
+			None => self.account(from, id, spending).map(|acc| acc.map(|a| a.nonce)).boxed(),
 		};
 
 		let gas_price_fut = match req.gas_price {
@@ -191,7 +239,7 @@ impl EthClient {
 		};
 
 		// if nonce resolves, this should too since it'll be in the LRU-cache.
-		let header_fut = self.header(id);
+		let header_fut = self.header(id, spending);
 
 		// fetch missing transaction fields from the network.
 		nonce_fut.join(gas_price_fut).and_then(move |(nonce, gas_price)| {
@@ -225,6 +273,10 @@ impl EthClient {
 				engine: client.engine().clone(),
 			};
 
+			if let Err(e) = proof_spending.request() {
+				return future::err(e).boxed()
+			}
+
 			let proved_future = sync.with_context(move |ctx| {
 				on_demand.transaction_proof(ctx, request).map_err(err_premature_cancel).boxed()
 			});
@@ -281,12 +333,29 @@ impl Eth for EthClient {
 	}
 
 	fn balance(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
-		self.account(address.into(), num.0.into())
+		self.account(address.into(), num.0.into(), &self.budget.start())
 			.map(|acc| acc.map_or(0.into(), |a| a.balance).into()).boxed()
 	}
 
-	fn storage_at(&self, _address: RpcH160, _key: RpcU256, _num: Trailing<BlockNumber>) -> BoxFuture<RpcH256, Error> {
-		future::err(errors::unimplemented(None)).boxed()
+	fn storage_at(&self, address: RpcH160, key: RpcU256, num: Trailing<BlockNumber>) -> BoxFuture<RpcH256, Error> {
+		let fallback = match self.fallback {
+			Some(ref fallback) => fallback.clone(),
+			None => return future::err(errors::unimplemented(None)).boxed(),
+		};
+		let params = vec![
+			trusted_fallback::param(address.clone()),
+			trusted_fallback::param(key),
+			trusted_fallback::param(num.0.clone()),
+		];
+
+		// storage of accounts proved to have none is answered locally, only values
+		// of non-empty storage are taken from the trusted node unverified.
+		self.account(address.into(), num.0.into(), &self.budget.start()).and_then(move |acc| {
+			match acc {
+				Some(ref acc) if acc.storage_root != SHA3_NULL_RLP => fallback.call("eth_getStorageAt", params),
+				_ => future::ok(RpcH256::default()).boxed(),
+			}
+		}).boxed()
 	}
 
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>, Error> {
@@ -298,14 +367,14 @@ impl Eth for EthClient {
 	}
 
 	fn transaction_count(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
-		self.account(address.into(), num.0.into())
+		self.account(address.into(), num.0.into(), &self.budget.start())
 			.map(|acc| acc.map_or(0.into(), |a| a.nonce).into()).boxed()
 	}
 
 	fn block_transaction_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+		let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), self.budget.start());
 
-		self.header(BlockId::Hash(hash.into())).and_then(move |hdr| {
+		self.header(BlockId::Hash(hash.into()), &spending).and_then(move |hdr| {
 			let hdr = match hdr {
 				None => return future::ok(None).boxed(),
 				Some(hdr) => hdr,
@@ -314,18 +383,17 @@ impl Eth for EthClient {
 			if hdr.transactions_root() == SHA3_NULL_RLP {
 				future::ok(Some(U256::from(0).into())).boxed()
 			} else {
-				sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(hdr)))
-					.map(|x| x.map(|b| Some(U256::from(b.transactions_count()).into())))
-					.map(|x| x.map_err(err_premature_cancel).boxed())
-					.unwrap_or_else(|| future::err(errors::network_disabled()).boxed())
+				fetch_block(&sync, &on_demand, spending, hdr)
+					.map(|b| Some(U256::from(b.transactions_count()).into()))
+					.boxed()
 			}
 		}).boxed()
 	}
 
 	fn block_transaction_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+		let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), self.budget.start());
 
-		self.header(num.into()).and_then(move |hdr| {
+		self.header(num.into(), &spending).and_then(move |hdr| {
 			let hdr = match hdr {
 				None => return future::ok(None).boxed(),
 				Some(hdr) => hdr,
@@ -334,18 +402,17 @@ impl Eth for EthClient {
 			if hdr.transactions_root() == SHA3_NULL_RLP {
 				future::ok(Some(U256::from(0).into())).boxed()
 			} else {
-				sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(hdr)))
-					.map(|x| x.map(|b| Some(U256::from(b.transactions_count()).into())))
-					.map(|x| x.map_err(err_premature_cancel).boxed())
-					.unwrap_or_else(|| future::err(errors::network_disabled()).boxed())
+				fetch_block(&sync, &on_demand, spending, hdr)
+					.map(|b| Some(U256::from(b.transactions_count()).into()))
+					.boxed()
 			}
 		}).boxed()
 	}
 
 	fn block_uncles_count_by_hash(&self, hash: RpcH256) -> BoxFuture<Option<RpcU256>, Error> {
-		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+		let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), self.budget.start());
 
-		self.header(BlockId::Hash(hash.into())).and_then(move |hdr| {
+		self.header(BlockId::Hash(hash.into()), &spending).and_then(move |hdr| {
 			let hdr = match hdr {
 				None => return future::ok(None).boxed(),
 				Some(hdr) => hdr,
@@ -354,18 +421,17 @@ impl Eth for EthClient {
 			if hdr.uncles_hash() == SHA3_EMPTY_LIST_RLP {
 				future::ok(Some(U256::from(0).into())).boxed()
 			} else {
-				sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(hdr)))
-					.map(|x| x.map(|b| Some(U256::from(b.uncles_count()).into())))
-					.map(|x| x.map_err(err_premature_cancel).boxed())
-					.unwrap_or_else(|| future::err(errors::network_disabled()).boxed())
+				fetch_block(&sync, &on_demand, spending, hdr)
+					.map(|b| Some(U256::from(b.uncles_count()).into()))
+					.boxed()
 			}
 		}).boxed()
 	}
 
 	fn block_uncles_count_by_number(&self, num: BlockNumber) -> BoxFuture<Option<RpcU256>, Error> {
-		let (sync, on_demand) = (self.sync.clone(), self.on_demand.clone());
+		let (sync, on_demand, spending) = (self.sync.clone(), self.on_demand.clone(), self.budget.start());
 
-		self.header(num.into()).and_then(move |hdr| {
+		self.header(num.into(), &spending).and_then(move |hdr| {
 			let hdr = match hdr {
 				None => return future::ok(None).boxed(),
 				Some(hdr) => hdr,
@@ -374,16 +440,37 @@ impl Eth for EthClient {
 			if hdr.uncles_hash() == SHA3_EMPTY_LIST_RLP {
 				future::ok(Some(U256::from(0).into())).boxed()
 			} else {
-				sync.with_context(|ctx| on_demand.block(ctx, request::Body::new(hdr)))
-					.map(|x| x.map(|b| Some(U256::from(b.uncles_count()).into())))
-					.map(|x| x.map_err(err_premature_cancel).boxed())
-					.unwrap_or_else(|| future::err(errors::network_disabled()).boxed())
+				fetch_block(&sync, &on_demand, spending, hdr)
+					.map(|b| Some(U256::from(b.uncles_count()).into()))
+					.boxed()
 			}
 		}).boxed()
 	}
 
 	fn code_at(&self, address: RpcH160, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		future::err(errors::unimplemented(None)).boxed()
+		let fallback = match self.fallback {
+			Some(ref fallback) => fallback.clone(),
+			None => return future::err(errors::unimplemented(None)).boxed(),
+		};
+		let params = vec![
+			trusted_fallback::param(address.clone()),
+			trusted_fallback::param(num.0.clone()),
+		];
+
+		// code from the trusted node is checked against the code hash of the proved account.
+		self.account(address.into(), num.0.into(), &self.budget.start()).and_then(move |acc| {
+			let code_hash = acc.map_or(SHA3_EMPTY, |acc| acc.code_hash);
+			if code_hash == SHA3_EMPTY {
+				return future::ok(Bytes::default()).boxed();
+			}
+
+			fallback.call::<Bytes>("eth_getCode", params).and_then(move |code| {
+				match code.0.sha3() == code_hash {
+					true => Ok(code),
+					false => Err(errors::trusted_fallback("Code doesn't match the proved account".into())),
+				}
+			}).boxed()
+		}).boxed()
 	}
 
 	fn send_raw_transaction(&self, raw: Bytes) -> Result<RpcH256, Error> {
@@ -411,7 +498,7 @@ impl Eth for EthClient {
 	}
 
 	fn call(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<Bytes, Error> {
-		self.proved_execution(req, num).and_then(|res| {
+		self.proved_execution(req, num, &self.budget.start()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok(exec.output.into()),
 				Err(e) => Err(errors::execution(e)),
@@ -421,7 +508,7 @@ impl Eth for EthClient {
 
 	fn estimate_gas(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<RpcU256, Error> {
 		// TODO: binary chop for more accurate estimates.
-		self.proved_execution(req, num).and_then(|res| {
+		self.proved_execution(req, num, &self.budget.start()).and_then(|res| {
 			match res {
 				Ok(exec) => Ok((exec.refunded + exec.gas_used).into()),
 				Err(e) => Err(errors::execution(e)),
@@ -441,7 +528,7 @@ impl Eth for EthClient {
 		Err(errors::unimplemented(None))
 	}
 
-	fn transaction_receipt(&self, hash: RpcH256) -> Result<Option<Receipt>, Error> {
+	fn transaction_receipt(&self, hash: RpcH256, _options: Trailing<ReceiptOptions>) -> Result<Option<Receipt>, Error> {
 		Err(errors::unimplemented(None))
 	}
 
@@ -470,15 +557,26 @@ impl Eth for EthClient {
 		Err(errors::deprecated("Compilation of Solidity via RPC is deprecated".to_string()))
 	}
 
-	fn logs(&self, _filter: Filter) -> Result<Vec<Log>, Error> {
-		Err(errors::unimplemented(None))
+	fn logs(&self, filter: Filter) -> Result<Vec<Log>, Error> {
+		let fallback = match self.fallback {
+			Some(ref fallback) => fallback,
+			None => return Err(errors::unimplemented(None)),
+		};
+
+		let limit = filter.limit;
+		fallback.call::<Vec<Log>>("eth_getLogs", vec![trusted_fallback::param(filter)]).wait()
+			.map(|logs| logs.into_iter().map(|mut log| {
+				log.log_type = UNVERIFIED_LOG_TYPE.into();
+				log
+			}).collect())
+			.map(|logs| limit_logs(logs, limit))
 	}
 
 	fn work(&self, _timeout: Trailing<u64>) -> Result<Work, Error> {
 		Err(errors::unimplemented(None))
 	}
 
-	fn submit_work(&self, _nonce: RpcH64, _pow_hash: RpcH256, _mix_hash: RpcH256) -> Result<bool, Error> {
+	fn submit_work(&self, _nonce: RpcH64, _pow_hash: RpcH256, _mix_hash: RpcH256, _credentials: Trailing<WorkerCredentials>) -> Result<bool, Error> {
 		Err(errors::unimplemented(None))
 	}
 
@@ -39,8 +39,11 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo
+	OperationsInfo, DappId, ChainStatus, NodeStatus, LightPeerInfo, ChainSpec,
+	AccountInfo, HwAccountInfo, StorageKeysPage, EncodedHeader,
+	SimulationRequest, SimulationResult, CallRequest, DbFlushMarker, StateDiff, CompactionStatus,
+	DbColumnStats, PendingSnapshot, Filter, LogsCursor, LogsPage, FeeHistory, ChtRange, PropagationStats, CallProof,
+	ReceiptsBackfillStatus, LightCacheUsage, WorkerStats, CallCacheStats, DecodedLog,
 };
 
 /// Parity implementation for light client.
@@ -222,6 +225,14 @@ impl Parity for ParityClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn list_storage_keys_paged(&self, _: H160, _: u64, _: Option<Bytes>, _: Trailing<BlockNumber>) -> Result<Option<StorageKeysPage>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn state_diff(&self, _: Vec<H160>, _: BlockNumber, _: BlockNumber) -> Result<StateDiff, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes, Error> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption_error)
@@ -258,6 +269,16 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn propagation_stats(&self) -> Result<Vec<PropagationStats>, Error> {
+		// light clients neither seal blocks nor keep local transactions in a queue.
+		Ok(Vec::new())
+	}
+
+	fn receipts_backfill_status(&self) -> Result<Option<ReceiptsBackfillStatus>, Error> {
+		// light clients fetch receipts on demand.
+		Ok(None)
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error> {
 		let mut map = BTreeMap::new();
 		let chain_info = self.light_dispatch.client.chain_info();
@@ -333,4 +354,95 @@ impl Parity for ParityClient {
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
 		})
 	}
+
+	fn node_status(&self) -> Result<NodeStatus, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn light_peers(&self) -> Result<Vec<LightPeerInfo>, Error> {
+		let sync = &self.light_dispatch.sync;
+		Ok(self.light_dispatch.on_demand.peers().into_iter().map(|peer| {
+			let credits = sync.remote_credits(&peer.id);
+			let mut info = LightPeerInfo::from(peer);
+			info.credits = credits.map(|(current, _)| current.into());
+			info.credit_limit = credits.map(|(_, limit)| limit.into());
+			info
+		}).collect())
+	}
+
+	fn worker_stats(&self) -> Result<BTreeMap<String, WorkerStats>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn call_cache_stats(&self) -> Result<Option<CallCacheStats>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn light_cache_usage(&self) -> Result<Vec<LightCacheUsage>, Error> {
+		Ok(self.light_dispatch.cache.lock().usage().into_iter().map(Into::into).collect())
+	}
+
+	fn chain_spec(&self) -> Result<ChainSpec, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn block_header(&self, number: Trailing<BlockNumber>) -> Result<Option<EncodedHeader>, Error> {
+		Ok(self.light_dispatch.client.block_header(number.0.into()).map(Into::into))
+	}
+
+	fn simulate_transaction(&self, _: SimulationRequest, _: Trailing<BlockNumber>) -> Result<SimulationResult, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn prove_call(&self, _: CallRequest, _: Trailing<BlockNumber>) -> Result<CallProof, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn intrinsic_gas(&self, _: CallRequest) -> Result<U256, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn shared_db_handshake(&self, _: H256) -> Result<DbFlushMarker, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn db_flush_marker(&self) -> Result<DbFlushMarker, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn compaction_status(&self) -> Result<CompactionStatus, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn db_stats(&self) -> Result<Vec<DbColumnStats>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn pending_snapshot(&self) -> Result<PendingSnapshot, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn logs_paged(&self, _: Filter, _: u64, _: Option<LogsCursor>) -> Result<LogsPage, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn decoded_logs(&self, _: Filter) -> Result<Vec<DecodedLog>, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn fee_history(&self, _: u64, _: Trailing<BlockNumber>) -> Result<FeeHistory, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn cht_ranges(&self) -> Result<Vec<ChtRange>, Error> {
+		let client = &self.light_dispatch.client;
+		Ok((0..).map(|number| client.cht_root(number).map(|root| ChtRange::new(number as u64, root)))
+			.take_while(Option::is_some)
+			.filter_map(|range| range)
+			.collect())
+	}
+
+	fn export_pending_transactions(&self) -> Result<Bytes, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
@@ -22,6 +22,7 @@ use std::sync::Arc;
 
 use ethsync::ManageNetwork;
 use fetch::Fetch;
+use light::on_demand::OnDemand;
 use futures::{BoxFuture, Future};
 use util::sha3;
 
@@ -33,14 +34,16 @@ use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction};
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
 	net: Arc<ManageNetwork>,
+	on_demand: Arc<OnDemand>,
 	fetch: F,
 }
 
 impl<F: Fetch> ParitySetClient<F> {
 	/// Creates new `ParitySetClient` with given `Fetch`.
-	pub fn new(net: Arc<ManageNetwork>, fetch: F) -> Self {
+	pub fn new(net: Arc<ManageNetwork>, on_demand: Arc<OnDemand>, fetch: F) -> Self {
 		ParitySetClient {
 			net: net,
+			on_demand: on_demand,
 			fetch: fetch,
 		}
 	}
@@ -71,6 +74,14 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn propose_signer(&self, _address: H160, _authorize: bool) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn discard_signer_proposal(&self, _address: H160) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn set_transactions_limit(&self, _limit: usize) -> Result<bool, Error> {
 		Err(errors::light_unimplemented(None))
 	}
@@ -93,6 +104,10 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		}
 	}
 
+	fn clear_bad_blocks(&self) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool, Error> {
 		self.net.deny_unreserved_peers();
 		Ok(true)
@@ -103,6 +118,13 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Ok(true)
 	}
 
+	fn set_public_address(&self, address: String) -> Result<bool, Error> {
+		match self.net.set_public_address(address) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Public address", e)),
+		}
+	}
+
 	fn start_network(&self) -> Result<bool, Error> {
 		self.net.start_network();
 		Ok(true)
@@ -143,4 +165,20 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>, Error> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn compact_database(&self) -> Result<bool, Error> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn pin_light_peer(&self, peer: usize) -> Result<bool, Error> {
+		Ok(self.on_demand.pin(peer))
+	}
+
+	fn unpin_light_peer(&self, peer: usize) -> Result<bool, Error> {
+		Ok(self.on_demand.unpin(peer))
+	}
+
+	fn import_pending_transactions(&self, _bundle: Bytes) -> Result<usize, Error> {
+		Err(errors::light_unimplemented(None))
+	}
 }
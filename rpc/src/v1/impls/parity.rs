@@ -15,11 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Parity-specific rpc implementation.
+use std::cmp;
 use std::sync::{Arc, Weak};
 use std::str::FromStr;
 use std::collections::{BTreeMap, HashSet};
 use futures::{future, Future, BoxFuture};
 
+use rlp::{self, UntrustedRlp};
 use ethcore_logger::RotatingLogger;
 use util::Address;
 use util::misc::version_data;
@@ -28,16 +30,25 @@ use crypto::ecies;
 use ethkey::{Brain, Generator};
 use ethstore::random_phrase;
 use ethsync::{SyncProvider, ManageNetwork};
-use ethcore::miner::MinerService;
-use ethcore::client::{MiningBlockChainClient};
+use ethcore::miner::{MinerService, PoolBundle, PooledTransaction, LocalTransactionStatus as QueueStatus};
+use ethcore::client::{MiningBlockChainClient, ProvingBlockChainClient, BlockId, CallAnalytics};
+use ethcore::filter::Filter as EthcoreFilter;
+use ethcore::log_entry::LocalizedLogEntry;
+use ethcore::transaction::{SignedTransaction, Transaction as EthTransaction, Action};
 use ethcore::mode::Mode;
 use ethcore::account_provider::AccountProvider;
 use updater::{Service as UpdateService};
+use light::request::ExecutionResponse;
 
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::helpers::{errors, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::{errors, fake_sign, SigningQueue, SignerService, NetworkSettings};
+use v1::helpers::abi_registry::AbiRegistry;
+use v1::helpers::block_id::{BlockIdResolver, ResolvedBlock};
+use v1::helpers::call_cache::CallCache;
 use v1::helpers::dispatch::DEFAULT_MAC;
+use v1::helpers::head_lag::HeadLag;
+use v1::helpers::work_tracker::WorkTracker;
 use v1::metadata::Metadata;
 use v1::traits::Parity;
 use v1::types::{
@@ -45,10 +56,22 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo
+	OperationsInfo, DappId, ChainStatus, NodeStatus, LightPeerInfo, ChainSpec,
+	AccountInfo, HwAccountInfo, StorageKeysPage, StorageKeysToken, EncodedHeader,
+	SimulationRequest, SimulationResult, CallRequest, DbFlushMarker, StateDiff, CompactionStatus,
+	DbColumnStats, PendingSnapshot, Filter, Log, LogsCursor, LogsPage, FeeHistory, ChtRange, PropagationStats, CallProof,
+	ReceiptsBackfillStatus, LightCacheUsage, WorkerStats, DecodedLog, DecodedLogsPage, CallCacheStats,
 };
 
+/// Maximal number of logs in a page of `parity_getLogsPaged` and `parity_decodedLogs`.
+const MAX_LOGS_PAGE: u64 = 10_000;
+/// Number of blocks searched for logs at a time.
+const LOGS_PAGE_BATCH: u64 = 1000;
+/// Number of blocks searched for logs before a page is returned, even if not full.
+const LOGS_PAGE_MAX_BLOCKS: u64 = 100_000;
+/// Maximal number of blocks in a response of `parity_feeHistory`.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+
 /// Parity implementation.
 pub struct ParityClient<C, M, S: ?Sized, U> where
 	C: MiningBlockChainClient,
@@ -67,6 +90,10 @@ pub struct ParityClient<C, M, S: ?Sized, U> where
 	signer: Option<Arc<SignerService>>,
 	dapps_interface: Option<String>,
 	dapps_port: Option<u16>,
+	head_lag: Arc<HeadLag>,
+	work_tracker: Arc<WorkTracker>,
+	abi_registry: Option<Arc<AbiRegistry>>,
+	call_cache: Option<Arc<CallCache>>,
 }
 
 impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
@@ -88,6 +115,10 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 		signer: Option<Arc<SignerService>>,
 		dapps_interface: Option<String>,
 		dapps_port: Option<u16>,
+		head_lag: Arc<HeadLag>,
+		work_tracker: Arc<WorkTracker>,
+		abi_registry: Option<Arc<AbiRegistry>>,
+		call_cache: Option<Arc<CallCache>>,
 	) -> Self {
 		ParityClient {
 			client: Arc::downgrade(client),
@@ -101,13 +132,75 @@ impl<C, M, S: ?Sized, U> ParityClient<C, M, S, U> where
 			signer: signer,
 			dapps_interface: dapps_interface,
 			dapps_port: dapps_port,
+			head_lag: head_lag,
+			work_tracker: work_tracker,
+			abi_registry: abi_registry,
+			call_cache: call_cache,
 		}
 	}
+
+	/// Logs matching the filter for a page of `parity_getLogsPaged` or `parity_decodedLogs`,
+	/// with the cursor of the next page.
+	fn logs_page(&self, filter: Filter, count: u64, cursor: Option<LogsCursor>) -> Result<(Vec<LocalizedLogEntry>, Option<LogsCursor>), Error> {
+		if count == 0 {
+			return Err(errors::invalid_params("count", "Page size must be positive"));
+		}
+
+		let client = take_weak!(self.client);
+		let filter: EthcoreFilter = filter.into();
+		let from = client.block_number(filter.from_block).ok_or_else(errors::unknown_block)?;
+		let to = client.block_number(filter.to_block).ok_or_else(errors::unknown_block)?;
+		let count = cmp::min(count, MAX_LOGS_PAGE) as usize;
+		let resume = match cursor {
+			Some(cursor) => {
+				let position = cursor.position()
+					.ok_or_else(|| errors::invalid_params("cursor", "Cursor position out of range"))?;
+				if client.block_hash(BlockId::Number(position.0)) != Some(cursor.block_hash.into()) {
+					return Err(errors::invalid_params("cursor", "Block of the cursor is no longer canonical"));
+				}
+				Some(position)
+			},
+			None => None,
+		};
+
+		let mut logs = Vec::new();
+		let mut block = resume.map_or(from, |(number, _, _)| cmp::max(from, number));
+		let last = cmp::min(to, block.saturating_add(LOGS_PAGE_MAX_BLOCKS - 1));
+		while block <= last {
+			let end = cmp::min(block + LOGS_PAGE_BATCH - 1, last);
+			let batch = EthcoreFilter {
+				from_block: BlockId::Number(block),
+				to_block: BlockId::Number(end),
+				address: filter.address.clone(),
+				topics: filter.topics.clone(),
+				limit: None,
+			};
+
+			for log in client.logs(batch) {
+				let position = (log.block_number, log.transaction_index as u64, log.log_index as u64);
+				if resume.map_or(false, |resume| position < resume) {
+					continue;
+				}
+				if logs.len() == count {
+					let next = LogsCursor::new(log.block_number, log.block_hash, log.transaction_index, log.log_index);
+					return Ok((logs, Some(next)));
+				}
+				logs.push(log);
+			}
+			block = end + 1;
+		}
+
+		let next = match last < to {
+			true => Some(LogsCursor::new(last + 1, client.block_hash(BlockId::Number(last + 1)).unwrap_or_default(), 0, 0)),
+			false => None,
+		};
+		Ok((logs, next))
+	}
 }
 
 impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 	M: MinerService + 'static,
-	C: MiningBlockChainClient + 'static,
+	C: MiningBlockChainClient + ProvingBlockChainClient + 'static,
 	S: SyncProvider + 'static,
 	U: UpdateService + 'static,
 {
@@ -273,6 +366,46 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn list_storage_keys_paged(&self, address: H160, count: u64, token: Option<Bytes>, block_number: Trailing<BlockNumber>) -> Result<Option<StorageKeysPage>, Error> {
+		let client = take_weak!(self.client);
+		let token = match token {
+			Some(token) => Some(StorageKeysToken::decode(&token.0).ok_or_else(|| errors::invalid_params("token", "Malformed continuation token"))?),
+			None => None,
+		};
+
+		let block = match token {
+			Some(ref token) => token.block.clone(),
+			None => BlockIdResolver::new(&*client).resolve_chain(block_number.0)?.hash(),
+		};
+
+		let after = token.map(|token| token.after);
+		let keys = match client.list_storage(BlockId::Hash(block), &address.into(), after.as_ref(), count) {
+			Some(keys) => keys,
+			None => return Ok(None),
+		};
+
+		let next = match keys.last() {
+			Some(last) if keys.len() as u64 == count => Some(StorageKeysToken { block: block, after: last.clone() }.encode()),
+			_ => None,
+		};
+
+		Ok(Some(StorageKeysPage {
+			keys: keys.into_iter().map(Into::into).collect(),
+			next: next,
+		}))
+	}
+
+	fn state_diff(&self, accounts: Vec<H160>, from: BlockNumber, to: BlockNumber) -> Result<StateDiff, Error> {
+		let client = take_weak!(self.client);
+		let accounts: Vec<Address> = accounts.into_iter().map(Into::into).collect();
+		let resolver = BlockIdResolver::new(&*client);
+		let (from, to) = (resolver.resolve_state(from)?, resolver.resolve_state(to)?);
+
+		client.state_diff(&accounts, from.id(), to.id())
+			.map(Into::into)
+			.ok_or_else(errors::state_pruned)
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes, Error> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption_error)
@@ -295,6 +428,14 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 		)
 	}
 
+	fn propagation_stats(&self) -> Result<Vec<PropagationStats>, Error> {
+		Ok(take_weak!(self.sync).propagation_stats().into_iter().map(Into::into).collect())
+	}
+
+	fn receipts_backfill_status(&self) -> Result<Option<ReceiptsBackfillStatus>, Error> {
+		Ok(take_weak!(self.sync).receipts_backfill_status().map(Into::into))
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error> {
 		let transactions = take_weak!(self.miner).local_transactions();
 		Ok(transactions
@@ -372,4 +513,214 @@ impl<C, M, S: ?Sized, U> Parity for ParityClient<C, M, S, U> where
 			block_gap: gap.map(|(x, y)| (x.into(), y.into())),
 		})
 	}
+
+	fn node_status(&self) -> Result<NodeStatus, Error> {
+		let chain_info = take_weak!(self.client).chain_info();
+		let lag = self.head_lag.lag(chain_info.best_block_timestamp);
+
+		Ok(NodeStatus {
+			best_block: chain_info.best_block_number.into(),
+			best_block_timestamp: chain_info.best_block_timestamp.into(),
+			head_lag: lag.map(Into::into),
+			clock_offset: self.head_lag.clock_offset(),
+			trailing: lag.map_or(false, |lag| self.head_lag.is_trailing(lag)),
+		})
+	}
+
+	fn light_peers(&self) -> Result<Vec<LightPeerInfo>, Error> {
+		Err(errors::light_only())
+	}
+
+	fn worker_stats(&self) -> Result<BTreeMap<String, WorkerStats>, Error> {
+		Ok(self.work_tracker.workers().into_iter().map(|(worker, stats)| (worker, stats.into())).collect())
+	}
+
+	fn call_cache_stats(&self) -> Result<Option<CallCacheStats>, Error> {
+		Ok(self.call_cache.as_ref().map(|cache| cache.stats().into()))
+	}
+
+	fn light_cache_usage(&self) -> Result<Vec<LightCacheUsage>, Error> {
+		Err(errors::light_only())
+	}
+
+	fn chain_spec(&self) -> Result<ChainSpec, Error> {
+		Ok(take_weak!(self.client).chain_spec_info().into())
+	}
+
+	fn block_header(&self, number: Trailing<BlockNumber>) -> Result<Option<EncodedHeader>, Error> {
+		Ok(take_weak!(self.client).block_header(number.0.into()).map(Into::into))
+	}
+
+	fn simulate_transaction(&self, request: SimulationRequest, block: Trailing<BlockNumber>) -> Result<SimulationResult, Error> {
+		let signed = match request {
+			SimulationRequest::Call(ref request) if request.env.is_some() => return Err(errors::env_overrides_unsupported()),
+			SimulationRequest::Call(request) => fake_sign::sign_call(&self.client, &self.miner, CallRequest::into(request))?,
+			SimulationRequest::Raw(raw) => {
+				let tx = UntrustedRlp::new(&raw.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
+				SignedTransaction::new(tx).map_err(errors::from_transaction_error)?
+			},
+		};
+
+		let analytics = CallAnalytics {
+			transaction_tracing: false,
+			vm_tracing: false,
+			state_diffing: true,
+		};
+
+		let client = take_weak!(self.client);
+		let result = match BlockIdResolver::new(&*client).resolve_state(block.0)? {
+			ResolvedBlock::Pending => take_weak!(self.miner).call(&*client, &signed, analytics),
+			block => client.call(&signed, block.id(), analytics),
+		};
+
+		result.map(Into::into).map_err(errors::from_call_error)
+	}
+
+	fn prove_call(&self, request: CallRequest, block: Trailing<BlockNumber>) -> Result<CallProof, Error> {
+		if request.env.is_some() {
+			return Err(errors::env_overrides_unsupported());
+		}
+
+		let client = take_weak!(self.client);
+		let block_hash = BlockIdResolver::new(&*client).resolve_chain(block.0)?.hash();
+		let id = BlockId::Hash(block_hash);
+
+		// built like the transaction of a PIP execution request, so the proof verifies the same way.
+		let request = CallRequest::into(request);
+		let from = request.from.unwrap_or(0.into());
+		let nonce = client.nonce(&from, id).ok_or_else(errors::state_pruned)?;
+		let signed = EthTransaction {
+			nonce: nonce,
+			action: request.to.map_or(Action::Create, Action::Call),
+			gas: request.gas.unwrap_or(50_000_000.into()),
+			gas_price: request.gas_price.unwrap_or(0.into()),
+			value: request.value.unwrap_or(0.into()),
+			data: request.data.map_or_else(Vec::new, |d| d.to_vec()),
+		}.fake_sign(from);
+
+		let (output, items) = client.prove_transaction(signed, id).ok_or_else(errors::state_pruned)?;
+
+		Ok(CallProof {
+			block_hash: block_hash.into(),
+			nonce: nonce.into(),
+			output: output.into(),
+			proof: rlp::encode(&ExecutionResponse { items: items }).to_vec().into(),
+		})
+	}
+
+	fn intrinsic_gas(&self, request: CallRequest) -> Result<U256, Error> {
+		let request = CallRequest::into(request);
+		let transaction = EthTransaction {
+			nonce: 0.into(),
+			action: request.to.map_or(Action::Create, Action::Call),
+			// without supplied gas only the init code size is checked
+			gas: request.gas.unwrap_or_else(|| u64::max_value().into()),
+			gas_price: request.gas_price.unwrap_or(0.into()),
+			value: request.value.unwrap_or(0.into()),
+			data: request.data.map_or_else(Vec::new, |d| d.to_vec()),
+		};
+
+		let schedule = take_weak!(self.client).latest_schedule();
+		transaction.verify_static(&schedule, take_weak!(self.miner).max_init_code_size())
+			.map_err(|e| errors::from_transaction_error(e.into()))?;
+		Ok(U256::from(transaction.gas_required(&schedule)))
+	}
+
+	fn shared_db_handshake(&self, genesis_hash: H256) -> Result<DbFlushMarker, Error> {
+		let client = take_weak!(self.client);
+		if client.is_read_only() {
+			return Err(errors::database_error("Node is read-only and can't share its database."));
+		}
+		if H256::from(client.chain_info().genesis_hash) != genesis_hash {
+			return Err(errors::invalid_params("genesisHash", "Genesis hash doesn't match the node's chain."));
+		}
+		Ok(client.db_flush_marker().into())
+	}
+
+	fn db_flush_marker(&self) -> Result<DbFlushMarker, Error> {
+		Ok(take_weak!(self.client).db_flush_marker().into())
+	}
+
+	fn compaction_status(&self) -> Result<CompactionStatus, Error> {
+		Ok(take_weak!(self.client).compaction_status().into())
+	}
+
+	fn db_stats(&self) -> Result<Vec<DbColumnStats>, Error> {
+		Ok(take_weak!(self.client).db_stats().into_iter().map(Into::into).collect())
+	}
+
+	fn pending_snapshot(&self) -> Result<PendingSnapshot, Error> {
+		let client = take_weak!(self.client);
+		Ok(take_weak!(self.miner).pending_snapshot(&*client).into())
+	}
+
+	fn logs_paged(&self, filter: Filter, count: u64, cursor: Option<LogsCursor>) -> Result<LogsPage, Error> {
+		let (logs, next) = self.logs_page(filter, count, cursor)?;
+		Ok(LogsPage {
+			logs: logs.into_iter().map(Log::from).collect(),
+			next: next,
+		})
+	}
+
+	fn decoded_logs(&self, filter: Filter, count: u64, cursor: Option<LogsCursor>) -> Result<DecodedLogsPage, Error> {
+		let (logs, next) = self.logs_page(filter, count, cursor)?;
+
+		if let Some(ref registry) = self.abi_registry {
+			let client = take_weak!(self.client);
+			registry.refresh(&client.chain_info().best_block_hash, |name| client.registry_address(name.into()));
+		}
+
+		Ok(DecodedLogsPage {
+			logs: logs.into_iter()
+				.map(|log| {
+					let event = self.abi_registry.as_ref()
+						.and_then(|registry| registry.decode(&log.entry.address, &log.entry.topics, &log.entry.data));
+					DecodedLog {
+						log: log.into(),
+						event: event.map(Into::into),
+					}
+				})
+				.collect(),
+			next: next,
+		})
+	}
+
+	fn fee_history(&self, count: u64, newest: Trailing<BlockNumber>) -> Result<FeeHistory, Error> {
+		if count == 0 || count > MAX_FEE_HISTORY_BLOCKS {
+			return Err(errors::invalid_params("count", format!("Block count must be between 1 and {}", MAX_FEE_HISTORY_BLOCKS)));
+		}
+
+		let client = take_weak!(self.client);
+		let newest = BlockIdResolver::new(&*client).resolve_chain(newest.0)?.hash();
+		Ok(client.block_fee_stats(BlockId::Hash(newest), count).into())
+	}
+
+	fn cht_ranges(&self) -> Result<Vec<ChtRange>, Error> {
+		let client = take_weak!(self.client);
+		Ok(client.cht_roots().into_iter()
+			.map(|(number, root)| ChtRange::new(number, root))
+			.collect())
+	}
+
+	fn export_pending_transactions(&self) -> Result<Bytes, Error> {
+		let miner = take_weak!(self.miner);
+		let local: HashSet<_> = miner.local_transactions().into_iter()
+			.filter(|&(_, ref status)| match *status {
+				QueueStatus::Pending | QueueStatus::Future => true,
+				_ => false,
+			})
+			.map(|(hash, _)| hash)
+			.collect();
+
+		let transactions = miner.pending_transactions().into_iter()
+			.chain(miner.future_transactions())
+			.map(|pending| PooledTransaction {
+				local: local.contains(&pending.hash()),
+				condition: pending.condition,
+				transaction: pending.transaction.into(),
+			})
+			.collect();
+
+		Ok(rlp::encode(&PoolBundle { transactions: transactions }).to_vec().into())
+	}
 }
@@ -24,20 +24,24 @@ use ethstore::KeyFile;
 use ethcore::account_provider::AccountProvider;
 
 use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
 use v1::helpers::errors;
+use v1::helpers::signing_audit::SigningAudit;
 use v1::traits::ParityAccounts;
-use v1::types::{H160 as RpcH160, H256 as RpcH256, DappId, Derive, DeriveHierarchical, DeriveHash};
+use v1::types::{H160 as RpcH160, H256 as RpcH256, DappId, Derive, DeriveHierarchical, DeriveHash, SigningAuditEntry};
 
 /// Account management (personal) rpc implementation.
 pub struct ParityAccountsClient {
 	accounts: Weak<AccountProvider>,
+	signing_audit: Arc<SigningAudit>,
 }
 
 impl ParityAccountsClient {
 	/// Creates new PersonalClient
-	pub fn new(store: &Arc<AccountProvider>) -> Self {
+	pub fn new(store: &Arc<AccountProvider>, signing_audit: Arc<SigningAudit>) -> Self {
 		ParityAccountsClient {
 			accounts: Arc::downgrade(store),
+			signing_audit: signing_audit,
 		}
 	}
 }
@@ -327,6 +331,14 @@ impl ParityAccounts for ParityAccountsClient {
 			.map(Into::into)
 			.map_err(|e| errors::account("Could not export account.", e))
 	}
+
+	fn signing_audit(&self, count: Trailing<usize>) -> Result<Vec<SigningAuditEntry>, Error> {
+		let count = match count.0 {
+			0 => None,
+			count => Some(count),
+		};
+		Ok(self.signing_audit.entries(count))
+	}
 }
 
 fn into_vec<A, B>(a: Vec<A>) -> Vec<B> where
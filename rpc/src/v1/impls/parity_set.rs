@@ -18,9 +18,10 @@
 use std::io;
 use std::sync::{Arc, Weak};
 
-use ethcore::miner::MinerService;
+use ethcore::miner::{MinerService, PoolBundle};
 use ethcore::client::MiningBlockChainClient;
 use ethcore::mode::Mode;
+use ethcore::transaction::{SignedTransaction, PendingTransaction};
 use ethsync::ManageNetwork;
 use fetch::{self, Fetch};
 use futures::{BoxFuture, Future};
@@ -77,7 +78,7 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 	}
 
 	fn set_extra_data(&self, extra_data: Bytes) -> Result<bool, Error> {
-		take_weak!(self.miner).set_extra_data(extra_data.into_vec());
+		take_weak!(self.miner).set_extra_data(extra_data.into_vec()).map_err(|e| errors::invalid_params("extraData", e))?;
 		Ok(true)
 	}
 
@@ -91,6 +92,22 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn propose_signer(&self, address: H160, authorize: bool) -> Result<bool, Error> {
+		if take_weak!(self.miner).propose_signer(address.into(), authorize) {
+			Ok(true)
+		} else {
+			Err(errors::signer_voting_unsupported())
+		}
+	}
+
+	fn discard_signer_proposal(&self, address: H160) -> Result<bool, Error> {
+		if take_weak!(self.miner).discard_signer_proposal(&address.into()) {
+			Ok(true)
+		} else {
+			Err(errors::signer_voting_unsupported())
+		}
+	}
+
 	fn set_transactions_limit(&self, limit: usize) -> Result<bool, Error> {
 		take_weak!(self.miner).set_transactions_limit(limit);
 		Ok(true)
@@ -115,6 +132,11 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		}
 	}
 
+	fn clear_bad_blocks(&self) -> Result<bool, Error> {
+		take_weak!(self.client).clear_bad_blocks();
+		Ok(true)
+	}
+
 	fn drop_non_reserved_peers(&self) -> Result<bool, Error> {
 		take_weak!(self.net).deny_unreserved_peers();
 		Ok(true)
@@ -125,6 +147,13 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn set_public_address(&self, address: String) -> Result<bool, Error> {
+		match take_weak!(self.net).set_public_address(address) {
+			Ok(()) => Ok(true),
+			Err(e) => Err(errors::invalid_params("Public address", e)),
+		}
+	}
+
 	fn start_network(&self) -> Result<bool, Error> {
 		take_weak!(self.net).start_network();
 		Ok(true)
@@ -179,4 +208,42 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 
 		Ok(miner.remove_pending_transaction(&*client, &hash).map(Into::into))
 	}
+
+	fn compact_database(&self) -> Result<bool, Error> {
+		take_weak!(self.client).compact_database()
+			.map(|_| true)
+			.map_err(errors::database_error)
+	}
+
+	fn import_pending_transactions(&self, bundle: Bytes) -> Result<usize, Error> {
+		let miner = take_weak!(self.miner);
+		let client = take_weak!(self.client);
+		let bundle = PoolBundle::decode(&bundle.into_vec()).map_err(|e| errors::invalid_params("bundle", e))?;
+
+		let mut imported = 0;
+		for pooled in bundle.transactions {
+			let hash = pooled.transaction.hash();
+			let result = match pooled.local {
+				true => SignedTransaction::new(pooled.transaction)
+					.map_err(Into::into)
+					.and_then(|tx| miner.import_own_transaction(&*client, PendingTransaction::new(tx, pooled.condition))),
+				false => miner.import_external_transactions(&*client, vec![pooled.transaction])
+					.pop()
+					.expect("one result is returned per transaction; qed"),
+			};
+			match result {
+				Ok(_) => imported += 1,
+				Err(e) => debug!(target: "rpc", "Skipping bundled transaction {:?}: {:?}", hash, e),
+			}
+		}
+		Ok(imported)
+	}
+
+	fn pin_light_peer(&self, _peer: usize) -> Result<bool, Error> {
+		Err(errors::light_only())
+	}
+
+	fn unpin_light_peer(&self, _peer: usize) -> Result<bool, Error> {
+		Err(errors::light_only())
+	}
 }
@@ -0,0 +1,91 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parity pub-sub rpc implementation.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use futures::{future, BoxFuture, Future};
+use jsonrpc_core::Error;
+use util::Mutex;
+
+use v1::helpers::errors;
+use v1::helpers::sync_events::SyncEvents;
+use v1::helpers::transaction_events::TransactionEvents;
+use v1::metadata::Metadata;
+use v1::traits::PubSub;
+use v1::types::{Index, U256};
+
+#[derive(Clone, Copy)]
+enum Topic {
+	SyncStatus,
+	TransactionQueue,
+}
+
+/// PubSub implementation.
+pub struct PubSubClient {
+	sync_events: Arc<SyncEvents>,
+	transaction_events: Arc<TransactionEvents>,
+	next_id: AtomicUsize,
+	subscriptions: Mutex<HashMap<usize, Topic>>,
+}
+
+impl PubSubClient {
+	/// Creates new `PubSubClient`.
+	pub fn new(sync_events: Arc<SyncEvents>, transaction_events: Arc<TransactionEvents>) -> Self {
+		PubSubClient {
+			sync_events: sync_events,
+			transaction_events: transaction_events,
+			next_id: AtomicUsize::new(1),
+			subscriptions: Default::default(),
+		}
+	}
+}
+
+impl PubSub for PubSubClient {
+	type Metadata = Metadata;
+
+	fn subscribe(&self, meta: Metadata, topic: String) -> BoxFuture<U256, Error> {
+		let sink = match meta.sink {
+			Some(sink) => sink,
+			None => return future::err(errors::subscriptions_unsupported()).boxed(),
+		};
+
+		let topic = match topic.as_str() {
+			"syncStatus" => Topic::SyncStatus,
+			"transactionQueue" => Topic::TransactionQueue,
+			_ => return future::err(errors::invalid_params("topic", format!("Unknown topic: {}", topic))).boxed(),
+		};
+
+		let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+		match topic {
+			Topic::SyncStatus => self.sync_events.subscribe(id, sink),
+			Topic::TransactionQueue => self.transaction_events.subscribe(id, sink),
+		}
+		self.subscriptions.lock().insert(id, topic);
+		future::ok(id.into()).boxed()
+	}
+
+	fn unsubscribe(&self, id: Index) -> Result<bool, Error> {
+		let id = id.value();
+		Ok(match self.subscriptions.lock().remove(&id) {
+			Some(Topic::SyncStatus) => self.sync_events.unsubscribe(id),
+			Some(Topic::TransactionQueue) => self.transaction_events.unsubscribe(id),
+			None => false,
+		})
+	}
+}
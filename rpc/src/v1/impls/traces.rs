@@ -27,6 +27,8 @@ use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
 use v1::traits::Traces;
 use v1::helpers::{errors, fake_sign};
+use v1::helpers::block_id::{BlockIdResolver, ResolvedBlock};
+use v1::helpers::response_limits::{self, ResponseLimits};
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, H256};
 
 fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
@@ -41,6 +43,7 @@ fn to_call_analytics(flags: Vec<String>) -> CallAnalytics {
 pub struct TracesClient<C, M> {
 	client: Weak<C>,
 	miner: Weak<M>,
+	response_limits: Option<Arc<ResponseLimits>>,
 }
 
 impl<C, M> TracesClient<C, M> {
@@ -49,19 +52,35 @@ impl<C, M> TracesClient<C, M> {
 		TracesClient {
 			client: Arc::downgrade(client),
 			miner: Arc::downgrade(miner),
+			response_limits: None,
 		}
 	}
+
+	/// Reject trace results whose estimated size exceeds the configured limits.
+	pub fn with_response_limits(mut self, limits: Arc<ResponseLimits>) -> Self {
+		self.response_limits = Some(limits);
+		self
+	}
+
+	fn check_traces(&self, method: &str, traces: Vec<LocalizedTrace>) -> Result<Vec<LocalizedTrace>, Error> {
+		response_limits::check(self.response_limits.as_ref().map(|l| &**l), method, &traces)?;
+		Ok(traces)
+	}
 }
 
 impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'static, M: MinerService + 'static {
 	fn filter(&self, filter: TraceFilter) -> Result<Option<Vec<LocalizedTrace>>, Error> {
-		Ok(take_weak!(self.client).filter_traces(filter.into())
-			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+		match take_weak!(self.client).filter_traces(filter.into()) {
+			Some(traces) => self.check_traces("trace_filter", traces.into_iter().map(LocalizedTrace::from).collect()).map(Some),
+			None => Ok(None),
+		}
 	}
 
 	fn block_traces(&self, block_number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>, Error> {
-		Ok(take_weak!(self.client).block_traces(block_number.into())
-			.map(|traces| traces.into_iter().map(LocalizedTrace::from).collect()))
+		match take_weak!(self.client).block_traces(block_number.into()) {
+			Some(traces) => self.check_traces("trace_block", traces.into_iter().map(LocalizedTrace::from).collect()).map(Some),
+			None => Ok(None),
+		}
 	}
 
 	fn transaction_traces(&self, transaction_hash: H256) -> Result<Option<Vec<LocalizedTrace>>, Error> {
@@ -82,10 +101,16 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 	fn call(&self, request: CallRequest, flags: Vec<String>, block: Trailing<BlockNumber>) -> Result<TraceResults, Error> {
 		let block = block.0;
 
+		let env = request.env_overrides();
 		let request = CallRequest::into(request);
 		let signed = fake_sign::sign_call(&self.client, &self.miner, request)?;
 
-		take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags))
+		let client = take_weak!(self.client);
+		let block = match BlockIdResolver::new(&*client).resolve_state(block)? {
+			ResolvedBlock::Pending if !env.is_empty() => return Err(errors::env_overrides_unsupported()),
+			block => block,
+		};
+		client.call_with_env(&signed, block.id(), to_call_analytics(flags), &env)
 			.map(TraceResults::from)
 			.map_err(errors::from_call_error)
 	}
@@ -96,7 +121,9 @@ impl<C, M> Traces for TracesClient<C, M> where C: MiningBlockChainClient + 'stat
 		let tx = UntrustedRlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e))?;
 		let signed = SignedTransaction::new(tx).map_err(errors::from_transaction_error)?;
 
-		take_weak!(self.client).call(&signed, block.into(), to_call_analytics(flags))
+		let client = take_weak!(self.client);
+		let block = BlockIdResolver::new(&*client).resolve_state(block)?;
+		client.call(&signed, block.id(), to_call_analytics(flags))
 			.map(TraceResults::from)
 			.map_err(errors::from_call_error)
 	}
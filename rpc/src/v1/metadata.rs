@@ -14,15 +14,48 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::fmt;
+use std::sync::Arc;
 use jsonrpc_core;
 
 use v1::types::{DappId, Origin};
 
+/// Pushes notifications to the client of a persistent connection.
+#[derive(Clone)]
+pub struct Sink(Arc<Fn(String) -> bool + Send + Sync>);
+
+impl Sink {
+	/// Create new sink sending messages with given function.
+	/// The function should return `false` once the connection is closed.
+	pub fn new<F>(send: F) -> Self where F: Fn(String) -> bool + Send + Sync + 'static {
+		Sink(Arc::new(send))
+	}
+
+	/// Send a message, returns `false` if the connection is closed.
+	pub fn send(&self, message: String) -> bool {
+		(self.0)(message)
+	}
+}
+
+impl fmt::Debug for Sink {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Sink")
+	}
+}
+
+impl PartialEq for Sink {
+	fn eq(&self, other: &Sink) -> bool {
+		&*self.0 as *const _ as *const () == &*other.0 as *const _ as *const ()
+	}
+}
+
 /// RPC methods metadata.
 #[derive(Clone, Default, Debug, PartialEq)]
 pub struct Metadata {
 	/// Request origin
 	pub origin: Origin,
+	/// Notifications sink of a persistent connection (if supported by the transport)
+	pub sink: Option<Sink>,
 }
 
 impl Metadata {
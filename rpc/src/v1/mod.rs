@@ -58,8 +58,8 @@ pub mod traits;
 pub mod tests;
 pub mod types;
 
-pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Signer, Personal, Traces, Rpc};
+pub use self::traits::{Web3, Eth, EthFilter, EthSigning, Net, Parity, ParityAccounts, ParitySet, ParitySigning, Signer, Personal, PubSub, Traces, Rpc};
 pub use self::impls::*;
-pub use self::helpers::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, block_import, informant, dispatch};
-pub use self::metadata::Metadata;
+pub use self::helpers::{SigningQueue, SignerService, ConfirmationsQueue, NetworkSettings, abi_registry, block_import, informant, dispatch, call_cache, missing_cache, head_lag, request_budget, response_limits, signing_audit, sync_events, transaction_events, trusted_fallback, work_auth, work_tracker};
+pub use self::metadata::{Metadata, Sink};
 pub use self::types::Origin;
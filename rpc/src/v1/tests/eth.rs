@@ -58,6 +58,8 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			reseal_on_external_tx: true,
 			reseal_on_own_tx: true,
 			tx_queue_size: 1024,
+			tx_queue_nonce_gap: !U256::zero(),
+			tx_queue_sender_memory_limit: usize::max_value(),
 			tx_gas_limit: !U256::zero(),
 			tx_queue_strategy: PrioritizationStrategy::GasPriceOnly,
 			tx_queue_gas_limit: GasLimit::None,
@@ -68,6 +70,10 @@ fn miner_service(spec: &Spec, accounts: Arc<AccountProvider>) -> Arc<Miner> {
 			work_queue_size: 50,
 			enable_resubmission: true,
 			refuse_service_transactions: false,
+			prepare_block_ahead: false,
+			gas_limit_contract: None,
+			tx_queue_max_init_code_size: None,
+			local_gas_reserve: 0,
 		},
 		GasPricer::new_fixed(20_000_000_000u64.into()),
 		&spec,
@@ -17,15 +17,15 @@
 //! Test implementation of miner service.
 
 use std::collections::hash_map::Entry;
-use util::{Address, H256, Bytes, U256, Uint};
+use util::{Address, H256, Bytes, U256, Uint, OutOfBounds};
 use util::standard::*;
-use ethcore::error::{Error, CallError};
+use ethcore::error::{Error, CallError, BlockError};
 use ethcore::client::{MiningBlockChainClient, Executed, CallAnalytics};
 use ethcore::block::{ClosedBlock, IsBlock};
 use ethcore::header::BlockNumber;
 use ethcore::transaction::{UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use ethcore::receipt::{Receipt, RichReceipt};
-use ethcore::miner::{MinerService, MinerStatus, TransactionImportResult, LocalTransactionStatus};
+use ethcore::miner::{MinerService, MinerStatus, PendingSnapshot, TransactionImportResult, LocalTransactionStatus};
 use ethcore::account_provider::SignError as AccountError;
 
 /// Test miner service.
@@ -44,6 +44,10 @@ pub struct TestMinerService {
 	pub last_nonces: RwLock<HashMap<Address, U256>>,
 	/// Password held by Engine.
 	pub password: RwLock<String>,
+	/// Pending block snapshot.
+	pub pending_snapshot: RwLock<PendingSnapshot>,
+	/// Signer votes proposed to the engine.
+	pub signer_proposals: RwLock<HashMap<Address, bool>>,
 
 	min_gas_price: RwLock<U256>,
 	gas_range_target: RwLock<(U256, U256)>,
@@ -66,6 +70,13 @@ impl Default for TestMinerService {
 			gas_range_target: RwLock::new((U256::from(12345), U256::from(54321))),
 			author: RwLock::new(Address::zero()),
 			password: RwLock::new(String::new()),
+			pending_snapshot: RwLock::new(PendingSnapshot {
+				ordinal: 1,
+				number: 1,
+				parent_hash: H256::default(),
+				transactions: 0,
+			}),
+			signer_proposals: RwLock::new(HashMap::new()),
 			extra_data: RwLock::new(vec![1, 2, 3, 4]),
 			limit: RwLock::new(1024),
 			tx_gas_limit: RwLock::new(!U256::zero()),
@@ -110,8 +121,22 @@ impl MinerService for TestMinerService {
 		Ok(())
 	}
 
-	fn set_extra_data(&self, extra_data: Bytes) {
+	fn propose_signer(&self, address: Address, authorize: bool) -> bool {
+		self.signer_proposals.write().insert(address, authorize);
+		true
+	}
+
+	fn discard_signer_proposal(&self, address: &Address) -> bool {
+		self.signer_proposals.write().remove(address);
+		true
+	}
+
+	fn set_extra_data(&self, extra_data: Bytes) -> Result<(), BlockError> {
+		if extra_data.len() > 32 {
+			return Err(BlockError::ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(32), found: extra_data.len() }));
+		}
 		*self.extra_data.write() = extra_data;
+		Ok(())
 	}
 
 	/// Set the lower gas limit we wish to target when sealing a new block.
@@ -272,7 +297,7 @@ impl MinerService for TestMinerService {
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, _chain: &MiningBlockChainClient, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<(), Error> {
-		unimplemented!();
+		Err(Error::PowHashInvalid)
 	}
 
 	fn balance(&self, _chain: &MiningBlockChainClient, address: &Address) -> Option<U256> {
@@ -283,10 +308,18 @@ impl MinerService for TestMinerService {
 			.unwrap_or(Some(U256::default()))
 	}
 
+	fn pending_snapshot(&self, _chain: &MiningBlockChainClient) -> PendingSnapshot {
+		self.pending_snapshot.read().clone()
+	}
+
 	fn call(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction, _analytics: CallAnalytics) -> Result<Executed, CallError> {
 		unimplemented!();
 	}
 
+	fn estimate_gas(&self, _chain: &MiningBlockChainClient, _t: &SignedTransaction) -> Result<U256, CallError> {
+		unimplemented!();
+	}
+
 	fn storage_at(&self, _chain: &MiningBlockChainClient, address: &Address, position: &H256) -> Option<H256> {
 		self.latest_closed_block.lock()
 			.as_ref()
@@ -48,4 +48,5 @@ impl SnapshotService for TestSnapshotService {
 	fn restore_state_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn restore_block_chunk(&self, _hash: H256, _chunk: Bytes) { }
 	fn provide_canon_hashes(&self, _hashes: &[(u64, H256)]) { }
+	fn completed_chunks(&self, _manifest: ManifestData) -> Vec<H256> { Vec::new() }
 }
\ No newline at end of file
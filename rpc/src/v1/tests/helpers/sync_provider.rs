@@ -18,7 +18,7 @@
 
 use std::collections::BTreeMap;
 use util::{H256, RwLock};
-use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use ethsync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats, PropagationStats, PropagationKind, ReceiptsBackfillStatus};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -53,6 +53,7 @@ impl TestSyncProvider {
 				num_snapshot_chunks: 0,
 				snapshot_chunks_done: 0,
 				last_imported_old_block_number: None,
+				num_head_stall_rotations: 0,
 			}),
 		}
 	}
@@ -121,5 +122,29 @@ impl SyncProvider for TestSyncProvider {
 			}
 		]
 	}
+
+	fn propagation_stats(&self) -> Vec<PropagationStats> {
+		vec![
+			PropagationStats {
+				hash: 2.into(),
+				kind: PropagationKind::Block,
+				block_number: 15,
+				first_propagated: 1500000010,
+				last_propagated: 1500000011,
+				sent_to: 3,
+				announced_to: 7,
+			},
+		]
+	}
+
+	fn receipts_backfill_status(&self) -> Option<ReceiptsBackfillStatus> {
+		Some(ReceiptsBackfillStatus {
+			start_block: 1000,
+			target_block: 900,
+			next_block: Some(950),
+			imported: 12,
+			pending: 3,
+		})
+	}
 }
 
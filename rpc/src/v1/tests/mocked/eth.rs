@@ -35,6 +35,11 @@ use ethsync::SyncState;
 use jsonrpc_core::IoHandler;
 use v1::{Eth, EthClient, EthClientOptions, EthFilter, EthFilterClient, EthSigning, SigningUnsafeClient};
 use v1::helpers::dispatch::FullDispatcher;
+use v1::helpers::call_cache::CallCache;
+use v1::helpers::missing_cache::MissingCache;
+use v1::helpers::response_limits::ResponseLimits;
+use v1::helpers::work_auth::WorkAuth;
+use v1::helpers::work_tracker::{WorkTracker, WorkerSubmissions};
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestSnapshotService};
 use v1::metadata::Metadata;
 use v1::types::Origin;
@@ -230,6 +235,34 @@ fn rpc_eth_logs() {
 	assert_eq!(tester.io.handle_request_sync(request3), Some(response3.to_owned()));
 }
 
+#[test]
+fn rpc_eth_logs_response_limit_after_limit() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.response_limits = Some(Arc::new("eth_getLogs=600".parse::<ResponseLimits>().unwrap()));
+	}));
+	let log = |index| LocalizedLogEntry {
+		block_number: 1,
+		block_hash: H256::default(),
+		entry: LogEntry {
+			address: Address::default(),
+			topics: vec![],
+			data: vec![1,2,3],
+		},
+		transaction_index: 0,
+		transaction_log_index: index,
+		transaction_hash: H256::default(),
+		log_index: index,
+	};
+	tester.client.set_logs(vec![log(0), log(1)]);
+
+	let request1 = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{}], "id": 1}"#;
+	let request2 = r#"{"jsonrpc": "2.0", "method": "eth_getLogs", "params": [{"limit":1}], "id": 1}"#;
+	let response2 = r#"{"jsonrpc":"2.0","result":[{"address":"0x0000000000000000000000000000000000000000","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000000","blockNumber":"0x1","data":"0x010203","logIndex":"0x1","topics":[],"transactionHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactionIndex":"0x0","transactionLogIndex":"0x1","type":"mined"}],"id":1}"#;
+
+	assert!(tester.io.handle_request_sync(request1).unwrap().contains(r#""reason":"responseTooLarge""#));
+	assert_eq!(tester.io.handle_request_sync(request2), Some(response2.to_owned()));
+}
+
 #[test]
 fn rpc_logs_filter() {
 	let tester = EthTester::default();
@@ -635,6 +668,85 @@ fn rpc_eth_call_latest() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_call_with_env_overrides() {
+	use ethcore::client::EnvOverrides;
+
+	let tester = EthTester::default();
+	tester.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_call","params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","env":{"difficulty":"0x100","timestamp":"0x5"}},"latest"],"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(*tester.client.last_env_overrides.read(), Some(EnvOverrides {
+		timestamp: Some(5),
+		difficulty: Some(0x100.into()),
+		..Default::default()
+	}));
+
+	let request = r#"{"jsonrpc":"2.0","method":"eth_call","params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","env":{"timestamp":"0x5"}},"pending"],"id":1}"#;
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+}
+
+#[test]
+fn rpc_eth_call_cached_until_new_block() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.call_cache = Some(Arc::new(CallCache::new(Duration::from_secs(60), 1024)));
+	}));
+	let executed = |output: Vec<u8>| Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![],
+		contracts_created: vec![],
+		output: output,
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	});
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_call",
+		"params": [{
+			"from": "0xb60e8dd61c5d32be8058bb8eb970870f07233155",
+			"to": "0xd46e8dd67c5d32be8058bb8eb970870f07244567",
+			"gas": "0x76c0",
+			"gasPrice": "0x9184e72a000",
+			"value": "0x9184e72a",
+			"data": "0xd46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675"
+		},
+		"latest"],
+		"id": 1
+	}"#;
+
+	tester.client.set_execution_result(executed(vec![0x12, 0x34, 0xff]));
+	assert_eq!(tester.io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#.to_owned()));
+
+	// served from cache
+	tester.client.set_execution_result(executed(vec![0x56]));
+	assert_eq!(tester.io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0x1234ff","id":1}"#.to_owned()));
+
+	// new head invalidates the cache
+	tester.client.add_blocks(1, EachBlockWith::Nothing);
+	assert_eq!(tester.io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":"0x56","id":1}"#.to_owned()));
+}
+
 #[test]
 fn rpc_eth_call() {
 	let tester = EthTester::default();
@@ -1023,6 +1135,36 @@ fn rpc_eth_transaction_receipt() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_transaction_receipt_with_proof_of_unknown_block() {
+	let receipt = LocalizedReceipt {
+		transaction_hash: H256::zero(),
+		transaction_index: 0,
+		block_hash: H256::from_str("ed76641c68a1c641aee09a94b3b471f4dc0316efe5ac19cf488e2674cf8d05b5").unwrap(),
+		block_number: 0x4510c,
+		cumulative_gas_used: U256::from(0x20),
+		gas_used: U256::from(0x10),
+		contract_address: None,
+		logs: vec![],
+		log_bloom: 0.into(),
+		state_root: Some(0.into()),
+	};
+
+	let hash = H256::from_str("b903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238").unwrap();
+	let tester = EthTester::default();
+	tester.client.set_transaction_receipt(TransactionId::Hash(hash), receipt);
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionReceipt",
+		"params": ["0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238", {"withProof": true}],
+		"id": 1
+	}"#;
+
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains("Unknown block number"), "{}", response);
+}
+
 #[test]
 fn rpc_eth_transaction_receipt_null() {
 	let tester = EthTester::default();
@@ -1038,6 +1180,44 @@ fn rpc_eth_transaction_receipt_null() {
 	assert_eq!(tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_eth_missing_receipt_cached_until_new_block() {
+	let tester = EthTester::new_with_options(EthClientOptions::with(|options| {
+		options.missing_cache = Some(Arc::new(MissingCache::new(Duration::from_secs(60))));
+	}));
+	let hash = H256::from_str("b903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238").unwrap();
+	let receipt = LocalizedReceipt {
+		transaction_hash: hash,
+		transaction_index: 0,
+		block_hash: H256::zero(),
+		block_number: 0x4510c,
+		cumulative_gas_used: U256::from(0x20),
+		gas_used: U256::from(0x10),
+		contract_address: None,
+		logs: vec![],
+		log_bloom: 0.into(),
+		state_root: None,
+	};
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "eth_getTransactionReceipt",
+		"params": ["0xb903239f8543d04b5dc1ba6579132b143087c68db1b2168786408fcbce568238"],
+		"id": 1
+	}"#;
+	let null = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(tester.io.handle_request_sync(request), Some(null.to_owned()));
+
+	// served from cache
+	tester.client.set_transaction_receipt(TransactionId::Hash(hash), receipt);
+	assert_eq!(tester.io.handle_request_sync(request), Some(null.to_owned()));
+
+	// new head invalidates the cache
+	tester.client.add_blocks(1, EachBlockWith::Nothing);
+	let response = tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""blockNumber":"0x4510c""#), "{}", response);
+}
+
 // These tests are incorrect: their output is undefined as long as eth_getCompilers is [].
 // Will ignore for now, but should probably be replaced by more substantial tests which check
 // the output of eth_getCompilers to determine whether to test. CI systems can then be preinstalled
@@ -1113,6 +1293,60 @@ fn rpc_get_work_should_not_return_block_number() {
 	assert_eq!(eth_tester.io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_work_with_worker_authentication() {
+	let auth = Arc::new(WorkAuth::new("secret", Some(1)));
+	let eth_tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.work_auth = Some(auth.clone());
+	}));
+	eth_tester.miner.set_author(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap());
+
+	// work packages are signed.
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getWork", "params": [], "id": 1}"#;
+	let signature = auth.sign_work(
+		&H256::from_str("3bbe93f74e7b97ae00784aeff8819c5cb600dd87e8b282a5d3446f3f871f0347").unwrap(),
+		&H256::zero(),
+		&H256::from_str("0000800000000000000000000000000000000000000000000000000000000000").unwrap(),
+	);
+	let response = eth_tester.io.handle_request_sync(request).unwrap();
+	assert!(response.contains(&format!(r#""0x1","0x{}"]"#, signature.hex())), "{}", response);
+
+	// submissions require credentials.
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_submitWork", "params": ["0x0000000000000001", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32005,"message":"Invalid worker credentials."},"id":1}"#;
+	assert_eq!(eth_tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "eth_submitWork", "params": ["0x0000000000000001", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", {{"worker":"rig1","token":"0x{}"}}], "id": 1}}"#, auth.token("rig1").hex());
+	assert_eq!(eth_tester.io.handle_request_sync(&request), Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.to_owned()));
+	assert_eq!(auth.stats("rig1").unwrap().rejected, 1);
+
+	// only one submission per minute allowed.
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32006,"message":"Worker exceeded 1 submissions per minute."},"id":1}"#;
+	assert_eq!(eth_tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_submit_work_reports_stale_work() {
+	let tracker = Arc::new(WorkTracker::default());
+	let eth_tester = EthTester::new_with_options(EthClientOptions::with(|mut options| {
+		options.work_tracker = Some(tracker.clone());
+	}));
+	eth_tester.miner.set_author(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_getWork", "params": [], "id": 1}"#;
+	eth_tester.io.handle_request_sync(request).unwrap();
+
+	// the test miner knows no work, as if it was superseded meanwhile.
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_submitWork", "params": ["0x0000000000000001", "0x3bbe93f74e7b97ae00784aeff8819c5cb600dd87e8b282a5d3446f3f871f0347", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", {"worker":"rig1","token":"0x0000000000000000000000000000000000000000000000000000000000000000"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32007,"message":"Work package was superseded by newer work.","data":{"reason":"staleWork"}},"id":1}"#;
+	assert_eq!(eth_tester.io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_submitWork", "params": ["0x0000000000000001", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef", {"worker":"rig1","token":"0x0000000000000000000000000000000000000000000000000000000000000000"}], "id": 1}"#;
+	assert_eq!(eth_tester.io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.to_owned()));
+
+	assert_eq!(tracker.workers()["rig1"], WorkerSubmissions { accepted: 0, stale: 1, rejected: 1 });
+}
+
 #[test]
 fn rpc_get_work_should_timeout() {
 	let eth_tester = EthTester::default();
@@ -27,4 +27,7 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn set_public_address(&self, address: String) -> Result<(), String> {
+		address.parse::<::std::net::SocketAddr>().map(|_| ()).map_err(|_| format!("Invalid socket address: {}", address))
+	}
 }
@@ -27,6 +27,10 @@ use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
+use v1::helpers::abi_registry::AbiRegistry;
+use v1::helpers::call_cache::CallCache;
+use v1::helpers::head_lag::HeadLag;
+use v1::helpers::work_tracker::{WorkTracker, Submission};
 use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 
@@ -43,6 +47,10 @@ pub struct Dependencies {
 	pub accounts: Arc<AccountProvider>,
 	pub dapps_interface: Option<String>,
 	pub dapps_port: Option<u16>,
+	pub head_lag: Arc<HeadLag>,
+	pub work_tracker: Arc<WorkTracker>,
+	pub abi_registry: Option<Arc<AbiRegistry>>,
+	pub call_cache: Option<Arc<CallCache>>,
 }
 
 impl Dependencies {
@@ -68,6 +76,10 @@ impl Dependencies {
 			accounts: Arc::new(AccountProvider::transient_provider()),
 			dapps_interface: Some("127.0.0.1".into()),
 			dapps_port: Some(18080),
+			head_lag: Arc::new(HeadLag::new(Vec::new(), 60)),
+			work_tracker: Arc::new(WorkTracker::default()),
+			abi_registry: None,
+			call_cache: None,
 		}
 	}
 
@@ -84,6 +96,10 @@ impl Dependencies {
 			signer,
 			self.dapps_interface.clone(),
 			self.dapps_port,
+			self.head_lag.clone(),
+			self.work_tracker.clone(),
+			self.abi_registry.clone(),
+			self.call_cache.clone(),
 		)
 	}
 
@@ -469,6 +485,28 @@ fn rpc_parity_transactions_stats() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_propagation_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_propagationStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"hash":"0x0000000000000000000000000000000000000000000000000000000000000002","kind":"block","blockNumber":15,"firstPropagated":1500000010,"lastPropagated":1500000011,"sentTo":3,"announcedTo":7}],"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_receipts_backfill_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_receiptsBackfillStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"startBlock":"0x3e8","targetBlock":"0x384","nextBlock":"0x3b6","imported":"0xc","pending":"0x3"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_local_transactions() {
 	let deps = Dependencies::new();
@@ -497,3 +535,424 @@ fn rpc_parity_chain_status() {
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_node_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_nodeStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"bestBlock":"0x0","bestBlockTimestamp":"0x0","headLag":null,"clockOffset":null,"trailing":false},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	deps.head_lag.set_clock_offset(Some(0));
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""clockOffset":0"#), "{}", response);
+	assert!(response.contains(r#""trailing":true"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_light_peers_full_node() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_lightPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is only supported by light clients."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_worker_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.work_tracker.note_submission("rig1", Submission::Accepted);
+	deps.work_tracker.note_submission("rig1", Submission::Stale);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_workerStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"rig1":{"accepted":1,"stale":1,"rejected":0}},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_call_cache_stats() {
+	use std::time::Duration;
+	use v1::helpers::call_cache::{CallKind, CallResult};
+	use util::H256;
+
+	let mut deps = Dependencies::new();
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_callCacheStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(deps.default_client().handle_request_sync(request), Some(response.to_owned()));
+
+	let cache = Arc::new(CallCache::new(Duration::from_secs(60), 1024));
+	let head = H256::from(1);
+	cache.insert(&head, &head, CallKind::Call, &head, CallResult::Output(vec![1, 2, 3]));
+	cache.get(&head, &head, CallKind::Call, &head);
+	deps.call_cache = Some(cache);
+
+	let response = r#"{"jsonrpc":"2.0","result":{"hits":1,"misses":0,"oversized":0,"invalidations":0,"entries":1,"size":3},"id":1}"#;
+	assert_eq!(deps.default_client().handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_decoded_logs() {
+	use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
+	use util::{H256, Hashable};
+
+	let mut deps = Dependencies::new();
+	let mut registry = AbiRegistry::default();
+	registry.insert("0x0000000000000000000000000000000000000010", br#"[{"anonymous":false,"inputs":[{"indexed":false,"name":"flag","type":"bool"}],"name":"Flagged","type":"event"}]"#).unwrap();
+	deps.abi_registry = Some(Arc::new(registry));
+	let io = deps.default_client();
+
+	let log = |address: u64, index: usize| LocalizedLogEntry {
+		entry: LogEntry {
+			address: Address::from(address),
+			topics: vec!["Flagged(bool)".as_bytes().sha3()],
+			data: H256::from(1).to_vec(),
+		},
+		block_hash: H256::default(),
+		block_number: 0,
+		transaction_hash: H256::default(),
+		transaction_index: index,
+		log_index: index,
+		transaction_log_index: 0,
+	};
+	deps.client.set_logs(vec![log(0x10, 0), log(0x11, 1)]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_decodedLogs", "params":[{}, 10, null], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#""event":{"name":"Flagged","signature":"Flagged(bool)","params":[{"name":"flag","value":true}]}"#), "{}", response);
+	assert!(response.contains(r#""type":"mined"},"event":null}"#), "{}", response);
+	assert!(response.contains(r#""next":null"#), "{}", response);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_decodedLogs", "params":[{}, 1, null], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	assert!(!response.contains(r#""event":null"#), "{}", response);
+	assert!(response.contains(r#""next":{"blockNumber":"0x0","#), "{}", response);
+	assert!(response.contains(r#""transactionIndex":"0x1","logIndex":"0x1"}"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_chain_spec() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chainSpec", "params":[], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(r#""name":"foundation","engine":"NullEngine","networkId":"0x2""#), "{}", response);
+	assert!(response.contains(r#""minGasLimit":"0x1388""#), "{}", response);
+	assert!(response.contains(r#""0x0000000000000000000000000000000000000001":"0x0""#), "{}", response);
+	assert!(response.contains(r#""transitions":{"eip140":"0xffffffffffffffff","eip210":"0xffffffffffffffff","eip211":"0xffffffffffffffff","eip214":"0xffffffffffffffff","eip98":"0x0"},"scheduleOverrides":{}"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_list_storage_keys_paged() {
+	use ethcore::client::{BlockChainClient, BlockId};
+	use util::H256;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	for key in 1..4 {
+		deps.client.storage.write().insert((Address::default(), H256::from(key)), H256::from(1));
+	}
+	let block = BlockChainClient::block_hash(&*deps.client, BlockId::Latest).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listStorageKeysPaged", "params":["0x0000000000000000000000000000000000000000", 2, null], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"keys":["0x0000000000000000000000000000000000000000000000000000000000000001","0x0000000000000000000000000000000000000000000000000000000000000002"],"next":"0x"#.to_owned()
+		+ &format!("{:?}{:?}", block, H256::from(2)) + r#""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listStorageKeysPaged", "params":["0x0000000000000000000000000000000000000000", 2, "0x"#.to_owned()
+		+ &format!("{:?}{:?}", block, H256::from(2)) + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"keys":["0x0000000000000000000000000000000000000000000000000000000000000003"],"next":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_listStorageKeysPaged", "params":["0x0000000000000000000000000000000000000000", 2, "0x01"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_get_logs_paged() {
+	use ethcore::client::{BlockChainClient, BlockId};
+	use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
+	use util::H256;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let hash = BlockChainClient::block_hash(&*deps.client, BlockId::Number(0)).unwrap();
+	deps.client.set_logs((0..3).map(|i| LocalizedLogEntry {
+		block_number: 0,
+		block_hash: hash,
+		entry: LogEntry {
+			address: Address::default(),
+			topics: vec![],
+			data: vec![i as u8],
+		},
+		transaction_index: i,
+		transaction_log_index: 0,
+		transaction_hash: H256::default(),
+		log_index: i,
+	}).collect());
+	let cursor = format!(r#"{{"blockNumber":"0x0","blockHash":"0x{:?}","transactionIndex":"0x2","logIndex":"0x2"}}"#, hash);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getLogsPaged", "params":[{}, 2, null], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""data":"0x00""#), "{}", response);
+	assert!(response.contains(r#""data":"0x01""#), "{}", response);
+	assert!(!response.contains(r#""data":"0x02""#), "{}", response);
+	assert!(response.contains(&format!(r#""next":{}"#, cursor)), "{}", response);
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_getLogsPaged", "params":[{{}}, 2, {}], "id": 1}}"#, cursor);
+	let response = io.handle_request_sync(&request).unwrap();
+	assert!(!response.contains(r#""data":"0x01""#), "{}", response);
+	assert!(response.contains(r#""data":"0x02""#), "{}", response);
+	assert!(response.contains(r#""next":null"#), "{}", response);
+
+	// cursor of a block that is not canonical anymore
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getLogsPaged", "params":[{}, 2, {"blockNumber":"0x0","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001","transactionIndex":"0x2","logIndex":"0x2"}], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+
+	// block number beyond 64 bits
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_getLogsPaged", "params":[{{}}, 2, {{"blockNumber":"0x10000000000000000","blockHash":"0x{:?}","transactionIndex":"0x2","logIndex":"0x2"}}], "id": 1}}"#, hash);
+	let response = io.handle_request_sync(&request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getLogsPaged", "params":[{}, 0, null], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_get_block_header_by_number() {
+	use ethcore::client::{BlockChainClient, BlockId};
+	use rustc_serialize::hex::ToHex;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let header = BlockChainClient::block_header(&*deps.client, BlockId::Number(0)).unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockHeaderByNumber", "params":["0x0"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+
+	assert!(response.contains(&format!(r#""rlp":"0x{}""#, header.rlp().as_raw().to_hex())), "{}", response);
+	assert!(response.contains(&format!(r#""hash":"0x{:?}""#, header.hash())), "{}", response);
+	assert!(response.contains(r#""number":"0x0""#), "{}", response);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_getBlockHeaderByNumber", "params":["0x10"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_simulate_transaction() {
+	use ethcore::client::Executed;
+	use ethcore::log_entry::LogEntry;
+	use util::{H256, U256};
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.client.set_execution_result(Ok(Executed {
+		exception: None,
+		gas: U256::zero(),
+		gas_used: U256::from(0xff30),
+		refunded: U256::from(0x5),
+		cumulative_gas_used: U256::zero(),
+		logs: vec![LogEntry {
+			address: Address::from(1),
+			topics: vec![H256::from(2)],
+			data: vec![0x12],
+		}],
+		contracts_created: vec![],
+		output: vec![0x12, 0x34, 0xff],
+		trace: vec![],
+		vm_trace: None,
+		state_diff: None,
+	}));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_simulateTransaction", "params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","data":"0x01"}, "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"gasUsed":"0xff30","output":"0x1234ff","logs":[{"address":"0x0000000000000000000000000000000000000001","topics":["0x0000000000000000000000000000000000000000000000000000000000000002"],"data":"0x12","blockHash":null,"blockNumber":null,"transactionHash":null,"transactionIndex":null,"logIndex":null,"transactionLogIndex":null,"type":"pending"}],"stateDiff":null,"error":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_simulateTransaction", "params":["0x01"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_prove_call() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_proveCall", "params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","data":"0x01"}, "pending"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+
+	// the test client keeps no state to prove.
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_proveCall", "params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","data":"0x01"}, "latest"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""reason":"statePruned""#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_intrinsic_gas() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_intrinsicGas", "params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","data":"0x0100"}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x5250","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_intrinsicGas", "params":[{"to":"0xd46e8dd67c5d32be8058bb8eb970870f07244567","gas":"0x5208","data":"0x01"}], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""reason":"invalidGas""#), "{}", response);
+	assert!(response.contains(r#""minimal":"0x524c""#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_shared_db_handshake() {
+	use ethcore::client::BlockChainClient;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let genesis = deps.client.chain_info().genesis_hash.hex();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_sharedDbHandshake", "params":["0x"#.to_owned() + &genesis + r#""], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"sequence":"0x0","blockNumber":"0x0","blockHash":"0x"#.to_owned() + &genesis + r#""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.clone()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dbFlushMarker", "params":[], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_sharedDbHandshake", "params":["0x0000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains("Genesis hash doesn't match"), "{}", response);
+}
+
+#[test]
+fn rpc_parity_state_diff() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateDiff", "params":[["0x0000000000000000000000000000000000000001"], "earliest", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_stateDiff", "params":[["0x0000000000000000000000000000000000000001"], "earliest", "0x10"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains("Unknown block number"), "{}", response);
+}
+
+#[test]
+fn rpc_parity_compaction_status() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	{
+		let mut status = deps.client.compaction_status.write();
+		status.in_progress = true;
+		status.columns_compacted = 2;
+		status.columns_total = 7;
+	}
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_compactionStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"inProgress":true,"columnsCompacted":2,"columnsTotal":7,"lastFinished":null},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_db_stats() {
+	use ethcore::client::DbColumnStats;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	*deps.client.db_stats.write() = vec![DbColumnStats {
+		name: "state".into(),
+		size_on_disk: 4096,
+		estimated_keys: 12,
+		cache_usage: 512,
+		cache_capacity: 2048,
+		pending_compaction_bytes: 0,
+		cache_hits: 3,
+		cache_misses: 1,
+	}];
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_dbStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"name":"state","sizeOnDisk":4096,"estimatedKeys":12,"cacheUsage":512,"cacheCapacity":2048,"pendingCompactionBytes":0,"cacheHits":3,"cacheMisses":1,"cacheHitRate":0.75}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_pending_snapshot() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.miner.pending_snapshot.write().ordinal = 5;
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_pendingSnapshot", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"ordinal":"0x5","blockNumber":"0x1","parentHash":"0x0000000000000000000000000000000000000000000000000000000000000000","transactions":"0x0"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_fee_history() {
+	use ethcore::client::EachBlockWith;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	deps.client.add_blocks(3, EachBlockWith::Nothing);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_feeHistory", "params":[2], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""oldestBlock":"0x2""#), "{}", response);
+	assert!(response.contains(r#""number":"0x3""#), "{}", response);
+	assert!(response.contains(r#""transactionCount":"0x0","minGasPrice":null"#), "{}", response);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_feeHistory", "params":[0], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""code":-32602"#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_cht_ranges() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_chtRanges", "params":[], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(r#"{"jsonrpc":"2.0","result":[],"id":1}"#.to_owned()));
+
+	// CHT 1 is missing, e.g. its blocks were not downloaded yet after a warp sync.
+	deps.client.cht_roots.write().insert(0, 1.into());
+	deps.client.cht_roots.write().insert(2, 2.into());
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains(r#""number":"0x0","firstBlock":"0x1","lastBlock":"0x800""#), "{}", response);
+	assert!(!response.contains(r#""number":"0x1""#), "{}", response);
+	assert!(response.contains(r#""number":"0x2","firstBlock":"0x1001","lastBlock":"0x1800""#), "{}", response);
+}
+
+#[test]
+fn rpc_parity_export_pending_transactions() {
+	use rlp;
+	use rustc_serialize::hex::ToHex;
+	use ethcore::miner::{PoolBundle, PooledTransaction};
+	use ethcore::transaction::Transaction;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+	let signed = Transaction::default().fake_sign(5.into());
+	deps.miner.pending_transactions.lock().insert(signed.hash(), signed.clone());
+	deps.miner.local_transactions.lock().insert(signed.hash(), LocalTransactionStatus::Pending);
+
+	let bundle = PoolBundle {
+		transactions: vec![PooledTransaction { transaction: signed.into(), local: true, condition: None }],
+	};
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_exportPendingTransactions", "params":[], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":"0x{}","id":1}}"#, rlp::encode(&bundle).to_hex());
+
+	assert_eq!(io.handle_request_sync(request), Some(response));
+}
@@ -21,11 +21,15 @@ use ethstore::EthStore;
 use ethstore::dir::RootDiskDirectory;
 use devtools::RandomTempPath;
 
-use jsonrpc_core::IoHandler;
+use jsonrpc_core::{self as rpc, IoHandler};
+use serde_json;
 use v1::{ParityAccounts, ParityAccountsClient};
+use v1::helpers::signing_audit::SigningAudit;
+use v1::types::Origin;
 
 struct ParityAccountsTester {
 	accounts: Arc<AccountProvider>,
+	signing_audit: Arc<SigningAudit>,
 	io: IoHandler,
 }
 
@@ -40,12 +44,14 @@ fn accounts_provider_with_vaults_support(temp_path: &str) -> Arc<AccountProvider
 }
 
 fn setup_with_accounts_provider(accounts_provider: Arc<AccountProvider>) -> ParityAccountsTester {
-	let parity_accounts = ParityAccountsClient::new(&accounts_provider);
+	let signing_audit = Arc::new(SigningAudit::new(10));
+	let parity_accounts = ParityAccountsClient::new(&accounts_provider, signing_audit.clone());
 	let mut io = IoHandler::default();
 	io.extend_with(parity_accounts.to_delegate());
 
 	let tester = ParityAccountsTester {
 		accounts: accounts_provider,
+		signing_audit: signing_audit,
 		io: io,
 	};
 
@@ -499,3 +505,30 @@ fn should_export_account() {
 	println!("Response: {:?}", response);
 	assert_eq!(result, Some(response.into()));
 }
+
+#[test]
+fn rpc_parity_signing_audit() {
+	// given
+	let tester = setup();
+	let request: rpc::Request = serde_json::from_str(r#"[
+		{"jsonrpc":"2.0","method":"personal_unlockAccount","params":["0x000000000000000000000000000000000000000a","password",null],"id":1},
+		{"jsonrpc":"2.0","method":"eth_sign","params":["0x000000000000000000000000000000000000000b","0x01"],"id":2}
+	]"#).unwrap();
+	let response: rpc::Response = serde_json::from_str(r#"[
+		{"jsonrpc":"2.0","result":true,"id":1},
+		{"jsonrpc":"2.0","error":{"code":-32020,"message":"Your account is locked."},"id":2}
+	]"#).unwrap();
+	tester.signing_audit.record(SigningAudit::calls(&request), &Origin::Rpc("test".into()), Some(&response));
+	let timestamp = tester.signing_audit.entries(None)[0].timestamp;
+
+	// when
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_signingAudit", "params": [1], "id": 1}"#;
+	let response = format!(r#"{{"jsonrpc":"2.0","result":[{{"timestamp":{},"method":"eth_sign","origin":{{"rpc":"test"}},"account":"0x000000000000000000000000000000000000000b","success":false,"error":"Your account is locked."}}],"id":1}}"#, timestamp);
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(request), Some(response));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_signingAudit", "params": [], "id": 1}"#;
+	let result = tester.io.handle_request_sync(request).unwrap();
+	assert_eq!(result.matches(r#""method":"#).count(), 2);
+}
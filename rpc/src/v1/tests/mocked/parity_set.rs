@@ -17,7 +17,7 @@
 use std::sync::Arc;
 use std::str::FromStr;
 use rustc_serialize::hex::FromHex;
-use util::{U256, Address};
+use util::{U256, H256, Address};
 
 use ethcore::miner::MinerService;
 use ethcore::client::TestBlockChainClient;
@@ -139,6 +139,23 @@ fn rpc_parity_set_extra_data() {
 	assert_eq!(miner.extra_data(), "cd1722f3947def4cf144679da39c4c32bdc35681".from_hex().unwrap());
 }
 
+#[test]
+fn rpc_parity_set_extra_data_rejects_too_long() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setExtraData", "params":["0x000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: extraData","data":"ExtraDataOutOfBounds(OutOfBounds { min: None, max: Some(32), found: 33 })"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.extra_data(), vec![1, 2, 3, 4]);
+}
+
 #[test]
 fn rpc_parity_set_author() {
 	let miner = miner_service();
@@ -172,6 +189,42 @@ fn rpc_parity_set_engine_signer() {
 	assert_eq!(*miner.password.read(), "password".to_string());
 }
 
+#[test]
+fn rpc_parity_propose_signer() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+	let signer = Address::from_str("cd1722f3947def4cf144679da39c4c32bdc35681").unwrap();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_proposeSigner", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681", false], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert_eq!(miner.signer_proposals.read().get(&signer), Some(&false));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_discardSignerProposal", "params":["0xcd1722f3947def4cf144679da39c4c32bdc35681"], "id": 1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(miner.signer_proposals.read().is_empty());
+}
+
+#[test]
+fn rpc_parity_clear_bad_blocks() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+	client.bad_blocks.write().insert(H256::from(1));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_clearBadBlocks", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(client.bad_blocks.read().is_empty());
+}
+
 
 #[test]
 fn rpc_parity_set_transactions_limit() {
@@ -232,3 +285,73 @@ fn rpc_parity_remove_transaction() {
 	miner.pending_transactions.lock().insert(hash, signed);
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
+
+#[test]
+fn rpc_parity_set_public_address() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setPublicAddress", "params":["1.2.3.4:30400"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_setPublicAddress", "params":["1.2.3.4"], "id": 1}"#;
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains("Invalid socket address"), "{}", response);
+}
+
+#[test]
+fn rpc_parity_compact_database() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_compactDatabase", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+	assert!(client.compaction_status.read().in_progress);
+
+	let response = io.handle_request_sync(request).unwrap();
+	assert!(response.contains("already in progress"), "{}", response);
+}
+
+#[test]
+fn rpc_parity_import_pending_transactions() {
+	use rlp;
+	use rustc_serialize::hex::ToHex;
+	use ethcore::miner::{PoolBundle, PooledTransaction};
+	use ethcore::transaction::{Transaction, Condition};
+	use ethkey::{Generator, Random};
+
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let local = Transaction { nonce: 1.into(), ..Default::default() }.sign(Random.generate().unwrap().secret(), None);
+	let external = Transaction { nonce: 2.into(), ..Default::default() }.sign(Random.generate().unwrap().secret(), None);
+	miner.last_nonces.write().insert(external.sender(), 1.into());
+	let bundle = PoolBundle {
+		transactions: vec![
+			PooledTransaction { transaction: local.clone().into(), local: true, condition: Some(Condition::Number(10)) },
+			PooledTransaction { transaction: external.clone().into(), local: false, condition: None },
+		],
+	};
+
+	let request = format!(r#"{{"jsonrpc": "2.0", "method": "parity_importPendingTransactions", "params":["0x{}"], "id": 1}}"#, rlp::encode(&bundle).to_hex());
+	let response = r#"{"jsonrpc":"2.0","result":2,"id":1}"#;
+	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
+	assert_eq!(*miner.imported_transactions.lock(), vec![local, external]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_importPendingTransactions", "params":["0x1234"], "id": 1}"#;
+	assert!(io.handle_request_sync(request).unwrap().contains("error"));
+}
@@ -0,0 +1,117 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::time::Duration;
+use ethcore::miner::TransactionEvent;
+use ethsync::{SyncStatus, SyncState};
+use jsonrpc_core::MetaIoHandler;
+use util::Mutex;
+use v1::{PubSub, PubSubClient, Metadata, Sink};
+use v1::helpers::sync_events::SyncEvents;
+use v1::helpers::transaction_events::TransactionEvents;
+
+fn status(state: SyncState) -> SyncStatus {
+	SyncStatus {
+		state: state,
+		protocol_version: 63,
+		network_id: 1,
+		start_block_number: 0,
+		last_imported_block_number: None,
+		highest_block_number: Some(100),
+		blocks_total: 0,
+		blocks_received: 0,
+		num_peers: 1,
+		num_active_peers: 1,
+		mem_used: 0,
+		num_snapshot_chunks: 0,
+		snapshot_chunks_done: 0,
+		last_imported_old_block_number: None,
+		num_head_stall_rotations: 0,
+	}
+}
+
+#[test]
+fn rpc_parity_subscribe_sync_status() {
+	let events = Arc::new(SyncEvents::new(Duration::from_secs(60)));
+	let mut io = MetaIoHandler::default();
+	io.extend_with(PubSubClient::new(events.clone(), Default::default()).to_delegate());
+
+	let received = Arc::new(Mutex::new(Vec::new()));
+	let r = received.clone();
+	let mut meta = Metadata::default();
+	meta.sink = Some(Sink::new(move |message| { r.lock().push(message); true }));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["syncStatus"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta.clone()), Some(response.to_owned()));
+
+	events.update(&status(SyncState::Blocks), 50);
+	events.update(&status(SyncState::Idle), 100);
+	let received = received.lock();
+	assert_eq!(received.len(), 3);
+	assert!(received[0].contains(r#""event":"started""#), "{}", received[0]);
+	assert!(received[1].contains(r#""event":"progress""#), "{}", received[1]);
+	assert!(received[2].contains(r#""event":"completed""#), "{}", received[2]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unsubscribe", "params": ["0x1"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_subscribe_transaction_queue() {
+	let sync_events = Arc::new(SyncEvents::new(Duration::from_secs(60)));
+	let events = Arc::new(TransactionEvents::default());
+	let mut io = MetaIoHandler::default();
+	io.extend_with(PubSubClient::new(sync_events.clone(), events.clone()).to_delegate());
+
+	let received = Arc::new(Mutex::new(Vec::new()));
+	let r = received.clone();
+	let mut meta = Metadata::default();
+	meta.sink = Some(Sink::new(move |message| { r.lock().push(message); true }));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["syncStatus"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x1","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta.clone()), Some(response.to_owned()));
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["transactionQueue"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x2","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta.clone()), Some(response.to_owned()));
+
+	events.notify(&TransactionEvent::Promoted(5.into()));
+	sync_events.update(&status(SyncState::Idle), 0);
+	let received = received.lock();
+	assert_eq!(received.len(), 1);
+	assert!(received[0].contains(r#""subscription":"0x2""#), "{}", received[0]);
+	assert!(received[0].contains(r#""event":"promoted""#), "{}", received[0]);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unsubscribe", "params": ["0x2"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta.clone()), Some(response.to_owned()));
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, meta), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_subscribe_requires_persistent_connection() {
+	let events = Arc::new(SyncEvents::new(Duration::from_secs(60)));
+	let mut io = MetaIoHandler::default();
+	io.extend_with(PubSubClient::new(events, Default::default()).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_subscribe", "params": ["syncStatus"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"Subscriptions are only supported over persistent connections (WebSockets)."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, Metadata::default()), Some(response.to_owned()));
+}
@@ -21,7 +21,7 @@ use jsonrpc_macros::Trailing;
 use futures::BoxFuture;
 
 use v1::types::{RichBlock, BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index};
-use v1::types::{Log, Receipt, SyncStatus, Transaction, Work};
+use v1::types::{Log, Receipt, ReceiptOptions, SyncStatus, Transaction, Work, WorkerCredentials};
 use v1::types::{H64, H160, H256, U256};
 
 build_rpc_trait! {
@@ -129,9 +129,9 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getTransactionByBlockNumberAndIndex")]
 		fn transaction_by_block_number_and_index(&self, BlockNumber, Index) -> Result<Option<Transaction>, Error>;
 
-		/// Returns transaction receipt.
+		/// Returns transaction receipt, optionally with a proof of its inclusion in the block.
 		#[rpc(name = "eth_getTransactionReceipt")]
-		fn transaction_receipt(&self, H256) -> Result<Option<Receipt>, Error>;
+		fn transaction_receipt(&self, H256, Trailing<ReceiptOptions>) -> Result<Option<Receipt>, Error>;
 
 		/// Returns an uncles at given block and index.
 		#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
@@ -169,9 +169,10 @@ build_rpc_trait! {
 		#[rpc(name = "eth_getWork")]
 		fn work(&self, Trailing<u64>) -> Result<Work, Error>;
 
-		/// Used for submitting a proof-of-work solution.
+		/// Used for submitting a proof-of-work solution. Credentials of the worker
+		/// (fourth parameter) are required when worker authentication is enabled.
 		#[rpc(name = "eth_submitWork")]
-		fn submit_work(&self, H64, H256, H256) -> Result<bool, Error>;
+		fn submit_work(&self, H64, H256, H256, Trailing<WorkerCredentials>) -> Result<bool, Error>;
 
 		/// Used for submitting mining hashrate.
 		#[rpc(name = "eth_submitHashrate")]
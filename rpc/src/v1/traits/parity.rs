@@ -27,8 +27,11 @@ use v1::types::{
 	Peers, Transaction, RpcSettings, Histogram,
 	TransactionStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo,
+	OperationsInfo, DappId, ChainStatus, NodeStatus, LightPeerInfo, ChainSpec,
+	AccountInfo, HwAccountInfo, StorageKeysPage, EncodedHeader,
+	SimulationRequest, SimulationResult, CallRequest, DbFlushMarker, StateDiff, CompactionStatus,
+	DbColumnStats, PendingSnapshot, Filter, LogsCursor, LogsPage, FeeHistory, ChtRange, PropagationStats, CallProof,
+	ReceiptsBackfillStatus, LightCacheUsage, WorkerStats, DecodedLogsPage, CallCacheStats,
 };
 
 build_rpc_trait! {
@@ -130,6 +133,17 @@ build_rpc_trait! {
 		#[rpc(name = "parity_listStorageKeys")]
 		fn list_storage_keys(&self, H160, u64, Option<H256>, Trailing<BlockNumber>) -> Result<Option<Vec<H256>>, Error>;
 
+		/// Returns a page of storage keys of the given address if Fat DB is enabled (`--fat-db`), or null if not.
+		/// Further pages are requested with the `next` token of the previous page (third parameter),
+		/// which also pins the listing to the block of the first page.
+		#[rpc(name = "parity_listStorageKeysPaged")]
+		fn list_storage_keys_paged(&self, H160, u64, Option<Bytes>, Trailing<BlockNumber>) -> Result<Option<StorageKeysPage>, Error>;
+
+		/// Returns the balance, nonce, code and storage changes of the given accounts between
+		/// the states of two blocks. Storage is only compared if Fat DB is enabled (`--fat-db`).
+		#[rpc(name = "parity_stateDiff")]
+		fn state_diff(&self, Vec<H160>, BlockNumber, BlockNumber) -> Result<StateDiff, Error>;
+
 		/// Encrypt some data with a public key under ECIES.
 		/// First parameter is the 512-byte destination public key, second is the message.
 		#[rpc(name = "parity_encryptMessage")]
@@ -147,6 +161,16 @@ build_rpc_trait! {
 		#[rpc(name = "parity_pendingTransactionsStats")]
 		fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>, Error>;
 
+		/// Returns when and to how many peers recent local transactions and locally sealed blocks
+		/// were sent or announced, latest first.
+		#[rpc(name = "parity_propagationStats")]
+		fn propagation_stats(&self) -> Result<Vec<PropagationStats>, Error>;
+
+		/// Returns progress of the background download of receipts missing for blocks in the chain,
+		/// or `null` if it's disabled (see `--ancient-receipts-depth`).
+		#[rpc(name = "parity_receiptsBackfillStatus")]
+		fn receipts_backfill_status(&self) -> Result<Option<ReceiptsBackfillStatus>, Error>;
+
 		/// Returns a list of current and past local transactions with status details.
 		#[rpc(name = "parity_localTransactions")]
 		fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>, Error>;
@@ -194,5 +218,102 @@ build_rpc_trait! {
 		/// Get the current chain status.
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus, Error>;
+
+		/// Get the lag of the best block behind the NTP-verified current time.
+		#[rpc(name = "parity_nodeStatus")]
+		fn node_status(&self) -> Result<NodeStatus, Error>;
+
+		/// Get the light protocol servers a light client is connected to.
+		#[rpc(name = "parity_lightPeers")]
+		fn light_peers(&self) -> Result<Vec<LightPeerInfo>, Error>;
+
+		/// Returns the number of accepted, stale and rejected solutions submitted by each mining worker.
+		#[rpc(name = "parity_workerStats")]
+		fn worker_stats(&self) -> Result<BTreeMap<String, WorkerStats>, Error>;
+
+		/// Get the hits, misses and size of the `eth_call` and `eth_estimateGas` result cache,
+		/// or null if the cache is disabled.
+		#[rpc(name = "parity_callCacheStats")]
+		fn call_cache_stats(&self) -> Result<Option<CallCacheStats>, Error>;
+
+		/// Get the memory usage, quota and eviction count of each of a light client's data caches.
+		#[rpc(name = "parity_lightCacheUsage")]
+		fn light_cache_usage(&self) -> Result<Vec<LightCacheUsage>, Error>;
+
+		/// Get the normalized parameters of the active chain spec.
+		#[rpc(name = "parity_chainSpec")]
+		fn chain_spec(&self) -> Result<ChainSpec, Error>;
+
+		/// Get the header of a block by number: its exact RLP encoding along with the decoded fields.
+		#[rpc(name = "parity_getBlockHeaderByNumber")]
+		fn block_header(&self, Trailing<BlockNumber>) -> Result<Option<EncodedHeader>, Error>;
+
+		/// Executes a transaction (raw signed transaction or call request object) on top of given block
+		/// without importing it, returning gas used, output, logs and state diff.
+		#[rpc(name = "parity_simulateTransaction")]
+		fn simulate_transaction(&self, SimulationRequest, Trailing<BlockNumber>) -> Result<SimulationResult, Error>;
+
+		/// Executes a call on top of given block and returns its output along with the state items
+		/// needed to re-execute it, in the format of a PIP execution proof, so that light clients
+		/// can verify the result. Missing gas defaults to 50M, gas price and value to zero.
+		#[rpc(name = "parity_proveCall")]
+		fn prove_call(&self, CallRequest, Trailing<BlockNumber>) -> Result<CallProof, Error>;
+
+		/// Returns the intrinsic gas of a transaction. Fails with the reason if the supplied gas
+		/// doesn't cover it or the contract creation code exceeds the node's limit.
+		#[rpc(name = "parity_intrinsicGas")]
+		fn intrinsic_gas(&self, CallRequest) -> Result<U256, Error>;
+
+		/// Handshake of a read-only instance sharing this node's database. Checks that both use
+		/// the same chain (by genesis hash) and returns the latest database flush marker.
+		#[rpc(name = "parity_sharedDbHandshake")]
+		fn shared_db_handshake(&self, H256) -> Result<DbFlushMarker, Error>;
+
+		/// Returns the marker of the latest database flush. Read-only instances sharing
+		/// the database reopen it when the marker changes.
+		#[rpc(name = "parity_dbFlushMarker")]
+		fn db_flush_marker(&self) -> Result<DbFlushMarker, Error>;
+
+		/// Returns the progress of the latest database compaction.
+		#[rpc(name = "parity_compactionStatus")]
+		fn compaction_status(&self) -> Result<CompactionStatus, Error>;
+
+		/// Returns the estimated size, key count, cache usage and pending compaction
+		/// of every database column.
+		#[rpc(name = "parity_dbStats")]
+		fn db_stats(&self) -> Result<Vec<DbColumnStats>, Error>;
+
+		/// Returns the pending block snapshot `eth_call` and `eth_estimateGas` at `pending`
+		/// are executed against. Its ordinal changes whenever the snapshot is rebuilt.
+		#[rpc(name = "parity_pendingSnapshot")]
+		fn pending_snapshot(&self) -> Result<PendingSnapshot, Error>;
+
+		/// Returns a page of at most the given number of logs matching the filter, ignoring its `limit`.
+		/// Further pages are requested with the `next` cursor of the previous page (third parameter).
+		/// A page may hold fewer logs while `next` is set when many blocks had to be searched.
+		/// Fails if the block of the cursor is no longer in the canonical chain.
+		#[rpc(name = "parity_getLogsPaged")]
+		fn logs_paged(&self, Filter, u64, Option<LogsCursor>) -> Result<LogsPage, Error>;
+
+		/// Returns a page of logs matching the filter, each with its event decoded if the ABI of the
+		/// emitting contract is known to the node (see `--jsonrpc-abi-dir`) or published in the registry.
+		/// Paged like `parity_getLogsPaged`. ABIs published in the registry are fetched in the background,
+		/// logs of a contract seen for the first time may be decoded only in later requests.
+		#[rpc(name = "parity_decodedLogs")]
+		fn decoded_logs(&self, Filter, u64, Option<LogsCursor>) -> Result<DecodedLogsPage, Error>;
+
+		/// Returns gas used ratio and min, median and max gas price of included transactions
+		/// of the given number of blocks ending with the given block (`latest` by default).
+		#[rpc(name = "parity_feeHistory")]
+		fn fee_history(&self, u64, Trailing<BlockNumber>) -> Result<FeeHistory, Error>;
+
+		/// Returns the canonical hash tries whose roots are known, with the blocks they cover.
+		#[rpc(name = "parity_chtRanges")]
+		fn cht_ranges(&self) -> Result<Vec<ChtRange>, Error>;
+
+		/// Returns the RLP bundle of the transaction queue, highest priority first, marking local transactions.
+		/// It can be imported into another node with `parity_importPendingTransactions`.
+		#[rpc(name = "parity_exportPendingTransactions")]
+		fn export_pending_transactions(&self) -> Result<Bytes, Error>;
 	}
 }
@@ -18,8 +18,9 @@
 use std::collections::BTreeMap;
 
 use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
 use ethstore::KeyFile;
-use v1::types::{H160, H256, DappId, DeriveHash, DeriveHierarchical};
+use v1::types::{H160, H256, DappId, DeriveHash, DeriveHierarchical, SigningAuditEntry};
 
 build_rpc_trait! {
 	/// Personal Parity rpc interface.
@@ -180,5 +181,11 @@ build_rpc_trait! {
 		/// Exports an account with given address if provided password matches.
 		#[rpc(name = "parity_exportAccount")]
 		fn export_account(&self, H160, String) -> Result<KeyFile, Error>;
+
+		/// Returns the most recent account-affecting calls (unlocking, signing, sending) with their
+		/// origin and outcome, oldest first. Optional argument limits the number of entries
+		/// (all entries kept in memory are returned if it's missing or `0`).
+		#[rpc(name = "parity_signingAudit")]
+		fn signing_audit(&self, Trailing<usize>) -> Result<Vec<SigningAuditEntry>, Error>;
 	}
 }
@@ -48,6 +48,15 @@ build_rpc_trait! {
 		#[rpc(name = "parity_setEngineSigner")]
 		fn set_engine_signer(&self, H160, String) -> Result<bool, Error>;
 
+		/// Votes on authorizing (`true`) or deauthorizing (`false`) a signer in sealed blocks
+		/// of chains which vote on signers in block headers (Clique).
+		#[rpc(name = "parity_proposeSigner")]
+		fn propose_signer(&self, H160, bool) -> Result<bool, Error>;
+
+		/// Stops voting on a signer proposed with `parity_proposeSigner`.
+		#[rpc(name = "parity_discardSignerProposal")]
+		fn discard_signer_proposal(&self, H160) -> Result<bool, Error>;
+
 		/// Sets the limits for transaction queue.
 		#[rpc(name = "parity_setTransactionsLimit")]
 		fn set_transactions_limit(&self, usize) -> Result<bool, Error>;
@@ -64,6 +73,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_removeReservedPeer")]
 		fn remove_reserved_peer(&self, String) -> Result<bool, Error>;
 
+		/// Forgets the blocks remembered as failing verification, so that their fork may be
+		/// downloaded and verified again.
+		#[rpc(name = "parity_clearBadBlocks")]
+		fn clear_bad_blocks(&self) -> Result<bool, Error>;
+
 		/// Drop all non-reserved peers.
 		#[rpc(name = "parity_dropNonReservedPeers")]
 		fn drop_non_reserved_peers(&self) -> Result<bool, Error>;
@@ -72,6 +86,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_acceptNonReservedPeers")]
 		fn accept_non_reserved_peers(&self) -> Result<bool, Error>;
 
+		/// Change the address (`ip:port`) advertised to other nodes, e.g. after the NAT mapping changed.
+		/// Takes effect without restarting the network; the configured discovery port is kept.
+		#[rpc(name = "parity_setPublicAddress")]
+		fn set_public_address(&self, String) -> Result<bool, Error>;
+
 		/// Start the network.
 		///
 		/// @deprecated - Use `set_mode("active")` instead.
@@ -89,6 +108,7 @@ build_rpc_trait! {
 		fn set_mode(&self, String) -> Result<bool, Error>;
 
 		/// Set the network spec. Argument must be one of: "foundation", "ropsten", "morden", "kovan", "olympic", "classic", "dev", "expanse" or a filename.
+		/// The node restarts on the chain's own database and remembers it for `--chain last`.
 		#[rpc(name = "parity_setChain")]
 		fn set_spec_name(&self, String) -> Result<bool, Error>;
 
@@ -112,5 +132,26 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>, Error>;
+
+		/// Starts compacting the database in the background, e.g. during a maintenance window.
+		/// Progress is reported by `parity_compactionStatus`.
+		#[rpc(name = "parity_compactDatabase")]
+		fn compact_database(&self) -> Result<bool, Error>;
+
+		/// Prefer the light protocol server with given peer id when making requests.
+		/// Returns `false` if there is no such peer.
+		#[rpc(name = "parity_pinLightPeer")]
+		fn pin_light_peer(&self, usize) -> Result<bool, Error>;
+
+		/// Stop preferring the light protocol server with given peer id.
+		/// Returns `false` if it wasn't pinned.
+		#[rpc(name = "parity_unpinLightPeer")]
+		fn unpin_light_peer(&self, usize) -> Result<bool, Error>;
+
+		/// Imports a bundle exported by `parity_exportPendingTransactions` into the transaction queue,
+		/// in the order of the bundle. Local transactions stay local and keep their conditions.
+		/// Returns the number of transactions imported.
+		#[rpc(name = "parity_importPendingTransactions")]
+		fn import_pending_transactions(&self, Bytes) -> Result<usize, Error>;
 	}
 }
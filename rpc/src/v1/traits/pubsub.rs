@@ -0,0 +1,37 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Parity pub-sub rpc interface.
+use jsonrpc_core::Error;
+use futures::BoxFuture;
+
+use v1::types::{Index, U256};
+
+build_rpc_trait! {
+	/// Notifications pushed over persistent connections.
+	pub trait PubSub {
+		type Metadata;
+
+		/// Subscribe to a topic (`syncStatus` or `transactionQueue`), returns subscription id.
+		/// Notifications are pushed as `parity_subscription` calls.
+		#[rpc(meta, name = "parity_subscribe")]
+		fn subscribe(&self, Self::Metadata, String) -> BoxFuture<U256, Error>;
+
+		/// Cancel a subscription.
+		#[rpc(name = "parity_unsubscribe")]
+		fn unsubscribe(&self, Index) -> Result<bool, Error>;
+	}
+}
@@ -18,6 +18,7 @@ use std::ops::Deref;
 use std::collections::BTreeMap;
 use serde::{Serialize, Serializer};
 use serde::ser::Error;
+use ethcore::encoded;
 use v1::types::{Bytes, Transaction, H160, H256, H2048, U256};
 
 /// Block Transactions
@@ -96,6 +97,78 @@ pub struct Block {
 	pub size: Option<U256>,
 }
 
+/// Block header representation together with its canonical RLP encoding.
+#[derive(Debug, Serialize)]
+pub struct EncodedHeader {
+	/// RLP-encoded header, exactly as hashed.
+	pub rlp: Bytes,
+	/// Hash of the header
+	pub hash: H256,
+	/// Hash of the parent
+	#[serde(rename="parentHash")]
+	pub parent_hash: H256,
+	/// Hash of the uncles
+	#[serde(rename="sha3Uncles")]
+	pub uncles_hash: H256,
+	/// Authors address
+	pub author: H160,
+	/// State root hash
+	#[serde(rename="stateRoot")]
+	pub state_root: H256,
+	/// Transactions root hash
+	#[serde(rename="transactionsRoot")]
+	pub transactions_root: H256,
+	/// Transactions receipts root hash
+	#[serde(rename="receiptsRoot")]
+	pub receipts_root: H256,
+	/// Block number
+	pub number: U256,
+	/// Gas Used
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Gas Limit
+	#[serde(rename="gasLimit")]
+	pub gas_limit: U256,
+	/// Extra data
+	#[serde(rename="extraData")]
+	pub extra_data: Bytes,
+	/// Logs bloom
+	#[serde(rename="logsBloom")]
+	pub logs_bloom: H2048,
+	/// Timestamp
+	pub timestamp: U256,
+	/// Difficulty
+	pub difficulty: U256,
+	/// Seal fields, each as its raw RLP item
+	#[serde(rename="sealFields")]
+	pub seal_fields: Vec<Bytes>,
+}
+
+impl From<encoded::Header> for EncodedHeader {
+	fn from(header: encoded::Header) -> Self {
+		let hash = header.hash();
+		let view = header.view();
+		EncodedHeader {
+			hash: hash.into(),
+			parent_hash: view.parent_hash().into(),
+			uncles_hash: view.uncles_hash().into(),
+			author: view.author().into(),
+			state_root: view.state_root().into(),
+			transactions_root: view.transactions_root().into(),
+			receipts_root: view.receipts_root().into(),
+			number: view.number().into(),
+			gas_used: view.gas_used().into(),
+			gas_limit: view.gas_limit().into(),
+			extra_data: view.extra_data().into(),
+			logs_bloom: view.log_bloom().into(),
+			timestamp: view.timestamp().into(),
+			difficulty: view.difficulty().into(),
+			seal_fields: view.seal().into_iter().map(Into::into).collect(),
+			rlp: header.rlp().as_raw().to_vec().into(),
+		}
+	}
+}
+
 /// Block representation with additional info
 #[derive(Debug)]
 pub struct RichBlock {
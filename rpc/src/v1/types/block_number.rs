@@ -18,6 +18,7 @@ use std::fmt;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::{Error, Visitor};
 use ethcore::client::BlockId;
+use util::H256;
 
 /// Represents rpc api block number param.
 #[derive(Debug, PartialEq, Clone, Hash, Eq)]
@@ -30,6 +31,8 @@ pub enum BlockNumber {
 	Earliest,
 	/// Pending block (being mined)
 	Pending,
+	/// Block with given hash
+	Hash(H256),
 }
 
 impl Default for BlockNumber {
@@ -61,6 +64,7 @@ impl Serialize for BlockNumber {
 			BlockNumber::Latest => serializer.serialize_str("latest"),
 			BlockNumber::Earliest => serializer.serialize_str("earliest"),
 			BlockNumber::Pending => serializer.serialize_str("pending"),
+			BlockNumber::Hash(ref hash) => serializer.serialize_str(&format!("0x{:?}", hash)),
 		}
 	}
 }
@@ -71,7 +75,7 @@ impl Visitor for BlockNumberVisitor {
 	type Value = BlockNumber;
 
 	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-		write!(formatter, "a block number or 'latest', 'earliest' or 'pending'")
+		write!(formatter, "a block number, a block hash or 'latest', 'earliest' or 'pending'")
 	}
 
 	fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> where E: Error {
@@ -79,6 +83,7 @@ impl Visitor for BlockNumberVisitor {
 			"latest" => Ok(BlockNumber::Latest),
 			"earliest" => Ok(BlockNumber::Earliest),
 			"pending" => Ok(BlockNumber::Pending),
+			_ if value.len() == 66 && value.starts_with("0x") => value[2..].parse().map(BlockNumber::Hash).map_err(|_| Error::custom("invalid block hash")),
 			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16).map(BlockNumber::Num).map_err(|_| Error::custom("invalid block number")),
 			_ => value.parse::<u64>().map(BlockNumber::Num).map_err(|_| Error::custom("invalid block number"))
 		}
@@ -96,6 +101,7 @@ impl Into<BlockId> for BlockNumber {
 			BlockNumber::Earliest => BlockId::Earliest,
 			BlockNumber::Latest => BlockId::Latest,
 			BlockNumber::Pending => BlockId::Pending,
+			BlockNumber::Hash(hash) => BlockId::Hash(hash),
 		}
 	}
 }
@@ -113,12 +119,21 @@ mod tests {
 		assert_eq!(deserialized, vec![BlockNumber::Num(10), BlockNumber::Num(10), BlockNumber::Latest, BlockNumber::Earliest, BlockNumber::Pending])
 	}
 
+	#[test]
+	fn block_hash_serialization() {
+		let s = r#""0x0000000000000000000000000000000000000000000000000000000000000001""#;
+		let deserialized: BlockNumber = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized, BlockNumber::Hash(H256::from(1)));
+		assert_eq!(serde_json::to_string(&deserialized).unwrap(), s);
+	}
+
 	#[test]
 	fn block_number_into() {
 		assert_eq!(BlockId::Number(100), BlockNumber::Num(100).into());
 		assert_eq!(BlockId::Earliest, BlockNumber::Earliest.into());
 		assert_eq!(BlockId::Latest, BlockNumber::Latest.into());
 		assert_eq!(BlockId::Pending, BlockNumber::Pending.into());
+		assert_eq!(BlockId::Hash(H256::from(1)), BlockNumber::Hash(H256::from(1)).into());
 	}
 }
 
@@ -0,0 +1,49 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `eth_call` and `eth_estimateGas` result cache statistics.
+
+use v1::helpers::call_cache::CallCacheStats as Stats;
+
+/// Usage statistics of the `eth_call` and `eth_estimateGas` result cache.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CallCacheStats {
+	/// Requests served from the cache.
+	pub hits: u64,
+	/// Requests not found in the cache.
+	pub misses: u64,
+	/// Results not cached because they exceeded the entry size limit.
+	pub oversized: u64,
+	/// Number of times the cache was cleared because of a new best block.
+	pub invalidations: u64,
+	/// Number of cached results.
+	pub entries: u64,
+	/// Bytes used by cached results.
+	pub size: u64,
+}
+
+impl From<Stats> for CallCacheStats {
+	fn from(stats: Stats) -> Self {
+		CallCacheStats {
+			hits: stats.hits as u64,
+			misses: stats.misses as u64,
+			oversized: stats.oversized as u64,
+			invalidations: stats.invalidations as u64,
+			entries: stats.entries as u64,
+			size: stats.size as u64,
+		}
+	}
+}
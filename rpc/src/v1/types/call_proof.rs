@@ -0,0 +1,52 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Result of a call together with the proof of its execution.
+
+use v1::types::{Bytes, H256, U256};
+
+/// Output of a call and the state it was executed against.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CallProof {
+	/// Hash of the block whose state the call was executed against.
+	#[serde(rename="blockHash")]
+	pub block_hash: H256,
+	/// Nonce of the sender the call was executed with.
+	pub nonce: U256,
+	/// Output of the call.
+	pub output: Bytes,
+	/// RLP list of all state items needed to re-execute the call,
+	/// encoded like the response to a PIP execution request.
+	pub proof: Bytes,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::CallProof;
+
+	#[test]
+	fn call_proof_serialization() {
+		let proof = CallProof {
+			block_hash: 5.into(),
+			nonce: 1.into(),
+			output: vec![0x12].into(),
+			proof: vec![0xc0].into(),
+		};
+
+		assert_eq!(serde_json::to_string(&proof).unwrap(), r#"{"blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","nonce":"0x1","output":"0x12","proof":"0xc0"}"#);
+	}
+}
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use ethcore::client::EnvOverrides as EthEnvOverrides;
+use util::U256 as EthU256;
 use v1::helpers::CallRequest as Request;
 use v1::types::{Bytes, H160, U256};
 
@@ -36,6 +38,44 @@ pub struct CallRequest {
 	pub data: Option<Bytes>,
 	/// Nonce
 	pub nonce: Option<U256>,
+	/// Overrides of the block environment the call is executed in
+	pub env: Option<EnvOverrides>,
+}
+
+/// Overrides of the block environment of a call
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvOverrides {
+	/// Block author
+	pub coinbase: Option<H160>,
+	/// Block timestamp
+	pub timestamp: Option<U256>,
+	/// Block difficulty
+	pub difficulty: Option<U256>,
+	/// Block gas limit
+	#[serde(rename="gasLimit")]
+	pub gas_limit: Option<U256>,
+}
+
+impl Into<EthEnvOverrides> for EnvOverrides {
+	fn into(self) -> EthEnvOverrides {
+		EthEnvOverrides {
+			author: self.coinbase.map(Into::into),
+			timestamp: self.timestamp.map(|t| {
+				let t: EthU256 = t.into();
+				t.low_u64()
+			}),
+			difficulty: self.difficulty.map(Into::into),
+			gas_limit: self.gas_limit.map(Into::into),
+		}
+	}
+}
+
+impl CallRequest {
+	/// Environment overrides of the call, empty if none were given.
+	pub fn env_overrides(&self) -> EthEnvOverrides {
+		self.env.clone().map(Into::into).unwrap_or_default()
+	}
 }
 
 impl Into<Request> for CallRequest {
@@ -58,7 +98,7 @@ mod tests {
 	use rustc_serialize::hex::FromHex;
 	use serde_json;
 	use v1::types::{U256, H160};
-	use super::CallRequest;
+	use super::{CallRequest, EnvOverrides};
 
 	#[test]
 	fn call_request_deserialize() {
@@ -81,6 +121,7 @@ mod tests {
 			value: Some(U256::from(3)),
 			data: Some(vec![0x12, 0x34, 0x56].into()),
 			nonce: Some(U256::from(4)),
+			env: None,
 		});
 	}
 
@@ -103,7 +144,8 @@ mod tests {
 			gas: Some(U256::from_str("76c0").unwrap()),
 			value: Some(U256::from_str("9184e72a").unwrap()),
 			data: Some("d46e8dd67c5d32be8d46e8dd67c5d32be8058bb8eb970870f072445675058bb8eb970870f072445675".from_hex().unwrap().into()),
-			nonce: None
+			nonce: None,
+			env: None,
 		});
 	}
 
@@ -120,6 +162,20 @@ mod tests {
 			value: None,
 			data: None,
 			nonce: None,
+			env: None,
 		});
 	}
+
+	#[test]
+	fn call_request_deserialize_env() {
+		let s = r#"{"to":"0x0000000000000000000000000000000000000001","env":{"timestamp":"0x10","gasLimit":"0x5208"}}"#;
+		let deserialized: CallRequest = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized.env, Some(EnvOverrides {
+			coinbase: None,
+			timestamp: Some(U256::from(0x10)),
+			difficulty: None,
+			gas_limit: Some(U256::from(0x5208)),
+		}));
+	}
 }
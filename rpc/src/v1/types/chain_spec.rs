@@ -0,0 +1,89 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Active chain spec parameters.
+
+use std::collections::BTreeMap;
+use ethcore::client::ChainSpecInfo;
+use v1::types::{H160, H256, U256};
+
+/// Normalized parameters of the active chain spec.
+#[derive(Debug, Serialize)]
+pub struct ChainSpec {
+	/// Spec name.
+	pub name: String,
+	/// Name of the consensus engine.
+	pub engine: String,
+	/// Network id.
+	#[serde(rename="networkId")]
+	pub network_id: U256,
+	/// Chain id.
+	#[serde(rename="chainId")]
+	pub chain_id: U256,
+	/// Main subprotocol name.
+	#[serde(rename="subprotocolName")]
+	pub subprotocol_name: String,
+	/// Account start nonce.
+	#[serde(rename="accountStartNonce")]
+	pub account_start_nonce: U256,
+	/// Maximum size of extra data.
+	#[serde(rename="maximumExtraDataSize")]
+	pub maximum_extra_data_size: U256,
+	/// Minimum gas limit.
+	#[serde(rename="minGasLimit")]
+	pub min_gas_limit: U256,
+	/// Fork block to check.
+	#[serde(rename="forkBlock")]
+	pub fork_block: Option<U256>,
+	/// Hash of the fork block to check.
+	#[serde(rename="forkHash")]
+	pub fork_hash: Option<H256>,
+	/// Whether block receipts root is validated.
+	#[serde(rename="validateReceipts")]
+	pub validate_receipts: bool,
+	/// Rule transitions mapped to the first block they are enforced at.
+	pub transitions: BTreeMap<String, U256>,
+	/// Schedule changes of the spec, by the (hex) block number they activate at.
+	#[serde(rename="scheduleOverrides")]
+	pub schedule_overrides: BTreeMap<String, BTreeMap<String, String>>,
+	/// Builtin contracts mapped to their activation block.
+	pub builtins: BTreeMap<H160, U256>,
+	/// Additional engine parameters.
+	#[serde(rename="engineParams")]
+	pub engine_params: BTreeMap<String, String>,
+}
+
+impl From<ChainSpecInfo> for ChainSpec {
+	fn from(i: ChainSpecInfo) -> Self {
+		ChainSpec {
+			name: i.name,
+			engine: i.engine,
+			network_id: i.network_id.into(),
+			chain_id: i.chain_id.into(),
+			subprotocol_name: i.subprotocol_name,
+			account_start_nonce: i.account_start_nonce.into(),
+			maximum_extra_data_size: i.maximum_extra_data_size.into(),
+			min_gas_limit: i.min_gas_limit.into(),
+			fork_block: i.fork_block.map(Into::into),
+			fork_hash: i.fork_hash.map(Into::into),
+			validate_receipts: i.validate_receipts,
+			transitions: i.transitions.into_iter().map(|(k, v)| (k, v.into())).collect(),
+			schedule_overrides: i.schedule_overrides.into_iter().map(|(k, v)| (format!("{:#x}", k), v)).collect(),
+			builtins: i.builtins.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+			engine_params: i.engine_params,
+		}
+	}
+}
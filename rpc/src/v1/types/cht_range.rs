@@ -0,0 +1,61 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Blocks covered by a canonical hash trie.
+
+use ethcore::cht;
+use util;
+use v1::types::{H256, U256};
+
+/// A canonical hash trie with a known root.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ChtRange {
+	/// Number of the CHT.
+	pub number: U256,
+	/// Number of the first block in the CHT.
+	#[serde(rename="firstBlock")]
+	pub first_block: U256,
+	/// Number of the last block in the CHT.
+	#[serde(rename="lastBlock")]
+	pub last_block: U256,
+	/// Root of the trie.
+	pub root: H256,
+}
+
+impl ChtRange {
+	/// Range of the CHT with given number and root.
+	pub fn new(number: u64, root: util::H256) -> Self {
+		ChtRange {
+			number: number.into(),
+			first_block: cht::start_number(number).into(),
+			last_block: (cht::start_number(number + 1) - 1).into(),
+			root: root.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::ChtRange;
+
+	#[test]
+	fn range_serialization() {
+		let range = ChtRange::new(1, 5.into());
+
+		assert_eq!(serde_json::to_string(&range).unwrap(), r#"{"number":"0x1","firstBlock":"0x801","lastBlock":"0x1000","root":"0x0000000000000000000000000000000000000000000000000000000000000005"}"#);
+	}
+}
@@ -0,0 +1,66 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database compaction status.
+
+use ethcore::client::CompactionStatus as EthCompactionStatus;
+
+/// Progress of the latest database compaction.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompactionStatus {
+	/// Whether a compaction is running.
+	#[serde(rename="inProgress")]
+	pub in_progress: bool,
+	/// Number of columns compacted so far.
+	#[serde(rename="columnsCompacted")]
+	pub columns_compacted: u32,
+	/// Number of columns to compact.
+	#[serde(rename="columnsTotal")]
+	pub columns_total: u32,
+	/// UNIX timestamp of the end of the last finished compaction.
+	#[serde(rename="lastFinished")]
+	pub last_finished: Option<u64>,
+}
+
+impl From<EthCompactionStatus> for CompactionStatus {
+	fn from(s: EthCompactionStatus) -> Self {
+		CompactionStatus {
+			in_progress: s.in_progress,
+			columns_compacted: s.columns_compacted,
+			columns_total: s.columns_total,
+			last_finished: s.last_finished,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::CompactionStatus;
+
+	#[test]
+	fn compaction_status_serialization() {
+		let status = CompactionStatus {
+			in_progress: true,
+			columns_compacted: 3,
+			columns_total: 7,
+			last_finished: None,
+		};
+		let serialized = serde_json::to_string(&status).unwrap();
+		assert_eq!(serialized, r#"{"inProgress":true,"columnsCompacted":3,"columnsTotal":7,"lastFinished":null}"#);
+		assert_eq!(serde_json::from_str::<CompactionStatus>(&serialized).unwrap(), status);
+	}
+}
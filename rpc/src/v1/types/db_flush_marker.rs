@@ -0,0 +1,61 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database flush marker.
+
+use ethcore::client::DbFlushMarker as EthDbFlushMarker;
+use v1::types::{H256, U256};
+
+/// Latest state of the node's database written to disk.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbFlushMarker {
+	/// Number of database flushes since the node was started.
+	pub sequence: U256,
+	/// Best block number at the time of the flush.
+	#[serde(rename="blockNumber")]
+	pub block_number: U256,
+	/// Best block hash at the time of the flush.
+	#[serde(rename="blockHash")]
+	pub block_hash: H256,
+}
+
+impl From<EthDbFlushMarker> for DbFlushMarker {
+	fn from(m: EthDbFlushMarker) -> Self {
+		DbFlushMarker {
+			sequence: m.sequence.into(),
+			block_number: m.best_block_number.into(),
+			block_hash: m.best_block_hash.into(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::DbFlushMarker;
+
+	#[test]
+	fn flush_marker_serialization() {
+		let marker = DbFlushMarker {
+			sequence: 5.into(),
+			block_number: 10.into(),
+			block_hash: 1u64.into(),
+		};
+		let serialized = serde_json::to_string(&marker).unwrap();
+		assert_eq!(serialized, r#"{"sequence":"0x5","blockNumber":"0xa","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#);
+		assert_eq!(serde_json::from_str::<DbFlushMarker>(&serialized).unwrap(), marker);
+	}
+}
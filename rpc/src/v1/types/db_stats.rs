@@ -0,0 +1,69 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Database column statistics.
+
+use ethcore::client::DbColumnStats as EthDbColumnStats;
+
+/// Estimated statistics of a database column.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbColumnStats {
+	/// Name of the column.
+	pub name: String,
+	/// Size of the column's table files on disk, in bytes.
+	#[serde(rename="sizeOnDisk")]
+	pub size_on_disk: u64,
+	/// Estimated number of keys.
+	#[serde(rename="estimatedKeys")]
+	pub estimated_keys: u64,
+	/// Memory used by the column's block cache, in bytes.
+	#[serde(rename="cacheUsage")]
+	pub cache_usage: u64,
+	/// Capacity of the column's block cache, in bytes.
+	#[serde(rename="cacheCapacity")]
+	pub cache_capacity: u64,
+	/// Estimated number of bytes compaction needs to rewrite.
+	#[serde(rename="pendingCompactionBytes")]
+	pub pending_compaction_bytes: u64,
+	/// Lookups answered by the node's in-memory cache of the column.
+	#[serde(rename="cacheHits")]
+	pub cache_hits: u64,
+	/// Lookups of the node's in-memory cache of the column which had to read the database.
+	#[serde(rename="cacheMisses")]
+	pub cache_misses: u64,
+	/// Share of lookups answered by the in-memory cache (`None` if there were none).
+	#[serde(rename="cacheHitRate")]
+	pub cache_hit_rate: Option<f64>,
+}
+
+impl From<EthDbColumnStats> for DbColumnStats {
+	fn from(s: EthDbColumnStats) -> Self {
+		DbColumnStats {
+			name: s.name,
+			size_on_disk: s.size_on_disk,
+			estimated_keys: s.estimated_keys,
+			cache_usage: s.cache_usage,
+			cache_capacity: s.cache_capacity,
+			pending_compaction_bytes: s.pending_compaction_bytes,
+			cache_hits: s.cache_hits,
+			cache_misses: s.cache_misses,
+			cache_hit_rate: match s.cache_hits + s.cache_misses {
+				0 => None,
+				lookups => Some(s.cache_hits as f64 / lookups as f64),
+			},
+		}
+	}
+}
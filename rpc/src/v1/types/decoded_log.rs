@@ -0,0 +1,102 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Logs with decoded events.
+
+use serde_json::Value;
+use v1::helpers::abi_registry;
+use v1::types::{Log, LogsCursor};
+
+/// Parameter of a decoded event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedParam {
+	/// Parameter name.
+	pub name: String,
+	/// Parameter value. Integers and bytes are hex-encoded, signed integers in two's complement.
+	pub value: Value,
+}
+
+/// Event decoded from a log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedEvent {
+	/// Event name.
+	pub name: String,
+	/// Event signature.
+	pub signature: String,
+	/// Event parameters, in order.
+	pub params: Vec<DecodedParam>,
+}
+
+impl From<abi_registry::DecodedEvent> for DecodedEvent {
+	fn from(e: abi_registry::DecodedEvent) -> Self {
+		DecodedEvent {
+			name: e.name,
+			signature: e.signature,
+			params: e.params.into_iter().map(|(name, value)| DecodedParam { name: name, value: value }).collect(),
+		}
+	}
+}
+
+/// Log with its event, if the ABI of the contract is known.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedLog {
+	/// The log.
+	pub log: Log,
+	/// Decoded event, `None` if the log couldn't be decoded.
+	pub event: Option<DecodedEvent>,
+}
+
+/// Page of logs with decoded events.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DecodedLogsPage {
+	/// Logs in chain order.
+	pub logs: Vec<DecodedLog>,
+	/// Position of the first log of the next page, or `None` if this is the last page.
+	pub next: Option<LogsCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::{self, Value};
+	use v1::types::{Log, H160};
+	use super::{DecodedLog, DecodedEvent, DecodedParam};
+
+	#[test]
+	fn decoded_log_serialization() {
+		let log = Log {
+			address: H160::from(0x10u64),
+			topics: vec![],
+			data: vec![].into(),
+			block_hash: None,
+			block_number: None,
+			transaction_hash: None,
+			transaction_index: None,
+			log_index: None,
+			transaction_log_index: None,
+			log_type: "mined".into(),
+		};
+		let decoded = DecodedLog {
+			log: log,
+			event: Some(DecodedEvent {
+				name: "Approval".into(),
+				signature: "Approval(address,bool)".into(),
+				params: vec![DecodedParam { name: "approved".into(), value: Value::Bool(true) }],
+			}),
+		};
+
+		assert_eq!(serde_json::to_string(&decoded).unwrap(), r#"{"log":{"address":"0x0000000000000000000000000000000000000010","topics":[],"data":"0x","blockHash":null,"blockNumber":null,"transactionHash":null,"transactionIndex":null,"logIndex":null,"transactionLogIndex":null,"type":"mined"},"event":{"name":"Approval","signature":"Approval(address,bool)","params":[{"name":"approved","value":true}]}}"#);
+	}
+}
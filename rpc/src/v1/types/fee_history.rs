@@ -0,0 +1,98 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Gas usage and gas price statistics of recent blocks.
+
+use ethcore::client::BlockFeeStats as EthBlockFeeStats;
+use util::Uint;
+use v1::types::U256;
+
+/// Gas usage of a block and gas prices of its transactions.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct BlockFeeStats {
+	/// Block number.
+	pub number: U256,
+	/// Gas used divided by the gas limit of the block.
+	#[serde(rename="gasUsedRatio")]
+	pub gas_used_ratio: f64,
+	/// Number of transactions in the block.
+	#[serde(rename="transactionCount")]
+	pub transaction_count: U256,
+	/// Lowest gas price of the block's transactions.
+	#[serde(rename="minGasPrice")]
+	pub min_gas_price: Option<U256>,
+	/// Median gas price of the block's transactions.
+	#[serde(rename="medianGasPrice")]
+	pub median_gas_price: Option<U256>,
+	/// Highest gas price of the block's transactions.
+	#[serde(rename="maxGasPrice")]
+	pub max_gas_price: Option<U256>,
+}
+
+impl From<EthBlockFeeStats> for BlockFeeStats {
+	fn from(s: EthBlockFeeStats) -> Self {
+		let gas_used_ratio = match s.gas_limit.is_zero() {
+			true => 0.0,
+			false => s.gas_used.low_u64() as f64 / s.gas_limit.low_u64() as f64,
+		};
+
+		BlockFeeStats {
+			number: s.number.into(),
+			gas_used_ratio: gas_used_ratio,
+			transaction_count: s.transaction_count.into(),
+			min_gas_price: s.min_gas_price.map(Into::into),
+			median_gas_price: s.median_gas_price.map(Into::into),
+			max_gas_price: s.max_gas_price.map(Into::into),
+		}
+	}
+}
+
+/// Statistics of consecutive blocks.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FeeHistory {
+	/// Number of the first block, `None` if there are no blocks.
+	#[serde(rename="oldestBlock")]
+	pub oldest_block: Option<U256>,
+	/// Statistics of each block, oldest first.
+	pub blocks: Vec<BlockFeeStats>,
+}
+
+impl From<Vec<EthBlockFeeStats>> for FeeHistory {
+	fn from(stats: Vec<EthBlockFeeStats>) -> Self {
+		FeeHistory {
+			oldest_block: stats.first().map(|s| s.number.into()),
+			blocks: stats.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use ethcore::client::BlockFeeStats as EthBlockFeeStats;
+	use super::FeeHistory;
+
+	#[test]
+	fn history_serialization() {
+		let stats = vec![
+			EthBlockFeeStats::new(9, 1000000.into(), 4000000.into(), vec![20.into(), 40.into()]),
+			EthBlockFeeStats::new(10, 0.into(), 4000000.into(), vec![]),
+		];
+		let history: FeeHistory = stats.into();
+
+		assert_eq!(serde_json::to_string(&history).unwrap(), r#"{"oldestBlock":"0x9","blocks":[{"number":"0x9","gasUsedRatio":0.25,"transactionCount":"0x2","minGasPrice":"0x14","medianGasPrice":"0x28","maxGasPrice":"0x28"},{"number":"0xa","gasUsedRatio":0.0,"transactionCount":"0x0","minGasPrice":null,"medianGasPrice":null,"maxGasPrice":null}]}"#);
+	}
+}
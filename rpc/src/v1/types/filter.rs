@@ -47,13 +47,23 @@ impl<T> Deserialize for VariadicValue<T> where T: Deserialize {
 	}
 }
 
+impl<T> Serialize for VariadicValue<T> where T: Deserialize + Serialize {
+	fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error> where S: Serializer {
+		match *self {
+			VariadicValue::Single(ref value) => value.serialize(s),
+			VariadicValue::Multiple(ref values) => values.serialize(s),
+			VariadicValue::Null => s.serialize_unit(),
+		}
+	}
+}
+
 /// Filter Address
 pub type FilterAddress = VariadicValue<H160>;
 /// Topic
 pub type Topic = VariadicValue<H256>;
 
 /// Filter
-#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Filter {
 	/// From Block
@@ -157,6 +167,13 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn filter_serialization_roundtrip() {
+		let s = r#"{"fromBlock":"0xa","toBlock":"latest","address":"0x0000000000000000000000000000000000000001","topics":[null,["0x000000000000000000000000a94f5374fce5edbc8e2a8697c15331677e6ebf0b"]],"limit":null}"#;
+		let deserialized: Filter = serde_json::from_str(s).unwrap();
+		assert_eq!(serde_json::to_string(&deserialized).unwrap(), s);
+	}
+
 	#[test]
 	fn filter_conversion() {
 		let filter = Filter {
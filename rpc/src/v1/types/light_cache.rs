@@ -0,0 +1,65 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client data cache usage.
+
+use light::cache::CacheUsage;
+
+/// Memory usage of one of the light client's data caches.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LightCacheUsage {
+	/// Name of the cache.
+	pub name: String,
+	/// Bytes used by cached items.
+	pub used: u64,
+	/// Share of the caches' common memory budget, in bytes.
+	pub quota: u64,
+	/// Number of cached items.
+	pub items: u64,
+	/// Number of items evicted or refused to stay within the budget.
+	pub evictions: u64,
+}
+
+impl From<CacheUsage> for LightCacheUsage {
+	fn from(usage: CacheUsage) -> Self {
+		LightCacheUsage {
+			name: usage.name.into(),
+			used: usage.used as u64,
+			quota: usage.quota as u64,
+			items: usage.items as u64,
+			evictions: usage.evictions as u64,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::LightCacheUsage;
+
+	#[test]
+	fn should_serialize_light_cache_usage() {
+		let usage = LightCacheUsage {
+			name: "headers".into(),
+			used: 1024,
+			quota: 4096,
+			items: 2,
+			evictions: 1,
+		};
+
+		assert_eq!(serde_json::to_string(&usage).unwrap(), r#"{"name":"headers","used":1024,"quota":4096,"items":2,"evictions":1}"#);
+	}
+}
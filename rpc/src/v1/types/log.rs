@@ -15,10 +15,11 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use ethcore::log_entry::{LocalizedLogEntry, LogEntry};
+use v1::helpers::response_limits::{ResponseSize, bytes_size};
 use v1::types::{Bytes, H160, H256, U256};
 
 /// Log
-#[derive(Debug, Serialize, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 pub struct Log {
 	/// H160
 	pub address: H160,
@@ -83,6 +84,17 @@ impl From<LogEntry> for Log {
 	}
 }
 
+impl ResponseSize for Log {
+	fn response_size(&self) -> usize {
+		// field names, address, hashes, numbers and type of a mined log
+		const OVERHEAD: usize = 450;
+		// quoted hash and separating comma
+		const TOPIC_SIZE: usize = 69;
+
+		OVERHEAD + self.topics.len() * TOPIC_SIZE + bytes_size(&self.data)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
@@ -0,0 +1,109 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Paginated logs listing.
+
+use util::{self, Uint};
+use v1::types::{Log, H256, U256};
+
+/// Position of a log in the chain.
+///
+/// Carries the hash of the block, so that a cursor issued before a reorganization
+/// is rejected instead of skipping or repeating logs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogsCursor {
+	/// Number of the block.
+	#[serde(rename="blockNumber")]
+	pub block_number: U256,
+	/// Hash of the block.
+	#[serde(rename="blockHash")]
+	pub block_hash: H256,
+	/// Index of the transaction in the block.
+	#[serde(rename="transactionIndex")]
+	pub transaction_index: U256,
+	/// Index of the log in the block.
+	#[serde(rename="logIndex")]
+	pub log_index: U256,
+}
+
+impl LogsCursor {
+	/// Cursor at given block, transaction index and log index.
+	pub fn new(block_number: u64, block_hash: util::H256, transaction_index: usize, log_index: usize) -> Self {
+		LogsCursor {
+			block_number: block_number.into(),
+			block_hash: block_hash.into(),
+			transaction_index: transaction_index.into(),
+			log_index: log_index.into(),
+		}
+	}
+
+	/// Block number, transaction index and log index, ordered as the logs are.
+	/// `None` if any of them doesn't fit in 64 bits.
+	pub fn position(&self) -> Option<(u64, u64, u64)> {
+		let number = |value: U256| {
+			let value: util::U256 = value.into();
+			match value > util::U256::from(u64::max_value()) {
+				true => None,
+				false => Some(value.low_u64()),
+			}
+		};
+		match (number(self.block_number), number(self.transaction_index), number(self.log_index)) {
+			(Some(block), Some(transaction), Some(log)) => Some((block, transaction, log)),
+			_ => None,
+		}
+	}
+}
+
+/// Page of logs matching a filter.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct LogsPage {
+	/// Logs in chain order.
+	pub logs: Vec<Log>,
+	/// Position of the first log of the next page, or `None` if this is the last page.
+	pub next: Option<LogsCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{LogsCursor, LogsPage};
+
+	#[test]
+	fn cursor_serialization() {
+		let s = r#"{"blockNumber":"0x3e8","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionIndex":"0x2","logIndex":"0x7"}"#;
+		let cursor: LogsCursor = serde_json::from_str(s).unwrap();
+
+		assert_eq!(cursor, LogsCursor::new(1000, 5.into(), 2, 7));
+		assert_eq!(cursor.position(), Some((1000, 2, 7)));
+		assert_eq!(serde_json::to_string(&cursor).unwrap(), s);
+	}
+
+	#[test]
+	fn cursor_position_out_of_range() {
+		let s = r#"{"blockNumber":"0x10000000000000001","blockHash":"0x0000000000000000000000000000000000000000000000000000000000000005","transactionIndex":"0x2","logIndex":"0x7"}"#;
+		let cursor: LogsCursor = serde_json::from_str(s).unwrap();
+
+		assert_eq!(cursor.position(), None);
+	}
+
+	#[test]
+	fn page_serialization() {
+		let page = LogsPage { logs: vec![], next: None };
+
+		assert_eq!(serde_json::to_string(&page).unwrap(), r#"{"logs":[],"next":null}"#);
+	}
+}
@@ -21,18 +21,33 @@ mod account_info;
 mod block;
 mod block_number;
 mod bytes;
+mod call_cache;
+mod call_proof;
 mod call_request;
+mod chain_spec;
+mod cht_range;
+mod compaction_status;
 mod confirmations;
 mod consensus_status;
+mod db_flush_marker;
+mod db_stats;
+mod decoded_log;
 mod derivation;
+mod fee_history;
 mod filter;
 mod hash;
 mod histogram;
 mod index;
+mod light_cache;
 mod log;
+mod logs_page;
+mod pending_snapshot;
 mod provenance;
 mod receipt;
 mod rpc_settings;
+mod signing_audit;
+mod simulation;
+mod storage_keys;
 mod sync;
 mod trace;
 mod trace_filter;
@@ -44,31 +59,49 @@ mod work;
 
 pub use self::account_info::{AccountInfo, HwAccountInfo};
 pub use self::bytes::Bytes;
-pub use self::block::{RichBlock, Block, BlockTransactions};
+pub use self::call_cache::CallCacheStats;
+pub use self::block::{RichBlock, Block, BlockTransactions, EncodedHeader};
 pub use self::block_number::BlockNumber;
-pub use self::call_request::CallRequest;
+pub use self::call_proof::CallProof;
+pub use self::call_request::{CallRequest, EnvOverrides};
+pub use self::chain_spec::ChainSpec;
+pub use self::cht_range::ChtRange;
+pub use self::compaction_status::CompactionStatus;
 pub use self::confirmations::{
 	ConfirmationPayload, ConfirmationRequest, ConfirmationResponse, ConfirmationResponseWithToken,
 	TransactionModification, SignRequest, DecryptRequest, Either
 };
 pub use self::consensus_status::*;
+pub use self::db_flush_marker::DbFlushMarker;
+pub use self::db_stats::DbColumnStats;
+pub use self::decoded_log::{DecodedLog, DecodedLogsPage, DecodedEvent, DecodedParam};
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::fee_history::{BlockFeeStats, FeeHistory};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
+pub use self::light_cache::LightCacheUsage;
 pub use self::log::Log;
+pub use self::logs_page::{LogsCursor, LogsPage};
+pub use self::pending_snapshot::PendingSnapshot;
 pub use self::provenance::{Origin, DappId};
-pub use self::receipt::Receipt;
+pub use self::receipt::{Receipt, ReceiptProof, ReceiptOptions};
 pub use self::rpc_settings::RpcSettings;
+pub use self::signing_audit::SigningAuditEntry;
+pub use self::simulation::{SimulationRequest, SimulationResult};
+pub use self::storage_keys::{StorageKeysPage, StorageKeysToken};
 pub use self::sync::{
 	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, LesProtocolInfo,
+	TransactionStats, PropagationStats, PropagationKind, ReceiptsBackfillStatus, ChainStatus, NodeStatus, LightPeerInfo, LightCapabilities, EthProtocolInfo, LesProtocolInfo, SyncEvent, SyncEventKind,
 };
-pub use self::trace::{LocalizedTrace, TraceResults};
+pub use self::trace::{LocalizedTrace, TraceResults, StateDiff};
 pub use self::trace_filter::TraceFilter;
-pub use self::transaction::{Transaction, RichRawTransaction, LocalTransactionStatus};
+pub use self::transaction::{
+	Transaction, RichRawTransaction, LocalTransactionStatus, TransactionQueueEvent, TransactionQueueEventKind,
+	TransactionDropReason,
+};
 pub use self::transaction_request::TransactionRequest;
 pub use self::transaction_condition::TransactionCondition;
 pub use self::uint::{U128, U256};
-pub use self::work::Work;
+pub use self::work::{Work, WorkerCredentials, WorkerStats};
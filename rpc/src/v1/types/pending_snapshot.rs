@@ -0,0 +1,46 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Pending block snapshot.
+
+use ethcore::miner::PendingSnapshot as EthPendingSnapshot;
+use v1::types::{H256, U256};
+
+/// Pending block snapshot `eth_call` and `eth_estimateGas` at `pending` are executed against.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PendingSnapshot {
+	/// Increases every time the snapshot is rebuilt.
+	pub ordinal: U256,
+	/// Number of the pending block.
+	#[serde(rename="blockNumber")]
+	pub block_number: U256,
+	/// Hash of the block the pending block is built on.
+	#[serde(rename="parentHash")]
+	pub parent_hash: H256,
+	/// Number of transactions in the pending block.
+	pub transactions: U256,
+}
+
+impl From<EthPendingSnapshot> for PendingSnapshot {
+	fn from(s: EthPendingSnapshot) -> Self {
+		PendingSnapshot {
+			ordinal: s.ordinal.into(),
+			block_number: s.number.into(),
+			parent_hash: s.parent_hash.into(),
+			transactions: s.transactions.into(),
+		}
+	}
+}
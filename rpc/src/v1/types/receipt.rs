@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use v1::types::{Log, H160, H256, H2048, U256};
+use v1::types::{Bytes, Log, H160, H256, H2048, U256};
 use ethcore::receipt::{Receipt as EthReceipt, RichReceipt, LocalizedReceipt};
 
 /// Receipt
@@ -49,6 +49,27 @@ pub struct Receipt {
 	/// Logs bloom
 	#[serde(rename="logsBloom")]
 	pub logs_bloom: H2048,
+	/// Proof of inclusion in the block, if requested
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub proof: Option<ReceiptProof>,
+}
+
+/// Proof that a receipt is included in a block.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ReceiptProof {
+	/// RLP of the block header
+	pub header: Bytes,
+	/// Receipts trie nodes from the header's receipts root down to the receipt
+	pub branch: Vec<Bytes>,
+}
+
+/// Options of a receipt query.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReceiptOptions {
+	/// Whether to include a proof of inclusion
+	#[serde(rename="withProof", default)]
+	pub with_proof: bool,
 }
 
 impl From<LocalizedReceipt> for Receipt {
@@ -64,6 +85,7 @@ impl From<LocalizedReceipt> for Receipt {
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			state_root: r.state_root.map(Into::into),
 			logs_bloom: r.log_bloom.into(),
+			proof: None,
 		}
 	}
 }
@@ -81,6 +103,7 @@ impl From<RichReceipt> for Receipt {
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			state_root: r.state_root.map(Into::into),
 			logs_bloom: r.log_bloom.into(),
+			proof: None,
 		}
 	}
 }
@@ -98,6 +121,7 @@ impl From<EthReceipt> for Receipt {
 			logs: r.logs.into_iter().map(Into::into).collect(),
 			state_root: r.state_root.map(Into::into),
 			logs_bloom: r.log_bloom.into(),
+			proof: None,
 		}
 	}
 }
@@ -105,7 +129,7 @@ impl From<EthReceipt> for Receipt {
 #[cfg(test)]
 mod tests {
 	use serde_json;
-	use v1::types::{Log, Receipt};
+	use v1::types::{Log, Receipt, ReceiptProof, ReceiptOptions};
 
 	#[test]
 	fn receipt_serialization() {
@@ -136,10 +160,27 @@ mod tests {
 			}],
 			logs_bloom: 15.into(),
 			state_root: Some(10.into()),
+			proof: None,
 		};
 
 		let serialized = serde_json::to_string(&receipt).unwrap();
 		assert_eq!(serialized, s);
 	}
-}
 
+	#[test]
+	fn receipt_proof_serialization() {
+		let proof = ReceiptProof {
+			header: vec![0xc0].into(),
+			branch: vec![vec![0x80].into(), vec![0x01, 0x02].into()],
+		};
+		assert_eq!(serde_json::to_string(&proof).unwrap(), r#"{"header":"0xc0","branch":["0x80","0x0102"]}"#);
+	}
+
+	#[test]
+	fn receipt_options_deserialization() {
+		let options: ReceiptOptions = serde_json::from_str(r#"{"withProof":true}"#).unwrap();
+		assert_eq!(options, ReceiptOptions { with_proof: true });
+		let options: ReceiptOptions = serde_json::from_str(r#"{}"#).unwrap();
+		assert_eq!(options, ReceiptOptions::default());
+	}
+}
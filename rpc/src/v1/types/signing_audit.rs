@@ -0,0 +1,58 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Signing audit log entry.
+
+use v1::types::{H160, Origin};
+
+/// Account-affecting RPC call recorded in the signing audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigningAuditEntry {
+	/// Unix timestamp (seconds) of the call.
+	pub timestamp: u64,
+	/// Called RPC method.
+	pub method: String,
+	/// Origin of the request.
+	pub origin: Origin,
+	/// Account the call referred to, if any.
+	pub account: Option<H160>,
+	/// Whether the call succeeded, `None` for notifications.
+	pub success: Option<bool>,
+	/// Error message of a failed call.
+	pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::Origin;
+	use super::SigningAuditEntry;
+
+	#[test]
+	fn audit_entry_serialization() {
+		let entry = SigningAuditEntry {
+			timestamp: 1500000000,
+			method: "personal_unlockAccount".into(),
+			origin: Origin::Rpc("http://localhost".into()),
+			account: Some(1.into()),
+			success: Some(false),
+			error: Some("Unable to unlock the account.".into()),
+		};
+		let serialized = serde_json::to_string(&entry).unwrap();
+		assert_eq!(serialized, r#"{"timestamp":1500000000,"method":"personal_unlockAccount","origin":{"rpc":"http://localhost"},"account":"0x0000000000000000000000000000000000000001","success":false,"error":"Unable to unlock the account."}"#);
+		assert_eq!(serde_json::from_str::<SigningAuditEntry>(&serialized).unwrap(), entry);
+	}
+}
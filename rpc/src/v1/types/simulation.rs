@@ -0,0 +1,92 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Transaction simulation request and result.
+
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::{self, Value};
+use ethcore::client::Executed;
+use v1::types::{Bytes, CallRequest, Log, StateDiff, U256};
+
+/// Transaction to simulate.
+#[derive(Debug, PartialEq)]
+pub enum SimulationRequest {
+	/// Unsigned transaction, given as call request.
+	Call(CallRequest),
+	/// RLP-encoded signed transaction.
+	Raw(Bytes),
+}
+
+impl Deserialize for SimulationRequest {
+	fn deserialize<D>(deserializer: D) -> Result<SimulationRequest, D::Error> where D: Deserializer {
+		let value = Value::deserialize(deserializer)?;
+		match value {
+			Value::String(_) => serde_json::from_value(value).map(SimulationRequest::Raw).map_err(D::Error::custom),
+			Value::Object(_) => serde_json::from_value(value).map(SimulationRequest::Call).map_err(D::Error::custom),
+			_ => Err(D::Error::custom("Expected raw signed transaction or call request object.")),
+		}
+	}
+}
+
+/// Outcome of a simulated transaction.
+#[derive(Debug, Serialize)]
+pub struct SimulationResult {
+	/// Gas used by the transaction.
+	#[serde(rename="gasUsed")]
+	pub gas_used: U256,
+	/// Output of the call or code of the created contract.
+	pub output: Bytes,
+	/// Logs the transaction would emit.
+	pub logs: Vec<Log>,
+	/// Changes the transaction would make to the state.
+	#[serde(rename="stateDiff")]
+	pub state_diff: Option<StateDiff>,
+	/// VM exception the execution ended with, if any.
+	pub error: Option<String>,
+}
+
+impl From<Executed> for SimulationResult {
+	fn from(executed: Executed) -> Self {
+		SimulationResult {
+			gas_used: executed.gas_used.into(),
+			output: executed.output.into(),
+			logs: executed.logs.into_iter().map(Into::into).collect(),
+			state_diff: executed.state_diff.map(Into::into),
+			error: executed.exception.map(|e| format!("{}", e)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use v1::types::Bytes;
+	use super::SimulationRequest;
+
+	#[test]
+	fn request_deserialization() {
+		let raw: SimulationRequest = serde_json::from_str(r#""0x1234""#).unwrap();
+		let call: SimulationRequest = serde_json::from_str(r#"{"data":"0x1234"}"#).unwrap();
+
+		assert_eq!(raw, SimulationRequest::Raw(Bytes::new(vec![0x12, 0x34])));
+		match call {
+			SimulationRequest::Call(call) => assert_eq!(call.data, Some(Bytes::new(vec![0x12, 0x34]))),
+			_ => panic!("Expected call request"),
+		}
+		assert!(serde_json::from_str::<SimulationRequest>("5").is_err());
+	}
+}
@@ -0,0 +1,80 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Paginated storage keys listing.
+
+use util;
+use v1::types::{Bytes, H256};
+
+/// Page of storage keys of an account.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct StorageKeysPage {
+	/// Storage keys in trie order.
+	pub keys: Vec<H256>,
+	/// Token to request the next page with, or `None` if this is the last page.
+	pub next: Option<Bytes>,
+}
+
+/// Position of a storage keys listing: the listed block and the last returned key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageKeysToken {
+	/// Hash of the block the keys are listed at.
+	pub block: util::H256,
+	/// Last key of the previous page.
+	pub after: util::H256,
+}
+
+impl StorageKeysToken {
+	/// Decode token from its opaque representation.
+	pub fn decode(bytes: &[u8]) -> Option<Self> {
+		match bytes.len() {
+			64 => Some(StorageKeysToken {
+				block: util::H256::from_slice(&bytes[..32]),
+				after: util::H256::from_slice(&bytes[32..]),
+			}),
+			_ => None,
+		}
+	}
+
+	/// Opaque representation of the token.
+	pub fn encode(&self) -> Bytes {
+		let mut bytes = self.block.to_vec();
+		bytes.extend_from_slice(&self.after);
+		bytes.into()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use util;
+	use super::{StorageKeysPage, StorageKeysToken};
+
+	#[test]
+	fn token_roundtrip() {
+		let token = StorageKeysToken { block: util::H256::from(1), after: util::H256::from(2) };
+
+		assert_eq!(StorageKeysToken::decode(&token.encode().0), Some(token));
+		assert_eq!(StorageKeysToken::decode(&[0u8; 32]), None);
+	}
+
+	#[test]
+	fn page_serialization() {
+		let page = StorageKeysPage { keys: vec![util::H256::from(1).into()], next: None };
+
+		assert_eq!(serde_json::to_string(&page).unwrap(), r#"{"keys":["0x0000000000000000000000000000000000000000000000000000000000000001"],"next":null}"#);
+	}
+}
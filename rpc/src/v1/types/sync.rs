@@ -15,9 +15,12 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use ethsync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats,
+	PropagationStats as SyncPropagationStats, PropagationKind as SyncPropagationKind,
+	ReceiptsBackfillStatus as SyncReceiptsBackfillStatus};
 use serde::{Serialize, Serializer};
-use v1::types::{U256, H512};
+use light::on_demand::PeerInfo as OnDemandPeerInfo;
+use v1::types::{U256, H256, H512};
 
 /// Sync info
 #[derive(Default, Debug, Serialize, PartialEq)]
@@ -159,6 +162,59 @@ pub struct TransactionStats {
 	pub propagated_to: BTreeMap<H512, usize>,
 }
 
+/// Kind of a propagated item.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum PropagationKind {
+	/// Local transaction.
+	#[serde(rename="transaction")]
+	Transaction,
+	/// Locally sealed block.
+	#[serde(rename="block")]
+	Block,
+}
+
+/// Propagation statistics for a local transaction or a locally sealed block.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PropagationStats {
+	/// Hash of the transaction or block.
+	pub hash: H256,
+	/// Whether it's a transaction or a block.
+	pub kind: PropagationKind,
+	/// Best block number when it was first propagated.
+	#[serde(rename="blockNumber")]
+	pub block_number: u64,
+	/// Unix time it was first sent.
+	#[serde(rename="firstPropagated")]
+	pub first_propagated: u64,
+	/// Unix time it was last sent or announced.
+	#[serde(rename="lastPropagated")]
+	pub last_propagated: u64,
+	/// Number of peers it was sent to.
+	#[serde(rename="sentTo")]
+	pub sent_to: usize,
+	/// Number of peers its hash was announced to.
+	#[serde(rename="announcedTo")]
+	pub announced_to: usize,
+}
+
+/// Progress of the download of receipts missing for blocks in the chain.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct ReceiptsBackfillStatus {
+	/// Best block number when the backfill started.
+	#[serde(rename="startBlock")]
+	pub start_block: U256,
+	/// Lowest block number to check.
+	#[serde(rename="targetBlock")]
+	pub target_block: U256,
+	/// Next block number to check, `null` once all are checked.
+	#[serde(rename="nextBlock")]
+	pub next_block: Option<U256>,
+	/// Number of blocks receipts were imported for.
+	pub imported: U256,
+	/// Number of blocks missing receipts waiting for download.
+	pub pending: U256,
+}
+
 impl From<SyncPeerInfo> for PeerInfo {
 	fn from(p: SyncPeerInfo) -> Self {
 		PeerInfo {
@@ -189,6 +245,106 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+impl From<SyncPropagationKind> for PropagationKind {
+	fn from(kind: SyncPropagationKind) -> Self {
+		match kind {
+			SyncPropagationKind::Transaction => PropagationKind::Transaction,
+			SyncPropagationKind::Block => PropagationKind::Block,
+		}
+	}
+}
+
+impl From<SyncPropagationStats> for PropagationStats {
+	fn from(s: SyncPropagationStats) -> Self {
+		PropagationStats {
+			hash: s.hash.into(),
+			kind: s.kind.into(),
+			block_number: s.block_number,
+			first_propagated: s.first_propagated,
+			last_propagated: s.last_propagated,
+			sent_to: s.sent_to,
+			announced_to: s.announced_to,
+		}
+	}
+}
+
+impl From<SyncReceiptsBackfillStatus> for ReceiptsBackfillStatus {
+	fn from(s: SyncReceiptsBackfillStatus) -> Self {
+		ReceiptsBackfillStatus {
+			start_block: s.start_block.into(),
+			target_block: s.target_block.into(),
+			next_block: s.next_block.map(Into::into),
+			imported: s.imported.into(),
+			pending: s.pending.into(),
+		}
+	}
+}
+
+/// Requests a light protocol server serves.
+#[derive(Default, Debug, Serialize)]
+pub struct LightCapabilities {
+	/// Whether it serves headers.
+	#[serde(rename="serveHeaders")]
+	pub serve_headers: bool,
+	/// Earliest block it serves bodies and receipts for.
+	#[serde(rename="serveChainSince")]
+	pub serve_chain_since: Option<U256>,
+	/// Earliest block it serves state for.
+	#[serde(rename="serveStateSince")]
+	pub serve_state_since: Option<U256>,
+	/// Whether it relays transactions.
+	#[serde(rename="txRelay")]
+	pub tx_relay: bool,
+}
+
+/// Light protocol server a light client is connected to.
+#[derive(Default, Debug, Serialize)]
+pub struct LightPeerInfo {
+	/// Peer id, valid for the duration of the connection.
+	pub id: usize,
+	/// Advertised best block hash.
+	#[serde(rename="headHash")]
+	pub head_hash: H256,
+	/// Advertised best block number.
+	#[serde(rename="headNumber")]
+	pub head_number: U256,
+	/// Advertised total difficulty.
+	#[serde(rename="headDifficulty")]
+	pub head_difficulty: U256,
+	/// Served requests.
+	pub capabilities: LightCapabilities,
+	/// Estimated request credits left, if the peer serves requests.
+	pub credits: Option<U256>,
+	/// Maximum request credits, if the peer serves requests.
+	#[serde(rename="creditLimit")]
+	pub credit_limit: Option<U256>,
+	/// Average response time in milliseconds, if measured.
+	pub latency: Option<u64>,
+	/// Whether requests are preferably sent to this peer.
+	pub pinned: bool,
+}
+
+impl From<OnDemandPeerInfo> for LightPeerInfo {
+	fn from(info: OnDemandPeerInfo) -> Self {
+		LightPeerInfo {
+			id: info.id,
+			head_hash: info.status.head_hash.into(),
+			head_number: info.status.head_num.into(),
+			head_difficulty: info.status.head_td.into(),
+			capabilities: LightCapabilities {
+				serve_headers: info.capabilities.serve_headers,
+				serve_chain_since: info.capabilities.serve_chain_since.map(Into::into),
+				serve_state_since: info.capabilities.serve_state_since.map(Into::into),
+				tx_relay: info.capabilities.tx_relay,
+			},
+			credits: None,
+			credit_limit: None,
+			latency: info.latency.map(|l| l.num_milliseconds() as u64),
+			pinned: info.pinned,
+		}
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 pub struct ChainStatus {
@@ -197,11 +353,80 @@ pub struct ChainStatus {
 	pub block_gap: Option<(U256, U256)>,
 }
 
+/// Node status: how far the best block trails the NTP-verified current time.
+#[derive(Default, Debug, Serialize)]
+pub struct NodeStatus {
+	/// Best block number.
+	#[serde(rename="bestBlock")]
+	pub best_block: U256,
+	/// Timestamp of the best block.
+	#[serde(rename="bestBlockTimestamp")]
+	pub best_block_timestamp: U256,
+	/// Seconds the best block is behind the current time, if the local clock was verified.
+	#[serde(rename="headLag")]
+	pub head_lag: Option<U256>,
+	/// Offset of the local clock from NTP time in milliseconds, if it was measured.
+	#[serde(rename="clockOffset")]
+	pub clock_offset: Option<i64>,
+	/// Whether the node appears to be trailing the network.
+	pub trailing: bool,
+}
+
+/// Kind of sync status transition.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum SyncEventKind {
+	/// Major sync has started.
+	#[serde(rename="started")]
+	Started,
+	/// Another 10% of blocks between the starting and highest block were imported.
+	#[serde(rename="progress")]
+	Progress,
+	/// Major sync has completed.
+	#[serde(rename="completed")]
+	Completed,
+	/// No progress was made for a while.
+	#[serde(rename="stalled")]
+	Stalled,
+	/// Chain head didn't advance while peers reported higher heads, peers were rotated.
+	#[serde(rename="peersRotated")]
+	PeersRotated,
+	/// Warp sync: waiting for snapshot manifest.
+	#[serde(rename="warpManifest")]
+	WarpManifest,
+	/// Warp sync: downloading snapshot chunks.
+	#[serde(rename="warpData")]
+	WarpData,
+	/// Warp sync: waiting for snapshot restoration.
+	#[serde(rename="warpRestoring")]
+	WarpRestoring,
+}
+
+/// Sync status transition.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SyncEvent {
+	/// Kind of transition
+	pub event: SyncEventKind,
+	/// Current block
+	#[serde(rename="currentBlock")]
+	pub current_block: U256,
+	/// Highest block seen so far
+	#[serde(rename="highestBlock")]
+	pub highest_block: Option<U256>,
+	/// Percentage of blocks synced (`progress` events only)
+	pub percentage: Option<u8>,
+	/// Warp sync snapshot chunks total.
+	#[serde(rename="warpChunksAmount")]
+	pub warp_chunks_amount: Option<U256>,
+	/// Warp sync snapshot chunks processed.
+	#[serde(rename="warpChunksProcessed")]
+	pub warp_chunks_processed: Option<U256>,
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, PropagationStats, PropagationKind, ChainStatus, NodeStatus, LightPeerInfo, SyncEvent, SyncEventKind};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -210,6 +435,20 @@ mod tests {
 		assert_eq!(serialized, r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null}"#);
 	}
 
+	#[test]
+	fn test_serialize_sync_event() {
+		let t = SyncEvent {
+			event: SyncEventKind::Progress,
+			current_block: 10.into(),
+			highest_block: Some(100.into()),
+			percentage: Some(10),
+			warp_chunks_amount: None,
+			warp_chunks_processed: None,
+		};
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"event":"progress","currentBlock":"0xa","highestBlock":"0x64","percentage":10,"warpChunksAmount":null,"warpChunksProcessed":null}"#);
+	}
+
 	#[test]
 	fn test_serialize_peers() {
 		let t = Peers::default();
@@ -228,6 +467,13 @@ mod tests {
 		assert_eq!(serialized, r#"{"startingBlock":"0x0","currentBlock":"0x0","highestBlock":"0x0","warpChunksAmount":null,"warpChunksProcessed":null}"#);
 	}
 
+	#[test]
+	fn test_serialize_light_peer_info() {
+		let t = LightPeerInfo::default();
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"id":0,"headHash":"0x0000000000000000000000000000000000000000000000000000000000000000","headNumber":"0x0","headDifficulty":"0x0","capabilities":{"serveHeaders":false,"serveChainSince":null,"serveStateSince":null,"txRelay":false},"credits":null,"creditLimit":null,"latency":null,"pinned":false}"#);
+	}
+
 	#[test]
 	fn test_serialize_block_gap() {
 		let mut t = ChainStatus::default();
@@ -252,4 +498,20 @@ mod tests {
 		let serialized = serde_json::to_string(&stats).unwrap();
 		assert_eq!(serialized, r#"{"firstSeen":100,"propagatedTo":{"0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a":50}}"#)
 	}
+
+	#[test]
+	fn test_serialize_propagation_stats() {
+		let stats = PropagationStats {
+			hash: 5.into(),
+			kind: PropagationKind::Block,
+			block_number: 100,
+			first_propagated: 1500000000,
+			last_propagated: 1500000002,
+			sent_to: 5,
+			announced_to: 20,
+		};
+
+		let serialized = serde_json::to_string(&stats).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","kind":"block","blockNumber":100,"firstPropagated":1500000000,"lastPropagated":1500000002,"sentTo":5,"announcedTo":20}"#)
+	}
 }
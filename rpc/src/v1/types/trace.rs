@@ -24,6 +24,7 @@ use ethcore::account_diff;
 use ethcore::executed;
 use ethcore::client::Executed;
 use util::Uint;
+use v1::helpers::response_limits::{ResponseSize, bytes_size};
 use v1::types::{Bytes, H160, H256, U256};
 
 #[derive(Debug, Serialize)]
@@ -252,6 +253,9 @@ pub enum CallType {
 	/// Delegate call
 	#[serde(rename="delegatecall")]
 	DelegateCall,
+	/// Static call
+	#[serde(rename="staticcall")]
+	StaticCall,
 }
 
 impl From<executed::CallType> for CallType {
@@ -261,6 +265,7 @@ impl From<executed::CallType> for CallType {
 			executed::CallType::Call => CallType::Call,
 			executed::CallType::CallCode => CallType::CallCode,
 			executed::CallType::DelegateCall => CallType::DelegateCall,
+			executed::CallType::StaticCall => CallType::StaticCall,
 		}
 	}
 }
@@ -482,6 +487,28 @@ impl From<EthLocalizedTrace> for LocalizedTrace {
 	}
 }
 
+impl ResponseSize for LocalizedTrace {
+	fn response_size(&self) -> usize {
+		// field names, addresses, hashes and numbers of a call trace
+		const OVERHEAD: usize = 600;
+		// a trace address entry and separating comma
+		const TRACE_ADDRESS_ENTRY_SIZE: usize = 4;
+
+		let action = match self.action {
+			Action::Call(ref call) => bytes_size(&call.input),
+			Action::Create(ref create) => bytes_size(&create.init),
+			Action::Suicide(_) => 0,
+		};
+		let result = match self.result {
+			Res::Call(ref call) => bytes_size(&call.output),
+			Res::Create(ref create) => bytes_size(&create.code),
+			_ => 0,
+		};
+
+		OVERHEAD + action + result + self.trace_address.len() * TRACE_ADDRESS_ENTRY_SIZE
+	}
+}
+
 /// Trace
 #[derive(Debug)]
 pub struct Trace {
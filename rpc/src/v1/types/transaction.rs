@@ -142,6 +142,59 @@ impl Serialize for LocalTransactionStatus {
 	}
 }
 
+/// Kind of transaction queue event.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum TransactionQueueEventKind {
+	/// Transaction was imported to the queue.
+	#[serde(rename="added")]
+	Added,
+	/// Transaction became ready to be included in a block.
+	#[serde(rename="promoted")]
+	Promoted,
+	/// Transaction nonce was used in the chain.
+	#[serde(rename="mined")]
+	Mined,
+	/// Transaction was dropped from the queue.
+	#[serde(rename="dropped")]
+	Dropped,
+	/// Transaction was replaced by another one with the same sender and nonce.
+	#[serde(rename="replaced")]
+	Replaced,
+}
+
+/// Reason a transaction was dropped from the queue.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub enum TransactionDropReason {
+	/// Queue limit was reached.
+	#[serde(rename="limit")]
+	Limit,
+	/// Transaction became invalid.
+	#[serde(rename="invalid")]
+	Invalid,
+	/// Transaction occupied the queue for too long.
+	#[serde(rename="expired")]
+	Expired,
+	/// Queue was cleared.
+	#[serde(rename="cleared")]
+	Cleared,
+}
+
+/// Transaction queue event.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct TransactionQueueEvent {
+	/// Kind of event
+	pub event: TransactionQueueEventKind,
+	/// Transaction hash
+	pub hash: H256,
+	/// Whether the transaction is ready to be included in a block (`added` events only)
+	pub pending: Option<bool>,
+	/// Why the transaction was dropped (`dropped` events only)
+	pub reason: Option<TransactionDropReason>,
+	/// Hash of the replacing transaction (`replaced` events only)
+	#[serde(rename="replacedBy")]
+	pub replaced_by: Option<H256>,
+}
+
 /// Geth-compatible output for eth_signTransaction method
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct RichRawTransaction {
@@ -253,9 +306,41 @@ impl From<miner::LocalTransactionStatus> for LocalTransactionStatus {
 	}
 }
 
+impl From<miner::TransactionEvent> for TransactionQueueEvent {
+	fn from(e: miner::TransactionEvent) -> Self {
+		use ethcore::miner::TransactionEvent::*;
+		let event = |kind, hash: ::util::H256| TransactionQueueEvent {
+			event: kind,
+			hash: hash.into(),
+			pending: None,
+			reason: None,
+			replaced_by: None,
+		};
+		match e {
+			Added(hash, pending) => TransactionQueueEvent { pending: Some(pending), ..event(TransactionQueueEventKind::Added, hash) },
+			Promoted(hash) => event(TransactionQueueEventKind::Promoted, hash),
+			Mined(hash) => event(TransactionQueueEventKind::Mined, hash),
+			Dropped(hash, reason) => TransactionQueueEvent { reason: Some(reason.into()), ..event(TransactionQueueEventKind::Dropped, hash) },
+			Replaced(hash, by) => TransactionQueueEvent { replaced_by: Some(by.into()), ..event(TransactionQueueEventKind::Replaced, hash) },
+		}
+	}
+}
+
+impl From<miner::DropReason> for TransactionDropReason {
+	fn from(r: miner::DropReason) -> Self {
+		match r {
+			miner::DropReason::Limit => TransactionDropReason::Limit,
+			miner::DropReason::Invalid => TransactionDropReason::Invalid,
+			miner::DropReason::Expired => TransactionDropReason::Expired,
+			miner::DropReason::Cleared => TransactionDropReason::Cleared,
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::{Transaction, LocalTransactionStatus};
+	use super::{Transaction, LocalTransactionStatus, TransactionQueueEvent};
+	use ethcore::miner::{TransactionEvent, DropReason};
 	use serde_json;
 
 	#[test]
@@ -309,5 +394,25 @@ mod tests {
 			r#","hash":"0x000000000000000000000000000000000000000000000000000000000000000a","gasPrice":"0x5"}"#
 		);
 	}
+
+	#[test]
+	fn test_transaction_queue_event_serialize() {
+		let added = TransactionQueueEvent::from(TransactionEvent::Added(1.into(), false));
+		let dropped = TransactionQueueEvent::from(TransactionEvent::Dropped(1.into(), DropReason::Expired));
+		let replaced = TransactionQueueEvent::from(TransactionEvent::Replaced(1.into(), 2.into()));
+
+		assert_eq!(
+			serde_json::to_string(&added).unwrap(),
+			r#"{"event":"added","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","pending":false,"reason":null,"replacedBy":null}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&dropped).unwrap(),
+			r#"{"event":"dropped","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","pending":null,"reason":"expired","replacedBy":null}"#
+		);
+		assert_eq!(
+			serde_json::to_string(&replaced).unwrap(),
+			r#"{"event":"replaced","hash":"0x0000000000000000000000000000000000000000000000000000000000000001","pending":null,"reason":null,"replacedBy":"0x0000000000000000000000000000000000000000000000000000000000000002"}"#
+		);
+	}
 }
 
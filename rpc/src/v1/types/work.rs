@@ -14,12 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{H256, U256};
+use super::{H256, H520, U256};
+use v1::helpers::work_tracker::WorkerSubmissions;
 
 use serde::{Serialize, Serializer};
 
 /// The result of an `eth_getWork` call: it differs based on an option
-/// whether to send the block number.
+/// whether to send the block number and whether work packages are signed.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Work {
 	/// The proof-of-work hash.
@@ -30,14 +31,71 @@ pub struct Work {
 	pub target: H256,
 	/// The block number: this isn't always stored.
 	pub number: Option<u64>,
+	/// Signature of the package, sent when worker authentication is enabled.
+	pub signature: Option<H520>,
 }
 
 impl Serialize for Work {
 	fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error> where S: Serializer {
-		match self.number.as_ref() {
-			Some(num) => (&self.pow_hash, &self.seed_hash, &self.target, U256::from(*num)).serialize(s),
-			None => (&self.pow_hash, &self.seed_hash, &self.target).serialize(s),
+		match (self.number.as_ref(), self.signature.as_ref()) {
+			(Some(num), Some(sig)) => (&self.pow_hash, &self.seed_hash, &self.target, U256::from(*num), sig).serialize(s),
+			(Some(num), None) => (&self.pow_hash, &self.seed_hash, &self.target, U256::from(*num)).serialize(s),
+			(None, Some(sig)) => (&self.pow_hash, &self.seed_hash, &self.target, sig).serialize(s),
+			(None, None) => (&self.pow_hash, &self.seed_hash, &self.target).serialize(s),
 		}
 	}
 }
 
+/// Identity of a mining worker submitting work.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkerCredentials {
+	/// Name of the worker.
+	pub worker: String,
+	/// Token of the worker.
+	pub token: H256,
+}
+
+/// Solutions submitted by a mining worker.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct WorkerStats {
+	/// Solutions accepted.
+	pub accepted: u64,
+	/// Solutions for superseded or already solved work.
+	pub stale: u64,
+	/// Solutions rejected otherwise.
+	pub rejected: u64,
+}
+
+impl From<WorkerSubmissions> for WorkerStats {
+	fn from(s: WorkerSubmissions) -> Self {
+		WorkerStats {
+			accepted: s.accepted,
+			stale: s.stale,
+			rejected: s.rejected,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use super::{Work, WorkerCredentials};
+
+	#[test]
+	fn work_serialization() {
+		let work = Work { pow_hash: 1.into(), seed_hash: 2.into(), target: 3.into(), number: Some(4), signature: Some(5.into()) };
+		let s = serde_json::to_string(&work).unwrap();
+		assert!(s.ends_with(r#","0x4","0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000005"]"#), "{}", s);
+
+		let work = Work { signature: None, ..work };
+		assert!(serde_json::to_string(&work).unwrap().ends_with(r#","0x4"]"#));
+	}
+
+	#[test]
+	fn credentials_deserialization() {
+		let s = r#"{"worker":"rig1","token":"0x0000000000000000000000000000000000000000000000000000000000000001"}"#;
+		let credentials: WorkerCredentials = serde_json::from_str(s).unwrap();
+		assert_eq!(credentials, WorkerCredentials { worker: "rig1".into(), token: 1.into() });
+	}
+}
@@ -183,6 +183,28 @@ impl<T: TimeProvider> AuthCodes<T> {
 		false
 	}
 
+	/// Checks if given hash is correct authcode and, if so, replaces that code with a new one,
+	/// so that every code authorizes a single connection.
+	/// Unlike `is_valid` the special initial token is never accepted.
+	pub fn consume(&mut self, hash: &H256, time: u64) -> io::Result<bool> {
+		let now = self.now.now();
+		// check time
+		if time >= now + TIME_THRESHOLD || time <= now - TIME_THRESHOLD {
+			warn!(target: "signer", "Received old authentication request. ({} vs {})", now, time);
+			return Ok(false);
+		}
+
+		let position = self.codes.iter().position(|code| &format!("{}:{}", code.code, time).sha3() == hash);
+		match position {
+			Some(position) => {
+				self.codes.remove(position);
+				self.generate_new()?;
+				Ok(true)
+			},
+			None => Ok(false),
+		}
+	}
+
 	/// Generates and returns a new code that can be used by `SignerUIs`
 	pub fn generate_new(&mut self) -> io::Result<String> {
 		let mut rng = OsRng::new()?;
@@ -298,6 +320,37 @@ mod tests {
 		assert_eq!(res2, false);
 	}
 
+	#[test]
+	fn should_consume_code_once() {
+		// given
+		let code = "23521352asdfasdfadf";
+		let time = 99;
+		let mut codes = AuthCodes::new(vec![code.into()], || 100);
+
+		// when
+		let res1 = codes.consume(&generate_hash(code, time), time).unwrap();
+		let res2 = codes.consume(&generate_hash(code, time), time).unwrap();
+
+		// then
+		assert_eq!(res1, true);
+		assert_eq!(res2, false);
+		assert_eq!(codes.codes.len(), 1);
+	}
+
+	#[test]
+	fn should_not_consume_initial_code() {
+		// given
+		let time = 99;
+		let mut codes = AuthCodes::new(vec![], || 100);
+
+		// when
+		let res = codes.consume(&generate_hash("initial", time), time).unwrap();
+
+		// then
+		assert_eq!(res, false);
+		assert!(codes.is_empty());
+	}
+
 	#[test]
 	fn should_read_old_format_from_file() {
 		// given
@@ -33,7 +33,7 @@ use rpc::informant::RpcStats;
 
 mod session;
 
-pub use self::session::MetaExtractor;
+pub use self::session::{MetaExtractor, Push};
 
 /// Signer startup error
 #[derive(Debug)]
@@ -63,6 +63,7 @@ pub struct ServerBuilder {
 	queue: Arc<ConfirmationsQueue>,
 	authcodes_path: PathBuf,
 	skip_origin_validation: bool,
+	one_time_tokens: bool,
 	stats: Option<Arc<RpcStats>>,
 }
 
@@ -73,6 +74,7 @@ impl ServerBuilder {
 			queue: queue,
 			authcodes_path: authcodes_path,
 			skip_origin_validation: false,
+			one_time_tokens: false,
 			stats: None,
 		}
 	}
@@ -84,6 +86,13 @@ impl ServerBuilder {
 		self
 	}
 
+	/// If set to `true` every authorization code is replaced with a new one
+	/// once it is used to open a connection.
+	pub fn one_time_tokens(mut self, one_time: bool) -> Self {
+		self.one_time_tokens = one_time;
+		self
+	}
+
 	/// Configure statistic collection
 	pub fn stats(mut self, stats: Arc<RpcStats>) -> Self {
 		self.stats = Some(stats);
@@ -117,6 +126,7 @@ impl ServerBuilder {
 			self.queue,
 			self.authcodes_path,
 			self.skip_origin_validation,
+			self.one_time_tokens,
 			self.stats,
 			meta_extractor,
 		)
@@ -148,6 +158,7 @@ impl Server {
 		queue: Arc<ConfirmationsQueue>,
 		authcodes_path: PathBuf,
 		skip_origin_validation: bool,
+		one_time_tokens: bool,
 		stats: Option<Arc<RpcStats>>,
 		meta_extractor: T,
 	) -> Result<Server, ServerError> {
@@ -164,7 +175,7 @@ impl Server {
 		let origin = format!("{}", addr);
 		let port = addr.port();
 		let ws = ws::Builder::new().with_settings(config).build(
-			session::Factory::new(handler, remote, origin, port, authcodes_path, skip_origin_validation, stats, meta_extractor)
+			session::Factory::new(handler, remote, origin, port, authcodes_path, skip_origin_validation, one_time_tokens, stats, meta_extractor)
 		)?;
 
 		let panic_handler = PanicHandler::new_in_arc();
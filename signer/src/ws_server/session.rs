@@ -18,6 +18,7 @@
 
 use std::path::{PathBuf, Path};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::str::FromStr;
 
 use authcode_store::AuthCodes;
@@ -80,7 +81,7 @@ fn origin_is_allowed(self_origin: &str, header: Option<&[u8]>) -> bool {
 	}
 }
 
-fn auth_token_hash(codes_path: &Path, protocols: ws::Result<Vec<&str>>) -> Option<H256> {
+fn auth_token_hash(codes_path: &Path, one_time: bool, protocols: ws::Result<Vec<&str>>) -> Option<H256> {
 	match protocols {
 		Ok(ref protocols) if protocols.len() == 1 => {
 			let protocol = protocols[0];
@@ -96,7 +97,11 @@ fn auth_token_hash(codes_path: &Path, protocols: ws::Result<Vec<&str>>) -> Optio
 						// remove old tokens
 						codes.clear_garbage();
 
-						let res = codes.is_valid(&auth, time);
+						let res = if one_time {
+							codes.consume(&auth, time).unwrap_or(false)
+						} else {
+							codes.is_valid(&auth, time)
+						};
 						// make sure to save back authcodes - it might have been modified
 						if codes.to_file(codes_path).is_err() {
 							warn!(target: "signer", "Couldn't save authorization codes to file.");
@@ -131,10 +136,24 @@ fn add_headers(mut response: ws::Response, mime: &str) -> ws::Response {
 	response
 }
 
+/// Sends messages to the client of a session.
+#[derive(Clone)]
+pub struct Push {
+	out: ws::Sender,
+	open: Arc<AtomicBool>,
+}
+
+impl Push {
+	/// Send a message to the client, returns `false` if the connection is already closed.
+	pub fn send(&self, message: String) -> bool {
+		self.open.load(Ordering::SeqCst) && self.out.send(message).is_ok()
+	}
+}
+
 /// Metadata extractor from session data.
 pub trait MetaExtractor<M: Metadata>: Send + Clone + 'static {
-	/// Extract metadata for given session
-	fn extract_metadata(&self, _session_id: &H256) -> M {
+	/// Extract metadata for given session. `push` allows sending notifications to the session.
+	fn extract_metadata(&self, _session_id: &H256, _push: Push) -> M {
 		Default::default()
 	}
 }
@@ -142,10 +161,12 @@ pub trait MetaExtractor<M: Metadata>: Send + Clone + 'static {
 pub struct Session<M: Metadata, S: Middleware<M>, T> {
 	session_id: H256,
 	out: ws::Sender,
+	open: Arc<AtomicBool>,
 	skip_origin_validation: bool,
 	self_origin: String,
 	self_port: u16,
 	authcodes_path: PathBuf,
+	one_time_tokens: bool,
 	handler: Arc<MetaIoHandler<M, S>>,
 	remote: Remote,
 	file_handler: Arc<ui::Handler>,
@@ -155,6 +176,7 @@ pub struct Session<M: Metadata, S: Middleware<M>, T> {
 
 impl<M: Metadata, S: Middleware<M>, T> Drop for Session<M, S, T> {
 	fn drop(&mut self) {
+		self.open.store(false, Ordering::SeqCst);
 		self.stats.as_ref().map(|stats| stats.close_session());
 	}
 }
@@ -202,7 +224,7 @@ impl<M: Metadata, S: Middleware<M>, T: MetaExtractor<M>> ws::Handler for Session
 		// (styles file skips origin validation, so make sure to prevent WS connections on this resource)
 		if req.header("sec-websocket-key").is_some() && !is_styles_file {
 			// Check authorization
-			let auth_token_hash = auth_token_hash(&self.authcodes_path, req.protocols());
+			let auth_token_hash = auth_token_hash(&self.authcodes_path, self.one_time_tokens, req.protocols());
 			match auth_token_hash {
 				None => {
 					info!(target: "signer", "Unauthorized connection to Signer API blocked.");
@@ -237,7 +259,11 @@ impl<M: Metadata, S: Middleware<M>, T: MetaExtractor<M>> ws::Handler for Session
 		let req = msg.as_text()?;
 		let out = self.out.clone();
 		// TODO [ToDr] Move to on_connect
-		let metadata = self.meta_extractor.extract_metadata(&self.session_id);
+		let push = Push {
+			out: self.out.clone(),
+			open: self.open.clone(),
+		};
+		let metadata = self.meta_extractor.extract_metadata(&self.session_id, push);
 
 		let future = self.handler.handle_request(req, metadata).map(move |response| {
 			if let Some(result) = response {
@@ -259,6 +285,7 @@ pub struct Factory<M: Metadata, S: Middleware<M>, T> {
 	self_origin: String,
 	self_port: u16,
 	authcodes_path: PathBuf,
+	one_time_tokens: bool,
 	meta_extractor: T,
 	file_handler: Arc<ui::Handler>,
 	stats: Option<Arc<RpcStats>>,
@@ -272,6 +299,7 @@ impl<M: Metadata, S: Middleware<M>, T> Factory<M, S, T> {
 		self_port: u16,
 		authcodes_path: PathBuf,
 		skip_origin_validation: bool,
+		one_time_tokens: bool,
 		stats: Option<Arc<RpcStats>>,
 		meta_extractor: T,
 	) -> Self {
@@ -282,6 +310,7 @@ impl<M: Metadata, S: Middleware<M>, T> Factory<M, S, T> {
 			self_origin: self_origin,
 			self_port: self_port,
 			authcodes_path: authcodes_path,
+			one_time_tokens: one_time_tokens,
 			meta_extractor: meta_extractor,
 			file_handler: Arc::new(ui::Handler::default()),
 			stats: stats,
@@ -298,12 +327,14 @@ impl<M: Metadata, S: Middleware<M>, T: MetaExtractor<M>> ws::Factory for Factory
 		Session {
 			session_id: 0.into(),
 			out: sender,
+			open: Arc::new(AtomicBool::new(true)),
 			handler: self.handler.clone(),
 			remote: self.remote.clone(),
 			skip_origin_validation: self.skip_origin_validation,
 			self_origin: self.self_origin.clone(),
 			self_port: self.self_port,
 			authcodes_path: self.authcodes_path.clone(),
+			one_time_tokens: self.one_time_tokens,
 			meta_extractor: self.meta_extractor.clone(),
 			file_handler: self.file_handler.clone(),
 			stats: self.stats.clone(),
@@ -33,7 +33,7 @@ use std::net::{SocketAddr, AddrParseError};
 use ipc::{BinaryConvertable, BinaryConvertError, IpcConfig};
 use std::str::FromStr;
 use parking_lot::RwLock;
-use chain::{ETH_PACKET_COUNT, SNAPSHOT_SYNC_PACKET_COUNT};
+use chain::{ETH_PACKET_COUNT, SNAPSHOT_SYNC_PACKET_COUNT, BLOCK_ANNOUNCE_DELAY_MS};
 use light::client::AsLightClient;
 use light::Provider;
 use light::net::{self as light_net, LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext};
@@ -64,6 +64,14 @@ pub struct SyncConfig {
 	pub warp_sync: bool,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Rotate peers if best block doesn't advance for this many seconds
+	/// while peers report higher heads.
+	pub head_stall_timeout: Option<u64>,
+	/// Number of block body or receipt batches requested from a peer at once.
+	/// Tuned for each peer from its response times and delivery rate if `None`.
+	pub request_parallelism: Option<usize>,
+	/// Check this many blocks below the best block for missing receipts and download them.
+	pub ancient_receipts_depth: Option<u64>,
 }
 
 impl Default for SyncConfig {
@@ -77,6 +85,9 @@ impl Default for SyncConfig {
 			fork_block: None,
 			warp_sync: false,
 			serve_light: false,
+			head_stall_timeout: Some(120),
+			request_parallelism: None,
+			ancient_receipts_depth: None,
 		}
 	}
 }
@@ -97,6 +108,12 @@ pub trait SyncProvider: Send + Sync {
 
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
+
+	/// Returns how recent local transactions and locally sealed blocks were propagated, latest first.
+	fn propagation_stats(&self) -> Vec<PropagationStats>;
+
+	/// Returns progress of the download of missing receipts, if enabled.
+	fn receipts_backfill_status(&self) -> Option<ReceiptsBackfillStatus>;
 }
 
 /// Transaction stats
@@ -109,6 +126,52 @@ pub struct TransactionStats {
 	pub propagated_to: BTreeMap<H512, usize>,
 }
 
+/// Kind of an item propagated to peers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub enum PropagationKind {
+	/// Local transaction.
+	Transaction,
+	/// Locally sealed block.
+	Block,
+}
+
+/// Progress of the download of receipts missing for blocks in the chain.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct ReceiptsBackfillStatus {
+	/// Best block number when the backfill started.
+	pub start_block: u64,
+	/// Lowest block number to check.
+	pub target_block: u64,
+	/// Next block number to check, `None` once all are checked.
+	pub next_block: Option<u64>,
+	/// Number of blocks receipts were imported for.
+	pub imported: u64,
+	/// Number of blocks missing receipts waiting for download.
+	pub pending: usize,
+}
+
+/// Propagation of a local transaction or a locally sealed block.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ipc", derive(Binary))]
+pub struct PropagationStats {
+	/// Hash of the transaction or block.
+	pub hash: H256,
+	/// Whether it's a transaction or a block.
+	pub kind: PropagationKind,
+	/// Best block number when it was first propagated.
+	pub block_number: u64,
+	/// Unix time it was first sent.
+	pub first_propagated: u64,
+	/// Unix time it was last sent or announced.
+	pub last_propagated: u64,
+	/// Number of peers it was sent to.
+	pub sent_to: usize,
+	/// Number of peers its hash was announced to.
+	pub announced_to: usize,
+}
+
 /// Peer connection information
 #[derive(Debug)]
 #[cfg_attr(feature = "ipc", derive(Binary))]
@@ -281,8 +344,19 @@ impl SyncProvider for EthSync {
 			.map(|(hash, stats)| (*hash, stats.into()))
 			.collect()
 	}
+
+	fn propagation_stats(&self) -> Vec<PropagationStats> {
+		self.eth_handler.sync.read().propagation_stats()
+	}
+
+	fn receipts_backfill_status(&self) -> Option<ReceiptsBackfillStatus> {
+		self.eth_handler.sync.read().receipts_backfill_status()
+	}
 }
 
+const MAINTAIN_SYNC_TIMER: TimerToken = 0;
+const ANNOUNCE_TIMER: TimerToken = 1;
+
 struct SyncProtocolHandler {
 	/// Shared blockchain client.
 	chain: Arc<BlockChainClient>,
@@ -297,7 +371,8 @@ struct SyncProtocolHandler {
 impl NetworkProtocolHandler for SyncProtocolHandler {
 	fn initialize(&self, io: &NetworkContext) {
 		if io.subprotocol_name() != WARP_SYNC_PROTOCOL_ID {
-			io.register_timer(0, 1000).expect("Error registering sync timer");
+			io.register_timer(MAINTAIN_SYNC_TIMER, 1000).expect("Error registering sync timer");
+			io.register_timer(ANNOUNCE_TIMER, BLOCK_ANNOUNCE_DELAY_MS).expect("Error registering announcement timer");
 		}
 	}
 
@@ -320,10 +395,17 @@ impl NetworkProtocolHandler for SyncProtocolHandler {
 		}
 	}
 
-	fn timeout(&self, io: &NetworkContext, _timer: TimerToken) {
-		self.sync.write().maintain_peers(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
-		self.sync.write().maintain_sync(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
-		self.sync.write().propagate_new_transactions(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
+	fn timeout(&self, io: &NetworkContext, timer: TimerToken) {
+		match timer {
+			ANNOUNCE_TIMER => {
+				self.sync.write().propagate_delayed_announcements(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
+			},
+			_ => {
+				self.sync.write().maintain_peers(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
+				self.sync.write().maintain_sync(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
+				self.sync.write().propagate_new_transactions(&mut NetSyncIo::new(io, &*self.chain, &*self.snapshot_service, &self.overlay));
+			}
+		}
 	}
 }
 
@@ -438,6 +520,8 @@ pub trait ManageNetwork : Send + Sync {
 	fn stop_network(&self);
 	/// Query the current configuration of the network
 	fn network_config(&self) -> NetworkConfiguration;
+	/// Change the address advertised to other nodes, given as `ip:port`
+	fn set_public_address(&self, address: String) -> Result<(), String>;
 }
 
 
@@ -479,6 +563,12 @@ impl ManageNetwork for EthSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn set_public_address(&self, address: String) -> Result<(), String> {
+		let address = address.parse().map_err(|_| format!("Invalid socket address: {}", address))?;
+		self.network.set_public_address(address);
+		Ok(())
+	}
 }
 
 /// IP fiter
@@ -713,6 +803,11 @@ impl LightSync {
 			move |ctx| self.proto.with_context(ctx, f),
 		)
 	}
+
+	/// Get the estimated current and maximum request credits we have with a peer.
+	pub fn remote_credits(&self, peer: &PeerId) -> Option<(U256, U256)> {
+		self.proto.remote_credits(peer)
+	}
 }
 
 impl ManageNetwork for LightSync {
@@ -755,6 +850,12 @@ impl ManageNetwork for LightSync {
 	fn network_config(&self) -> NetworkConfiguration {
 		NetworkConfiguration::from(self.network.config().clone())
 	}
+
+	fn set_public_address(&self, address: String) -> Result<(), String> {
+		let address = address.parse().map_err(|_| format!("Invalid socket address: {}", address))?;
+		self.network.set_public_address(address);
+		Ok(())
+	}
 }
 
 impl LightSyncProvider for LightSync {
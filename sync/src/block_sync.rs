@@ -29,8 +29,10 @@ use sync_io::SyncIo;
 use blocks::BlockCollection;
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
-const MAX_BODIES_TO_REQUEST: usize = 64;
-const MAX_RECEPITS_TO_REQUEST: usize = 128;
+/// Block bodies requested per unit of request parallelism.
+pub const MAX_BODIES_TO_REQUEST: usize = 64;
+/// Block receipts requested per unit of request parallelism.
+pub const MAX_RECEPITS_TO_REQUEST: usize = 128;
 const SUBCHAIN_SIZE: u64 = 256;
 const MAX_ROUND_PARENTS: usize = 16;
 const MAX_PARALLEL_SUBCHAIN_DOWNLOAD: usize = 5;
@@ -411,13 +413,14 @@ impl BlockDownloader {
 		self.imported_this_round = None;
 	}
 
-	/// Find some headers or blocks to download for a peer.
-	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize) -> Option<BlockRequest> {
+	/// Find some headers or blocks to download for a peer, asking for `parallelism`
+	/// batches of bodies or receipts at once.
+	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize, parallelism: usize) -> Option<BlockRequest> {
 		match self.state {
 			State::Idle => {
 				self.start_sync_round(io);
 				if self.state == State::ChainHead {
-					return self.request_blocks(io, num_active_peers);
+					return self.request_blocks(io, num_active_peers, parallelism);
 				}
 			},
 			State::ChainHead => {
@@ -435,7 +438,7 @@ impl BlockDownloader {
 			},
 			State::Blocks => {
 				// check to see if we need to download any block bodies first
-				let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, false);
+				let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST * parallelism, false);
 				if !needed_bodies.is_empty() {
 					return Some(BlockRequest::Bodies {
 						hashes: needed_bodies,
@@ -443,7 +446,7 @@ impl BlockDownloader {
 				}
 
 				if self.download_receipts {
-					let needed_receipts = self.blocks.needed_receipts(MAX_RECEPITS_TO_REQUEST, false);
+					let needed_receipts = self.blocks.needed_receipts(MAX_RECEPITS_TO_REQUEST * parallelism, false);
 					if !needed_receipts.is_empty() {
 						return Some(BlockRequest::Receipts {
 							hashes: needed_receipts,
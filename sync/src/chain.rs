@@ -101,10 +101,15 @@ use sync_io::SyncIo;
 use time;
 use super::SyncConfig;
 use block_sync::{BlockDownloader, BlockRequest, BlockDownloaderImportError as DownloaderImportError, DownloadAction};
+use block_sync::{MAX_BODIES_TO_REQUEST, MAX_RECEPITS_TO_REQUEST};
 use rand::Rng;
 use snapshot::{Snapshot, ChunkType};
-use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
+use api::{EthProtocolInfo as PeerInfoDigest, PropagationStats, PropagationKind, ReceiptsBackfillStatus, WARP_SYNC_PROTOCOL_ID};
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use propagation_stats::PropagationHistory;
+use receipts_backfill::ReceiptsBackfill;
+use known_transactions::KnownTransactions;
+use request_parallelism::RequestParallelism;
 
 known_heap_size!(0, PeerInfo);
 
@@ -122,6 +127,8 @@ const MAX_RECEIPTS_HEADERS_TO_SEND: usize = 256;
 const MIN_PEERS_PROPAGATION: usize = 4;
 const MAX_PEERS_PROPAGATION: usize = 128;
 const MAX_PEER_LAG_PROPAGATION: BlockNumber = 20;
+/// Delay before announcing new blocks to peers which didn't get them pushed right away.
+pub const BLOCK_ANNOUNCE_DELAY_MS: u64 = 250;
 const MAX_NEW_HASHES: usize = 64;
 const MAX_TX_TO_IMPORT: usize = 512;
 const MAX_NEW_BLOCK_AGE: BlockNumber = 20;
@@ -219,6 +226,8 @@ pub struct SyncStatus {
 	pub snapshot_chunks_done: usize,
 	/// Last fully downloaded and imported ancient block number (if any).
 	pub last_imported_old_block_number: Option<BlockNumber>,
+	/// Number of times peers were rotated because the chain head stalled.
+	pub num_head_stall_rotations: usize,
 }
 
 impl SyncStatus {
@@ -265,6 +274,8 @@ enum BlockSet {
 	NewBlocks,
 	/// Missing old blocks
 	OldBlocks,
+	/// Receipts missing for blocks in the chain
+	AncientReceipts,
 }
 #[derive(Clone, Eq, PartialEq)]
 enum ForkConfirmation {
@@ -301,6 +312,8 @@ struct PeerInfo {
 	ask_time: u64,
 	/// Holds a set of transactions recently sent to this peer to avoid spamming.
 	last_sent_transactions: HashSet<H256>,
+	/// Transactions the peer is known to have, to avoid sending them again.
+	known_transactions: KnownTransactions,
 	/// Pending request is expired and result should be ignored
 	expired: bool,
 	/// Peer fork confirmation status
@@ -311,6 +324,8 @@ struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Number of block data batches to request at once
+	request_parallelism: RequestParallelism,
 }
 
 impl PeerInfo {
@@ -360,6 +375,8 @@ pub struct ChainSync {
 	new_blocks: BlockDownloader,
 	/// Block download process for ancient blocks
 	old_blocks: Option<BlockDownloader>,
+	/// Download of receipts missing for blocks in the chain
+	receipts_backfill: Option<ReceiptsBackfill>,
 	/// Last propagated block number
 	last_sent_block_number: BlockNumber,
 	/// Network ID
@@ -375,8 +392,22 @@ pub struct ChainSync {
 	sync_start_time: Option<u64>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Propagation history of local transactions and sealed blocks
+	propagation_history: PropagationHistory,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
+	/// Number of blocks below the best block to check for missing receipts
+	ancient_receipts_depth: Option<u64>,
+	/// Rotate peers if best block doesn't advance for this many seconds while peers are ahead.
+	head_stall_timeout: Option<u64>,
+	/// Configured request parallelism; tuned per peer if `None`.
+	request_parallelism: Option<usize>,
+	/// Best block number and timestamp it was first seen at.
+	head_stall_since: (BlockNumber, u64),
+	/// Number of peer rotations triggered by the chain head watchdog.
+	head_stall_rotations: usize,
+	/// Deadline and peers to announce the best block to once it passes.
+	delayed_announcement: Option<(u64, Vec<PeerId>)>,
 }
 
 type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
@@ -394,13 +425,21 @@ impl ChainSync {
 			active_peers: HashSet::new(),
 			new_blocks: BlockDownloader::new(false, &chain_info.best_block_hash, chain_info.best_block_number),
 			old_blocks: None,
+			receipts_backfill: None,
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
 			download_old_blocks: config.download_old_blocks,
+			ancient_receipts_depth: config.ancient_receipts_depth,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			propagation_history: PropagationHistory::default(),
+			head_stall_timeout: config.head_stall_timeout,
+			request_parallelism: config.request_parallelism,
+			head_stall_since: (chain_info.best_block_number, time::precise_time_ns()),
+			head_stall_rotations: 0,
+			delayed_announcement: None,
 		};
 		sync.update_targets(chain);
 		sync
@@ -427,6 +466,7 @@ impl ChainSync {
 				self.new_blocks.heap_size()
 				+ self.old_blocks.as_ref().map_or(0, |d| d.heap_size())
 				+ self.peers.heap_size_of_children(),
+			num_head_stall_rotations: self.head_stall_rotations,
 		}
 	}
 
@@ -446,9 +486,22 @@ impl ChainSync {
 		self.transactions_stats.stats()
 	}
 
+	/// Returns propagation history of local transactions and sealed blocks, latest first
+	pub fn propagation_stats(&self) -> Vec<PropagationStats> {
+		self.propagation_history.stats()
+	}
+
+	/// Returns progress of the download of missing receipts, if enabled
+	pub fn receipts_backfill_status(&self) -> Option<ReceiptsBackfillStatus> {
+		self.receipts_backfill.as_ref().map(|backfill| backfill.status())
+	}
+
 	/// Updates transactions were received by a peer
 	pub fn transactions_received(&mut self, hashes: Vec<H256>, peer_id: PeerId) {
 		if let Some(mut peer_info) = self.peers.get_mut(&peer_id) {
+			for hash in &hashes {
+				peer_info.known_transactions.insert(hash);
+			}
 			peer_info.last_sent_transactions.extend(&hashes);
 		}
 	}
@@ -470,7 +523,7 @@ impl ChainSync {
 			io.snapshot_service().abort_restore();
 		}
 		for (_, ref mut p) in &mut self.peers {
-			if p.block_set != Some(BlockSet::OldBlocks) {
+			if p.block_set != Some(BlockSet::OldBlocks) && p.block_set != Some(BlockSet::AncientReceipts) {
 				p.reset_asking();
 				if p.difficulty.is_none() {
 					// assume peer has up to date difficulty
@@ -582,6 +635,10 @@ impl ChainSync {
 				self.old_blocks = Some(downloader);
 			}
 		}
+		self.receipts_backfill = self.ancient_receipts_depth.map(|depth| {
+			trace!(target: "sync", "Checking receipts of blocks #{} to #{}", chain.best_block_number.saturating_sub(depth), chain.best_block_number);
+			ReceiptsBackfill::new(chain.best_block_number, depth)
+		});
 	}
 
 	/// Called by peer to report status
@@ -600,12 +657,14 @@ impl ChainSync {
 			asking_hash: None,
 			ask_time: 0,
 			last_sent_transactions: HashSet::new(),
+			known_transactions: KnownTransactions::default(),
 			expired: false,
 			confirmation: if self.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
+			request_parallelism: RequestParallelism::new(self.request_parallelism),
 		};
 
 		if self.sync_start_time.is_none() {
@@ -712,6 +771,11 @@ impl ChainSync {
 		let result =  {
 			let mut downloader = match block_set {
 				BlockSet::NewBlocks => &mut self.new_blocks,
+				BlockSet::AncientReceipts => {
+					trace!(target: "sync", "Ignored unexpected block headers");
+					self.continue_sync(io);
+					return Ok(());
+				},
 				BlockSet::OldBlocks => {
 					match self.old_blocks {
 						None => {
@@ -757,6 +821,7 @@ impl ChainSync {
 
 	/// Called by peer once it has new block bodies
 	fn on_peer_block_bodies(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.note_response(peer_id, PeerAsking::BlockBodies, r.item_count().unwrap_or(0), MAX_BODIES_TO_REQUEST);
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockBodies) {
@@ -784,7 +849,12 @@ impl ChainSync {
 							return Ok(());
 						},
 						Some(ref mut blocks) => blocks,
-					}
+					},
+					BlockSet::AncientReceipts => {
+						trace!(target: "sync", "Ignored unexpected block bodies");
+						self.continue_sync(io);
+						return Ok(());
+					},
 				};
 				downloader.import_bodies(io, r)
 			};
@@ -811,8 +881,10 @@ impl ChainSync {
 
 	/// Called by peer once it has new block receipts
 	fn on_peer_block_receipts(&mut self, io: &mut SyncIo, peer_id: PeerId, r: &UntrustedRlp) -> Result<(), PacketDecodeError> {
+		self.note_response(peer_id, PeerAsking::BlockReceipts, r.item_count().unwrap_or(0), MAX_RECEPITS_TO_REQUEST);
 		self.clear_peer_download(peer_id);
 		let block_set = self.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
+		let asked = self.peers.get(&peer_id).map_or_else(Vec::new, |p| p.asking_blocks.clone());
 		if !self.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) {
 			trace!(target: "sync", "{}: Ignored unexpected receipts", peer_id);
 			self.continue_sync(io);
@@ -826,6 +898,21 @@ impl ChainSync {
 		else if self.state == SyncState::Waiting {
 			trace!(target: "sync", "Ignored block receipts while waiting");
 		}
+		else if block_set == BlockSet::AncientReceipts {
+			let result = match self.receipts_backfill {
+				Some(ref mut backfill) => backfill.import(io.chain(), &asked, r),
+				None => {
+					trace!(target: "sync", "Ignored block receipts while receipts backfill is inactive");
+					Ok(())
+				},
+			};
+			if result == Err(DownloaderImportError::Invalid) {
+				io.disable_peer(peer_id);
+				self.deactivate_peer(io, peer_id);
+			} else {
+				self.sync_peer(io, peer_id, false);
+			}
+		}
 		else
 		{
 			let result = {
@@ -838,7 +925,11 @@ impl ChainSync {
 							return Ok(());
 						},
 						Some(ref mut blocks) => blocks,
-					}
+					},
+					BlockSet::AncientReceipts => {
+						self.continue_sync(io);
+						return Ok(());
+					},
 				};
 				downloader.import_receipts(io, r)
 			};
@@ -1030,6 +1121,7 @@ impl ChainSync {
 			Ok(manifest) => manifest,
 		};
 		self.snapshot.reset_to(&manifest, &manifest_rlp.as_raw().sha3());
+		self.snapshot.note_completed(io.snapshot_service().completed_chunks(manifest.clone()));
 		io.snapshot_service().begin_restore(manifest);
 		self.state = SyncState::SnapshotData;
 
@@ -1122,24 +1214,34 @@ impl ChainSync {
 
 	/// Resume downloading
 	fn continue_sync(&mut self, io: &mut SyncIo) {
-		let mut peers: Vec<(PeerId, U256, u8)> = self.peers.iter().filter_map(|(k, p)|
-			if p.can_sync() { Some((*k, p.difficulty.unwrap_or_else(U256::zero), p.protocol_version)) } else { None }).collect();
-		random::new().shuffle(&mut peers); //TODO: sort by rating
-		// prefer peers with higher protocol version
-		peers.sort_by(|&(_, _, ref v1), &(_, _, ref v2)| v1.cmp(v2));
+		let peers = self.peers_to_sync(io.chain());
 		trace!(target: "sync", "Syncing with peers: {} active, {} confirmed, {} total", self.active_peers.len(), peers.len(), self.peers.len());
-		for (p, _, _) in peers {
+		for p in peers {
 			if self.active_peers.contains(&p) {
 				self.sync_peer(io, p, false);
 			}
 		}
 		if (self.state != SyncState::WaitingPeers && self.state != SyncState::SnapshotWaiting && self.state != SyncState::Waiting && self.state != SyncState::Idle)
-			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.block_set != Some(BlockSet::OldBlocks) && p.can_sync()) {
+			&& !self.peers.values().any(|p| p.asking != PeerAsking::Nothing && p.block_set != Some(BlockSet::OldBlocks) && p.block_set != Some(BlockSet::AncientReceipts) && p.can_sync()) {
 
 			self.complete_sync(io);
 		}
 	}
 
+	/// Confirmed peers in the order they should be given work.
+	fn peers_to_sync(&self, chain: &BlockChainClient) -> Vec<PeerId> {
+		let mut peers: Vec<(PeerId, u8, bool)> = self.peers.iter().filter_map(|(k, p)| match p.can_sync() {
+			true => Some((*k, p.protocol_version, chain.block_status(BlockId::Hash(p.latest_hash.clone())) == BlockStatus::Bad)),
+			false => None,
+		}).collect();
+		random::new().shuffle(&mut peers); //TODO: sort by rating
+		// prefer peers with higher protocol version
+		peers.sort_by(|&(_, ref v1, _), &(_, ref v2, _)| v1.cmp(v2));
+		// peers advertising a known bad chain go last
+		peers.sort_by_key(|&(_, _, bad)| bad);
+		peers.into_iter().map(|(p, _, _)| p).collect()
+	}
+
 	/// Called after all blocks have been downloaded
 	fn complete_sync(&mut self, io: &mut SyncIo) {
 		trace!(target: "sync", "Sync complete");
@@ -1159,7 +1261,7 @@ impl ChainSync {
 			trace!(target: "sync", "Skipping deactivated peer {}", peer_id);
 			return;
 		}
-		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash) = {
+		let (peer_latest, peer_difficulty, peer_snapshot_number, peer_snapshot_hash, parallelism) = {
 			if let Some(peer) = self.peers.get_mut(&peer_id) {
 				if peer.asking != PeerAsking::Nothing || !peer.can_sync() {
 					trace!(target: "sync", "Skipping busy peer {}", peer_id);
@@ -1173,7 +1275,7 @@ impl ChainSync {
 					trace!(target: "sync", "Waiting for the snapshot restoration");
 					return;
 				}
-				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned())
+				(peer.latest_hash.clone(), peer.difficulty.clone(), peer.snapshot_number.as_ref().cloned().unwrap_or(0), peer.snapshot_hash.as_ref().cloned(), peer.request_parallelism.get())
 			} else {
 				return;
 			}
@@ -1183,7 +1285,8 @@ impl ChainSync {
 		let num_active_peers = self.peers.values().filter(|p| p.asking != PeerAsking::Nothing).count();
 
 		let higher_difficulty = peer_difficulty.map_or(true, |pd| pd > syncing_difficulty);
-		if force || higher_difficulty || self.old_blocks.is_some() {
+		let backfilling = self.receipts_backfill.as_ref().map_or(false, |backfill| !backfill.is_complete());
+		if force || higher_difficulty || self.old_blocks.is_some() || backfilling {
 			match self.state {
 				SyncState::WaitingPeers => {
 					trace!(target: "sync", "Checking snapshot sync: {} vs {}", peer_snapshot_number, chain_info.best_block_number);
@@ -1200,7 +1303,7 @@ impl ChainSync {
 					if !have_latest && (higher_difficulty || force || self.state == SyncState::NewBlocks) {
 						// check if got new blocks to download
 						trace!(target: "sync", "Syncing with peer {}, force={}, td={:?}, our td={}, state={:?}", peer_id, force, peer_difficulty, syncing_difficulty, self.state);
-						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers) {
+						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers, parallelism) {
 							self.request_blocks(io, peer_id, request, BlockSet::NewBlocks);
 							if self.state == SyncState::Idle {
 								self.state = SyncState::Blocks;
@@ -1209,14 +1312,26 @@ impl ChainSync {
 						}
 					}
 
-					if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers)) {
+					if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers, parallelism)) {
 						self.request_blocks(io, peer_id, request, BlockSet::OldBlocks);
 						return;
 					}
+
+					let can_serve_receipts = self.peers.get(&peer_id).map_or(false, |p| p.protocol_version >= PROTOCOL_VERSION_63);
+					if can_serve_receipts {
+						let hashes = match self.receipts_backfill {
+							Some(ref mut backfill) => backfill.request(io.chain(), MAX_RECEPITS_TO_REQUEST),
+							None => Vec::new(),
+						};
+						if !hashes.is_empty() {
+							self.request_receipts(io, peer_id, hashes, BlockSet::AncientReceipts);
+							return;
+						}
+					}
 				},
 				SyncState::SnapshotData => {
 					if let RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } = io.snapshot_service().status() {
-						if self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+						if self.snapshot.done_chunks().saturating_sub((state_chunks_done + block_chunks_done) as usize) > MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
 							trace!(target: "sync", "Snapshot queue full, pausing sync");
 							self.state = SyncState::SnapshotWaiting;
 							return;
@@ -1284,6 +1399,9 @@ impl ChainSync {
 					if let Some(ref mut old) = self.old_blocks {
 						old.clear_receipt_download(&peer.asking_blocks);
 					}
+					if let Some(ref mut backfill) = self.receipts_backfill {
+						backfill.clear_download(&peer.asking_blocks);
+					}
 				},
 				PeerAsking::SnapshotData => {
 					if let Some(hash) = peer.asking_snapshot_data {
@@ -1311,7 +1429,8 @@ impl ChainSync {
 					trace!(target: "sync", "Background block download is complete");
 					self.old_blocks = None;
 				}
-			}
+			},
+			BlockSet::AncientReceipts => (),
 		}
 	}
 
@@ -1383,6 +1502,19 @@ impl ChainSync {
 		peer.block_set = Some(set);
 	}
 
+	/// Feed the response time and size of a block data request into the peer's parallelism tuning.
+	fn note_response(&mut self, peer_id: PeerId, asking: PeerAsking, delivered: usize, batch_size: usize) {
+		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
+			if peer.asking != asking || peer.expired {
+				return;
+			}
+			let elapsed_ms = time::precise_time_ns().saturating_sub(peer.ask_time) / 1_000_000;
+			let requested = peer.asking_blocks.len();
+			peer.request_parallelism.on_response(requested, delivered, batch_size, elapsed_ms);
+			trace!(target: "sync", "{}: Request parallelism {}, response time {}ms", peer_id, peer.request_parallelism.get(), elapsed_ms);
+		}
+	}
+
 	/// Reset peer status after request is complete.
 	fn reset_peer_asking(&mut self, peer_id: PeerId, asking: PeerAsking) -> bool {
 		if let Some(ref mut peer) = self.peers.get_mut(&peer_id) {
@@ -1450,6 +1582,9 @@ impl ChainSync {
 				continue;
 			}
 			let tx = rlp.as_raw().to_vec();
+			if let Some(peer_info) = self.peers.get_mut(&peer_id) {
+				peer_info.known_transactions.insert(&tx.sha3());
+			}
 			transactions.push(tx);
 		}
 		io.chain().queue_transactions(transactions, peer_id);
@@ -1771,6 +1906,9 @@ impl ChainSync {
 		for p in aborting {
 			self.on_peer_aborting(io, p);
 		}
+		for peer in self.peers.values_mut() {
+			peer.known_transactions.maintain();
+		}
 
 		// Check for handshake timeouts
 		for (peer, ask_time) in &self.handshaking_peers {
@@ -1794,7 +1932,7 @@ impl ChainSync {
 					self.continue_sync(io);
 				},
 				RestorationStatus::Ongoing { state_chunks_done, block_chunks_done, .. } => {
-					if !self.snapshot.is_complete() && self.snapshot.done_chunks() - (state_chunks_done + block_chunks_done) as usize <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
+					if !self.snapshot.is_complete() && self.snapshot.done_chunks().saturating_sub((state_chunks_done + block_chunks_done) as usize) <= MAX_SNAPSHOT_CHUNKS_DOWNLOAD_AHEAD {
 						trace!(target:"sync", "Resuming snapshot sync");
 						self.state = SyncState::SnapshotData;
 						self.continue_sync(io);
@@ -1950,6 +2088,7 @@ impl ChainSync {
 			return 0;
 		}
 
+		let local_transactions = io.chain().local_transaction_hashes().into_iter().collect::<HashSet<_>>();
 		let (transactions, service_transactions): (Vec<_>, Vec<_>) = transactions.into_iter()
 			.partition(|tx| !tx.transaction.gas_price.is_zero());
 
@@ -1957,14 +2096,14 @@ impl ChainSync {
 		let mut affected_peers = HashSet::new();
 		if !transactions.is_empty() {
 			let peers = self.select_peers_for_transactions(|_| true);
-			affected_peers = self.propagate_transactions_to_peers(io, peers, transactions);
+			affected_peers = self.propagate_transactions_to_peers(io, peers, transactions, &local_transactions);
 		}
 
 		// most of times service_transactions will be empty
 		// => there's no need to merge packets
 		if !service_transactions.is_empty() {
 			let service_transactions_peers = self.select_peers_for_transactions(|peer_id| accepts_service_transaction(&io.peer_info(*peer_id)));
-			let service_transactions_affected_peers = self.propagate_transactions_to_peers(io, service_transactions_peers, service_transactions);
+			let service_transactions_affected_peers = self.propagate_transactions_to_peers(io, service_transactions_peers, service_transactions, &local_transactions);
 			affected_peers.extend(&service_transactions_affected_peers);
 		}
 
@@ -1986,7 +2125,7 @@ impl ChainSync {
 			.collect()
 	}
 
-	fn propagate_transactions_to_peers(&mut self, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<PendingTransaction>) -> HashSet<PeerId> {
+	fn propagate_transactions_to_peers(&mut self, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<PendingTransaction>, local_transactions: &HashSet<H256>) -> HashSet<PeerId> {
 		let all_transactions_hashes = transactions.iter()
 			.map(|tx| tx.transaction.hash())
 			.collect::<HashSet<H256>>();
@@ -2001,20 +2140,26 @@ impl ChainSync {
 
 		// sqrt(x)/x scaled to max u32
 		let block_number = io.chain().chain_info().best_block_number;
+		let now = time::get_time().sec as u64;
 
 		let lucky_peers = {
 			peers.into_iter()
 				.filter_map(|peer_id| {
 					let stats = &mut self.transactions_stats;
+					let history = &mut self.propagation_history;
 					let peer_info = self.peers.get_mut(&peer_id)
 						.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
 
 					// Send all transactions
-					if peer_info.last_sent_transactions.is_empty() {
+					if peer_info.last_sent_transactions.is_empty() && !all_transactions_hashes.iter().any(|h| peer_info.known_transactions.contains(h)) {
 						// update stats
 						for hash in &all_transactions_hashes {
 							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 							stats.propagated(*hash, id, block_number);
+							if local_transactions.contains(hash) {
+								history.sent(*hash, PropagationKind::Transaction, block_number, peer_id, now);
+							}
+							peer_info.known_transactions.insert(hash);
 						}
 						peer_info.last_sent_transactions = all_transactions_hashes.clone();
 						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
@@ -2022,12 +2167,16 @@ impl ChainSync {
 
 					// Get hashes of all transactions to send to this peer
 					let to_send = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
+						.filter(|h| !peer_info.known_transactions.contains(h))
 						.take(MAX_TRANSACTIONS_TO_PROPAGATE)
 						.cloned()
 						.collect::<HashSet<_>>();
 					if to_send.is_empty() {
 						return None;
 					}
+					for hash in &to_send {
+						peer_info.known_transactions.insert(hash);
+					}
 
 					// Construct RLP
 					let mut packet = RlpStream::new_list(to_send.len());
@@ -2037,6 +2186,9 @@ impl ChainSync {
 							// update stats
 							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
 							stats.propagated(tx.transaction.hash(), id, block_number);
+							if local_transactions.contains(&tx.transaction.hash()) {
+								history.sent(tx.transaction.hash(), PropagationKind::Transaction, block_number, peer_id, now);
+							}
 						}
 					}
 
@@ -2067,26 +2219,76 @@ impl ChainSync {
 		peers
 	}
 
+	/// Blocks sealed locally are pushed in full to all lagging peers. Relayed blocks are pushed
+	/// to a square root of lagging peers right away; the rest only get the hashes announced
+	/// after `BLOCK_ANNOUNCE_DELAY_MS`, by which time most of them have the blocks already.
 	fn propagate_latest_blocks(&mut self, io: &mut SyncIo, sealed: &[H256]) {
 		let chain_info = io.chain().chain_info();
 		if (((chain_info.best_block_number as i64) - (self.last_sent_block_number as i64)).abs() as BlockNumber) < MAX_PEER_LAG_PROPAGATION {
-			let mut peers = self.get_lagging_peers(&chain_info);
+			let peers = self.get_lagging_peers(&chain_info);
 			if sealed.is_empty() {
-				let hashes = self.propagate_new_hashes(&chain_info, io, &peers);
-				peers = ChainSync::select_random_peers(&peers);
-				let blocks = self.propagate_blocks(&chain_info, io, sealed, &peers);
-				if blocks != 0 || hashes != 0 {
-					trace!(target: "sync", "Sent latest {} blocks and {} hashes to peers.", blocks, hashes);
+				let lucky_peers = ChainSync::select_random_peers(&peers);
+				let blocks = self.propagate_blocks(&chain_info, io, sealed, &lucky_peers);
+				let rest: Vec<PeerId> = peers.into_iter().filter(|id| !lucky_peers.contains(id)).collect();
+				if blocks != 0 || !rest.is_empty() {
+					trace!(target: "sync", "Sent latest {} blocks to peers, delayed announcement to {} peers.", blocks, rest.len());
 				}
+				self.delay_announcement(rest);
 			} else {
 				self.propagate_blocks(&chain_info, io, sealed, &peers);
 				self.propagate_new_hashes(&chain_info, io, &peers);
+				let now = time::get_time().sec as u64;
+				for hash in sealed {
+					for peer_id in &peers {
+						self.propagation_history.sent(*hash, PropagationKind::Block, chain_info.best_block_number, *peer_id, now);
+					}
+				}
 				trace!(target: "sync", "Sent sealed block to all peers");
-			};
+			}
 		}
 		self.last_sent_block_number = chain_info.best_block_number;
 	}
 
+	fn delay_announcement(&mut self, peers: Vec<PeerId>) {
+		if peers.is_empty() {
+			return;
+		}
+		match self.delayed_announcement {
+			Some((_, ref mut pending)) => {
+				for id in peers {
+					if !pending.contains(&id) {
+						pending.push(id);
+					}
+				}
+			},
+			None => {
+				let deadline = time::precise_time_ns() + BLOCK_ANNOUNCE_DELAY_MS * 1_000_000;
+				self.delayed_announcement = Some((deadline, peers));
+			}
+		}
+	}
+
+	/// Announce the best block to peers whose announcement was delayed and who still don't have it.
+	pub fn propagate_delayed_announcements(&mut self, io: &mut SyncIo) {
+		match self.delayed_announcement {
+			Some((deadline, _)) if deadline <= time::precise_time_ns() => {},
+			_ => return,
+		}
+		let (_, peers) = self.delayed_announcement.take().expect("delayed_announcement checked to be Some above; qed");
+		let chain_info = io.chain().chain_info();
+		let peers: Vec<PeerId> = peers.into_iter()
+			.filter(|id| self.peers.get(id).map_or(false, |p| p.latest_hash != chain_info.best_block_hash))
+			.collect();
+		let hashes = self.propagate_new_hashes(&chain_info, io, &peers);
+		if hashes != 0 {
+			let now = time::get_time().sec as u64;
+			for peer_id in &peers {
+				self.propagation_history.announced(&chain_info.best_block_hash, *peer_id, now);
+			}
+			trace!(target: "sync", "Sent delayed announcement to {} peers.", hashes);
+		}
+	}
+
 	/// Distribute valid proposed blocks to subset of current peers.
 	fn propagate_proposed_blocks(&mut self, io: &mut SyncIo, proposed: &[Bytes]) {
 		let peers = self.get_consensus_peers();
@@ -2106,6 +2308,47 @@ impl ChainSync {
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
 		self.maybe_start_snapshot_sync(io);
 		self.check_resume(io);
+		self.check_head_stall(io);
+	}
+
+	/// Disconnect peers claiming a higher head and restart block download
+	/// if the best block hasn't advanced for `head_stall_timeout` seconds.
+	fn check_head_stall(&mut self, io: &mut SyncIo) {
+		let timeout = match self.head_stall_timeout {
+			Some(timeout) => timeout,
+			None => return,
+		};
+		let tick = time::precise_time_ns();
+		let chain_info = io.chain().chain_info();
+		// Not stalled while the head moves, blocks are being imported or a snapshot is being restored.
+		let busy = match self.state {
+			SyncState::Idle | SyncState::Blocks | SyncState::NewBlocks => !io.chain().queue_info().is_empty(),
+			_ => true,
+		};
+		if busy || chain_info.best_block_number != self.head_stall_since.0 {
+			self.head_stall_since = (chain_info.best_block_number, tick);
+			return;
+		}
+		if (tick - self.head_stall_since.1) / 1_000_000_000 <= timeout {
+			return;
+		}
+
+		let ahead: Vec<PeerId> = self.peers.iter()
+			.filter(|&(_, p)| p.is_allowed() && p.difficulty.map_or(false, |d| d > chain_info.total_difficulty))
+			.map(|(id, _)| *id)
+			.collect();
+		self.head_stall_since = (chain_info.best_block_number, tick);
+		if ahead.is_empty() {
+			return;
+		}
+
+		warn!(target: "sync", "Best block #{} hasn't changed for over {}s while {} peers report higher heads. Rotating peers.", chain_info.best_block_number, timeout, ahead.len());
+		self.head_stall_rotations += 1;
+		for peer_id in ahead {
+			io.disconnect_peer(peer_id);
+			self.on_peer_aborting(io, peer_id);
+		}
+		self.restart(io);
 	}
 
 	/// called when block is imported to chain - propagates the blocks and updates transactions sent to peers
@@ -2127,9 +2370,10 @@ impl ChainSync {
 			// Select random peer to re-broadcast transactions to.
 			let peer = random::new().gen_range(0, self.peers.len());
 			trace!(target: "sync", "Re-broadcasting transactions to a random peer.");
-			self.peers.values_mut().nth(peer).map(|mut peer_info|
-				peer_info.last_sent_transactions.clear()
-			);
+			self.peers.values_mut().nth(peer).map(|mut peer_info| {
+				peer_info.last_sent_transactions.clear();
+				peer_info.known_transactions.clear();
+			});
 		}
 	}
 
@@ -2182,6 +2426,7 @@ mod tests {
 	use ::SyncConfig;
 	use super::{PeerInfo, PeerAsking};
 	use ethkey;
+	use api::PropagationKind;
 	use ethcore::header::*;
 	use ethcore::client::*;
 	use ethcore::transaction::UnverifiedTransaction;
@@ -2253,6 +2498,7 @@ mod tests {
 			num_snapshot_chunks: 0,
 			snapshot_chunks_done: 0,
 			last_imported_old_block_number: None,
+			num_head_stall_rotations: 0,
 		}
 	}
 
@@ -2427,12 +2673,14 @@ mod tests {
 				asking_hash: None,
 				ask_time: 0,
 				last_sent_transactions: HashSet::new(),
+				known_transactions: KnownTransactions::default(),
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
+				request_parallelism: RequestParallelism::new(None),
 			});
 
 	}
@@ -2449,6 +2697,22 @@ mod tests {
 		assert_eq!(1, lagging_peers.len());
 	}
 
+	#[test]
+	fn prefers_peers_not_advertising_bad_blocks() {
+		let client = TestBlockChainClient::new();
+		client.bad_blocks.write().insert(H256::from(1));
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		for peer in 0..5 {
+			insert_dummy_peer(&mut sync, peer, if peer == 2 { H256::from(1) } else { H256::from(2) });
+		}
+
+		for _ in 0..10 {
+			let peers = sync.peers_to_sync(&client);
+			assert_eq!(peers.len(), 5);
+			assert_eq!(peers.last(), Some(&2));
+		}
+	}
+
 	#[test]
 	fn calculates_tree_for_lagging_peer() {
 		let mut client = TestBlockChainClient::new();
@@ -2466,6 +2730,32 @@ mod tests {
 		assert_eq!(107, rlp.len());
 	}
 
+	#[test]
+	fn rotates_peers_ahead_when_head_stalls() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		insert_dummy_peer(&mut sync, 1, client.block_hash_delta_minus(1));
+		let total_difficulty = client.chain_info().total_difficulty;
+		sync.peers.get_mut(&0).unwrap().difficulty = Some(total_difficulty + 1.into());
+		sync.peers.get_mut(&1).unwrap().difficulty = Some(total_difficulty);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		sync.maintain_sync(&mut io);
+		assert!(io.to_disconnect.is_empty());
+
+		sync.head_stall_timeout = Some(0);
+		sync.head_stall_since.1 = 0;
+		sync.maintain_sync(&mut io);
+
+		assert!(io.to_disconnect.contains(&0));
+		assert!(!io.to_disconnect.contains(&1));
+		assert!(!sync.peers.contains_key(&0));
+		assert_eq!(sync.status().num_head_stall_rotations, 1);
+	}
+
 	#[test]
 	fn sends_new_hashes_to_lagging_peer() {
 		let mut client = TestBlockChainClient::new();
@@ -2528,6 +2818,46 @@ mod tests {
 		assert_eq!(0x07, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn records_sealed_block_propagation() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let hash = client.block_hash(BlockId::Number(99)).unwrap();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		sync.last_sent_block_number = 99;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		sync.propagate_latest_blocks(&mut io, &[hash.clone()]);
+
+		let stats = sync.propagation_stats();
+		assert_eq!(stats.len(), 1);
+		assert_eq!(stats[0].hash, hash);
+		assert_eq!(stats[0].kind, PropagationKind::Block);
+		assert_eq!(stats[0].sent_to, 1);
+	}
+
+	#[test]
+	fn sends_sealed_block_to_all_peers() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let hash = client.block_hash(BlockId::Number(99)).unwrap();
+		let mut sync = ChainSync::new(SyncConfig::default(), &client);
+		for peer_id in 0..16 {
+			insert_dummy_peer(&mut sync, peer_id, client.block_hash_delta_minus(5));
+		}
+		sync.last_sent_block_number = 99;
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		sync.propagate_latest_blocks(&mut io, &[hash.clone()]);
+
+		// NEW_BLOCK_PACKET to every peer, nothing left to announce later
+		assert_eq!(16, io.packets.iter().filter(|p| p.packet_id == 0x07).count());
+		assert!(sync.delayed_announcement.is_none());
+		assert_eq!(sync.propagation_stats()[0].sent_to, 16);
+	}
+
 	#[test]
 	fn sends_proposed_block() {
 		let mut client = TestBlockChainClient::new();
@@ -2548,12 +2878,14 @@ mod tests {
 				asking_hash: None,
 				ask_time: 0,
 				last_sent_transactions: HashSet::new(),
+				known_transactions: KnownTransactions::default(),
 				expired: false,
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
 				asking_snapshot_data: None,
 				block_set: None,
+				request_parallelism: RequestParallelism::new(None),
 			});
 		let ss = TestSnapshotService::new();
 		let mut io = TestIo::new(&mut client, &ss, &queue, None);
@@ -2592,6 +2924,24 @@ mod tests {
 		assert_eq!(0x02, io.packets[0].packet_id);
 	}
 
+	#[test]
+	fn does_not_propagate_transactions_known_to_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_transaction_to_queue();
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		for tx in client.ready_transactions() {
+			sync.peers.get_mut(&0).unwrap().known_transactions.insert(&tx.transaction.hash());
+		}
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		let peer_count = sync.propagate_new_transactions(&mut io);
+
+		assert_eq!(0, peer_count);
+		assert_eq!(0, io.packets.len());
+	}
+
 	#[test]
 	fn does_not_propagate_new_transactions_after_new_block() {
 		let mut client = TestBlockChainClient::new();
@@ -0,0 +1,165 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Record of the transactions a peer is known to have.
+
+use std::time::{Duration, Instant};
+use util::H256;
+
+/// Number of bits in each bloom filter generation.
+const GENERATION_BITS: usize = 1 << 16;
+/// Number of bits set for each transaction hash.
+const BITS_PER_HASH: usize = 4;
+/// Number of hashes a generation takes before it is retired, keeping the false positive rate around 0.2%.
+const GENERATION_CAPACITY: usize = 4096;
+/// Time after which a generation is retired, however full.
+const GENERATION_LIFETIME_SECS: u64 = 300;
+
+#[derive(Clone)]
+struct Generation {
+	bits: Vec<u64>,
+	len: usize,
+	started: Instant,
+}
+
+impl Generation {
+	fn new() -> Self {
+		Generation {
+			bits: vec![0; GENERATION_BITS / 64],
+			len: 0,
+			started: Instant::now(),
+		}
+	}
+
+	fn positions(hash: &H256) -> [usize; BITS_PER_HASH] {
+		// Transaction hashes are uniformly distributed already; every 8 bytes give a position.
+		let mut positions = [0; BITS_PER_HASH];
+		for (i, position) in positions.iter_mut().enumerate() {
+			let word = hash[i * 8..(i + 1) * 8].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+			*position = word % GENERATION_BITS;
+		}
+		positions
+	}
+
+	fn insert(&mut self, hash: &H256) {
+		for p in &Generation::positions(hash) {
+			self.bits[p / 64] |= 1 << (p % 64);
+		}
+		self.len += 1;
+	}
+
+	fn contains(&self, hash: &H256) -> bool {
+		Generation::positions(hash).iter().all(|p| self.bits[p / 64] & (1 << (p % 64)) != 0)
+	}
+}
+
+/// Rolling bloom filter of hashes of transactions a peer is known to have, because it either sent
+/// them to us or got them from us. Two generations are kept: hashes go into the current one, which
+/// replaces the previous one once full or too old. A false positive thus holds a transaction back
+/// from the peer at most until both generations rolled over.
+#[derive(Clone)]
+pub struct KnownTransactions {
+	current: Generation,
+	previous: Generation,
+}
+
+impl Default for KnownTransactions {
+	fn default() -> Self {
+		KnownTransactions {
+			current: Generation::new(),
+			previous: Generation::new(),
+		}
+	}
+}
+
+impl KnownTransactions {
+	/// Note that the peer has the transaction.
+	pub fn insert(&mut self, hash: &H256) {
+		if self.current.len >= GENERATION_CAPACITY {
+			self.roll();
+		}
+		self.current.insert(hash);
+	}
+
+	/// Whether the peer probably has the transaction.
+	pub fn contains(&self, hash: &H256) -> bool {
+		self.current.contains(hash) || self.previous.contains(hash)
+	}
+
+	/// Forget all transactions.
+	pub fn clear(&mut self) {
+		*self = KnownTransactions::default();
+	}
+
+	/// Retire the current generation if it has been around for too long.
+	pub fn maintain(&mut self) {
+		if self.current.started.elapsed() >= Duration::from_secs(GENERATION_LIFETIME_SECS) {
+			self.roll();
+		}
+	}
+
+	fn roll(&mut self) {
+		self.previous = ::std::mem::replace(&mut self.current, Generation::new());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use util::H256;
+	use super::{KnownTransactions, GENERATION_CAPACITY};
+
+	#[test]
+	fn remembers_inserted_hashes() {
+		let mut known = KnownTransactions::default();
+		let hash = H256::random();
+		assert!(!known.contains(&hash));
+
+		known.insert(&hash);
+		assert!(known.contains(&hash));
+
+		known.clear();
+		assert!(!known.contains(&hash));
+	}
+
+	#[test]
+	fn forgets_hashes_after_two_generations() {
+		let mut known = KnownTransactions::default();
+		let hash = H256::random();
+		known.insert(&hash);
+
+		for i in 0..GENERATION_CAPACITY {
+			known.insert(&H256::from(i as u64));
+		}
+		// The hash made it into the previous generation.
+		assert!(known.contains(&hash));
+
+		for i in 0..GENERATION_CAPACITY {
+			known.insert(&H256::from(i as u64));
+		}
+		assert!(!known.contains(&hash));
+	}
+
+	#[test]
+	fn keeps_false_positives_rare() {
+		let mut known = KnownTransactions::default();
+		for _ in 0..GENERATION_CAPACITY {
+			known.insert(&H256::random());
+		}
+
+		let false_positives = (0..10_000).filter(|_| known.contains(&H256::random())).count();
+		assert!(false_positives < 100, "{} false positives out of 10000", false_positives);
+	}
+}
@@ -57,6 +57,10 @@ mod block_sync;
 mod sync_io;
 mod snapshot;
 mod transactions_stats;
+mod propagation_stats;
+mod receipts_backfill;
+mod known_transactions;
+mod request_parallelism;
 
 pub mod light_sync;
 
@@ -74,6 +78,7 @@ mod api;
 
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
+pub use request_parallelism::MAX_REQUEST_PARALLELISM;
 pub use network::{is_valid_node_url, NonReservedPeerMode, NetworkError};
 
 /// IPC interfaces
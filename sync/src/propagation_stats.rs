@@ -0,0 +1,158 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Bounded history of how local transactions and locally sealed blocks were propagated.
+
+use std::collections::{HashSet, VecDeque};
+use api::{PropagationStats, PropagationKind};
+use network::PeerId;
+use util::H256;
+use util::hash::H256FastMap;
+
+type BlockNumber = u64;
+
+/// Number of transactions and blocks to keep the history of.
+pub const MAX_PROPAGATION_RECORDS: usize = 512;
+
+#[derive(Debug)]
+struct Record {
+	kind: PropagationKind,
+	block_number: BlockNumber,
+	first_propagated: u64,
+	last_propagated: u64,
+	sent_to: HashSet<PeerId>,
+	announced_to: HashSet<PeerId>,
+}
+
+impl Record {
+	fn stats(&self, hash: &H256) -> PropagationStats {
+		PropagationStats {
+			hash: *hash,
+			kind: self.kind,
+			block_number: self.block_number,
+			first_propagated: self.first_propagated,
+			last_propagated: self.last_propagated,
+			sent_to: self.sent_to.len(),
+			announced_to: self.announced_to.len(),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct PropagationHistory {
+	limit: usize,
+	order: VecDeque<H256>,
+	records: H256FastMap<Record>,
+}
+
+impl Default for PropagationHistory {
+	fn default() -> Self {
+		PropagationHistory::new(MAX_PROPAGATION_RECORDS)
+	}
+}
+
+impl PropagationHistory {
+	/// Keep the history of at most `limit` transactions and blocks.
+	pub fn new(limit: usize) -> Self {
+		PropagationHistory {
+			limit: limit,
+			order: VecDeque::new(),
+			records: Default::default(),
+		}
+	}
+
+	/// Note that the transaction or block was sent to given peer at unix time `now`.
+	pub fn sent(&mut self, hash: H256, kind: PropagationKind, block_number: BlockNumber, peer: PeerId, now: u64) {
+		let record = self.record(hash, kind, block_number, now);
+		record.sent_to.insert(peer);
+		record.last_propagated = now;
+	}
+
+	/// Note that the hash of an already propagated block was announced to given peer at unix time `now`.
+	pub fn announced(&mut self, hash: &H256, peer: PeerId, now: u64) {
+		if let Some(record) = self.records.get_mut(hash) {
+			record.announced_to.insert(peer);
+			record.last_propagated = now;
+		}
+	}
+
+	/// Statistics of all recorded transactions and blocks, most recently first propagated first.
+	pub fn stats(&self) -> Vec<PropagationStats> {
+		self.order.iter().rev()
+			.filter_map(|hash| self.records.get(hash).map(|record| record.stats(hash)))
+			.collect()
+	}
+
+	fn record(&mut self, hash: H256, kind: PropagationKind, block_number: BlockNumber, now: u64) -> &mut Record {
+		if !self.records.contains_key(&hash) {
+			if self.order.len() >= self.limit {
+				if let Some(oldest) = self.order.pop_front() {
+					self.records.remove(&oldest);
+				}
+			}
+			self.order.push_back(hash);
+		}
+
+		self.records.entry(hash).or_insert_with(|| Record {
+			kind: kind,
+			block_number: block_number,
+			first_propagated: now,
+			last_propagated: now,
+			sent_to: HashSet::new(),
+			announced_to: HashSet::new(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use api::{PropagationStats, PropagationKind};
+	use super::PropagationHistory;
+
+	#[test]
+	fn should_count_distinct_peers() {
+		let mut history = PropagationHistory::default();
+		let hash = 5.into();
+
+		history.sent(hash, PropagationKind::Block, 10, 1, 100);
+		history.sent(hash, PropagationKind::Block, 10, 1, 101);
+		history.sent(hash, PropagationKind::Block, 10, 2, 102);
+		history.announced(&hash, 3, 105);
+		history.announced(&6.into(), 3, 105);
+
+		assert_eq!(history.stats(), vec![PropagationStats {
+			hash: hash,
+			kind: PropagationKind::Block,
+			block_number: 10,
+			first_propagated: 100,
+			last_propagated: 105,
+			sent_to: 2,
+			announced_to: 1,
+		}]);
+	}
+
+	#[test]
+	fn should_forget_oldest_records() {
+		let mut history = PropagationHistory::new(2);
+		history.sent(1.into(), PropagationKind::Transaction, 10, 1, 100);
+		history.sent(2.into(), PropagationKind::Transaction, 10, 1, 100);
+		history.sent(1.into(), PropagationKind::Transaction, 11, 2, 110);
+		history.sent(3.into(), PropagationKind::Block, 11, 1, 110);
+
+		let hashes: Vec<_> = history.stats().into_iter().map(|stats| stats.hash).collect();
+		assert_eq!(hashes, vec![3.into(), 2.into()]);
+	}
+}
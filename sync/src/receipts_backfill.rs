@@ -0,0 +1,275 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Background download of receipts missing for blocks already in the chain,
+//! e.g. historical blocks after a snapshot restoration.
+//!
+//! Blocks are checked from the best block down to the target depth. Receipts of
+//! blocks without transactions are filled in locally, the rest are requested from
+//! peers and checked against the receipts root of the block header on import.
+
+use std::collections::{HashSet, VecDeque};
+use ethcore::client::{BlockChainClient, BlockId, BlockImportError};
+use ethcore::header::BlockNumber;
+use rlp::{UntrustedRlp, EMPTY_LIST_RLP};
+use util::{H256, SHA3_NULL_RLP};
+use api::ReceiptsBackfillStatus;
+use block_sync::BlockDownloaderImportError as DownloaderImportError;
+
+/// Number of blocks checked for missing receipts at a time.
+const MAX_BLOCKS_TO_SCAN: usize = 256;
+/// Number of blocks found missing receipts kept waiting for download.
+const MAX_QUEUED: usize = 1024;
+
+pub struct ReceiptsBackfill {
+	/// Block the backfill started at.
+	start: BlockNumber,
+	/// Lowest block to check.
+	target: BlockNumber,
+	/// Next block to check, `None` once all are.
+	next: Option<BlockNumber>,
+	/// Blocks missing receipts, waiting to be requested.
+	queued: VecDeque<H256>,
+	/// Blocks with receipts being downloaded.
+	downloading: HashSet<H256>,
+	/// Number of blocks receipts were imported for.
+	imported: u64,
+}
+
+impl ReceiptsBackfill {
+	/// Check `depth` blocks below the best block `best`.
+	pub fn new(best: BlockNumber, depth: u64) -> Self {
+		ReceiptsBackfill {
+			start: best,
+			target: best.saturating_sub(depth),
+			next: Some(best),
+			queued: VecDeque::new(),
+			downloading: HashSet::new(),
+			imported: 0,
+		}
+	}
+
+	/// Hashes of at most `count` blocks to request receipts for.
+	pub fn request(&mut self, chain: &BlockChainClient, count: usize) -> Vec<H256> {
+		self.scan(chain);
+
+		let mut hashes = Vec::new();
+		while hashes.len() < count {
+			match self.queued.pop_front() {
+				Some(hash) => {
+					self.downloading.insert(hash.clone());
+					hashes.push(hash);
+				},
+				None => break,
+			}
+		}
+		hashes
+	}
+
+	/// Import receipts delivered for blocks `hashes`, in order.
+	pub fn import(&mut self, chain: &BlockChainClient, hashes: &[H256], r: &UntrustedRlp) -> Result<(), DownloaderImportError> {
+		let item_count = r.item_count().map_err(|_| DownloaderImportError::Invalid)?;
+		if item_count > hashes.len() {
+			trace!(target: "sync", "Got {} receipts for {} requested blocks", item_count, hashes.len());
+			return Err(DownloaderImportError::Invalid);
+		}
+
+		let mut delivered = Vec::with_capacity(item_count);
+		for (hash, receipts) in hashes.iter().zip(r.iter()) {
+			self.queued.retain(|h| h != hash);
+			self.downloading.remove(hash);
+			delivered.push((hash.clone(), receipts.as_raw().to_vec()));
+		}
+
+		let mut invalid = false;
+		let results = chain.import_block_receipts(delivered);
+		for (hash, result) in hashes.iter().cloned().zip(results) {
+			match result {
+				Ok(()) => self.imported += 1,
+				Err(BlockImportError::Block(e)) => {
+					trace!(target: "sync", "Invalid receipts for block {}: {:?}", hash, e);
+					self.queued.push_back(hash);
+					invalid = true;
+				},
+				Err(e) => {
+					debug!(target: "sync", "Error importing receipts for block {}: {:?}", hash, e);
+					self.queued.push_back(hash);
+				},
+			}
+		}
+
+		match invalid {
+			true => Err(DownloaderImportError::Invalid),
+			false => Ok(()),
+		}
+	}
+
+	/// Requeue blocks whose receipts were requested but won't be delivered.
+	pub fn clear_download(&mut self, hashes: &[H256]) {
+		for hash in hashes {
+			if self.downloading.remove(hash) {
+				self.queued.push_back(hash.clone());
+			}
+		}
+	}
+
+	/// Whether all blocks were checked and their receipts imported.
+	pub fn is_complete(&self) -> bool {
+		self.next.is_none() && self.queued.is_empty() && self.downloading.is_empty()
+	}
+
+	/// Progress of the backfill.
+	pub fn status(&self) -> ReceiptsBackfillStatus {
+		ReceiptsBackfillStatus {
+			start_block: self.start,
+			target_block: self.target,
+			next_block: self.next,
+			imported: self.imported,
+			pending: self.queued.len() + self.downloading.len(),
+		}
+	}
+
+	fn scan(&mut self, chain: &BlockChainClient) {
+		let mut scanned = 0;
+		let mut empty = Vec::new();
+		while let Some(number) = self.next {
+			if scanned == MAX_BLOCKS_TO_SCAN || self.queued.len() >= MAX_QUEUED {
+				break;
+			}
+			scanned += 1;
+			self.next = match number > self.target {
+				true => Some(number - 1),
+				false => None,
+			};
+
+			// missing blocks come with their receipts once downloaded.
+			let header = match chain.block_header(BlockId::Number(number)) {
+				Some(header) => header,
+				None => continue,
+			};
+			let hash = header.hash();
+			if chain.block_receipts(&hash).is_some() {
+				continue;
+			}
+
+			if header.receipts_root() == SHA3_NULL_RLP {
+				empty.push((hash, EMPTY_LIST_RLP.to_vec()));
+			} else {
+				self.queued.push_back(hash);
+			}
+		}
+
+		if !empty.is_empty() {
+			let hashes = empty.iter().map(|&(ref hash, _)| hash.clone()).collect::<Vec<_>>();
+			for (hash, result) in hashes.into_iter().zip(chain.import_block_receipts(empty)) {
+				match result {
+					Ok(()) => self.imported += 1,
+					Err(e) => {
+						// leave it to be downloaded instead.
+						debug!(target: "sync", "Error importing empty receipts for block {}: {:?}", hash, e);
+						self.queued.push_back(hash);
+					},
+				}
+			}
+		}
+
+		if self.is_complete() {
+			debug!(target: "sync", "Receipts backfill complete: blocks #{}-#{} checked, {} imported", self.target, self.start, self.imported);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ethcore::client::{TestBlockChainClient, EachBlockWith, BlockChainClient};
+	use ethcore::header::{Header, BlockNumber};
+	use rlp::{RlpStream, UntrustedRlp};
+	use util::H256;
+	use super::ReceiptsBackfill;
+
+	fn add_block_with_receipts(client: &TestBlockChainClient) -> H256 {
+		let mut header = Header::new();
+		header.set_number(client.numbers.read().len() as BlockNumber);
+		header.set_parent_hash(client.last_hash.read().clone());
+		header.set_receipts_root(H256::from(1));
+
+		let mut block = RlpStream::new_list(3);
+		block.append(&header).begin_list(0).begin_list(0);
+		client.import_block(block.out()).unwrap()
+	}
+
+	#[test]
+	fn fills_receipts_of_empty_blocks() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let mut backfill = ReceiptsBackfill::new(10, 4);
+
+		assert!(backfill.request(&client, 10).is_empty());
+		assert!(backfill.is_complete());
+		assert_eq!(backfill.status().imported, 5);
+	}
+
+	#[test]
+	fn requests_and_imports_missing_receipts() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(5, EachBlockWith::Nothing);
+		let blocks: Vec<_> = (0..3).map(|_| add_block_with_receipts(&client)).collect();
+		let mut backfill = ReceiptsBackfill::new(8, 4);
+
+		let hashes = backfill.request(&client, 2);
+		assert_eq!(hashes, vec![blocks[2], blocks[1]]);
+		assert_eq!(backfill.status().pending, 3);
+
+		// only the first block is delivered.
+		let mut receipts = RlpStream::new_list(1);
+		receipts.begin_list(0);
+		assert_eq!(backfill.import(&client, &hashes, &UntrustedRlp::new(&receipts.out())), Ok(()));
+		backfill.clear_download(&hashes);
+		assert!(client.block_receipts(&blocks[2]).is_some());
+
+		let status = backfill.status();
+		assert_eq!((status.imported, status.pending, status.next_block), (3, 2, None));
+		assert_eq!(backfill.request(&client, 10), vec![blocks[0], blocks[1]]);
+		assert!(!backfill.is_complete());
+	}
+
+	#[test]
+	fn rejects_excess_receipts() {
+		let client = TestBlockChainClient::new();
+		let block = add_block_with_receipts(&client);
+		let mut backfill = ReceiptsBackfill::new(1, 0);
+		assert_eq!(backfill.request(&client, 1), vec![block]);
+
+		let mut receipts = RlpStream::new_list(2);
+		receipts.begin_list(0).begin_list(0);
+		assert!(backfill.import(&client, &[block], &UntrustedRlp::new(&receipts.out())).is_err());
+	}
+
+	#[test]
+	fn requeues_receipts_failing_to_import() {
+		let client = TestBlockChainClient::new();
+		let mut backfill = ReceiptsBackfill::new(0, 0);
+		assert!(backfill.request(&client, 1).is_empty());
+
+		// the block is unknown to the client, which isn't the peer's fault.
+		let unknown = H256::from(1);
+		let mut receipts = RlpStream::new_list(1);
+		receipts.begin_list(0);
+		assert_eq!(backfill.import(&client, &[unknown], &UntrustedRlp::new(&receipts.out())), Ok(()));
+		assert_eq!(backfill.status().pending, 1);
+		assert_eq!(backfill.request(&client, 1), vec![unknown]);
+	}
+}
@@ -0,0 +1,152 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-peer tuning of the number of block data batches requested at once.
+//!
+//! The eth protocol has no request ids, so a peer serves one request at a time.
+//! Parallelism is the number of standard-sized batches merged into that request:
+//! on a high-latency link a larger request keeps the connection busy for longer
+//! than the round trip it costs.
+
+/// Upper bound of batches requested from a peer at once.
+pub const MAX_REQUEST_PARALLELISM: usize = 8;
+/// Responses are expected to take about this long at the tuned parallelism.
+const TARGET_RESPONSE_MS: f64 = 2000.0;
+/// Weight of the latest sample in the moving averages.
+const SAMPLE_WEIGHT: f64 = 0.25;
+
+/// Measured performance of a peer and the parallelism derived from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestParallelism {
+	/// Parallelism configured by the user; not tuned if set.
+	fixed: Option<usize>,
+	/// Batches to request at once.
+	current: usize,
+	/// Smoothed response time, in milliseconds.
+	rtt_ms: Option<f64>,
+	/// Smoothed delivery rate, in batches per second.
+	rate: Option<f64>,
+}
+
+impl RequestParallelism {
+	/// Create for a new peer. Starts at one batch unless `fixed` is given.
+	pub fn new(fixed: Option<usize>) -> Self {
+		let fixed = fixed.map(|n| clamp(n, 1, MAX_REQUEST_PARALLELISM));
+		RequestParallelism {
+			fixed: fixed,
+			current: fixed.unwrap_or(1),
+			rtt_ms: None,
+			rate: None,
+		}
+	}
+
+	/// Number of batches to request at once.
+	pub fn get(&self) -> usize {
+		self.current
+	}
+
+	/// Smoothed response time of the peer, in milliseconds.
+	pub fn rtt_ms(&self) -> Option<f64> {
+		self.rtt_ms
+	}
+
+	/// Record a response delivering `delivered` of the `requested` items, `batch_size` items
+	/// making one batch, which arrived `elapsed_ms` after the request was sent.
+	pub fn on_response(&mut self, requested: usize, delivered: usize, batch_size: usize, elapsed_ms: u64) {
+		if requested == 0 || batch_size == 0 {
+			return;
+		}
+
+		let elapsed_ms = ::std::cmp::max(elapsed_ms, 1) as f64;
+		let batches = delivered as f64 / batch_size as f64;
+		self.rtt_ms = Some(average(self.rtt_ms, elapsed_ms));
+		self.rate = Some(average(self.rate, batches * 1000.0 / elapsed_ms));
+
+		if self.fixed.is_some() {
+			return;
+		}
+
+		if delivered < requested {
+			// the peer caps its responses; asking for more only wastes the round trip.
+			self.current = clamp(batches.ceil() as usize, 1, self.current);
+			return;
+		}
+
+		// batches the peer can deliver within the target response time, growing one step
+		// at a time since the rate measured with small requests underestimates the link.
+		let affordable = (self.rate.unwrap_or(0.0) * TARGET_RESPONSE_MS / 1000.0) as usize;
+		self.current = clamp(affordable, 1, ::std::cmp::min(self.current + 1, MAX_REQUEST_PARALLELISM));
+	}
+}
+
+fn average(previous: Option<f64>, sample: f64) -> f64 {
+	match previous {
+		Some(previous) => previous + (sample - previous) * SAMPLE_WEIGHT,
+		None => sample,
+	}
+}
+
+fn clamp(value: usize, min: usize, max: usize) -> usize {
+	::std::cmp::max(min, ::std::cmp::min(value, max))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{RequestParallelism, MAX_REQUEST_PARALLELISM};
+
+	// simulate a link with given round trip time and transfer time of a batch.
+	fn simulate(rtt_ms: u64, batch_ms: u64) -> RequestParallelism {
+		let mut p = RequestParallelism::new(None);
+		for _ in 0..30 {
+			let requested = p.get() * 64;
+			p.on_response(requested, requested, 64, rtt_ms + batch_ms * p.get() as u64);
+		}
+		p
+	}
+
+	#[test]
+	fn tunes_to_link() {
+		assert_eq!(RequestParallelism::new(None).get(), 1);
+		// high latency, high bandwidth.
+		assert_eq!(simulate(500, 100).get(), MAX_REQUEST_PARALLELISM);
+		// responses of 4 batches take 1.8s.
+		assert_eq!(simulate(200, 400).get(), 4);
+		// low bandwidth.
+		assert_eq!(simulate(500, 1000).get(), 1);
+		assert!(simulate(500, 1000).rtt_ms().unwrap() > 1400.0);
+	}
+
+	#[test]
+	fn shrinks_to_what_peer_serves() {
+		let mut p = RequestParallelism::new(None);
+		for _ in 0..20 {
+			let requested = p.get() * 64;
+			p.on_response(requested, requested, 64, 10);
+		}
+		assert_eq!(p.get(), MAX_REQUEST_PARALLELISM);
+
+		p.on_response(MAX_REQUEST_PARALLELISM * 64, 128, 64, 10);
+		assert_eq!(p.get(), 2);
+	}
+
+	#[test]
+	fn fixed_is_not_tuned() {
+		let mut p = RequestParallelism::new(Some(3));
+		p.on_response(192, 10, 64, 5000);
+		assert_eq!(p.get(), 3);
+		assert_eq!(RequestParallelism::new(Some(100)).get(), MAX_REQUEST_PARALLELISM);
+	}
+}
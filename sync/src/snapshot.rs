@@ -62,6 +62,15 @@ impl Snapshot {
 		self.snapshot_hash = Some(hash.clone());
 	}
 
+	/// Mark chunks restored before as downloaded.
+	pub fn note_completed(&mut self, hashes: Vec<H256>) {
+		for hash in hashes {
+			if self.pending_state_chunks.contains(&hash) || self.pending_block_chunks.contains(&hash) {
+				self.completed_chunks.insert(hash);
+			}
+		}
+	}
+
 	/// Validate chunk and mark it as downloaded
 	pub fn validate_chunk(&mut self, chunk: &[u8]) -> Result<ChunkType, ()> {
 		let hash = chunk.sha3();
@@ -199,5 +208,20 @@ mod test {
 		assert_eq!(snapshot.done_chunks(), snapshot.total_chunks());
 		assert_eq!(snapshot.snapshot_hash(), Some(manifest.into_rlp().sha3()));
 	}
+
+	#[test]
+	fn note_completed_chunks() {
+		let mut snapshot = Snapshot::new();
+		let (manifest, mhash, _, _) = test_manifest();
+		snapshot.reset_to(&manifest, &mhash);
+
+		snapshot.note_completed(vec![manifest.state_hashes[0].clone(), manifest.block_hashes[3].clone(), H256::random()]);
+		assert_eq!(snapshot.done_chunks(), 2);
+
+		let requested: Vec<H256> = (0..38).map(|_| snapshot.needed_chunk().unwrap()).collect();
+		assert!(snapshot.needed_chunk().is_none());
+		assert!(!requested.contains(&manifest.state_hashes[0]));
+		assert!(!requested.contains(&manifest.block_hashes[3]));
+	}
 }
 
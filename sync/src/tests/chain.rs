@@ -16,7 +16,7 @@
 
 use util::*;
 use ethcore::client::{TestBlockChainClient, BlockChainClient, BlockId, EachBlockWith};
-use chain::{SyncState};
+use chain::{SyncState, BLOCK_ANNOUNCE_DELAY_MS};
 use super::helpers::*;
 use SyncConfig;
 
@@ -191,20 +191,17 @@ fn propagate_hashes() {
 	net.trigger_chain_new_blocks(0); //first event just sets the marker
 	net.trigger_chain_new_blocks(0);
 
-	// 5 peers with NewHahses, 4 with blocks
-	assert_eq!(9, net.peer(0).queue.read().len());
-	let mut hashes = 0;
-	let mut blocks = 0;
-	for i in 0..net.peer(0).queue.read().len() {
-		if net.peer(0).queue.read()[i].packet_id == 0x1 {
-			hashes += 1;
-		}
-		if net.peer(0).queue.read()[i].packet_id == 0x7 {
-			blocks += 1;
-		}
-	}
-	assert_eq!(blocks, 4);
-	assert_eq!(hashes, 5);
+	// 4 peers with blocks right away
+	assert_eq!(4, net.peer(0).queue.read().len());
+	assert!(net.peer(0).queue.read().iter().all(|p| p.packet_id == 0x7));
+
+	// the remaining peer gets NewHashes after a delay
+	net.trigger_delayed_announcements(0);
+	assert_eq!(4, net.peer(0).queue.read().len());
+	::std::thread::sleep(::std::time::Duration::from_millis(BLOCK_ANNOUNCE_DELAY_MS));
+	net.trigger_delayed_announcements(0);
+	assert_eq!(5, net.peer(0).queue.read().len());
+	assert_eq!(0x1, net.peer(0).queue.read()[4].packet_id);
 }
 
 #[test]
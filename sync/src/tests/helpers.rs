@@ -390,6 +390,11 @@ impl<C: FlushingBlockChainClient> TestNet<EthPeer<C>> {
 		let peer = &mut self.peers[peer_id];
 		peer.sync.write().chain_new_blocks(&mut TestIo::new(&*peer.chain, &peer.snapshot_service, &peer.queue, None), &[], &[], &[], &[], &[], &[]);
 	}
+
+	pub fn trigger_delayed_announcements(&mut self, peer_id: usize) {
+		let peer = &mut self.peers[peer_id];
+		peer.sync.write().propagate_delayed_announcements(&mut TestIo::new(&*peer.chain, &peer.snapshot_service, &peer.queue, None));
+	}
 }
 
 impl ChainNotify for EthPeer<EthcoreClient> {
@@ -118,6 +118,10 @@ impl SnapshotService for TestSnapshotService {
 	fn provide_canon_hashes(&self, hashes: &[(u64, H256)]) {
 		self.canon_hashes.lock().extend(hashes.iter().cloned());
 	}
+
+	fn completed_chunks(&self, _manifest: ManifestData) -> Vec<H256> {
+		Vec::new()
+	}
 }
 
 #[test]
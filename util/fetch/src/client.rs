@@ -126,6 +126,29 @@ impl Client {
 		*self.client.write() = (time::Instant::now(), client.clone());
 		Ok(client)
 	}
+
+	fn spawn(&self, url: &str, post: Option<Post>, abort: Abort) -> CpuFuture<Response, Error> {
+		match self.client() {
+			Ok(client) => {
+				self.pool.spawn(FetchTask {
+					url: url.into(),
+					post: post,
+					client: client,
+					limit: self.limit,
+					abort: abort,
+				})
+			},
+			Err(err) => {
+				self.pool.spawn(futures::future::err(err))
+			},
+		}
+	}
+
+	/// Post a JSON body with given extra headers to given URL and get a future for the response.
+	pub fn post_json(&self, url: &str, body: Vec<u8>, headers: Vec<(&'static str, String)>) -> CpuFuture<Response, Error> {
+		debug!(target: "fetch", "Posting to: {:?}", url);
+		self.spawn(url, Some(Post { body: body, headers: headers }), Default::default())
+	}
 }
 
 impl Fetch for Client {
@@ -146,25 +169,18 @@ impl Fetch for Client {
 
 	fn fetch_with_abort(&self, url: &str, abort: Abort) -> Self::Result {
 		debug!(target: "fetch", "Fetching from: {:?}", url);
-
-		match self.client() {
-			Ok(client) => {
-				self.pool.spawn(FetchTask {
-					url: url.into(),
-					client: client,
-					limit: self.limit,
-					abort: abort,
-				})
-			},
-			Err(err) => {
-				self.pool.spawn(futures::future::err(err))
-			},
-		}
+		self.spawn(url, None, abort)
 	}
 }
 
+struct Post {
+	body: Vec<u8>,
+	headers: Vec<(&'static str, String)>,
+}
+
 struct FetchTask {
 	url: String,
+	post: Option<Post>,
 	client: Arc<reqwest::Client>,
 	limit: Option<usize>,
 	abort: Abort,
@@ -182,9 +198,20 @@ impl Future for FetchTask {
 		}
 
 		trace!(target: "fetch", "Starting fetch task: {:?}", self.url);
-		let result = self.client.get(&self.url)
-						  .header(reqwest::header::UserAgent("Parity Fetch".into()))
-						  .send()?;
+		let request = match self.post.take() {
+			Some(post) => {
+				let mut headers = reqwest::header::Headers::new();
+				headers.set(reqwest::header::ContentType::json());
+				for (name, value) in post.headers {
+					headers.set_raw(name, vec![value.into_bytes()]);
+				}
+				self.client.post(&self.url).headers(headers).body(post.body)
+			},
+			None => self.client.get(&self.url),
+		};
+		let result = request
+			.header(reqwest::header::UserAgent("Parity Fetch".into()))
+			.send()?;
 
 		Ok(futures::Async::Ready(Response {
 			inner: ResponseInner::Response(result),
@@ -536,6 +536,13 @@ impl Discovery {
 		self.start();
 	}
 
+	/// Change the endpoint advertised in our pings and start a new round so
+	/// that the table learns about it.
+	pub fn set_public_endpoint(&mut self, endpoint: NodeEndpoint) {
+		self.public_endpoint = endpoint;
+		self.refresh();
+	}
+
 	pub fn register_socket<Host:Handler>(&self, event_loop: &mut EventLoop<Host>) -> Result<(), NetworkError> {
 		event_loop.register(&self.udp_socket, Token(self.token), Ready::all(), PollOpt::edge()).expect("Error registering UDP socket");
 		Ok(())
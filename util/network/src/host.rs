@@ -521,6 +521,31 @@ impl Host {
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(info.id().clone(), e.clone())))
 	}
 
+	/// Change the address advertised to other nodes, e.g. after the external NAT mapping has changed.
+	/// The advertised discovery port stays the configured one, as when the public interface was initialized.
+	pub fn set_public_address(&self, address: SocketAddr, io: &IoContext<NetworkIoMessage>) {
+		{
+			let mut info = self.info.write();
+			let endpoint = NodeEndpoint { address: address, udp_port: info.local_endpoint.udp_port };
+			info.config.public_address = Some(address);
+			// the public interface picks up the configured address when it's initialized.
+			if info.public_endpoint.is_none() {
+				return;
+			}
+			info.public_endpoint = Some(endpoint);
+		}
+
+		let endpoint = self.info.read().public_endpoint.clone().expect("public endpoint was set above; qed");
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.set_public_endpoint(endpoint);
+		}
+		io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
+
+		if let Some(url) = self.external_url() {
+			io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
+		}
+	}
+
 	pub fn local_url(&self) -> String {
 		let info = self.info.read();
 		format!("{}", Node::new(info.id().clone(), info.local_endpoint.clone()))
@@ -21,6 +21,7 @@ use stats::NetworkStats;
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use std::net::SocketAddr;
 use ansi_term::Colour;
 
 struct HostHandler {
@@ -115,6 +116,15 @@ impl NetworkService {
 		host.as_ref().map(|h| h.local_url())
 	}
 
+	/// Change the address advertised to other nodes while the network is running.
+	pub fn set_public_address(&self, address: SocketAddr) {
+		let host = self.host.read();
+		if let Some(ref host) = *host {
+			let io = IoContext::new(self.io_service.channel(), 0);
+			host.set_public_address(address, &io);
+		}
+	}
+
 	/// Start network IO
 	pub fn start(&self) -> Result<(), NetworkError> {
 		let mut host = self.host.write();
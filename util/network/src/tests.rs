@@ -119,6 +119,21 @@ fn net_connect() {
 	assert!(service2.stats().sessions() >= 1);
 }
 
+#[test]
+fn net_set_public_address() {
+	let service = NetworkService::new(NetworkConfiguration::new_local()).unwrap();
+	service.start().unwrap();
+	while service.external_url().is_none() {
+		thread::sleep(Duration::from_millis(50));
+	}
+
+	// the configured discovery port is kept
+	let udp_port = service.external_url().unwrap().rsplit(':').next().unwrap().to_owned();
+
+	service.set_public_address("1.2.3.4:30400".parse().unwrap());
+	assert!(service.external_url().unwrap().ends_with(&format!("@1.2.3.4:30400+{}", udp_port)));
+}
+
 #[test]
 fn net_start_stop() {
 	let config = NetworkConfiguration::new_local();
@@ -22,6 +22,7 @@ use heapsize::HeapSizeOf;
 use lru_cache::LruCache;
 
 use std::hash::Hash;
+use std::mem;
 
 const INITIAL_CAPACITY: usize = 4;
 
@@ -30,6 +31,7 @@ pub struct MemoryLruCache<K: Eq + Hash, V: HeapSizeOf> {
 	inner: LruCache<K, V>,
 	cur_size: usize,
 	max_size: usize,
+	evictions: usize,
 }
 
 impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
@@ -39,6 +41,7 @@ impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
 			inner: LruCache::new(INITIAL_CAPACITY),
 			max_size: max_size,
 			cur_size: 0,
+			evictions: 0,
 		}
 	}
 
@@ -52,20 +55,38 @@ impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
 			self.inner.set_capacity(cap * 2);
 		}
 
+		self.cur_size += Self::entry_size(&val);
+
 		// account for any element displaced from the cache.
 		if let Some(lru) = self.inner.insert(key, val) {
-			self.cur_size -= lru.heap_size_of_children();
+			self.cur_size -= Self::entry_size(&lru);
 		}
 
 		// remove elements until we are below the memory target.
 		while self.cur_size > self.max_size {
-			match self.inner.remove_lru() {
-				Some((_, v)) => self.cur_size -= v.heap_size_of_children(),
-				_ => break,
+			if !self.remove_lru() {
+				break;
 			}
 		}
 	}
 
+	/// Evict the least recently used item. Returns `false` if the cache is empty.
+	pub fn remove_lru(&mut self) -> bool {
+		match self.inner.remove_lru() {
+			Some((_, v)) => {
+				self.cur_size -= Self::entry_size(&v);
+				self.evictions += 1;
+				true
+			},
+			None => false,
+		}
+	}
+
+	// bytes taken by an entry: the key and value themselves and what the value owns.
+	fn entry_size(val: &V) -> usize {
+		mem::size_of::<K>() + mem::size_of::<V>() + val.heap_size_of_children()
+	}
+
 	/// Get a reference to an item in the cache. It is a logic error for its
 	/// heap size to be altered while borrowed.
 	pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
@@ -76,4 +97,56 @@ impl<K: Eq + Hash, V: HeapSizeOf> MemoryLruCache<K, V> {
 	pub fn current_size(&self) -> usize {
 		self.cur_size
 	}
+
+	/// Maximum size of values in bytes.
+	pub fn max_size(&self) -> usize {
+		self.max_size
+	}
+
+	/// Number of items in the cache.
+	pub fn len(&self) -> usize {
+		self.inner.len()
+	}
+
+	/// Whether the cache holds no items.
+	pub fn is_empty(&self) -> bool {
+		self.inner.len() == 0
+	}
+
+	/// Number of items removed to stay below the maximum size.
+	pub fn evictions(&self) -> usize {
+		self.evictions
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::MemoryLruCache;
+
+	#[test]
+	fn evicts_to_stay_below_max_size() {
+		let mut cache = MemoryLruCache::new(256);
+		for i in 0..4 {
+			cache.insert(i, vec![0u8; 100]);
+		}
+
+		assert_eq!(cache.len(), 2);
+		assert_eq!(cache.evictions(), 2);
+		assert!(cache.current_size() <= cache.max_size());
+		assert!(cache.get_mut(&0).is_none());
+		assert!(cache.get_mut(&3).is_some());
+	}
+
+	#[test]
+	fn accounts_inline_size() {
+		let mut cache = MemoryLruCache::new(64);
+		for i in 0..8u64 {
+			cache.insert(i, i);
+		}
+
+		// values without heap allocations still take their key and value size.
+		assert_eq!(cache.current_size(), 64);
+		assert_eq!(cache.len(), 4);
+		assert_eq!(cache.evictions(), 4);
+	}
 }
\ No newline at end of file
@@ -0,0 +1,165 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! `Host` header validation for HTTP servers.
+
+/// Single allowed host, e.g. `localhost:8545` or `*.mydomain.tld`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct HostPattern {
+	/// Hostname; for wildcard patterns the suffix following `*`, including the leading dot.
+	hostname: String,
+	/// Whether the pattern matches any subdomain of `hostname`.
+	wildcard: bool,
+	/// Required port, if any.
+	port: Option<String>,
+}
+
+impl HostPattern {
+	/// Parse a pattern. Hostnames are matched case-insensitively.
+	pub fn parse(pattern: &str) -> Self {
+		let pattern = pattern.trim().to_lowercase();
+		let (hostname, port) = split_port(&pattern);
+		let (hostname, wildcard) = match hostname.starts_with("*.") {
+			true => (hostname[1..].to_owned(), true),
+			false => (hostname.to_owned(), false),
+		};
+
+		HostPattern {
+			hostname: hostname,
+			wildcard: wildcard,
+			port: port.map(Into::into),
+		}
+	}
+
+	/// Check if `Host` header value matches this pattern.
+	pub fn matches(&self, host: &str, ignore_port: bool) -> bool {
+		let host = host.to_lowercase();
+		let (hostname, port) = split_port(&host);
+
+		let hostname_matches = match self.wildcard {
+			true => hostname.ends_with(&self.hostname) && hostname.len() > self.hostname.len(),
+			false => hostname == self.hostname,
+		};
+		let port_matches = ignore_port || self.port.as_ref().map(String::as_str) == port;
+
+		hostname_matches && port_matches
+	}
+}
+
+/// Splits `host:port` into its parts. IPv6 literals (`[::1]:8545`) are supported.
+fn split_port(host: &str) -> (&str, Option<&str>) {
+	let port_separator = match host.rfind(']') {
+		Some(bracket) => host[bracket..].find(':').map(|pos| bracket + pos),
+		None => host.rfind(':'),
+	};
+
+	match port_separator {
+		Some(pos) => (&host[..pos], Some(&host[pos + 1..])),
+		None => (host, None),
+	}
+}
+
+/// Set of hosts allowed in the `Host` header.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AllowedHosts {
+	/// Allowed patterns; `None` allows any host.
+	patterns: Option<Vec<HostPattern>>,
+	/// Accept a matching hostname regardless of the port.
+	ignore_port: bool,
+}
+
+impl AllowedHosts {
+	/// Allow hosts matching any of given patterns (or any host if `None`).
+	pub fn new(patterns: Option<Vec<String>>, ignore_port: bool) -> Self {
+		AllowedHosts {
+			patterns: patterns.map(|patterns| patterns.iter().map(|p| HostPattern::parse(p)).collect()),
+			ignore_port: ignore_port,
+		}
+	}
+
+	/// Allow any host.
+	pub fn any() -> Self {
+		AllowedHosts::new(None, false)
+	}
+
+	/// Additionally allow given host (no-op if any host is allowed).
+	pub fn add(&mut self, pattern: &str) {
+		if let Some(ref mut patterns) = self.patterns {
+			patterns.push(HostPattern::parse(pattern));
+		}
+	}
+
+	/// Check the `Host` header value of a request. Requests without the header are allowed
+	/// only if validation is disabled.
+	pub fn is_allowed(&self, host: Option<&str>) -> bool {
+		match (self.patterns.as_ref(), host) {
+			(None, _) => true,
+			(Some(_), None) => false,
+			(Some(patterns), Some(host)) => patterns.iter().any(|pattern| pattern.matches(host, self.ignore_port)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{HostPattern, AllowedHosts};
+
+	#[test]
+	fn should_match_exact_host() {
+		let pattern = HostPattern::parse("localhost:8545");
+
+		assert!(pattern.matches("localhost:8545", false));
+		assert!(pattern.matches("LocalHost:8545", false));
+		assert!(!pattern.matches("localhost:8546", false));
+		assert!(!pattern.matches("localhost", false));
+		assert!(pattern.matches("localhost:8546", true));
+		assert!(!pattern.matches("otherhost:8545", true));
+	}
+
+	#[test]
+	fn should_match_wildcard_subdomains() {
+		let pattern = HostPattern::parse("*.mydomain.tld");
+
+		assert!(pattern.matches("node.mydomain.tld", false));
+		assert!(pattern.matches("a.b.mydomain.tld", false));
+		assert!(!pattern.matches("mydomain.tld", false));
+		assert!(!pattern.matches("evilmydomain.tld", false));
+		assert!(!pattern.matches("node.mydomain.tld:8080", false));
+		assert!(pattern.matches("node.mydomain.tld:8080", true));
+	}
+
+	#[test]
+	fn should_handle_ipv6_literals() {
+		let pattern = HostPattern::parse("[::1]:5001");
+
+		assert!(pattern.matches("[::1]:5001", false));
+		assert!(pattern.matches("[::1]", true));
+		assert!(!pattern.matches("[::1]", false));
+	}
+
+	#[test]
+	fn should_validate_allowed_hosts() {
+		let mut hosts = AllowedHosts::new(Some(vec!["*.mydomain.tld".into()]), false);
+		hosts.add("127.0.0.1:8545");
+
+		assert!(hosts.is_allowed(Some("node.mydomain.tld")));
+		assert!(hosts.is_allowed(Some("127.0.0.1:8545")));
+		assert!(!hosts.is_allowed(Some("127.0.0.1:8546")));
+		assert!(!hosts.is_allowed(None));
+		assert!(AllowedHosts::any().is_allowed(None));
+		assert!(!AllowedHosts::new(Some(vec![]), true).is_allowed(Some("localhost")));
+	}
+}
@@ -24,8 +24,9 @@ use common::*;
 use elastic_array::*;
 use hashdb::DBValue;
 use rlp::{UntrustedRlp, RlpType, Compressible};
-use rocksdb::{DB, Writable, WriteBatch, WriteOptions, IteratorMode, DBIterator,
+use rocksdb::{DB, Writable, WriteBatch, WriteOptions, IteratorMode,
 	Options, DBCompactionStyle, BlockBasedOptions, Direction, Cache, Column, ReadOptions};
+use rocksdb_read_only::ReadOnlyDB;
 #[cfg(target_os = "linux")]
 use regex::Regex;
 #[cfg(target_os = "linux")]
@@ -35,6 +36,9 @@ use std::fs::File;
 
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
+const READ_ONLY_ERROR: &'static str = "Database is opened read-only";
+// default cache size for columns not specified, in MiB.
+const DB_DEFAULT_COLUMN_CACHE: usize = 2;
 
 /// Write transaction. Batches a sequence of put/delete operations for efficiency.
 #[derive(Default, Clone, PartialEq)]
@@ -169,6 +173,31 @@ pub trait KeyValueDB: Sync + Send {
 
 	/// Attempt to replace this database with a new one located at the given path.
 	fn restore(&self, new_db: &str) -> Result<(), UtilError>;
+
+	/// Reopen the database, discarding unflushed changes. A read-only database
+	/// sees the changes flushed by the writer since it was last opened.
+	fn reopen(&self) -> Result<(), UtilError>;
+
+	/// Compact the whole key range of a column, blocking until done.
+	fn compact(&self, col: Option<u32>) -> Result<(), String>;
+
+	/// Get the estimated statistics of a column.
+	fn column_stats(&self, col: Option<u32>) -> ColumnStats;
+}
+
+/// Estimated statistics of a single database column.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ColumnStats {
+	/// Total size of the column's table files on disk, in bytes.
+	pub size_on_disk: u64,
+	/// Estimated number of keys.
+	pub estimated_keys: u64,
+	/// Memory used by the column's block cache, in bytes.
+	pub cache_usage: u64,
+	/// Capacity of the column's block cache, in bytes.
+	pub cache_capacity: u64,
+	/// Estimated number of bytes compaction needs to rewrite.
+	pub pending_compaction_bytes: u64,
 }
 
 /// A key-value database fulfilling the `KeyValueDB` trait, living in memory.
@@ -254,6 +283,22 @@ impl KeyValueDB for InMemory {
 	fn restore(&self, _new_db: &str) -> Result<(), UtilError> {
 		Err(UtilError::SimpleString("Attempted to restore in-memory database".into()))
 	}
+
+	fn reopen(&self) -> Result<(), UtilError> { Ok(()) }
+
+	fn compact(&self, _col: Option<u32>) -> Result<(), String> { Ok(()) }
+
+	fn column_stats(&self, col: Option<u32>) -> ColumnStats {
+		let columns = self.columns.read();
+		match columns.get(&col) {
+			Some(map) => ColumnStats {
+				size_on_disk: map.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum(),
+				estimated_keys: map.len() as u64,
+				..Default::default()
+			},
+			None => ColumnStats::default(),
+		}
+	}
 }
 
 /// Compaction profile for the database settings
@@ -359,6 +404,9 @@ pub struct DatabaseConfig {
 	pub columns: Option<u32>,
 	/// Should we keep WAL enabled?
 	pub wal: bool,
+	/// Open the database read-only. Allows sharing it with another process which
+	/// has it opened for writing. All writes fail.
+	pub read_only: bool,
 }
 
 impl DatabaseConfig {
@@ -384,6 +432,7 @@ impl Default for DatabaseConfig {
 			compaction: CompactionProfile::default(),
 			columns: None,
 			wal: true,
+			read_only: false,
 		}
 	}
 }
@@ -393,7 +442,7 @@ impl Default for DatabaseConfig {
 // inner DB (to prevent closing via restoration) may be re-evaluated in the future.
 //
 pub struct DatabaseIterator<'a> {
-	iter: DBIterator,
+	iter: Box<Iterator<Item=(Box<[u8]>, Box<[u8]>)>>,
 	_marker: PhantomData<&'a Database>,
 }
 
@@ -412,9 +461,6 @@ struct DBAndColumns {
 
 // get column family configuration from database config.
 fn col_config(col: u32, config: &DatabaseConfig) -> Options {
-	// default cache size for columns not specified.
-	const DEFAULT_CACHE: usize = 2;
-
 	let mut opts = Options::new();
 	opts.set_compaction_style(DBCompactionStyle::DBUniversalCompaction);
 	opts.set_target_file_size_base(config.compaction.initial_file_size);
@@ -423,7 +469,7 @@ fn col_config(col: u32, config: &DatabaseConfig) -> Options {
 	let col_opt = config.columns.map(|_| col);
 
 	{
-		let cache_size = config.cache_sizes.get(&col_opt).cloned().unwrap_or(DEFAULT_CACHE);
+		let cache_size = config.cache_sizes.get(&col_opt).cloned().unwrap_or(DB_DEFAULT_COLUMN_CACHE);
 		let mut block_opts = BlockBasedOptions::new();
 		// all goes to read cache.
 		block_opts.set_cache(Cache::new(cache_size * 1024 * 1024));
@@ -436,6 +482,8 @@ fn col_config(col: u32, config: &DatabaseConfig) -> Options {
 /// Key-Value database.
 pub struct Database {
 	db: RwLock<Option<DBAndColumns>>,
+	// Set instead of `db` when the database is opened read-only.
+	read_only_db: RwLock<Option<ReadOnlyDB>>,
 	config: DatabaseConfig,
 	write_opts: WriteOptions,
 	read_opts: ReadOptions,
@@ -455,8 +503,12 @@ impl Database {
 		Database::open(&DatabaseConfig::default(), path)
 	}
 
-	/// Open database file. Creates if it does not exist.
+	/// Open database file. Creates if it does not exist, unless opened read-only.
 	pub fn open(config: &DatabaseConfig, path: &str) -> Result<Database, String> {
+		if config.read_only {
+			return Self::open_read_only(config, path);
+		}
+
 		let mut opts = Options::new();
 		if let Some(rate_limit) = config.compaction.write_rate_limit {
 			opts.set_parsed_options(&format!("rate_limiter_bytes_per_sec={}", rate_limit))?;
@@ -518,13 +570,19 @@ impl Database {
 		let db = match db {
 			Ok(db) => db,
 			Err(ref s) if s.starts_with("Corruption:") => {
-				info!("{}", s);
-				info!("Attempting DB repair for {}", path);
-				DB::repair(&opts, path)?;
-
-				match cfnames.is_empty() {
-					true => DB::open(&opts, path)?,
-					false => DB::open_cf(&opts, path, &cfnames, &cf_options)?
+				warn!("Database corruption detected in {}: {}", path, s);
+				warn!("Attempting DB repair for {}", path);
+				let repaired = DB::repair(&opts, path).and_then(|_| match cfnames.is_empty() {
+					true => DB::open(&opts, path),
+					false => DB::open_cf(&opts, path, &cfnames, &cf_options),
+				});
+				match repaired {
+					Ok(db) => {
+						warn!("Database {} repaired; recently written data may have been lost", path);
+						db
+					},
+					Err(e) => return Err(format!("Database {} is corrupted and could not be repaired ({}). \
+						Remove it with `parity db kill` and sync again.", path, e)),
 				}
 			},
 			Err(s) => { return Err(s); }
@@ -532,6 +590,7 @@ impl Database {
 		let num_cols = cfs.len();
 		Ok(Database {
 			db: RwLock::new(Some(DBAndColumns{ db: db, cfs: cfs })),
+			read_only_db: RwLock::new(None),
 			config: config.clone(),
 			write_opts: write_opts,
 			overlay: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
@@ -542,6 +601,24 @@ impl Database {
 		})
 	}
 
+	fn open_read_only(config: &DatabaseConfig, path: &str) -> Result<Database, String> {
+		let db = ReadOnlyDB::open(path, config.columns, config.max_open_files)?;
+		let num_cols = db.num_columns();
+		let mut read_opts = ReadOptions::new();
+		read_opts.set_verify_checksums(false);
+		Ok(Database {
+			db: RwLock::new(None),
+			read_only_db: RwLock::new(Some(db)),
+			config: config.clone(),
+			write_opts: WriteOptions::new(),
+			overlay: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
+			flushing: RwLock::new((0..(num_cols + 1)).map(|_| HashMap::new()).collect()),
+			flushing_lock: Mutex::new((false)),
+			path: path.to_owned(),
+			read_opts: read_opts,
+		})
+	}
+
 	/// Helper to create new transaction for this database.
 	pub fn transaction(&self) -> DBTransaction {
 		DBTransaction::new()
@@ -623,6 +700,9 @@ impl Database {
 
 	/// Commit buffered changes to database.
 	pub fn flush(&self) -> Result<(), String> {
+		if self.config.read_only {
+			return Err(READ_ONLY_ERROR.to_owned());
+		}
 		let mut lock = self.flushing_lock.lock();
 		// If RocksDB batch allocation fails the thread gets terminated and the lock is released.
 		// The value inside the lock is used to detect that.
@@ -638,6 +718,9 @@ impl Database {
 
 	/// Commit transaction to database.
 	pub fn write(&self, tr: DBTransaction) -> Result<(), String> {
+		if self.config.read_only {
+			return Err(READ_ONLY_ERROR.to_owned());
+		}
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let batch = WriteBatch::new();
@@ -664,6 +747,9 @@ impl Database {
 
 	/// Get value by key.
 	pub fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<DBValue>, String> {
+		if let Some(ref db) = *self.read_only_db.read() {
+			return db.get(col, key).map(|r| r.map(|v| DBValue::from_slice(&v)));
+		}
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let overlay = &self.overlay.read()[Self::to_overlay_column(col)];
@@ -691,6 +777,12 @@ impl Database {
 	/// Get value by partial key. Prefix size should match configured prefix size. Only searches flushed values.
 	// TODO: support prefix seek for unflushed data
 	pub fn get_by_prefix(&self, col: Option<u32>, prefix: &[u8]) -> Option<Box<[u8]>> {
+		if let Some(ref db) = *self.read_only_db.read() {
+			return match db.iter(col, Some(prefix)).next() {
+				Some((k, v)) => if k[0 .. prefix.len()] == prefix[..] { Some(v) } else { None },
+				_ => None
+			};
+		}
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let mut iter = col.map_or_else(|| db.iterator_opt(IteratorMode::From(prefix, Direction::Forward), &self.read_opts),
@@ -709,6 +801,12 @@ impl Database {
 	/// Get database iterator for flushed data.
 	pub fn iter(&self, col: Option<u32>) -> DatabaseIterator {
 		//TODO: iterate over overlay
+		if let Some(ref db) = *self.read_only_db.read() {
+			return DatabaseIterator {
+				iter: Box::new(db.iter(col, None)),
+				_marker: PhantomData,
+			};
+		}
 		match *self.db.read() {
 			Some(DBAndColumns { ref db, ref cfs }) => {
 				let iter = col.map_or_else(
@@ -718,7 +816,7 @@ impl Database {
 				);
 
 				DatabaseIterator {
-					iter: iter,
+					iter: Box::new(iter),
 					_marker: PhantomData,
 				}
 			},
@@ -729,6 +827,7 @@ impl Database {
 	/// Close the database
 	fn close(&self) {
 		*self.db.write() = None;
+		*self.read_only_db.write() = None;
 		self.overlay.write().clear();
 		self.flushing.write().clear();
 	}
@@ -766,24 +865,88 @@ impl Database {
 			}
 		}
 
+		self.reopen()
+	}
+
+	/// Reopen the database, discarding unflushed changes. A read-only database
+	/// sees the changes flushed by the writer since it was last opened.
+	pub fn reopen(&self) -> Result<(), UtilError> {
+		// read-only handles don't lock the database, so keep serving reads until the new one is open.
+		if !self.config.read_only {
+			self.close();
+		}
+
 		// reopen the database and steal handles into self
 		let db = Self::open(&self.config, &self.path)?;
 		*self.db.write() = mem::replace(&mut *db.db.write(), None);
+		*self.read_only_db.write() = mem::replace(&mut *db.read_only_db.write(), None);
 		*self.overlay.write() = mem::replace(&mut *db.overlay.write(), Vec::new());
 		*self.flushing.write() = mem::replace(&mut *db.flushing.write(), Vec::new());
 		Ok(())
 	}
 
+	/// Compact the whole key range of a column, blocking until done.
+	pub fn compact(&self, col: Option<u32>) -> Result<(), String> {
+		if self.config.read_only {
+			return Err(READ_ONLY_ERROR.to_owned());
+		}
+		match *self.db.read() {
+			Some(DBAndColumns { ref db, ref cfs }) => {
+				match col {
+					None => db.compact_range(None, None),
+					Some(c) => db.compact_range_cf(cfs[c as usize], None, None),
+				}
+				Ok(())
+			},
+			None => Err("Database is closed".to_owned())
+		}
+	}
+
+	/// Get the estimated statistics of a column, as reported by RocksDB.
+	pub fn column_stats(&self, col: Option<u32>) -> ColumnStats {
+		match *self.db.read() {
+			Some(DBAndColumns { ref db, ref cfs }) => {
+				let property = |name: &str| -> u64 {
+					let value = match col {
+						None => db.property_int_value(name),
+						Some(c) => db.property_int_value_cf(cfs[c as usize], name),
+					};
+					value.ok().and_then(|v| v).unwrap_or(0)
+				};
+				let cache_size = self.config.cache_sizes.get(&col).cloned().unwrap_or(DB_DEFAULT_COLUMN_CACHE);
+				ColumnStats {
+					size_on_disk: property("rocksdb.total-sst-files-size"),
+					estimated_keys: property("rocksdb.estimate-num-keys"),
+					cache_usage: property("rocksdb.block-cache-usage"),
+					cache_capacity: cache_size as u64 * 1024 * 1024,
+					pending_compaction_bytes: property("rocksdb.estimate-pending-compaction-bytes"),
+				}
+			},
+			None => ColumnStats::default(),
+		}
+	}
+
 	/// The number of non-default column families.
 	pub fn num_columns(&self) -> u32 {
+		if let Some(ref db) = *self.read_only_db.read() {
+			return db.num_columns() as u32;
+		}
 		self.db.read().as_ref()
 			.and_then(|db| if db.cfs.is_empty() { None } else { Some(db.cfs.len()) } )
 			.map(|n| n as u32)
 			.unwrap_or(0)
 	}
 
+	/// Whether the database was opened read-only.
+	pub fn is_read_only(&self) -> bool {
+		self.config.read_only
+	}
+
 	/// Drop a column family.
 	pub fn drop_column(&self) -> Result<(), String> {
+		if self.config.read_only {
+			return Err(READ_ONLY_ERROR.to_owned());
+		}
 		match *self.db.write() {
 			Some(DBAndColumns { ref mut db, ref mut cfs }) => {
 				if let Some(col) = cfs.pop() {
@@ -799,6 +962,9 @@ impl Database {
 
 	/// Add a column family.
 	pub fn add_column(&self) -> Result<(), String> {
+		if self.config.read_only {
+			return Err(READ_ONLY_ERROR.to_owned());
+		}
 		match *self.db.write() {
 			Some(DBAndColumns { ref mut db, ref mut cfs }) => {
 				let col = cfs.len() as u32;
@@ -842,6 +1008,18 @@ impl KeyValueDB for Database {
 	fn restore(&self, new_db: &str) -> Result<(), UtilError> {
 		Database::restore(self, new_db)
 	}
+
+	fn reopen(&self) -> Result<(), UtilError> {
+		Database::reopen(self)
+	}
+
+	fn compact(&self, col: Option<u32>) -> Result<(), String> {
+		Database::compact(self, col)
+	}
+
+	fn column_stats(&self, col: Option<u32>) -> ColumnStats {
+		Database::column_stats(self, col)
+	}
 }
 
 impl Drop for Database {
@@ -918,6 +1096,56 @@ mod tests {
 		test_db(&DatabaseConfig::default());
 	}
 
+	#[test]
+	fn read_only() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let db = Database::open_default(path).unwrap();
+		let mut batch = db.transaction();
+		batch.put(None, b"key", b"value");
+		db.write(batch).unwrap();
+
+		let config = DatabaseConfig { read_only: true, ..Default::default() };
+		let read_only = Database::open(&config, path).unwrap();
+		assert!(read_only.is_read_only());
+		assert_eq!(&*read_only.get(None, b"key").unwrap().unwrap(), b"value");
+
+		let mut batch = read_only.transaction();
+		batch.put(None, b"key", b"other");
+		assert!(read_only.write(batch).is_err());
+		assert!(read_only.flush().is_err());
+
+		let mut batch = db.transaction();
+		batch.put(None, b"key", b"other");
+		db.write(batch).unwrap();
+		assert_eq!(&*read_only.get(None, b"key").unwrap().unwrap(), b"value");
+		read_only.reopen().unwrap();
+		assert_eq!(&*read_only.get(None, b"key").unwrap().unwrap(), b"other");
+
+		let empty = RandomTempPath::create_dir();
+		assert!(Database::open(&config, empty.as_path().to_str().unwrap()).is_err());
+	}
+
+	#[test]
+	fn read_only_columns() {
+		let path = RandomTempPath::create_dir();
+		let path = path.as_path().to_str().unwrap();
+		let db = Database::open(&DatabaseConfig::with_columns(Some(2)), path).unwrap();
+		let mut batch = db.transaction();
+		batch.put(Some(1), b"key1", b"cat");
+		batch.put(Some(1), b"key2", b"dog");
+		db.write(batch).unwrap();
+
+		let config = DatabaseConfig { read_only: true, ..DatabaseConfig::with_columns(Some(2)) };
+		let read_only = Database::open(&config, path).unwrap();
+		assert_eq!(read_only.num_columns(), 2);
+		assert_eq!(&*read_only.get(Some(1), b"key1").unwrap().unwrap(), b"cat");
+		assert!(read_only.get(Some(0), b"key1").unwrap().is_none());
+		assert_eq!(&*read_only.get_by_prefix(Some(1), b"key2").unwrap(), b"dog");
+		assert_eq!(read_only.iter(Some(1)).count(), 2);
+		assert!(read_only.add_column().is_err());
+	}
+
 	#[test]
 	#[cfg(target_os = "linux")]
 	fn df_to_rotational() {
@@ -928,6 +1156,24 @@ mod tests {
 		assert_eq!(rotational_from_df_output(example_df), expected_output);
 	}
 
+	#[test]
+	fn column_stats() {
+		let mut config = DatabaseConfig::with_columns(Some(2));
+		config.set_cache(Some(1), 8);
+		let path = RandomTempPath::create_dir();
+		let db = Database::open(&config, path.as_path().to_str().unwrap()).unwrap();
+
+		let mut batch = db.transaction();
+		batch.put(Some(1), b"cat", b"meow");
+		batch.put(Some(1), b"dog", b"woof");
+		db.write(batch).unwrap();
+
+		let stats = db.column_stats(Some(1));
+		assert_eq!(stats.estimated_keys, 2);
+		assert_eq!(stats.cache_capacity, 8 * 1024 * 1024);
+		assert_eq!(db.column_stats(Some(0)).estimated_keys, 0);
+	}
+
 	#[test]
 	fn add_columns() {
 		let config = DatabaseConfig::default();
@@ -130,6 +130,7 @@ pub mod migration;
 pub mod overlaydb;
 pub mod journaldb;
 pub mod kvdb;
+mod rocksdb_read_only;
 pub mod triehash;
 pub mod trie;
 pub mod nibbleslice;
@@ -137,6 +138,7 @@ pub mod nibblevec;
 pub mod semantic_version;
 pub mod snappy;
 pub mod cache;
+pub mod hosts;
 mod timer;
 
 pub use common::*;
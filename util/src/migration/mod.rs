@@ -265,6 +265,7 @@ impl Manager {
 			compaction: config.compaction_profile,
 			columns: columns,
 			wal: true,
+			read_only: false,
 		};
 
 		let db_root = database_path(old_path);
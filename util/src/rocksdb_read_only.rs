@@ -0,0 +1,279 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Read-only RocksDB bindings.
+//!
+//! The `rocksdb` crate only opens databases for writing, which takes the database lock.
+//! These bind the read-only open of the RocksDB C API (linked through the `rocksdb` crate),
+//! so that a second process can read a database while its writer is running.
+
+use std::ffi::{CStr, CString};
+use std::ptr;
+use std::slice;
+use libc::{self, c_char, c_int, c_uchar, c_void, size_t};
+
+#[allow(non_camel_case_types)]
+enum rocksdb_t {}
+#[allow(non_camel_case_types)]
+enum rocksdb_options_t {}
+#[allow(non_camel_case_types)]
+enum rocksdb_readoptions_t {}
+#[allow(non_camel_case_types)]
+enum rocksdb_column_family_handle_t {}
+#[allow(non_camel_case_types)]
+enum rocksdb_iterator_t {}
+
+extern {
+	fn rocksdb_options_create() -> *mut rocksdb_options_t;
+	fn rocksdb_options_destroy(options: *mut rocksdb_options_t);
+	fn rocksdb_options_set_max_open_files(options: *mut rocksdb_options_t, max: c_int);
+
+	fn rocksdb_readoptions_create() -> *mut rocksdb_readoptions_t;
+	fn rocksdb_readoptions_destroy(options: *mut rocksdb_readoptions_t);
+	fn rocksdb_readoptions_set_verify_checksums(options: *mut rocksdb_readoptions_t, verify: c_uchar);
+
+	fn rocksdb_open_for_read_only(
+		options: *const rocksdb_options_t,
+		name: *const c_char,
+		error_if_log_file_exist: c_uchar,
+		errptr: *mut *mut c_char,
+	) -> *mut rocksdb_t;
+
+	fn rocksdb_open_for_read_only_column_families(
+		options: *const rocksdb_options_t,
+		name: *const c_char,
+		num_column_families: c_int,
+		column_family_names: *const *const c_char,
+		column_family_options: *const *const rocksdb_options_t,
+		column_family_handles: *mut *mut rocksdb_column_family_handle_t,
+		error_if_log_file_exist: c_uchar,
+		errptr: *mut *mut c_char,
+	) -> *mut rocksdb_t;
+
+	fn rocksdb_close(db: *mut rocksdb_t);
+	fn rocksdb_column_family_handle_destroy(handle: *mut rocksdb_column_family_handle_t);
+
+	fn rocksdb_get(
+		db: *mut rocksdb_t,
+		options: *const rocksdb_readoptions_t,
+		key: *const c_char,
+		key_len: size_t,
+		value_len: *mut size_t,
+		errptr: *mut *mut c_char,
+	) -> *mut c_char;
+
+	fn rocksdb_get_cf(
+		db: *mut rocksdb_t,
+		options: *const rocksdb_readoptions_t,
+		column_family: *mut rocksdb_column_family_handle_t,
+		key: *const c_char,
+		key_len: size_t,
+		value_len: *mut size_t,
+		errptr: *mut *mut c_char,
+	) -> *mut c_char;
+
+	fn rocksdb_create_iterator(db: *mut rocksdb_t, options: *const rocksdb_readoptions_t) -> *mut rocksdb_iterator_t;
+	fn rocksdb_create_iterator_cf(
+		db: *mut rocksdb_t,
+		options: *const rocksdb_readoptions_t,
+		column_family: *mut rocksdb_column_family_handle_t,
+	) -> *mut rocksdb_iterator_t;
+	fn rocksdb_iter_destroy(iter: *mut rocksdb_iterator_t);
+	fn rocksdb_iter_valid(iter: *const rocksdb_iterator_t) -> c_uchar;
+	fn rocksdb_iter_seek_to_first(iter: *mut rocksdb_iterator_t);
+	fn rocksdb_iter_seek(iter: *mut rocksdb_iterator_t, key: *const c_char, key_len: size_t);
+	fn rocksdb_iter_next(iter: *mut rocksdb_iterator_t);
+	fn rocksdb_iter_key(iter: *const rocksdb_iterator_t, key_len: *mut size_t) -> *const c_char;
+	fn rocksdb_iter_value(iter: *const rocksdb_iterator_t, value_len: *mut size_t) -> *const c_char;
+}
+
+/// Takes the error message set by a RocksDB call, if any.
+unsafe fn take_error(err: *mut c_char) -> Result<(), String> {
+	if err.is_null() {
+		return Ok(());
+	}
+	let message = CStr::from_ptr(err).to_string_lossy().into_owned();
+	libc::free(err as *mut c_void);
+	Err(message)
+}
+
+/// RocksDB database opened read-only. It doesn't take the database lock and sees the data
+/// flushed (or logged) by the writer up to the moment it was opened.
+pub struct ReadOnlyDB {
+	db: *mut rocksdb_t,
+	options: *mut rocksdb_options_t,
+	read_options: *mut rocksdb_readoptions_t,
+	// handles of `col0`, `col1`, ...; the default column family is used for `None`.
+	cfs: Vec<*mut rocksdb_column_family_handle_t>,
+}
+
+// RocksDB handles are thread-safe for reads.
+unsafe impl Send for ReadOnlyDB {}
+unsafe impl Sync for ReadOnlyDB {}
+
+impl ReadOnlyDB {
+	/// Open the database at `path`, with given number of non-default column families.
+	pub fn open(path: &str, columns: Option<u32>, max_open_files: i32) -> Result<ReadOnlyDB, String> {
+		let cpath = CString::new(path.as_bytes()).map_err(|_| format!("Invalid database path: {}", path))?;
+		let cfnames = Some("default".to_owned()).into_iter()
+			.chain((0..columns.unwrap_or(0)).map(|c| format!("col{}", c)))
+			.map(|name| CString::new(name).expect("column names contain no NUL bytes; qed"))
+			.collect::<Vec<_>>();
+
+		unsafe {
+			let options = rocksdb_options_create();
+			rocksdb_options_set_max_open_files(options, max_open_files as c_int);
+			let read_options = rocksdb_readoptions_create();
+			rocksdb_readoptions_set_verify_checksums(read_options, 0);
+
+			let mut err = ptr::null_mut();
+			let mut handles = vec![ptr::null_mut(); cfnames.len()];
+			let db = match columns {
+				None => rocksdb_open_for_read_only(options, cpath.as_ptr(), 0, &mut err),
+				Some(_) => {
+					let names = cfnames.iter().map(|name| name.as_ptr()).collect::<Vec<_>>();
+					let cf_options = cfnames.iter().map(|_| options as *const _).collect::<Vec<_>>();
+					rocksdb_open_for_read_only_column_families(
+						options,
+						cpath.as_ptr(),
+						names.len() as c_int,
+						names.as_ptr(),
+						cf_options.as_ptr(),
+						handles.as_mut_ptr(),
+						0,
+						&mut err,
+					)
+				},
+			};
+
+			if let Err(e) = take_error(err) {
+				rocksdb_readoptions_destroy(read_options);
+				rocksdb_options_destroy(options);
+				return Err(e);
+			}
+
+			// the default column family handle isn't needed, `None` reads through the database.
+			let mut handles = handles.into_iter();
+			if let Some(default) = handles.next() {
+				if !default.is_null() {
+					rocksdb_column_family_handle_destroy(default);
+				}
+			}
+
+			Ok(ReadOnlyDB {
+				db: db,
+				options: options,
+				read_options: read_options,
+				cfs: handles.collect(),
+			})
+		}
+	}
+
+	/// Number of non-default column families.
+	pub fn num_columns(&self) -> usize {
+		self.cfs.len()
+	}
+
+	/// Get value by key.
+	pub fn get(&self, col: Option<u32>, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+		let mut len: size_t = 0;
+		let mut err = ptr::null_mut();
+		unsafe {
+			let value = match col {
+				None => rocksdb_get(self.db, self.read_options, key.as_ptr() as *const c_char, key.len() as size_t, &mut len, &mut err),
+				Some(c) => rocksdb_get_cf(self.db, self.read_options, self.cfs[c as usize], key.as_ptr() as *const c_char, key.len() as size_t, &mut len, &mut err),
+			};
+			take_error(err)?;
+			if value.is_null() {
+				return Ok(None);
+			}
+			let result = slice::from_raw_parts(value as *const u8, len as usize).to_vec();
+			libc::free(value as *mut c_void);
+			Ok(Some(result))
+		}
+	}
+
+	/// Iterate over a column, starting at `from` (or at the first key).
+	pub fn iter(&self, col: Option<u32>, from: Option<&[u8]>) -> ReadOnlyIterator {
+		unsafe {
+			let iter = match col {
+				None => rocksdb_create_iterator(self.db, self.read_options),
+				Some(c) => rocksdb_create_iterator_cf(self.db, self.read_options, self.cfs[c as usize]),
+			};
+			match from {
+				Some(key) => rocksdb_iter_seek(iter, key.as_ptr() as *const c_char, key.len() as size_t),
+				None => rocksdb_iter_seek_to_first(iter),
+			}
+			ReadOnlyIterator {
+				iter: iter,
+				started: false,
+			}
+		}
+	}
+}
+
+impl Drop for ReadOnlyDB {
+	fn drop(&mut self) {
+		unsafe {
+			for cf in self.cfs.drain(..) {
+				rocksdb_column_family_handle_destroy(cf);
+			}
+			rocksdb_close(self.db);
+			rocksdb_readoptions_destroy(self.read_options);
+			rocksdb_options_destroy(self.options);
+		}
+	}
+}
+
+/// Iterator over a read-only database. Must not outlive the database it was created from.
+pub struct ReadOnlyIterator {
+	iter: *mut rocksdb_iterator_t,
+	started: bool,
+}
+
+unsafe impl Send for ReadOnlyIterator {}
+
+impl Iterator for ReadOnlyIterator {
+	type Item = (Box<[u8]>, Box<[u8]>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		unsafe {
+			if self.started && rocksdb_iter_valid(self.iter) != 0 {
+				rocksdb_iter_next(self.iter);
+			}
+			self.started = true;
+
+			if rocksdb_iter_valid(self.iter) == 0 {
+				return None;
+			}
+
+			let mut key_len: size_t = 0;
+			let mut value_len: size_t = 0;
+			let key = rocksdb_iter_key(self.iter, &mut key_len);
+			let value = rocksdb_iter_value(self.iter, &mut value_len);
+			Some((
+				slice::from_raw_parts(key as *const u8, key_len as usize).to_vec().into_boxed_slice(),
+				slice::from_raw_parts(value as *const u8, value_len as usize).to_vec().into_boxed_slice(),
+			))
+		}
+	}
+}
+
+impl Drop for ReadOnlyIterator {
+	fn drop(&mut self) {
+		unsafe { rocksdb_iter_destroy(self.iter); }
+	}
+}